@@ -0,0 +1,59 @@
+// Compares /api/processes latency when the background poller samples
+// processes against the old behaviour of refreshing sysinfo twice, 200 ms
+// apart, inside every request.
+//
+// Run with: cargo bench --bench processes_latency
+
+use axum::{body::Body, http::Request, routing::get, Json, Router};
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+use task_manager_backend::{build_router, config::Config, AppState};
+use tower::ServiceExt;
+
+const ITERATIONS: usize = 50;
+
+async fn inline_processes() -> Json<usize> {
+    let count = tokio::task::spawn_blocking(|| {
+        let mut sys = System::new();
+        sys.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::everything());
+        std::thread::sleep(Duration::from_millis(200));
+        sys.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::everything());
+        sys.processes().len()
+    })
+    .await
+    .unwrap();
+    Json(count)
+}
+
+async fn measure(router: Router) -> Vec<Duration> {
+    let mut samples = Vec::with_capacity(ITERATIONS);
+    for _ in 0..ITERATIONS {
+        let request = Request::builder().uri("/api/processes").body(Body::empty()).unwrap();
+        let start = Instant::now();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert!(response.status().is_success());
+        samples.push(start.elapsed());
+    }
+    samples.sort();
+    samples
+}
+
+fn report(label: &str, samples: &[Duration]) {
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+    let p50 = samples[samples.len() / 2];
+    let p99 = samples[samples.len() * 99 / 100];
+    println!("{:<22} mean {:>10.3?}  p50 {:>10.3?}  p99 {:>10.3?}", label, mean, p50, p99);
+}
+
+#[tokio::main]
+async fn main() {
+    let inline = Router::new().route("/api/processes", get(inline_processes));
+    report("inline double refresh", &measure(inline).await);
+
+    let state = AppState::new(Config::default());
+    let poller = state.spawn_poller();
+    // Let the first tick fill the cache
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    report("background poller", &measure(build_router(state)).await);
+    poller.abort();
+}
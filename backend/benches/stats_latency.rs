@@ -0,0 +1,57 @@
+// Compares /api/stats latency with the background poller against the old
+// behaviour of refreshing sysinfo inside every request.
+//
+// Run with: cargo bench --bench stats_latency
+
+use axum::{body::Body, extract::State, http::Request, routing::get, Json, Router};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use task_manager_backend::{
+    build_router,
+    config::Config,
+    source::{SysinfoSource, SystemSource},
+    AppState,
+};
+use tower::ServiceExt;
+
+const ITERATIONS: usize = 200;
+
+async fn inline_stats(State(source): State<Arc<dyn SystemSource>>) -> Json<serde_json::Value> {
+    let stats = tokio::task::spawn_blocking(move || source.snapshot_stats()).await.unwrap();
+    Json(serde_json::to_value(stats).unwrap())
+}
+
+async fn measure(router: Router) -> Vec<Duration> {
+    let mut samples = Vec::with_capacity(ITERATIONS);
+    for _ in 0..ITERATIONS {
+        let request = Request::builder().uri("/api/stats").body(Body::empty()).unwrap();
+        let start = Instant::now();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert!(response.status().is_success());
+        samples.push(start.elapsed());
+    }
+    samples.sort();
+    samples
+}
+
+fn report(label: &str, samples: &[Duration]) {
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+    let p50 = samples[samples.len() / 2];
+    let p99 = samples[samples.len() * 99 / 100];
+    println!("{:<22} mean {:>10.3?}  p50 {:>10.3?}  p99 {:>10.3?}", label, mean, p50, p99);
+}
+
+#[tokio::main]
+async fn main() {
+    let source: Arc<dyn SystemSource> = Arc::new(SysinfoSource::new());
+
+    let inline = Router::new()
+        .route("/api/stats", get(inline_stats))
+        .with_state(source.clone());
+    report("inline refresh", &measure(inline).await);
+
+    let state = AppState::with_source(Config::default(), source);
+    let poller = state.spawn_poller();
+    report("background poller", &measure(build_router(state)).await);
+    poller.abort();
+}
@@ -0,0 +1,52 @@
+//! Embeds what `/api/version` reports about the build: the git commit and
+//! whether tracked files had uncommitted changes, the build time, and the
+//! compiler and target. Each git value is left empty when git or the
+//! repository isn't there, as when building from a source tarball.
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let commit = git(&["rev-parse", "HEAD"]).unwrap_or_default();
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no"])
+        .map(|changes| (!changes.is_empty()).to_string())
+        .unwrap_or_default();
+
+    // SOURCE_DATE_EPOCH keeps reproducible builds reproducible
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs());
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+
+    println!("cargo:rustc-env=TASKMGR_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=TASKMGR_GIT_DIRTY={}", dirty);
+    println!("cargo:rustc-env=TASKMGR_BUILD_TIMESTAMP={}", timestamp);
+    println!("cargo:rustc-env=TASKMGR_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rustc-env=TASKMGR_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+
+    // Rerun on a new commit or checkout, or a change to the index, rather
+    // than on every source edit
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    if let Some(dir) = git(&["rev-parse", "--absolute-git-dir"]) {
+        println!("cargo:rerun-if-changed={}/HEAD", dir);
+        println!("cargo:rerun-if-changed={}/index", dir);
+        if let Some(branch) = git(&["symbolic-ref", "-q", "HEAD"]) {
+            println!("cargo:rerun-if-changed={}/{}", dir, branch);
+        }
+    }
+}
+
+/// Trimmed stdout of a successful `git` run
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
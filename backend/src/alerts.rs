@@ -0,0 +1,508 @@
+//! Threshold alerts evaluated by the background poller.
+//!
+//! Rules come from `[[alerts]]` tables in the config file, or the
+//! `--persist-rules` file once it exists, and can be changed at runtime
+//! through `/api/alert_rules`. Each poll feeds the current value of every
+//! rule's metric to [`AlertEngine::observe`]; a rule fires once its condition
+//! has held for `sustained_secs` and resolves on the first sample where it no
+//! longer holds.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::mpsc;
+use utoipa::ToSchema;
+
+use crate::config::ConfigError;
+use crate::error::{ApiError, FieldError};
+use crate::models::{Alert, AlertRuleInfo, AlertRuleRequest, SystemStats};
+use crate::processes::normalized_cpu;
+use crate::source::ProcessTable;
+use crate::webhook::{AlertEvent, AlertTransition, WebhookFormat, WebhookTarget};
+
+/// Resolved alerts older than this many are dropped
+pub const ALERT_HISTORY_CAPACITY: usize = 1000;
+
+/// Entries returned by `GET /api/alerts/history` without `?limit=`
+pub const DEFAULT_ALERT_HISTORY_LIMIT: usize = 100;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    /// Machine-wide CPU, or the summed CPU of the rule's processes, 0-100
+    CpuPercent,
+    /// RAM in use, or the rule's processes' share of it, 0-100
+    MemoryPercent,
+    /// The fullest mounted disk, 0-100, so one full disk isn't hidden by
+    /// empty ones
+    DiskPercent,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Above,
+    #[serde(rename = ">=")]
+    AtLeast,
+    #[serde(rename = "<")]
+    Below,
+    #[serde(rename = "<=")]
+    AtMost,
+}
+
+impl Comparison {
+    pub fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::AtLeast => value >= threshold,
+            Comparison::Below => value < threshold,
+            Comparison::AtMost => value <= threshold,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Above => ">",
+            Comparison::AtLeast => ">=",
+            Comparison::Below => "<",
+            Comparison::AtMost => "<=",
+        }
+    }
+}
+
+/// One `[[alerts]]` table of the config file, or entry of the
+/// `--persist-rules` file
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    /// Unique; alerts refer to their rule by it
+    pub name: String,
+    pub metric: AlertMetric,
+    pub operator: Comparison,
+    pub threshold: f64,
+    /// How long the condition must hold before the alert fires; 0 fires on
+    /// the first sample
+    #[serde(default)]
+    pub sustained_secs: u64,
+    /// Only processes with this name, matched case-insensitively, instead of
+    /// the whole machine. Not allowed with `disk_percent`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process: Option<String>,
+    /// Notified on trigger and resolve instead of `[alert_webhook]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookTarget>,
+}
+
+impl AlertRule {
+    /// The metric's current value; `None` when no process matches the rule
+    pub fn value(&self, stats: &SystemStats, table: Option<&ProcessTable>) -> Option<f64> {
+        let Some(name) = &self.process else {
+            return Some(match self.metric {
+                AlertMetric::CpuPercent => stats.cpu.percent as f64,
+                AlertMetric::MemoryPercent => stats.memory.percent as f64,
+                AlertMetric::DiskPercent => fullest_disk(stats),
+            });
+        };
+        let table = table?;
+        let matching: Vec<_> = table.processes.iter().filter(|p| p.name.eq_ignore_ascii_case(name)).collect();
+        if matching.is_empty() {
+            return None;
+        }
+        match self.metric {
+            AlertMetric::CpuPercent => Some(matching.iter().map(|p| normalized_cpu(p, table) as f64).sum()),
+            AlertMetric::MemoryPercent => {
+                let memory: u64 = matching.iter().map(|p| p.memory).sum();
+                Some(memory as f64 / table.total_memory.max(1) as f64 * 100.0)
+            }
+            AlertMetric::DiskPercent => None,
+        }
+    }
+
+    /// What's wrong with each field, leaving name clashes with other rules
+    /// to the caller
+    pub fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push(FieldError::new("name", "must not be empty"));
+        }
+        if !(self.threshold.is_finite() && self.threshold > 0.0) {
+            errors.push(FieldError::new("threshold", "must be a number greater than 0"));
+        }
+        match &self.process {
+            Some(name) if name.trim().is_empty() => {
+                errors.push(FieldError::new("process", "must not be empty; leave it out to watch the whole machine"));
+            }
+            Some(_) if self.metric == AlertMetric::DiskPercent => {
+                errors.push(FieldError::new("process", "disk_percent can't be scoped to a process"));
+            }
+            _ => {}
+        }
+        if let Some(Err(reason)) = self.webhook.as_ref().map(WebhookTarget::validate) {
+            errors.push(FieldError::new("webhook.url", reason));
+        }
+        errors
+    }
+}
+
+fn fullest_disk(stats: &SystemStats) -> f64 {
+    stats
+        .disk
+        .disks
+        .iter()
+        .filter(|disk| disk.total > 0)
+        .map(|disk| disk.total.saturating_sub(disk.available) as f64 / disk.total as f64 * 100.0)
+        .reduce(f64::max)
+        .unwrap_or(stats.disk.percent as f64)
+}
+
+/// Rejects duplicate names and anything [`AlertRule::field_errors`] reports
+pub fn validate(rules: &[AlertRule], default_webhook: Option<&WebhookTarget>) -> Result<(), ConfigError> {
+    if let Some(webhook) = default_webhook {
+        webhook.validate().map_err(|reason| ConfigError::AlertRule(format!("[alert_webhook]: {}", reason)))?;
+    }
+    for (index, rule) in rules.iter().enumerate() {
+        if rule.name.trim().is_empty() {
+            return Err(ConfigError::AlertRule("every rule needs a name".to_string()));
+        }
+        if rules[..index].iter().any(|other| other.name == rule.name) {
+            return Err(ConfigError::AlertRule(format!("'{}': duplicate name", rule.name)));
+        }
+        if let Some(error) = rule.field_errors().first() {
+            return Err(ConfigError::AlertRule(format!("'{}': {}: {}", rule.name, error.field, error.message)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a rule sent to `/api/alert_rules`, reporting every bad field at once
+pub fn parse_rule(request: AlertRuleRequest) -> Result<AlertRule, ApiError> {
+    let mut errors = Vec::new();
+    let metric = parse_name::<AlertMetric>(&request.metric);
+    if metric.is_none() {
+        errors.push(FieldError::new(
+            "metric",
+            format!("unknown metric '{}'; expected cpu_percent, memory_percent or disk_percent", request.metric),
+        ));
+    }
+    let operator = parse_name::<Comparison>(&request.operator);
+    if operator.is_none() {
+        errors.push(FieldError::new("operator", format!("unknown operator '{}'; expected >, >=, < or <=", request.operator)));
+    }
+    let webhook = request.webhook.map(|webhook| {
+        let format = match webhook.format.as_deref() {
+            None => WebhookFormat::default(),
+            Some(name) => parse_name(name).unwrap_or_else(|| {
+                errors.push(FieldError::new("webhook.format", format!("unknown format '{}'; expected json or slack", name)));
+                WebhookFormat::default()
+            }),
+        };
+        WebhookTarget { url: webhook.url, format }
+    });
+    // Placeholders for unknown names, so the remaining fields are still checked
+    let rule = AlertRule {
+        name: request.name,
+        metric: metric.unwrap_or(AlertMetric::CpuPercent),
+        operator: operator.unwrap_or(Comparison::Above),
+        threshold: request.threshold,
+        sustained_secs: request.sustained_secs,
+        process: request.process,
+        webhook,
+    };
+    errors.extend(rule.field_errors());
+    if errors.is_empty() {
+        Ok(rule)
+    } else {
+        Err(ApiError::InvalidFields { errors })
+    }
+}
+
+/// A unit variant from the name it has in JSON and TOML
+fn parse_name<T: DeserializeOwned>(name: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+/// The rules last saved to `path` by `--persist-rules`; `None` until the
+/// first change through the API creates the file
+pub fn load_rules(path: &Path) -> Result<Option<Vec<AlertRule>>, ConfigError> {
+    let invalid = |reason: String| ConfigError::RulesFile { path: path.to_path_buf(), reason };
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(invalid(err.to_string())),
+    };
+    serde_json::from_str(&text).map(Some).map_err(|err| invalid(err.to_string()))
+}
+
+/// Writes `rules` as a JSON array, replacing `path` in one rename so a crash
+/// never leaves half a file
+pub fn save_rules(path: &Path, rules: &[&AlertRule]) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(rules)?;
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, json)?;
+    std::fs::rename(&temp, path)
+}
+
+/// Per-rule progress between samples
+#[derive(Default)]
+struct RuleState {
+    /// When the condition started holding, while it still does
+    breaching_since: Option<u64>,
+    firing: Option<Alert>,
+}
+
+impl RuleState {
+    /// Forgets any breach in progress and resolves the firing alert at `now`,
+    /// moving it to `history`
+    fn resolve(&mut self, now: u64, history: &mut VecDeque<Alert>) -> Option<Alert> {
+        self.breaching_since = None;
+        let mut alert = self.firing.take()?;
+        alert.resolved_at = Some(now);
+        if history.len() == ALERT_HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        history.push_back(alert.clone());
+        Some(alert)
+    }
+}
+
+struct RuleSlot {
+    /// Assigned at startup in file order, then one more than the last for each
+    /// rule added; not kept across restarts
+    id: u64,
+    rule: AlertRule,
+    state: RuleState,
+}
+
+impl RuleSlot {
+    fn info(&self) -> AlertRuleInfo {
+        let rule = &self.rule;
+        AlertRuleInfo {
+            id: self.id,
+            name: rule.name.clone(),
+            metric: rule.metric,
+            operator: rule.operator,
+            threshold: rule.threshold,
+            sustained_secs: rule.sustained_secs,
+            process: rule.process.clone(),
+            webhook: rule.webhook.as_ref().map(WebhookTarget::redacted),
+            firing: self.state.firing.is_some(),
+        }
+    }
+}
+
+struct Inner {
+    rules: Vec<RuleSlot>,
+    /// Resolved alerts, oldest first
+    history: VecDeque<Alert>,
+    next_id: u64,
+    next_rule_id: u64,
+}
+
+impl Inner {
+    fn position(&self, id: u64) -> Result<usize, ApiError> {
+        self.rules.iter().position(|slot| slot.id == id).ok_or(ApiError::AlertRuleNotFound { id })
+    }
+
+    /// Names are how alerts refer to their rule, so they stay unique
+    fn check_unique(&self, name: &str, except: Option<u64>) -> Result<(), ApiError> {
+        if self.rules.iter().any(|slot| slot.rule.name == name && Some(slot.id) != except) {
+            return Err(ApiError::InvalidFields {
+                errors: vec![FieldError::new("name", format!("a rule named '{}' already exists", name))],
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Shared handle to the alert state; cheap to clone
+#[derive(Clone)]
+pub struct AlertEngine {
+    inner: Arc<Mutex<Inner>>,
+    /// Used by rules without a webhook of their own
+    default_webhook: Option<WebhookTarget>,
+    notify: Arc<OnceLock<mpsc::UnboundedSender<AlertEvent>>>,
+    /// `--persist-rules`: rewritten on every change through the API
+    persist_path: Option<Arc<Path>>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>, default_webhook: Option<WebhookTarget>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                next_rule_id: rules.len() as u64 + 1,
+                rules: (1..)
+                    .zip(rules)
+                    .map(|(id, rule)| RuleSlot { id, rule, state: RuleState::default() })
+                    .collect(),
+                history: VecDeque::new(),
+                next_id: 1,
+            })),
+            default_webhook,
+            notify: Arc::default(),
+            persist_path: None,
+        }
+    }
+
+    /// Saves the rules to `path` whenever they change
+    pub fn persisting_to(mut self, path: PathBuf) -> Self {
+        self.persist_path = Some(path.into());
+        self
+    }
+
+    /// Every webhook a rule may notify, duplicates included
+    pub fn webhook_targets(&self) -> Vec<WebhookTarget> {
+        let inner = self.inner.lock().expect("alerts mutex poisoned");
+        self.default_webhook
+            .iter()
+            .chain(inner.rules.iter().filter_map(|slot| slot.rule.webhook.as_ref()))
+            .cloned()
+            .collect()
+    }
+
+    /// Starts queueing triggers and resolves of rules with a webhook; the
+    /// receiver is meant for [`crate::webhook::Webhooks::deliver`]
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<AlertEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let _ = self.notify.set(tx);
+        rx
+    }
+
+    /// Feeds one sample taken at `now` (Unix epoch seconds). `value` gives
+    /// each rule's current reading; `None` counts as not breaching.
+    pub fn observe(&self, now: u64, value: impl Fn(&AlertRule) -> Option<f64>) {
+        let mut inner = self.inner.lock().expect("alerts mutex poisoned");
+        let Inner { rules, history, next_id, .. } = &mut *inner;
+        for RuleSlot { rule, state, .. } in rules.iter_mut() {
+            let current = value(rule);
+            let breaching = current.filter(|v| rule.operator.holds(*v, rule.threshold));
+            let Some(current) = breaching else {
+                if let Some(alert) = state.resolve(now, history) {
+                    tracing::info!(rule = %rule.name, id = alert.id, "alert resolved");
+                    self.notify(AlertTransition::Resolved, alert, rule);
+                }
+                continue;
+            };
+            let since = *state.breaching_since.get_or_insert(now);
+            match &mut state.firing {
+                Some(alert) => alert.current_value = current,
+                None if now.saturating_sub(since) >= rule.sustained_secs => {
+                    tracing::warn!(rule = %rule.name, value = current, threshold = rule.threshold, "alert triggered");
+                    let alert = Alert {
+                        id: *next_id,
+                        rule: rule.name.clone(),
+                        triggered_at: now,
+                        resolved_at: None,
+                        current_value: current,
+                    };
+                    self.notify(AlertTransition::Triggered, alert.clone(), rule);
+                    state.firing = Some(alert);
+                    *next_id += 1;
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Queues a transition for the rule's webhook, if it has one and
+    /// [`Self::subscribe`] was called
+    fn notify(&self, transition: AlertTransition, alert: Alert, rule: &AlertRule) {
+        let (Some(notify), Some(target)) = (self.notify.get(), rule.webhook.as_ref().or(self.default_webhook.as_ref())) else {
+            return;
+        };
+        // Fails only once the delivery task has stopped at shutdown
+        let _ = notify.send(AlertEvent {
+            transition,
+            alert,
+            metric: rule.metric,
+            operator: rule.operator,
+            threshold: rule.threshold,
+            process: rule.process.clone(),
+            target: target.clone(),
+        });
+    }
+
+    /// Evaluates every rule against a poll's results
+    pub fn observe_stats(&self, now: u64, stats: &SystemStats, table: Option<&ProcessTable>) {
+        self.observe(now, |rule| rule.value(stats, table));
+    }
+
+    /// Alerts firing now, oldest first
+    pub fn active(&self) -> Vec<Alert> {
+        let inner = self.inner.lock().expect("alerts mutex poisoned");
+        let mut active: Vec<Alert> = inner.rules.iter().filter_map(|slot| slot.state.firing.clone()).collect();
+        active.sort_by_key(|alert| alert.id);
+        active
+    }
+
+    /// Up to `limit` resolved alerts, most recently resolved first
+    pub fn history(&self, limit: usize) -> Vec<Alert> {
+        let inner = self.inner.lock().expect("alerts mutex poisoned");
+        inner.history.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Every rule, in the order they were added
+    pub fn rules(&self) -> Vec<AlertRuleInfo> {
+        let inner = self.inner.lock().expect("alerts mutex poisoned");
+        inner.rules.iter().map(RuleSlot::info).collect()
+    }
+
+    /// Adds a rule, evaluated from the next poll on
+    pub fn add_rule(&self, rule: AlertRule) -> Result<AlertRuleInfo, ApiError> {
+        let mut inner = self.inner.lock().expect("alerts mutex poisoned");
+        inner.check_unique(&rule.name, None)?;
+        self.persist(inner.rules.iter().map(|slot| &slot.rule).chain([&rule]))?;
+        let id = inner.next_rule_id;
+        inner.next_rule_id += 1;
+        tracing::info!(id, rule = %rule.name, "alert rule added");
+        let slot = RuleSlot { id, rule, state: RuleState::default() };
+        let info = slot.info();
+        inner.rules.push(slot);
+        Ok(info)
+    }
+
+    /// Replaces rule `id`. An alert it had firing resolves at `now`, and the
+    /// new condition is judged afresh from the next poll.
+    pub fn update_rule(&self, id: u64, rule: AlertRule, now: u64) -> Result<AlertRuleInfo, ApiError> {
+        let mut inner = self.inner.lock().expect("alerts mutex poisoned");
+        let index = inner.position(id)?;
+        inner.check_unique(&rule.name, Some(id))?;
+        self.persist(inner.rules.iter().map(|slot| if slot.id == id { &rule } else { &slot.rule }))?;
+        let Inner { rules, history, .. } = &mut *inner;
+        let slot = &mut rules[index];
+        let old = std::mem::replace(&mut slot.rule, rule);
+        if let Some(alert) = slot.state.resolve(now, history) {
+            self.notify(AlertTransition::Resolved, alert, &old);
+        }
+        tracing::info!(id, rule = %slot.rule.name, "alert rule updated");
+        Ok(slot.info())
+    }
+
+    /// Deletes rule `id`, resolving its alert at `now` if it was firing
+    pub fn remove_rule(&self, id: u64, now: u64) -> Result<AlertRule, ApiError> {
+        let mut inner = self.inner.lock().expect("alerts mutex poisoned");
+        let index = inner.position(id)?;
+        self.persist(inner.rules.iter().filter(|slot| slot.id != id).map(|slot| &slot.rule))?;
+        let Inner { rules, history, .. } = &mut *inner;
+        let mut slot = rules.remove(index);
+        if let Some(alert) = slot.state.resolve(now, history) {
+            self.notify(AlertTransition::Resolved, alert, &slot.rule);
+        }
+        tracing::info!(id, rule = %slot.rule.name, "alert rule removed");
+        Ok(slot.rule)
+    }
+
+    /// Saves `rules` with `--persist-rules`. Called before a change is
+    /// applied, so a failed write leaves the rules as they were.
+    fn persist<'a>(&self, rules: impl Iterator<Item = &'a AlertRule>) -> Result<(), ApiError> {
+        let Some(path) = &self.persist_path else {
+            return Ok(());
+        };
+        let rules: Vec<&AlertRule> = rules.collect();
+        save_rules(path, &rules).map_err(|err| {
+            tracing::error!(path = %path.display(), error = %err, "cannot save alert rules");
+            ApiError::Internal
+        })
+    }
+}
@@ -0,0 +1,94 @@
+//! ARP cache: `/proc/net/arp` on Linux, `arp -an` on macOS. Both are
+//! readable without root.
+
+use crate::error::ApiError;
+use crate::models::ArpEntry;
+
+const INCOMPLETE_MAC: &str = "00:00:00:00:00:00";
+
+#[cfg(target_os = "linux")]
+pub async fn list() -> Result<Vec<ArpEntry>, ApiError> {
+    match tokio::fs::read_to_string("/proc/net/arp").await {
+        Ok(text) => Ok(parse_proc_arp(&text)),
+        // Kernels built without IPv4 have no table
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => {
+            tracing::warn!(error = %err, "cannot read /proc/net/arp");
+            Err(ApiError::Internal)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub async fn list() -> Result<Vec<ArpEntry>, ApiError> {
+    let output = tokio::process::Command::new("/usr/sbin/arp")
+        .arg("-an")
+        .output()
+        .await
+        .map_err(|err| {
+            tracing::warn!(error = %err, "cannot run arp");
+            ApiError::Internal
+        })?;
+    Ok(parse_arp_an(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub async fn list() -> Result<Vec<ArpEntry>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "ARP cache listing" })
+}
+
+/// Parses rows like `192.168.1.1  0x1  0x2  52:54:00:12:35:02  *  eth0`
+/// after the header line
+pub fn parse_proc_arp(text: &str) -> Vec<ArpEntry> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(ArpEntry {
+                ip_address: fields.next()?.to_string(),
+                hw_type: fields.next()?.to_string(),
+                flags: fields.next()?.to_string(),
+                mac_address: normalize_mac(fields.next()?),
+                mask: fields.next()?.to_string(),
+                device: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses `? (192.168.1.1) at 0:11:22:33:44:55 on en0 ifscope [ethernet]`;
+/// unresolved hosts read `at (incomplete)`
+pub fn parse_arp_an(text: &str) -> Vec<ArpEntry> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let ip_address = fields.get(1)?.strip_prefix('(')?.strip_suffix(')')?.to_string();
+            let at = fields.iter().position(|&f| f == "at")?;
+            let mac_address = match *fields.get(at + 1)? {
+                "(incomplete)" => INCOMPLETE_MAC.to_string(),
+                mac => normalize_mac(mac),
+            };
+            let on = fields.iter().position(|&f| f == "on")?;
+            let hw_type = fields
+                .iter()
+                .find_map(|f| f.strip_prefix('[').and_then(|f| f.strip_suffix(']')))
+                .unwrap_or_default();
+            Some(ArpEntry {
+                ip_address,
+                hw_type: hw_type.to_string(),
+                flags: if fields.contains(&"permanent") { "permanent" } else { "" }.to_string(),
+                mac_address,
+                mask: "*".to_string(),
+                device: fields.get(on + 1)?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// macOS drops leading zeros (`0:1b:...`); pad each octet and lowercase
+fn normalize_mac(mac: &str) -> String {
+    mac.split(':')
+        .map(|octet| format!("{:0>2}", octet.to_ascii_lowercase()))
+        .collect::<Vec<_>>()
+        .join(":")
+}
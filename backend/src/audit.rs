@@ -0,0 +1,163 @@
+//! Audit trail of destructive actions, kept in memory and optionally appended
+//! to a JSON-lines file by a background writer
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts},
+};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::ApiError;
+use crate::middleware::RequestId;
+use crate::models::AuditEntry;
+
+/// Oldest entries are dropped from memory past this; the file keeps everything
+pub const AUDIT_MEMORY_CAPACITY: usize = 10_000;
+
+/// Entries returned by `GET /api/audit` without `?limit=`
+pub const DEFAULT_AUDIT_LIMIT: usize = 100;
+
+/// Who made a request: the peer address and a short fingerprint of the
+/// bearer token, plus the request's ID to match it with the logs
+#[derive(Clone, Debug, Default)]
+pub struct Actor {
+    pub client_ip: Option<String>,
+    pub token_id: Option<String>,
+    pub request_id: Option<String>,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let client_ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+        let token_id = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(token_id);
+        let request_id = parts.extensions.get::<RequestId>().map(|RequestId(id)| id.clone());
+        Ok(Self { client_ip, token_id, request_id })
+    }
+}
+
+/// First 8 hex digits of the token's SHA-256; identifies it without revealing it
+pub fn token_id(token: &str) -> String {
+    Sha256::digest(token.trim().as_bytes())
+        .iter()
+        .take(4)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl AuditEntry {
+    /// `outcome` is `success`, or the error code the request failed with
+    pub fn new<T>(action: &str, pid: u32, process_name: Option<String>, result: &Result<T, ApiError>, actor: &Actor) -> Self {
+        Self {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            action: action.to_string(),
+            pid,
+            process_name,
+            outcome: match result {
+                Ok(_) => "success".to_string(),
+                Err(err) => err.code().to_string(),
+            },
+            client_ip: actor.client_ip.clone(),
+            token_id: actor.token_id.clone(),
+            request_id: actor.request_id.clone(),
+        }
+    }
+}
+
+/// Shared handle to the audit trail; cheap to clone
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    entries: Arc<Mutex<VecDeque<AuditEntry>>>,
+    file: Arc<OnceLock<mpsc::UnboundedSender<AuditEntry>>>,
+}
+
+impl AuditLog {
+    /// Stores `entry` and queues it for the file writer; never blocks on I/O
+    pub fn record(&self, entry: AuditEntry) {
+        if let Some(file) = self.file.get() {
+            // Fails only once the writer has given up; memory still has the entry
+            let _ = file.send(entry.clone());
+        }
+        let mut entries = self.entries.lock().expect("audit mutex poisoned");
+        if entries.len() == AUDIT_MEMORY_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Up to `limit` entries, newest first
+    pub fn recent(&self, limit: usize) -> Vec<AuditEntry> {
+        let entries = self.entries.lock().expect("audit mutex poisoned");
+        entries.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Whether the file writer is still accepting entries; `None` when none
+    /// was started
+    pub fn writer_running(&self) -> Option<bool> {
+        self.file.get().map(|file| !file.is_closed())
+    }
+
+    /// Starts appending every recorded entry to `path`. An unopenable or
+    /// unwritable file logs a warning and leaves the log memory-only.
+    pub fn writer(&self, path: PathBuf, shutdown: CancellationToken) -> impl std::future::Future<Output = ()> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let _ = self.file.set(tx);
+        write_entries(path, rx, shutdown)
+    }
+}
+
+async fn write_entries(path: PathBuf, mut rx: mpsc::UnboundedReceiver<AuditEntry>, shutdown: CancellationToken) {
+    let file = tokio::fs::OpenOptions::new().create(true).append(true).open(&path).await;
+    let mut file = match file {
+        Ok(file) => file,
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "cannot open audit log, keeping entries in memory only");
+            return;
+        }
+    };
+
+    loop {
+        let entry = tokio::select! {
+            Some(entry) = rx.recv() => entry,
+            _ = shutdown.cancelled() => break,
+        };
+        if let Err(err) = append(&mut file, &entry).await {
+            tracing::warn!(path = %path.display(), error = %err, "audit log write failed, keeping entries in memory only");
+            return;
+        }
+    }
+
+    // Flush whatever was recorded before shutdown began
+    while let Ok(entry) = rx.try_recv() {
+        if append(&mut file, &entry).await.is_err() {
+            break;
+        }
+    }
+    let _ = file.flush().await;
+}
+
+async fn append(file: &mut tokio::fs::File, entry: &AuditEntry) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    file.write_all(&line).await
+}
@@ -0,0 +1,89 @@
+//! Linux capability sets of a process, from the `Cap*:` lines of
+//! `/proc/<pid>/status`. Each line is a hex bitmask; bit N is capability N.
+
+use crate::models::ProcessCapabilities;
+
+/// Capability names by bit number, from `linux/capability.h`
+const NAMES: &[&str] = &[
+    "CAP_CHOWN",
+    "CAP_DAC_OVERRIDE",
+    "CAP_DAC_READ_SEARCH",
+    "CAP_FOWNER",
+    "CAP_FSETID",
+    "CAP_KILL",
+    "CAP_SETGID",
+    "CAP_SETUID",
+    "CAP_SETPCAP",
+    "CAP_LINUX_IMMUTABLE",
+    "CAP_NET_BIND_SERVICE",
+    "CAP_NET_BROADCAST",
+    "CAP_NET_ADMIN",
+    "CAP_NET_RAW",
+    "CAP_IPC_LOCK",
+    "CAP_IPC_OWNER",
+    "CAP_SYS_MODULE",
+    "CAP_SYS_RAWIO",
+    "CAP_SYS_CHROOT",
+    "CAP_SYS_PTRACE",
+    "CAP_SYS_PACCT",
+    "CAP_SYS_ADMIN",
+    "CAP_SYS_BOOT",
+    "CAP_SYS_NICE",
+    "CAP_SYS_RESOURCE",
+    "CAP_SYS_TIME",
+    "CAP_SYS_TTY_CONFIG",
+    "CAP_MKNOD",
+    "CAP_LEASE",
+    "CAP_AUDIT_WRITE",
+    "CAP_AUDIT_CONTROL",
+    "CAP_SETFCAP",
+    "CAP_MAC_OVERRIDE",
+    "CAP_MAC_ADMIN",
+    "CAP_SYSLOG",
+    "CAP_WAKE_ALARM",
+    "CAP_BLOCK_SUSPEND",
+    "CAP_AUDIT_READ",
+    "CAP_PERFMON",
+    "CAP_BPF",
+    "CAP_CHECKPOINT_RESTORE",
+];
+
+/// `None` on other platforms or when the process is gone
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<ProcessCapabilities> {
+    let text = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    parse_status(&text)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<ProcessCapabilities> {
+    None
+}
+
+/// `None` without a `CapEff:` line. `CapAmb:` arrived in Linux 4.3, so
+/// ambient is empty on older kernels.
+pub fn parse_status(text: &str) -> Option<ProcessCapabilities> {
+    let set = |prefix: &str| -> Option<Vec<String>> {
+        let value = text.lines().find_map(|line| line.strip_prefix(prefix))?;
+        u64::from_str_radix(value.trim(), 16).ok().map(names)
+    };
+    Some(ProcessCapabilities {
+        effective: set("CapEff:")?,
+        permitted: set("CapPrm:").unwrap_or_default(),
+        inheritable: set("CapInh:").unwrap_or_default(),
+        bounding: set("CapBnd:").unwrap_or_default(),
+        ambient: set("CapAmb:").unwrap_or_default(),
+    })
+}
+
+/// Names of the bits set in `mask`, by bit number. A bit newer than the
+/// table is named `CAP_<bit>`.
+pub fn names(mask: u64) -> Vec<String> {
+    (0..64)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| match NAMES.get(bit as usize) {
+            Some(name) => name.to_string(),
+            None => format!("CAP_{}", bit),
+        })
+        .collect()
+}
@@ -0,0 +1,22 @@
+//! Helpers for Rust clients of `/api/ws/stats/binary`
+
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+use crate::models::SystemStats;
+
+/// The text message that sets the push rate
+pub fn control_message(interval: Duration) -> String {
+    serde_json::json!({ "interval_ms": interval.as_millis() as u64 }).to_string()
+}
+
+/// One binary frame as the snapshot the server sent
+pub fn decode_frame(bytes: &[u8]) -> Result<SystemStats, String> {
+    decode_frame_as(bytes)
+}
+
+/// One binary frame into the client's own type, which may declare only the
+/// fields it needs
+pub fn decode_frame_as<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    rmp_serde::from_slice(bytes).map_err(|err| err.to_string())
+}
@@ -0,0 +1,51 @@
+//! Renames processes by writing `/proc/<pid>/comm`.
+//!
+//! This is the same kernel call as `prctl(PR_SET_NAME)`, but prctl renames
+//! only the calling thread, which here would be whichever tokio worker ran
+//! the request. Writing `comm` renames the thread-group leader, which is what
+//! `/proc/<pid>/comm`, `ps` and `top` show. Either way the kernel only lets a
+//! process rename itself and its own threads: a write to another process's
+//! `comm` fails with EINVAL, for root too.
+
+use crate::error::ApiError;
+
+/// `TASK_COMM_LEN` minus the terminating NUL; the kernel truncates longer names
+pub const MAX_NAME_BYTES: usize = 15;
+
+/// Rejects empty names, names over [`MAX_NAME_BYTES`] and control characters
+pub fn validate_name(name: &str) -> Result<(), ApiError> {
+    let reason = if name.is_empty() {
+        "name must not be empty".to_string()
+    } else if name.len() > MAX_NAME_BYTES {
+        format!("name is {} bytes; the kernel allows at most {}", name.len(), MAX_NAME_BYTES)
+    } else if name.chars().any(char::is_control) {
+        "name must not contain control characters".to_string()
+    } else {
+        return Ok(());
+    };
+    Err(ApiError::InvalidBody { reason })
+}
+
+#[cfg(target_os = "linux")]
+pub fn write(pid: u32, name: &str) -> Result<(), ApiError> {
+    use std::io::Write;
+
+    let result = std::fs::OpenOptions::new()
+        .write(true)
+        .open(format!("/proc/{}/comm", pid))
+        .and_then(|mut file| file.write_all(name.as_bytes()));
+    result.map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => ApiError::ProcessNotFound { pid },
+        std::io::ErrorKind::PermissionDenied => ApiError::PermissionDenied { pid },
+        _ if err.raw_os_error() == Some(libc::EINVAL) => ApiError::RenameNotPermitted { pid },
+        _ => {
+            tracing::warn!(pid, error = %err, "cannot write comm");
+            ApiError::Internal
+        }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn write(_pid: u32, _name: &str) -> Result<(), ApiError> {
+    Err(ApiError::NotImplemented { feature: "process renaming" })
+}
@@ -0,0 +1,702 @@
+//! Runtime configuration
+
+use axum::http::HeaderValue;
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::alerts::{self, AlertRule};
+use crate::docker;
+use crate::environ::{Redactor, DEFAULT_REDACT_PATTERNS};
+use crate::gpu::DEFAULT_GPU_COOLDOWN;
+use crate::policy::{KillPolicy, MatchStyle};
+use crate::webhook::WebhookTarget;
+
+/// Address the HTTP listener binds to
+pub const DEFAULT_HOST: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
+pub const DEFAULT_PORT: u16 = 8000;
+
+/// Responses smaller than this are sent uncompressed (e.g. /health)
+pub const DEFAULT_COMPRESSION_MIN_SIZE: u16 = 1024;
+
+/// How often the background poller refreshes system stats
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Origins of the Vite dev server and of a frontend served on port 3000, the
+/// only browser pages allowed by default
+pub const DEFAULT_CORS_ORIGINS: &[&str] = &["http://localhost:5173", "http://127.0.0.1:5173", "http://localhost:3000"];
+
+/// How long shutdown waits for in-flight requests before forcing exit
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `tracing` filter used when `TASKMGR_LOG_LEVEL` is unset
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Sustained mutating requests per second allowed from one client IP
+pub const DEFAULT_RATE_LIMIT: u32 = 5;
+
+/// Mutating requests one client IP may send back to back before being limited
+pub const DEFAULT_RATE_LIMIT_BURST: u32 = 10;
+
+/// Shells, init systems, session managers and terminals: the ancestors
+/// `/api/apps?group=tree` stops below, so apps launched from them form their own groups
+pub const DEFAULT_SESSION_PROCESSES: &[&str] = &[
+    // Unix init, shells and remote sessions
+    "init", "systemd", "launchd", "kthreadd", "login", "sshd", "sudo", "su", "doas",
+    "sh", "bash", "dash", "zsh", "fish", "ksh", "csh", "tcsh", "nu",
+    "tmux", "tmux: server", "screen",
+    // Desktop sessions and terminals
+    "gdm", "gdm-session-worker", "lightdm", "sddm", "Xorg", "gnome-session-binary", "gnome-shell",
+    "plasmashell", "kwin_wayland", "xfce4-session", "gnome-terminal-server", "konsole",
+    "alacritty", "kitty", "wezterm-gui", "xterm", "Terminal", "iTerm2",
+    // Windows
+    "System", "smss.exe", "wininit.exe", "winlogon.exe", "services.exe", "svchost.exe", "userinit.exe",
+    "explorer.exe", "cmd.exe", "powershell.exe", "pwsh.exe", "conhost.exe", "WindowsTerminal.exe", "OpenConsole.exe",
+];
+
+/// HTTPS port used when TLS is enabled without `--tls-port`
+pub const DEFAULT_TLS_PORT: u16 = 8443;
+
+/// Command-line flags; these override the `--config` file and `TASKMGR_*` variables
+#[derive(Parser, Clone, Debug, Default)]
+#[command(name = "task_manager_backend", version, about = "Task Manager Pro backend")]
+pub struct Cli {
+    /// Address to bind [env: TASKMGR_HOST] [default: 0.0.0.0]
+    #[arg(long, value_name = "ADDR")]
+    pub host: Option<IpAddr>,
+    /// HTTP port [env: TASKMGR_PORT] [default: 8000]
+    #[arg(long, value_name = "PORT")]
+    pub port: Option<u16>,
+    /// How often system stats are refreshed [env: TASKMGR_POLL_INTERVAL_MS] [default: 1000]
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub sample_interval_ms: Option<u64>,
+    /// Seconds to wait for in-flight requests on shutdown [env: TASKMGR_DRAIN_TIMEOUT_SECS] [default: 30]
+    #[arg(long, value_name = "SECS")]
+    pub drain_timeout_secs: Option<u64>,
+    /// Seconds NVML is left alone after 5 failed GPU samples in a row [env: TASKMGR_GPU_COOLDOWN_SECS] [default: 60]
+    #[arg(long, value_name = "SECS")]
+    pub gpu_cooldown_secs: Option<u64>,
+    /// Reject every kill/suspend/resume request [env: TASKMGR_READ_ONLY]
+    #[arg(long)]
+    pub read_only: bool,
+    /// Mutating requests per second per client IP, 0 to disable [env: TASKMGR_RATE_LIMIT] [default: 5]
+    #[arg(long, value_name = "N")]
+    pub rate_limit: Option<u32>,
+    /// Mutating requests allowed back to back before the limit applies [env: TASKMGR_RATE_LIMIT_BURST] [default: 10]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub rate_limit_burst: Option<u32>,
+    /// Log output format [env: TASKMGR_LOG_FORMAT] [default: json]
+    #[arg(long, value_name = "FORMAT")]
+    pub log_format: Option<LogFormat>,
+    /// Bearer token for mutating requests [env: TASKMGR_TOKEN] [default: random]
+    #[arg(long, value_name = "TOKEN")]
+    pub token: Option<String>,
+    /// Browser origin allowed by CORS; repeat for several, `*` allows any
+    /// [env: TASKMGR_CORS_ORIGINS, comma-separated] [default: the Vite dev server on :5173]
+    #[arg(long = "cors-origin", value_name = "ORIGIN")]
+    pub cors_origins: Vec<String>,
+    /// Also append audit entries to this JSON-lines file [env: TASKMGR_AUDIT_LOG]
+    #[arg(long, value_name = "PATH")]
+    pub audit_log: Option<PathBuf>,
+    /// Save alert rules changed through the API to this JSON file, which
+    /// replaces `[[alerts]]` once it exists [env: TASKMGR_PERSIST_RULES]
+    #[arg(long, value_name = "PATH")]
+    pub persist_rules: Option<PathBuf>,
+    /// TOML config file
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// PEM certificate chain; enables HTTPS together with --tls-key
+    #[arg(long, value_name = "PATH")]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key for --tls-cert
+    #[arg(long, value_name = "PATH")]
+    pub tls_key: Option<PathBuf>,
+    /// HTTPS port [default: 8443]
+    #[arg(long, value_name = "PORT")]
+    pub tls_port: Option<u16>,
+    /// Query Docker for container names even if its socket isn't there at startup [env: TASKMGR_DOCKER]
+    #[arg(long)]
+    pub docker: bool,
+}
+
+/// Contents of the `--config` TOML file; every key is optional
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    #[serde(default)]
+    pub tls: FileTlsConfig,
+    /// Replaces `TASKMGR_ALLOWED_COMMANDS` when present
+    pub allowed_commands: Option<Vec<String>>,
+    /// Executables `POST /api/process/launch` may run; any when absent
+    pub launch_executables: Option<Vec<String>>,
+    #[serde(default)]
+    pub cors: FileCorsConfig,
+    /// Deprecated alias of `[cors] allowed_origins`, which wins when both are set
+    pub cors_origins: Option<Vec<String>>,
+    /// Replaces `TASKMGR_PROTECTED_PROCESSES` when present
+    pub protected_processes: Option<Vec<String>>,
+    /// Replaces the default session processes when present
+    pub session_processes: Option<Vec<String>>,
+    /// Globs over process name or exe path that may never be killed
+    #[serde(default)]
+    pub protected_names: Vec<String>,
+    /// When non-empty, globs naming the only processes that may be killed
+    #[serde(default)]
+    pub killable_only_names: Vec<String>,
+    /// Replaces `TASKMGR_REDACT_ENV` when present
+    pub redact_env: Option<Vec<String>>,
+    /// `[[alerts]]` tables
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+    /// Notified by rules without a webhook of their own
+    pub alert_webhook: Option<WebhookTarget>,
+}
+
+/// `[tls]` table of the config file
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FileTlsConfig {
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+    pub port: Option<u16>,
+}
+
+/// `[cors]` table of the config file
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FileCorsConfig {
+    /// Replaces `TASKMGR_CORS_ORIGINS` when present; `["*"]` allows any origin
+    pub allowed_origins: Option<Vec<String>>,
+}
+
+impl FileConfig {
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("cannot read config file {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid config file {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("TLS needs both a certificate and a key")]
+    IncompleteTls,
+    #[error("invalid kill policy pattern: {0}")]
+    Pattern(#[from] globset::Error),
+    #[error("invalid redact_env pattern: {0}")]
+    RedactPattern(globset::Error),
+    #[error("invalid CORS origin '{0}': expected scheme://host[:port]")]
+    CorsOrigin(String),
+    #[error("invalid alert rule {0}")]
+    AlertRule(String),
+    #[error("cannot load alert rules from {}: {reason}", path.display())]
+    RulesFile { path: PathBuf, reason: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub host: IpAddr,
+    pub port: u16,
+    pub compression: CompressionConfig,
+    pub poll_interval: Duration,
+    pub drain_timeout: Duration,
+    /// How long GPU stats stop querying NVML once it keeps failing
+    pub gpu_cooldown: Duration,
+    /// Any `tracing` filter directive, e.g. `debug` or `task_manager_backend=debug`;
+    /// `RUST_LOG` overrides it when set
+    pub log_level: String,
+    pub log_format: LogFormat,
+    pub auth: AuthConfig,
+    /// HTTPS settings; `None` serves plain HTTP only
+    pub tls: Option<TlsConfig>,
+    /// Commands `POST /api/process/start` may run, matched exactly; empty allows none
+    pub allowed_commands: Vec<String>,
+    /// Executables `POST /api/process/launch` may run, matched exactly; `None` allows any
+    pub launch_executables: Option<Vec<String>>,
+    pub cors_origins: CorsOrigins,
+    /// JSON-lines file the audit trail is appended to; `None` keeps it in memory only
+    pub audit_log: Option<PathBuf>,
+    /// Per-IP limit on mutating requests; `None` disables it
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Process names protected on top of the built-in rules, matched case-insensitively
+    pub protected_processes: Vec<String>,
+    /// `protected_names` / `killable_only_names` from the config file
+    pub kill_policy: KillPolicy,
+    /// Names `/api/apps?group=tree` won't group under, matched case-insensitively
+    pub session_processes: Vec<String>,
+    /// Globs over environment variable names whose values
+    /// `/api/process/:pid/environ` masks, matched case-insensitively
+    pub redact_env: Vec<String>,
+    pub docker: DockerConfig,
+    /// Threshold rules the poller evaluates at startup: the `persist_rules`
+    /// file when it exists, else `[[alerts]]`
+    pub alert_rules: Vec<AlertRule>,
+    /// JSON file rules changed through `/api/alert_rules` are saved to; `None`
+    /// keeps changes until the backend stops
+    pub persist_rules: Option<PathBuf>,
+    /// `[alert_webhook]`: where rules without a webhook of their own send alerts
+    pub alert_webhook: Option<WebhookTarget>,
+}
+
+/// Docker Engine API access for `/api/containers`
+#[derive(Clone, Debug, PartialEq)]
+pub struct DockerConfig {
+    pub enabled: bool,
+    /// Unix socket, or named pipe on Windows
+    pub socket: PathBuf,
+}
+
+impl Default for DockerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            socket: PathBuf::from(docker::DEFAULT_SOCKET),
+        }
+    }
+}
+
+impl DockerConfig {
+    /// `TASKMGR_DOCKER_SOCKET` moves the socket (e.g. rootless Docker's
+    /// `$XDG_RUNTIME_DIR/docker.sock`). `TASKMGR_DOCKER=off` disables the
+    /// integration and `on` enables it even while the socket is missing;
+    /// unset, it is enabled when the socket exists.
+    fn from_vars(enabled: Option<&str>, socket: Option<&str>) -> Self {
+        let socket = socket
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(docker::DEFAULT_SOCKET));
+        Self {
+            enabled: enabled.map_or_else(|| socket.exists(), is_truthy),
+            socket,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitConfig {
+    pub per_second: u32,
+    pub burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_second: DEFAULT_RATE_LIMIT,
+            burst: DEFAULT_RATE_LIMIT_BURST,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// `TASKMGR_RATE_LIMIT` of `0` or `off` disables limiting
+    fn from_vars(per_second: Option<&str>, burst: Option<&str>) -> Option<Self> {
+        let defaults = Self::default();
+        let per_second = match per_second.map(str::trim) {
+            None => defaults.per_second,
+            Some(v) if !is_truthy(v) => return None,
+            Some(v) => v.parse().unwrap_or(defaults.per_second),
+        };
+        Some(Self {
+            per_second,
+            burst: burst
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|b| *b > 0)
+                .unwrap_or(defaults.burst),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per line, for log shippers
+    #[default]
+    Json,
+}
+
+/// Which browser origins may call the API
+#[derive(Clone, Debug, PartialEq)]
+pub enum CorsOrigins {
+    /// `Access-Control-Allow-Origin: *`; any website can read the API
+    Any,
+    /// Exact origins such as `http://localhost:5173`
+    List(Vec<String>),
+}
+
+impl Default for CorsOrigins {
+    fn default() -> Self {
+        Self::List(DEFAULT_CORS_ORIGINS.iter().map(|o| o.to_string()).collect())
+    }
+}
+
+impl CorsOrigins {
+    /// A `*` entry anywhere opts into [`CorsOrigins::Any`]
+    pub fn from_list<S: AsRef<str>>(origins: impl IntoIterator<Item = S>) -> Self {
+        let mut list = Vec::new();
+        for origin in origins {
+            let origin = origin.as_ref().trim().trim_end_matches('/');
+            match origin {
+                "*" => return Self::Any,
+                "" => {}
+                _ => list.push(origin.to_string()),
+            }
+        }
+        Self::List(list)
+    }
+
+    /// Whether `origin` is named in the list; never for [`CorsOrigins::Any`],
+    /// which names none
+    pub fn lists(&self, origin: &str) -> bool {
+        match self {
+            Self::Any => false,
+            Self::List(origins) => origins.iter().any(|listed| listed == origin),
+        }
+    }
+
+    /// Every listed origin must be a valid header value with a scheme, so
+    /// none is silently dropped when the CORS layer is built
+    fn validate(&self) -> Result<(), ConfigError> {
+        let Self::List(origins) = self else {
+            return Ok(());
+        };
+        match origins
+            .iter()
+            .find(|origin| !origin.contains("://") || HeaderValue::from_str(origin).is_err())
+        {
+            Some(origin) => Err(ConfigError::CorsOrigin(origin.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TlsConfig {
+    /// PEM certificate chain, re-read when its mtime changes
+    pub cert: PathBuf,
+    /// PEM private key
+    pub key: PathBuf,
+    pub port: u16,
+}
+
+impl TlsConfig {
+    /// CLI values win over the file; cert and key must be given together
+    fn resolve(cli: &Cli, file: FileTlsConfig) -> Result<Option<Self>, ConfigError> {
+        let cert = cli.tls_cert.clone().or(file.cert);
+        let key = cli.tls_key.clone().or(file.key);
+        match (cert, key) {
+            (Some(cert), Some(key)) => Ok(Some(Self {
+                cert,
+                key,
+                port: cli.tls_port.or(file.port).unwrap_or(DEFAULT_TLS_PORT),
+            })),
+            (None, None) => Ok(None),
+            _ => Err(ConfigError::IncompleteTls),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AuthConfig {
+    /// Bearer token required on mutating routes; `None` disables auth
+    pub token: Option<String>,
+    /// Also require the token on GET routes (except `/health`)
+    pub protect_reads: bool,
+    /// Refuse every mutating request, token or not
+    pub read_only: bool,
+}
+
+impl AuthConfig {
+    /// Uses `TASKMGR_TOKEN` when set, otherwise generates a random token.
+    /// `TASKMGR_AUTH_READS=on` extends the requirement to GET routes and
+    /// `TASKMGR_READ_ONLY=on` refuses mutating requests outright.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let token = lookup("TASKMGR_TOKEN")
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(random_token);
+        Self {
+            token: Some(token),
+            protect_reads: lookup("TASKMGR_AUTH_READS").as_deref().is_some_and(is_truthy),
+            read_only: lookup("TASKMGR_READ_ONLY").as_deref().is_some_and(is_truthy),
+        }
+    }
+}
+
+fn random_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Comma-separated variable → trimmed, non-empty entries
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn default_session_processes() -> Vec<String> {
+    DEFAULT_SESSION_PROCESSES.iter().map(|name| name.to_string()).collect()
+}
+
+fn default_redact_env() -> Vec<String> {
+    DEFAULT_REDACT_PATTERNS.iter().map(|pattern| pattern.to_string()).collect()
+}
+
+fn is_truthy(value: &str) -> bool {
+    !matches!(value.trim().to_ascii_lowercase().as_str(), "0" | "false" | "off" | "no" | "")
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            host: DEFAULT_HOST,
+            port: DEFAULT_PORT,
+            compression: CompressionConfig::default(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            gpu_cooldown: DEFAULT_GPU_COOLDOWN,
+            log_level: DEFAULT_LOG_LEVEL.to_string(),
+            log_format: LogFormat::default(),
+            auth: AuthConfig::default(),
+            tls: None,
+            allowed_commands: Vec::new(),
+            launch_executables: None,
+            cors_origins: CorsOrigins::default(),
+            audit_log: None,
+            rate_limit: Some(RateLimitConfig::default()),
+            protected_processes: Vec::new(),
+            kill_policy: KillPolicy::default(),
+            session_processes: default_session_processes(),
+            redact_env: default_redact_env(),
+            docker: DockerConfig::default(),
+            alert_rules: Vec::new(),
+            persist_rules: None,
+            alert_webhook: None,
+        }
+    }
+}
+
+impl Config {
+    /// Reads `TASKMGR_*` variables, falling back to defaults
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Like [`Config::from_env`], with variables supplied by `lookup`
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        Self {
+            host: lookup("TASKMGR_HOST")
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_HOST),
+            port: lookup("TASKMGR_PORT")
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_PORT),
+            compression: CompressionConfig::from_vars(
+                lookup("TASKMGR_COMPRESSION").as_deref(),
+                lookup("TASKMGR_COMPRESSION_MIN_SIZE").as_deref(),
+            ),
+            poll_interval: lookup("TASKMGR_POLL_INTERVAL_MS")
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_POLL_INTERVAL),
+            drain_timeout: lookup("TASKMGR_DRAIN_TIMEOUT_SECS")
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_DRAIN_TIMEOUT),
+            gpu_cooldown: lookup("TASKMGR_GPU_COOLDOWN_SECS")
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_GPU_COOLDOWN),
+            log_level: lookup("TASKMGR_LOG_LEVEL").unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string()),
+            log_format: lookup("TASKMGR_LOG_FORMAT")
+                .and_then(|v| LogFormat::from_str(v.trim(), true).ok())
+                .unwrap_or_default(),
+            auth: AuthConfig::from_lookup(&lookup),
+            tls: None,
+            allowed_commands: lookup("TASKMGR_ALLOWED_COMMANDS")
+                .map(|v| split_list(&v))
+                .unwrap_or_default(),
+            launch_executables: None,
+            cors_origins: lookup("TASKMGR_CORS_ORIGINS")
+                .map(|v| CorsOrigins::from_list(v.split(',')))
+                .unwrap_or_default(),
+            audit_log: lookup("TASKMGR_AUDIT_LOG")
+                .filter(|v| !v.trim().is_empty())
+                .map(PathBuf::from),
+            rate_limit: RateLimitConfig::from_vars(
+                lookup("TASKMGR_RATE_LIMIT").as_deref(),
+                lookup("TASKMGR_RATE_LIMIT_BURST").as_deref(),
+            ),
+            protected_processes: lookup("TASKMGR_PROTECTED_PROCESSES")
+                .map(|v| split_list(&v))
+                .unwrap_or_default(),
+            kill_policy: KillPolicy::default(),
+            session_processes: lookup("TASKMGR_SESSION_PROCESSES")
+                .map(|v| split_list(&v))
+                .unwrap_or_else(default_session_processes),
+            redact_env: lookup("TASKMGR_REDACT_ENV")
+                .map(|v| split_list(&v))
+                .unwrap_or_else(default_redact_env),
+            docker: DockerConfig::from_vars(
+                lookup("TASKMGR_DOCKER").as_deref(),
+                lookup("TASKMGR_DOCKER_SOCKET").as_deref(),
+            ),
+            alert_rules: Vec::new(),
+            persist_rules: lookup("TASKMGR_PERSIST_RULES")
+                .filter(|v| !v.trim().is_empty())
+                .map(PathBuf::from),
+            alert_webhook: None,
+        }
+    }
+
+    pub fn bind_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.host, self.port)
+    }
+
+    /// Environment first, then the `--config` file, then CLI flags
+    pub fn load(cli: &Cli) -> Result<Self, ConfigError> {
+        Self::from_env().merge(cli)
+    }
+
+    /// Applies the `--config` file and CLI flags on top of `self`
+    pub fn merge(mut self, cli: &Cli) -> Result<Self, ConfigError> {
+        let file = match &cli.config {
+            Some(path) => FileConfig::read(path)?,
+            None => FileConfig::default(),
+        };
+        self.tls = TlsConfig::resolve(cli, file.tls)?;
+        if let Some(commands) = file.allowed_commands {
+            self.allowed_commands = commands;
+        }
+        if file.launch_executables.is_some() {
+            self.launch_executables = file.launch_executables;
+        }
+        if let Some(names) = file.protected_processes {
+            self.protected_processes = names;
+        }
+        if let Some(names) = file.session_processes {
+            self.session_processes = names;
+        }
+        if let Some(patterns) = file.redact_env {
+            self.redact_env = patterns;
+        }
+        Redactor::new(&self.redact_env).map_err(ConfigError::RedactPattern)?;
+        self.kill_policy = KillPolicy::new(&file.protected_names, &file.killable_only_names, MatchStyle::NATIVE)?;
+        if let Some(origins) = file.cors.allowed_origins.or(file.cors_origins) {
+            self.cors_origins = CorsOrigins::from_list(origins);
+        }
+        if !cli.cors_origins.is_empty() {
+            self.cors_origins = CorsOrigins::from_list(&cli.cors_origins);
+        }
+        self.cors_origins.validate()?;
+        alerts::validate(&file.alerts, file.alert_webhook.as_ref())?;
+        self.alert_rules = file.alerts;
+        self.alert_webhook = file.alert_webhook;
+        
+        if let Some(host) = cli.host {
+            self.host = host;
+        }
+        if let Some(port) = cli.port {
+            self.port = port;
+        }
+        if let Some(ms) = cli.sample_interval_ms {
+            self.poll_interval = Duration::from_millis(ms);
+        }
+        if let Some(format) = cli.log_format {
+            self.log_format = format;
+        }
+        match cli.rate_limit {
+            Some(0) => self.rate_limit = None,
+            Some(per_second) => self.rate_limit.get_or_insert_with(RateLimitConfig::default).per_second = per_second,
+            None => {}
+        }
+        if let (Some(burst), Some(limit)) = (cli.rate_limit_burst, self.rate_limit.as_mut()) {
+            limit.burst = burst;
+        }
+        if let Some(path) = &cli.audit_log {
+            self.audit_log = Some(path.clone());
+        }
+        if let Some(path) = &cli.persist_rules {
+            self.persist_rules = Some(path.clone());
+        }
+        if let Some(path) = &self.persist_rules {
+            if let Some(rules) = alerts::load_rules(path)? {
+                alerts::validate(&rules, None)?;
+                self.alert_rules = rules;
+            }
+        }
+        if let Some(secs) = cli.drain_timeout_secs {
+            self.drain_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = cli.gpu_cooldown_secs {
+            self.gpu_cooldown = Duration::from_secs(secs);
+        }
+        if let Some(token) = cli.token.as_deref().filter(|t| !t.trim().is_empty()) {
+            self.auth.token = Some(token.to_string());
+        }
+        self.auth.read_only |= cli.read_only;
+        self.docker.enabled |= cli.docker;
+        Ok(self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompressionConfig {
+    pub enabled: bool,
+    pub min_size: u16,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_size: DEFAULT_COMPRESSION_MIN_SIZE,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Reads `TASKMGR_COMPRESSION` (on/off) and `TASKMGR_COMPRESSION_MIN_SIZE` (bytes)
+    pub fn from_env() -> Self {
+        Self::from_vars(
+            std::env::var("TASKMGR_COMPRESSION").ok().as_deref(),
+            std::env::var("TASKMGR_COMPRESSION_MIN_SIZE").ok().as_deref(),
+        )
+    }
+
+    pub fn from_vars(enabled: Option<&str>, min_size: Option<&str>) -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: enabled.map(is_truthy).unwrap_or(defaults.enabled),
+            min_size: min_size
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.min_size),
+        }
+    }
+}
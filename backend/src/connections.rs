@@ -0,0 +1,240 @@
+//! Socket table: `/proc/net/{tcp,tcp6,udp,udp6}` joined with `/proc/<pid>/fd`
+//!
+//! The proc tables list sockets by inode only. Each process's open file
+//! descriptors link to `socket:[<inode>]`, which is how a socket is traced back
+//! to its owner. Other users' descriptors are only readable as root, so without
+//! it their sockets are listed with no `pid`.
+//!
+//! `/proc/net/*` only shows the backend's own network namespace. A process in
+//! a container has its own, so its sockets are read from `/proc/<pid>/net/*`
+//! instead; `namespace_inode` tells the two apart.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+
+use crate::error::ApiError;
+use crate::models::TcpConnection;
+use crate::namespaces;
+
+/// The files read, named as their `protocol`
+pub const PROTOCOLS: &[&str] = &["tcp", "tcp6", "udp", "udp6"];
+
+/// Every socket in the four tables. Walking every process's descriptors
+/// touches a lot of files, so this runs on the blocking pool.
+pub async fn list() -> Result<Vec<TcpConnection>, ApiError> {
+    tokio::task::spawn_blocking(list_blocking)
+        .await
+        .map_err(|_| ApiError::Internal)?
+}
+
+/// Sockets `pid` holds open, from the tables of its own network namespace
+pub async fn list_for_process(pid: u32) -> Result<Vec<TcpConnection>, ApiError> {
+    tokio::task::spawn_blocking(move || list_for_process_blocking(pid))
+        .await
+        .map_err(|_| ApiError::Internal)?
+}
+
+/// The `tcp` table of `pid`'s network namespace: `/proc/<pid>/net/tcp` when
+/// that file exists and the process is in a different namespace from PID 1,
+/// such as a container's; otherwise `/proc/net/tcp`. Also the fallback when
+/// either namespace can't be read. The other tables sit next to it.
+pub fn proc_net_path(pid: u32) -> PathBuf {
+    let own_table = PathBuf::from(format!("/proc/{}/net/tcp", pid));
+    let net_ns = |pid: u32| namespaces::read(pid).and_then(|ns| ns.net_ns);
+    match (net_ns(pid), net_ns(1)) {
+        (Some(process), Some(init)) if process != init && own_table.exists() => own_table,
+        _ => PathBuf::from("/proc/net/tcp"),
+    }
+}
+
+/// Every connection in the tables next to `tcp_path`, each paired with its
+/// socket inode
+#[cfg(target_os = "linux")]
+fn read_tables(tcp_path: &std::path::Path) -> Result<Vec<(TcpConnection, u64)>, ApiError> {
+    let mut sockets = Vec::new();
+    for protocol in PROTOCOLS {
+        let path = tcp_path.with_file_name(protocol);
+        // tcp6/udp6 are missing when IPv6 is disabled
+        match std::fs::read_to_string(&path) {
+            Ok(text) => sockets.extend(parse_proc_net(&text, protocol)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                tracing::warn!(error = %err, path = %path.display(), "cannot read /proc/net table");
+                return Err(ApiError::Internal);
+            }
+        }
+    }
+    Ok(sockets)
+}
+
+#[cfg(target_os = "linux")]
+fn list_blocking() -> Result<Vec<TcpConnection>, ApiError> {
+    let sockets = read_tables(std::path::Path::new("/proc/net/tcp"))?;
+    let namespace_inode = namespaces::read(std::process::id()).and_then(|ns| ns.net_ns);
+    let owners = socket_owners();
+    Ok(sockets
+        .into_iter()
+        .map(|(mut connection, inode)| {
+            if let Some((pid, name)) = owners.get(&inode) {
+                connection.pid = Some(*pid);
+                connection.process_name = name.clone();
+            }
+            connection.namespace_inode = namespace_inode;
+            connection
+        })
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+fn list_for_process_blocking(pid: u32) -> Result<Vec<TcpConnection>, ApiError> {
+    let fds = std::fs::read_dir(format!("/proc/{}/fd", pid)).map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => ApiError::ProcessNotFound { pid },
+        std::io::ErrorKind::PermissionDenied => ApiError::PermissionDenied { pid },
+        _ => {
+            tracing::warn!(pid, error = %err, "cannot read process descriptors");
+            ApiError::Internal
+        }
+    })?;
+    let inodes: std::collections::HashSet<u64> = fds
+        .flatten()
+        .filter_map(|fd| socket_inode(&std::fs::read_link(fd.path()).ok()?.to_string_lossy()))
+        .collect();
+    if inodes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tcp_path = proc_net_path(pid);
+    let namespace_pid = if tcp_path.starts_with("/proc/net") { std::process::id() } else { pid };
+    let namespace_inode = namespaces::read(namespace_pid).and_then(|ns| ns.net_ns);
+    let name = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok().map(|comm| comm.trim_end().to_string());
+    Ok(read_tables(&tcp_path)?
+        .into_iter()
+        .filter(|(_, inode)| inodes.contains(inode))
+        .map(|(mut connection, _)| {
+            connection.pid = Some(pid);
+            connection.process_name = name.clone();
+            connection.namespace_inode = namespace_inode;
+            connection
+        })
+        .collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn list_blocking() -> Result<Vec<TcpConnection>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Connection listing" })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn list_for_process_blocking(_pid: u32) -> Result<Vec<TcpConnection>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Connection listing" })
+}
+
+/// Socket inode → (pid, process name) for every descriptor this user may read
+#[cfg(target_os = "linux")]
+fn socket_owners() -> std::collections::HashMap<u64, (u32, Option<String>)> {
+    let mut owners = std::collections::HashMap::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return owners;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let mut name = None;
+        for fd in fds.flatten() {
+            let Some(inode) = std::fs::read_link(fd.path()).ok().and_then(|link| socket_inode(&link.to_string_lossy())) else {
+                continue;
+            };
+            let name = name.get_or_insert_with(|| {
+                std::fs::read_to_string(entry.path().join("comm")).ok().map(|comm| comm.trim_end().to_string())
+            });
+            // A socket shared after fork() goes to whichever process is seen first
+            owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+        }
+    }
+    owners
+}
+
+/// The inode in a descriptor link such as `socket:[12345]`
+pub fn socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+}
+
+/// Parses one `/proc/net` table into connections, each paired with its socket
+/// inode. `pid`, `process_name` and `namespace_inode` are left empty.
+pub fn parse_proc_net(text: &str, protocol: &str) -> Vec<(TcpConnection, u64)> {
+    let is_udp = protocol.starts_with("udp");
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            // sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (local_address, local_port) = parse_hex_address(fields.get(1)?)?;
+            let (remote_address, remote_port) = parse_hex_address(fields.get(2)?)?;
+            let state = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            let connection = TcpConnection {
+                local_address: local_address.to_string(),
+                local_port,
+                remote_address: remote_address.to_string(),
+                remote_port,
+                state: state_name(state, is_udp).to_string(),
+                pid: None,
+                process_name: None,
+                protocol: protocol.to_string(),
+                namespace_inode: None,
+            };
+            Some((connection, inode))
+        })
+        .collect()
+}
+
+/// Decodes `0100007F:0050` (IPv4) or a 32-digit IPv6 address plus port. The
+/// kernel prints the network-order address as 32-bit words in host byte
+/// order, so `0100007F` is 127.0.0.1 on little-endian machines; the port is
+/// plain hex.
+pub fn parse_hex_address(field: &str) -> Option<(IpAddr, u16)> {
+    let (address, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut words = Vec::with_capacity(4);
+    for chunk in address.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+        words.push(word);
+    }
+    let ip = match (address.len(), words.as_slice()) {
+        (8, [word]) => IpAddr::V4(Ipv4Addr::from(word.to_ne_bytes())),
+        (32, words) => {
+            let mut octets = [0u8; 16];
+            for (i, word) in words.iter().enumerate() {
+                octets[i * 4..i * 4 + 4].copy_from_slice(&word.to_ne_bytes());
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+    Some((ip, port))
+}
+
+/// Names from `include/net/tcp_states.h`. UDP reuses the numbering: 1 is a
+/// connected socket and 7 an unconnected one, which `ss` shows as UNCONN.
+fn state_name(state: u8, is_udp: bool) -> &'static str {
+    match state {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 if is_udp => "UNCONN",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        0x0C => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }
+}
@@ -0,0 +1,40 @@
+//! Which container a process runs in, from the cgroup path in `/proc/<pid>/cgroup`.
+//!
+//! Docker, containerd (Kubernetes), CRI-O and Podman all name a container's
+//! cgroup after its 64-hex-digit ID, e.g. `docker-<id>.scope` under systemd
+//! or `/docker/<id>` with the cgroupfs driver. cgroup v1 lists one line per
+//! hierarchy (`12:pids:/docker/<id>`), v2 a single `0::/...` line.
+
+/// Scope prefixes runtimes put before the ID under the systemd cgroup driver.
+/// `libpod-conmon-` is Podman's monitor process, which runs outside the container.
+const RUNTIME_PREFIXES: &[&str] = &["docker-", "cri-containerd-", "crio-", "libpod-", "containerd-"];
+
+/// The container ID in a `/proc/<pid>/cgroup` file, or `None` for processes
+/// on the host. The innermost ID-looking path segment wins, so a container
+/// nested in a pod is attributed to the container rather than the pod.
+pub fn parse_container_id(text: &str) -> Option<String> {
+    text.lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .find_map(|path| path.rsplit('/').find_map(segment_id))
+        .map(String::from)
+}
+
+fn segment_id(segment: &str) -> Option<&str> {
+    if segment.starts_with("libpod-conmon-") {
+        return None;
+    }
+    let id = segment.strip_suffix(".scope").unwrap_or(segment);
+    let id = RUNTIME_PREFIXES.iter().find_map(|prefix| id.strip_prefix(prefix)).unwrap_or(id);
+    (id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())).then_some(id)
+}
+
+/// `None` on other platforms, on the host, or when the process is gone
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<String> {
+    parse_container_id(&std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<String> {
+    None
+}
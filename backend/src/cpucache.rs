@@ -0,0 +1,172 @@
+//! CPU cache sizes: sysfs on Linux, `sysctl` on macOS and
+//! `GetLogicalProcessorInformation` on Windows.
+//!
+//! Sizes are per cache instance as the OS reports them for the first CPU,
+//! the same figures `lscpu -C` shows in its ONE-SIZE column.
+
+use crate::models::CpuCacheInfo;
+
+/// Whether a cache holds data, instructions or both
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CacheType {
+    Data,
+    Instruction,
+    Unified,
+}
+
+/// Parses a sysfs cache `size` such as "32K", "8192K" or "1M" into KiB;
+/// a bare number is bytes
+pub fn parse_cache_size(text: &str) -> Option<u32> {
+    let text = text.trim();
+    let (digits, multiplier_kb) = match text.char_indices().last()? {
+        (i, 'K' | 'k') => (&text[..i], 1),
+        (i, 'M' | 'm') => (&text[..i], 1024),
+        (i, 'G' | 'g') => (&text[..i], 1024 * 1024),
+        _ => return text.parse::<u64>().ok().map(|bytes| (bytes / 1024) as u32),
+    };
+    digits.trim().parse::<u32>().ok()?.checked_mul(multiplier_kb)
+}
+
+/// sysfs `type` values
+pub fn parse_cache_type(text: &str) -> Option<CacheType> {
+    match text.trim() {
+        "Data" => Some(CacheType::Data),
+        "Instruction" => Some(CacheType::Instruction),
+        "Unified" => Some(CacheType::Unified),
+        _ => None,
+    }
+}
+
+/// Sorts `(level, type, KiB)` entries into the four fields; the first entry
+/// for a level wins. L1 is split into data and instruction caches on every
+/// current CPU; a unified L1 is reported as `l1d_kb`.
+pub fn summarize(entries: impl IntoIterator<Item = (u8, CacheType, u32)>) -> CpuCacheInfo {
+    let mut info = CpuCacheInfo::default();
+    for (level, kind, size_kb) in entries {
+        let slot = match (level, kind) {
+            (1, CacheType::Instruction) => &mut info.l1i_kb,
+            (1, _) => &mut info.l1d_kb,
+            (2, _) => &mut info.l2_kb,
+            (3, _) => &mut info.l3_kb,
+            _ => continue,
+        };
+        slot.get_or_insert(size_kb);
+    }
+    info
+}
+
+/// Each `/sys/devices/system/cpu/cpu0/cache/index*` directory describes one
+/// cache by `level`, `type` and `size`
+#[cfg(target_os = "linux")]
+pub fn read() -> CpuCacheInfo {
+    let Ok(dirs) = std::fs::read_dir("/sys/devices/system/cpu/cpu0/cache") else {
+        return CpuCacheInfo::default();
+    };
+    let mut dirs: Vec<_> = dirs
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("index")))
+        .collect();
+    dirs.sort();
+    summarize(dirs.iter().filter_map(|dir| {
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).ok();
+        Some((
+            read("level")?.trim().parse().ok()?,
+            parse_cache_type(&read("type")?)?,
+            parse_cache_size(&read("size")?)?,
+        ))
+    }))
+}
+
+/// Sizes in bytes from `hw.l1dcachesize` and friends; Apple Silicon reports
+/// the performance cores' caches there
+#[cfg(target_os = "macos")]
+pub fn read() -> CpuCacheInfo {
+    fn sysctl_bytes(name: &std::ffi::CStr) -> Option<u32> {
+        let mut value: u64 = 0;
+        let mut len = std::mem::size_of::<u64>();
+        // SAFETY: `value` is a writable u64 and `len` holds its size; the
+        // kernel writes at most `len` bytes and updates `len`
+        let rc = unsafe {
+            libc::sysctlbyname(name.as_ptr(), &mut value as *mut u64 as *mut libc::c_void, &mut len, std::ptr::null_mut(), 0)
+        };
+        // Some keys are 32-bit; the low bytes come first on every Mac
+        (rc == 0 && value > 0).then(|| (value / 1024) as u32)
+    }
+    CpuCacheInfo {
+        l1d_kb: sysctl_bytes(c"hw.l1dcachesize"),
+        l1i_kb: sysctl_bytes(c"hw.l1icachesize"),
+        l2_kb: sysctl_bytes(c"hw.l2cachesize"),
+        l3_kb: sysctl_bytes(c"hw.l3cachesize"),
+    }
+}
+
+#[cfg(windows)]
+pub fn read() -> CpuCacheInfo {
+    windows::read()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn read() -> CpuCacheInfo {
+    CpuCacheInfo::default()
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{summarize, CacheType};
+    use crate::models::CpuCacheInfo;
+
+    const RELATION_CACHE: u32 = 2;
+
+    /// `SYSTEM_LOGICAL_PROCESSOR_INFORMATION`; the union is 16 bytes
+    #[repr(C)]
+    struct ProcessorInformation {
+        processor_mask: usize,
+        relationship: u32,
+        union: [u64; 2],
+    }
+
+    /// `CACHE_DESCRIPTOR`, the union member for `RelationCache`
+    #[repr(C)]
+    struct CacheDescriptor {
+        level: u8,
+        associativity: u8,
+        line_size: u16,
+        size: u32,
+        kind: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetLogicalProcessorInformation(buffer: *mut ProcessorInformation, length: *mut u32) -> i32;
+    }
+
+    pub fn read() -> CpuCacheInfo {
+        let mut length = 0u32;
+        // SAFETY: a null buffer with length 0 only asks for the size needed
+        unsafe { GetLogicalProcessorInformation(std::ptr::null_mut(), &mut length) };
+        let count = length as usize / std::mem::size_of::<ProcessorInformation>();
+        let mut buffer: Vec<ProcessorInformation> = Vec::with_capacity(count);
+        // SAFETY: the buffer has room for `length` bytes, and the call
+        // initialises the entries it reports in `length`
+        let entries = unsafe {
+            if GetLogicalProcessorInformation(buffer.as_mut_ptr(), &mut length) == 0 {
+                return CpuCacheInfo::default();
+            }
+            buffer.set_len(length as usize / std::mem::size_of::<ProcessorInformation>());
+            buffer
+        };
+        summarize(entries.iter().filter(|entry| entry.relationship == RELATION_CACHE).filter_map(|entry| {
+            // SAFETY: for RelationCache the union holds a CACHE_DESCRIPTOR,
+            // which fits in its 16 bytes
+            let cache = unsafe { std::ptr::read_unaligned(entry.union.as_ptr() as *const CacheDescriptor) };
+            let kind = match cache.kind {
+                0 => CacheType::Unified,
+                1 => CacheType::Instruction,
+                2 => CacheType::Data,
+                _ => return None,
+            };
+            Some((cache.level, kind, cache.size / 1024))
+        }))
+    }
+}
@@ -0,0 +1,45 @@
+//! Working directory lookup for when sysinfo doesn't report one
+
+/// The `/proc/<pid>/cwd` link. The kernel appends " (deleted)" when the
+/// directory has been removed.
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<String> {
+    let path = std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()?;
+    Some(path.display().to_string())
+}
+
+/// `proc_pidinfo(PROC_PIDVNODEPATHINFO)`, the call behind `lsof -d cwd`
+#[cfg(target_os = "macos")]
+pub fn read(pid: u32) -> Option<String> {
+    // SAFETY: proc_vnodepathinfo is plain old data, valid when zeroed
+    let mut info: libc::proc_vnodepathinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_vnodepathinfo>() as libc::c_int;
+    // SAFETY: `info` is a writable buffer of exactly `size` bytes
+    let written = unsafe {
+        libc::proc_pidinfo(
+            pid as libc::c_int,
+            libc::PROC_PIDVNODEPATHINFO,
+            0,
+            &mut info as *mut libc::proc_vnodepathinfo as *mut libc::c_void,
+            size,
+        )
+    };
+    if written != size {
+        return None;
+    }
+    let path = &info.pvi_cdir.vip_path;
+    // SAFETY: `vip_path` is a MAXPATHLEN byte buffer split into rows for old rustc
+    let bytes = unsafe { std::slice::from_raw_parts(path.as_ptr() as *const u8, std::mem::size_of_val(path)) };
+    let path = std::ffi::CStr::from_bytes_until_nul(bytes).ok()?.to_string_lossy();
+    (!path.is_empty()).then(|| path.into_owned())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn read(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Whether `cwd` is still a directory, as far as the backend can see
+pub fn exists(cwd: &str) -> bool {
+    std::path::Path::new(cwd).is_dir()
+}
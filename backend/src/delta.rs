@@ -0,0 +1,144 @@
+//! `GET /api/stats?mode=delta`: cumulative counters as the change since the
+//! same client's previous request
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::models::{PressureStall, SystemStats};
+use crate::utils::format_bytes;
+
+/// Identifies a polling client across delta requests; generated when absent
+pub static CLIENT_ID_HEADER: HeaderName = HeaderName::from_static("x-client-id");
+
+/// Milliseconds between this delta response and the client's previous one
+pub static DELTA_INTERVAL_HEADER: HeaderName = HeaderName::from_static("x-delta-interval-ms");
+
+/// Clients that haven't polled for this long start over from zero
+pub const CLIENT_STATE_TTL: Duration = Duration::from_secs(60);
+
+/// Longest `X-Client-ID` accepted; longer or non-ASCII IDs are replaced
+const MAX_CLIENT_ID_LEN: usize = 128;
+
+/// Clients tracked at once by [`DeltaTracker`]; stale ones are dropped first
+pub const DELTA_MAX_CLIENTS: usize = 10_000;
+
+/// Last stats each client was sent
+#[derive(Clone)]
+pub struct DeltaTracker {
+    clients: Arc<DashMap<String, (Instant, SystemStats)>>,
+    max_clients: usize,
+}
+
+impl Default for DeltaTracker {
+    fn default() -> Self {
+        Self {
+            clients: Arc::new(DashMap::new()),
+            max_clients: DELTA_MAX_CLIENTS,
+        }
+    }
+}
+
+impl DeltaTracker {
+    /// Overrides [`DELTA_MAX_CLIENTS`]
+    pub fn max_clients(mut self, max_clients: usize) -> Self {
+        self.max_clients = max_clients.max(1);
+        self
+    }
+
+    /// `current` with counters replaced by the change since `client_id`'s
+    /// previous call, plus the time elapsed. A client's first call, and one
+    /// after [`CLIENT_STATE_TTL`] of silence, returns zeros.
+    pub fn delta(&self, client_id: &str, current: &SystemStats) -> (SystemStats, Option<Duration>) {
+        let now = Instant::now();
+        if !self.clients.contains_key(client_id) && self.clients.len() >= self.max_clients {
+            self.evict(now);
+        }
+
+        let previous = self.clients.insert(client_id.to_string(), (now, current.clone()));
+        match previous.filter(|(seen, _)| now.duration_since(*seen) < CLIENT_STATE_TTL) {
+            Some((seen, previous)) => (diff(&previous, current), Some(now.duration_since(seen))),
+            None => (diff(current, current), None),
+        }
+    }
+
+    /// Number of clients currently holding state
+    pub fn tracked_clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Drops clients past [`CLIENT_STATE_TTL`]; if none are, drops the least
+    /// recently seen
+    fn evict(&self, now: Instant) {
+        self.clients.retain(|_, (seen, _)| now.duration_since(*seen) < CLIENT_STATE_TTL);
+        if self.clients.len() >= self.max_clients {
+            let oldest = self.clients.iter().min_by_key(|entry| entry.value().0).map(|entry| entry.key().clone());
+            if let Some(client) = oldest {
+                self.clients.remove(&client);
+            }
+        }
+    }
+}
+
+/// The client's `X-Client-ID` if usable, otherwise a fresh random one
+pub fn client_id(headers: &HeaderMap) -> String {
+    headers
+        .get(&CLIENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_CLIENT_ID_LEN)
+        .map(String::from)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string())
+}
+
+/// Headers sent with every delta response
+pub fn response_headers(client_id: &str, interval: Option<Duration>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(client_id) {
+        headers.insert(CLIENT_ID_HEADER.clone(), value);
+    }
+    let interval_ms = interval.map(|d| d.as_millis() as u64).unwrap_or(0);
+    headers.insert(DELTA_INTERVAL_HEADER.clone(), HeaderValue::from(interval_ms));
+    headers
+}
+
+/// `current` with its cumulative counters reduced to the change since
+/// `previous`: network bytes, overall and per interface, and the memory
+/// pressure stall totals. Counters that went backwards (interface reset) report
+/// 0, as do interfaces and stall totals that weren't present before; gauges
+/// such as CPU, memory and the PSI averages are unchanged.
+pub fn diff(previous: &SystemStats, current: &SystemStats) -> SystemStats {
+    let mut stats = current.clone();
+    let network = &mut stats.network;
+    network.bytes_sent = current.network.bytes_sent.saturating_sub(previous.network.bytes_sent);
+    network.bytes_recv = current.network.bytes_recv.saturating_sub(previous.network.bytes_recv);
+    network.bytes_sent_formatted = format_bytes(network.bytes_sent);
+    network.bytes_recv_formatted = format_bytes(network.bytes_recv);
+
+    for interface in &mut network.interfaces {
+        match previous.network.interfaces.iter().find(|p| p.name == interface.name) {
+            Some(before) => {
+                interface.bytes_sent = interface.bytes_sent.saturating_sub(before.bytes_sent);
+                interface.bytes_recv = interface.bytes_recv.saturating_sub(before.bytes_recv);
+            }
+            None => {
+                interface.bytes_sent = 0;
+                interface.bytes_recv = 0;
+            }
+        }
+    }
+
+    if let Some(pressure) = &mut stats.memory_pressure {
+        let before = previous.memory_pressure.as_ref();
+        diff_stall(&mut pressure.some, before.map(|p| &p.some));
+        if let Some(full) = &mut pressure.full {
+            diff_stall(full, before.and_then(|p| p.full.as_ref()));
+        }
+    }
+    stats
+}
+
+fn diff_stall(stall: &mut PressureStall, before: Option<&PressureStall>) {
+    stall.total_us = before.map_or(0, |before| stall.total_us.saturating_sub(before.total_us));
+}
@@ -0,0 +1,204 @@
+//! Per-disk capacity, storage type (SSD, HDD, NVMe), removability and I/O
+//! queue settings, and switching a disk's I/O scheduler
+
+use sysinfo::{Disk, DiskKind, Disks};
+
+use crate::error::ApiError;
+use crate::models::{DiskInfo, DiskScheduler};
+
+/// Storage type and removability of the block device behind a mount
+#[derive(Debug, PartialEq)]
+pub struct BlockInfo {
+    /// "SSD", "HDD", "NVMe" or "Unknown"
+    pub disk_type: &'static str,
+    pub is_removable: bool,
+}
+
+/// I/O queue settings of a whole disk, from `/sys/block/<device>/queue`
+#[derive(Debug, Default, PartialEq)]
+pub struct QueueSettings {
+    pub scheduler: Option<String>,
+    pub nr_requests: Option<u32>,
+    pub queue_depth: Option<u32>,
+    pub read_ahead_kb: Option<u32>,
+}
+
+/// Every disk sysinfo lists, sorted by mount point
+pub fn list(disks: &Disks) -> Vec<DiskInfo> {
+    let mut list: Vec<DiskInfo> = disks
+        .iter()
+        .map(|disk| {
+            let (block, queue) = block_info(disk);
+            DiskInfo {
+                name: disk.name().to_string_lossy().to_string(),
+                mount_point: disk.mount_point().display().to_string(),
+                file_system: disk.file_system().to_string_lossy().to_string(),
+                total: disk.total_space(),
+                available: disk.available_space(),
+                disk_type: block.disk_type.to_string(),
+                is_removable: block.is_removable,
+                scheduler: queue.scheduler,
+                nr_requests: queue.nr_requests,
+                queue_depth: queue.queue_depth,
+                read_ahead_kb: queue.read_ahead_kb,
+            }
+        })
+        .collect();
+    list.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    list
+}
+
+/// sysinfo's own classification; on macOS it comes from IOKit's
+/// `kIOPropertyMediumTypeKey`
+pub fn kind_name(kind: DiskKind) -> &'static str {
+    match kind {
+        DiskKind::SSD => "SSD",
+        DiskKind::HDD => "HDD",
+        DiskKind::Unknown(_) => "Unknown",
+    }
+}
+
+/// Builds a [`BlockInfo`] for the whole-disk `device` (e.g. `sda`, `nvme0n1`)
+/// from the files in `/sys/block/<device>`; `read` returns a file's contents,
+/// or `None` when it is missing. NVMe drives are non-rotational too, so the
+/// device name is what tells them apart from SATA SSDs.
+pub fn parse_sysfs_block(device: &str, read: impl Fn(&str) -> Option<String>) -> BlockInfo {
+    let value = |file: &str| read(file).map(|s| s.trim().to_string());
+    let disk_type = match value("queue/rotational").as_deref() {
+        Some("1") => "HDD",
+        Some("0") if device.starts_with("nvme") => "NVMe",
+        Some("0") => "SSD",
+        _ => "Unknown",
+    };
+    BlockInfo {
+        disk_type,
+        is_removable: value("removable").as_deref() == Some("1"),
+    }
+}
+
+/// Builds [`QueueSettings`] from the same files as [`parse_sysfs_block`]
+pub fn parse_sysfs_queue(read: impl Fn(&str) -> Option<String>) -> QueueSettings {
+    let number = |file: &str| read(file).and_then(|v| v.trim().parse().ok());
+    QueueSettings {
+        scheduler: read("queue/scheduler").and_then(|v| parse_scheduler(&v).0),
+        nr_requests: number("queue/nr_requests"),
+        queue_depth: number("queue/iosched/quantum"),
+        read_ahead_kb: number("queue/read_ahead_kb"),
+    }
+}
+
+/// Splits `queue/scheduler` (`mq-deadline kyber [bfq] none`) into the active
+/// scheduler, the bracketed one, and every available one. A disk offering
+/// only one may print it without brackets.
+pub fn parse_scheduler(text: &str) -> (Option<String>, Vec<String>) {
+    let mut active = None;
+    let available: Vec<String> = text
+        .split_whitespace()
+        .map(|name| match name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+            Some(name) => {
+                active = Some(name.to_string());
+                name.to_string()
+            }
+            None => name.to_string(),
+        })
+        .collect();
+    if active.is_none() && available.len() == 1 {
+        active = available.first().cloned();
+    }
+    (active, available)
+}
+
+/// Switches the whole disk `device` (e.g. `sda`) to `scheduler` and returns
+/// the scheduler as the kernel now reports it
+#[cfg(target_os = "linux")]
+pub async fn set_scheduler(device: String, scheduler: String) -> Result<DiskScheduler, ApiError> {
+    // Same naming rules as the SMART endpoint
+    crate::smart::device_path(&device)?;
+    let path = std::path::Path::new("/sys/block").join(&device).join("queue/scheduler");
+    tokio::task::spawn_blocking(move || {
+        let current = std::fs::read_to_string(&path).map_err(|err| match err.kind() {
+            // Partitions and unknown names have no entry under /sys/block
+            std::io::ErrorKind::NotFound => ApiError::DeviceNotFound { device: device.clone() },
+            _ => {
+                tracing::warn!(error = %err, path = %path.display(), "cannot read I/O scheduler");
+                ApiError::Internal
+            }
+        })?;
+        let (_, available) = parse_scheduler(&current);
+        if !available.contains(&scheduler) {
+            return Err(ApiError::InvalidScheduler { device, scheduler, available });
+        }
+        std::fs::write(&path, scheduler.as_bytes()).map_err(|err| match err.kind() {
+            std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem => {
+                ApiError::SchedulerPermissionDenied { device: device.clone() }
+            }
+            _ => ApiError::InvalidScheduler {
+                device: device.clone(),
+                scheduler: scheduler.clone(),
+                available: available.clone(),
+            },
+        })?;
+        let (active, available) = std::fs::read_to_string(&path).map(|v| parse_scheduler(&v)).unwrap_or((None, available));
+        Ok(DiskScheduler { device, scheduler: active.unwrap_or(scheduler), available })
+    })
+    .await
+    .map_err(|_| ApiError::Internal)?
+}
+
+#[cfg(not(target_os = "linux"))]
+pub async fn set_scheduler(device: String, _scheduler: String) -> Result<DiskScheduler, ApiError> {
+    crate::smart::device_path(&device)?;
+    Err(ApiError::NotImplemented { feature: "Changing the I/O scheduler" })
+}
+
+/// Resolves the mounted device to its whole disk (`/dev/nvme0n1p2` to
+/// `nvme0n1`, `/dev/mapper/root` to `dm-0`) and reads its sysfs entry.
+/// Mounts without a block device (overlay, tmpfs) keep sysinfo's answer.
+#[cfg(target_os = "linux")]
+fn block_info(disk: &Disk) -> (BlockInfo, QueueSettings) {
+    let fallback = || {
+        let block = BlockInfo {
+            disk_type: kind_name(disk.kind()),
+            is_removable: disk.is_removable(),
+        };
+        (block, QueueSettings::default())
+    };
+    let Some(device) = whole_disk(std::path::Path::new(disk.name())) else {
+        return fallback();
+    };
+    let dir = std::path::Path::new("/sys/block").join(&device);
+    if !dir.exists() {
+        return fallback();
+    }
+    let read = |file: &str| std::fs::read_to_string(dir.join(file)).ok();
+    let mut info = parse_sysfs_block(&device, read);
+    // sysinfo also counts devices attached over USB, which sysfs doesn't flag
+    info.is_removable |= disk.is_removable();
+    (info, parse_sysfs_queue(read))
+}
+
+/// `/sys/class/block/<partition>` links into its disk's directory, so the
+/// parent directory's name is the whole disk
+#[cfg(target_os = "linux")]
+fn whole_disk(device: &std::path::Path) -> Option<String> {
+    let device = std::fs::canonicalize(device).ok()?;
+    let name = device.strip_prefix("/dev").ok()?.to_str()?;
+    let class = std::path::Path::new("/sys/class/block").join(name);
+    if !class.join("partition").exists() {
+        return Some(name.to_string());
+    }
+    let parent = std::fs::canonicalize(&class).ok()?;
+    Some(parent.parent()?.file_name()?.to_str()?.to_string())
+}
+
+/// sysinfo queries IOKit's medium type on macOS; NVMe isn't told apart
+/// from other SSDs there or on other platforms, and queue settings are
+/// Linux-only
+#[cfg(not(target_os = "linux"))]
+fn block_info(disk: &Disk) -> (BlockInfo, QueueSettings) {
+    let block = BlockInfo {
+        disk_type: kind_name(disk.kind()),
+        is_removable: disk.is_removable(),
+    };
+    (block, QueueSettings::default())
+}
@@ -0,0 +1,238 @@
+//! Container names, images and states from the Docker Engine API.
+//!
+//! Requests go over the daemon's Unix socket (a named pipe on Windows) as
+//! HTTP/1.0, so the daemon closes the connection after each reply and never
+//! sends a chunked body. [`crate::containers`] finds which container each
+//! process is in; this only adds what the cgroup path can't tell.
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::error::ApiError;
+use crate::models::ContainerGroup;
+
+/// Where Docker Desktop and the Linux packages put the API
+#[cfg(windows)]
+pub const DEFAULT_SOCKET: &str = r"\\.\pipe\docker_engine";
+#[cfg(not(windows))]
+pub const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+/// Listing and inspecting answer quickly unless the daemon is wedged
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `docker stop` waits 10 seconds before SIGKILL, so the reply can take that long
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What Docker knows about one running container
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DockerContainer {
+    /// Full 64-hex-digit ID, as in the cgroup path
+    pub id: String,
+    /// Name without Docker's leading `/`
+    pub name: String,
+    pub image: String,
+    /// "running", "paused", "restarting", ...
+    pub state: String,
+}
+
+/// A row of `GET /containers/json`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListRow {
+    id: String,
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    state: String,
+}
+
+/// The part of `GET /containers/<id>/json` this module reads
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Inspect {
+    state: InspectState,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InspectState {
+    #[serde(default)]
+    started_at: String,
+}
+
+/// Parses the body of `GET /containers/json`
+pub fn parse_container_list(body: &[u8]) -> Option<Vec<DockerContainer>> {
+    let rows: Vec<ListRow> = serde_json::from_slice(body).ok()?;
+    Some(
+        rows.into_iter()
+            .map(|row| DockerContainer {
+                id: row.id,
+                name: row.names.first().map(|name| name.trim_start_matches('/').to_string()).unwrap_or_default(),
+                image: row.image,
+                state: row.state,
+            })
+            .collect(),
+    )
+}
+
+/// `State.StartedAt` from `GET /containers/<id>/json`, trimmed to whole
+/// seconds like the other timestamps in the API. Docker reports
+/// `0001-01-01T00:00:00Z` for a container that never started.
+pub fn parse_started_at(body: &[u8]) -> Option<String> {
+    let inspect: Inspect = serde_json::from_slice(body).ok()?;
+    let started_at = inspect.state.started_at;
+    if started_at.is_empty() || started_at.starts_with("0001-") {
+        return None;
+    }
+    Some(match started_at.split_once('.') {
+        Some((seconds, _)) => format!("{}Z", seconds),
+        None => started_at,
+    })
+}
+
+/// Status code and body of an HTTP/1.x response
+pub fn parse_response(raw: &[u8]) -> Option<(u16, &[u8])> {
+    let header_end = raw.windows(4).position(|window| window == b"\r\n\r\n")?;
+    let status_line = raw[..header_end].split(|&b| b == b'\r').next()?;
+    let status = std::str::from_utf8(status_line).ok()?.split_whitespace().nth(1)?.parse().ok()?;
+    Some((status, &raw[header_end + 4..]))
+}
+
+/// Copies Docker's name, image, state and start time onto the cgroup-derived
+/// groups with the same ID; groups Docker doesn't know (containerd, CRI-O,
+/// Podman) keep their process name
+pub fn apply(groups: &mut [ContainerGroup], containers: &[DockerContainer]) {
+    for group in groups {
+        let Some(container) = containers.iter().find(|c| c.id == group.container_id) else {
+            continue;
+        };
+        if !container.name.is_empty() {
+            group.name = container.name.clone();
+        }
+        group.image = Some(container.image.clone());
+        group.state = Some(container.state.clone());
+    }
+}
+
+/// Docker accepts a full or abbreviated ID or a name; anything else could
+/// reach another API path
+pub fn validate_id(id: &str) -> Result<(), ApiError> {
+    let valid = !id.is_empty()
+        && id.len() <= 128
+        && id.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidContainerId { id: id.to_string() })
+    }
+}
+
+/// Client for one daemon endpoint; cheap to clone
+#[derive(Clone, Debug)]
+pub struct DockerClient {
+    endpoint: Arc<PathBuf>,
+}
+
+impl DockerClient {
+    pub fn new(endpoint: impl Into<PathBuf>) -> Self {
+        Self { endpoint: Arc::new(endpoint.into()) }
+    }
+
+    pub fn endpoint(&self) -> &Path {
+        &self.endpoint
+    }
+
+    /// Adds Docker's details to `groups`; `false` when the daemon couldn't
+    /// be reached, leaving the groups as they were
+    pub async fn enrich(&self, groups: &mut [ContainerGroup]) -> bool {
+        let containers = match self.get("/containers/json", REQUEST_TIMEOUT).await {
+            Ok((200, body)) => parse_container_list(&body),
+            Ok((status, _)) => {
+                tracing::warn!(status, "Docker refused to list containers");
+                None
+            }
+            Err(err) => {
+                tracing::debug!(endpoint = %self.endpoint.display(), error = %err, "Docker is unreachable");
+                None
+            }
+        };
+        let Some(containers) = containers else {
+            return false;
+        };
+        apply(groups, &containers);
+        let started = futures::future::join_all(groups.iter().filter(|group| group.image.is_some()).map(|group| async move {
+            let path = format!("/containers/{}/json", group.container_id);
+            match self.get(&path, REQUEST_TIMEOUT).await {
+                Ok((200, body)) => parse_started_at(&body),
+                _ => None,
+            }
+        }))
+        .await;
+        for (group, started_at) in groups.iter_mut().filter(|group| group.image.is_some()).zip(started) {
+            group.started_at = started_at;
+        }
+        true
+    }
+
+    /// Stops a container the way `docker stop` does; `Ok(false)` when it was
+    /// already stopped
+    pub async fn stop(&self, id: &str) -> Result<bool, ApiError> {
+        validate_id(id)?;
+        let (status, body) = self
+            .request("POST", &format!("/containers/{}/stop", id), STOP_TIMEOUT)
+            .await
+            .map_err(|err| {
+                tracing::warn!(endpoint = %self.endpoint.display(), error = %err, "Docker is unreachable");
+                ApiError::DockerUnavailable
+            })?;
+        match status {
+            204 => Ok(true),
+            304 => Ok(false),
+            404 => Err(ApiError::ContainerNotFound { id: id.to_string() }),
+            _ => {
+                tracing::warn!(id, status, body = %String::from_utf8_lossy(&body), "Docker failed to stop container");
+                Err(ApiError::Internal)
+            }
+        }
+    }
+
+    async fn get(&self, path: &str, timeout: Duration) -> std::io::Result<(u16, Vec<u8>)> {
+        self.request("GET", path, timeout).await
+    }
+
+    async fn request(&self, method: &str, path: &str, timeout: Duration) -> std::io::Result<(u16, Vec<u8>)> {
+        let request = format!("{} {} HTTP/1.0\r\nHost: docker\r\nContent-Length: 0\r\n\r\n", method, path);
+        let raw = tokio::time::timeout(timeout, async {
+            let stream = connect(&self.endpoint).await?;
+            exchange(stream, request.as_bytes()).await
+        })
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "Docker did not answer in time"))??;
+        let (status, body) = parse_response(&raw)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "malformed HTTP response"))?;
+        Ok((status, body.to_vec()))
+    }
+}
+
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, request: &[u8]) -> std::io::Result<Vec<u8>> {
+    stream.write_all(request).await?;
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).await?;
+    Ok(raw)
+}
+
+#[cfg(unix)]
+async fn connect(endpoint: &Path) -> std::io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(endpoint).await
+}
+
+#[cfg(windows)]
+async fn connect(endpoint: &Path) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(endpoint)
+}
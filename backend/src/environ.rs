@@ -0,0 +1,70 @@
+//! A process's environment variables, with secret-looking values redacted.
+//!
+//! Environments routinely hold credentials, so `GET /api/process/:pid/environ`
+//! always requires the bearer token and masks keys matching `redact_env`.
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::error::ApiError;
+
+/// `(key, value)` pairs in the order the OS lists them
+pub type EnvVars = Vec<(String, String)>;
+
+/// What redacted values are replaced with
+pub const REDACTED: &str = "***";
+
+pub const DEFAULT_REDACT_PATTERNS: &[&str] = &["*TOKEN*", "*SECRET*", "*PASSWORD*"];
+
+/// Glob patterns over variable names, matched case-insensitively
+#[derive(Clone, Debug)]
+pub struct Redactor(GlobSet);
+
+impl Redactor {
+    pub fn new(patterns: &[String]) -> Result<Self, globset::Error> {
+        let mut set = GlobSetBuilder::new();
+        for pattern in patterns {
+            set.add(GlobBuilder::new(pattern).case_insensitive(true).build()?);
+        }
+        Ok(Self(set.build()?))
+    }
+
+    pub fn matches(&self, key: &str) -> bool {
+        self.0.is_match(key)
+    }
+
+    /// Replaces the value of every matching variable with [`REDACTED`]
+    pub fn apply(&self, vars: &mut [(String, String)]) {
+        for (key, value) in vars {
+            if self.matches(key) {
+                *value = REDACTED.to_string();
+            }
+        }
+    }
+}
+
+/// Splits the NUL-separated `KEY=value` entries of `/proc/<pid>/environ`.
+/// Values may contain `=`; entries without one are skipped.
+pub fn parse_environ(bytes: &[u8]) -> EnvVars {
+    bytes
+        .split(|b| *b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (key, value) = entry.split_once('=')?;
+            (!key.is_empty()).then(|| (key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Reads `/proc/<pid>/environ`, which only the owner (or root) may open
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Result<EnvVars, ApiError> {
+    match std::fs::read(format!("/proc/{}/environ", pid)) {
+        Ok(bytes) => Ok(parse_environ(&bytes)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(ApiError::ProcessNotFound { pid }),
+        Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => Err(ApiError::PermissionDenied { pid }),
+        Err(err) => {
+            tracing::warn!(pid, error = %err, "cannot read process environment");
+            Err(ApiError::Internal)
+        }
+    }
+}
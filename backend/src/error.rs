@@ -0,0 +1,308 @@
+//! API error type and its JSON envelope
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::middleware::RequestId;
+use crate::msgpack::Json;
+
+/// Every handler error is rendered as `{"error": {"code", "message", "pid"?, "request_id"?}}`
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("Process {pid} not found")]
+    ProcessNotFound { pid: u32 },
+    #[error("Permission denied for process {pid}")]
+    PermissionDenied { pid: u32 },
+    #[error("Process {pid} is protected and cannot be terminated")]
+    ProcessProtected { pid: u32 },
+    #[error("No running process is in process group {pgid}")]
+    ProcessGroupNotFound { pgid: u32 },
+    #[error("Permission denied signalling process group {pgid}")]
+    ProcessGroupPermissionDenied { pgid: u32 },
+    #[error("Process {pid} is not in killable_only_names")]
+    KillNotAllowed { pid: u32 },
+    #[error("Process {pid} is the backend itself or its parent and cannot be terminated")]
+    CannotKillSelf { pid: u32 },
+    #[error("App cannot be closed: {reason}")]
+    AppNotCloseable { pid: u32, reason: String },
+    #[error("None of the requested processes could be terminated")]
+    NoProcessesTerminated,
+    #[error("Missing bearer token in Authorization header")]
+    MissingToken,
+    #[error("Invalid bearer token")]
+    InvalidToken,
+    #[error("Server is running in read-only mode")]
+    ReadOnly,
+    #[error("The API token is only handed to clients on this machine")]
+    NotLoopback,
+    #[error("The API token is only handed to pages from an origin on the CORS allowlist")]
+    OriginNotAllowed,
+    #[error("Command '{command}' is not in allowed_commands")]
+    CommandNotAllowed { command: String },
+    #[error("Environment variable {name} can't be set: it changes which executable or libraries are loaded")]
+    EnvNotAllowed { name: String },
+    #[error("Process {pid} can't be renamed: Linux only lets a process change its own name, even for root")]
+    RenameNotPermitted { pid: u32 },
+    #[error("Process {pid}'s executable or command line can't be read, so it can't be relaunched")]
+    CannotRestartUnknownCommand { pid: u32 },
+    #[error("Process {pid}'s output can't be streamed: it wasn't started by this server and its stdout and stderr aren't files")]
+    OutputNotAvailable { pid: u32 },
+    #[error("Failed to start '{command}': {source}")]
+    SpawnFailed {
+        command: String,
+        source: std::io::Error,
+    },
+    #[error("Permission denied starting '{command}': {source}")]
+    SpawnPermissionDenied {
+        command: String,
+        source: std::io::Error,
+    },
+    #[error("Executable '{command}' is not in launch_executables")]
+    ExecutableNotAllowed { command: String },
+    #[error("Service {name} not found")]
+    ServiceNotFound { name: String },
+    #[error("Permission denied for service {name}; controlling services needs administrator rights")]
+    ServicePermissionDenied { name: String },
+    #[error("Service {name} has running dependent services; stop those first")]
+    ServiceHasDependents { name: String },
+    #[error("'{name}' is not a valid service name")]
+    InvalidServiceName { name: String },
+    #[error("Disk {device} not found")]
+    DeviceNotFound { device: String },
+    #[error("Permission denied for disk {device}; SMART data needs root")]
+    DevicePermissionDenied { device: String },
+    #[error("'{device}' is not a valid disk name")]
+    InvalidDeviceName { device: String },
+    #[error("'{scheduler}' is not an I/O scheduler of disk {device}; available: {}", available.join(", "))]
+    InvalidScheduler { device: String, scheduler: String, available: Vec<String> },
+    #[error("Permission denied for disk {device}; changing the I/O scheduler needs root")]
+    SchedulerPermissionDenied { device: String },
+    #[error("Container {id} not found")]
+    ContainerNotFound { id: String },
+    #[error("'{id}' is not a valid container ID or name")]
+    InvalidContainerId { id: String },
+    #[error("Docker daemon is not reachable")]
+    DockerUnavailable,
+    #[error("Kernel parameter {key} not found")]
+    SysctlNotFound { key: String },
+    #[error("Permission denied for kernel parameter {key}; writing needs root")]
+    SysctlPermissionDenied { key: String },
+    #[error("'{key}' is not a valid kernel parameter name")]
+    InvalidSysctlKey { key: String },
+    #[error("The kernel rejected the value for {key}")]
+    InvalidSysctlValue { key: String },
+    #[error("{feature} is not available on this system")]
+    NotImplemented { feature: &'static str },
+    #[error("Invalid request body: {reason}")]
+    InvalidBody { reason: String },
+    #[error("Invalid request body: {}", FieldError::join(errors))]
+    InvalidFields { errors: Vec<FieldError> },
+    #[error("Alert rule {id} not found")]
+    AlertRuleNotFound { id: u64 },
+    #[error("Unknown field {}; valid fields: {}", unknown.join(", "), valid.join(", "))]
+    UnknownFields { unknown: Vec<String>, valid: Vec<String> },
+    #[error("Too many requests; retry in {retry_after_secs}s")]
+    RateLimited { retry_after_secs: u64 },
+    #[error("Server is shutting down")]
+    ShuttingDown,
+    #[error("Internal server error")]
+    Internal,
+}
+
+/// Body of every non-2xx response
+#[derive(Serialize, ToSchema)]
+pub struct ErrorEnvelope {
+    error: ErrorBody,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Stable machine-readable code, e.g. "process_not_found"
+    code: &'static str,
+    /// Human-readable description
+    message: String,
+    /// The process the error refers to, when there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+    /// What's wrong with each rejected field, for `invalid_fields`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<Vec<FieldError>>,
+    /// The request's `X-Request-ID`, to find it in the backend's logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+/// One rejected field of a request body
+#[derive(Serialize, ToSchema, Clone, Debug, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into() }
+    }
+
+    /// `field: message` pairs separated by `; `
+    pub fn join(errors: &[FieldError]) -> String {
+        errors.iter().map(|e| format!("{}: {}", e.field, e.message)).collect::<Vec<_>>().join("; ")
+    }
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::ProcessNotFound { .. }
+            | ApiError::ProcessGroupNotFound { .. }
+            | ApiError::ServiceNotFound { .. }
+            | ApiError::DeviceNotFound { .. }
+            | ApiError::ContainerNotFound { .. }
+            | ApiError::SysctlNotFound { .. }
+            | ApiError::AlertRuleNotFound { .. } => StatusCode::NOT_FOUND,
+            ApiError::SpawnFailed { .. }
+            | ApiError::EnvNotAllowed { .. }
+            | ApiError::InvalidServiceName { .. }
+            | ApiError::InvalidDeviceName { .. }
+            | ApiError::InvalidScheduler { .. }
+            | ApiError::InvalidContainerId { .. }
+            | ApiError::InvalidSysctlKey { .. }
+            | ApiError::InvalidSysctlValue { .. }
+            | ApiError::InvalidBody { .. }
+            | ApiError::InvalidFields { .. }
+            | ApiError::UnknownFields { .. } => StatusCode::BAD_REQUEST,
+            ApiError::MissingToken | ApiError::InvalidToken => StatusCode::UNAUTHORIZED,
+            ApiError::PermissionDenied { .. }
+            | ApiError::ProcessGroupPermissionDenied { .. }
+            | ApiError::ProcessProtected { .. }
+            | ApiError::CannotKillSelf { .. }
+            | ApiError::KillNotAllowed { .. }
+            | ApiError::AppNotCloseable { .. }
+            | ApiError::NoProcessesTerminated
+            | ApiError::ReadOnly
+            | ApiError::NotLoopback
+            | ApiError::OriginNotAllowed
+            | ApiError::CommandNotAllowed { .. }
+            | ApiError::ExecutableNotAllowed { .. }
+            | ApiError::SpawnPermissionDenied { .. }
+            | ApiError::ServicePermissionDenied { .. }
+            | ApiError::DevicePermissionDenied { .. }
+            | ApiError::SchedulerPermissionDenied { .. }
+            | ApiError::SysctlPermissionDenied { .. }
+            | ApiError::RenameNotPermitted { .. } => StatusCode::FORBIDDEN,
+            ApiError::CannotRestartUnknownCommand { .. }
+            | ApiError::OutputNotAvailable { .. }
+            | ApiError::ServiceHasDependents { .. } => StatusCode::CONFLICT,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::NotImplemented { .. } => StatusCode::NOT_IMPLEMENTED,
+            ApiError::ShuttingDown | ApiError::DockerUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::ProcessNotFound { .. } => "process_not_found",
+            ApiError::ProcessGroupNotFound { .. } => "process_group_not_found",
+            ApiError::PermissionDenied { .. }
+            | ApiError::ProcessGroupPermissionDenied { .. }
+            | ApiError::SpawnPermissionDenied { .. }
+            | ApiError::ServicePermissionDenied { .. }
+            | ApiError::DevicePermissionDenied { .. }
+            | ApiError::SchedulerPermissionDenied { .. }
+            | ApiError::SysctlPermissionDenied { .. } => "permission_denied",
+            ApiError::ProcessProtected { .. } => "process_protected",
+            ApiError::CannotKillSelf { .. } => "cannot_kill_self",
+            ApiError::KillNotAllowed { .. } => "kill_not_allowed",
+            ApiError::AppNotCloseable { .. } => "app_not_closeable",
+            ApiError::NoProcessesTerminated => "no_processes_terminated",
+            ApiError::MissingToken => "missing_token",
+            ApiError::InvalidToken => "invalid_token",
+            ApiError::ReadOnly => "read_only",
+            ApiError::NotLoopback => "not_loopback",
+            ApiError::OriginNotAllowed => "origin_not_allowed",
+            ApiError::CommandNotAllowed { .. } => "command_not_allowed",
+            ApiError::EnvNotAllowed { .. } => "env_not_allowed",
+            ApiError::ExecutableNotAllowed { .. } => "executable_not_allowed",
+            ApiError::RenameNotPermitted { .. } => "rename_not_permitted",
+            ApiError::CannotRestartUnknownCommand { .. } => "cannot_restart_unknown_command",
+            ApiError::OutputNotAvailable { .. } => "output_not_available",
+            ApiError::SpawnFailed { .. } => "spawn_failed",
+            ApiError::ServiceNotFound { .. } => "service_not_found",
+            ApiError::ServiceHasDependents { .. } => "service_has_dependents",
+            ApiError::InvalidServiceName { .. } => "invalid_service_name",
+            ApiError::DeviceNotFound { .. } => "device_not_found",
+            ApiError::InvalidDeviceName { .. } => "invalid_device_name",
+            ApiError::InvalidScheduler { .. } => "invalid_scheduler",
+            ApiError::ContainerNotFound { .. } => "container_not_found",
+            ApiError::InvalidContainerId { .. } => "invalid_container_id",
+            ApiError::DockerUnavailable => "docker_unavailable",
+            ApiError::SysctlNotFound { .. } => "sysctl_not_found",
+            ApiError::InvalidSysctlKey { .. } => "invalid_sysctl_key",
+            ApiError::InvalidSysctlValue { .. } => "invalid_sysctl_value",
+            ApiError::InvalidBody { .. } => "invalid_body",
+            ApiError::InvalidFields { .. } => "invalid_fields",
+            ApiError::AlertRuleNotFound { .. } => "alert_rule_not_found",
+            ApiError::UnknownFields { .. } => "unknown_field",
+            ApiError::NotImplemented { .. } => "not_implemented",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::ShuttingDown => "shutting_down",
+            ApiError::Internal => "internal_error",
+        }
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        match self {
+            ApiError::ProcessNotFound { pid }
+            | ApiError::PermissionDenied { pid }
+            | ApiError::ProcessProtected { pid }
+            | ApiError::CannotKillSelf { pid }
+            | ApiError::KillNotAllowed { pid }
+            | ApiError::CannotRestartUnknownCommand { pid }
+            | ApiError::OutputNotAvailable { pid }
+            | ApiError::RenameNotPermitted { pid }
+            | ApiError::AppNotCloseable { pid, .. } => Some(*pid),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorEnvelope {
+            error: ErrorBody {
+                code: self.code(),
+                message: self.to_string(),
+                pid: self.pid(),
+                fields: match &self {
+                    ApiError::InvalidFields { errors } => Some(errors.clone()),
+                    _ => None,
+                },
+                request_id: RequestId::current(),
+            },
+        };
+        let mut response = (self.status(), Json(body)).into_response();
+        if response.status() == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Bearer"));
+        }
+        if let ApiError::RateLimited { retry_after_secs } = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(retry_after_secs));
+        }
+        response
+    }
+}
+
+/// Turns a handler panic into the standard 500 envelope, request ID included,
+/// instead of a dropped connection. The panic hook installed by
+/// [`crate::logging::init`] has already logged it.
+pub fn handle_panic(_payload: Box<dyn std::any::Any + Send + 'static>) -> Response {
+    ApiError::Internal.into_response()
+}
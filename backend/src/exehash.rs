@@ -0,0 +1,46 @@
+//! SHA-256 of process executables, for checking binaries against known-good
+//! checksums.
+//!
+//! Hashing a browser or IDE binary reads hundreds of megabytes, so hashes are
+//! cached per path and reused until the file's modification time changes.
+
+use dashmap::DashMap;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Hex SHA-256 per executable, keyed by path and modification time
+#[derive(Clone, Default)]
+pub struct ExeHashCache {
+    /// Only the newest version of each path is kept. Sharded, so lookups for
+    /// one executable don't wait on another's
+    entries: Arc<DashMap<PathBuf, (SystemTime, String)>>,
+}
+
+impl ExeHashCache {
+    /// Lowercase hex SHA-256 of the file at `path`, or `None` when it can't
+    /// be read: deleted since the process started, or not readable by the
+    /// backend's user. Blocks while hashing, so call it off the async runtime.
+    pub fn get(&self, path: &Path) -> Option<String> {
+        let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+        if let Some(entry) = self.entries.get(path) {
+            let (seen, hash) = entry.value();
+            if *seen == modified {
+                return Some(hash.clone());
+            }
+        }
+        // The read guard is gone by here; inserting under it would deadlock its shard
+        let hash = sha256_file(path)?;
+        self.entries.insert(path.to_path_buf(), (modified, hash.clone()));
+        Some(hash)
+    }
+}
+
+/// Streams the file through the hasher rather than reading it into memory
+pub fn sha256_file(path: &Path) -> Option<String> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).ok()?;
+    Some(hex::encode(hasher.finalize()))
+}
@@ -0,0 +1,82 @@
+//! `GET /api/processes/export`: the process list as a CSV or TSV download
+
+use serde::Deserialize;
+
+use crate::models::ProcessData;
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Tsv,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Tsv => "text/tab-separated-values; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+        }
+    }
+
+    fn delimiter(self) -> u8 {
+        match self {
+            ExportFormat::Csv => b',',
+            ExportFormat::Tsv => b'\t',
+        }
+    }
+}
+
+pub const COLUMNS: [&str; 11] = [
+    "PID",
+    "Name",
+    "Username",
+    "CPU%",
+    "Memory%",
+    "MemoryMB",
+    "Status",
+    "Threads",
+    "CreateTime",
+    "Exe",
+    "CommandLine",
+];
+
+/// `Content-Disposition` value naming the file after `timestamp` (Unix seconds)
+pub fn attachment(format: ExportFormat, timestamp: u64) -> String {
+    format!("attachment; filename=\"processes-{}.{}\"", timestamp, format.extension())
+}
+
+/// One header row plus one row per process. Fields containing the delimiter,
+/// quotes or newlines (command lines often do) are quoted and escaped.
+pub fn write(processes: &[ProcessData], format: ExportFormat) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(format.delimiter())
+        .from_writer(Vec::new());
+    writer.write_record(COLUMNS)?;
+
+    for process in processes {
+        writer.write_record([
+            process.pid.to_string(),
+            process.name.clone(),
+            process.username.clone(),
+            format!("{:.2}", process.cpu_percent),
+            format!("{:.2}", process.memory_percent),
+            format!("{:.2}", process.memory_mb),
+            process.status.clone(),
+            process.num_threads.to_string(),
+            process.create_time.to_string(),
+            process.exe.clone(),
+            process.cmdline.join(" "),
+        ])?;
+    }
+
+    writer.into_inner().map_err(|err| err.into_error().into())
+}
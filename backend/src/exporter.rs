@@ -0,0 +1,49 @@
+//! Prometheus text exposition for `/metrics`. Every gauge reads the poller's
+//! latest snapshot when scraped, so a scrape never triggers a refresh.
+
+use arc_swap::ArcSwap;
+use prometheus::{Encoder, PullingGauge, Registry, TextEncoder};
+use std::sync::Arc;
+
+use crate::metrics::ServerMetrics;
+use crate::models::SystemStats;
+
+/// Prefixed to every metric name
+const NAMESPACE: &str = "taskmgr";
+
+type Read = Box<dyn Fn() -> f64 + Send + Sync>;
+
+/// Registry with the machine-wide gauges and the backend's own
+pub fn registry(stats: Arc<ArcSwap<SystemStats>>, metrics: ServerMetrics) -> Registry {
+    let snapshot = |read: fn(&SystemStats) -> f64| -> Read {
+        let stats = stats.clone();
+        Box::new(move || read(&stats.load()))
+    };
+    let gauges: [(&str, &str, Read); 7] = [
+        ("cpu_percent", "Overall CPU usage, 0-100", snapshot(|s| s.cpu.percent as f64)),
+        ("memory_used_bytes", "RAM in use", snapshot(|s| s.memory.used as f64)),
+        ("memory_total_bytes", "Total RAM", snapshot(|s| s.memory.total as f64)),
+        ("disk_used_bytes", "Bytes used across mounted disks", snapshot(|s| s.disk.used as f64)),
+        ("disk_total_bytes", "Combined size of mounted disks", snapshot(|s| s.disk.total as f64)),
+        ("uptime_seconds", "Seconds since the machine booted", snapshot(|s| s.system.uptime_seconds as f64)),
+        (
+            "websocket_clients",
+            "WebSocket clients connected to the backend",
+            Box::new(move || metrics.websocket_clients() as f64),
+        ),
+    ];
+
+    let registry = Registry::new_custom(Some(NAMESPACE.to_string()), None).expect("namespace is a valid metric prefix");
+    for (name, help, read) in gauges {
+        let gauge = PullingGauge::new(name, help, read).expect("gauge names are valid");
+        registry.register(Box::new(gauge)).expect("gauge names are unique");
+    }
+    registry
+}
+
+/// Everything in `registry`, in the text format Prometheus scrapes
+pub fn encode(registry: &Registry) -> Result<Vec<u8>, prometheus::Error> {
+    let mut body = Vec::new();
+    TextEncoder::new().encode(&registry.gather(), &mut body)?;
+    Ok(body)
+}
@@ -0,0 +1,121 @@
+//! `?fields=` on `/api/stats` and `/api/processes`: the response is
+//! serialized to a [`Value`] and pruned to the requested dotted paths.
+//!
+//! Valid paths come from the type's OpenAPI schema rather than a serialized
+//! sample, so a field this machine leaves out (`gpu` without an NVIDIA card)
+//! is still accepted and simply absent from the result. Arrays are selected
+//! whole: `cpu.per_core` is a path, nothing below it is.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use utoipa::openapi::schema::Schema;
+use utoipa::openapi::RefOr;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+
+/// Deepest nesting followed, against self-referencing schemas
+const MAX_DEPTH: usize = 8;
+
+/// Parsed `?fields=`; each path split at the dots
+#[derive(Debug, PartialEq)]
+pub struct FieldSelection {
+    paths: Vec<Vec<String>>,
+}
+
+impl FieldSelection {
+    /// Parses `cpu.percent,memory`. Blank entries are ignored; paths not in
+    /// `T`'s schema fail with every valid path listed.
+    pub fn parse<T: ToSchema>(fields: &str) -> Result<Self, ApiError> {
+        let valid = field_paths::<T>();
+        let requested: Vec<&str> = fields.split(',').map(str::trim).filter(|f| !f.is_empty()).collect();
+        let unknown: Vec<String> = requested
+            .iter()
+            .filter(|field| !valid.iter().any(|v| v == *field))
+            .map(|field| field.to_string())
+            .collect();
+        if !unknown.is_empty() {
+            return Err(ApiError::UnknownFields { unknown, valid });
+        }
+        Ok(Self {
+            paths: requested.iter().map(|field| field.split('.').map(str::to_string).collect()).collect(),
+        })
+    }
+
+    /// Copies the selected paths of `value` into a new object; paths missing
+    /// from `value` are left out
+    pub fn apply(&self, value: &Value) -> Value {
+        let mut selected = Value::Object(Map::new());
+        for path in &self.paths {
+            let Some(found) = path.iter().try_fold(value, |value, key| value.get(key)) else {
+                continue;
+            };
+            let mut target = &mut selected;
+            for key in &path[..path.len() - 1] {
+                target = target
+                    .as_object_mut()
+                    .expect("only objects are inserted on the way")
+                    .entry(key.as_str())
+                    .or_insert_with(|| Value::Object(Map::new()));
+            }
+            // A parent selected whole (`cpu` with `cpu.percent`) already holds it
+            if let Some(object) = target.as_object_mut() {
+                object.insert(path[path.len() - 1].clone(), found.clone());
+            }
+        }
+        selected
+    }
+}
+
+/// `body` as a [`Value`], by way of JSON text: `serde_json::to_value` widens
+/// an `f32` to the `f64` nearest it, so 0.1 would come out as
+/// 0.10000000149011612 instead of as in the full response
+pub fn to_value<T: Serialize>(body: &T) -> Value {
+    let text = serde_json::to_vec(body).expect("response serializes");
+    serde_json::from_slice(&text).expect("serde_json output parses")
+}
+
+/// Every dotted path into `T`, in schema order: `cpu`, `cpu.cores`,
+/// `cpu.cores.logical`, ...
+pub fn field_paths<T: ToSchema>() -> Vec<String> {
+    let mut components = Vec::new();
+    T::schemas(&mut components);
+    let components: HashMap<String, RefOr<Schema>> = components.into_iter().collect();
+    let mut paths = Vec::new();
+    collect(&T::schema(), &components, "", 0, &mut paths);
+    paths
+}
+
+fn collect(schema: &RefOr<Schema>, components: &HashMap<String, RefOr<Schema>>, prefix: &str, depth: usize, paths: &mut Vec<String>) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let schema = match schema {
+        RefOr::Ref(reference) => {
+            let name = reference.ref_location.rsplit('/').next().unwrap_or_default();
+            match components.get(name) {
+                Some(schema) => return collect(schema, components, prefix, depth + 1, paths),
+                None => return,
+            }
+        }
+        RefOr::T(schema) => schema,
+    };
+    match schema {
+        Schema::Object(object) => {
+            for (name, property) in &object.properties {
+                let path = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+                if !paths.contains(&path) {
+                    paths.push(path.clone());
+                }
+                collect(property, components, &path, depth + 1, paths);
+            }
+        }
+        // Option<T> is `oneOf: [null, T]`
+        Schema::OneOf(one_of) => one_of.items.iter().for_each(|item| collect(item, components, prefix, depth + 1, paths)),
+        Schema::AllOf(all_of) => all_of.items.iter().for_each(|item| collect(item, components, prefix, depth + 1, paths)),
+        Schema::AnyOf(any_of) => any_of.items.iter().for_each(|item| collect(item, components, prefix, depth + 1, paths)),
+        _ => {}
+    }
+}
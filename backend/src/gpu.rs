@@ -0,0 +1,200 @@
+//! NVIDIA GPU statistics via NVML.
+//!
+//! [`GpuStatsCollector`] initialises NVML once and keeps the handle instead of
+//! loading the library for every sample. Transient NVML errors are retried with
+//! exponential backoff. After [`GPU_FAILURE_THRESHOLD`] failed samples in a row
+//! a [`CircuitBreaker`] stops calling NVML until a cooldown has passed, so a
+//! machine without an NVIDIA GPU or driver isn't probed on every poll.
+
+use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+use nvml_wrapper::error::NvmlError;
+use nvml_wrapper::Nvml;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::GPUStats;
+use crate::utils::format_bytes;
+
+/// How long NVML is left alone once the circuit opens
+pub const DEFAULT_GPU_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Failed samples in a row that open the circuit
+pub const GPU_FAILURE_THRESHOLD: u32 = 5;
+
+/// Tries per NVML call, the first included
+const RETRY_ATTEMPTS: u32 = 3;
+
+/// Pause after the first transient error; doubled after each further one
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(10);
+
+/// Counts consecutive failures and, past a threshold, refuses calls until a
+/// cooldown has passed. The first call after the cooldown is a trial: success
+/// closes the circuit, failure opens it for another cooldown.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            failures: 0,
+            open_until: None,
+        }
+    }
+
+    /// Whether a call may be made at `now`
+    pub fn allows(&self, now: Instant) -> bool {
+        self.open_until.is_none_or(|until| now >= until)
+    }
+
+    pub fn succeeded(&mut self) {
+        self.failures = 0;
+        self.open_until = None;
+    }
+
+    pub fn failed(&mut self, now: Instant) {
+        self.failures = self.failures.saturating_add(1);
+        if self.failures >= self.threshold {
+            self.open_until = Some(now + self.cooldown);
+        }
+    }
+
+    /// Failures since the last success
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+/// Runs `call` up to `attempts` times while it fails with an error
+/// `is_transient` accepts, sleeping `base_delay`, then twice that, and so on
+/// between tries. Blocks the thread; call it from blocking code only.
+pub fn retry<T, E>(
+    attempts: u32,
+    base_delay: Duration,
+    is_transient: impl Fn(&E) -> bool,
+    mut call: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    loop {
+        match call() {
+            Err(err) if attempt < attempts && is_transient(&err) => {
+                std::thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Errors that may clear up on their own; a missing library, driver or
+/// device won't
+fn is_transient(err: &NvmlError) -> bool {
+    matches!(
+        err,
+        NvmlError::Timeout
+            | NvmlError::InUse
+            | NvmlError::IrqIssue
+            | NvmlError::InsufficientMemory
+            | NvmlError::NoData
+            | NvmlError::Unknown
+    )
+}
+
+struct Collector {
+    /// `None` until NVML initialises, and again while the circuit is open
+    nvml: Option<Nvml>,
+    breaker: CircuitBreaker,
+}
+
+/// The first GPU's stats, from an NVML handle kept across samples
+pub struct GpuStatsCollector {
+    inner: Mutex<Collector>,
+}
+
+impl GpuStatsCollector {
+    /// Initialises NVML now; a failure counts towards opening the circuit
+    pub fn new(cooldown: Duration) -> Self {
+        let mut breaker = CircuitBreaker::new(GPU_FAILURE_THRESHOLD, cooldown);
+        let nvml = match init() {
+            Ok(nvml) => Some(nvml),
+            Err(err) => {
+                tracing::debug!(error = %err, "NVML unavailable, no GPU stats");
+                breaker.failed(Instant::now());
+                None
+            }
+        };
+        Self {
+            inner: Mutex::new(Collector { nvml, breaker }),
+        }
+    }
+
+    /// `None` without an NVIDIA GPU, after an NVML error, or while the circuit is open
+    pub fn collect(&self) -> Option<GPUStats> {
+        let mut inner = self.inner.lock().expect("gpu collector mutex poisoned");
+        let now = Instant::now();
+        if !inner.breaker.allows(now) {
+            return None;
+        }
+
+        let result = match &inner.nvml {
+            Some(nvml) => read_first_gpu(nvml),
+            None => init().and_then(|nvml| {
+                let stats = read_first_gpu(&nvml);
+                inner.nvml = Some(nvml);
+                stats
+            }),
+        };
+        match result {
+            Ok(stats) => {
+                inner.breaker.succeeded();
+                Some(stats)
+            }
+            Err(err) => {
+                inner.breaker.failed(now);
+                if !inner.breaker.allows(now) {
+                    tracing::debug!(error = %err, failures = inner.breaker.failures(), "NVML failing, pausing GPU stats");
+                    // Initialise afresh after the cooldown, in case the driver was reloaded
+                    inner.nvml = None;
+                }
+                None
+            }
+        }
+    }
+}
+
+fn init() -> Result<Nvml, NvmlError> {
+    retry(RETRY_ATTEMPTS, RETRY_BASE_DELAY, is_transient, Nvml::init)
+}
+
+fn read_first_gpu(nvml: &Nvml) -> Result<GPUStats, NvmlError> {
+    retry(RETRY_ATTEMPTS, RETRY_BASE_DELAY, is_transient, || {
+        let device = nvml.device_by_index(0)?;
+        let name = device.name().unwrap_or_else(|_| "Unknown GPU".to_string());
+        let memory_info = device.memory_info()?;
+        let utilization = device.utilization_rates()?;
+        let temperature = device.temperature(TemperatureSensor::Gpu).ok().map(|t| t as f32);
+
+        let memory_used = memory_info.used;
+        let memory_total = memory_info.total;
+        let memory_percent = (memory_used as f64 / memory_total as f64 * 100.0) as f32;
+
+        Ok(GPUStats {
+            name,
+            load: utilization.gpu as f32,
+            memory_used,
+            memory_total,
+            memory_percent,
+            memory_used_formatted: format_bytes(memory_used),
+            memory_total_formatted: format_bytes(memory_total),
+            temperature,
+        })
+    })
+}
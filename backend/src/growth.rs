@@ -0,0 +1,147 @@
+//! Slow memory growth of long-running processes, for spotting leaks.
+//!
+//! The background poller offers every process listing, but a point is taken
+//! only once per [`GROWTH_INTERVAL_SECS`], so two hours fit in
+//! [`GROWTH_CAPACITY`] points per process. Only processes whose resident
+//! memory reaches [`GROWTH_FLOOR_BYTES`] start being tracked, and at most
+//! [`MAX_GROWTH_TRACKED`] at a time. Tracks are keyed by PID and start time:
+//! exited processes are evicted and a restarted one, with a new PID or a
+//! reused one, starts a new track.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+
+use crate::models::MemoryGrowthEntry;
+use crate::processes::cmp_nan_lowest;
+use crate::source::ProcessTable;
+
+/// Seconds between two points of a track
+pub const GROWTH_INTERVAL_SECS: u64 = 60;
+
+/// Points kept per process: 2 hours at one a minute
+pub const GROWTH_CAPACITY: usize = 120;
+
+/// Resident memory a process needs before it is tracked: 50 MiB
+pub const GROWTH_FLOOR_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Processes tracked at once; larger ones beyond this are not picked up
+/// until a tracked one exits
+pub const MAX_GROWTH_TRACKED: usize = 1024;
+
+/// Look-back of `/api/processes/growth` without `?minutes=`
+pub const DEFAULT_GROWTH_MINUTES: u64 = 30;
+
+/// Most minutes `/api/processes/growth` looks back
+pub const MAX_GROWTH_MINUTES: u64 = 120;
+
+/// Processes returned without `?limit=`
+pub const DEFAULT_GROWTH_LIMIT: usize = 20;
+
+/// Ranking accepted by `?sort=` on `/api/processes/growth`; largest growth first
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum GrowthSort {
+    /// By MiB gained
+    #[default]
+    Absolute,
+    /// By percent of the first point in the window
+    Relative,
+}
+
+struct Track {
+    name: String,
+    /// Start time of the process the points belong to
+    start_time: u64,
+    /// (Unix epoch seconds, resident bytes), oldest first
+    points: VecDeque<(u64, u64)>,
+}
+
+#[derive(Default)]
+struct Inner {
+    tracks: HashMap<u32, Track>,
+    /// When the last round of points was taken
+    last_point: Option<u64>,
+}
+
+/// Shared handle to the tracked processes; cheap to clone
+#[derive(Clone, Default)]
+pub struct MemoryGrowth {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl MemoryGrowth {
+    /// Takes a point for every tracked process in `table` if a minute has
+    /// passed since the last round, starting tracks for processes over the
+    /// floor and evicting those that exited
+    pub fn record(&self, now: u64, table: &ProcessTable) {
+        let mut inner = self.inner.lock().expect("growth mutex poisoned");
+        if inner.last_point.is_some_and(|last| now < last + GROWTH_INTERVAL_SECS) {
+            return;
+        }
+        inner.last_point = Some(now);
+
+        let alive: HashSet<(u32, u64)> = table.processes.iter().map(|p| (p.pid, p.start_time)).collect();
+        inner.tracks.retain(|pid, track| alive.contains(&(*pid, track.start_time)));
+
+        for process in &table.processes {
+            let starts = process.memory >= GROWTH_FLOOR_BYTES && inner.tracks.len() < MAX_GROWTH_TRACKED;
+            if !starts && !inner.tracks.contains_key(&process.pid) {
+                continue;
+            }
+            let track = inner.tracks.entry(process.pid).or_insert_with(|| Track {
+                name: process.name.clone(),
+                start_time: process.start_time,
+                points: VecDeque::with_capacity(GROWTH_CAPACITY),
+            });
+            if track.points.len() == GROWTH_CAPACITY {
+                track.points.pop_front();
+            }
+            track.points.push_back((now, process.memory));
+        }
+    }
+
+    /// Processes with at least two points taken at or after `from`, ranked by
+    /// growth between the first and the last of them
+    pub fn ranked(&self, from: u64, sort: GrowthSort, limit: usize) -> Vec<MemoryGrowthEntry> {
+        let inner = self.inner.lock().expect("growth mutex poisoned");
+        let mut entries: Vec<MemoryGrowthEntry> = inner
+            .tracks
+            .iter()
+            .filter_map(|(&pid, track)| {
+                let window: Vec<(u64, u64)> = track.points.iter().copied().filter(|&(at, _)| at >= from).collect();
+                let (&(since, first), &(_, last)) = (window.first()?, window.last()?);
+                if window.len() < 2 {
+                    return None;
+                }
+                let growth_bytes = last as f64 - first as f64;
+                Some(MemoryGrowthEntry {
+                    pid,
+                    name: track.name.clone(),
+                    since,
+                    memory_mb: mib(last),
+                    growth_mb: growth_bytes / (1024.0 * 1024.0),
+                    growth_percent: growth_bytes / first.max(1) as f64 * 100.0,
+                    samples: window.iter().map(|&(_, bytes)| mib(bytes)).collect(),
+                })
+            })
+            .collect();
+        let key = |entry: &MemoryGrowthEntry| match sort {
+            GrowthSort::Absolute => entry.growth_mb,
+            GrowthSort::Relative => entry.growth_percent,
+        };
+        entries.sort_by(|a, b| cmp_nan_lowest(key(b), key(a)).then(a.pid.cmp(&b.pid)));
+        entries.truncate(limit);
+        entries
+    }
+
+    /// Processes currently tracked
+    pub fn tracked(&self) -> usize {
+        self.inner.lock().expect("growth mutex poisoned").tracks.len()
+    }
+}
+
+fn mib(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
@@ -0,0 +1,2349 @@
+//! HTTP handlers
+
+use axum::{
+    extract::{ws::WebSocketUpgrade, ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{sse::Sse, IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sysinfo::Signal;
+
+use crate::alerts::{self, DEFAULT_ALERT_HISTORY_LIMIT};
+use crate::arp;
+use crate::audit::{Actor, DEFAULT_AUDIT_LIMIT};
+use crate::comm;
+use crate::config::Config;
+use crate::connections;
+use crate::cwd;
+use crate::delta;
+use crate::disks;
+use crate::environ::Redactor;
+use crate::error::{ApiError, ErrorEnvelope, FieldError};
+use crate::export::{self, ExportFormat};
+use crate::exporter;
+use crate::fields::{self, FieldSelection};
+use crate::growth::{GrowthSort, DEFAULT_GROWTH_LIMIT, DEFAULT_GROWTH_MINUTES, MAX_GROWTH_MINUTES};
+use crate::history::{DEFAULT_HISTORY_MINUTES, MAX_HISTORY_MINUTES};
+use crate::interrupts;
+use crate::kernel;
+use crate::limits;
+use crate::maps;
+use crate::mounts;
+use crate::oom;
+use crate::output;
+use crate::pci;
+use crate::msgpack::{Json, MsgpackOrJson};
+use crate::policy::{MatchStyle, PatternList};
+use crate::poller::{DEFAULT_MAX_STATS_AGE, STALE_SAMPLE_INTERVALS, STATS_AGE_HEADER};
+use crate::models::*;
+use crate::processes::{
+    children, cpu_time_seconds, descendants, filter_and_sort, group_by_container, group_app_trees, group_apps, group_by_process_group, group_by_user, list_processes,
+    process_group_members, retain_user_apps, sort_apps, username, AppSortKey, ContainerFilter, Runtime, SortKey, SortOrder,
+};
+use crate::protection;
+use crate::services::{self, ServiceAction};
+use crate::smart;
+use crate::usb;
+use crate::source::{ProcessSample, SystemSource};
+use crate::spawn;
+use crate::startup;
+use crate::sysctl;
+use crate::threads;
+use crate::utils::{format_bytes, signal_from_number, unix_now};
+use crate::version;
+use crate::wsstats;
+use crate::AppState;
+
+/// Runs a blocking [`SystemSource`] call off the async runtime
+async fn with_source<T, F>(state: &AppState, f: F) -> T
+where
+    F: FnOnce(&dyn SystemSource) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let source = state.source.clone();
+    tokio::task::spawn_blocking(move || f(source.as_ref()))
+        .await
+        .expect("system source task panicked")
+}
+
+/// Reads only what the poller and writers already publish, so a monitor
+/// polling it never causes a refresh
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "system",
+    responses(
+        (status = 200, description = "Backend is up and sampling", body = HealthResponse),
+        (status = 503, description = "A component is degraded; see `degraded`", body = HealthResponse)
+    )
+)]
+pub async fn health_check(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    let interval = state.config.poll_interval;
+    let age = state.poller_health.last_sample_age();
+    let (processes, samples) = state.process_history.size();
+    let components = HealthComponents {
+        sampler: SamplerHealth {
+            running: state.poller_health.is_running(),
+            last_sample_age_ms: age.as_millis() as u64,
+            interval_ms: interval.as_millis() as u64,
+        },
+        history: HistoryHealth { processes, samples },
+        audit_writer: match state.audit.writer_running() {
+            Some(true) => "running",
+            Some(false) => "stopped",
+            None => "disabled",
+        }
+        .to_string(),
+        nvml_available: state.stats.load().gpu.is_some(),
+        read_only: state.config.auth.read_only,
+    };
+
+    let mut degraded = Vec::new();
+    if age > interval * STALE_SAMPLE_INTERVALS {
+        degraded.push("sampler".to_string());
+    }
+    if components.audit_writer == "stopped" {
+        degraded.push("audit_writer".to_string());
+    }
+    let (status, code, message) = if degraded.is_empty() {
+        ("ok", StatusCode::OK, "Rust backend is running!")
+    } else {
+        ("degraded", StatusCode::SERVICE_UNAVAILABLE, "Rust backend is degraded")
+    };
+    let body = HealthResponse {
+        status: status.to_string(),
+        message: message.to_string(),
+        version: version::VERSION.to_string(),
+        api_versions: crate::API_VERSIONS.iter().map(|v| v.to_string()).collect(),
+        degraded,
+        components,
+    };
+    (code, Json(body))
+}
+
+/// Scrape target for Prometheus; the gauges read the latest published snapshot
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "system",
+    responses((status = 200, description = "Gauges in the Prometheus text format", body = String, content_type = "text/plain"))
+)]
+pub async fn get_metrics(State(state): State<AppState>) -> Result<Response, ApiError> {
+    let body = exporter::encode(&state.registry).map_err(|err| {
+        tracing::error!(error = %err, "encoding Prometheus metrics failed");
+        ApiError::Internal
+    })?;
+    Ok(([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], body).into_response())
+}
+
+/// Lets a client spot that it was built against another backend version
+#[utoipa::path(
+    get,
+    path = "/api/version",
+    tag = "system",
+    responses((status = 200, description = "Crate version and build details", body = VersionInfo))
+)]
+pub async fn get_version() -> Json<VersionInfo> {
+    Json(version::info())
+}
+
+/// Hands the bearer token to the bundled UI, which can't read the startup log.
+/// Only answers clients on this machine that addressed it as localhost, so
+/// neither the LAN nor a DNS-rebound page can fetch it, and only from a page
+/// whose `Origin` is in the CORS allowlist; with `allowed_origins = ["*"]` any
+/// website could read the reply, so it is refused outright.
+#[utoipa::path(
+    get,
+    path = "/api/auth/token",
+    tag = "system",
+    responses(
+        (status = 200, description = "The token mutating routes expect; null when auth is disabled", body = AuthToken),
+        (status = 403, description = "Client isn't on this machine, or its origin isn't allowlisted", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_auth_token(
+    State(state): State<AppState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+) -> Result<Json<AuthToken>, ApiError> {
+    let from_loopback = peer.is_some_and(|ConnectInfo(addr)| addr.ip().to_canonical().is_loopback());
+    let host_is_local = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(is_local_host);
+    if !from_loopback || !host_is_local {
+        return Err(ApiError::NotLoopback);
+    }
+    let origin_listed = headers
+        .get(header::ORIGIN)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|origin| state.config.cors_origins.lists(origin));
+    if !origin_listed {
+        return Err(ApiError::OriginNotAllowed);
+    }
+    Ok(Json(AuthToken {
+        token: state.config.auth.token.clone(),
+    }))
+}
+
+/// `localhost`, `127.0.0.1` or `[::1]`, with or without a port
+fn is_local_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// The backend's own footprint and traffic. Its CPU and memory come from the
+/// process table the poller last refreshed, so this never triggers a refresh.
+#[utoipa::path(
+    get,
+    path = "/api/self",
+    tag = "system",
+    responses(
+        (status = 200, description = "Resource usage and request counters of the backend process", body = SelfMetrics)
+    )
+)]
+pub async fn get_self(State(state): State<AppState>) -> Json<SelfMetrics> {
+    let pid = std::process::id();
+    let sample = with_source(&state, move |source| source.find_process(pid)).await;
+    let cpu_count = state.stats.load().cpu.cores.logical.max(1);
+    let requests = state.metrics.requests();
+    let (processes, samples) = state.process_history.size();
+    Json(SelfMetrics {
+        pid,
+        memory_bytes: sample.as_ref().map(|s| s.memory),
+        virtual_memory_bytes: sample.as_ref().map(|s| s.virtual_memory),
+        cpu_percent: sample.as_ref().map(|s| s.cpu_usage / cpu_count as f32),
+        uptime_seconds: state.metrics.uptime().as_secs(),
+        worker_threads: tokio::runtime::Handle::current().metrics().num_workers(),
+        websocket_clients: state.metrics.websocket_clients(),
+        requests_total: requests.values().sum(),
+        requests,
+        panics_total: state.metrics.panics(),
+        history: HistoryUsage {
+            processes,
+            samples,
+            bytes: state.process_history.memory_bytes(),
+        },
+    })
+}
+
+#[derive(Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsMode {
+    #[default]
+    Absolute,
+    Delta,
+}
+
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    #[serde(default)]
+    mode: StatsMode,
+    max_age_ms: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/cpu/info",
+    tag = "system",
+    responses((status = 200, description = "CPU model, core counts and cache sizes", body = CpuInfo))
+)]
+pub async fn get_cpu_info(State(state): State<AppState>) -> Json<CpuInfo> {
+    Json(with_source(&state, |source| source.cpu_info()).await)
+}
+
+/// Served from the poller's snapshot, re-sampled first only when it is older
+/// than `max_age_ms`
+#[utoipa::path(
+    get,
+    path = "/api/stats",
+    tag = "system",
+    params(
+        ("mode" = Option<String>, Query, description = "`delta` returns network counters as the change since this client's previous request"),
+        ("max_age_ms" = Option<u64>, Query, description = "Oldest snapshot to accept, in milliseconds; an older one is refreshed before responding. Defaults to 2000"),
+        ("fields" = Option<String>, Query, description = "Comma-separated dotted paths to return, e.g. `cpu.percent,memory.percent`; everything when absent"),
+        ("X-Client-ID" = Option<String>, Header, description = "Client identity for `mode=delta`; generated and echoed back when absent")
+    ),
+    responses(
+        (status = 200, description = "Latest system snapshot; `X-Stats-Age-Ms` says how old it is", body = SystemStats),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 400, description = "Unknown field in `fields`", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_stats(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+    Query(fields): Query<FieldsQuery>,
+    headers: HeaderMap
+) -> Result<Response, ApiError> {
+    let selection = fields.selection::<SystemStats>()?;
+    let max_age = query.max_age_ms.map(Duration::from_millis).unwrap_or(DEFAULT_MAX_STATS_AGE);
+    let age = state.stats_refresh.ensure_fresh(&state.source, &state.stats, &state.poller_health, max_age).await;
+    let stats = state.stats.load();
+    let mut response = if query.mode == StatsMode::Absolute {
+        select(SystemStats::clone(&stats), selection.as_ref())
+    } else {
+        let client_id = delta::client_id(&headers);
+        let (stats, interval) = state.deltas.delta(&client_id, &stats);
+        (delta::response_headers(&client_id, interval), select(stats, selection.as_ref())).into_response()
+    };
+    response.headers_mut().insert(STATS_AGE_HEADER.clone(), (age.as_millis() as u64).into());
+    Ok(response)
+}
+
+#[derive(Deserialize)]
+pub struct FieldsQuery {
+    fields: Option<String>,
+}
+
+impl FieldsQuery {
+    fn selection<T: utoipa::ToSchema>(&self) -> Result<Option<FieldSelection>, ApiError> {
+        self.fields.as_deref().map(FieldSelection::parse::<T>).transpose()
+    }
+}
+
+/// `body` as JSON, pruned to `selection` when there is one
+fn select<T: serde::Serialize + Send + Sync + 'static>(body: T, selection: Option<&FieldSelection>) -> Response {
+    match selection {
+        Some(selection) => Json(selection.apply(&fields::to_value(&body))).into_response(),
+        None => Json(body).into_response(),
+    }
+}
+
+/// For dashboards polling at 10 Hz, where JSON text would be over twice the size
+#[utoipa::path(
+    get,
+    path = "/api/ws/stats/binary",
+    tag = "system",
+    responses(
+        (status = 101, description = "WebSocket pushing `SystemStats` as MessagePack binary frames; send `{\"interval_ms\": 100}` as text to set the rate", body = SystemStats)
+    )
+)]
+pub async fn get_stats_binary_ws(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| async move {
+        let _client = state.metrics.websocket_opened();
+        wsstats::push_stats(socket, state).await
+    })
+}
+
+#[derive(Deserialize, Default)]
+pub struct ProcessQuery {
+    #[serde(default, alias = "sort")]
+    sort_by: SortKey,
+    order: Option<SortOrder>,
+    name_contains: Option<String>,
+    user: Option<String>,
+    #[serde(default)]
+    containers: ContainerFilter,
+}
+
+impl ProcessQuery {
+    fn apply(&self, processes: &mut Vec<ProcessData>) {
+        if let Some(user) = &self.user {
+            processes.retain(|p| p.username == *user);
+        }
+        processes.retain(|p| self.containers.keeps(p));
+        let order = self.order.unwrap_or(self.sort_by.default_order());
+        filter_and_sort(processes, self.name_contains.as_deref(), self.sort_by, order);
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+    #[serde(flatten)]
+    filter: ProcessQuery,
+}
+
+async fn query_processes(state: &AppState, query: &ProcessQuery) -> Vec<ProcessData> {
+    let mut table = with_source(state, |source| source.list_processes()).await;
+    protection::mark(&mut table, &state.config.protected_processes, &state.config.kill_policy);
+    let mut processes = list_processes(&table);
+    query.apply(&mut processes);
+    processes
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/processes",
+    tag = "processes",
+    params(
+        ("sort_by" = Option<String>, Query, description = "`cpu` (default), `memory`, `name`, `pid`, `started` or `cpu_time`; `sort` is an alias"),
+        ("order" = Option<String>, Query, description = "`asc` or `desc`; defaults to `desc` for cpu, memory and started, `asc` otherwise"),
+        ("name_contains" = Option<String>, Query, description = "Case-insensitive substring of the process name"),
+        ("user" = Option<String>, Query, description = "Only processes of this user, by exact name; `N/A` for unresolved owners"),
+        ("containers" = Option<String>, Query, description = "`include` (default), `exclude` to hide container processes, or `only` to show just them"),
+        ("fields" = Option<String>, Query, description = "Comma-separated process fields to return, e.g. `pid,name,cpu_percent`; everything when absent")
+    ),
+    responses(
+        (status = 200, description = "All processes", body = ProcessListResponse),
+        (status = 400, description = "Unknown field in `fields`", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_processes(
+    State(state): State<AppState>,
+    Query(query): Query<ProcessQuery>,
+    Query(fields): Query<FieldsQuery>
+) -> Result<Response, ApiError> {
+    let selection = fields.selection::<ProcessData>()?;
+    let processes = query_processes(&state, &query).await;
+    let total_count = processes.len();
+    let Some(selection) = selection else {
+        return Ok(Json(ProcessListResponse { processes, total_count }).into_response());
+    };
+    let processes: Vec<serde_json::Value> = processes
+        .iter()
+        .map(|process| selection.apply(&fields::to_value(process)))
+        .collect();
+    Ok(Json(serde_json::json!({ "processes": processes, "total_count": total_count })).into_response())
+}
+
+/// Same rows, filters and order as `/api/processes`, as a file download
+#[utoipa::path(
+    get,
+    path = "/api/processes/export",
+    tag = "processes",
+    params(
+        ("format" = Option<String>, Query, description = "`csv` (default) or `tsv`"),
+        ("sort_by" = Option<String>, Query, description = "As for `/api/processes`"),
+        ("order" = Option<String>, Query, description = "As for `/api/processes`"),
+        ("name_contains" = Option<String>, Query, description = "As for `/api/processes`"),
+        ("user" = Option<String>, Query, description = "As for `/api/processes`"),
+        ("containers" = Option<String>, Query, description = "As for `/api/processes`")
+    ),
+    responses(
+        (status = 200, description = "PID, Name, Username, CPU%, Memory%, MemoryMB, Status, Threads, CreateTime, Exe, CommandLine", content_type = "text/csv", body = String)
+    )
+)]
+pub async fn export_processes(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>
+) -> Result<Response, ApiError> {
+    let processes = query_processes(&state, &query.filter).await;
+    let body = export::write(&processes, query.format).map_err(|err| {
+        tracing::error!(error = %err, "process export failed");
+        ApiError::Internal
+    })?;
+    
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let headers = [
+        (header::CONTENT_TYPE, query.format.content_type().to_string()),
+        (header::CONTENT_DISPOSITION, export::attachment(query.format, timestamp)),
+    ];
+    Ok((headers, body).into_response())
+}
+
+/// Resource use per user, e.g. who is loading a shared build server
+#[utoipa::path(
+    get,
+    path = "/api/users",
+    tag = "processes",
+    responses((status = 200, description = "One entry per user, highest CPU first", body = UsersListResponse))
+)]
+pub async fn get_users(State(state): State<AppState>) -> Json<UsersListResponse> {
+    let table = with_source(&state, |source| source.list_processes()).await;
+    let users = group_by_user(&table);
+    let total_count = users.len();
+    Json(UsersListResponse { users, total_count })
+}
+
+/// Process groups, the unit shells use for jobs: everything a pipeline or
+/// `make -j` started shares one
+#[utoipa::path(
+    get,
+    path = "/api/process-groups",
+    tag = "processes",
+    responses((status = 200, description = "One entry per process group, highest CPU first; empty on Windows", body = ProcessGroupsResponse))
+)]
+pub async fn get_process_groups(State(state): State<AppState>) -> Json<ProcessGroupsResponse> {
+    let table = with_source(&state, |source| source.list_processes()).await;
+    let groups = group_by_process_group(&table);
+    let total_count = groups.len();
+    Json(ProcessGroupsResponse { groups, total_count })
+}
+
+/// Processes grouped by the Docker, containerd, CRI-O or Podman container
+/// they run in, with names and images from Docker when its daemon answers
+#[utoipa::path(
+    get,
+    path = "/api/containers",
+    tag = "processes",
+    responses((status = 200, description = "One entry per container, highest CPU first; empty off Linux", body = ContainersListResponse))
+)]
+pub async fn get_containers(State(state): State<AppState>) -> Json<ContainersListResponse> {
+    let table = with_source(&state, |source| source.list_processes()).await;
+    let mut containers = group_by_container(&table);
+    let docker_connected = match &state.docker {
+        Some(docker) => docker.enrich(&mut containers).await,
+        None => false,
+    };
+    let total_count = containers.len();
+    Json(ContainersListResponse { containers, total_count, docker_connected })
+}
+
+/// Like `docker stop`: SIGTERM, then SIGKILL after Docker's grace period
+#[utoipa::path(
+    post,
+    path = "/api/container/{id}/stop",
+    tag = "processes",
+    params(("id" = String, Path, description = "Container ID, abbreviated ID or name")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Container stopped, or was already stopped", body = SuccessResponse),
+        (status = 400, description = "Invalid container ID", body = ErrorEnvelope),
+        (status = 403, description = "Read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such container", body = ErrorEnvelope),
+        (status = 503, description = "Docker integration disabled or daemon unreachable", body = ErrorEnvelope)
+    )
+)]
+pub async fn stop_container(
+    Path(id): Path<String>,
+    State(state): State<AppState>
+) -> Result<Json<SuccessResponse>, ApiError> {
+    let docker = state.docker.as_ref().ok_or(ApiError::DockerUnavailable)?;
+    let stopped = docker.stop(&id).await?;
+    tracing::info!(container = %id, stopped, "container stop requested");
+    
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: if stopped {
+            format!("Stopped container {}", id)
+        } else {
+            format!("Container {} was already stopped", id)
+        },
+    }))
+}
+
+#[derive(Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AppGrouping {
+    #[default]
+    Name,
+    Tree,
+}
+
+#[derive(Deserialize)]
+pub struct AppsQuery {
+    #[serde(default)]
+    group: AppGrouping,
+    #[serde(default)]
+    user_apps_only: bool,
+    #[serde(default)]
+    sort: AppSortKey,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/apps",
+    tag = "processes",
+    params(
+        ("group" = Option<String>, Query, description = "`name` (default) groups by process name; `tree` groups descendants under their top-most ancestor below a shell or session process"),
+        ("user_apps_only" = Option<bool>, Query, description = "Only apps run from a user directory or owned by the backend's user"),
+        ("sort" = Option<String>, Query, description = "`cpu` (default), `memory`, `disk`, `network` (highest first) or `name`")
+    ),
+    responses((status = 200, description = "Processes grouped by name or process tree", body = AppsListResponse))
+)]
+pub async fn get_apps(
+    State(state): State<AppState>,
+    Query(query): Query<AppsQuery>
+) -> Json<AppsListResponse> {
+    let mut table = with_source(&state, |source| source.list_processes()).await;
+    protection::mark(&mut table, &state.config.protected_processes, &state.config.kill_policy);
+    let mut apps = match query.group {
+        AppGrouping::Name => group_apps(&table),
+        AppGrouping::Tree => group_app_trees(&table, &state.config.session_processes),
+    };
+    if query.user_apps_only {
+        retain_user_apps(&mut apps, &table);
+    }
+    sort_apps(&mut apps, query.sort);
+    let total_count = apps.len();
+    
+    Json(AppsListResponse {
+        apps,
+        total_count,
+    })
+}
+
+/// Kills `pid` unless it is the backend itself or its parent, the kill policy
+/// forbids it, or it is protected and `force` is off. The name is returned
+/// whenever the process exists, since the audit log wants it even on failure.
+fn kill_checked(
+    source: &dyn SystemSource,
+    pid: u32,
+    config: &Config,
+    force: bool
+) -> (Option<String>, Result<String, ApiError>) {
+    signal_checked(source, pid, config, force, |source, pid| source.kill(pid))
+}
+
+/// [`SystemSource::kill`], [`SystemSource::suspend`] or [`SystemSource::resume`]
+type SignalFn = fn(&dyn SystemSource, u32) -> Result<String, ApiError>;
+
+/// [`kill_checked`] for any signal; suspending the backend would hang it just
+/// as surely as killing it would stop it
+fn signal_checked(
+    source: &dyn SystemSource,
+    pid: u32,
+    config: &Config,
+    force: bool,
+    signal: impl FnOnce(&dyn SystemSource, u32) -> Result<String, ApiError>
+) -> (Option<String>, Result<String, ApiError>) {
+    let Some(process) = source.find_process(pid) else {
+        return (None, Err(ApiError::ProcessNotFound { pid }));
+    };
+    let result = protection::check_kill(&process, &config.protected_processes, &config.kill_policy, force)
+        .and_then(|()| signal(source, pid));
+    (Some(process.name), result)
+}
+
+#[derive(Deserialize)]
+pub struct KillQuery {
+    #[serde(default)]
+    force: bool,
+}
+
+impl KillQuery {
+    /// `?force=true` only counts on a request that passed token auth
+    fn forced(&self, state: &AppState, actor: &Actor) -> bool {
+        self.force && state.config.auth.token.is_some() && actor.token_id.is_some()
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/process/{pid}/kill",
+    tag = "processes",
+    params(
+        ("pid" = u32, Path, description = "Process ID"),
+        ("force" = Option<bool>, Query, description = "Kill even a protected process; needs the bearer token")
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Process terminated", body = SuccessResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Protected process, forbidden by the kill policy, the backend itself, permission denied or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope)
+    )
+)]
+pub async fn kill_process(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    Query(query): Query<KillQuery>,
+    actor: Actor
+) -> Result<Json<SuccessResponse>, ApiError> {
+    let force = query.forced(&state, &actor);
+    let config = state.config.clone();
+    let (name, result) = with_source(&state, move |source| {
+        kill_checked(source, pid, &config, force)
+    })
+    .await;
+    let action = if force { "force_kill" } else { "kill" };
+    state.audit.record(AuditEntry::new(action, pid, name, &result, &actor));
+    let name = result?;
+    tracing::info!(pid, name = %name, "process killed");
+    
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Process {} terminated", name),
+    }))
+}
+
+/// How long `/api/process/:pid/restart` waits after SIGTERM by default
+const DEFAULT_RESTART_TIMEOUT_MS: u64 = 5_000;
+
+/// Longest grace period a restart request may ask for
+const MAX_RESTART_TIMEOUT_MS: u64 = 60_000;
+
+/// How long to wait for a process to disappear after SIGKILL
+const RESTART_KILL_WAIT: Duration = Duration::from_secs(2);
+
+const RESTART_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Deserialize)]
+pub struct RestartQuery {
+    #[serde(default)]
+    force: bool,
+    timeout_ms: Option<u64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/process/{pid}/restart",
+    tag = "processes",
+    params(
+        ("pid" = u32, Path, description = "Process ID"),
+        ("force" = Option<bool>, Query, description = "Restart even a protected process; needs the bearer token"),
+        ("timeout_ms" = Option<u64>, Query, description = "Grace period after SIGTERM before SIGKILL (default 5000, at most 60000)")
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Process stopped and started again", body = RestartProcessResponse),
+        (status = 400, description = "The command could not be started again", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Protected process, forbidden by the kill policy, the backend itself, permission denied or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 409, description = "Executable or command line unreadable; nothing was killed", body = ErrorEnvelope)
+    )
+)]
+pub async fn restart_process(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    Query(query): Query<RestartQuery>,
+    actor: Actor
+) -> Result<Json<RestartProcessResponse>, ApiError> {
+    let force = KillQuery { force: query.force }.forced(&state, &actor);
+    let grace = Duration::from_millis(query.timeout_ms.unwrap_or(DEFAULT_RESTART_TIMEOUT_MS).min(MAX_RESTART_TIMEOUT_MS));
+    let config = state.config.clone();
+    // The command is captured first: once the process is gone nothing can be read
+    let (name, stopped) = with_source(&state, move |source| {
+        let Some(process) = source.refresh_process(pid) else {
+            return (None, Err(ApiError::ProcessNotFound { pid }));
+        };
+        let result = protection::check_kill(&process, &config.protected_processes, &config.kill_policy, force)
+            .and_then(|()| spawn::relaunch_request(&process).ok_or(ApiError::CannotRestartUnknownCommand { pid }))
+            .and_then(|request| match source.signal(pid, Signal::Term) {
+                // No SIGTERM on Windows; go straight to the kill
+                Err(ApiError::NotImplemented { .. }) => source.kill(pid).map(|_| request),
+                result => result.map(|_| request),
+            });
+        (Some(process.name), result)
+    })
+    .await;
+    let request = match stopped {
+        Ok(request) => request,
+        Err(err) => {
+            let result = Err(err);
+            state.audit.record(AuditEntry::new("restart", pid, name, &result, &actor));
+            return result;
+        }
+    };
+    let name = name.unwrap_or_default();
+    
+    let escalated = !wait_for_exit(&state, pid, grace).await;
+    if escalated {
+        let _ = with_source(&state, move |source| source.kill(pid)).await;
+        if !wait_for_exit(&state, pid, RESTART_KILL_WAIT).await {
+            tracing::warn!(pid, "process still present after SIGKILL; relaunching anyway");
+        }
+    }
+    
+    let result = spawn::launch(&request);
+    state.audit.record(AuditEntry::new("restart", pid, Some(name.clone()), &result, &actor));
+    let new_pid = result?;
+    tracing::info!(old_pid = pid, new_pid, name = %name, escalated, "process restarted");
+    Ok(Json(RestartProcessResponse {
+        old_pid: pid,
+        new_pid,
+        name,
+        escalated,
+        success: true,
+        info_url: format!("/api/v1/process/{}/info", new_pid),
+    }))
+}
+
+/// Polls until `pid` has exited; false if it is still running after `timeout`
+async fn wait_for_exit(state: &AppState, pid: u32, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if with_source(state, move |source| source.refresh_process(pid)).await.is_none() {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(RESTART_POLL_INTERVAL).await;
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/app/close",
+    tag = "processes",
+    request_body(content = Vec<u32>, description = "PIDs of the app group"),
+    params(("force" = Option<bool>, Query, description = "Also kill protected members; needs the bearer token")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "At least one process terminated", body = SuccessResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "A member may not be killed (nothing is), nothing could be terminated, or read-only mode", body = ErrorEnvelope)
+    )
+)]
+pub async fn kill_app(
+    State(state): State<AppState>,
+    Query(query): Query<KillQuery>,
+    actor: Actor,
+    MsgpackOrJson(pids): MsgpackOrJson<Vec<u32>>
+) -> Result<Json<SuccessResponse>, ApiError> {
+    let force = query.forced(&state, &actor);
+    let config = state.config.clone();
+    let results = with_source(&state, move |source| {
+        // All or nothing: one member that may not be killed refuses the whole group
+        let refused = pids.iter().filter_map(|&pid| source.find_process(pid)).find_map(|process| {
+            protection::check_kill(&process, &config.protected_processes, &config.kill_policy, force)
+                .err()
+                .map(|err| (process.pid, process.name, err))
+        });
+        match refused {
+            Some((pid, name, err)) => Err((pid, name, err)),
+            None => Ok(pids
+                .into_iter()
+                .map(|pid| (pid, kill_checked(source, pid, &config, force)))
+                .collect::<Vec<_>>()),
+        }
+    })
+    .await;
+    let action = if force { "force_kill" } else { "kill" };
+    let results = match results {
+        Ok(results) => results,
+        Err((pid, name, err)) => {
+            let err = ApiError::AppNotCloseable { pid, reason: err.to_string() };
+            let result = Err(err);
+            state.audit.record(AuditEntry::new(action, pid, Some(name), &result, &actor));
+            return result;
+        }
+    };
+    let mut killed_count = 0;
+    for (pid, (name, result)) in results {
+        state.audit.record(AuditEntry::new(action, pid, name, &result, &actor));
+        if let Ok(name) = result {
+            tracing::info!(pid, name = %name, "process killed");
+            killed_count += 1;
+        }
+    }
+    
+    if killed_count > 0 {
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Terminated {} process(es)", killed_count),
+        }))
+    } else {
+        Err(ApiError::NoProcessesTerminated)
+    }
+}
+
+/// Most PIDs `/api/processes/batch-kill` accepts in one request
+const MAX_BATCH_KILL: usize = 1000;
+
+#[utoipa::path(
+    post,
+    path = "/api/processes/batch-kill",
+    tag = "processes",
+    request_body = BatchKillRequest,
+    params(("force" = Option<bool>, Query, description = "Also kill protected processes; needs the bearer token")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Per-PID outcome; failures don't stop the other PIDs", body = BatchKillResponse),
+        (status = 400, description = "No PIDs, more than 1000, or an unsupported signal", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Read-only mode", body = ErrorEnvelope)
+    )
+)]
+pub async fn batch_kill(
+    State(state): State<AppState>,
+    Query(query): Query<KillQuery>,
+    actor: Actor,
+    MsgpackOrJson(request): MsgpackOrJson<BatchKillRequest>
+) -> Result<Json<BatchKillResponse>, ApiError> {
+    if request.pids.is_empty() || request.pids.len() > MAX_BATCH_KILL {
+        return Err(ApiError::InvalidBody {
+            reason: format!("pids must hold 1 to {} entries", MAX_BATCH_KILL),
+        });
+    }
+    let number = request.signal.unwrap_or(9);
+    let signal = signal_from_number(number).ok_or_else(|| ApiError::InvalidBody {
+        reason: format!("unsupported signal {}; use 1, 2, 3, 9 or 15", number),
+    })?;
+    let mut pids = request.pids;
+    let mut seen = std::collections::HashSet::new();
+    pids.retain(|pid| seen.insert(*pid));
+
+    let force = query.forced(&state, &actor);
+    let config = state.config.clone();
+    let results = with_source(&state, move |source| {
+        pids.into_iter()
+            .map(|pid| (pid, signal_checked(source, pid, &config, force, |source, pid| source.signal(pid, signal))))
+            .collect::<Vec<_>>()
+    })
+    .await;
+
+    let action = if force { "force_kill" } else { "kill" };
+    let mut response = BatchKillResponse {
+        killed: Vec::new(),
+        not_found: Vec::new(),
+        forbidden: Vec::new(),
+        errors: Default::default(),
+    };
+    for (pid, (name, result)) in results {
+        state.audit.record(AuditEntry::new(action, pid, name, &result, &actor));
+        match result {
+            Ok(name) => {
+                tracing::info!(pid, name = %name, signal = number, "process killed");
+                response.killed.push(pid);
+            }
+            Err(err) if err.status() == StatusCode::NOT_FOUND => response.not_found.push(pid),
+            Err(err) if err.status() == StatusCode::FORBIDDEN => response.forbidden.push(pid),
+            Err(err) => {
+                response.errors.insert(pid, err.to_string());
+            }
+        }
+    }
+    Ok(Json(response))
+}
+
+#[derive(Deserialize)]
+pub struct GroupKillQuery {
+    #[serde(default)]
+    force: bool,
+    signal: Option<i32>,
+}
+
+/// Signals the whole group with one `kill(-pgid)`, so the check runs on every
+/// member first: one protected member, or the backend itself, refuses the lot
+#[utoipa::path(
+    post,
+    path = "/api/process-group/{pgid}/kill",
+    tag = "processes",
+    params(
+        ("pgid" = u32, Path, description = "Process group ID"),
+        ("signal" = Option<i32>, Query, description = "1, 2, 3, 9 or 15 (default 9)"),
+        ("force" = Option<bool>, Query, description = "Also kill protected members; needs the bearer token")
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Signal sent to every member", body = ProcessGroupKillResponse),
+        (status = 400, description = "Unsupported signal", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "A member is protected, forbidden by the kill policy or the backend itself; permission denied or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No process in that group", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn kill_process_group(
+    Path(pgid): Path<u32>,
+    State(state): State<AppState>,
+    Query(query): Query<GroupKillQuery>,
+    actor: Actor
+) -> Result<Json<ProcessGroupKillResponse>, ApiError> {
+    let number = query.signal.unwrap_or(9);
+    let signal = signal_from_number(number).ok_or_else(|| ApiError::InvalidFields {
+        errors: vec![FieldError::new("signal", format!("unsupported signal {}; use 1, 2, 3, 9 or 15", number))],
+    })?;
+    let force = KillQuery { force: query.force }.forced(&state, &actor);
+    let config = state.config.clone();
+    let (members, result) = with_source(&state, move |source| {
+        let table = source.list_processes();
+        let members = process_group_members(&table, pgid);
+        let result = if members.is_empty() {
+            Err(ApiError::ProcessGroupNotFound { pgid })
+        } else {
+            members
+                .iter()
+                .try_for_each(|process| protection::check_kill(process, &config.protected_processes, &config.kill_policy, force))
+                .and_then(|()| source.signal_group(pgid, signal))
+        };
+        let members: Vec<(u32, String)> = members.into_iter().map(|process| (process.pid, process.name.clone())).collect();
+        (members, result)
+    })
+    .await;
+
+    let action = if force { "force_kill" } else { "kill" };
+    for (pid, name) in &members {
+        state.audit.record(AuditEntry::new(action, *pid, Some(name.clone()), &result, &actor));
+    }
+    result?;
+    tracing::info!(pgid, signal = number, processes = members.len(), "process group killed");
+    Ok(Json(ProcessGroupKillResponse {
+        pgid,
+        affected_pids: members.into_iter().map(|(pid, _)| pid).collect(),
+        signal: number,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/process/start",
+    tag = "processes",
+    request_body = StartProcessRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Started; with `wait: true` an SSE stream of `started`, `stdout`, `stderr` and `exit` events", body = StartProcessResponse),
+        (status = 400, description = "The command could not be started, or `env` sets `PATH` or a loader variable", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Command not in `allowed_commands`, not executable, or read-only mode", body = ErrorEnvelope)
+    )
+)]
+pub async fn start_process(
+    State(state): State<AppState>,
+    MsgpackOrJson(request): MsgpackOrJson<StartProcessRequest>
+) -> Result<Response, ApiError> {
+    if !state.config.allowed_commands.contains(&request.command) {
+        return Err(ApiError::CommandNotAllowed { command: request.command });
+    }
+    spawn::check_env(request.env.keys())?;
+    
+    let child = spawn::start(&request)?;
+    tracing::info!(command = %request.command, pid = child.id(), wait = request.wait, "process started");
+    
+    if request.wait {
+        return Ok(Sse::new(spawn::output_events(child)).into_response());
+    }
+    
+    let pid = child.id().unwrap_or_default();
+    state.spawned.track(child);
+    Ok(Json(StartProcessResponse { pid, success: true }).into_response())
+}
+
+/// Task Manager's "Run new task": starts any executable, unless the config
+/// file sets `launch_executables`
+#[utoipa::path(
+    post,
+    path = "/api/process/launch",
+    tag = "processes",
+    request_body = LaunchProcessRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Started", body = LaunchProcessResponse),
+        (status = 400, description = "Executable or working directory not found", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Not in `launch_executables`, not executable, or read-only mode", body = ErrorEnvelope)
+    )
+)]
+pub async fn launch_process(
+    State(state): State<AppState>,
+    MsgpackOrJson(request): MsgpackOrJson<LaunchProcessRequest>
+) -> Result<Json<LaunchProcessResponse>, ApiError> {
+    if let Some(allowed) = &state.config.launch_executables {
+        if !allowed.contains(&request.command) {
+            return Err(ApiError::ExecutableNotAllowed { command: request.command });
+        }
+    }
+    
+    let pid = spawn::launch(&request)?;
+    tracing::info!(command = %request.command, pid, detached = request.detached, "process launched");
+    Ok(Json(LaunchProcessResponse {
+        pid,
+        success: true,
+        info_url: format!("/api/v1/process/{}/info", pid),
+    }))
+}
+
+/// Kill errors that mean "not allowed to", as opposed to "tried and failed"
+const REFUSAL_CODES: &[&str] = &["process_protected", "cannot_kill_self", "kill_not_allowed"];
+
+#[utoipa::path(
+    post,
+    path = "/api/processes/kill_by_name",
+    tag = "processes",
+    request_body = KillByNameRequest,
+    params(("force" = Option<bool>, Query, description = "Also kill protected processes; needs the bearer token")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Per-PID outcome, empty when nothing matches", body = KillByNameResponse),
+        (status = 400, description = "Empty name or invalid glob", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Read-only mode", body = ErrorEnvelope)
+    )
+)]
+pub async fn kill_by_name(
+    State(state): State<AppState>,
+    Query(query): Query<KillQuery>,
+    actor: Actor,
+    MsgpackOrJson(request): MsgpackOrJson<KillByNameRequest>
+) -> Result<Json<KillByNameResponse>, ApiError> {
+    if request.name.trim().is_empty() {
+        return Err(ApiError::InvalidBody { reason: "name must not be empty".to_string() });
+    }
+    let style = MatchStyle::NATIVE;
+    let glob = match request.match_mode {
+        NameMatch::Glob => Some(
+            PatternList::new(std::slice::from_ref(&request.name), style)
+                .map_err(|err| ApiError::InvalidBody { reason: err.to_string() })?,
+        ),
+        NameMatch::Exact => None,
+    };
+    let matches = move |process: &ProcessSample| match &glob {
+        Some(glob) => glob.matches(&process.name, process.exe.as_deref()),
+        None => [Some(process.name.as_str()), process.exe.as_deref()].into_iter().flatten().any(|text| match style {
+            MatchStyle::Unix => text == request.name,
+            MatchStyle::Windows => text.eq_ignore_ascii_case(&request.name),
+        }),
+    };
+    
+    let force = query.forced(&state, &actor);
+    let dry_run = request.dry_run;
+    let config = state.config.clone();
+    let results = with_source(&state, move |source| {
+        let mut matched: Vec<ProcessSample> = source.list_processes().processes.into_iter().filter(|p| matches(p)).collect();
+        matched.sort_by_key(|process| process.pid);
+        matched
+            .into_iter()
+            .map(|process| {
+                let result = if dry_run {
+                    protection::check_kill(&process, &config.protected_processes, &config.kill_policy, force)
+                        .map(|()| process.name.clone())
+                } else {
+                    kill_checked(source, process.pid, &config, force).1
+                };
+                (process, result)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await;
+    
+    let action = if force { "force_kill" } else { "kill" };
+    let mut response = KillByNameResponse {
+        dry_run,
+        results: Vec::with_capacity(results.len()),
+        killed: 0,
+        would_kill: 0,
+        skipped: 0,
+        failed: 0,
+    };
+    for (process, result) in results {
+        if !dry_run {
+            state.audit.record(AuditEntry::new(action, process.pid, Some(process.name.clone()), &result, &actor));
+        }
+        let (outcome, reason) = match &result {
+            Ok(_) if dry_run => (KillOutcome::WouldKill, None),
+            Ok(name) => {
+                tracing::info!(pid = process.pid, name = %name, "process killed");
+                (KillOutcome::Killed, None)
+            }
+            Err(err) if REFUSAL_CODES.contains(&err.code()) => (KillOutcome::Skipped, Some(err.code().to_string())),
+            Err(err) => (KillOutcome::Failed, Some(err.code().to_string())),
+        };
+        match outcome {
+            KillOutcome::Killed => response.killed += 1,
+            KillOutcome::WouldKill => response.would_kill += 1,
+            KillOutcome::Skipped => response.skipped += 1,
+            KillOutcome::Failed => response.failed += 1,
+        }
+        response.results.push(KillByNameResult {
+            pid: process.pid,
+            name: process.name,
+            exe: process.exe,
+            outcome,
+            reason,
+        });
+    }
+    Ok(Json(response))
+}
+
+/// Signals one process after the same checks as a kill without `force`,
+/// audited as `action`
+async fn signal_process(
+    state: &AppState,
+    actor: &Actor,
+    pid: u32,
+    action: &'static str,
+    signal: SignalFn
+) -> Result<String, ApiError> {
+    let config = state.config.clone();
+    let (name, result) = with_source(state, move |source| signal_checked(source, pid, &config, false, signal)).await;
+    state.audit.record(AuditEntry::new(action, pid, name, &result, actor));
+    if let Ok(name) = &result {
+        tracing::info!(pid, name = %name, "process {}", action);
+    }
+    result
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/process/{pid}/suspend",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Process stopped", body = SuccessResponse),
+        (status = 403, description = "Protected process, forbidden by the kill policy, the backend itself, permission denied or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn suspend_process(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    actor: Actor
+) -> Result<Json<SuccessResponse>, ApiError> {
+    let name = signal_process(&state, &actor, pid, "suspend", |source, pid| source.suspend(pid)).await?;
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Process {} suspended", name),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/process/{pid}/resume",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Process continued", body = SuccessResponse),
+        (status = 403, description = "Protected process, forbidden by the kill policy, the backend itself, permission denied or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn resume_process(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    actor: Actor
+) -> Result<Json<SuccessResponse>, ApiError> {
+    let name = signal_process(&state, &actor, pid, "resume", |source, pid| source.resume(pid)).await?;
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Process {} resumed", name),
+    }))
+}
+
+/// Not checked against the protected list: shielding critical services from
+/// the OOM killer is what this is for
+#[utoipa::path(
+    post,
+    path = "/api/process/{pid}/oom_adj",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    request_body = OomAdjustRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Adjustment written; the scores as the kernel now reports them", body = OomAdjustResponse),
+        (status = 400, description = "Adjustment outside -1000..=1000", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Permission denied (lowering needs root) or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn set_oom_adj(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    actor: Actor,
+    MsgpackOrJson(request): MsgpackOrJson<OomAdjustRequest>
+) -> Result<Json<OomAdjustResponse>, ApiError> {
+    let adj = request.adjustment;
+    if !oom::ADJ_RANGE.contains(&adj) {
+        return Err(ApiError::InvalidBody {
+            reason: format!("adjustment must be between {} and {}", oom::ADJ_RANGE.start(), oom::ADJ_RANGE.end()),
+        });
+    }
+    let (name, result) = with_source(&state, move |source| {
+        let name = source.find_process(pid).map(|process| process.name);
+        let result = match &name {
+            Some(_) => source.set_oom_score_adj(pid, adj),
+            None => Err(ApiError::ProcessNotFound { pid }),
+        };
+        (name, result)
+    })
+    .await;
+    state.audit.record(AuditEntry::new("oom_adj", pid, name.clone(), &result, &actor));
+    let score = result?;
+    let name = name.unwrap_or_default();
+    tracing::info!(pid, name = %name, adj, "OOM score adjusted");
+    Ok(Json(OomAdjustResponse {
+        pid,
+        name,
+        oom_score: score.score,
+        oom_score_adj: score.adj,
+        success: true,
+    }))
+}
+
+/// Linux only lets a process rename itself, so in practice this works for the
+/// backend's own PID; anything else is refused by the kernel with 403
+/// `rename_not_permitted`. Not checked against the protected list: the name
+/// is cosmetic and the process keeps running.
+#[utoipa::path(
+    post,
+    path = "/api/process/{pid}/rename",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    request_body = RenameProcessRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Name written to /proc/<pid>/comm", body = RenameProcessResponse),
+        (status = 400, description = "Name empty, over 15 bytes or containing control characters", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Not the backend's own process, permission denied or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn rename_process(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    actor: Actor,
+    MsgpackOrJson(request): MsgpackOrJson<RenameProcessRequest>
+) -> Result<Json<RenameProcessResponse>, ApiError> {
+    comm::validate_name(&request.name)?;
+    let name = request.name;
+    let (old_name, result) = with_source(&state, {
+        let name = name.clone();
+        move |source| {
+            let old_name = source.find_process(pid).map(|process| process.name);
+            let result = match &old_name {
+                Some(_) => source.rename(pid, &name),
+                None => Err(ApiError::ProcessNotFound { pid }),
+            };
+            (old_name, result)
+        }
+    })
+    .await;
+    state.audit.record(AuditEntry::new("rename", pid, old_name.clone(), &result, &actor));
+    result?;
+    let old_name = old_name.unwrap_or_default();
+    tracing::info!(pid, old_name = %old_name, name = %name, "process renamed");
+    Ok(Json(RenameProcessResponse {
+        pid,
+        old_name,
+        name,
+        success: true,
+        note: "Only /proc/<pid>/comm changes (what ps and top show); the command line is untouched, and \
+               the process list may keep the old name until the process restarts"
+            .to_string(),
+    }))
+}
+
+/// Signals every PID in an app group, carrying on past failures. Each PID is
+/// audited as `action` and checked as in [`signal_process`].
+async fn signal_app(
+    state: &AppState,
+    actor: &Actor,
+    pids: Vec<u32>,
+    action: &'static str,
+    signal: SignalFn
+) -> (Vec<u32>, Vec<PidFailure>) {
+    let config = state.config.clone();
+    let results = with_source(state, move |source| {
+        pids.into_iter()
+            .map(|pid| (pid, signal_checked(source, pid, &config, false, signal)))
+            .collect::<Vec<_>>()
+    })
+    .await;
+    let (mut done, mut failed) = (Vec::new(), Vec::new());
+    for (pid, (name, result)) in results {
+        state.audit.record(AuditEntry::new(action, pid, name, &result, actor));
+        match result {
+            Ok(name) => {
+                tracing::info!(pid, name = %name, "process {}", action);
+                done.push(pid);
+            }
+            Err(err) => failed.push(PidFailure { pid, reason: err.to_string() }),
+        }
+    }
+    (done, failed)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/app/suspend",
+    tag = "processes",
+    request_body(content = Vec<u32>, description = "PIDs of the app group"),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Per-PID outcome; failures don't stop the other PIDs", body = AppSuspendResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Read-only mode", body = ErrorEnvelope)
+    )
+)]
+pub async fn suspend_app(
+    State(state): State<AppState>,
+    actor: Actor,
+    MsgpackOrJson(pids): MsgpackOrJson<Vec<u32>>
+) -> Json<AppSuspendResponse> {
+    let (suspended, failed) = signal_app(&state, &actor, pids, "suspend", |source, pid| source.suspend(pid)).await;
+    Json(AppSuspendResponse { suspended, failed })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/app/resume",
+    tag = "processes",
+    request_body(content = Vec<u32>, description = "PIDs of the app group"),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Per-PID outcome; failures don't stop the other PIDs", body = AppResumeResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Read-only mode", body = ErrorEnvelope)
+    )
+)]
+pub async fn resume_app(
+    State(state): State<AppState>,
+    actor: Actor,
+    MsgpackOrJson(pids): MsgpackOrJson<Vec<u32>>
+) -> Json<AppResumeResponse> {
+    let (resumed, failed) = signal_app(&state, &actor, pids, "resume", |source, pid| source.resume(pid)).await;
+    Json(AppResumeResponse { resumed, failed })
+}
+
+/// The rules `kill_process` and `kill_app` apply before sending any signal
+#[utoipa::path(
+    get,
+    path = "/api/policy",
+    tag = "processes",
+    responses((status = 200, description = "Active kill policy", body = KillPolicyInfo))
+)]
+pub async fn get_policy(State(state): State<AppState>) -> Json<KillPolicyInfo> {
+    let policy = &state.config.kill_policy;
+    Json(KillPolicyInfo {
+        protected_pids: protection::PROTECTED_PIDS.to_vec(),
+        protected_processes: state.config.protected_processes.clone(),
+        protected_names: policy.protected_names.patterns().to_vec(),
+        killable_only_names: policy.killable_only_names.patterns().to_vec(),
+        case_sensitive: policy.protected_names.style() == MatchStyle::Unix,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    limit: Option<usize>,
+}
+
+/// Most recent kills, suspends and resumes, newest first
+#[utoipa::path(
+    get,
+    path = "/api/audit",
+    tag = "processes",
+    params(("limit" = Option<usize>, Query, description = "Maximum entries to return (default 100)")),
+    responses((status = 200, description = "Audit entries, newest first", body = Vec<AuditEntry>))
+)]
+pub async fn get_audit(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>
+) -> Json<Vec<AuditEntry>> {
+    Json(state.audit.recent(query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT)))
+}
+
+#[derive(Deserialize)]
+pub struct AlertsQuery {
+    #[serde(default)]
+    active: bool,
+}
+
+#[derive(Deserialize)]
+pub struct AlertHistoryQuery {
+    limit: Option<usize>,
+}
+
+/// Firing alerts, then resolved ones; `?active=true` leaves out the resolved
+#[utoipa::path(
+    get,
+    path = "/api/alerts",
+    tag = "system",
+    params(("active" = Option<bool>, Query, description = "Only alerts still firing")),
+    responses((status = 200, description = "Firing alerts oldest first, then the last 100 resolved, newest first", body = Vec<Alert>))
+)]
+pub async fn get_alerts(
+    State(state): State<AppState>,
+    Query(query): Query<AlertsQuery>
+) -> Json<Vec<Alert>> {
+    let mut alerts = state.alerts.active();
+    if !query.active {
+        alerts.extend(state.alerts.history(DEFAULT_ALERT_HISTORY_LIMIT));
+    }
+    Json(alerts)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/alerts/history",
+    tag = "system",
+    params(("limit" = Option<usize>, Query, description = "Maximum entries to return (default 100)")),
+    responses((status = 200, description = "Resolved alerts, most recently resolved first", body = Vec<Alert>))
+)]
+pub async fn get_alert_history(
+    State(state): State<AppState>,
+    Query(query): Query<AlertHistoryQuery>
+) -> Json<Vec<Alert>> {
+    Json(state.alerts.history(query.limit.unwrap_or(DEFAULT_ALERT_HISTORY_LIMIT)))
+}
+
+/// Empty when no rule has a webhook
+#[utoipa::path(
+    get,
+    path = "/api/alerts/webhooks/status",
+    tag = "system",
+    responses((status = 200, description = "Delivery results per webhook, in config order", body = Vec<WebhookStatus>))
+)]
+pub async fn get_webhook_status(State(state): State<AppState>) -> Json<Vec<WebhookStatus>> {
+    Json(state.webhooks.statuses())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/alert_rules",
+    tag = "system",
+    responses((status = 200, description = "Alert rules in the order they were added", body = Vec<AlertRuleInfo>))
+)]
+pub async fn get_alert_rules(State(state): State<AppState>) -> Json<Vec<AlertRuleInfo>> {
+    Json(state.alerts.rules())
+}
+
+/// Evaluated from the next poll on; saved to `--persist-rules` if set
+#[utoipa::path(
+    post,
+    path = "/api/alert_rules",
+    tag = "system",
+    request_body = AlertRuleRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Rule added", body = AlertRuleInfo),
+        (status = 400, description = "Unknown metric, operator or webhook format, threshold not above 0, or the name is taken; `fields` says which", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Read-only mode", body = ErrorEnvelope),
+        (status = 500, description = "The rules file couldn't be written; nothing changed", body = ErrorEnvelope)
+    )
+)]
+pub async fn create_alert_rule(
+    State(state): State<AppState>,
+    MsgpackOrJson(request): MsgpackOrJson<AlertRuleRequest>
+) -> Result<(StatusCode, Json<AlertRuleInfo>), ApiError> {
+    let rule = alerts::parse_rule(request)?;
+    let alerts = state.alerts.clone();
+    let info = tokio::task::spawn_blocking(move || alerts.add_rule(rule))
+        .await
+        .map_err(|_| ApiError::Internal)??;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+/// Replaces the whole rule. An alert it has firing resolves now and the new
+/// condition starts over, sustain window included.
+#[utoipa::path(
+    put,
+    path = "/api/alert_rules/{id}",
+    tag = "system",
+    params(("id" = u64, Path, description = "Rule ID from GET /api/alert_rules")),
+    request_body = AlertRuleRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Rule replaced", body = AlertRuleInfo),
+        (status = 400, description = "Unknown metric, operator or webhook format, threshold not above 0, or the name is taken; `fields` says which", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such rule", body = ErrorEnvelope),
+        (status = 500, description = "The rules file couldn't be written; nothing changed", body = ErrorEnvelope)
+    )
+)]
+pub async fn update_alert_rule(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    MsgpackOrJson(request): MsgpackOrJson<AlertRuleRequest>
+) -> Result<Json<AlertRuleInfo>, ApiError> {
+    let rule = alerts::parse_rule(request)?;
+    let alerts = state.alerts.clone();
+    let info = tokio::task::spawn_blocking(move || alerts.update_rule(id, rule, unix_now()))
+        .await
+        .map_err(|_| ApiError::Internal)??;
+    Ok(Json(info))
+}
+
+/// An alert the rule has firing resolves now
+#[utoipa::path(
+    delete,
+    path = "/api/alert_rules/{id}",
+    tag = "system",
+    params(("id" = u64, Path, description = "Rule ID from GET /api/alert_rules")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Rule deleted", body = SuccessResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such rule", body = ErrorEnvelope),
+        (status = 500, description = "The rules file couldn't be written; nothing changed", body = ErrorEnvelope)
+    )
+)]
+pub async fn delete_alert_rule(Path(id): Path<u64>, State(state): State<AppState>) -> Result<Json<SuccessResponse>, ApiError> {
+    let alerts = state.alerts.clone();
+    let rule = tokio::task::spawn_blocking(move || alerts.remove_rule(id, unix_now()))
+        .await
+        .map_err(|_| ApiError::Internal)??;
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Alert rule '{}' deleted", rule.name),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/info",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    responses(
+        (status = 200, description = "Process details", body = DetailedProcessInfo),
+        (status = 404, description = "No such process", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_info(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>
+) -> Result<Json<DetailedProcessInfo>, ApiError> {
+    let (process, set_sizes, memory_breakdown, namespaces, seccomp_status, oom, faults, table) = with_source(&state, move |source| {
+        let process = source.find_process(pid)?;
+        let table = source.list_processes();
+        Some((
+            process,
+            source.set_sizes(pid),
+            source.memory_breakdown(pid),
+            source.namespaces(pid),
+            source.seccomp_status(pid),
+            source.oom_score(pid),
+            source.page_faults(pid),
+            table,
+        ))
+    })
+    .await
+    .ok_or(ApiError::ProcessNotFound { pid })?;
+    let parent_alive = process.parent.is_some_and(|parent| table.processes.iter().any(|p| p.pid == parent));
+    let cpu_time_total = cpu_time_seconds(&process);
+    let runtime = Runtime::new(process.start_time, unix_now());
+    let exe_sha256 = match process.exe.clone() {
+        Some(exe) => {
+            let hashes = state.exe_hashes.clone();
+            tokio::task::spawn_blocking(move || hashes.get(std::path::Path::new(&exe))).await.ok().flatten()
+        }
+        None => None,
+    };
+    
+    Ok(Json(DetailedProcessInfo {
+        pid,
+        username: username(&process),
+        name: process.name,
+        status: process.status,
+        create_time: process.start_time,
+        create_time_iso: runtime.create_time_iso,
+        running_seconds: runtime.running_seconds,
+        running_formatted: runtime.running_formatted,
+        cpu_percent: process.cpu_usage,
+        cpu_time_user_seconds: process.cpu_time_user,
+        cpu_time_system_seconds: process.cpu_time_system,
+        cpu_time_total_seconds: cpu_time_total,
+        memory_info: ProcessMemoryInfo {
+            rss: process.memory,
+            vms: process.virtual_memory,
+            rss_formatted: format_bytes(process.memory),
+            vms_formatted: format_bytes(process.virtual_memory),
+        },
+        pss_kb: set_sizes.map(|sizes| sizes.pss_kb),
+        uss_kb: set_sizes.map(|sizes| sizes.uss_kb),
+        memory_breakdown,
+        namespaces,
+        seccomp_status,
+        oom_score: oom.map(|oom| oom.score),
+        oom_score_adj: oom.map(|oom| oom.adj),
+        minor_faults: faults.map(|faults| faults.minor),
+        major_faults: faults.map(|faults| faults.major),
+        children_minor_faults: faults.map(|faults| faults.children_minor),
+        children_major_faults: faults.map(|faults| faults.children_major),
+        parent_pid: process.parent,
+        parent_alive,
+        children: children(&table, pid),
+        num_threads: 0,
+        exe: process.exe.unwrap_or_else(|| "N/A".to_string()),
+        exe_sha256,
+        cwd_exists: process.cwd.as_deref().is_some_and(cwd::exists),
+        cwd: process.cwd.unwrap_or_else(|| "N/A".to_string()),
+        cmdline: process.cmd.join(" "),
+        connections: 0,
+        open_files: 0,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ChildrenQuery {
+    #[serde(default)]
+    recursive: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/children",
+    tag = "processes",
+    params(
+        ("pid" = u32, Path, description = "Process ID"),
+        ("recursive" = Option<bool>, Query, description = "Every descendant breadth-first, each with `depth`, instead of only direct children")
+    ),
+    responses(
+        (status = 200, description = "Children (or descendants) of the process, lowest PID first per level", body = Vec<ProcessData>),
+        (status = 404, description = "No such process", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_children(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    Query(query): Query<ChildrenQuery>
+) -> Result<Json<Vec<ProcessData>>, ApiError> {
+    let table = with_source(&state, |source| source.list_processes()).await;
+    descendants(&table, pid, query.recursive)
+        .map(Json)
+        .ok_or(ApiError::ProcessNotFound { pid })
+}
+
+/// Reads every thread twice, [`threads::SAMPLE_INTERVAL`] apart; the wait
+/// happens between two blocking reads, so nothing is locked during it
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/threads",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    responses(
+        (status = 200, description = "Threads with CPU usage over a ~200ms window, busiest first", body = ProcessThreads),
+        (status = 403, description = "The process belongs to another user", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_threads(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>
+) -> Result<Json<ProcessThreads>, ApiError> {
+    let before = with_source(&state, move |source| source.threads(pid)).await?;
+    let started = std::time::Instant::now();
+    tokio::time::sleep(threads::SAMPLE_INTERVAL).await;
+    let after = with_source(&state, move |source| source.threads(pid)).await?;
+    let elapsed = started.elapsed();
+
+    let threads = threads::cpu_usage(&before, after, elapsed);
+    let total_cpu_percent = threads.iter().filter_map(|t| t.cpu_percent).reduce(|a, b| a + b);
+    Ok(Json(ProcessThreads {
+        pid,
+        thread_count: threads.len(),
+        total_cpu_percent,
+        sample_ms: elapsed.as_millis() as u64,
+        threads,
+    }))
+}
+
+/// Polling this endpoint gives live rates: each response carries the change
+/// per second since the previous request for the same PID
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/io",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    responses(
+        (status = 200, description = "Cumulative I/O counters, with rates from the second request on", body = ProcessIoStats),
+        (status = 403, description = "The process belongs to another user", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_io(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>
+) -> Result<Json<ProcessIoStats>, ApiError> {
+    let counters = with_source(&state, move |source| source.io_counters(pid)).await?;
+    let per_sec = state.io_samples.record(pid, counters);
+    Ok(Json(ProcessIoStats {
+        pid,
+        read_bytes: counters.read_bytes,
+        write_bytes: counters.write_bytes,
+        read_syscalls: counters.read_syscalls,
+        write_syscalls: counters.write_syscalls,
+        cancelled_write_bytes: counters.cancelled_write_bytes,
+        read_bytes_formatted: format_bytes(counters.read_bytes),
+        write_bytes_formatted: format_bytes(counters.write_bytes),
+        per_sec,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    minutes: Option<u64>,
+}
+
+/// Served from the poller's samples, so it costs no system calls
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/history",
+    tag = "processes",
+    params(
+        ("pid" = u32, Path, description = "Process ID"),
+        ("minutes" = Option<u64>, Query, description = "How far back to look, 1-10 (default 5)")
+    ),
+    responses(
+        (status = 200, description = "CPU and memory samples, oldest first", body = ProcessHistoryResponse),
+        (status = 404, description = "No such process", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_history(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>
+) -> Result<Json<ProcessHistoryResponse>, ApiError> {
+    with_source(&state, move |source| source.find_process(pid))
+        .await
+        .ok_or(ApiError::ProcessNotFound { pid })?;
+    let minutes = query.minutes.unwrap_or(DEFAULT_HISTORY_MINUTES).clamp(1, MAX_HISTORY_MINUTES);
+    let samples = state.process_history.since(pid, unix_now().saturating_sub(minutes * 60));
+    Ok(Json(ProcessHistoryResponse { pid, minutes, samples }))
+}
+
+#[derive(Deserialize)]
+pub struct GrowthQuery {
+    minutes: Option<u64>,
+    #[serde(default)]
+    sort: GrowthSort,
+    limit: Option<usize>,
+}
+
+/// Served from the poller's per-minute points, so it costs no system calls
+#[utoipa::path(
+    get,
+    path = "/api/processes/growth",
+    tag = "processes",
+    params(
+        ("minutes" = Option<u64>, Query, description = "Window to measure growth over, 1-120 (default 30)"),
+        ("sort" = Option<String>, Query, description = "`absolute` (MiB gained, default) or `relative` (percent gained)"),
+        ("limit" = Option<usize>, Query, description = "Maximum processes to return (default 20)")
+    ),
+    responses((status = 200, description = "Tracked processes, largest growth first", body = MemoryGrowthResponse))
+)]
+pub async fn get_memory_growth(
+    State(state): State<AppState>,
+    Query(query): Query<GrowthQuery>
+) -> Json<MemoryGrowthResponse> {
+    let minutes = query.minutes.unwrap_or(DEFAULT_GROWTH_MINUTES).clamp(1, MAX_GROWTH_MINUTES);
+    let from = unix_now().saturating_sub(minutes * 60);
+    Json(MemoryGrowthResponse {
+        minutes,
+        tracked: state.memory_growth.tracked(),
+        processes: state.memory_growth.ranked(from, query.sort, query.limit.unwrap_or(DEFAULT_GROWTH_LIMIT)),
+    })
+}
+
+/// Needs no root on Linux, so "Too many open files" can be diagnosed without a shell
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/limits",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    responses(
+        (status = 200, description = "Soft and hard resource limits", body = ProcessResourceLimits),
+        (status = 403, description = "Permission denied (macOS, another user's process)", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform or kernel", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_limits(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>
+) -> Result<Json<ProcessResourceLimits>, ApiError> {
+    with_source(&state, move |source| source.find_process(pid))
+        .await
+        .ok_or(ApiError::ProcessNotFound { pid })?;
+    Ok(Json(limits::read(pid).await?))
+}
+
+/// Which privileges a process holds, e.g. whether a container kept `CAP_SYS_ADMIN`
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/capabilities",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    responses(
+        (status = 200, description = "Effective, permitted, inheritable, bounding and ambient capability sets", body = ProcessCapabilities),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_capabilities(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>
+) -> Result<Json<ProcessCapabilities>, ApiError> {
+    let (process, capabilities) =
+        with_source(&state, move |source| (source.find_process(pid), source.capabilities(pid))).await;
+    process.ok_or(ApiError::ProcessNotFound { pid })?;
+    capabilities
+        .map(Json)
+        .ok_or(ApiError::NotImplemented { feature: "process capabilities (Linux only)" })
+}
+
+/// Reads the tables of the process's own network namespace, so sockets of a
+/// process in a container are found too
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/connections",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    responses(
+        (status = 200, description = "TCP and UDP sockets the process holds open", body = Vec<TcpConnection>),
+        (status = 403, description = "The process belongs to another user", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_connections(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>
+) -> Result<Json<Vec<TcpConnection>>, ApiError> {
+    with_source(&state, move |source| source.find_process(pid))
+        .await
+        .ok_or(ApiError::ProcessNotFound { pid })?;
+    Ok(Json(connections::list_for_process(pid).await?))
+}
+
+/// Browsers map thousands of files; the UI pages through them
+const DEFAULT_MAPS_LIMIT: usize = 500;
+
+#[derive(Deserialize)]
+pub struct MapsQuery {
+    filter: Option<String>,
+    limit: Option<usize>,
+    #[serde(default)]
+    offset: usize,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/maps",
+    tag = "processes",
+    params(
+        ("pid" = u32, Path, description = "Process ID"),
+        ("filter" = Option<String>, Query, description = "Only files whose path contains this (case-insensitive), e.g. `ssl`"),
+        ("limit" = Option<usize>, Query, description = "Maximum entries to return (default 500)"),
+        ("offset" = Option<usize>, Query, description = "Entries to skip, for paging")
+    ),
+    responses(
+        (status = 200, description = "Mapped files plus one anonymous-memory summary", body = ProcessMaps),
+        (status = 403, description = "The process belongs to another user", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_maps(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    Query(query): Query<MapsQuery>
+) -> Result<Json<ProcessMaps>, ApiError> {
+    let regions = with_source(&state, move |source| source.memory_maps(pid)).await?;
+    let mappings = maps::summarize(&regions, query.filter.as_deref().filter(|f| !f.is_empty()));
+    Ok(Json(ProcessMaps {
+        pid,
+        total_count: mappings.len(),
+        mappings: mappings
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(DEFAULT_MAPS_LIMIT))
+            .collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct EnvironQuery {
+    redact: Option<bool>,
+}
+
+/// Always needs the bearer token, even when reads are otherwise open
+#[utoipa::path(
+    get,
+    path = "/api/process/{pid}/environ",
+    tag = "processes",
+    params(
+        ("pid" = u32, Path, description = "Process ID"),
+        ("redact" = Option<bool>, Query, description = "Mask values of keys matching `redact_env` (default true)")
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Environment variables", body = ProcessEnviron),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "The process belongs to another user", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_environ(
+    Path(pid): Path<u32>,
+    State(state): State<AppState>,
+    Query(query): Query<EnvironQuery>
+) -> Result<Json<ProcessEnviron>, ApiError> {
+    let mut vars = with_source(&state, move |source| source.environ(pid)).await?;
+    let redacted = query.redact.unwrap_or(true);
+    if redacted {
+        // Patterns were validated when the config was loaded
+        let redactor = Redactor::new(&state.config.redact_env).map_err(|_| ApiError::Internal)?;
+        redactor.apply(&mut vars);
+    }
+    Ok(Json(ProcessEnviron {
+        pid,
+        redacted,
+        variables: vars.into_iter().collect(),
+    }))
+}
+
+/// Output can hold secrets too, so this also always needs the token. Errors
+/// are plain responses, before the upgrade.
+#[utoipa::path(
+    get,
+    path = "/api/ws/process/{pid}/output",
+    tag = "processes",
+    params(("pid" = u32, Path, description = "Process ID")),
+    security(("bearer" = [])),
+    responses(
+        (status = 101, description = "WebSocket of text frames: `{\"stream\": \"stdout\", \"line\": \"...\"}` per line, then `{\"event\": \"exit\", \"code\": 0}` and a close"),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "The process belongs to another user", body = ErrorEnvelope),
+        (status = 404, description = "No such process", body = ErrorEnvelope),
+        (status = 409, description = "Not started by this server, and stdout and stderr aren't files (Linux) or can't be followed (other platforms)", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_process_output_ws(
+    ws: WebSocketUpgrade,
+    Path(pid): Path<u32>,
+    State(state): State<AppState>
+) -> Result<Response, ApiError> {
+    let output = match state.spawned.subscribe(pid) {
+        Some(output) => output,
+        None => output::follow(pid)?,
+    };
+    Ok(ws.on_upgrade(move |socket| async move {
+        let _client = state.metrics.websocket_opened();
+        output::stream(socket, output, state.shutdown.clone()).await
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/disks/{device}/smart",
+    tag = "system",
+    params(("device" = String, Path, description = "Device name under /dev, e.g. `sda` or `nvme0n1`")),
+    responses(
+        (status = 200, description = "SMART report; `health` is UNSUPPORTED when the disk has no SMART", body = SmartData),
+        (status = 400, description = "Invalid device name", body = ErrorEnvelope),
+        (status = 403, description = "Not running as root", body = ErrorEnvelope),
+        (status = 404, description = "No such device", body = ErrorEnvelope),
+        (status = 501, description = "smartctl is not installed", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_disk_smart(Path(device): Path<String>) -> Result<Json<SmartData>, ApiError> {
+    Ok(Json(smart::read(&device).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/disks/{device}/scheduler",
+    tag = "system",
+    params(("device" = String, Path, description = "Whole disk under /sys/block, e.g. `sda` or `nvme0n1`")),
+    request_body = SchedulerRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Switched; the scheduler as the kernel now reports it", body = DiskScheduler),
+        (status = 400, description = "Invalid device name, or a scheduler the disk doesn't offer", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Not running as root, or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such disk", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn set_disk_scheduler(
+    Path(device): Path<String>,
+    MsgpackOrJson(request): MsgpackOrJson<SchedulerRequest>,
+) -> Result<Json<DiskScheduler>, ApiError> {
+    let changed = disks::set_scheduler(device, request.scheduler).await?;
+    tracing::info!(device = %changed.device, scheduler = %changed.scheduler, "I/O scheduler changed");
+    Ok(Json(changed))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/mounts",
+    tag = "system",
+    responses(
+        (status = 200, description = "Mounted filesystems with byte and inode usage", body = Vec<MountInfo>),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_mounts() -> Result<Json<Vec<MountInfo>>, ApiError> {
+    Ok(Json(mounts::list().await?))
+}
+
+#[derive(Deserialize)]
+pub struct ConnectionFilter {
+    pid: Option<u32>,
+    state: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/network/connections",
+    tag = "system",
+    params(
+        ("pid" = Option<u32>, Query, description = "Only sockets owned by this process"),
+        ("state" = Option<String>, Query, description = "Only sockets in this state, e.g. `ESTABLISHED` or `LISTEN` (case-insensitive)")
+    ),
+    responses(
+        (status = 200, description = "TCP and UDP sockets with their owning process", body = Vec<TcpConnection>),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_connections(Query(query): Query<ConnectionFilter>) -> Result<Json<Vec<TcpConnection>>, ApiError> {
+    let mut connections = connections::list().await?;
+    if let Some(pid) = query.pid {
+        connections.retain(|c| c.pid == Some(pid));
+    }
+    if let Some(state) = query.state {
+        connections.retain(|c| c.state.eq_ignore_ascii_case(&state));
+    }
+    Ok(Json(connections))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/network/arp",
+    tag = "system",
+    responses(
+        (status = 200, description = "ARP cache: IPv4 neighbours and their MAC addresses", body = Vec<ArpEntry>),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_arp() -> Result<Json<Vec<ArpEntry>>, ApiError> {
+    Ok(Json(arp::list().await?))
+}
+
+/// Reads sysfs off the async runtime
+#[utoipa::path(
+    get,
+    path = "/api/hardware/pci",
+    tag = "system",
+    responses(
+        (status = 200, description = "PCI functions by address, with driver and IOMMU group", body = Vec<PciDevice>),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_pci_devices() -> Result<Json<Vec<PciDevice>>, ApiError> {
+    let devices = tokio::task::spawn_blocking(pci::list).await.map_err(|_| ApiError::Internal)??;
+    Ok(Json(devices))
+}
+
+/// Reads sysfs or IOKit off the async runtime
+#[utoipa::path(
+    get,
+    path = "/api/hardware/usb",
+    tag = "system",
+    responses(
+        (status = 200, description = "Connected USB devices, root hubs included, by bus and device number", body = Vec<UsbDevice>),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_usb_devices() -> Result<Json<Vec<UsbDevice>>, ApiError> {
+    let devices = tokio::task::spawn_blocking(usb::list).await.map_err(|_| ApiError::Internal)??;
+    Ok(Json(devices))
+}
+
+#[derive(Deserialize)]
+pub struct ModuleFilter {
+    filter: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/kernel/modules",
+    tag = "system",
+    params(("filter" = Option<String>, Query, description = "Case-insensitive substring of the module name")),
+    responses(
+        (status = 200, description = "Loaded kernel modules", body = Vec<KernelModule>),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_kernel_modules(Query(query): Query<ModuleFilter>) -> Result<Json<Vec<KernelModule>>, ApiError> {
+    let mut modules = kernel::list_modules().await?;
+    if let Some(filter) = query.filter.map(|f| f.to_lowercase()) {
+        modules.retain(|m| m.name.to_lowercase().contains(&filter));
+    }
+    Ok(Json(modules))
+}
+
+/// Reads the counters twice to measure the interrupt rate; the request
+/// takes about [`interrupts::SAMPLE_INTERVAL`]
+#[utoipa::path(
+    get,
+    path = "/api/kernel/interrupts",
+    tag = "system",
+    responses(
+        (status = 200, description = "Interrupt counters and the rate over a ~200ms window", body = InterruptStats),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_kernel_interrupts() -> Result<Json<InterruptStats>, ApiError> {
+    let (before, _) = interrupts::read().await?;
+    let started = std::time::Instant::now();
+    tokio::time::sleep(interrupts::SAMPLE_INTERVAL).await;
+    let (after, softirqs) = interrupts::read().await?;
+    let elapsed = started.elapsed();
+
+    Ok(Json(InterruptStats {
+        total_interrupts_per_sec: interrupts::rate(interrupts::total(&before), interrupts::total(&after), elapsed),
+        sample_ms: elapsed.as_millis() as u64,
+        interrupts: after,
+        softirqs,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct SysctlQuery {
+    prefix: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/kernel/params",
+    tag = "system",
+    params(("prefix" = Option<String>, Query, description = "Only this key or the keys below it, e.g. `net.ipv4` or `vm`")),
+    responses(
+        (status = 200, description = "Kernel parameters sorted by key, like `sysctl -a`", body = Vec<SysctlParam>),
+        (status = 400, description = "Invalid prefix", body = ErrorEnvelope),
+        (status = 404, description = "No such key", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_kernel_params(Query(query): Query<SysctlQuery>) -> Result<Json<Vec<SysctlParam>>, ApiError> {
+    let prefix = query.prefix.filter(|p| !p.is_empty());
+    Ok(Json(sysctl::list(prefix).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/kernel/params",
+    tag = "system",
+    request_body = SysctlParam,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Written; the value as the kernel now reports it", body = SysctlParam),
+        (status = 400, description = "Invalid key, or the kernel rejected the value", body = ErrorEnvelope),
+        (status = 401, description = "Missing or invalid token", body = ErrorEnvelope),
+        (status = 403, description = "Not running as root, /proc/sys is read-only, or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such key", body = ErrorEnvelope),
+        (status = 501, description = "Not supported on this platform", body = ErrorEnvelope)
+    )
+)]
+pub async fn set_kernel_param(MsgpackOrJson(param): MsgpackOrJson<SysctlParam>) -> Result<Json<SysctlParam>, ApiError> {
+    let written = sysctl::write(param.key, param.value).await?;
+    tracing::info!(key = %written.key, value = %written.value, "kernel parameter written");
+    Ok(Json(written))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/services",
+    tag = "services",
+    responses((status = 200, description = "systemd service units; `supported: false` without systemd", body = ServicesResponse))
+)]
+pub async fn get_services(State(state): State<AppState>) -> Result<Json<ServicesResponse>, ApiError> {
+    let mut services = match services::list().await {
+        Ok(services) => services,
+        // The dashboard hides its services tab rather than showing an error
+        Err(ApiError::NotImplemented { .. }) => {
+            return Ok(Json(ServicesResponse { supported: false, services: Vec::new() }));
+        }
+        Err(err) => return Err(err),
+    };
+    let logical_cores = state.stats.load().cpu.cores.logical.max(1) as f32;
+    
+    let services = with_source(&state, move |source| {
+        for service in &mut services {
+            service.cpu_percent = service
+                .pid
+                .and_then(|pid| source.find_process(pid))
+                .map(|process| process.cpu_usage / logical_cores);
+        }
+        services
+    })
+    .await;
+    
+    Ok(Json(ServicesResponse { supported: true, services }))
+}
+
+async fn control_service(name: String, action: ServiceAction) -> Result<Json<SuccessResponse>, ApiError> {
+    let unit = services::service_name(&name)?;
+    services::control(&unit, action).await?;
+    tracing::info!(unit = %unit, ?action, "service job queued");
+    
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("{} {}", action.past_tense(), unit),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/services/{name}/start",
+    tag = "services",
+    params(("name" = String, Path, description = "Unit name, `.service` optional; the service name (not display name) on Windows")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Start job queued", body = SuccessResponse),
+        (status = 400, description = "Invalid unit name", body = ErrorEnvelope),
+        (status = 403, description = "Needs administrator rights (polkit or UAC), or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such unit", body = ErrorEnvelope),
+        (status = 501, description = "Neither systemd nor Windows", body = ErrorEnvelope)
+    )
+)]
+pub async fn start_service(Path(name): Path<String>) -> Result<Json<SuccessResponse>, ApiError> {
+    control_service(name, ServiceAction::Start).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/services/{name}/stop",
+    tag = "services",
+    params(("name" = String, Path, description = "Unit name, `.service` optional; the service name (not display name) on Windows")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Stop job queued", body = SuccessResponse),
+        (status = 400, description = "Invalid unit name", body = ErrorEnvelope),
+        (status = 403, description = "Needs administrator rights (polkit or UAC), or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such unit", body = ErrorEnvelope),
+        (status = 409, description = "Windows: services that depend on it are running", body = ErrorEnvelope),
+        (status = 501, description = "Neither systemd nor Windows", body = ErrorEnvelope)
+    )
+)]
+pub async fn stop_service(Path(name): Path<String>) -> Result<Json<SuccessResponse>, ApiError> {
+    control_service(name, ServiceAction::Stop).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/services/{name}/restart",
+    tag = "services",
+    params(("name" = String, Path, description = "Unit name, `.service` optional; the service name (not display name) on Windows")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Restart job queued", body = SuccessResponse),
+        (status = 400, description = "Invalid unit name", body = ErrorEnvelope),
+        (status = 403, description = "Needs administrator rights (polkit or UAC), or read-only mode", body = ErrorEnvelope),
+        (status = 404, description = "No such unit", body = ErrorEnvelope),
+        (status = 409, description = "Windows: services that depend on it are running", body = ErrorEnvelope),
+        (status = 501, description = "Neither systemd nor Windows", body = ErrorEnvelope)
+    )
+)]
+pub async fn restart_service(Path(name): Path<String>) -> Result<Json<SuccessResponse>, ApiError> {
+    control_service(name, ServiceAction::Restart).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/startup",
+    tag = "system",
+    responses(
+        (status = 200, description = "Programs started at login, with the process each one is running as", body = Vec<StartupItem>),
+        (status = 501, description = "Neither Linux nor Windows", body = ErrorEnvelope)
+    )
+)]
+pub async fn get_startup(State(state): State<AppState>) -> Result<Json<Vec<StartupItem>>, ApiError> {
+    let mut items = startup::list().await?;
+    let logical_cores = state.stats.load().cpu.cores.logical as f32;
+    
+    let items = with_source(&state, move |source| {
+        startup::attach_processes(&mut items, &source.list_processes().processes, logical_cores);
+        items
+    })
+    .await;
+    
+    Ok(Json(items))
+}
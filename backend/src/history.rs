@@ -0,0 +1,86 @@
+//! Recent CPU and memory readings of every process, for sparklines.
+//!
+//! The background poller appends one [`HistorySample`] per process each
+//! tick. PIDs missing from the latest listing are dropped, and a PID reused
+//! by a new process starts over.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::models::HistorySample;
+use crate::processes::normalized_cpu;
+use crate::source::ProcessTable;
+
+/// Samples kept per PID: 10 minutes at the default 1 s poll interval
+pub const HISTORY_CAPACITY: usize = 600;
+
+/// Most minutes `/api/process/:pid/history` looks back
+pub const MAX_HISTORY_MINUTES: u64 = 10;
+
+/// Look-back of `/api/process/:pid/history` without `?minutes=`
+pub const DEFAULT_HISTORY_MINUTES: u64 = 5;
+
+struct Track {
+    /// Start time of the process the samples belong to
+    start_time: u64,
+    samples: VecDeque<HistorySample>,
+}
+
+/// Shared handle to every PID's history; cheap to clone
+#[derive(Clone, Default)]
+pub struct ProcessHistory {
+    tracks: Arc<Mutex<HashMap<u32, Track>>>,
+}
+
+impl ProcessHistory {
+    /// Appends a sample taken at `now` (Unix epoch seconds) for every process
+    /// in `table` and forgets the rest
+    pub fn record(&self, now: u64, table: &ProcessTable) {
+        let mut tracks = self.tracks.lock().expect("history mutex poisoned");
+        let alive: HashSet<u32> = table.processes.iter().map(|p| p.pid).collect();
+        tracks.retain(|pid, _| alive.contains(pid));
+
+        for process in &table.processes {
+            let track = tracks.entry(process.pid).or_insert_with(|| Track {
+                start_time: process.start_time,
+                samples: VecDeque::new(),
+            });
+            if track.start_time != process.start_time {
+                track.start_time = process.start_time;
+                track.samples.clear();
+            }
+            if track.samples.len() == HISTORY_CAPACITY {
+                track.samples.pop_front();
+            }
+            track.samples.push_back(HistorySample {
+                timestamp: now,
+                cpu_percent: normalized_cpu(process, table),
+                memory_mb: process.memory as f64 / (1024.0 * 1024.0),
+            });
+        }
+    }
+
+    /// `pid`'s samples taken at or after `from`, oldest first
+    pub fn since(&self, pid: u32, from: u64) -> Vec<HistorySample> {
+        let tracks = self.tracks.lock().expect("history mutex poisoned");
+        tracks
+            .get(&pid)
+            .map(|track| track.samples.iter().filter(|s| s.timestamp >= from).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Processes tracked and samples held across all of them
+    pub fn size(&self) -> (usize, usize) {
+        let tracks = self.tracks.lock().expect("history mutex poisoned");
+        (tracks.len(), tracks.values().map(|t| t.samples.len()).sum())
+    }
+
+    /// Estimated heap held by the history, in bytes: sample buffers at their
+    /// capacity plus the table of tracks
+    pub fn memory_bytes(&self) -> u64 {
+        let tracks = self.tracks.lock().expect("history mutex poisoned");
+        let samples: usize = tracks.values().map(|t| t.samples.capacity()).sum();
+        let table = tracks.capacity() * std::mem::size_of::<(u32, Track)>();
+        (samples * std::mem::size_of::<HistorySample>() + table) as u64
+    }
+}
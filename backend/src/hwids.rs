@@ -0,0 +1,114 @@
+//! Parser for the `usb.ids` and `pci.ids` databases, which share a format:
+//! vendors with their devices indented below, then a `C` section of classes
+//! with their subclasses indented below. Deeper levels (USB interfaces, PCI
+//! subsystems, programming interfaces) and other sections are skipped.
+
+use std::collections::HashMap;
+
+struct Vendor {
+    name: String,
+    devices: HashMap<u16, String>,
+}
+
+struct Class {
+    name: String,
+    subclasses: HashMap<u8, String>,
+}
+
+/// Vendor, device, class and subclass names
+#[derive(Default)]
+pub struct IdDatabase {
+    vendors: HashMap<u16, Vendor>,
+    classes: HashMap<u8, Class>,
+}
+
+/// What indented lines below the current top-level line belong to
+enum Section {
+    Vendor(u16),
+    Class(u8),
+    Other,
+}
+
+impl IdDatabase {
+    pub fn parse(text: &str) -> Self {
+        let mut ids = IdDatabase::default();
+        let mut section = Section::Other;
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(child) = line.strip_prefix('\t') {
+                if child.starts_with('\t') {
+                    continue;
+                }
+                let Some((id, name)) = id_and_name(child) else { continue };
+                match section {
+                    Section::Vendor(vendor) => {
+                        if let (Some(vendor), Ok(id)) = (ids.vendors.get_mut(&vendor), u16::from_str_radix(id, 16)) {
+                            vendor.devices.insert(id, name.to_string());
+                        }
+                    }
+                    Section::Class(class) => {
+                        if let (Some(class), Ok(id)) = (ids.classes.get_mut(&class), u8::from_str_radix(id, 16)) {
+                            class.subclasses.insert(id, name.to_string());
+                        }
+                    }
+                    Section::Other => {}
+                }
+                continue;
+            }
+            section = Section::Other;
+            if let Some(class) = line.strip_prefix("C ") {
+                if let Some((id, name)) = id_and_name(class) {
+                    if let Ok(id) = u8::from_str_radix(id, 16) {
+                        ids.classes.insert(id, Class { name: name.to_string(), subclasses: HashMap::new() });
+                        section = Section::Class(id);
+                    }
+                }
+                continue;
+            }
+            let Some((id, name)) = id_and_name(line) else { continue };
+            if id.len() != 4 {
+                continue;
+            }
+            if let Ok(id) = u16::from_str_radix(id, 16) {
+                ids.vendors.insert(id, Vendor { name: name.to_string(), devices: HashMap::new() });
+                section = Section::Vendor(id);
+            }
+        }
+        ids
+    }
+
+    pub fn vendor(&self, vendor: u16) -> Option<&str> {
+        self.vendors.get(&vendor).map(|v| v.name.as_str())
+    }
+
+    /// A USB product or PCI device
+    pub fn device(&self, vendor: u16, device: u16) -> Option<&str> {
+        self.vendors.get(&vendor)?.devices.get(&device).map(String::as_str)
+    }
+
+    pub fn class(&self, class: u8) -> Option<&str> {
+        self.classes.get(&class).map(|c| c.name.as_str())
+    }
+
+    pub fn subclass(&self, class: u8, subclass: u8) -> Option<&str> {
+        self.classes.get(&class)?.subclasses.get(&subclass).map(String::as_str)
+    }
+}
+
+/// Parses the first of `paths` that can be read, or `bundled` when none can.
+/// Distributions keep their copy current through `hwdata`, `pciutils` or
+/// `usbutils`, so the one compiled in is only the fallback.
+pub fn load(paths: &[&str], bundled: &str) -> IdDatabase {
+    match paths.iter().find_map(|path| std::fs::read(path).ok()) {
+        Some(bytes) => IdDatabase::parse(&String::from_utf8_lossy(&bytes)),
+        None => IdDatabase::parse(bundled),
+    }
+}
+
+/// Splits `046d  Logitech, Inc.` at the two-space separator
+fn id_and_name(line: &str) -> Option<(&str, &str)> {
+    let (id, name) = line.split_once("  ")?;
+    Some((id.trim(), name.trim()))
+}
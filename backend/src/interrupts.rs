@@ -0,0 +1,112 @@
+//! Hardware and soft interrupt counters from `/proc/interrupts` and
+//! `/proc/softirqs`.
+//!
+//! The counters are cumulative since boot; the handler reads them twice
+//! [`SAMPLE_INTERVAL`] apart to turn the hardware total into a rate.
+
+use std::time::Duration;
+
+use crate::error::ApiError;
+use crate::models::{IrqStat, SoftIrqStat};
+
+/// Time between the two readings, as for per-thread CPU usage
+pub const SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Parses `/proc/interrupts`. The header names one column per CPU; each row
+/// is the IRQ, its per-CPU counts, then for numbered IRQs the controller,
+/// hardware IRQ and trigger (`IO-APIC 2-edge`, or `GICv3 27 Level` on ARM)
+/// and the device names. Summary rows like `LOC` have a description instead,
+/// and `ERR`/`MIS` a single count.
+pub fn parse_proc_interrupts(text: &str) -> Vec<IrqStat> {
+    let mut lines = text.lines();
+    let cpus = lines.next().map_or(0, |header| header.split_whitespace().count());
+    lines
+        .filter_map(|line| {
+            let (irq, rest) = line.split_once(':')?;
+            let irq = irq.trim();
+            if irq.is_empty() {
+                return None;
+            }
+            let (per_cpu_counts, rest) = take_counts(rest, cpus);
+            let mut words = rest.split_whitespace().peekable();
+            let (controller, device_name) = if irq.chars().all(|c| c.is_ascii_digit()) {
+                let controller = words.next().unwrap_or_default().to_string();
+                words.next_if(|word| word.starts_with(|c: char| c.is_ascii_digit()));
+                words.next_if(|word| matches!(*word, "Level" | "Edge"));
+                (controller, words.collect::<Vec<_>>().join(" "))
+            } else {
+                (String::new(), words.collect::<Vec<_>>().join(" "))
+            };
+            Some(IrqStat {
+                irq: irq.to_string(),
+                per_cpu_counts,
+                controller,
+                device_name,
+            })
+        })
+        .collect()
+}
+
+/// Parses `/proc/softirqs`: a CPU header, then `NAME: count count ...`
+pub fn parse_proc_softirqs(text: &str) -> Vec<SoftIrqStat> {
+    let mut lines = text.lines();
+    let cpus = lines.next().map_or(0, |header| header.split_whitespace().count());
+    lines
+        .filter_map(|line| {
+            let (name, rest) = line.split_once(':')?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| SoftIrqStat {
+                name: name.to_string(),
+                per_cpu_counts: take_counts(rest, cpus).0,
+            })
+        })
+        .collect()
+}
+
+/// Up to `cpus` leading numbers of `text`, and what follows them
+fn take_counts(text: &str, cpus: usize) -> (Vec<u64>, &str) {
+    let mut counts = Vec::new();
+    let mut rest = text;
+    while counts.len() < cpus {
+        let trimmed = rest.trim_start();
+        let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+        let Ok(count) = trimmed[..end].parse() else {
+            break;
+        };
+        counts.push(count);
+        rest = &trimmed[end..];
+    }
+    (counts, rest)
+}
+
+/// Every hardware interrupt counted so far, on every CPU
+pub fn total(irqs: &[IrqStat]) -> u64 {
+    irqs.iter().flat_map(|irq| &irq.per_cpu_counts).sum()
+}
+
+/// Hardware interrupts per second between two readings `elapsed` apart.
+/// Counters that went backwards (a CPU went offline) count as 0.
+pub fn rate(before: u64, after: u64, elapsed: Duration) -> u64 {
+    (after.saturating_sub(before) as f64 / elapsed.as_secs_f64().max(f64::EPSILON)).round() as u64
+}
+
+/// One reading of both files
+#[cfg(target_os = "linux")]
+pub async fn read() -> Result<(Vec<IrqStat>, Vec<SoftIrqStat>), ApiError> {
+    let read = |path: &'static str| async move {
+        tokio::fs::read_to_string(path).await.map_err(|err| {
+            tracing::warn!(path, error = %err, "cannot read interrupt counters");
+            ApiError::Internal
+        })
+    };
+    let irqs = parse_proc_interrupts(&read("/proc/interrupts").await?);
+    // /proc/softirqs is missing on kernels older than 2.6.31
+    let softirqs = read("/proc/softirqs").await.map(|text| parse_proc_softirqs(&text)).unwrap_or_default();
+    Ok((irqs, softirqs))
+}
+
+/// macOS keeps interrupt counts in IOKit and Windows in performance counters
+#[cfg(not(target_os = "linux"))]
+pub async fn read() -> Result<(Vec<IrqStat>, Vec<SoftIrqStat>), ApiError> {
+    Err(ApiError::NotImplemented { feature: "Interrupt statistics" })
+}
@@ -0,0 +1,80 @@
+//! Loaded kernel modules: `/proc/modules` on Linux, `kextstat` on macOS
+
+use crate::error::ApiError;
+use crate::models::KernelModule;
+
+/// Lists loaded modules; a kernel built without module support has none
+#[cfg(target_os = "linux")]
+pub async fn list_modules() -> Result<Vec<KernelModule>, ApiError> {
+    match tokio::fs::read_to_string("/proc/modules").await {
+        Ok(text) => Ok(parse_proc_modules(&text)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => {
+            tracing::warn!(error = %err, "cannot read /proc/modules");
+            Err(ApiError::Internal)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub async fn list_modules() -> Result<Vec<KernelModule>, ApiError> {
+    let output = tokio::process::Command::new("kextstat")
+        .arg("-l")
+        .output()
+        .await
+        .map_err(|err| {
+            tracing::warn!(error = %err, "cannot run kextstat");
+            ApiError::Internal
+        })?;
+    Ok(parse_kextstat(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub async fn list_modules() -> Result<Vec<KernelModule>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Kernel module listing" })
+}
+
+/// Parses lines like `nf_tables 286720 3 nft_compat,nft_chain_nat, Live 0x0000000000000000`
+pub fn parse_proc_modules(text: &str) -> Vec<KernelModule> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?.to_string();
+            let size_bytes = fields.next()?.parse().ok()?;
+            let used_by_count = fields.next()?.parse().unwrap_or(0);
+            let used_by = match fields.next()? {
+                "-" => Vec::new(),
+                list => list.split(',').filter(|m| !m.is_empty()).map(String::from).collect(),
+            };
+            let state = fields.next().unwrap_or("Live").to_string();
+            Some(KernelModule {
+                name,
+                size_bytes,
+                used_by_count,
+                used_by,
+                state,
+            })
+        })
+        .collect()
+}
+
+/// Parses `kextstat -l` rows: `Index Refs Address Size Wired Name (Version) ...`
+pub fn parse_kextstat(text: &str) -> Vec<KernelModule> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            let used_by_count = fields[1].parse().ok()?;
+            let size_bytes = u64::from_str_radix(fields[3].trim_start_matches("0x"), 16).ok()?;
+            Some(KernelModule {
+                name: fields[5].to_string(),
+                size_bytes,
+                used_by_count,
+                used_by: Vec::new(),
+                state: "Live".to_string(),
+            })
+        })
+        .collect()
+}
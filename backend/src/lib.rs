@@ -0,0 +1,417 @@
+//! Task Manager Pro backend: router, shared state and handlers.
+//!
+//! `main.rs` only parses configuration and serves the router built here,
+//! so integration tests can drive the exact same stack with `oneshot`.
+
+pub mod alerts;
+pub mod arp;
+pub mod audit;
+pub mod capabilities;
+pub mod client;
+pub mod comm;
+pub mod config;
+pub mod connections;
+pub mod containers;
+pub mod cpucache;
+pub mod cwd;
+pub mod delta;
+pub mod disks;
+pub mod docker;
+pub mod environ;
+pub mod error;
+pub mod exehash;
+pub mod export;
+pub mod exporter;
+pub mod fields;
+pub mod gpu;
+pub mod growth;
+pub mod handlers;
+pub mod history;
+pub mod hwids;
+pub mod interrupts;
+pub mod kernel;
+pub mod limits;
+pub mod logging;
+pub mod maps;
+pub mod metrics;
+pub mod middleware;
+pub mod mock;
+pub mod models;
+pub mod msgpack;
+pub mod mounts;
+pub mod namespaces;
+pub mod netif;
+pub mod oom;
+pub mod openapi;
+pub mod output;
+pub mod pci;
+pub mod policy;
+pub mod poller;
+pub mod pressure;
+pub mod processes;
+pub mod procio;
+pub mod procstat;
+pub mod protection;
+pub mod schedstat;
+pub mod seccomp;
+pub mod services;
+pub mod smaps;
+pub mod smart;
+pub mod shutdown;
+pub mod source;
+pub mod spawn;
+pub mod startup;
+pub mod sysctl;
+pub mod threads;
+pub mod tls;
+pub mod topology;
+pub mod usb;
+pub mod utils;
+pub mod version;
+pub mod vmstat;
+pub mod webhook;
+pub mod wifi;
+pub mod wsstats;
+
+use arc_swap::ArcSwap;
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Request},
+    response::Redirect,
+    routing::{get, post, put},
+    Router,
+};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tower::ServiceBuilder;
+use tower_http::catch_panic::CatchPanicLayer;
+use tower_http::compression::{
+    predicate::{DefaultPredicate, Predicate, SizeAbove},
+    CompressionLayer,
+};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::trace::TraceLayer;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::alerts::AlertEngine;
+use crate::audit::AuditLog;
+use crate::config::{Config, CorsOrigins};
+use crate::delta::{DeltaTracker, CLIENT_ID_HEADER, DELTA_INTERVAL_HEADER};
+use crate::docker::DockerClient;
+use crate::exehash::ExeHashCache;
+use crate::error::handle_panic;
+use crate::handlers::*;
+use crate::growth::MemoryGrowth;
+use crate::history::ProcessHistory;
+use crate::metrics::ServerMetrics;
+use crate::middleware::{
+    AuthLayer, DeprecationLayer, DrainLayer, EtagLayer, MsgpackLayer, RateLimitLayer, RateLimiter, RequestCountLayer, RequestId,
+    RequestLogLayer, DEPRECATION_HEADER, REQUEST_ID_HEADER, RESPONSE_TIME_HEADER,
+};
+use crate::models::SystemStats;
+use crate::openapi::ApiDoc;
+use crate::output::SpawnedProcesses;
+use crate::poller::{OnDemandRefresh, PollerHealth, STATS_AGE_HEADER};
+use crate::procio::IoRateCache;
+use crate::source::{SysinfoSource, SystemSource};
+use crate::webhook::{Webhooks, WEBHOOK_BACKOFF};
+
+/// State shared by every handler.
+///
+/// Axum clones the state for each request, so every field is a cheap handle
+/// (an `Arc` or a type wrapping one) and new shared state goes here as another
+/// such field rather than in a separate `State` layer. System data is reached
+/// through [`SystemSource`], never a raw `sysinfo::System`.
+#[derive(Clone)]
+pub struct AppState {
+    pub source: Arc<dyn SystemSource>,
+    pub config: Arc<Config>,
+    /// Latest stats published by the background poller
+    pub stats: Arc<ArcSwap<SystemStats>>,
+    /// Cancelled when shutdown begins; stops the poller and turns new requests away
+    pub shutdown: CancellationToken,
+    /// Background workers; `main` waits for them to flush after the server stops
+    pub tasks: TaskTracker,
+    /// Record of kills, suspends and resumes
+    pub audit: AuditLog,
+    /// Per-client baselines for `/api/stats?mode=delta`
+    pub deltas: DeltaTracker,
+    /// Executable checksums for `/api/process/:pid/info`
+    pub exe_hashes: ExeHashCache,
+    /// Previous `/api/process/:pid/io` readings, for rates
+    pub io_samples: IoRateCache,
+    /// Docker Engine API client; `None` when the integration is disabled
+    pub docker: Option<DockerClient>,
+    /// Firing and resolved threshold alerts, updated by the poller
+    pub alerts: AlertEngine,
+    /// Each live process's recent CPU and memory, appended by the poller
+    pub process_history: ProcessHistory,
+    /// Per-minute memory of large processes, appended by the poller
+    pub memory_growth: MemoryGrowth,
+    /// Alert webhook client and delivery results
+    pub webhooks: Webhooks,
+    /// When the poller last published `stats`, and whether it is running
+    pub poller_health: PollerHealth,
+    /// Refreshes `stats` for `/api/stats?max_age_ms=` between poller ticks
+    pub stats_refresh: OnDemandRefresh,
+    /// Processes started by `/api/process/start`, whose output can be streamed
+    pub spawned: SpawnedProcesses,
+    /// The backend's own request and WebSocket counters, for `/api/self`
+    pub metrics: ServerMetrics,
+    /// Prometheus collectors served at `/metrics`; features register theirs here
+    pub registry: Arc<prometheus::Registry>,
+}
+
+impl AppState {
+    /// State backed by the real machine
+    pub fn new(config: Config) -> Self {
+        let source = SysinfoSource::with_gpu_cooldown(config.gpu_cooldown);
+        Self::with_source(config, Arc::new(source))
+    }
+
+    /// Takes an initial stats sample so handlers never see an empty snapshot
+    pub fn with_source(config: Config, source: Arc<dyn SystemSource>) -> Self {
+        let stats = Arc::new(ArcSwap::from_pointee(source.snapshot_stats()));
+        let poller_health = PollerHealth::new(Instant::now());
+        let docker = config.docker.enabled.then(|| DockerClient::new(&config.docker.socket));
+        let mut alerts = AlertEngine::new(config.alert_rules.clone(), config.alert_webhook.clone());
+        if let Some(path) = &config.persist_rules {
+            alerts = alerts.persisting_to(path.clone());
+        }
+        let webhooks = Webhooks::new(&alerts.webhook_targets(), WEBHOOK_BACKOFF);
+        let metrics = ServerMetrics::default();
+        let registry = Arc::new(exporter::registry(stats.clone(), metrics.clone()));
+        Self {
+            source,
+            config: Arc::new(config),
+            stats,
+            shutdown: CancellationToken::new(),
+            tasks: TaskTracker::new(),
+            audit: AuditLog::default(),
+            deltas: DeltaTracker::default(),
+            exe_hashes: ExeHashCache::default(),
+            io_samples: IoRateCache::default(),
+            docker,
+            alerts,
+            process_history: ProcessHistory::default(),
+            memory_growth: MemoryGrowth::default(),
+            webhooks,
+            poller_health,
+            stats_refresh: OnDemandRefresh::default(),
+            spawned: SpawnedProcesses::default(),
+            metrics,
+            registry,
+        }
+    }
+
+    /// Starts refreshing `stats` every `config.poll_interval` until shutdown
+    pub fn spawn_poller(&self) -> JoinHandle<()> {
+        self.tasks.spawn(poller::poll_stats(
+            self.source.clone(),
+            self.stats.clone(),
+            self.alerts.clone(),
+            self.process_history.clone(),
+            self.memory_growth.clone(),
+            self.poller_health.clone(),
+            self.config.poll_interval,
+            self.shutdown.clone(),
+        ))
+    }
+    
+    /// Starts appending audit entries to `config.audit_log`, if set
+    pub fn spawn_audit_writer(&self) -> Option<JoinHandle<()>> {
+        let path = self.config.audit_log.clone()?;
+        Some(self.tasks.spawn(self.audit.writer(path, self.shutdown.clone())))
+    }
+
+    /// Starts POSTing alert transitions. Runs even when no rule has a webhook
+    /// yet, as one can be added through `/api/alert_rules`.
+    pub fn spawn_webhook_sender(&self) -> JoinHandle<()> {
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
+        let events = self.alerts.subscribe();
+        self.tasks.spawn(self.webhooks.clone().deliver(events, hostname, self.shutdown.clone()))
+    }
+}
+
+/// Versions mounted under `/api/<version>`, as advertised by `/health`. A
+/// version is added here once a response shape it changes is mounted for it.
+pub const API_VERSIONS: [&str; 1] = ["v1"];
+
+/// Where `/stats` ends up once nested
+const STATS_ROUTES: [&str; 2] = ["/api/v1/stats", "/api/stats"];
+
+/// Where `/auth/token` ends up once nested; [`AuthLayer`] lets exactly these
+/// through without a token
+pub const AUTH_TOKEN_ROUTES: [&str; 2] = ["/api/v1/auth/token", "/api/auth/token"];
+
+/// Every `/api` route, relative to the version prefix it is nested under
+fn api_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        // Negotiated inside the ETag so each representation gets its own tag;
+        // the router-wide MsgpackLayer skips STATS_ROUTES
+        .route("/stats", get(get_stats).layer(ServiceBuilder::new().layer(EtagLayer).layer(MsgpackLayer::default())))
+        .route("/ws/stats/binary", get(get_stats_binary_ws))
+        .route("/cpu/info", get(get_cpu_info))
+        .route("/processes", get(get_processes))
+        .route("/processes/export", get(export_processes))
+        .route("/processes/growth", get(get_memory_growth))
+        .route("/processes/batch-kill", post(batch_kill))
+        .route("/processes/kill_by_name", post(kill_by_name))
+        .route("/process-groups", get(get_process_groups))
+        .route("/process-group/:pgid/kill", post(kill_process_group))
+        .route("/apps", get(get_apps))
+        .route("/users", get(get_users))
+        .route("/self", get(get_self))
+        .route("/version", get(get_version))
+        .route("/auth/token", get(get_auth_token))
+        .route("/containers", get(get_containers))
+        .route("/container/:id/stop", post(stop_container))
+        .route("/disks/:device/smart", get(get_disk_smart))
+        .route("/disks/:device/scheduler", post(set_disk_scheduler))
+        .route("/hardware/pci", get(get_pci_devices))
+        .route("/hardware/usb", get(get_usb_devices))
+        .route("/kernel/modules", get(get_kernel_modules))
+        .route("/kernel/interrupts", get(get_kernel_interrupts))
+        .route("/kernel/params", get(get_kernel_params).post(set_kernel_param))
+        .route("/mounts", get(get_mounts))
+        .route("/network/connections", get(get_connections))
+        .route("/network/arp", get(get_arp))
+        .route("/audit", get(get_audit))
+        .route("/alerts", get(get_alerts))
+        .route("/alerts/history", get(get_alert_history))
+        .route("/alerts/webhooks/status", get(get_webhook_status))
+        .route("/alert_rules", get(get_alert_rules).post(create_alert_rule))
+        .route("/alert_rules/:id", put(update_alert_rule).delete(delete_alert_rule))
+        .route("/policy", get(get_policy))
+        .route("/services", get(get_services))
+        .route("/services/:name/start", post(start_service))
+        .route("/services/:name/stop", post(stop_service))
+        .route("/services/:name/restart", post(restart_service))
+        // Singular spelling of the three above
+        .route("/service/:name/start", post(start_service))
+        .route("/service/:name/stop", post(stop_service))
+        .route("/service/:name/restart", post(restart_service))
+        .route("/startup", get(get_startup))
+        .route("/app/close", post(kill_app))
+        .route("/app/suspend", post(suspend_app))
+        .route("/app/resume", post(resume_app))
+        .route("/process/start", post(start_process))
+        .route("/process/launch", post(launch_process))
+        .route("/process/:pid/kill", post(kill_process))
+        .route("/process/:pid/restart", post(restart_process))
+        .route("/process/:pid/suspend", post(suspend_process))
+        .route("/process/:pid/resume", post(resume_process))
+        .route("/process/:pid/oom_adj", post(set_oom_adj))
+        .route("/process/:pid/rename", post(rename_process))
+        .route("/process/:pid/info", get(get_process_info))
+        .route("/process/:pid/children", get(get_process_children))
+        .route("/process/:pid/maps", get(get_process_maps))
+        .route("/process/:pid/threads", get(get_process_threads))
+        .route("/process/:pid/io", get(get_process_io))
+        .route("/process/:pid/limits", get(get_process_limits))
+        .route("/process/:pid/capabilities", get(get_process_capabilities))
+        .route("/process/:pid/history", get(get_process_history))
+        .route("/process/:pid/connections", get(get_process_connections))
+        // Environments hold secrets, so this read needs the token regardless of protect_reads
+        .route(
+            "/process/:pid/environ",
+            get(get_process_environ).layer(AuthLayer::new(state.config.auth.token.as_deref(), true)),
+        )
+        // So can output, for the same reason
+        .route(
+            "/ws/process/:pid/output",
+            get(get_process_output_ws).layer(AuthLayer::new(state.config.auth.token.as_deref(), true)),
+        )
+}
+
+/// Other paths the spec and Swagger UI are known by, redirected to the one
+/// place they are served
+const DOCS_REDIRECTS: [(&str, &str); 3] =
+    [("/docs", "/swagger-ui/"), ("/docs/", "/swagger-ui/"), ("/api/openapi.json", "/openapi.json")];
+
+pub fn build_router(state: AppState) -> Router {
+    let allow_origin = match &state.config.cors_origins {
+        CorsOrigins::Any => AllowOrigin::any(),
+        CorsOrigins::List(origins) => AllowOrigin::list(
+            origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        ),
+    };
+    let cors = CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(Any)
+        .allow_headers(Any)
+        .expose_headers([
+            REQUEST_ID_HEADER.clone(),
+            RESPONSE_TIME_HEADER.clone(),
+            CLIENT_ID_HEADER.clone(),
+            DELTA_INTERVAL_HEADER.clone(),
+            STATS_AGE_HEADER.clone(),
+            DEPRECATION_HEADER.clone(),
+            header::LINK,
+            header::RETRY_AFTER,
+        ]);
+    
+    let compression_config = state.config.compression.clone();
+    let metrics = state.metrics.clone();
+    let drain = DrainLayer::new(state.shutdown.clone());
+    let auth = AuthLayer::new(state.config.auth.token.as_deref(), state.config.auth.protect_reads)
+        .read_only(state.config.auth.read_only);
+    
+    let api = api_routes(&state);
+    let router = Router::new()
+        .route("/health", get(health_check))
+        .route("/metrics", get(get_metrics))
+        .nest("/api/v1", api.clone())
+        .nest("/api", api.layer(DeprecationLayer::new("/api/v1")))
+        .route_layer(auth);
+    // Outside auth so that wrong-token attempts count against the limit too
+    let router = match state.config.rate_limit {
+        Some(limit) => router.route_layer(RateLimitLayer::new(RateLimiter::new(limit.per_second, limit.burst))),
+        None => router,
+    };
+    // Outermost route layer, so refused requests are counted as well
+    let router = router.route_layer(RequestCountLayer::new(state.metrics.clone()));
+    let router = DOCS_REDIRECTS.into_iter().fold(router, |router, (from, to)| {
+        router.route(from, get(move || async move { Redirect::permanent(to) }))
+    });
+    let router = router
+        .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", ApiDoc::openapi()))
+        .with_state(state)
+        // Skips `/stats`, already negotiated inside its ETag layer
+        .layer(MsgpackLayer::except(&STATS_ROUTES));
+    
+    // gzip + br, skipped for small bodies; CORS stays outermost so preflights never hit it
+    let router = if compression_config.enabled {
+        router.layer(
+            CompressionLayer::new()
+                .gzip(true)
+                .br(true)
+                .compress_when(DefaultPredicate::new().and(SizeAbove::new(compression_config.min_size))),
+        )
+    } else {
+        router
+    };
+    
+    // Span only: RequestLogLayer already writes the one-line summary per request
+    let trace = TraceLayer::new_for_http()
+        .make_span_with(|req: &Request<Body>| {
+            let request_id = req.extensions().get::<RequestId>().map(|id| id.0.as_str()).unwrap_or_default();
+            tracing::info_span!("request", method = %req.method(), path = %req.uri().path(), request_id)
+        })
+        .on_request(())
+        .on_response(());
+    
+    router
+        .layer(CatchPanicLayer::custom(move |payload| {
+            metrics.record_panic();
+            handle_panic(payload)
+        }))
+        .layer(drain)
+        .layer(cors)
+        .layer(trace)
+        .layer(RequestLogLayer)
+}
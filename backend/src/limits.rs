@@ -0,0 +1,157 @@
+//! Resource limits (`ulimit`) of one process.
+//!
+//! On Linux `/proc/<pid>/limits` is world-readable, so any user can see any
+//! process's limits. macOS has no equivalent for other processes:
+//! `getrlimit` only answers for the backend itself, and `proc_pid_rusage`
+//! gives any process's peak memory.
+
+use crate::error::ApiError;
+use crate::models::{ProcessResourceLimits, ResourceLimit};
+
+/// `/proc/<pid>/limits` row names and their `ulimit`/`prlimit` keys
+const RESOURCES: &[(&str, &str)] = &[
+    ("Max cpu time", "cpu"),
+    ("Max file size", "fsize"),
+    ("Max data size", "data"),
+    ("Max stack size", "stack"),
+    ("Max core file size", "core"),
+    ("Max resident set", "rss"),
+    ("Max processes", "nproc"),
+    ("Max open files", "nofile"),
+    ("Max locked memory", "memlock"),
+    ("Max address space", "as"),
+    ("Max file locks", "locks"),
+    ("Max pending signals", "sigpending"),
+    ("Max msgqueue size", "msgqueue"),
+    ("Max nice priority", "nice"),
+    ("Max realtime priority", "rtprio"),
+    ("Max realtime timeout", "rttime"),
+];
+
+pub async fn read(pid: u32) -> Result<ProcessResourceLimits, ApiError> {
+    tokio::task::spawn_blocking(move || read_blocking(pid))
+        .await
+        .map_err(|_| ApiError::Internal)?
+}
+
+#[cfg(target_os = "linux")]
+fn read_blocking(pid: u32) -> Result<ProcessResourceLimits, ApiError> {
+    let text = std::fs::read_to_string(format!("/proc/{}/limits", pid)).map_err(|err| match err.kind() {
+        // The file arrived in Linux 2.6.24
+        std::io::ErrorKind::NotFound if std::path::Path::new(&format!("/proc/{}", pid)).exists() => {
+            ApiError::NotImplemented { feature: "/proc/<pid>/limits (Linux 2.6.24 or later)" }
+        }
+        std::io::ErrorKind::NotFound => ApiError::ProcessNotFound { pid },
+        std::io::ErrorKind::PermissionDenied => ApiError::PermissionDenied { pid },
+        _ => {
+            tracing::warn!(pid, error = %err, "cannot read process limits");
+            ApiError::Internal
+        }
+    })?;
+    // Only the owner and root may list the descriptors
+    let open_files = std::fs::read_dir(format!("/proc/{}/fd", pid)).ok().map(|fds| fds.count() as u64);
+    Ok(ProcessResourceLimits {
+        pid,
+        limits: parse_proc_limits(&text),
+        open_files,
+        max_rss_bytes: None,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn read_blocking(pid: u32) -> Result<ProcessResourceLimits, ApiError> {
+    // SAFETY: rusage_info_v4 is plain old data, valid when zeroed
+    let mut info: libc::rusage_info_v4 = unsafe { std::mem::zeroed() };
+    // SAFETY: `info` is the struct the V4 flavour writes
+    let result = unsafe {
+        libc::proc_pid_rusage(pid as libc::c_int, libc::RUSAGE_INFO_V4, &mut info as *mut _ as *mut libc::rusage_info_t)
+    };
+    if result != 0 {
+        return Err(match std::io::Error::last_os_error().raw_os_error() {
+            Some(libc::ESRCH) => ApiError::ProcessNotFound { pid },
+            _ => ApiError::PermissionDenied { pid },
+        });
+    }
+    let limits = if pid == std::process::id() { own_limits() } else { Vec::new() };
+    Ok(ProcessResourceLimits {
+        pid,
+        limits,
+        open_files: None,
+        max_rss_bytes: Some(info.ri_lifetime_max_phys_footprint),
+    })
+}
+
+/// The backend's own limits from `getrlimit`, which can't ask about other processes
+#[cfg(target_os = "macos")]
+fn own_limits() -> Vec<ResourceLimit> {
+    [
+        (libc::RLIMIT_CPU, "cpu", "seconds"),
+        (libc::RLIMIT_FSIZE, "fsize", "bytes"),
+        (libc::RLIMIT_DATA, "data", "bytes"),
+        (libc::RLIMIT_STACK, "stack", "bytes"),
+        (libc::RLIMIT_CORE, "core", "bytes"),
+        (libc::RLIMIT_RSS, "rss", "bytes"),
+        (libc::RLIMIT_NPROC, "nproc", "processes"),
+        (libc::RLIMIT_NOFILE, "nofile", "files"),
+        (libc::RLIMIT_MEMLOCK, "memlock", "bytes"),
+        (libc::RLIMIT_AS, "as", "bytes"),
+    ]
+    .into_iter()
+    .filter_map(|(resource, key, unit)| {
+        // SAFETY: rlimit is plain old data, valid when zeroed
+        let mut limit: libc::rlimit = unsafe { std::mem::zeroed() };
+        // SAFETY: `limit` is a valid out-pointer
+        if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+            return None;
+        }
+        let value = |v: libc::rlim_t| (v != libc::RLIM_INFINITY).then_some(v);
+        let name = RESOURCES.iter().find(|(_, k)| *k == key).map_or(key, |(name, _)| *name);
+        Some(ResourceLimit {
+            resource: key.to_string(),
+            name: name.to_string(),
+            soft: value(limit.rlim_cur),
+            hard: value(limit.rlim_max),
+            unit: Some(unit.to_string()),
+        })
+    })
+    .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn read_blocking(_pid: u32) -> Result<ProcessResourceLimits, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Process resource limits" })
+}
+
+/// Parses the fixed-width table of `/proc/<pid>/limits`, slicing each row at
+/// the header's column positions. `unlimited` becomes `None`.
+pub fn parse_proc_limits(text: &str) -> Vec<ResourceLimit> {
+    let mut lines = text.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let (Some(soft_at), Some(hard_at), Some(units_at)) =
+        (header.find("Soft Limit"), header.find("Hard Limit"), header.find("Units"))
+    else {
+        return Vec::new();
+    };
+    let column = |line: &str, from: usize, to: usize| line.get(from..to.min(line.len())).unwrap_or("").trim().to_string();
+    let value = |text: String| if text == "unlimited" { None } else { text.parse().ok() };
+
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let name = column(line, 0, soft_at);
+            let unit = column(line, units_at, line.len());
+            ResourceLimit {
+                resource: RESOURCES
+                    .iter()
+                    .find(|(row, _)| *row == name)
+                    .map_or_else(|| name.to_ascii_lowercase().replace(' ', "_"), |(_, key)| key.to_string()),
+                soft: value(column(line, soft_at, hard_at)),
+                hard: value(column(line, hard_at, units_at)),
+                unit: (!unit.is_empty()).then_some(unit),
+                name,
+            }
+        })
+        .collect()
+}
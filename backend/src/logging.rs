@@ -0,0 +1,47 @@
+//! Structured logging to stdout
+
+use tracing_subscriber::EnvFilter;
+
+use crate::config::LogFormat;
+
+/// Installs the global subscriber and sends panics through it. `RUST_LOG`
+/// wins over `level` when set; both take `tracing` filter directives.
+pub fn init(level: &str, format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(level))
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    
+    match format {
+        // The per-request span (method, path, request_id) is attached as `span`
+        LogFormat::Json => subscriber
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .init(),
+        LogFormat::Text => subscriber.init(),
+    }
+    install_panic_hook();
+}
+
+/// Logs each panic as an `error` event with its message, location and
+/// backtrace, in place of the default stderr report. The hook runs on the
+/// panicking thread, so a handler panic carries its request's span and ID.
+fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("non-string panic payload");
+        let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        tracing::error!(
+            panic = message,
+            location = location.as_deref().unwrap_or("unknown"),
+            backtrace = %std::backtrace::Backtrace::force_capture(),
+            "panic"
+        );
+    }));
+}
@@ -1,576 +1,132 @@
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    routing::{get, post},
-    Json, Router,
-};
-use serde::Serialize;
-use std::collections::HashMap;
+use clap::Parser;
+use std::future::{Future, IntoFuture};
+use std::io::ErrorKind;
 use std::net::SocketAddr;
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-use sysinfo::{Pid, System};
-use tower_http::cors::{Any, CorsLayer};
-use nvml_wrapper::Nvml;
-
-// DATA STRUCTURES matching Python backend exactly
-
-#[derive(Serialize, Clone)]
-struct SystemStats {
-    timestamp: String,
-    cpu: CPUStats,
-    memory: MemoryStats,
-    disk: DiskStats,
-    network: NetworkStats,
-    system: SystemInfo,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    gpu: Option<GPUStats>,
-}
-
-#[derive(Serialize, Clone)]
-struct CPUStats {
-    percent: f32,
-    cores: CPUCores,
-    per_core: Vec<f32>,
-}
-
-#[derive(Serialize, Clone)]
-struct CPUCores {
-    physical: usize,
-    logical: usize,
-}
-
-#[derive(Serialize, Clone)]
-struct MemoryStats {
-    total: u64,
-    available: u64,
-    used: u64,
-    percent: f32,
-    total_formatted: String,
-    used_formatted: String,
-}
-
-#[derive(Serialize, Clone)]
-struct DiskStats {
-    total: u64,
-    used: u64,
-    free: u64,
-    percent: f32,
-    total_formatted: String,
-    used_formatted: String,
-}
-
-#[derive(Serialize, Clone)]
-struct NetworkStats {
-    bytes_sent: u64,
-    bytes_recv: u64,
-    bytes_sent_formatted: String,
-    bytes_recv_formatted: String,
-}
-
-#[derive(Serialize, Clone)]
-struct SystemInfo {
-    os: String,
-    uptime_seconds: u64,
-}
-
-#[derive(Serialize, Clone)]
-struct GPUStats {
-    name: String,
-    load: f32,
-    memory_used: u64,
-    memory_total: u64,
-    memory_percent: f32,
-    memory_used_formatted: String,
-    memory_total_formatted: String,
-    temperature: Option<f32>,
-}
-
-#[derive(Serialize, Clone)]
-struct ProcessData {
-    pid: u32,
-    name: String,
-    username: String,
-    cpu_percent: f32,
-    memory_percent: f32,
-    memory_mb: f64,
-    status: String,
-    num_threads: usize,
-    create_time: u64,
-    exe: String,
-    cwd: String,
-    cmdline: Vec<String>,
-    is_protected: bool,
-}
-
-#[derive(Serialize)]
-struct ProcessListResponse {
-    processes: Vec<ProcessData>,
-    total_count: usize,
-}
-
-#[derive(Serialize)]
-struct AppGroup {
-    name: String,
-    pids: Vec<u32>,
-    cpu_percent: f32,
-    memory_mb: f64,
-    memory_percent: f32,
-    status: String,
-    process_count: usize,
-    exe: String,
-    is_closeable: bool,
-}
-
-#[derive(Serialize)]
-struct AppsListResponse {
-    apps: Vec<AppGroup>,
-    total_count: usize,
-}
-
-#[derive(Serialize)]
-struct DetailedProcessInfo {
-    pid: u32,
-    name: String,
-    status: String,
-    username: String,
-    create_time: u64,
-    cpu_percent: f32,
-    memory_info: ProcessMemoryInfo,
-    num_threads: usize,
-    exe: String,
-    cwd: String,
-    cmdline: String,
-    connections: usize,
-    open_files: usize,
-}
-
-#[derive(Serialize)]
-struct ProcessMemoryInfo {
-    rss: u64,
-    vms: u64,
-    rss_formatted: String,
-    vms_formatted: String,
-}
-
-#[derive(Serialize)]
-struct SuccessResponse {
-    success: bool,
-    message: String,
-}
-
-// UTILITY FUNCTIONS
+use std::time::Duration;
+use task_manager_backend::{
+    build_router,
+    config::{Cli, Config, CorsOrigins},
+    logging, shutdown, tls, AppState,
+};
+use tokio_util::sync::CancellationToken;
 
-fn format_bytes(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    if bytes == 0 {
-        return "0 B".to_string();
-    }
-    
-    let size = bytes as f64;
-    let base = 1024_f64;
-    let i = (size.ln() / base.ln()).floor() as usize;
-    let i = i.min(UNITS.len() - 1);
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let config = Config::load(&cli).unwrap_or_else(|err| {
+        eprintln!("error: {err}");
+        std::process::exit(2);
+    });
+    logging::init(&config.log_level, config.log_format);
     
-    let value = size / base.powi(i as i32);
-    format!("{:.1} {}", value, UNITS[i])
-}
-
-fn get_process_status(status: sysinfo::ProcessStatus) -> String {
-    match status {
-        sysinfo::ProcessStatus::Run => "running".to_string(),
-        sysinfo::ProcessStatus::Sleep => "sleeping".to_string(),
-        sysinfo::ProcessStatus::Stop => "stopped".to_string(),
-        sysinfo::ProcessStatus::Zombie => "zombie".to_string(),
-        sysinfo::ProcessStatus::Dead => "dead".to_string(),
-        _ => "unknown".to_string(),
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "Task Manager Pro Backend (Rust + Axum) starting");
+    if let Some(token) = &config.auth.token {
+        tracing::info!(token = %token, protect_reads = config.auth.protect_reads, "API token required for mutating requests");
     }
-}
-
-fn get_gpu_stats() -> Option<GPUStats> {
-    match Nvml::init() {
-        Ok(nvml) => {
-            if let Ok(device) = nvml.device_by_index(0) {
-                let name = device.name().unwrap_or_else(|_| "Unknown GPU".to_string());
-                let memory_info = device.memory_info().ok()?;
-                let utilization = device.utilization_rates().ok()?;
-                let temperature = device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu)
-                    .ok()
-                    .map(|t| t as f32);
-                
-                let memory_used = memory_info.used;
-                let memory_total = memory_info.total;
-                let memory_percent = (memory_used as f64 / memory_total as f64 * 100.0) as f32;
-                
-                Some(GPUStats {
-                    name,
-                    load: utilization.gpu as f32,
-                    memory_used,
-                    memory_total,
-                    memory_percent,
-                    memory_used_formatted: format_bytes(memory_used),
-                    memory_total_formatted: format_bytes(memory_total),
-                    temperature,
-                })
-            } else {
-                None
-            }
-        }
-        Err(_) => None,
+    if config.cors_origins == CorsOrigins::Any {
+        tracing::warn!("CORS allows any origin: every website open in a browser can call this API, and /api/auth/token stays closed");
+    }
+    if config.auth.read_only {
+        tracing::info!("read-only mode: mutating requests are rejected");
+    }
+    match config.rate_limit {
+        Some(limit) => tracing::info!(per_second = limit.per_second, burst = limit.burst, "rate limiting mutating requests per client IP"),
+        None => tracing::warn!("rate limiting disabled"),
+    }
+    if let Some(path) = &config.audit_log {
+        tracing::info!(path = %path.display(), "audit log file enabled");
+    }
+    if let Some(path) = &config.persist_rules {
+        tracing::info!(path = %path.display(), rules = config.alert_rules.len(), "alert rules saved to file");
+    }
+    if config.compression.enabled {
+        tracing::info!(min_size = config.compression.min_size, "response compression enabled");
     }
-}
-
-// HANDLERS
-
-async fn health_check() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "status": "ok",
-        "message": "Rust backend is running!",
-        "version": "2.0.0"
-    }))
-}
-
-async fn get_stats(State(sys): State<Arc<tokio::sync::Mutex<System>>>) -> Json<SystemStats> {
-    let mut sys = sys.lock().await;
-    
-    sys.refresh_memory();
-    sys.refresh_cpu_all();
-    
-    let cpu_usage = sys.global_cpu_usage();
-    let cpus = sys.cpus();
-    let per_core: Vec<f32> = cpus.iter().map(|cpu| cpu.cpu_usage()).collect();
-    
-    let used_memory = sys.used_memory();
-    let total_memory = sys.total_memory();
-    let available_memory = sys.available_memory();
-    let memory_percent = (used_memory as f64 / total_memory as f64 * 100.0) as f32;
     
-    // Get disk stats
-    let disks = sysinfo::Disks::new_with_refreshed_list();
-    let (total_disk, used_disk) = disks.iter().fold((0u64, 0u64), |(t, u), disk| {
-        (t + disk.total_space(), u + (disk.total_space() - disk.available_space()))
-    });
-    let disk_percent = if total_disk > 0 {
-        (used_disk as f64 / total_disk as f64 * 100.0) as f32
-    } else {
-        0.0
+    let addr = config.bind_addr();
+    let tls_config = config.tls.clone();
+    let drain_timeout = config.drain_timeout;
+    let state = AppState::new(config);
+    let shutdown = state.shutdown.clone();
+    let tasks = state.tasks.clone();
+    state.spawn_poller();
+    state.spawn_audit_writer();
+    state.spawn_webhook_sender();
+    shutdown::spawn_signal_handler(shutdown.clone(), drain_timeout);
+    let app = build_router(state);
+    
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .unwrap_or_else(|err| exit_on_bind_error(addr, err));
+    
+    let Some(tls_config) = tls_config else {
+        tracing::info!(%addr, "server listening");
+        let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned());
+        serve_until_drained(server.into_future(), addr, &shutdown, drain_timeout).await;
+        shutdown::finish_tasks(&tasks, drain_timeout).await;
+        tracing::info!("shutdown complete");
+        return;
     };
     
-    // Get network stats
-    let networks = sysinfo::Networks::new_with_refreshed_list();
-    let (bytes_sent, bytes_recv) = networks.iter().fold((0u64, 0u64), |(s, r), (_name, network)| {
-        (s + network.total_transmitted(), r + network.total_received())
+    let rustls = tls::load(&tls_config).unwrap_or_else(|err| {
+        eprintln!("error: {err}");
+        std::process::exit(1);
     });
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-        .to_string();
-    
-    Json(SystemStats {
-        timestamp,
-        cpu: CPUStats {
-            percent: cpu_usage,
-            cores: CPUCores {
-                physical: cpus.len(),
-                logical: cpus.len(),
-            },
-            per_core,
-        },
-        memory: MemoryStats {
-            total: total_memory,
-            available: available_memory,
-            used: used_memory,
-            percent: memory_percent,
-            total_formatted: format_bytes(total_memory),
-            used_formatted: format_bytes(used_memory),
-        },
-        disk: DiskStats {
-            total: total_disk,
-            used: used_disk,
-            free: total_disk - used_disk,
-            percent: disk_percent,
-            total_formatted: format_bytes(total_disk),
-            used_formatted: format_bytes(used_disk),
-        },
-        network: NetworkStats {
-            bytes_sent,
-            bytes_recv,
-            bytes_sent_formatted: format_bytes(bytes_sent),
-            bytes_recv_formatted: format_bytes(bytes_recv),
-        },
-        system: SystemInfo {
-            os: std::env::consts::OS.to_string(),
-            uptime_seconds: System::uptime(),
-        },
-        gpu: get_gpu_stats(),
-    })
-}
-
-async fn get_processes(State(sys): State<Arc<tokio::sync::Mutex<System>>>) -> Json<ProcessListResponse> {
-    let mut sys_guard = sys.lock().await;
-    
-    // Refresh processes twice with a small delay for accurate CPU readings
-    sys_guard.refresh_processes_specifics(
-        sysinfo::ProcessesToUpdate::All, 
-        true, 
-        sysinfo::ProcessRefreshKind::everything()
+    tls::spawn_cert_reloader(rustls.clone(), tls_config.clone(), tls::CERT_RELOAD_INTERVAL);
+    
+    let tls_addr = SocketAddr::new(addr.ip(), tls_config.port);
+    tracing::info!(%addr, "redirecting HTTP to HTTPS");
+    tokio::spawn(
+        axum::serve(listener, tls::redirect_router(tls_config.port))
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+            .into_future(),
     );
     
-    drop(sys_guard);
-    tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
-    let mut sys_guard = sys.lock().await;
-    
-    sys_guard.refresh_processes_specifics(
-        sysinfo::ProcessesToUpdate::All, 
-        true, 
-        sysinfo::ProcessRefreshKind::everything()
-    );
-    
-    let total_memory = sys_guard.total_memory() as f64;
-    let num_cpus = sys_guard.cpus().len() as f32;
-    
-    let mut processes: Vec<ProcessData> = sys_guard
-        .processes()
-        .iter()
-        .map(|(pid, process)| {
-            let memory = process.memory();
-            let memory_mb = memory as f64 / (1024.0 * 1024.0);
-            let memory_percent = (memory as f64 / total_memory * 100.0) as f32;
-            
-            // Divide by CPU count to match Windows Task Manager behavior
-            let cpu_percent = process.cpu_usage() / num_cpus;
-            
-            ProcessData {
-                pid: pid.as_u32(),
-                name: process.name().to_string_lossy().to_string(),
-                username: "N/A".to_string(),
-                cpu_percent,
-                memory_percent,
-                memory_mb,
-                status: get_process_status(process.status()),
-                num_threads: 0,
-                create_time: process.start_time(),
-                exe: process.exe().map(|p| p.display().to_string()).unwrap_or_else(|| "N/A".to_string()),
-                cwd: process.cwd().map(|p| p.display().to_string()).unwrap_or_else(|| "N/A".to_string()),
-                cmdline: process.cmd()
-                    .iter()
-                    .map(|s| s.to_string_lossy().to_string())
-                    .collect(),
-                is_protected: false,
-            }
-        })
-        .collect();
-    
-    processes.sort_by(|a, b| b.cpu_percent.partial_cmp(&a.cpu_percent).unwrap());
-    
-    let total_count = processes.len();
-    
-    Json(ProcessListResponse {
-        processes,
-        total_count,
-    })
-}
-
-async fn get_apps(State(sys): State<Arc<tokio::sync::Mutex<System>>>) -> Json<AppsListResponse> {
-    let mut sys_guard = sys.lock().await;
-    
-    // Refresh processes twice with a small delay for accurate CPU readings
-    sys_guard.refresh_processes_specifics(
-        sysinfo::ProcessesToUpdate::All, 
-        true, 
-        sysinfo::ProcessRefreshKind::everything()
-    );
-    
-    drop(sys_guard);
-    tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
-    let mut sys_guard = sys.lock().await;
-    
-    sys_guard.refresh_processes_specifics(
-        sysinfo::ProcessesToUpdate::All, 
-        true, 
-        sysinfo::ProcessRefreshKind::everything()
-    );
-    
-    let mut apps: HashMap<String, AppGroup> = HashMap::new();
-    let total_memory = sys_guard.total_memory() as f64;
-    let num_cpus = sys_guard.cpus().len() as f32;
-    
-    for (pid, process) in sys_guard.processes() {
-        let name = process.name().to_string_lossy().to_string();
-        let memory = process.memory();
-        let memory_mb = memory as f64 / (1024.0 * 1024.0);
-        let memory_percent = (memory as f64 / total_memory * 100.0) as f32;
-        
-        // Divide by CPU count to match Windows Task Manager behavior
-        let cpu = process.cpu_usage() / num_cpus;
-        let exe = process.exe().map(|p| p.display().to_string()).unwrap_or_else(|| "N/A".to_string());
-        
-        apps.entry(name.clone())
-            .and_modify(|app| {
-                app.pids.push(pid.as_u32());
-                app.cpu_percent += cpu;
-                app.memory_mb += memory_mb;
-                app.memory_percent += memory_percent;
-                app.process_count += 1;
-            })
-            .or_insert_with(|| AppGroup {
-                name: name.clone(),
-                pids: vec![pid.as_u32()],
-                cpu_percent: cpu,
-                memory_mb,
-                memory_percent,
-                status: "running".to_string(),
-                process_count: 1,
-                exe,
-                is_closeable: true,
-            });
-    }
-    
-    let mut app_list: Vec<AppGroup> = apps.into_values().collect();
-    app_list.sort_by(|a, b| b.cpu_percent.partial_cmp(&a.cpu_percent).unwrap());
-    
-    let total_count = app_list.len();
-    
-    Json(AppsListResponse {
-        apps: app_list,
-        total_count,
-    })
-}
-
-async fn kill_process(
-    Path(pid): Path<u32>,
-    State(sys): State<Arc<tokio::sync::Mutex<System>>>
-) -> Result<Json<SuccessResponse>, StatusCode> {
-    let sys = sys.lock().await;
-    
-    if let Some(process) = sys.process(Pid::from_u32(pid)) {
-        if process.kill() {
-            Ok(Json(SuccessResponse {
-                success: true,
-                message: format!("Process {} terminated", process.name().to_string_lossy()),
-            }))
-        } else {
-            Err(StatusCode::FORBIDDEN)
+    let handle = axum_server::Handle::new();
+    tokio::spawn({
+        let handle = handle.clone();
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown.cancelled().await;
+            handle.graceful_shutdown(None);
         }
-    } else {
-        Err(StatusCode::NOT_FOUND)
-    }
-}
-
-async fn kill_app(
-    State(sys): State<Arc<tokio::sync::Mutex<System>>>,
-    Json(pids): Json<Vec<u32>>
-) -> Result<Json<SuccessResponse>, StatusCode> {
-    let sys = sys.lock().await;
-    
-    let mut killed_count = 0;
+    });
     
-    for pid in pids {
-        if let Some(process) = sys.process(Pid::from_u32(pid)) {
-            if process.kill() {
-                killed_count += 1;
+    tracing::info!(addr = %tls_addr, "server listening (HTTPS)");
+    let server = axum_server::bind_rustls(tls_addr, rustls)
+        .handle(handle)
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+    serve_until_drained(server, tls_addr, &shutdown, drain_timeout).await;
+    shutdown::finish_tasks(&tasks, drain_timeout).await;
+    tracing::info!("shutdown complete");
+}
+
+/// Runs `server` until it finishes draining, or exits the process if the
+/// drain outlasts `drain_timeout`
+async fn serve_until_drained(
+    server: impl Future<Output = std::io::Result<()>>,
+    addr: SocketAddr,
+    shutdown: &CancellationToken,
+    drain_timeout: Duration,
+) {
+    tokio::select! {
+        result = server => {
+            if let Err(err) = result {
+                exit_on_bind_error(addr, err);
             }
         }
-    }
-    
-    if killed_count > 0 {
-        Ok(Json(SuccessResponse {
-            success: true,
-            message: format!("Terminated {} process(es)", killed_count),
-        }))
-    } else {
-        Err(StatusCode::FORBIDDEN)
+        _ = shutdown::drain_deadline(shutdown.clone(), drain_timeout) => std::process::exit(1),
     }
 }
 
-async fn suspend_process(
-    Path(_pid): Path<u32>,
-) -> Result<Json<SuccessResponse>, StatusCode> {
-    Ok(Json(SuccessResponse {
-        success: true,
-        message: "Suspend not yet implemented in Rust backend".to_string(),
-    }))
-}
-
-async fn resume_process(
-    Path(_pid): Path<u32>,
-) -> Result<Json<SuccessResponse>, StatusCode> {
-    Ok(Json(SuccessResponse {
-        success: true,
-        message: "Resume not yet implemented in Rust backend".to_string(),
-    }))
-}
-
-async fn get_process_info(
-    Path(pid): Path<u32>,
-    State(sys): State<Arc<tokio::sync::Mutex<System>>>
-) -> Result<Json<DetailedProcessInfo>, StatusCode> {
-    let sys = sys.lock().await;
-    
-    if let Some(process) = sys.process(Pid::from_u32(pid)) {
-        let memory = process.memory();
-        let virtual_memory = process.virtual_memory();
-        
-        Ok(Json(DetailedProcessInfo {
-            pid,
-            name: process.name().to_string_lossy().to_string(),
-            status: get_process_status(process.status()),
-            username: "N/A".to_string(),
-            create_time: process.start_time(),
-            cpu_percent: process.cpu_usage(),
-            memory_info: ProcessMemoryInfo {
-                rss: memory,
-                vms: virtual_memory,
-                rss_formatted: format_bytes(memory),
-                vms_formatted: format_bytes(virtual_memory),
-            },
-            num_threads: 0,
-            exe: process.exe().map(|p| p.display().to_string()).unwrap_or_else(|| "N/A".to_string()),
-            cwd: process.cwd().map(|p| p.display().to_string()).unwrap_or_else(|| "N/A".to_string()),
-            cmdline: process.cmd()
-                .iter()
-                .map(|s| s.to_string_lossy().to_string())
-                .collect::<Vec<_>>()
-                .join(" "),
-            connections: 0,
-            open_files: 0,
-        }))
-    } else {
-        Err(StatusCode::NOT_FOUND)
+/// Prints a one-line explanation instead of a panic backtrace
+fn exit_on_bind_error(addr: SocketAddr, err: std::io::Error) -> ! {
+    match err.kind() {
+        ErrorKind::AddrInUse => eprintln!("error: port {} already in use", addr.port()),
+        ErrorKind::PermissionDenied => eprintln!("error: permission denied binding to {addr}"),
+        ErrorKind::AddrNotAvailable => eprintln!("error: address {} is not available on this machine", addr.ip()),
+        _ => eprintln!("error: cannot listen on {addr}: {err}"),
     }
-}
-
-#[tokio::main]
-async fn main() {
-    println!("🚀 Task Manager Pro Backend v2.0 (Rust + Axum)");
-    println!("📡 API: http://localhost:8000");
-    println!("⚡ Performance: Native Rust - 10-20x faster than Python");
-    
-    let sys = Arc::new(tokio::sync::Mutex::new(System::new_all()));
-    
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-    
-    let app = Router::new()
-        .route("/health", get(health_check))
-        .route("/api/stats", get(get_stats))
-        .route("/api/processes", get(get_processes))
-        .route("/api/apps", get(get_apps))
-        .route("/api/app/close", post(kill_app))
-        .route("/api/process/:pid/kill", post(kill_process))
-        .route("/api/process/:pid/suspend", post(suspend_process))
-        .route("/api/process/:pid/resume", post(resume_process))
-        .route("/api/process/:pid/info", get(get_process_info))
-        .with_state(sys)
-        .layer(cors);
-    
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8000));
-    println!("✓ Server listening on {}", addr);
-    
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    std::process::exit(1);
 }
@@ -0,0 +1,120 @@
+//! Files a process has mapped into memory, from `/proc/<pid>/maps`.
+//!
+//! A browser maps tens of thousands of regions, so regions are merged per
+//! file and every anonymous region (heap, stacks, `[vdso]`, JIT memory) is
+//! folded into one summary entry.
+
+use std::collections::BTreeMap;
+
+use crate::error::ApiError;
+use crate::models::MemoryMapping;
+
+/// One line of `/proc/<pid>/maps`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapRegion {
+    pub start: u64,
+    pub end: u64,
+    /// e.g. `r-xp`: read, write, execute, then `p`rivate or `s`hared
+    pub permissions: String,
+    /// File or pseudo-path like `[heap]`; `None` for anonymous memory
+    pub path: Option<String>,
+}
+
+impl MapRegion {
+    pub fn size(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// No backing file: unnamed regions and kernel pseudo-paths like `[stack]`
+    pub fn is_anonymous(&self) -> bool {
+        self.path.as_deref().is_none_or(|path| path.starts_with('['))
+    }
+}
+
+/// Parses `start-end perms offset dev inode [path]`. The path is the rest of
+/// the line and may contain spaces; malformed lines are skipped.
+pub fn parse_proc_maps(text: &str) -> Vec<MapRegion> {
+    text.lines()
+        .filter_map(|line| {
+            let mut rest = line;
+            let mut field = || {
+                let (value, tail) = rest.trim_start().split_once(' ').unwrap_or((rest.trim_start(), ""));
+                rest = tail;
+                value
+            };
+            let (start, end) = field().split_once('-')?;
+            let permissions = field().to_string();
+            // offset, device and inode
+            for _ in 0..3 {
+                field();
+            }
+            let path = rest.trim();
+            Some(MapRegion {
+                start: u64::from_str_radix(start, 16).ok()?,
+                end: u64::from_str_radix(end, 16).ok()?,
+                permissions,
+                path: (!path.is_empty()).then(|| path.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// One entry per mapped file, sorted by path, after a single anonymous entry.
+/// `filter` keeps files whose path contains it (case-insensitive) and drops
+/// the anonymous entry, which has no path to match.
+pub fn summarize(regions: &[MapRegion], filter: Option<&str>) -> Vec<MemoryMapping> {
+    let filter = filter.map(str::to_lowercase);
+    let mut files: BTreeMap<&str, MemoryMapping> = BTreeMap::new();
+    let mut anonymous = MemoryMapping {
+        path: None,
+        anonymous: true,
+        total_bytes: 0,
+        regions: 0,
+        permissions: Vec::new(),
+    };
+    for region in regions {
+        let entry = match region.path.as_deref() {
+            Some(path) if !region.is_anonymous() => {
+                if filter.as_deref().is_some_and(|filter| !path.to_lowercase().contains(filter)) {
+                    continue;
+                }
+                files.entry(path).or_insert_with(|| MemoryMapping {
+                    path: Some(path.to_string()),
+                    anonymous: false,
+                    total_bytes: 0,
+                    regions: 0,
+                    permissions: Vec::new(),
+                })
+            }
+            _ => &mut anonymous,
+        };
+        entry.total_bytes += region.size();
+        entry.regions += 1;
+        if !entry.permissions.contains(&region.permissions) {
+            entry.permissions.push(region.permissions.clone());
+        }
+    }
+
+    let anonymous = (filter.is_none() && anonymous.regions > 0).then_some(anonymous);
+    anonymous.into_iter().chain(files.into_values()).collect()
+}
+
+/// Only the owner (or root) may read another process's maps
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Result<Vec<MapRegion>, ApiError> {
+    match std::fs::read_to_string(format!("/proc/{}/maps", pid)) {
+        Ok(text) => Ok(parse_proc_maps(&text)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(ApiError::ProcessNotFound { pid }),
+        Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => Err(ApiError::PermissionDenied { pid }),
+        Err(err) => {
+            tracing::warn!(pid, error = %err, "cannot read process memory maps");
+            Err(ApiError::Internal)
+        }
+    }
+}
+
+/// Windows would need `EnumProcessModules` and macOS `mach_vm_region`
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Result<Vec<MapRegion>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Process memory maps" })
+}
@@ -0,0 +1,88 @@
+//! The backend's own counters for `/api/self`: requests served per route,
+//! counted by [`crate::middleware::RequestCountLayer`], WebSocket clients
+//! currently connected, and handler panics turned into 500s.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+struct Inner {
+    started: Instant,
+    requests: Mutex<HashMap<String, u64>>,
+    websockets: AtomicUsize,
+    panics: AtomicU64,
+}
+
+/// Shared handle to the counters; cheap to clone
+#[derive(Clone)]
+pub struct ServerMetrics {
+    inner: Arc<Inner>,
+}
+
+impl Default for ServerMetrics {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                started: Instant::now(),
+                requests: Mutex::new(HashMap::new()),
+                websockets: AtomicUsize::new(0),
+                panics: AtomicU64::new(0),
+            }),
+        }
+    }
+}
+
+impl ServerMetrics {
+    /// Time since the counters were created, at startup
+    pub fn uptime(&self) -> Duration {
+        self.inner.started.elapsed()
+    }
+
+    /// Counts one request to `route`, a pattern such as `/api/v1/process/:pid/kill`
+    pub fn record_request(&self, route: &str) {
+        let mut requests = self.inner.requests.lock().expect("request counter mutex poisoned");
+        match requests.get_mut(route) {
+            Some(count) => *count += 1,
+            None => {
+                requests.insert(route.to_string(), 1);
+            }
+        }
+    }
+
+    /// Requests served per route, by route
+    pub fn requests(&self) -> BTreeMap<String, u64> {
+        let requests = self.inner.requests.lock().expect("request counter mutex poisoned");
+        requests.iter().map(|(route, count)| (route.clone(), *count)).collect()
+    }
+
+    /// Counts a WebSocket client as connected until the guard is dropped
+    pub fn websocket_opened(&self) -> WebSocketGuard {
+        self.inner.websockets.fetch_add(1, Ordering::Relaxed);
+        WebSocketGuard { metrics: self.clone() }
+    }
+
+    pub fn websocket_clients(&self) -> usize {
+        self.inner.websockets.load(Ordering::Relaxed)
+    }
+
+    /// Counts a handler panic caught by the router
+    pub fn record_panic(&self) {
+        self.inner.panics.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn panics(&self) -> u64 {
+        self.inner.panics.load(Ordering::Relaxed)
+    }
+}
+
+/// Held for as long as a WebSocket connection is served
+pub struct WebSocketGuard {
+    metrics: ServerMetrics,
+}
+
+impl Drop for WebSocketGuard {
+    fn drop(&mut self) {
+        self.metrics.inner.websockets.fetch_sub(1, Ordering::Relaxed);
+    }
+}
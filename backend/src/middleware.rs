@@ -0,0 +1,743 @@
+//! Reusable tower layers
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, MatchedPath},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::metrics::ServerMetrics;
+use crate::msgpack;
+
+/// Adds a SHA-256 `ETag` to successful responses and answers matching
+/// `If-None-Match` requests with `304 Not Modified`
+#[derive(Clone, Copy, Default)]
+pub struct EtagLayer;
+
+impl<S> Layer<S> for EtagLayer {
+    type Service = EtagService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        EtagService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct EtagService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for EtagService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+        // Take the service that was driven to readiness, leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let response = inner.call(req).await?;
+            if response.status() != StatusCode::OK {
+                return Ok(response);
+            }
+
+            let (mut parts, body) = response.into_parts();
+            let bytes = match to_bytes(body, usize::MAX).await {
+                Ok(bytes) => bytes,
+                Err(_) => return Ok(ApiError::Internal.into_response()),
+            };
+            let etag = format!("\"{:x}\"", Sha256::digest(&bytes));
+            let etag_value = HeaderValue::from_str(&etag).expect("hex digest is a valid header value");
+
+            if if_none_match.is_some_and(|value| etag_matches(&value, &etag)) {
+                let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+                not_modified.headers_mut().insert(header::ETAG, etag_value);
+                return Ok(not_modified);
+            }
+
+            parts.headers.insert(header::ETAG, etag_value);
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Ok(Response::from_parts(parts, Body::from(bytes)))
+        })
+    }
+}
+
+/// `If-None-Match` may be `*` or a comma-separated list of (possibly weak) tags
+pub fn etag_matches(if_none_match: &HeaderValue, etag: &str) -> bool {
+    let Ok(value) = if_none_match.to_str() else {
+        return false;
+    };
+    value
+        .split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Milliseconds the backend spent on the request, middleware included
+pub static RESPONSE_TIME_HEADER: HeaderName = HeaderName::from_static("x-response-time-ms");
+
+/// Longest incoming `X-Request-ID` kept; longer or non-ASCII IDs are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    /// Set while [`RequestLogLayer`] serves a request, for error bodies
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// The ID assigned to a request by [`RequestLogLayer`], available as a request extension
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The ID of the request being served on this task, if any
+    pub fn current() -> Option<String> {
+        CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+    }
+
+    /// The caller's `X-Request-ID` when it is usable, so its logs and ours
+    /// share one ID; a fresh UUID otherwise
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let incoming = headers
+            .get(&REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic()));
+        Self(incoming.map(str::to_string).unwrap_or_else(|| Uuid::new_v4().to_string()))
+    }
+}
+
+/// Logs one structured line per request, tags both request and response with
+/// an `X-Request-ID` (the caller's, or a fresh one) and adds
+/// `X-Response-Time-Ms` to the response
+#[derive(Clone, Copy, Default)]
+pub struct RequestLogLayer;
+
+impl<S> Layer<S> for RequestLogLayer {
+    type Service = RequestLogService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestLogService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestLogService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for RequestLogService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let RequestId(request_id) = RequestId::from_headers(req.headers());
+        let id_header = HeaderValue::from_str(&request_id).expect("request ID is visible ASCII");
+        let method = req.method().to_string();
+        let path = req.uri().path().to_string();
+        let client_ip = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        tracing::debug!(request_id = %request_id, body = %body_summary(req.headers()), "request body");
+        req.headers_mut().insert(REQUEST_ID_HEADER.clone(), id_header.clone());
+        req.extensions_mut().insert(RequestId(request_id.clone()));
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let start = Instant::now();
+
+        Box::pin(async move {
+            let mut response = CURRENT_REQUEST_ID.scope(request_id.clone(), inner.call(req)).await?;
+            let latency_ms = start.elapsed().as_millis() as u64;
+            response.headers_mut().insert(REQUEST_ID_HEADER.clone(), id_header);
+            response.headers_mut().insert(RESPONSE_TIME_HEADER.clone(), HeaderValue::from(latency_ms));
+
+            tracing::info!(
+                method = %method,
+                path = %path,
+                status = response.status().as_u16(),
+                latency_ms,
+                client_ip = %client_ip,
+                request_id = %request_id,
+                "request"
+            );
+            tracing::debug!(request_id = %request_id, body = %body_summary(response.headers()), "response body");
+            Ok(response)
+        })
+    }
+}
+
+/// `"<content-type>, <n> bytes"` without buffering the body
+fn body_summary(headers: &HeaderMap) -> String {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("none");
+    let length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown");
+    format!("{}, {} bytes", content_type, length)
+}
+
+/// Requires `Authorization: Bearer <token>` on mutating requests, and on
+/// reads too when `protect_reads` is set. `/health` and CORS preflights stay open.
+#[derive(Clone)]
+pub struct AuthLayer {
+    token: Option<Arc<str>>,
+    protect_reads: bool,
+    read_only: bool,
+}
+
+impl AuthLayer {
+    /// `None` disables authentication entirely
+    pub fn new(token: Option<&str>, protect_reads: bool) -> Self {
+        Self {
+            token: token.map(Arc::from),
+            protect_reads,
+            read_only: false,
+        }
+    }
+
+    /// Rejects every mutating request with `403 read_only`
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AuthService<S> {
+    inner: S,
+    layer: AuthLayer,
+}
+
+impl<S> Service<Request<Body>> for AuthService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if let Err(err) = self.layer.check(&req) {
+            return Box::pin(async move { Ok(err.into_response()) });
+        }
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { inner.call(req).await })
+    }
+}
+
+impl AuthLayer {
+    fn requires_token(&self, req: &Request<Body>) -> bool {
+        match *req.method() {
+            Method::OPTIONS => false,
+            // The token route checks the client itself and can't ask for the token it hands out
+            Method::GET | Method::HEAD => {
+                let token_route = req
+                    .extensions()
+                    .get::<MatchedPath>()
+                    .is_some_and(|route| crate::AUTH_TOKEN_ROUTES.contains(&route.as_str()));
+                self.protect_reads && req.uri().path() != "/health" && !token_route
+            }
+            _ => true,
+        }
+    }
+
+    fn check(&self, req: &Request<Body>) -> Result<(), ApiError> {
+        if self.read_only && !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+            return Err(ApiError::ReadOnly);
+        }
+        let Some(expected) = self.token.as_deref() else {
+            return Ok(());
+        };
+        if !self.requires_token(req) {
+            return Ok(());
+        }
+
+        let supplied = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or(ApiError::MissingToken)?;
+
+        if constant_time_eq(supplied.trim().as_bytes(), expected.as_bytes()) {
+            Ok(())
+        } else {
+            Err(ApiError::InvalidToken)
+        }
+    }
+}
+
+/// Compares without short-circuiting so response timing doesn't leak the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Answers `503 shutting_down` once `shutdown` is cancelled, so requests that
+/// arrive on kept-alive connections during the drain don't start new work
+#[derive(Clone)]
+pub struct DrainLayer {
+    shutdown: CancellationToken,
+}
+
+impl DrainLayer {
+    pub fn new(shutdown: CancellationToken) -> Self {
+        Self { shutdown }
+    }
+}
+
+impl<S> Layer<S> for DrainLayer {
+    type Service = DrainService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DrainService {
+            inner,
+            shutdown: self.shutdown.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DrainService<S> {
+    inner: S,
+    shutdown: CancellationToken,
+}
+
+impl<S> Service<Request<Body>> for DrainService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if self.shutdown.is_cancelled() {
+            return Box::pin(async move {
+                let mut response = ApiError::ShuttingDown.into_response();
+                response
+                    .headers_mut()
+                    .insert(header::CONNECTION, HeaderValue::from_static("close"));
+                Ok(response)
+            });
+        }
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { inner.call(req).await })
+    }
+}
+
+/// Set to `true` on responses from deprecated routes (RFC 9745)
+pub static DEPRECATION_HEADER: HeaderName = HeaderName::from_static("deprecation");
+
+/// Marks responses of the unversioned `/api/...` aliases as deprecated, with a
+/// `Link` to the same route under `successor`, and warns once per route so a
+/// polling client doesn't flood the log. Must sit inside the nest, where the
+/// prefix is already stripped from the path.
+#[derive(Clone)]
+pub struct DeprecationLayer {
+    successor: &'static str,
+    warned: Arc<Mutex<HashSet<String>>>,
+}
+
+impl DeprecationLayer {
+    pub fn new(successor: &'static str) -> Self {
+        Self {
+            successor,
+            warned: Arc::default(),
+        }
+    }
+}
+
+impl<S> Layer<S> for DeprecationLayer {
+    type Service = DeprecationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DeprecationService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DeprecationService<S> {
+    inner: S,
+    layer: DeprecationLayer,
+}
+
+impl<S> Service<Request<Body>> for DeprecationService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let successor = format!("{}{}", self.layer.successor, req.uri().path());
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| req.uri().path().to_string());
+        if self.layer.warned.lock().expect("deprecation mutex poisoned").insert(route.clone()) {
+            tracing::warn!(%route, %successor, "deprecated unversioned API route called");
+        }
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+            let headers = response.headers_mut();
+            headers.insert(DEPRECATION_HEADER.clone(), HeaderValue::from_static("true"));
+            if let Ok(link) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor)) {
+                headers.insert(header::LINK, link);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Counts each request in [`ServerMetrics`] under its route pattern. Added
+/// with `route_layer`, so paths that match no route aren't counted and can't
+/// grow the table.
+#[derive(Clone)]
+pub struct RequestCountLayer {
+    metrics: ServerMetrics,
+}
+
+impl RequestCountLayer {
+    pub fn new(metrics: ServerMetrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for RequestCountLayer {
+    type Service = RequestCountService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestCountService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestCountService<S> {
+    inner: S,
+    metrics: ServerMetrics,
+}
+
+impl<S> Service<Request<Body>> for RequestCountService<S>
+where
+    S: Service<Request<Body>, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if let Some(route) = req.extensions().get::<MatchedPath>() {
+            self.metrics.record_request(route.as_str());
+        }
+        self.inner.call(req)
+    }
+}
+
+/// Clients tracked at once by [`RateLimiter`]; idle ones are dropped first
+pub const RATE_LIMIT_MAX_CLIENTS: usize = 10_000;
+
+/// Token bucket per client IP: `per_second` requests sustained, `burst` at once
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    max_clients: usize,
+    buckets: Mutex<HashMap<Option<IpAddr>, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(per_second: u32, burst: u32) -> Self {
+        Self {
+            per_second: f64::from(per_second.max(1)),
+            burst: f64::from(burst.max(1)),
+            max_clients: RATE_LIMIT_MAX_CLIENTS,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Overrides [`RATE_LIMIT_MAX_CLIENTS`]
+    pub fn max_clients(mut self, max_clients: usize) -> Self {
+        self.max_clients = max_clients.max(1);
+        self
+    }
+
+    /// Takes a token for `client`, or says how long until one is available
+    pub fn check(&self, client: Option<IpAddr>) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("rate limit mutex poisoned");
+        if !buckets.contains_key(&client) && buckets.len() >= self.max_clients {
+            self.evict(&mut buckets, now);
+        }
+
+        let bucket = buckets.entry(client).or_insert(Bucket { tokens: self.burst, updated: now });
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second))
+        }
+    }
+
+    /// Number of clients currently holding state
+    pub fn tracked_clients(&self) -> usize {
+        self.buckets.lock().expect("rate limit mutex poisoned").len()
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.per_second).min(self.burst)
+    }
+
+    /// Drops buckets that have refilled completely, which behave exactly like
+    /// new ones; if every client is active, drops the least recently seen
+    fn evict(&self, buckets: &mut HashMap<Option<IpAddr>, Bucket>, now: Instant) {
+        buckets.retain(|_, bucket| self.refilled(bucket, now) < self.burst);
+        if buckets.len() >= self.max_clients {
+            let oldest = buckets.iter().min_by_key(|(_, bucket)| bucket.updated).map(|(client, _)| *client);
+            if let Some(client) = oldest {
+                buckets.remove(&client);
+            }
+        }
+    }
+}
+
+/// Applies a [`RateLimiter`] to mutating requests, answering `429 rate_limited`
+/// with `Retry-After`. GET, HEAD and OPTIONS pass through untouched.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: RateLimiter) -> Self {
+        Self { limiter: Arc::new(limiter) }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+            let client = req
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip());
+            if let Err(wait) = self.limiter.check(client) {
+                let err = ApiError::RateLimited {
+                    retry_after_secs: wait.as_secs_f64().ceil().max(1.0) as u64,
+                };
+                tracing::warn!(client_ip = ?client, path = %req.uri().path(), "rate limit exceeded");
+                return Box::pin(async move { Ok(err.into_response()) });
+            }
+        }
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { inner.call(req).await })
+    }
+}
+
+/// Re-encodes JSON responses as MessagePack when the request's `Accept` asks
+/// for it; see [`crate::msgpack`]. Other responses pass through untouched.
+#[derive(Clone, Copy, Default)]
+pub struct MsgpackLayer {
+    except: &'static [&'static str],
+}
+
+impl MsgpackLayer {
+    /// Leaves the routes matched as one of `routes` alone, for those that
+    /// negotiate with a layer of their own
+    pub fn except(routes: &'static [&'static str]) -> Self {
+        Self { except: routes }
+    }
+}
+
+impl<S> Layer<S> for MsgpackLayer {
+    type Service = MsgpackService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MsgpackService { inner, except: self.except }
+    }
+}
+
+#[derive(Clone)]
+pub struct MsgpackService<S> {
+    inner: S,
+    except: &'static [&'static str],
+}
+
+impl<S> Service<Request<Body>> for MsgpackService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let excepted = req
+            .extensions()
+            .get::<MatchedPath>()
+            .is_some_and(|route| self.except.contains(&route.as_str()));
+        if excepted {
+            return Box::pin(async move { inner.call(req).await });
+        }
+        let wants_msgpack = msgpack::accepts_msgpack(req.headers());
+
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+            let encoder = response.extensions_mut().remove::<msgpack::MsgpackEncoder>();
+            let is_json = response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+            if !is_json {
+                return Ok(response);
+            }
+            response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+            if !wants_msgpack {
+                return Ok(response);
+            }
+
+            let (mut parts, body) = response.into_parts();
+            let encoded = match encoder {
+                Some(encoder) => encoder.encode(),
+                None => match to_bytes(body, usize::MAX).await {
+                    Ok(bytes) => rmp_serde::to_vec_named(&serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null)),
+                    Err(_) => return Ok(ApiError::Internal.into_response()),
+                },
+            };
+            let Ok(encoded) = encoded else {
+                return Ok(ApiError::Internal.into_response());
+            };
+            parts.headers.insert(header::CONTENT_TYPE, msgpack::content_type());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Ok(Response::from_parts(parts, Body::from(encoded)))
+        })
+    }
+}
@@ -0,0 +1,268 @@
+//! Deterministic [`SystemSource`] for tests, serving canned process tables
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use sysinfo::Signal;
+
+use crate::environ::EnvVars;
+use crate::error::ApiError;
+use crate::maps::MapRegion;
+use crate::oom::OomScore;
+use crate::procio::IoCounters;
+use crate::models::*;
+use crate::source::{ProcessSample, ProcessTable, SystemSource};
+use crate::threads::ThreadSample;
+use crate::topology::Topology;
+use crate::utils::format_bytes;
+
+pub struct MockSystemSource {
+    table: Mutex<ProcessTable>,
+    killed: Mutex<Vec<u32>>,
+    /// `None` marks an environment the caller may not read
+    environs: Mutex<HashMap<u32, Option<EnvVars>>>,
+    maps: Mutex<HashMap<u32, Vec<MapRegion>>>,
+    threads: Mutex<HashMap<u32, Vec<ThreadSample>>>,
+    /// `None` marks counters the caller may not read
+    io: Mutex<HashMap<u32, Option<IoCounters>>>,
+}
+
+impl MockSystemSource {
+    pub fn new(table: ProcessTable) -> Self {
+        Self {
+            table: Mutex::new(table),
+            killed: Mutex::new(Vec::new()),
+            environs: Mutex::new(HashMap::new()),
+            maps: Mutex::new(HashMap::new()),
+            threads: Mutex::new(HashMap::new()),
+            io: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Loads a fixture shaped like [`ProcessTable`]
+    pub fn from_fixture(json: &str) -> serde_json::Result<Self> {
+        Ok(Self::new(serde_json::from_str(json)?))
+    }
+
+    /// PIDs killed so far, in call order
+    pub fn killed(&self) -> Vec<u32> {
+        self.killed.lock().unwrap().clone()
+    }
+
+    /// Environment served for `pid`; processes without one have an empty environment
+    pub fn set_environ(&self, pid: u32, vars: &[(&str, &str)]) {
+        let vars = vars.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        self.environs.lock().unwrap().insert(pid, Some(vars));
+    }
+
+    /// Makes reading `pid`'s environment fail as it would for another user's process
+    pub fn deny_environ(&self, pid: u32) {
+        self.environs.lock().unwrap().insert(pid, None);
+    }
+
+    /// Memory maps served for `pid`; processes without any map nothing
+    pub fn set_maps(&self, pid: u32, regions: Vec<MapRegion>) {
+        self.maps.lock().unwrap().insert(pid, regions);
+    }
+
+    /// Threads served for `pid`; every reading returns the same CPU times
+    pub fn set_threads(&self, pid: u32, threads: Vec<ThreadSample>) {
+        self.threads.lock().unwrap().insert(pid, threads);
+    }
+
+    /// I/O counters served for `pid` until the next call; `None` makes
+    /// reading them fail as for another user's process. Unset counters are 0.
+    pub fn set_io(&self, pid: u32, counters: Option<IoCounters>) {
+        self.io.lock().unwrap().insert(pid, counters);
+    }
+
+    /// Puts `pids` in process group `pgid`, in the session it leads
+    pub fn set_process_group(&self, pids: &[u32], pgid: u32) {
+        let mut table = self.table.lock().unwrap();
+        for process in table.processes.iter_mut().filter(|p| pids.contains(&p.pid)) {
+            process.process_group_id = Some(pgid);
+            process.session_id = Some(pgid);
+        }
+    }
+
+    /// Stands in for a signal: the next listing shows the new status
+    fn set_status(&self, pid: u32, status: &str) -> Result<String, ApiError> {
+        let mut table = self.table.lock().unwrap();
+        let process = table
+            .processes
+            .iter_mut()
+            .find(|p| p.pid == pid)
+            .ok_or(ApiError::ProcessNotFound { pid })?;
+        process.status = status.to_string();
+        Ok(process.name.clone())
+    }
+}
+
+impl SystemSource for MockSystemSource {
+    fn cpu_info(&self) -> CpuInfo {
+        let cpu_count = self.table.lock().unwrap().cpu_count.max(1);
+        CpuInfo {
+            brand: "Mock CPU".to_string(),
+            vendor: "Mock".to_string(),
+            frequency_mhz: 0,
+            physical_cores: Some(cpu_count),
+            logical_cores: cpu_count,
+            cache: CpuCacheInfo::default(),
+        }
+    }
+
+    fn snapshot_stats(&self) -> SystemStats {
+        let table = self.table.lock().unwrap();
+        let cpu_count = table.cpu_count.max(1);
+        let used = table.processes.iter().map(|p| p.memory).sum::<u64>().min(table.total_memory);
+        let cpu = table.processes.iter().map(|p| p.cpu_usage).sum::<f32>() / cpu_count as f32;
+        
+        SystemStats {
+            timestamp: "0".to_string(),
+            cpu: CPUStats {
+                percent: cpu,
+                // One package, one node, no SMT
+                cores: Topology { physical_cores: Some(cpu_count), sockets: Some(1), numa_nodes: Some(1) }.cores(cpu_count),
+                per_core: vec![cpu; cpu_count],
+            },
+            memory: MemoryStats {
+                total: table.total_memory,
+                available: table.total_memory - used,
+                used,
+                percent: (used as f64 / table.total_memory.max(1) as f64 * 100.0) as f32,
+                total_formatted: format_bytes(table.total_memory),
+                used_formatted: format_bytes(used),
+                system_major_faults_per_sec: 0,
+            },
+            memory_pressure: None,
+            disk: DiskStats {
+                total: 0,
+                used: 0,
+                free: 0,
+                percent: 0.0,
+                total_formatted: format_bytes(0),
+                used_formatted: format_bytes(0),
+                disks: Vec::new(),
+            },
+            network: NetworkStats {
+                bytes_sent: 0,
+                bytes_recv: 0,
+                bytes_sent_formatted: format_bytes(0),
+                bytes_recv_formatted: format_bytes(0),
+                interfaces: Vec::new(),
+            },
+            system: SystemInfo {
+                os: "mock".to_string(),
+                uptime_seconds: 0,
+            },
+            kernel: KernelStats::default(),
+            gpu: None,
+        }
+    }
+
+    fn list_processes(&self) -> ProcessTable {
+        self.table.lock().unwrap().clone()
+    }
+
+    fn find_process(&self, pid: u32) -> Option<ProcessSample> {
+        self.table.lock().unwrap().processes.iter().find(|p| p.pid == pid).cloned()
+    }
+
+    fn kill(&self, pid: u32) -> Result<String, ApiError> {
+        let mut table = self.table.lock().unwrap();
+        let index = table
+            .processes
+            .iter()
+            .position(|p| p.pid == pid)
+            .ok_or(ApiError::ProcessNotFound { pid })?;
+        
+        self.killed.lock().unwrap().push(pid);
+        Ok(table.processes.remove(index).name)
+    }
+
+    /// Every terminating signal removes the process, as [`Self::kill`] does
+    fn signal(&self, pid: u32, signal: Signal) -> Result<String, ApiError> {
+        match signal {
+            Signal::Stop => self.suspend(pid),
+            Signal::Continue => self.resume(pid),
+            _ => self.kill(pid),
+        }
+    }
+
+    /// Signals each member in turn, as [`Self::signal`] does
+    fn signal_group(&self, pgid: u32, signal: Signal) -> Result<(), ApiError> {
+        let members: Vec<u32> = {
+            let table = self.table.lock().unwrap();
+            table.processes.iter().filter(|p| p.process_group_id == Some(pgid)).map(|p| p.pid).collect()
+        };
+        if members.is_empty() {
+            return Err(ApiError::ProcessGroupNotFound { pgid });
+        }
+        members.into_iter().try_for_each(|pid| self.signal(pid, signal).map(|_| ()))
+    }
+
+    fn suspend(&self, pid: u32) -> Result<String, ApiError> {
+        self.set_status(pid, "stopped")
+    }
+
+    fn resume(&self, pid: u32) -> Result<String, ApiError> {
+        self.set_status(pid, "running")
+    }
+
+    fn environ(&self, pid: u32) -> Result<EnvVars, ApiError> {
+        self.find_process(pid).ok_or(ApiError::ProcessNotFound { pid })?;
+        match self.environs.lock().unwrap().get(&pid) {
+            Some(Some(vars)) => Ok(vars.clone()),
+            Some(None) => Err(ApiError::PermissionDenied { pid }),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn threads(&self, pid: u32) -> Result<Vec<ThreadSample>, ApiError> {
+        self.find_process(pid).ok_or(ApiError::ProcessNotFound { pid })?;
+        Ok(self.threads.lock().unwrap().get(&pid).cloned().unwrap_or_default())
+    }
+
+    fn memory_maps(&self, pid: u32) -> Result<Vec<MapRegion>, ApiError> {
+        self.find_process(pid).ok_or(ApiError::ProcessNotFound { pid })?;
+        Ok(self.maps.lock().unwrap().get(&pid).cloned().unwrap_or_default())
+    }
+
+    fn oom_score(&self, pid: u32) -> Option<OomScore> {
+        let process = self.find_process(pid)?;
+        Some(OomScore { score: process.oom_score?, adj: process.oom_score_adj? })
+    }
+
+    /// The score follows the adjustment, as for a process using no memory
+    fn set_oom_score_adj(&self, pid: u32, adj: i32) -> Result<OomScore, ApiError> {
+        let mut table = self.table.lock().unwrap();
+        let process = table
+            .processes
+            .iter_mut()
+            .find(|p| p.pid == pid)
+            .ok_or(ApiError::ProcessNotFound { pid })?;
+        let score = OomScore { score: adj.max(0), adj };
+        process.oom_score = Some(score.score);
+        process.oom_score_adj = Some(score.adj);
+        Ok(score)
+    }
+
+    fn rename(&self, pid: u32, name: &str) -> Result<(), ApiError> {
+        let mut table = self.table.lock().unwrap();
+        let process = table
+            .processes
+            .iter_mut()
+            .find(|p| p.pid == pid)
+            .ok_or(ApiError::ProcessNotFound { pid })?;
+        process.name = name.to_string();
+        Ok(())
+    }
+
+    fn io_counters(&self, pid: u32) -> Result<IoCounters, ApiError> {
+        self.find_process(pid).ok_or(ApiError::ProcessNotFound { pid })?;
+        match self.io.lock().unwrap().get(&pid) {
+            Some(Some(counters)) => Ok(*counters),
+            Some(None) => Err(ApiError::PermissionDenied { pid }),
+            None => Ok(IoCounters::default()),
+        }
+    }
+}
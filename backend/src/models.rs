@@ -0,0 +1,1509 @@
+//! Request and response types; responses match the Python backend's JSON exactly
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+use crate::alerts::{AlertMetric, Comparison};
+
+/// Machine-wide snapshot served by `/api/stats`
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct SystemStats {
+    /// Unix epoch seconds when the snapshot was taken
+    pub timestamp: String,
+    pub cpu: CPUStats,
+    pub memory: MemoryStats,
+    /// Linux PSI; absent on other platforms and kernels without `/proc/pressure`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_pressure: Option<MemoryPressure>,
+    pub disk: DiskStats,
+    pub network: NetworkStats,
+    pub system: SystemInfo,
+    pub kernel: KernelStats,
+    /// Absent when no NVIDIA GPU / NVML is available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<GPUStats>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct CPUStats {
+    /// Overall CPU usage, 0-100
+    pub percent: f32,
+    pub cores: CPUCores,
+    /// Usage of each logical core, 0-100
+    pub per_core: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct CPUCores {
+    /// Physical core count
+    pub physical: usize,
+    /// Logical core (hardware thread) count
+    pub logical: usize,
+    /// CPU packages; `null` when the OS doesn't say (Linux only)
+    pub sockets: Option<u32>,
+    /// `physical / sockets`
+    pub cores_per_socket: Option<u32>,
+    /// `logical / physical`: 2 with SMT (Hyper-Threading), 1 without
+    pub threads_per_core: u32,
+    /// NUMA nodes (Linux only)
+    pub numa_nodes: Option<u32>,
+}
+
+/// Served by `/api/cpu/info`; things about the CPU that don't change while it runs
+#[derive(Serialize, Clone, ToSchema)]
+pub struct CpuInfo {
+    /// Model name, e.g. "AMD Ryzen 7 5800X 8-Core Processor"
+    pub brand: String,
+    /// e.g. "GenuineIntel", "AuthenticAMD" or "Apple"
+    pub vendor: String,
+    /// Current frequency of the first core in MHz; 0 where the OS doesn't say
+    pub frequency_mhz: u64,
+    /// Physical core count; null where the OS doesn't say
+    pub physical_cores: Option<usize>,
+    /// Logical core (hardware thread) count
+    pub logical_cores: usize,
+    pub cache: CpuCacheInfo,
+}
+
+/// Size of one instance of each cache level in KiB; null when not reported
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, ToSchema)]
+pub struct CpuCacheInfo {
+    /// Level 1 data cache, per core
+    pub l1d_kb: Option<u32>,
+    /// Level 1 instruction cache, per core
+    pub l1i_kb: Option<u32>,
+    pub l2_kb: Option<u32>,
+    /// Usually shared by all cores, or by a cluster of them
+    pub l3_kb: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct MemoryStats {
+    /// Total RAM in bytes
+    pub total: u64,
+    /// Bytes available for new allocations
+    pub available: u64,
+    /// Bytes in use
+    pub used: u64,
+    /// `used / total`, 0-100
+    pub percent: f32,
+    /// `total` as a human-readable string, e.g. "15.6 GB"
+    pub total_formatted: String,
+    /// `used` as a human-readable string
+    pub used_formatted: String,
+    /// Page faults per second that needed disk I/O (swap-ins and mapped
+    /// files read from disk), over all processes; 0 off Linux
+    pub system_major_faults_per_sec: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct DiskStats {
+    /// Combined size of all mounted disks in bytes
+    pub total: u64,
+    /// Bytes used across all disks
+    pub used: u64,
+    /// Bytes free across all disks
+    pub free: u64,
+    /// `used / total`, 0-100
+    pub percent: f32,
+    /// `total` as a human-readable string
+    pub total_formatted: String,
+    /// `used` as a human-readable string
+    pub used_formatted: String,
+    /// Each mounted disk, sorted by mount point
+    pub disks: Vec<DiskInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct DiskInfo {
+    /// Device the filesystem lives on, e.g. "/dev/nvme0n1p2"
+    pub name: String,
+    pub mount_point: String,
+    /// e.g. "ext4", "apfs", "NTFS"
+    pub file_system: String,
+    /// Size in bytes
+    pub total: u64,
+    /// Bytes available to unprivileged users
+    pub available: u64,
+    /// "SSD", "HDD", "NVMe" or "Unknown"
+    pub disk_type: String,
+    /// USB sticks, SD cards and other hot-pluggable media
+    pub is_removable: bool,
+    /// Active I/O scheduler, e.g. "mq-deadline", "bfq" or "none" (Linux)
+    pub scheduler: Option<String>,
+    /// Requests the block layer queues per hardware queue (Linux)
+    pub nr_requests: Option<u32>,
+    /// Requests dispatched per round (`iosched/quantum`); only some
+    /// schedulers have it (Linux)
+    pub queue_depth: Option<u32>,
+    /// Read-ahead window in KiB (Linux)
+    pub read_ahead_kb: Option<u32>,
+}
+
+/// Body of `POST /api/disks/{device}/scheduler`
+#[derive(Deserialize, ToSchema)]
+pub struct SchedulerRequest {
+    /// One of the disk's `available` schedulers
+    pub scheduler: String,
+}
+
+/// I/O scheduler of a whole disk after `POST /api/disks/{device}/scheduler`
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct DiskScheduler {
+    /// e.g. "sda", "nvme0n1"
+    pub device: String,
+    /// Active scheduler, as the kernel now reports it
+    pub scheduler: String,
+    /// Schedulers the kernel offers for this disk
+    pub available: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct NetworkStats {
+    /// Bytes sent on all interfaces since boot
+    pub bytes_sent: u64,
+    /// Bytes received on all interfaces since boot
+    pub bytes_recv: u64,
+    /// `bytes_sent` as a human-readable string
+    pub bytes_sent_formatted: String,
+    /// `bytes_recv` as a human-readable string
+    pub bytes_recv_formatted: String,
+    /// Per-interface counters and link state, sorted by name
+    pub interfaces: Vec<NetworkInterfaceStats>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct NetworkInterfaceStats {
+    /// Interface name, e.g. "eth0" or "en0"
+    pub name: String,
+    /// Bytes sent since boot
+    pub bytes_sent: u64,
+    /// Bytes received since boot
+    pub bytes_recv: u64,
+    /// Interface is up and has carrier
+    pub is_up: bool,
+    /// Negotiated link speed; `null` when down or unknown
+    pub speed_mbps: Option<u64>,
+    /// "full" or "half"; `null` when unknown
+    pub duplex: Option<String>,
+    /// Hardware address as `aa:bb:cc:dd:ee:ff`
+    pub mac_address: Option<String>,
+    /// IPv4 and IPv6 addresses assigned to the interface
+    pub ip_addresses: Vec<String>,
+    pub mtu: Option<u32>,
+    /// Wireless link details; `null` for wired and virtual interfaces, and
+    /// off Linux and macOS
+    pub wifi: Option<WifiInfo>,
+}
+
+/// The wireless network an interface is on
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct WifiInfo {
+    /// Network name; empty while not associated
+    pub ssid: String,
+    /// Received signal strength, e.g. -55; 0 while not associated
+    pub signal_dbm: i32,
+    /// Channel frequency, e.g. 2437 or 5180; 0 while not associated
+    pub frequency_mhz: u32,
+    /// Rate of the last transmitted frame
+    pub bitrate_mbps: f32,
+    /// Associated with an access point
+    pub is_connected: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct SystemInfo {
+    /// Operating system family, e.g. "windows" or "linux"
+    pub os: String,
+    /// Seconds since boot
+    pub uptime_seconds: u64,
+}
+
+/// Share of time tasks were stalled waiting for memory, from `/proc/pressure/memory`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+pub struct MemoryPressure {
+    /// At least one task was stalled
+    pub some: PressureStall,
+    /// Every non-idle task was stalled at once; absent on old kernels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full: Option<PressureStall>,
+}
+
+/// One line of a PSI file
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+pub struct PressureStall {
+    /// Percent of the last 10 seconds spent stalled
+    pub avg10: f32,
+    /// Percent of the last 60 seconds
+    pub avg60: f32,
+    /// Percent of the last 300 seconds
+    pub avg300: f32,
+    /// Total stall time since boot in microseconds
+    pub total_us: u64,
+}
+
+/// Scheduler pressure from `/proc/stat`; all zeros on other platforms.
+/// Rates cover the time since the previous snapshot.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, ToSchema)]
+pub struct KernelStats {
+    pub context_switches_per_sec: u64,
+    /// New processes and threads per second
+    pub forks_per_sec: u64,
+    /// Tasks runnable at the moment of the snapshot
+    pub processes_running: u32,
+    /// Tasks waiting on I/O at the moment of the snapshot
+    pub processes_blocked: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct GPUStats {
+    /// GPU model name
+    pub name: String,
+    /// GPU utilisation, 0-100
+    pub load: f32,
+    /// Video memory in use, in bytes
+    pub memory_used: u64,
+    /// Total video memory, in bytes
+    pub memory_total: u64,
+    /// `memory_used / memory_total`, 0-100
+    pub memory_percent: f32,
+    /// `memory_used` as a human-readable string
+    pub memory_used_formatted: String,
+    /// `memory_total` as a human-readable string
+    pub memory_total_formatted: String,
+    /// Core temperature in °C, if the sensor is readable
+    pub temperature: Option<f32>,
+}
+
+/// One row of `/api/processes`
+#[derive(Serialize, Clone, ToSchema)]
+pub struct ProcessData {
+    /// Process ID
+    pub pid: u32,
+    /// Executable name
+    pub name: String,
+    /// Owning user, or "N/A"
+    pub username: String,
+    /// CPU usage divided by core count (Task Manager style), 0-100
+    pub cpu_percent: f32,
+    /// Resident memory as a share of total RAM, 0-100
+    pub memory_percent: f32,
+    /// Resident memory in MiB
+    pub memory_mb: f64,
+    /// "running", "sleeping", "stopped", "zombie", "dead" or "unknown"
+    pub status: String,
+    /// Thread count
+    pub num_threads: usize,
+    /// Start time as Unix epoch seconds
+    pub create_time: u64,
+    /// Start time as RFC 3339 UTC, e.g. "2023-11-14T22:13:20Z"; null when the
+    /// OS reports no start time (some kernel threads)
+    pub create_time_iso: Option<String>,
+    /// Seconds since the process started, 0 if its start is in the future
+    /// (clock skew); null like `create_time_iso`
+    pub running_seconds: Option<u64>,
+    /// `running_seconds` as e.g. "3h 12m"
+    pub running_formatted: Option<String>,
+    /// Executable path, or "N/A"
+    pub exe: String,
+    /// Working directory, or "N/A"
+    pub cwd: String,
+    /// Command line arguments, including argv[0]
+    pub cmdline: Vec<String>,
+    /// True when the backend refuses to kill this process
+    pub is_protected: bool,
+    /// True for the backend's own process and its parent, which can never be killed
+    pub is_self: bool,
+    /// Session ID (Linux only); processes of one login or terminal share it
+    pub session_id: Option<u32>,
+    /// Process group ID (Linux only); a shell pipeline shares one
+    pub process_group_id: Option<u32>,
+    /// Controlling terminal under `/dev`, e.g. `pts/0` (Linux only); null for daemons
+    pub tty: Option<String>,
+    /// Full 64-hex-digit ID of the container the process runs in (Linux
+    /// only); null on the host
+    pub container_id: Option<String>,
+    /// Parent process ID; null for roots and when the OS doesn't say
+    pub parent_pid: Option<u32>,
+    /// CPU seconds (user + system) used since the process started; Linux only
+    pub cpu_time_seconds: Option<f64>,
+    /// Disk bytes read per second over the sampling interval
+    pub disk_read_rate: f64,
+    /// Disk bytes written per second over the sampling interval
+    pub disk_write_rate: f64,
+    /// OOM killer badness (Linux only); the highest score is killed first
+    pub oom_score: Option<i32>,
+    /// Adjustment from -1000 (never kill) to 1000 (kill first), Linux only
+    pub oom_score_adj: Option<i32>,
+    /// Hops from the root PID; only set by `/api/process/:pid/children?recursive=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProcessListResponse {
+    /// Processes sorted by `cpu_percent`, highest first
+    pub processes: Vec<ProcessData>,
+    /// Number of entries in `processes`
+    pub total_count: usize,
+}
+
+/// Processes sharing a name, aggregated like Task Manager's Apps view
+#[derive(Serialize, ToSchema)]
+pub struct AppGroup {
+    /// Shared process name
+    pub name: String,
+    /// Every PID in the group
+    pub pids: Vec<u32>,
+    /// Summed `cpu_percent` of all members
+    pub cpu_percent: f32,
+    /// Summed resident memory in MiB
+    pub memory_mb: f64,
+    /// Summed `memory_percent` of all members
+    pub memory_percent: f32,
+    /// "stopped" when every member is suspended, otherwise "running"
+    pub status: String,
+    /// Number of PIDs in the group
+    pub process_count: usize,
+    /// Executable path of the first member seen, or "N/A"
+    pub exe: String,
+    /// False when any member is protected or the backend itself, in which case
+    /// `/api/app/close` refuses the group
+    pub is_closeable: bool,
+    /// Summed `disk_read_rate` of all members, bytes per second
+    pub disk_read_rate: f64,
+    /// Summed `disk_write_rate` of all members, bytes per second
+    pub disk_write_rate: f64,
+    /// `disk_read_rate` as e.g. "1.5 MB/s"
+    pub disk_read_rate_formatted: String,
+    pub disk_write_rate_formatted: String,
+    /// Summed network bytes received per second; null where the OS keeps no
+    /// per-process network counters (currently every platform)
+    pub net_recv_rate: Option<f64>,
+    pub net_sent_rate: Option<f64>,
+    pub net_recv_rate_formatted: Option<String>,
+    pub net_sent_rate_formatted: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AppsListResponse {
+    /// Apps sorted by `cpu_percent`, highest first
+    pub apps: Vec<AppGroup>,
+    /// Number of entries in `apps`
+    pub total_count: usize,
+}
+
+/// Every process of one user, aggregated for `/api/users`
+#[derive(Serialize, ToSchema)]
+pub struct UserSummary {
+    /// Account name; "N/A" collects processes whose owner can't be resolved
+    pub username: String,
+    /// True for root (Unix) and SYSTEM (Windows), which the UI may collapse
+    pub is_system: bool,
+    pub process_count: usize,
+    /// Summed `cpu_percent` of the user's processes
+    pub cpu_percent: f32,
+    /// Summed resident memory in MiB
+    pub memory_mb: f64,
+    /// Summed `memory_percent` of the user's processes
+    pub memory_percent: f32,
+    /// Up to 3 processes with the highest `cpu_percent`, busiest first
+    pub top_processes: Vec<ChildProcess>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UsersListResponse {
+    /// Users sorted by `cpu_percent`, highest first
+    pub users: Vec<UserSummary>,
+    /// Number of entries in `users`
+    pub total_count: usize,
+}
+
+/// Every process in one process group (a shell pipeline or job, a daemon and
+/// its workers), aggregated for `/api/process-groups`
+#[derive(Serialize, ToSchema)]
+pub struct ProcessGroupSummary {
+    pub pgid: u32,
+    /// Leader of the session the group belongs to, whose PID is the session
+    /// ID; it may have exited since
+    pub session_leader_pid: Option<u32>,
+    /// Name of the group leader, the process whose PID is `pgid`; null once
+    /// it has exited
+    pub leader_name: Option<String>,
+    /// Members, lowest PID first
+    pub pids: Vec<u32>,
+    pub process_count: usize,
+    /// Summed `cpu_percent` of the members
+    pub cpu_percent: f32,
+    /// Summed resident memory in MiB
+    pub memory_mb: f64,
+    /// Summed `memory_percent` of the members
+    pub memory_percent: f32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProcessGroupsResponse {
+    /// Groups sorted by `cpu_percent`, highest first
+    pub groups: Vec<ProcessGroupSummary>,
+    /// Number of entries in `groups`
+    pub total_count: usize,
+}
+
+/// Served by `POST /api/process-group/:pgid/kill`
+#[derive(Serialize, ToSchema)]
+pub struct ProcessGroupKillResponse {
+    pub pgid: u32,
+    /// Members when the signal was sent, lowest PID first
+    pub affected_pids: Vec<u32>,
+    pub signal: i32,
+}
+
+/// Processes of one container, aggregated like [`AppGroup`]
+#[derive(Serialize, ToSchema)]
+pub struct ContainerGroup {
+    /// Full 64-hex-digit container ID; `docker ps` shows the first 12
+    pub container_id: String,
+    /// Docker's name for the container when Docker is connected and knows
+    /// it, otherwise the name of its lowest PID, normally the entrypoint
+    pub name: String,
+    /// Image the container runs, e.g. "postgres:16"; Docker containers only
+    pub image: Option<String>,
+    /// Docker's state: "running", "paused", "restarting", ...
+    pub state: Option<String>,
+    /// When the container last started, RFC 3339 UTC
+    pub started_at: Option<String>,
+    /// Every PID in the container, lowest first
+    pub pids: Vec<u32>,
+    /// Summed `cpu_percent` of all members
+    pub cpu_percent: f32,
+    /// Summed resident memory in MiB
+    pub memory_mb: f64,
+    /// Summed `memory_percent` of all members
+    pub memory_percent: f32,
+    /// Number of PIDs in the container
+    pub process_count: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ContainersListResponse {
+    /// Containers sorted by `cpu_percent`, highest first
+    pub containers: Vec<ContainerGroup>,
+    /// Number of entries in `containers`
+    pub total_count: usize,
+    /// Whether the Docker daemon answered; without it `image`, `state` and
+    /// `started_at` are null
+    pub docker_connected: bool,
+}
+
+/// Served by `/api/process/:pid/info`
+#[derive(Serialize, ToSchema)]
+pub struct DetailedProcessInfo {
+    /// Process ID
+    pub pid: u32,
+    /// Executable name
+    pub name: String,
+    /// "running", "sleeping", "stopped", "zombie", "dead" or "unknown"
+    pub status: String,
+    /// Owning user, or "N/A"
+    pub username: String,
+    /// Start time as Unix epoch seconds
+    pub create_time: u64,
+    /// Start time as RFC 3339 UTC, e.g. "2023-11-14T22:13:20Z"; null when the
+    /// OS reports no start time (some kernel threads)
+    pub create_time_iso: Option<String>,
+    /// Seconds since the process started, 0 if its start is in the future
+    /// (clock skew); null like `create_time_iso`
+    pub running_seconds: Option<u64>,
+    /// `running_seconds` as e.g. "3h 12m"
+    pub running_formatted: Option<String>,
+    /// Raw CPU usage summed over cores (may exceed 100)
+    pub cpu_percent: f32,
+    /// CPU seconds spent in user mode since the process started (Linux only)
+    pub cpu_time_user_seconds: Option<f64>,
+    /// CPU seconds spent in the kernel on the process's behalf (Linux only)
+    pub cpu_time_system_seconds: Option<f64>,
+    /// Sum of the two
+    pub cpu_time_total_seconds: Option<f64>,
+    pub memory_info: ProcessMemoryInfo,
+    /// Proportional set size in KiB: shared pages split between their users.
+    /// Linux 4.14+ only; null elsewhere or without access to the process.
+    pub pss_kb: Option<u64>,
+    /// Unique set size in KiB: pages no other process maps. Null like `pss_kb`.
+    pub uss_kb: Option<u64>,
+    /// Resident memory by kind of mapping (Linux only); null without access
+    /// to `/proc/<pid>/smaps`
+    pub memory_breakdown: Option<ProcessMemoryBreakdown>,
+    /// Namespace inodes (Linux only); equal values mean a shared namespace
+    pub namespaces: Option<ProcessNamespaces>,
+    /// seccomp mode (Linux only); `disabled` means no syscall sandbox
+    pub seccomp_status: Option<SeccompStatus>,
+    /// OOM killer badness (Linux only); the highest score is killed first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oom_score: Option<i32>,
+    /// Adjustment from -1000 (never kill) to 1000 (kill first), Linux only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oom_score_adj: Option<i32>,
+    /// Page faults served without disk I/O (Linux only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minor_faults: Option<u64>,
+    /// Page faults that read from swap or a mapped file not yet in RAM;
+    /// a climbing count means the process is short of memory (Linux only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub major_faults: Option<u64>,
+    /// Minor faults of children the process has waited for (Linux only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children_minor_faults: Option<u64>,
+    /// Major faults of children the process has waited for (Linux only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children_major_faults: Option<u64>,
+    /// Parent process ID, kept even after the parent has exited
+    pub parent_pid: Option<u32>,
+    /// False when `parent_pid` is null or no longer running
+    pub parent_alive: bool,
+    /// Direct children, lowest PID first
+    pub children: Vec<ChildProcess>,
+    /// Thread count
+    pub num_threads: usize,
+    /// Executable path, or "N/A"
+    pub exe: String,
+    /// Lowercase hex SHA-256 of the executable file; null when the backend
+    /// can't read it
+    pub exe_sha256: Option<String>,
+    /// Working directory, or "N/A"
+    pub cwd: String,
+    /// False when the working directory has been deleted, can't be seen by
+    /// the backend, or is unknown
+    pub cwd_exists: bool,
+    /// Command line joined with spaces
+    pub cmdline: String,
+    /// Open network connections
+    pub connections: usize,
+    /// Open file handles
+    pub open_files: usize,
+}
+
+/// A kernel parameter; also the body of `POST /api/kernel/params`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct SysctlParam {
+    /// Dotted name, e.g. "net.core.somaxconn"
+    pub key: String,
+    /// As `sysctl` prints it; multiple values are space-separated
+    pub value: String,
+}
+
+/// A row of `/proc/interrupts`
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct IrqStat {
+    /// IRQ number, or a summary row such as "LOC" (local timer) or "NMI"
+    pub irq: String,
+    /// Interrupts handled by each CPU since boot; `ERR` and `MIS` have one total
+    pub per_cpu_counts: Vec<u64>,
+    /// Interrupt controller, e.g. "IO-APIC" or "PCI-MSI"; empty for summary rows
+    pub controller: String,
+    /// Devices sharing the line, or the description of a summary row
+    pub device_name: String,
+}
+
+/// A row of `/proc/softirqs`
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct SoftIrqStat {
+    /// e.g. "TIMER", "NET_RX", "BLOCK"
+    pub name: String,
+    /// Soft interrupts run on each CPU since boot
+    pub per_cpu_counts: Vec<u64>,
+}
+
+/// Served by `/api/kernel/interrupts`
+#[derive(Serialize, ToSchema)]
+pub struct InterruptStats {
+    /// Hardware interrupts per second on all CPUs over the sampling window
+    pub total_interrupts_per_sec: u64,
+    /// Length of the sampling window
+    pub sample_ms: u64,
+    pub interrupts: Vec<IrqStat>,
+    pub softirqs: Vec<SoftIrqStat>,
+}
+
+/// One thread of a process
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct ThreadInfo {
+    /// Thread ID; the main thread's equals the PID
+    pub tid: u32,
+    /// Thread name, e.g. "Compositor"; often the process name
+    pub name: String,
+    /// "running", "sleeping", "stopped", "zombie", "dead" or "unknown"
+    pub state: String,
+    /// Share of one core over the sampling window; null when it can't be measured
+    pub cpu_percent: Option<f32>,
+}
+
+/// Served by `/api/process/:pid/threads`
+#[derive(Serialize, ToSchema)]
+pub struct ProcessThreads {
+    pub pid: u32,
+    pub thread_count: usize,
+    /// Sum of the known `cpu_percent`s; null when none could be measured
+    pub total_cpu_percent: Option<f32>,
+    /// Length of the sampling window
+    pub sample_ms: u64,
+    /// Busiest first
+    pub threads: Vec<ThreadInfo>,
+}
+
+/// Served by `/api/process/:pid/io`; counters are cumulative since the process started
+#[derive(Serialize, ToSchema)]
+pub struct ProcessIoStats {
+    pub pid: u32,
+    /// Bytes fetched from storage; reads served from the page cache don't count
+    pub read_bytes: u64,
+    /// Bytes sent to storage, counted when dirty pages are queued for writeback
+    pub write_bytes: u64,
+    /// `read()`-family system calls, including ones on pipes and sockets
+    pub read_syscalls: u64,
+    /// `write()`-family system calls
+    pub write_syscalls: u64,
+    /// Bytes written to files that were truncated or deleted before reaching storage
+    pub cancelled_write_bytes: u64,
+    /// `read_bytes` as e.g. "1.5 GB"
+    pub read_bytes_formatted: String,
+    pub write_bytes_formatted: String,
+    /// Rates since the previous request for this PID within the last 60
+    /// seconds; null on the first request
+    pub per_sec: Option<ProcessIoRates>,
+}
+
+/// Change per second between two `/api/process/:pid/io` requests
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct ProcessIoRates {
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    pub read_syscalls_per_sec: f64,
+    pub write_syscalls_per_sec: f64,
+    /// Time between the two readings
+    pub interval_ms: u64,
+}
+
+/// A mapped file, or all of a process's anonymous memory
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct MemoryMapping {
+    /// Mapped file; null for the anonymous summary
+    pub path: Option<String>,
+    /// True for the one entry summing heap, stacks and other unnamed regions
+    pub anonymous: bool,
+    /// Address space covered by every region of this entry
+    pub total_bytes: u64,
+    /// Number of regions merged into this entry
+    pub regions: usize,
+    /// Distinct permission strings of those regions, e.g. "r-xp"
+    pub permissions: Vec<String>,
+}
+
+/// Served by `/api/process/:pid/maps`
+#[derive(Serialize, ToSchema)]
+pub struct ProcessMaps {
+    pub pid: u32,
+    /// Entries matching `filter`, before `offset` and `limit`
+    pub total_count: usize,
+    /// Anonymous summary first, then files sorted by path
+    pub mappings: Vec<MemoryMapping>,
+}
+
+/// Served by `/api/process/:pid/environ`
+#[derive(Serialize, ToSchema)]
+pub struct ProcessEnviron {
+    pub pid: u32,
+    /// Whether values of keys matching `redact_env` were replaced with "***"
+    pub redacted: bool,
+    /// Variables sorted by name
+    pub variables: BTreeMap<String, String>,
+}
+
+/// A process in brief: a direct child in [`DetailedProcessInfo::children`],
+/// or one of a user's busiest in [`UserSummary::top_processes`]
+#[derive(Serialize, ToSchema)]
+pub struct ChildProcess {
+    pub pid: u32,
+    pub name: String,
+    /// CPU usage divided by core count, 0-100, as in `/api/processes`
+    pub cpu_percent: f32,
+    /// Resident memory in MiB
+    pub memory_mb: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProcessMemoryInfo {
+    /// Resident set size in bytes
+    pub rss: u64,
+    /// Virtual memory size in bytes
+    pub vms: u64,
+    /// `rss` as a human-readable string
+    pub rss_formatted: String,
+    /// `vms` as a human-readable string
+    pub vms_formatted: String,
+}
+
+/// Resident memory in KiB by where it is mapped from, summed over
+/// `/proc/<pid>/smaps`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, ToSchema)]
+pub struct ProcessMemoryBreakdown {
+    /// Mappings with no backing file: `malloc` arenas beyond the heap,
+    /// `mmap(MAP_ANONYMOUS)` and thread stacks
+    pub anonymous_kb: u64,
+    /// Mappings of a file: executables, shared libraries, `mmap`ed data
+    pub file_mapped_kb: u64,
+    /// Pages also mapped by another process (`Shared_Clean` + `Shared_Dirty`),
+    /// across all mappings
+    pub shared_kb: u64,
+    /// The main thread's `[stack]`
+    pub stack_kb: u64,
+    /// The `brk` heap, `[heap]`
+    pub heap_kb: u64,
+}
+
+/// `GET /api/version`
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct VersionInfo {
+    /// Crate version, as `/health` reports it
+    pub version: String,
+    /// Full hash of the commit built; null when built outside a git checkout
+    pub git_commit: Option<String>,
+    /// Tracked files had uncommitted changes when built
+    pub git_dirty: Option<bool>,
+    /// RFC 3339 UTC; `SOURCE_DATE_EPOCH` when set
+    pub build_timestamp: Option<String>,
+    /// e.g. "rustc 1.80.0 (051478957 2024-07-21)"
+    pub rustc_version: Option<String>,
+    /// Target triple, e.g. "x86_64-unknown-linux-gnu"
+    pub target: String,
+}
+
+/// `GET /api/auth/token`
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct AuthToken {
+    /// Bearer token for mutating routes; null when auth is disabled
+    pub token: Option<String>,
+}
+
+/// `GET /health`
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct HealthResponse {
+    /// "ok", or "degraded" with the failing components named in `degraded`
+    pub status: String,
+    pub message: String,
+    /// Crate version; `/api/version` adds the build details
+    pub version: String,
+    /// Versions mounted under `/api/`, e.g. "v1"
+    pub api_versions: Vec<String>,
+    /// Keys of `components` that are failing; empty when `status` is "ok"
+    pub degraded: Vec<String>,
+    pub components: HealthComponents,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct HealthComponents {
+    pub sampler: SamplerHealth,
+    pub history: HistoryHealth,
+    /// "running", "stopped" after a write failure, or "disabled" without `audit_log`
+    pub audit_writer: String,
+    /// NVML loaded and reported a GPU in the latest sample
+    pub nvml_available: bool,
+    /// Mutating routes are refused (`auth.read_only`)
+    pub read_only: bool,
+}
+
+/// The background poller that publishes `/api/stats`
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SamplerHealth {
+    /// False before it starts and once it has exited or panicked
+    pub running: bool,
+    /// Milliseconds since the last sample was published
+    pub last_sample_age_ms: u64,
+    /// Configured poll interval; the sampler is degraded after 3 of them
+    pub interval_ms: u64,
+}
+
+/// Per-process CPU and memory history behind `/api/process/:pid/history`
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct HistoryHealth {
+    pub processes: usize,
+    /// Samples held across all processes
+    pub samples: usize,
+}
+
+/// `GET /api/self`: the backend process itself
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SelfMetrics {
+    pub pid: u32,
+    /// Resident memory in bytes; null when the system source doesn't list
+    /// the backend (the mock source)
+    pub memory_bytes: Option<u64>,
+    pub virtual_memory_bytes: Option<u64>,
+    /// CPU usage over the last poll interval, 0-100
+    pub cpu_percent: Option<f32>,
+    /// Seconds since the server started
+    pub uptime_seconds: u64,
+    /// Worker threads of the async runtime serving requests
+    pub worker_threads: usize,
+    /// WebSocket connections open right now
+    pub websocket_clients: usize,
+    /// Requests since start per route pattern, e.g. "/api/v1/process/:pid/kill";
+    /// refused ones (401, 429) included
+    pub requests: BTreeMap<String, u64>,
+    pub requests_total: u64,
+    /// Handler panics answered with `500 internal_error` since start
+    pub panics_total: u64,
+    pub history: HistoryUsage,
+}
+
+/// Memory held by the buffers behind `/api/process/:pid/history`
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct HistoryUsage {
+    pub processes: usize,
+    pub samples: usize,
+    /// Estimated heap size of the buffers, allocated but unused capacity included
+    pub bytes: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SuccessResponse {
+    /// Always true
+    pub success: bool,
+    /// Human-readable outcome
+    pub message: String,
+}
+
+/// Body of `POST /api/process/start`
+#[derive(Deserialize, ToSchema)]
+pub struct StartProcessRequest {
+    /// Executable name or path; must appear in `allowed_commands`
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory; defaults to the backend's own
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Extra environment variables, added to the backend's environment;
+    /// `PATH`, `PATHEXT`, `LD_*` and `DYLD_*` are refused
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Stream stdout/stderr over SSE until the process exits instead of returning immediately
+    #[serde(default)]
+    pub wait: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct StartProcessResponse {
+    pub pid: u32,
+    /// Always true
+    pub success: bool,
+}
+
+/// Body of `POST /api/process/launch`
+#[derive(Deserialize, ToSchema)]
+pub struct LaunchProcessRequest {
+    /// Executable name or path; must appear in `launch_executables` when that is set
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory; defaults to the backend's own
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Keep running after the backend exits; false leaves it in the backend's process group
+    #[serde(default = "default_true")]
+    pub detached: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LaunchProcessResponse {
+    pub pid: u32,
+    /// Always true
+    pub success: bool,
+    /// `/api/v1/process/<pid>/info`, to follow the new process
+    pub info_url: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RestartProcessResponse {
+    /// The process that was stopped
+    pub old_pid: u32,
+    /// The process started in its place
+    pub new_pid: u32,
+    pub name: String,
+    /// True when the process outlived the grace period and got SIGKILL
+    pub escalated: bool,
+    /// Always true
+    pub success: bool,
+    /// `/api/v1/process/<new_pid>/info`, to follow the new process
+    pub info_url: String,
+}
+
+#[derive(Serialize, ToSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeccompStatus {
+    Disabled,
+    /// Only read, write, _exit and sigreturn allowed
+    Strict,
+    /// A BPF filter decides which syscalls are allowed
+    Filter,
+}
+
+/// Linux capability sets of a process, each a list of names such as `CAP_NET_ADMIN`
+#[derive(Serialize, ToSchema, Clone, Debug, Default, PartialEq)]
+pub struct ProcessCapabilities {
+    /// What the kernel checks the process's privileged operations against
+    pub effective: Vec<String>,
+    /// The most the process can make effective
+    pub permitted: Vec<String>,
+    /// Kept across execve when the program's file capabilities allow it
+    pub inheritable: Vec<String>,
+    /// The limit on what the process and its children can ever gain
+    pub bounding: Vec<String>,
+    /// Kept across execve of programs without file capabilities
+    pub ambient: Vec<String>,
+}
+
+/// Inode numbers of a process's namespaces; each is null when its link can't be read
+#[derive(Serialize, ToSchema, Clone, Debug, Default, PartialEq)]
+pub struct ProcessNamespaces {
+    pub pid_ns: Option<u64>,
+    pub mnt_ns: Option<u64>,
+    pub net_ns: Option<u64>,
+    pub ipc_ns: Option<u64>,
+    pub user_ns: Option<u64>,
+    pub uts_ns: Option<u64>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct OomAdjustRequest {
+    /// New `oom_score_adj`, -1000 (never kill) to 1000 (kill first)
+    pub adjustment: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OomAdjustResponse {
+    pub pid: u32,
+    pub name: String,
+    /// Badness after the change
+    pub oom_score: i32,
+    pub oom_score_adj: i32,
+    pub success: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RenameProcessRequest {
+    /// New `comm`, 1-15 bytes
+    pub name: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RenameProcessResponse {
+    pub pid: u32,
+    pub old_name: String,
+    pub name: String,
+    pub success: bool,
+    /// What the rename does and doesn't change
+    pub note: String,
+}
+
+/// One row of `ulimit -a` for a process
+#[derive(Serialize, ToSchema, Clone, Debug, PartialEq)]
+pub struct ResourceLimit {
+    /// Key as `ulimit`/`prlimit` spell it, e.g. "nofile"
+    pub resource: String,
+    /// As `/proc/<pid>/limits` names it, e.g. "Max open files"
+    pub name: String,
+    /// null when unlimited
+    pub soft: Option<u64>,
+    /// Ceiling the process may raise `soft` to; null when unlimited
+    pub hard: Option<u64>,
+    /// "bytes", "files", "seconds", ...; null for the priorities
+    pub unit: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProcessResourceLimits {
+    pub pid: u32,
+    pub limits: Vec<ResourceLimit>,
+    /// Descriptors open now, to compare with `nofile`; null unless the
+    /// backend may list them (Linux, same user or root)
+    pub open_files: Option<u64>,
+    /// Peak physical footprint so far (macOS only)
+    pub max_rss_bytes: Option<u64>,
+}
+
+/// A loaded kernel module (Linux) or kext (macOS)
+#[derive(Serialize, ToSchema)]
+pub struct KernelModule {
+    pub name: String,
+    pub size_bytes: u64,
+    /// Reference count reported by the kernel
+    pub used_by_count: u32,
+    /// Modules that depend on this one (empty on macOS)
+    pub used_by: Vec<String>,
+    /// "Live", "Loading" or "Unloading"
+    pub state: String,
+}
+
+/// A connected USB device, as served by `/api/hardware/usb`
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct UsbDevice {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// From the bundled `usb.ids`, else the device's manufacturer string
+    pub vendor_name: Option<String>,
+    /// From the bundled `usb.ids`, else the device's product string
+    pub product_name: Option<String>,
+    pub serial: Option<String>,
+    /// Device class, e.g. "Hub" or "Human Interface Device"; for devices
+    /// that define it per interface, the first interface's class
+    pub class: String,
+    pub bus: u8,
+    /// Address on the bus
+    pub device_number: u8,
+    /// Negotiated speed, e.g. "480 Mbps" or "5 Gbps"; "unknown" if not reported
+    pub speed: String,
+}
+
+/// A PCI function, as served by `/api/hardware/pci`
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct PciDevice {
+    pub domain: u16,
+    pub bus: u8,
+    pub slot: u8,
+    pub function: u8,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    /// e.g. "Display controller"; the code in hex when unlisted
+    pub class: String,
+    /// e.g. "VGA compatible controller"; the code in hex when unlisted
+    pub subclass: String,
+    /// From the bundled `pci.ids`
+    pub vendor_name: Option<String>,
+    /// From the bundled `pci.ids`
+    pub device_name: Option<String>,
+    /// Kernel driver bound to the device, e.g. "nvidia" or "vfio-pci"
+    pub driver: Option<String>,
+    /// `None` when the IOMMU is off or absent
+    pub iommu_group: Option<u32>,
+}
+
+/// Active kill rules, as served by `/api/policy`
+#[derive(Serialize, ToSchema)]
+pub struct KillPolicyInfo {
+    /// Always protected by PID
+    pub protected_pids: Vec<u32>,
+    /// Exact names from `TASKMGR_PROTECTED_PROCESSES` / `protected_processes`;
+    /// `?force=true` overrides these
+    pub protected_processes: Vec<String>,
+    /// Globs over process name or exe path that can never be killed
+    pub protected_names: Vec<String>,
+    /// When non-empty, the only processes that can be killed
+    pub killable_only_names: Vec<String>,
+    /// Whether the globs are case-sensitive (false on Windows)
+    pub case_sensitive: bool,
+}
+
+/// A PID `/api/app/suspend` or `/api/app/resume` could not signal
+#[derive(Serialize, ToSchema)]
+pub struct PidFailure {
+    pub pid: u32,
+    /// The error message, e.g. "Process 42 not found"
+    pub reason: String,
+}
+
+/// Body of `POST /api/processes/batch-kill`
+#[derive(Deserialize, ToSchema)]
+pub struct BatchKillRequest {
+    /// 1 to 1000 PIDs; duplicates are signalled once
+    pub pids: Vec<u32>,
+    /// 1 (HUP), 2 (INT), 3 (QUIT), 9 (KILL) or 15 (TERM); defaults to 9 like `/api/process/:pid/kill`
+    #[serde(default)]
+    pub signal: Option<i32>,
+}
+
+/// Per-PID outcome of `POST /api/processes/batch-kill`
+#[derive(Serialize, ToSchema)]
+pub struct BatchKillResponse {
+    pub killed: Vec<u32>,
+    pub not_found: Vec<u32>,
+    /// Protected, forbidden by the kill policy, the backend itself, or not ours to signal
+    pub forbidden: Vec<u32>,
+    /// Any other failure, by PID
+    pub errors: HashMap<u32, String>,
+}
+
+/// How `POST /api/processes/kill_by_name` compares `name`
+#[derive(Deserialize, ToSchema, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NameMatch {
+    /// The process name or full exe path equals `name`
+    #[default]
+    Exact,
+    /// `name` is a glob over the process name or exe path, as in `protected_names`
+    Glob,
+}
+
+/// Body of `POST /api/processes/kill_by_name`
+#[derive(Deserialize, ToSchema)]
+pub struct KillByNameRequest {
+    pub name: String,
+    #[serde(default, rename = "match")]
+    pub match_mode: NameMatch,
+    /// Report what would be killed without signalling anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, ToSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KillOutcome {
+    Killed,
+    /// Dry run only: the checks pass, so a real request would kill it
+    WouldKill,
+    /// Refused by the protection rules, the kill policy or the self-kill check
+    Skipped,
+    Failed,
+}
+
+/// One matching process in `POST /api/processes/kill_by_name`
+#[derive(Serialize, ToSchema)]
+pub struct KillByNameResult {
+    pub pid: u32,
+    pub name: String,
+    pub exe: Option<String>,
+    pub outcome: KillOutcome,
+    /// Error code for `skipped` and `failed`, e.g. "process_protected"
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct KillByNameResponse {
+    pub dry_run: bool,
+    /// Matching processes, by PID
+    pub results: Vec<KillByNameResult>,
+    pub killed: usize,
+    pub would_kill: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Per-PID outcome of `/api/app/suspend`
+#[derive(Serialize, ToSchema)]
+pub struct AppSuspendResponse {
+    pub suspended: Vec<u32>,
+    pub failed: Vec<PidFailure>,
+}
+
+/// Per-PID outcome of `/api/app/resume`
+#[derive(Serialize, ToSchema)]
+pub struct AppResumeResponse {
+    pub resumed: Vec<u32>,
+    pub failed: Vec<PidFailure>,
+}
+
+/// One poll's reading of a process, for `/api/process/:pid/history`
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct HistorySample {
+    /// Unix epoch seconds
+    pub timestamp: u64,
+    /// Divided by core count, 0-100, as in `/api/processes`
+    pub cpu_percent: f32,
+    /// Resident memory in MiB
+    pub memory_mb: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProcessHistoryResponse {
+    pub pid: u32,
+    /// Look-back actually applied, after clamping to 1-10
+    pub minutes: u64,
+    /// Oldest first
+    pub samples: Vec<HistorySample>,
+}
+
+/// A process's resident memory over the window of `/api/processes/growth`
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct MemoryGrowthEntry {
+    pub pid: u32,
+    pub name: String,
+    /// Unix epoch seconds of the first point in the window; later than the
+    /// window's start when tracking began within it
+    pub since: u64,
+    /// Resident memory in MiB at the last point
+    pub memory_mb: f64,
+    /// Last point minus first, in MiB; negative when the process shrank
+    pub growth_mb: f64,
+    /// `growth_mb` as a percentage of the first point
+    pub growth_percent: f64,
+    /// Resident MiB once a minute, oldest first, for a sparkline
+    pub samples: Vec<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MemoryGrowthResponse {
+    /// Look-back actually applied, after clamping to 1-120
+    pub minutes: u64,
+    /// Processes currently tracked, before the window and limit are applied
+    pub tracked: usize,
+    /// Largest growth first
+    pub processes: Vec<MemoryGrowthEntry>,
+}
+
+/// A threshold alert from a `[[alerts]]` rule
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct Alert {
+    /// Increases with each alert since the backend started
+    pub id: u64,
+    /// Name of the rule that fired
+    pub rule: String,
+    /// Unix epoch seconds when the rule's condition had held for its sustain window
+    pub triggered_at: u64,
+    /// Unix epoch seconds of the first sample without the condition; null while firing
+    pub resolved_at: Option<u64>,
+    /// The metric's latest reading while firing
+    pub current_value: f64,
+}
+
+/// An alert rule as listed by `/api/alert_rules`
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct AlertRuleInfo {
+    /// Assigned at startup and on creation; not kept across restarts, unlike `name`
+    pub id: u64,
+    pub name: String,
+    pub metric: AlertMetric,
+    pub operator: Comparison,
+    pub threshold: f64,
+    pub sustained_secs: u64,
+    pub process: Option<String>,
+    /// Scheme and host of the rule's own webhook; null when it uses `[alert_webhook]`
+    pub webhook: Option<String>,
+    /// Whether the rule has an alert firing now
+    pub firing: bool,
+}
+
+/// Body of `POST /api/alert_rules` and `PUT /api/alert_rules/:id`; the same
+/// fields as an `[[alerts]]` table
+#[derive(Deserialize, ToSchema)]
+pub struct AlertRuleRequest {
+    /// Unique among the rules
+    pub name: String,
+    /// "cpu_percent", "memory_percent" or "disk_percent"
+    pub metric: String,
+    /// ">", ">=", "<" or "<="
+    pub operator: String,
+    /// Greater than 0
+    pub threshold: f64,
+    #[serde(default)]
+    pub sustained_secs: u64,
+    pub process: Option<String>,
+    pub webhook: Option<AlertRuleWebhook>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AlertRuleWebhook {
+    pub url: String,
+    /// "json" (default) or "slack"
+    pub format: Option<String>,
+}
+
+/// Delivery results of one alert webhook
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct WebhookStatus {
+    /// Scheme and host of the URL; the path is hidden as it usually holds a secret
+    pub target: String,
+    /// Notifications accepted with a 2xx
+    pub delivered: u64,
+    /// Notifications given up on, after retries
+    pub failures: u64,
+    /// Status of the last response; null if it never answered
+    pub last_status_code: Option<u16>,
+    /// Why the last notification failed; null once one succeeds
+    pub last_error: Option<String>,
+    /// Unix epoch seconds of the last notification's final attempt
+    pub last_attempt_at: Option<u64>,
+}
+
+/// One destructive action, as served by `/api/audit`
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct AuditEntry {
+    /// Unix epoch seconds
+    pub timestamp: u64,
+    /// "kill", "suspend" or "resume"
+    pub action: String,
+    pub pid: u32,
+    /// `null` when the process did not exist
+    pub process_name: Option<String>,
+    /// "success", or the error code the request failed with
+    pub outcome: String,
+    pub client_ip: Option<String>,
+    /// First 8 hex digits of the bearer token's SHA-256
+    pub token_id: Option<String>,
+    /// `X-Request-ID` of the request, as in the backend's request log
+    pub request_id: Option<String>,
+}
+
+/// Where a startup entry is registered
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupSource {
+    /// A `.desktop` file in an XDG autostart directory (Linux)
+    XdgAutostart,
+    /// A user systemd unit wanted by a target (Linux)
+    SystemdUser,
+    /// A value under a `Run` registry key (Windows)
+    Registry,
+    /// A file or shortcut in a Startup folder (Windows)
+    StartupFolder,
+}
+
+/// A program started at login, served by `/api/startup`
+#[derive(Serialize, ToSchema, Clone, Debug, PartialEq)]
+pub struct StartupItem {
+    /// Entry name: the desktop file's `Name`, the unit or registry value name,
+    /// or the shortcut name
+    pub name: String,
+    /// Command line as registered; empty when it couldn't be read
+    pub command: String,
+    pub source: StartupSource,
+    /// File, directory or registry key the entry lives in
+    pub location: String,
+    /// False when the entry is there but switched off (`Hidden=true`, or
+    /// disabled in Task Manager's Startup tab)
+    pub enabled: bool,
+    /// Oldest running process whose executable matches the command
+    pub pid: Option<u32>,
+    /// CPU usage of that process, 0-100
+    pub cpu_percent: Option<f32>,
+    /// Resident memory of that process in bytes
+    pub memory_bytes: Option<u64>,
+}
+
+/// A systemd service unit
+#[derive(Serialize, ToSchema)]
+pub struct ServiceInfo {
+    /// Unit name, e.g. "nginx.service"; the service name on Windows, e.g. "wuauserv"
+    pub name: String,
+    /// Unit description; the display name on Windows, e.g. "Windows Update"
+    pub description: String,
+    /// "loaded", "not-found", "masked", ...; always "loaded" on Windows
+    pub load_state: String,
+    /// "active", "inactive", "failed", "activating", ...
+    pub active_state: String,
+    /// "running", "exited", "dead", "auto-restart", ...; on Windows the
+    /// service state: "running", "stopped", "paused", "start pending", ...
+    pub sub_state: String,
+    /// Whether it starts at boot: systemd's "enabled", "disabled", "static",
+    /// ...; on Windows "auto", "manual", "disabled", "boot" or "system"
+    pub start_type: Option<String>,
+    /// Main process, when the service is running; the same `pid` as in
+    /// `/api/processes`. On Windows several services can share one svchost.exe.
+    pub pid: Option<u32>,
+    /// cgroup memory usage; null when memory accounting is off
+    pub memory_bytes: Option<u64>,
+    /// Times systemd restarted the unit after it failed, since it was last
+    /// started by hand; a climbing count means a restart loop. Null before systemd 235.
+    pub restart_count: Option<u32>,
+    /// CPU usage of the main process, 0-100
+    pub cpu_percent: Option<f32>,
+}
+
+/// Served by `/api/services`
+#[derive(Serialize, ToSchema)]
+pub struct ServicesResponse {
+    /// False on macOS and on Linux systems not booted with systemd
+    pub supported: bool,
+    /// Sorted by unit name
+    pub services: Vec<ServiceInfo>,
+}
+
+/// SMART health report for one disk, from `smartctl --json -a`
+#[derive(Serialize, ToSchema)]
+pub struct SmartData {
+    /// Device name as requested, e.g. "sda"
+    pub device: String,
+    /// "PASSED", "FAILED", "UNKNOWN", or "UNSUPPORTED" when the device has no SMART
+    pub health: String,
+    pub model: Option<String>,
+    pub temperature_celsius: Option<u32>,
+    /// Raw value of ATA attribute 5; null for NVMe and non-ATA disks
+    pub reallocated_sectors: Option<u64>,
+    pub power_on_hours: Option<u64>,
+    /// ATA attribute table; empty for NVMe and non-ATA disks
+    pub attributes: Vec<SmartAttribute>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SmartAttribute {
+    pub id: u32,
+    pub name: String,
+    /// Normalized value; lower is worse
+    pub value: u32,
+    pub worst: u32,
+    /// Failure threshold for `value`
+    pub thresh: u32,
+    pub raw_value: u64,
+    /// True when the attribute is at or below its threshold
+    pub failing: bool,
+}
+
+/// A mounted filesystem with byte and inode usage
+#[derive(Serialize, ToSchema)]
+pub struct MountInfo {
+    /// Source, e.g. "/dev/sda1" or "server:/export"
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    /// Mount options, e.g. ["rw", "relatime"]
+    pub options: Vec<String>,
+    /// nfs, cifs, sshfs and similar
+    pub is_network_fs: bool,
+    pub is_read_only: bool,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    /// Space available to unprivileged users
+    pub free_bytes: u64,
+    /// 0 for filesystems without fixed inode tables (btrfs, tmpfs on some kernels)
+    pub inode_total: u64,
+    pub inode_free: u64,
+}
+
+/// One TCP or UDP socket from the kernel's socket tables
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct TcpConnection {
+    /// "127.0.0.1", "::1", "0.0.0.0" for any
+    pub local_address: String,
+    pub local_port: u16,
+    pub remote_address: String,
+    /// 0 for listening and unconnected sockets
+    pub remote_port: u16,
+    /// TCP state such as "ESTABLISHED" or "LISTEN"; "UNCONN" for unconnected UDP
+    pub state: String,
+    /// Owning process; `None` when its `/proc/<pid>/fd` isn't readable
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+    /// "tcp", "tcp6", "udp" or "udp6"
+    pub protocol: String,
+    /// Inode of the network namespace the socket lives in, as `net_ns` in
+    /// `/api/process/:pid/info`; null when it can't be read
+    pub namespace_inode: Option<u64>,
+}
+
+/// A neighbour from the kernel's ARP cache
+#[derive(Serialize, ToSchema)]
+pub struct ArpEntry {
+    pub ip_address: String,
+    /// ARP hardware type: "0x1" (Ethernet) on Linux, e.g. "ethernet" on macOS
+    pub hw_type: String,
+    /// Linux: "0x2" complete, "0x6" permanent, "0x0" incomplete; macOS: "permanent" or ""
+    pub flags: String,
+    /// Lowercase, two digits per octet; "00:00:00:00:00:00" while unresolved
+    pub mac_address: String,
+    /// "*" unless the entry is a proxy ARP entry
+    pub mask: String,
+    pub device: String,
+}
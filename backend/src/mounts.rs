@@ -0,0 +1,164 @@
+//! Mounted filesystems: `/proc/mounts` + `statvfs` on Linux, `getmntinfo` on macOS.
+//!
+//! Unlike [`crate::models::DiskStats`] this reports inodes, so "no space left"
+//! with free bytes (inode exhaustion) is visible.
+
+use crate::error::ApiError;
+use crate::models::MountInfo;
+
+const NETWORK_FS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "sshfs", "fuse.sshfs", "9p", "afs", "afpfs",
+    "ceph", "glusterfs", "fuse.glusterfs", "davfs", "fuse.davfs2", "lustre", "webdav",
+];
+
+pub fn is_network_fs(fs_type: &str) -> bool {
+    NETWORK_FS.contains(&fs_type)
+}
+
+/// Lists every mount. `statvfs` can block on an unreachable network share,
+/// so this runs on the blocking pool.
+pub async fn list() -> Result<Vec<MountInfo>, ApiError> {
+    tokio::task::spawn_blocking(list_blocking)
+        .await
+        .map_err(|_| ApiError::Internal)?
+}
+
+#[cfg(target_os = "linux")]
+fn list_blocking() -> Result<Vec<MountInfo>, ApiError> {
+    let text = std::fs::read_to_string("/proc/mounts").map_err(|err| {
+        tracing::warn!(error = %err, "cannot read /proc/mounts");
+        ApiError::Internal
+    })?;
+    let mut mounts = parse_proc_mounts(&text);
+    for mount in &mut mounts {
+        fill_usage(mount);
+    }
+    Ok(mounts)
+}
+
+#[cfg(target_os = "macos")]
+fn list_blocking() -> Result<Vec<MountInfo>, ApiError> {
+    use std::ffi::CStr;
+
+    let mut entries: *mut libc::statfs = std::ptr::null_mut();
+    // SAFETY: getmntinfo points `entries` at a libc-owned array of `count` statfs structs
+    let count = unsafe { libc::getmntinfo(&mut entries, libc::MNT_NOWAIT) };
+    if count <= 0 || entries.is_null() {
+        return Err(ApiError::Internal);
+    }
+    // SAFETY: see above; the array stays valid until the next getmntinfo call on this thread
+    let entries = unsafe { std::slice::from_raw_parts(entries, count as usize) };
+
+    let text = |chars: &[libc::c_char]| {
+        // SAFETY: statfs name fields are NUL-terminated
+        unsafe { CStr::from_ptr(chars.as_ptr()) }.to_string_lossy().into_owned()
+    };
+    Ok(entries
+        .iter()
+        .map(|fs| {
+            let flags = fs.f_flags as i64;
+            let read_only = flags & libc::MNT_RDONLY as i64 != 0;
+            let mut options = vec![if read_only { "ro" } else { "rw" }.to_string()];
+            for (flag, name) in [
+                (libc::MNT_NOSUID as i64, "nosuid"),
+                (libc::MNT_NOEXEC as i64, "noexec"),
+                (libc::MNT_NODEV as i64, "nodev"),
+                (libc::MNT_LOCAL as i64, "local"),
+            ] {
+                if flags & flag != 0 {
+                    options.push(name.to_string());
+                }
+            }
+            let block_size = fs.f_bsize as u64;
+            let fs_type = text(&fs.f_fstypename);
+            MountInfo {
+                device: text(&fs.f_mntfromname),
+                mount_point: text(&fs.f_mntonname),
+                is_network_fs: is_network_fs(&fs_type),
+                fs_type,
+                options,
+                is_read_only: read_only,
+                total_bytes: fs.f_blocks * block_size,
+                used_bytes: (fs.f_blocks - fs.f_bfree) * block_size,
+                free_bytes: fs.f_bavail * block_size,
+                inode_total: fs.f_files,
+                inode_free: fs.f_ffree,
+            }
+        })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn list_blocking() -> Result<Vec<MountInfo>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Mount point listing" })
+}
+
+/// Parses `/proc/mounts`; usage fields are left at 0
+pub fn parse_proc_mounts(text: &str) -> Vec<MountInfo> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = unescape(fields.next()?);
+            let mount_point = unescape(fields.next()?);
+            let fs_type = fields.next()?.to_string();
+            let options: Vec<String> = fields.next()?.split(',').map(String::from).collect();
+            Some(MountInfo {
+                device,
+                mount_point,
+                is_network_fs: is_network_fs(&fs_type),
+                is_read_only: options.iter().any(|o| o == "ro"),
+                fs_type,
+                options,
+                total_bytes: 0,
+                used_bytes: 0,
+                free_bytes: 0,
+                inode_total: 0,
+                inode_free: 0,
+            })
+        })
+        .collect()
+}
+
+/// The kernel writes space, tab, newline and backslash as `\ooo` octal escapes
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes
+            .get(i + 1..i + 4)
+            .filter(|digits| bytes[i] == b'\\' && digits.iter().all(|b| (b'0'..=b'7').contains(b)))
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match octal {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(target_os = "linux")]
+fn fill_usage(mount: &mut MountInfo) {
+    let Ok(path) = std::ffi::CString::new(mount.mount_point.as_bytes()) else {
+        return;
+    };
+    // SAFETY: `path` is NUL-terminated and `stat` is a valid out-pointer
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return;
+    }
+
+    let fragment = if stat.f_frsize > 0 { stat.f_frsize } else { stat.f_bsize } as u64;
+    let blocks = stat.f_blocks as u64;
+    mount.total_bytes = blocks * fragment;
+    mount.used_bytes = blocks.saturating_sub(stat.f_bfree as u64) * fragment;
+    mount.free_bytes = stat.f_bavail as u64 * fragment;
+    mount.inode_total = stat.f_files as u64;
+    mount.inode_free = stat.f_ffree as u64;
+}
@@ -0,0 +1,123 @@
+//! MessagePack as an alternative to JSON, chosen per request with `Accept` and
+//! `Content-Type: application/msgpack`.
+//!
+//! Handlers respond with this module's [`Json`], which keeps the value so
+//! [`crate::middleware::MsgpackLayer`] can encode the typed value with
+//! `rmp_serde`: an `f32` stays a float32 and NaN stays NaN, where JSON has to
+//! write `null`. Responses built elsewhere are transcoded from their JSON body.
+
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+
+use crate::error::ApiError;
+
+pub const MSGPACK: &str = "application/msgpack";
+
+/// Accepted in `Accept` and `Content-Type` alongside [`MSGPACK`]
+const MSGPACK_LEGACY: &str = "application/x-msgpack";
+
+/// `true` when `value`'s media type (parameters ignored) is MessagePack
+fn is_msgpack(value: &str) -> bool {
+    let media_type = value.split(';').next().unwrap_or("").trim();
+    media_type.eq_ignore_ascii_case(MSGPACK) || media_type.eq_ignore_ascii_case(MSGPACK_LEGACY)
+}
+
+/// Whether `Accept` ranks MessagePack above zero and no lower than JSON
+pub fn accepts_msgpack(headers: &HeaderMap) -> bool {
+    let (mut msgpack, mut json) = (0.0f32, 0.0f32);
+    let ranges = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','));
+    for range in ranges {
+        if is_msgpack(range) {
+            msgpack = msgpack.max(quality(range));
+        } else if range.split(';').next().unwrap_or("").trim().eq_ignore_ascii_case("application/json") {
+            json = json.max(quality(range));
+        }
+    }
+    msgpack > 0.0 && msgpack >= json
+}
+
+/// The `q=` parameter of a media range, 1 when absent
+fn quality(range: &str) -> f32 {
+    range
+        .split(';')
+        .skip(1)
+        .filter_map(|param| param.trim().strip_prefix("q="))
+        .find_map(|q| q.trim().parse().ok())
+        .unwrap_or(1.0)
+}
+
+pub fn content_type() -> HeaderValue {
+    HeaderValue::from_static(MSGPACK)
+}
+
+/// Request body decoded from JSON or, with `Content-Type: application/msgpack`, MessagePack
+pub struct MsgpackOrJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for MsgpackOrJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = axum::response::Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let msgpack = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(is_msgpack);
+        if !msgpack {
+            let axum::Json(value) = axum::Json::<T>::from_request(req, state)
+                .await
+                .map_err(|rejection| ApiError::InvalidBody { reason: rejection.body_text() }.into_response())?;
+            return Ok(Self(value));
+        }
+
+        let bytes = Bytes::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+        rmp_serde::from_slice(&bytes)
+            .map(Self)
+            .map_err(|err| ApiError::InvalidBody { reason: err.to_string() }.into_response())
+    }
+}
+
+/// A JSON response that also carries its value for [`MsgpackEncoder`]
+pub struct Json<T>(pub T);
+
+impl<T> IntoResponse for Json<T>
+where
+    T: Serialize + Send + Sync + 'static,
+{
+    fn into_response(self) -> Response {
+        let mut response = axum::Json(&self.0).into_response();
+        let value = Arc::new(self.0);
+        response
+            .extensions_mut()
+            .insert(MsgpackEncoder(Arc::new(move || rmp_serde::to_vec_named(&*value))));
+        response
+    }
+}
+
+type Encode = dyn Fn() -> Result<Vec<u8>, rmp_serde::encode::Error> + Send + Sync;
+
+/// Response extension that serialises the handler's value as MessagePack,
+/// field names included
+#[derive(Clone)]
+pub struct MsgpackEncoder(Arc<Encode>);
+
+impl MsgpackEncoder {
+    pub fn encode(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        (self.0)()
+    }
+}
@@ -0,0 +1,36 @@
+//! Linux namespaces of a process, from the `/proc/<pid>/ns/*` links.
+//!
+//! Each link reads like `net:[4026531833]`; the number is the namespace's
+//! inode, so two processes with the same `net_ns` share a network stack.
+
+use crate::models::ProcessNamespaces;
+
+/// `None` on other platforms, or when none of the links can be read
+/// (process gone or owned by another user)
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<ProcessNamespaces> {
+    let inode = |kind: &str| {
+        let target = std::fs::read_link(format!("/proc/{}/ns/{}", pid, kind)).ok()?;
+        parse_ns_link(&target.to_string_lossy())
+    };
+    let namespaces = ProcessNamespaces {
+        pid_ns: inode("pid"),
+        mnt_ns: inode("mnt"),
+        net_ns: inode("net"),
+        ipc_ns: inode("ipc"),
+        user_ns: inode("user"),
+        uts_ns: inode("uts"),
+    };
+    (namespaces != ProcessNamespaces::default()).then_some(namespaces)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<ProcessNamespaces> {
+    None
+}
+
+/// The inode in a link target like `net:[4026531833]`
+pub fn parse_ns_link(target: &str) -> Option<u64> {
+    let (_, rest) = target.split_once(":[")?;
+    rest.strip_suffix(']')?.parse().ok()
+}
@@ -0,0 +1,250 @@
+//! Per-interface link state: carrier, speed, duplex, MAC, addresses, MTU and WiFi
+
+use std::collections::HashMap;
+use sysinfo::{NetworkData, Networks};
+
+use crate::models::NetworkInterfaceStats;
+use crate::wifi;
+
+/// Link-layer details of one interface; fields the OS doesn't report stay `None`
+#[derive(Debug, Default, PartialEq)]
+pub struct LinkInfo {
+    pub is_up: bool,
+    pub speed_mbps: Option<u64>,
+    pub duplex: Option<String>,
+    pub mac_address: Option<String>,
+    pub mtu: Option<u32>,
+}
+
+/// Counters from `networks` joined with each interface's link state, sorted by name
+pub fn interfaces(networks: &Networks) -> Vec<NetworkInterfaceStats> {
+    let mut addresses = ip_addresses();
+    let mut wireless = wifi::read();
+    let mut list: Vec<NetworkInterfaceStats> = networks
+        .iter()
+        .map(|(name, data)| {
+            let link = link_info(name, data);
+            NetworkInterfaceStats {
+                name: name.clone(),
+                bytes_sent: data.total_transmitted(),
+                bytes_recv: data.total_received(),
+                is_up: link.is_up,
+                speed_mbps: link.speed_mbps,
+                duplex: link.duplex,
+                mac_address: link.mac_address,
+                ip_addresses: addresses
+                    .remove(name)
+                    .unwrap_or_else(|| data.ip_networks().iter().map(|net| net.addr.to_string()).collect()),
+                mtu: link.mtu,
+                wifi: wireless.remove(name),
+            }
+        })
+        .collect();
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+    list
+}
+
+/// Builds a [`LinkInfo`] from the files in `/sys/class/net/<iface>`; `read`
+/// returns a file's contents, or `None` when it is missing or unreadable
+/// (`speed` and `duplex` fail with `EINVAL` while the link is down).
+pub fn parse_sysfs_link(read: impl Fn(&str) -> Option<String>) -> LinkInfo {
+    let value = |file: &str| read(file).map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+
+    // Loopback and some virtual devices report "unknown"; fall back to carrier
+    let is_up = match value("operstate").as_deref() {
+        Some("up") => true,
+        Some("unknown") => value("carrier").as_deref() == Some("1"),
+        _ => false,
+    };
+    LinkInfo {
+        is_up,
+        // -1 when the driver doesn't know
+        speed_mbps: value("speed").and_then(|s| s.parse::<i64>().ok()).filter(|&s| s > 0).map(|s| s as u64),
+        duplex: value("duplex").filter(|d| d == "full" || d == "half"),
+        mac_address: value("address").filter(|a| a != "00:00:00:00:00:00"),
+        mtu: value("mtu").and_then(|s| s.parse().ok()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn link_info(name: &str, _data: &NetworkData) -> LinkInfo {
+    let dir = std::path::Path::new("/sys/class/net").join(name);
+    parse_sysfs_link(|file| std::fs::read_to_string(dir.join(file)).ok())
+}
+
+#[cfg(target_os = "macos")]
+fn link_info(name: &str, _data: &NetworkData) -> LinkInfo {
+    let mut link = macos::link_layer().remove(name).unwrap_or_default();
+    if let Some(media) = macos::media_status(name) {
+        link.is_up &= media.active;
+        link.duplex = media.duplex;
+    }
+    link
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn link_info(_name: &str, data: &NetworkData) -> LinkInfo {
+    let mac = data.mac_address();
+    LinkInfo {
+        is_up: !data.ip_networks().is_empty(),
+        mac_address: (!mac.is_unspecified()).then(|| mac.to_string()),
+        ..LinkInfo::default()
+    }
+}
+
+/// IPv4 and IPv6 addresses by interface name, via `getifaddrs`
+#[cfg(unix)]
+fn ip_addresses() -> HashMap<String, Vec<String>> {
+    let mut by_name: HashMap<String, Vec<String>> = HashMap::new();
+    ifaddrs::for_each(|name, entry| {
+        // SAFETY: `ifa_addr` is null or points to a sockaddr owned by the list
+        if let Some(ip) = unsafe { ifaddrs::ip(entry.ifa_addr) } {
+            by_name.entry(name.to_string()).or_default().push(ip.to_string());
+        }
+    });
+    by_name
+}
+
+/// Other platforms fall back to sysinfo's addresses in [`interfaces`]
+#[cfg(not(unix))]
+fn ip_addresses() -> HashMap<String, Vec<String>> {
+    HashMap::new()
+}
+
+#[cfg(unix)]
+mod ifaddrs {
+    use std::ffi::CStr;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    /// Calls `f` with the name and entry of every `getifaddrs` record
+    pub fn for_each(mut f: impl FnMut(&str, &libc::ifaddrs)) {
+        let mut head: *mut libc::ifaddrs = std::ptr::null_mut();
+        // SAFETY: on success `head` is a list we own until `freeifaddrs`
+        if unsafe { libc::getifaddrs(&mut head) } != 0 {
+            return;
+        }
+        let mut cursor = head;
+        // SAFETY: every `ifa_next` is null or a valid entry of the same list
+        while let Some(entry) = unsafe { cursor.as_ref() } {
+            cursor = entry.ifa_next;
+            // SAFETY: `ifa_name` is a NUL-terminated string owned by the list
+            let name = unsafe { CStr::from_ptr(entry.ifa_name) }.to_string_lossy();
+            f(&name, entry);
+        }
+        // SAFETY: `head` came from `getifaddrs` and is freed exactly once
+        unsafe { libc::freeifaddrs(head) };
+    }
+
+    /// Reads an AF_INET/AF_INET6 address; other families give `None`
+    ///
+    /// # Safety
+    /// `addr` must be null or point to a sockaddr of the size its family implies.
+    pub unsafe fn ip(addr: *const libc::sockaddr) -> Option<IpAddr> {
+        match i32::from(addr.as_ref()?.sa_family) {
+            libc::AF_INET => {
+                let sin = &*(addr as *const libc::sockaddr_in);
+                Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr))))
+            }
+            libc::AF_INET6 => {
+                let sin6 = &*(addr as *const libc::sockaddr_in6);
+                Some(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::collections::HashMap;
+    use std::os::fd::AsRawFd;
+
+    use super::{ifaddrs, LinkInfo};
+
+    /// `struct ifmediareq` from `<net/if.h>`, which Darwin packs to 4 bytes
+    #[repr(C, packed(4))]
+    struct IfMediaReq {
+        ifm_name: [libc::c_char; libc::IFNAMSIZ],
+        ifm_current: libc::c_int,
+        ifm_mask: libc::c_int,
+        ifm_status: libc::c_int,
+        ifm_active: libc::c_int,
+        ifm_count: libc::c_int,
+        ifm_ulist: *mut libc::c_int,
+    }
+
+    /// `_IOWR('i', 56, struct ifmediareq)`
+    const SIOCGIFMEDIA: libc::c_ulong = 0xc02c_6938;
+    const IFM_AVALID: libc::c_int = 0x1;
+    const IFM_ACTIVE: libc::c_int = 0x2;
+    const IFM_FDX: libc::c_int = 0x0010_0000;
+    const IFM_HDX: libc::c_int = 0x0020_0000;
+
+    pub struct MediaStatus {
+        pub active: bool,
+        pub duplex: Option<String>,
+    }
+
+    /// Flags, MAC, MTU and baud rate from the AF_LINK entries of `getifaddrs`
+    pub fn link_layer() -> HashMap<String, LinkInfo> {
+        let mut links = HashMap::new();
+        ifaddrs::for_each(|name, entry| {
+            // SAFETY: `ifa_addr` is null or a sockaddr owned by the list
+            let Some(addr) = (unsafe { entry.ifa_addr.as_ref() }) else { return };
+            if i32::from(addr.sa_family) != libc::AF_LINK {
+                return;
+            }
+            // SAFETY: AF_LINK entries carry a sockaddr_dl and an if_data
+            let (dl, data) = unsafe {
+                (
+                    &*(entry.ifa_addr as *const libc::sockaddr_dl),
+                    (entry.ifa_data as *const libc::if_data).as_ref(),
+                )
+            };
+            let flags = entry.ifa_flags as libc::c_int;
+            let start = dl.sdl_nlen as usize;
+            let mac = dl
+                .sdl_data
+                .get(start..start + dl.sdl_alen as usize)
+                .filter(|bytes| bytes.len() == 6)
+                .map(|bytes| bytes.iter().map(|b| format!("{:02x}", *b as u8)).collect::<Vec<_>>().join(":"));
+            links.insert(
+                name.to_string(),
+                LinkInfo {
+                    is_up: flags & libc::IFF_UP != 0 && flags & libc::IFF_RUNNING != 0,
+                    speed_mbps: data.map(|d| d.ifi_baudrate as u64 / 1_000_000).filter(|&s| s > 0),
+                    duplex: None,
+                    mac_address: mac,
+                    mtu: data.map(|d| d.ifi_mtu),
+                },
+            );
+        });
+        links
+    }
+
+    /// Carrier and duplex via `SIOCGIFMEDIA`; `None` for interfaces without media
+    pub fn media_status(name: &str) -> Option<MediaStatus> {
+        let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+        // SAFETY: all-zero is a valid `ifmediareq` with no media list requested
+        let mut req: IfMediaReq = unsafe { std::mem::zeroed() };
+        for (dst, src) in req.ifm_name.iter_mut().zip(name.bytes().take(libc::IFNAMSIZ - 1)) {
+            *dst = src as libc::c_char;
+        }
+        // SAFETY: `req` outlives the call and matches the layout the ioctl expects
+        if unsafe { libc::ioctl(socket.as_raw_fd(), SIOCGIFMEDIA, &mut req) } != 0 {
+            return None;
+        }
+        let (status, active) = (req.ifm_status, req.ifm_active);
+        let duplex = if active & IFM_FDX != 0 {
+            Some("full".to_string())
+        } else if active & IFM_HDX != 0 {
+            Some("half".to_string())
+        } else {
+            None
+        };
+        Some(MediaStatus {
+            active: status & IFM_AVALID == 0 || status & IFM_ACTIVE != 0,
+            duplex,
+        })
+    }
+}
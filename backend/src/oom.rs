@@ -0,0 +1,59 @@
+//! Who the OOM killer picks first, from `/proc/<pid>/oom_score` and
+//! `/proc/<pid>/oom_score_adj`
+
+use crate::error::ApiError;
+
+/// The kernel kills the process with the highest `score` when memory runs out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OomScore {
+    /// 0-1000 (more with a positive adjustment): roughly the share of RAM
+    /// and swap the process uses, in tenths of a percent, plus `adj`
+    pub score: i32,
+    /// -1000 (never kill) to 1000 (kill first), set by the process or its manager
+    pub adj: i32,
+}
+
+/// `None` on other platforms or when the process is gone
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<OomScore> {
+    let read = |file: &str| std::fs::read_to_string(format!("/proc/{}/{}", pid, file)).ok();
+    Some(OomScore {
+        score: parse_score(&read("oom_score")?)?,
+        adj: parse_score(&read("oom_score_adj")?)?,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<OomScore> {
+    None
+}
+
+/// Valid values of `oom_score_adj`
+pub const ADJ_RANGE: std::ops::RangeInclusive<i32> = -1000..=1000;
+
+/// Lowering the adjustment below its previous value needs `CAP_SYS_RESOURCE`;
+/// raising it is allowed for the process's owner
+#[cfg(target_os = "linux")]
+pub fn write_adj(pid: u32, adj: i32) -> Result<(), ApiError> {
+    std::fs::write(format!("/proc/{}/oom_score_adj", pid), adj.to_string()).map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => ApiError::ProcessNotFound { pid },
+        std::io::ErrorKind::PermissionDenied => ApiError::PermissionDenied { pid },
+        _ if err.raw_os_error() == Some(libc::EINVAL) => ApiError::InvalidBody {
+            reason: format!("the kernel rejected an OOM score adjustment of {}", adj),
+        },
+        _ => {
+            tracing::warn!(pid, error = %err, "cannot write oom_score_adj");
+            ApiError::Internal
+        }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn write_adj(_pid: u32, _adj: i32) -> Result<(), ApiError> {
+    Err(ApiError::NotImplemented { feature: "OOM score adjustment" })
+}
+
+/// Both files hold a single decimal number and a newline
+pub fn parse_score(text: &str) -> Option<i32> {
+    text.trim().parse().ok()
+}
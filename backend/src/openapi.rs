@@ -0,0 +1,140 @@
+//! OpenAPI 3 description of the HTTP API, served at `/openapi.json`
+
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::error::{ErrorBody, ErrorEnvelope, FieldError};
+use crate::handlers;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Task Manager Pro API",
+        description = "Real-time process and system monitoring backend"
+    ),
+    paths(
+        handlers::health_check,
+        handlers::get_metrics,
+        handlers::get_stats,
+        handlers::get_stats_binary_ws,
+        handlers::get_cpu_info,
+        handlers::get_processes,
+        handlers::export_processes,
+        handlers::batch_kill,
+        handlers::kill_by_name,
+        handlers::get_process_groups,
+        handlers::kill_process_group,
+        handlers::get_apps,
+        handlers::get_users,
+        handlers::get_self,
+        handlers::get_version,
+        handlers::get_auth_token,
+        handlers::get_containers,
+        handlers::stop_container,
+        handlers::kill_app,
+        handlers::suspend_app,
+        handlers::resume_app,
+        handlers::kill_process,
+        handlers::restart_process,
+        handlers::start_process,
+        handlers::launch_process,
+        handlers::suspend_process,
+        handlers::resume_process,
+        handlers::set_oom_adj,
+        handlers::rename_process,
+        handlers::get_process_info,
+        handlers::get_process_children,
+        handlers::get_process_maps,
+        handlers::get_process_threads,
+        handlers::get_process_io,
+        handlers::get_process_limits,
+        handlers::get_process_capabilities,
+        handlers::get_process_history,
+        handlers::get_memory_growth,
+        handlers::get_process_connections,
+        handlers::get_process_environ,
+        handlers::get_process_output_ws,
+        handlers::get_audit,
+        handlers::get_alerts,
+        handlers::get_alert_history,
+        handlers::get_webhook_status,
+        handlers::get_alert_rules,
+        handlers::create_alert_rule,
+        handlers::update_alert_rule,
+        handlers::delete_alert_rule,
+        handlers::get_policy,
+        handlers::get_disk_smart,
+        handlers::set_disk_scheduler,
+        handlers::get_pci_devices,
+        handlers::get_usb_devices,
+        handlers::get_kernel_modules,
+        handlers::get_kernel_interrupts,
+        handlers::get_kernel_params,
+        handlers::set_kernel_param,
+        handlers::get_mounts,
+        handlers::get_connections,
+        handlers::get_arp,
+        handlers::get_services,
+        handlers::start_service,
+        handlers::stop_service,
+        handlers::restart_service,
+        handlers::get_startup,
+    ),
+    components(schemas(ErrorEnvelope, ErrorBody, FieldError)),
+    modifiers(&BearerAuth, &VersionedPaths, &ServiceAliases),
+    tags(
+        (name = "system", description = "Machine-wide statistics"),
+        (name = "processes", description = "Process listing and control"),
+        (name = "services", description = "systemd units and Windows services")
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the `bearer` scheme referenced by the mutating routes
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// Documents the routes under `/api/v1`; handlers are annotated with the
+/// unversioned paths, which remain as deprecated aliases
+struct VersionedPaths;
+
+impl Modify for VersionedPaths {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let paths = std::mem::take(&mut openapi.paths.paths);
+        openapi.paths.paths = paths
+            .into_iter()
+            .map(|(path, item)| match path.strip_prefix("/api/") {
+                Some(rest) => (format!("/api/v1/{}", rest), item),
+                None => (path, item),
+            })
+            .collect();
+    }
+}
+
+/// Documents `/service/:name/...`, which routes to the same handlers as
+/// `/services/:name/...`
+struct ServiceAliases;
+
+impl Modify for ServiceAliases {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for action in ["start", "stop", "restart"] {
+            let Some(mut item) = openapi.paths.paths.get(&format!("/api/v1/services/{{name}}/{action}")).cloned() else {
+                continue;
+            };
+            // Operation IDs must stay unique across the spec
+            if let Some(operation) = item.post.as_mut() {
+                operation.operation_id = Some(format!("{action}_service_singular"));
+            }
+            openapi.paths.paths.insert(format!("/api/v1/service/{{name}}/{action}"), item);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! `/api/ws/process/:pid/output`: a process's stdout and stderr line by line
+//! as WebSocket text frames `{"stream": "stdout", "line": "..."}`, then
+//! `{"event": "exit", "code": 0}` once it exits.
+//!
+//! Processes started by `POST /api/process/start` without `wait` are taken
+//! over by [`SpawnedProcesses`]: a task owns the [`Child`], reads both pipes
+//! until it exits and fans the lines out to every attached client. Lines
+//! written while nobody is attached are dropped, and a client too slow to
+//! keep up skips ahead.
+//!
+//! Other processes are followed on Linux when `/proc/<pid>/fd/1` or `fd/2` is
+//! a regular file, i.e. output redirected to a log. A pipe or terminal can't
+//! be read without taking the data from its real reader, so those are refused.
+//! Their exit code isn't known to us, so the exit frame carries `null`.
+
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Child;
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::ApiError;
+
+/// Lines buffered per spawned process for clients that fall behind
+pub const OUTPUT_CHANNEL_CAPACITY: usize = 1024;
+
+/// How often a followed file is checked for new lines and its process for exit
+pub const FOLLOW_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputEvent {
+    /// One line, without its line ending
+    Line(OutputStream, String),
+    /// Exit code; `None` when killed by a signal or not known
+    Exit(Option<i32>),
+}
+
+impl OutputEvent {
+    /// The text frame sent for this event
+    pub fn frame(&self) -> String {
+        match self {
+            OutputEvent::Line(stream, line) => serde_json::json!({ "stream": stream.as_str(), "line": line }).to_string(),
+            OutputEvent::Exit(code) => serde_json::json!({ "event": "exit", "code": code }).to_string(),
+        }
+    }
+}
+
+/// Output channels of the processes this server started and that are still
+/// running, by PID; cheap to clone
+#[derive(Clone, Default)]
+pub struct SpawnedProcesses {
+    channels: Arc<Mutex<HashMap<u32, broadcast::Sender<OutputEvent>>>>,
+}
+
+impl SpawnedProcesses {
+    /// Takes over `child`, whose stdout and stderr should be piped: reads
+    /// both until it exits, then reaps it and forgets its PID
+    pub fn track(&self, mut child: Child) {
+        let (tx, _) = broadcast::channel(OUTPUT_CHANNEL_CAPACITY);
+        let pid = child.id();
+        if let Some(pid) = pid {
+            self.channels.lock().expect("spawned mutex poisoned").insert(pid, tx.clone());
+        }
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let channels = self.channels.clone();
+        tokio::spawn(async move {
+            let (_, _, status) = tokio::join!(
+                forward_lines(stdout, OutputStream::Stdout, &tx),
+                forward_lines(stderr, OutputStream::Stderr, &tx),
+                child.wait(),
+            );
+            // Forgotten first, so a client attaching now can't wait for an
+            // exit event that was already sent
+            if let Some(pid) = pid {
+                channels.lock().expect("spawned mutex poisoned").remove(&pid);
+            }
+            let _ = tx.send(OutputEvent::Exit(status.ok().and_then(|s| s.code())));
+        });
+    }
+
+    /// `pid`'s output, if this server started it and it is still running
+    pub fn subscribe(&self, pid: u32) -> Option<ProcessOutput> {
+        let channels = self.channels.lock().expect("spawned mutex poisoned");
+        channels.get(&pid).map(|tx| ProcessOutput::Spawned(tx.subscribe()))
+    }
+}
+
+/// Keeps reading with nobody subscribed so the child never blocks on a full pipe
+async fn forward_lines(pipe: Option<impl AsyncRead + Unpin>, stream: OutputStream, tx: &broadcast::Sender<OutputEvent>) {
+    let Some(pipe) = pipe else {
+        return;
+    };
+    let mut reader = BufReader::new(pipe);
+    let mut line = Vec::new();
+    while matches!(reader.read_until(b'\n', &mut line).await, Ok(n) if n > 0) {
+        let _ = tx.send(OutputEvent::Line(stream, trim_line(&line)));
+        line.clear();
+    }
+}
+
+fn trim_line(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+/// Where a connection's events come from
+pub enum ProcessOutput {
+    Spawned(broadcast::Receiver<OutputEvent>),
+    Followed(mpsc::Receiver<OutputEvent>),
+}
+
+impl ProcessOutput {
+    /// The next event; `None` once the source is gone without an exit event
+    pub async fn next(&mut self) -> Option<OutputEvent> {
+        match self {
+            ProcessOutput::Spawned(rx) => loop {
+                match rx.recv().await {
+                    Ok(event) => return Some(event),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            },
+            ProcessOutput::Followed(rx) => rx.recv().await,
+        }
+    }
+}
+
+/// Follows the files `pid`'s stdout and stderr are redirected to, from their
+/// current end. 404 for no such process, 403 when its descriptors can't be
+/// inspected, 409 when neither is a regular file.
+#[cfg(target_os = "linux")]
+pub fn follow(pid: u32) -> Result<ProcessOutput, ApiError> {
+    let proc_dir = std::path::PathBuf::from(format!("/proc/{}", pid));
+    if !proc_dir.exists() {
+        return Err(ApiError::ProcessNotFound { pid });
+    }
+    let mut files: Vec<(OutputStream, std::path::PathBuf)> = Vec::new();
+    let mut targets = Vec::new();
+    for (fd, stream) in [(1, OutputStream::Stdout), (2, OutputStream::Stderr)] {
+        let path = proc_dir.join("fd").join(fd.to_string());
+        match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.is_file() => {
+                // `2>&1` into the same log would otherwise send every line twice
+                let target = std::fs::read_link(&path).ok();
+                if target.is_none() || !targets.contains(&target) {
+                    targets.push(target);
+                    files.push((stream, path));
+                }
+            }
+            Ok(_) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+                return Err(ApiError::PermissionDenied { pid });
+            }
+            Err(_) => {}
+        }
+    }
+    if files.is_empty() {
+        return Err(ApiError::OutputNotAvailable { pid });
+    }
+
+    let (tx, rx) = mpsc::channel(OUTPUT_CHANNEL_CAPACITY);
+    tokio::spawn(async move {
+        let followers = files.into_iter().map(|(stream, path)| follow_file(pid, stream, path, tx.clone()));
+        futures::future::join_all(followers).await;
+        let _ = tx.send(OutputEvent::Exit(None)).await;
+    });
+    Ok(ProcessOutput::Followed(rx))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn follow(pid: u32) -> Result<ProcessOutput, ApiError> {
+    Err(ApiError::OutputNotAvailable { pid })
+}
+
+/// Sends lines appended to `path` until `pid` exits or the receiver is gone.
+/// A line still being written is held back until its newline arrives.
+#[cfg(target_os = "linux")]
+async fn follow_file(pid: u32, stream: OutputStream, path: std::path::PathBuf, tx: mpsc::Sender<OutputEvent>) {
+    use tokio::io::AsyncSeekExt;
+
+    // Opened through /proc, so a log rotated away since is still the one followed
+    let Ok(mut file) = tokio::fs::File::open(&path).await else {
+        return;
+    };
+    if file.seek(std::io::SeekFrom::End(0)).await.is_err() {
+        return;
+    }
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    loop {
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(_) if line.ends_with(b"\n") => {
+                if tx.send(OutputEvent::Line(stream, trim_line(&line))).await.is_err() {
+                    return;
+                }
+                line.clear();
+            }
+            Ok(_) => {
+                if !is_running(pid) || tx.is_closed() {
+                    return;
+                }
+                tokio::time::sleep(FOLLOW_INTERVAL).await;
+            }
+            Err(_) => return,
+        }
+    }
+}
+
+/// Exited processes linger as zombies (`Z`) until their parent reaps them
+#[cfg(target_os = "linux")]
+fn is_running(pid: u32) -> bool {
+    std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .ok()
+        .and_then(|stat| stat.rsplit_once(')').and_then(|(_, rest)| rest.trim_start().chars().next()))
+        .is_some_and(|state| state != 'Z' && state != 'X')
+}
+
+/// Serves one connection until the process exits, the client leaves or
+/// shutdown begins
+pub async fn stream(mut socket: WebSocket, mut output: ProcessOutput, shutdown: CancellationToken) {
+    loop {
+        tokio::select! {
+            event = output.next() => {
+                let event = event.unwrap_or(OutputEvent::Exit(None));
+                let exited = matches!(event, OutputEvent::Exit(_));
+                if socket.send(Message::Text(event.frame())).await.is_err() {
+                    break;
+                }
+                if exited {
+                    let close = CloseFrame { code: close_code::NORMAL, reason: "process exited".into() };
+                    let _ = socket.send(Message::Close(Some(close))).await;
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                // Nothing to control; axum answers pings itself
+                Some(Ok(_)) => {}
+            },
+            _ = shutdown.cancelled() => {
+                let close = CloseFrame { code: close_code::AWAY, reason: "server shutting down".into() };
+                let _ = socket.send(Message::Close(Some(close))).await;
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! PCI devices from `/sys/bus/pci/devices` (Linux only), with the driver
+//! bound to each and its IOMMU group, for checking GPU passthrough and SR-IOV
+//! setups. Names come from the system's `pci.ids`, or the bundled
+//! `data/pci.ids` when it has none.
+
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+use crate::hwids::{self, IdDatabase};
+use crate::models::PciDevice;
+
+/// Where `hwdata` (Fedora, Arch) and `pciutils` (Debian, Ubuntu) install it
+pub const SYSTEM_IDS: &[&str] = &["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids"];
+
+/// Snapshot of <https://pci-ids.ucw.cz/>
+const BUNDLED_IDS: &str = include_str!("../data/pci.ids");
+
+/// Read and parsed on first use
+pub fn ids() -> &'static IdDatabase {
+    static IDS: OnceLock<IdDatabase> = OnceLock::new();
+    IDS.get_or_init(|| hwids::load(SYSTEM_IDS, BUNDLED_IDS))
+}
+
+/// Splits `0000:00:1f.2` into domain, bus, slot and function
+pub fn parse_address(address: &str) -> Option<(u16, u8, u8, u8)> {
+    let (domain, rest) = address.split_once(':')?;
+    let (bus, rest) = rest.split_once(':')?;
+    let (slot, function) = rest.split_once('.')?;
+    Some((
+        u16::from_str_radix(domain, 16).ok()?,
+        u8::from_str_radix(bus, 16).ok()?,
+        u8::from_str_radix(slot, 16).ok()?,
+        u8::from_str_radix(function, 16).ok()?,
+    ))
+}
+
+/// Builds a [`PciDevice`] from the entry `/sys/bus/pci/devices/<address>`.
+/// `read` returns a file's contents and `link` the last component of a
+/// symlink's target, each `None` when missing. `None` for a malformed address
+/// or missing IDs.
+pub fn parse_sysfs_pci(
+    address: &str,
+    ids: &IdDatabase,
+    read: impl Fn(&str) -> Option<String>,
+    link: impl Fn(&str) -> Option<String>,
+) -> Option<PciDevice> {
+    let (domain, bus, slot, function) = parse_address(address)?;
+    let hex = |file: &str| read(file).and_then(|v| u32::from_str_radix(v.trim().trim_start_matches("0x"), 16).ok());
+    let vendor_id = hex("vendor")? as u16;
+    let device_id = hex("device")? as u16;
+    // 0xCCSSPP: class, subclass, programming interface
+    let class_code = hex("class").unwrap_or(0);
+    let (class, subclass) = ((class_code >> 16) as u8, (class_code >> 8) as u8);
+    Some(PciDevice {
+        domain,
+        bus,
+        slot,
+        function,
+        vendor_id,
+        device_id,
+        class: ids.class(class).map(str::to_string).unwrap_or_else(|| format!("{:02x}", class)),
+        subclass: ids.subclass(class, subclass).map(str::to_string).unwrap_or_else(|| format!("{:02x}", subclass)),
+        vendor_name: ids.vendor(vendor_id).map(str::to_string),
+        device_name: ids.device(vendor_id, device_id).map(str::to_string),
+        driver: link("driver"),
+        iommu_group: link("iommu_group").and_then(|group| group.parse().ok()),
+    })
+}
+
+/// Every function on every bus, by address
+#[cfg(target_os = "linux")]
+pub fn list() -> Result<Vec<PciDevice>, ApiError> {
+    let root = std::path::Path::new("/sys/bus/pci/devices");
+    let entries = match std::fs::read_dir(root) {
+        Ok(entries) => entries,
+        // No PCI bus (some ARM boards, containers without it in /sys)
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            tracing::warn!(error = %err, "cannot read /sys/bus/pci/devices");
+            return Err(ApiError::Internal);
+        }
+    };
+    let ids = ids();
+    let mut devices: Vec<PciDevice> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let address = entry.file_name().to_string_lossy().into_owned();
+            let dir = root.join(&address);
+            parse_sysfs_pci(
+                &address,
+                ids,
+                |file| std::fs::read_to_string(dir.join(file)).ok(),
+                |file| {
+                    let target = std::fs::read_link(dir.join(file)).ok()?;
+                    Some(target.file_name()?.to_string_lossy().into_owned())
+                },
+            )
+        })
+        .collect();
+    devices.sort_by_key(|d| (d.domain, d.bus, d.slot, d.function));
+    Ok(devices)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn list() -> Result<Vec<PciDevice>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "PCI device listing" })
+}
@@ -0,0 +1,123 @@
+//! Site kill policy from the `--config` file: `protected_names` that may never
+//! be killed, and `killable_only_names` that, when set, are the only processes
+//! that may be. Unlike the built-in protection rules, `?force=true` can't override it.
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::error::ApiError;
+use crate::source::ProcessSample;
+
+/// How patterns and process names/paths are compared
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchStyle {
+    /// Case-sensitive, `/` separators
+    Unix,
+    /// Case-insensitive, `\` and `/` both separators
+    Windows,
+}
+
+impl Default for MatchStyle {
+    fn default() -> Self {
+        Self::NATIVE
+    }
+}
+
+impl MatchStyle {
+    pub const NATIVE: Self = if cfg!(windows) { MatchStyle::Windows } else { MatchStyle::Unix };
+
+    /// Windows paths are compared with `/` so `\` never acts as a glob escape
+    fn normalize(self, text: &str) -> String {
+        match self {
+            MatchStyle::Unix => text.to_string(),
+            MatchStyle::Windows => text.replace('\\', "/"),
+        }
+    }
+}
+
+/// Glob patterns matched against a process's name and its executable path.
+/// `*` also crosses path separators, so `*/bin/*` matches at any depth.
+#[derive(Clone, Debug, Default)]
+pub struct PatternList {
+    patterns: Vec<String>,
+    matchers: Vec<GlobMatcher>,
+    style: MatchStyle,
+}
+
+impl PatternList {
+    pub fn new(patterns: &[String], style: MatchStyle) -> Result<Self, globset::Error> {
+        let matchers = patterns
+            .iter()
+            .map(|pattern| {
+                GlobBuilder::new(&style.normalize(pattern))
+                    .case_insensitive(style == MatchStyle::Windows)
+                    .build()
+                    .map(|glob| glob.compile_matcher())
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            patterns: patterns.to_vec(),
+            matchers,
+            style,
+        })
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn style(&self) -> MatchStyle {
+        self.style
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether any pattern matches `name` or `exe`
+    pub fn matches(&self, name: &str, exe: Option<&str>) -> bool {
+        [Some(name), exe]
+            .into_iter()
+            .flatten()
+            .map(|text| self.style.normalize(text))
+            .any(|text| self.matchers.iter().any(|m| m.is_match(&text)))
+    }
+}
+
+/// Compared by pattern text; the compiled matchers follow from it
+impl PartialEq for PatternList {
+    fn eq(&self, other: &Self) -> bool {
+        self.patterns == other.patterns && self.style == other.style
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KillPolicy {
+    pub protected_names: PatternList,
+    /// Empty means every process not otherwise protected may be killed
+    pub killable_only_names: PatternList,
+}
+
+impl KillPolicy {
+    pub fn new(protected_names: &[String], killable_only_names: &[String], style: MatchStyle) -> Result<Self, globset::Error> {
+        Ok(Self {
+            protected_names: PatternList::new(protected_names, style)?,
+            killable_only_names: PatternList::new(killable_only_names, style)?,
+        })
+    }
+
+    /// Why the policy forbids killing `process`, if it does
+    pub fn check(&self, process: &ProcessSample) -> Result<(), ApiError> {
+        let exe = process.exe.as_deref();
+        if self.protected_names.matches(&process.name, exe) {
+            return Err(ApiError::ProcessProtected { pid: process.pid });
+        }
+        if !self.killable_only_names.is_empty() && !self.killable_only_names.matches(&process.name, exe) {
+            return Err(ApiError::KillNotAllowed { pid: process.pid });
+        }
+        Ok(())
+    }
+
+    pub fn allows(&self, process: &ProcessSample) -> bool {
+        self.check(process).is_ok()
+    }
+}
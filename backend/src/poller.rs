@@ -0,0 +1,183 @@
+//! Background stats collection.
+//!
+//! A single task refreshes [`SystemStats`] on a fixed interval and publishes
+//! it through an [`ArcSwap`], so `/api/stats` never touches sysinfo itself.
+//! Each tick also re-samples processes ([`SystemSource::refresh_processes`]),
+//! so per-process CPU is measured over the full interval and the process
+//! endpoints answer from that sample. The sample is then checked against the
+//! alert rules and appended to each process's history and, once a minute, to
+//! the memory growth tracks. The task exits once the shutdown token is
+//! cancelled.
+//!
+//! [`PollerHealth`] records when the last sample was published and whether
+//! the task is still running, for `/health`. [`OnDemandRefresh`] lets
+//! `/api/stats?max_age_ms=` replace a snapshot that is too old without
+//! waiting for the next tick.
+
+use arc_swap::ArcSwap;
+use axum::http::HeaderName;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+
+use crate::alerts::AlertEngine;
+use crate::growth::MemoryGrowth;
+use crate::history::ProcessHistory;
+use crate::models::SystemStats;
+use crate::source::SystemSource;
+use crate::utils::unix_now;
+
+/// Milliseconds since the stats in the response were sampled
+pub static STATS_AGE_HEADER: HeaderName = HeaderName::from_static("x-stats-age-ms");
+
+/// `/api/stats` refreshes a snapshot older than this unless `max_age_ms` says otherwise
+pub const DEFAULT_MAX_STATS_AGE: Duration = Duration::from_secs(2);
+
+/// `/health` reports the sampler as stalled once the last sample is this many
+/// poll intervals old
+pub const STALE_SAMPLE_INTERVALS: u32 = 3;
+
+struct HealthInner {
+    running: AtomicBool,
+    last_sample: Mutex<Instant>,
+}
+
+/// Shared view of the poller's progress; cheap to clone
+#[derive(Clone)]
+pub struct PollerHealth {
+    inner: Arc<HealthInner>,
+}
+
+impl PollerHealth {
+    /// `sampled_at` is when the snapshot the state starts with was taken
+    pub fn new(sampled_at: Instant) -> Self {
+        Self {
+            inner: Arc::new(HealthInner {
+                running: AtomicBool::new(false),
+                last_sample: Mutex::new(sampled_at),
+            }),
+        }
+    }
+
+    /// Time since the last sample was published
+    pub fn last_sample_age(&self) -> Duration {
+        self.inner.last_sample.lock().expect("poller health mutex poisoned").elapsed()
+    }
+
+    /// False before the poller starts and once it has returned or panicked
+    pub fn is_running(&self) -> bool {
+        self.inner.running.load(Ordering::Relaxed)
+    }
+
+    fn sampled(&self) {
+        *self.inner.last_sample.lock().expect("poller health mutex poisoned") = Instant::now();
+    }
+}
+
+/// Marks the poller running for as long as it is held, unwinding included
+struct RunningGuard(PollerHealth);
+
+impl RunningGuard {
+    fn new(health: PollerHealth) -> Self {
+        health.inner.running.store(true, Ordering::Relaxed);
+        Self(health)
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.inner.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Refreshes the stats snapshot between poller ticks. Only stats are
+/// re-sampled; the process table, alerts and history still move with the
+/// poller.
+#[derive(Clone, Default)]
+pub struct OnDemandRefresh {
+    /// When the last on-demand refresh was published. Held across a refresh,
+    /// so concurrent callers wait for it instead of starting their own.
+    last: Arc<tokio::sync::Mutex<Option<Instant>>>,
+}
+
+impl OnDemandRefresh {
+    /// Age of `snapshot` after refreshing it, if it was older than `max_age`
+    pub async fn ensure_fresh(
+        &self,
+        source: &Arc<dyn SystemSource>,
+        snapshot: &ArcSwap<SystemStats>,
+        health: &PollerHealth,
+        max_age: Duration,
+    ) -> Duration {
+        let mut last = self.last.lock().await;
+        let age = match *last {
+            Some(refreshed) => refreshed.elapsed().min(health.last_sample_age()),
+            None => health.last_sample_age(),
+        };
+        if age <= max_age {
+            return age;
+        }
+
+        let source = source.clone();
+        match tokio::task::spawn_blocking(move || source.snapshot_stats()).await {
+            Ok(stats) => {
+                snapshot.store(Arc::new(stats));
+                *last = Some(Instant::now());
+                Duration::ZERO
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "on-demand stats refresh failed");
+                age
+            }
+        }
+    }
+}
+
+/// Runs until `shutdown` is cancelled; spawn it on the state's task tracker
+#[allow(clippy::too_many_arguments)]
+pub async fn poll_stats(
+    source: Arc<dyn SystemSource>,
+    snapshot: Arc<ArcSwap<SystemStats>>,
+    alerts: AlertEngine,
+    history: ProcessHistory,
+    growth: MemoryGrowth,
+    health: PollerHealth,
+    interval: Duration,
+    shutdown: CancellationToken,
+) {
+    let _running = RunningGuard::new(health.clone());
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+        let source = source.clone();
+        let alerts = alerts.clone();
+        let history = history.clone();
+        let growth = growth.clone();
+        let started = Instant::now();
+        let refresh = move || {
+            source.refresh_processes();
+            let stats = source.snapshot_stats();
+            let table = source.list_processes();
+            let now = unix_now();
+            alerts.observe_stats(now, &stats, Some(&table));
+            history.record(now, &table);
+            growth.record(now, &table);
+            stats
+        };
+        match tokio::task::spawn_blocking(refresh).await {
+            Ok(stats) => {
+                snapshot.store(Arc::new(stats));
+                health.sampled();
+                tracing::debug!(elapsed_ms = started.elapsed().as_millis() as u64, "stats refreshed");
+            }
+            Err(err) => tracing::warn!(error = %err, "stats refresh failed"),
+        }
+    }
+}
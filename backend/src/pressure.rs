@@ -0,0 +1,45 @@
+//! Memory pressure stall information (PSI) from `/proc/pressure/memory`,
+//! available on Linux 4.20+ kernels built with `CONFIG_PSI`
+
+use crate::models::{MemoryPressure, PressureStall};
+
+/// `None` where the file doesn't exist: other platforms, older kernels, or
+/// PSI disabled with `psi=0`
+#[cfg(target_os = "linux")]
+pub fn read_memory() -> Option<MemoryPressure> {
+    parse_psi(&std::fs::read_to_string("/proc/pressure/memory").ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_memory() -> Option<MemoryPressure> {
+    None
+}
+
+/// Parses `some avg10=0.31 avg60=0.12 avg300=0.03 total=123456` and the
+/// matching `full` line; `full` is optional, as in `/proc/pressure/cpu`
+/// before Linux 5.13
+pub fn parse_psi(text: &str) -> Option<MemoryPressure> {
+    let line = |kind: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(kind)?.strip_prefix(' '))
+            .and_then(parse_stall)
+    };
+    Some(MemoryPressure {
+        some: line("some")?,
+        full: line("full"),
+    })
+}
+
+fn parse_stall(fields: &str) -> Option<PressureStall> {
+    let field = |name: &str| {
+        fields
+            .split_whitespace()
+            .find_map(|field| field.strip_prefix(name)?.strip_prefix('='))
+    };
+    Some(PressureStall {
+        avg10: field("avg10")?.parse().ok()?,
+        avg60: field("avg60")?.parse().ok()?,
+        avg300: field("avg300")?.parse().ok()?,
+        total_us: field("total")?.parse().ok()?,
+    })
+}
@@ -0,0 +1,528 @@
+//! Turning raw [`ProcessSample`]s into the API's process and app views
+
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+use crate::models::{AppGroup, ChildProcess, ContainerGroup, ProcessData, ProcessGroupSummary, UserSummary};
+use crate::protection;
+use crate::source::{ProcessSample, ProcessTable};
+use crate::utils::{format_duration, format_rate, format_rfc3339, unix_now};
+
+pub fn to_process_data(process: &ProcessSample, table: &ProcessTable) -> ProcessData {
+    let memory_mb = process.memory as f64 / (1024.0 * 1024.0);
+    let memory_percent = (process.memory as f64 / table.total_memory as f64 * 100.0) as f32;
+    let runtime = Runtime::new(process.start_time, unix_now());
+
+    ProcessData {
+        pid: process.pid,
+        name: process.name.clone(),
+        username: username(process),
+        cpu_percent: normalized_cpu(process, table),
+        memory_percent,
+        memory_mb,
+        status: process.status.clone(),
+        num_threads: 0,
+        create_time: process.start_time,
+        create_time_iso: runtime.create_time_iso,
+        running_seconds: runtime.running_seconds,
+        running_formatted: runtime.running_formatted,
+        exe: process.exe.clone().unwrap_or_else(|| "N/A".to_string()),
+        cwd: process.cwd.clone().unwrap_or_else(|| "N/A".to_string()),
+        cmdline: process.cmd.clone(),
+        is_protected: process.is_protected,
+        is_self: protection::is_self(process.pid),
+        session_id: process.session_id,
+        process_group_id: process.process_group_id,
+        tty: process.tty.clone(),
+        container_id: process.container_id.clone(),
+        parent_pid: process.parent,
+        cpu_time_seconds: cpu_time_seconds(process),
+        disk_read_rate: process.disk_read_rate,
+        disk_write_rate: process.disk_write_rate,
+        oom_score: process.oom_score,
+        oom_score_adj: process.oom_score_adj,
+        depth: None,
+    }
+}
+
+/// Start time and elapsed runtime in the forms the API reports them
+#[derive(Debug, PartialEq)]
+pub struct Runtime {
+    pub create_time_iso: Option<String>,
+    pub running_seconds: Option<u64>,
+    pub running_formatted: Option<String>,
+}
+
+impl Runtime {
+    /// A `start_time` of 0 means the OS didn't say, so every field is `None`
+    /// rather than a 1970 date
+    pub fn new(start_time: u64, now: u64) -> Self {
+        if start_time == 0 {
+            return Self {
+                create_time_iso: None,
+                running_seconds: None,
+                running_formatted: None,
+            };
+        }
+        let running = now.saturating_sub(start_time);
+        Self {
+            create_time_iso: Some(format_rfc3339(start_time)),
+            running_seconds: Some(running),
+            running_formatted: Some(format_duration(running)),
+        }
+    }
+}
+
+/// User plus system CPU time, if the platform reports either
+pub fn cpu_time_seconds(process: &ProcessSample) -> Option<f64> {
+    process.cpu_time_user.into_iter().chain(process.cpu_time_system).reduce(|a, b| a + b)
+}
+
+/// The owner's name, or "N/A" when it can't be resolved
+pub fn username(process: &ProcessSample) -> String {
+    process.username.clone().unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Divide by CPU count to match Windows Task Manager behavior
+pub fn normalized_cpu(process: &ProcessSample, table: &ProcessTable) -> f32 {
+    process.cpu_usage / table.cpu_count.max(1) as f32
+}
+
+/// Processes whose parent is `pid`, lowest PID first
+pub fn children(table: &ProcessTable, pid: u32) -> Vec<ChildProcess> {
+    let mut children: Vec<ChildProcess> = table
+        .processes
+        .iter()
+        .filter(|process| process.parent == Some(pid) && process.pid != pid)
+        .map(|process| ChildProcess {
+            pid: process.pid,
+            name: process.name.clone(),
+            cpu_percent: normalized_cpu(process, table),
+            memory_mb: process.memory as f64 / (1024.0 * 1024.0),
+        })
+        .collect();
+    children.sort_by_key(|child| child.pid);
+    children
+}
+
+/// Children of `pid`, or with `recursive` every descendant breadth-first
+/// with its `depth` (1 for children); lower PIDs first within a level.
+/// A PID is visited once, so parent cycles end. `None` when `pid` isn't running.
+pub fn descendants(table: &ProcessTable, pid: u32, recursive: bool) -> Option<Vec<ProcessData>> {
+    table.processes.iter().find(|process| process.pid == pid)?;
+    let mut by_parent: HashMap<u32, Vec<&ProcessSample>> = HashMap::new();
+    for process in &table.processes {
+        if let Some(parent) = process.parent.filter(|&parent| parent != process.pid) {
+            by_parent.entry(parent).or_default().push(process);
+        }
+    }
+    for children in by_parent.values_mut() {
+        children.sort_by_key(|child| child.pid);
+    }
+
+    let mut found = Vec::new();
+    let mut seen = HashSet::from([pid]);
+    let mut queue = VecDeque::from([(pid, 0u32)]);
+    while let Some((parent, depth)) = queue.pop_front() {
+        for child in by_parent.get(&parent).into_iter().flatten() {
+            if !seen.insert(child.pid) {
+                continue;
+            }
+            let mut data = to_process_data(child, table);
+            if recursive {
+                data.depth = Some(depth + 1);
+                queue.push_back((child.pid, depth + 1));
+            }
+            found.push(data);
+        }
+    }
+    Some(found)
+}
+
+pub fn list_processes(table: &ProcessTable) -> Vec<ProcessData> {
+    let mut processes: Vec<ProcessData> = table
+        .processes
+        .iter()
+        .map(|process| to_process_data(process, table))
+        .collect();
+
+    processes.sort_by(|a, b| cmp_nan_lowest(b.cpu_percent.into(), a.cpu_percent.into()));
+    processes
+}
+
+/// Orders readings with NaN below every number, so a bad sample sorts last
+/// in the usual descending order. `sort_by` needs a total order; `partial_cmp`
+/// gives none once a NaN is involved.
+pub fn cmp_nan_lowest(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.total_cmp(&b),
+    }
+}
+
+/// Column accepted by `?sort_by=` on the process list endpoints
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    #[default]
+    #[serde(alias = "cpu_percent")]
+    Cpu,
+    #[serde(alias = "memory_mb", alias = "memory_percent")]
+    Memory,
+    Name,
+    Pid,
+    #[serde(alias = "create_time")]
+    Started,
+    /// Accumulated CPU seconds; processes without a value sort as 0
+    #[serde(alias = "cpu_time_seconds")]
+    CpuTime,
+}
+
+impl SortKey {
+    /// Busiest and newest first; names and PIDs read naturally ascending
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortKey::Cpu | SortKey::Memory | SortKey::Started | SortKey::CpuTime => SortOrder::Desc,
+            SortKey::Name | SortKey::Pid => SortOrder::Asc,
+        }
+    }
+}
+
+/// `?containers=` on the process list endpoints
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerFilter {
+    /// Host and container processes alike
+    #[default]
+    Include,
+    /// Only processes on the host
+    Exclude,
+    /// Only processes in a container
+    Only,
+}
+
+impl ContainerFilter {
+    pub fn keeps(self, process: &ProcessData) -> bool {
+        match self {
+            Self::Include => true,
+            Self::Exclude => process.container_id.is_none(),
+            Self::Only => process.container_id.is_some(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Keeps processes whose name contains `name_contains` (case-insensitive),
+/// then sorts by `key`; ties go to the lower PID so the order is stable
+pub fn filter_and_sort(
+    processes: &mut Vec<ProcessData>,
+    name_contains: Option<&str>,
+    key: SortKey,
+    order: SortOrder,
+) {
+    if let Some(needle) = name_contains.map(str::to_lowercase).filter(|n| !n.is_empty()) {
+        processes.retain(|p| p.name.to_lowercase().contains(&needle));
+    }
+
+    processes.sort_by(|a, b| {
+        let ordering = match key {
+            SortKey::Cpu => cmp_nan_lowest(a.cpu_percent.into(), b.cpu_percent.into()),
+            SortKey::Memory => cmp_nan_lowest(a.memory_mb, b.memory_mb),
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::Pid => a.pid.cmp(&b.pid),
+            SortKey::Started => a.create_time.cmp(&b.create_time),
+            SortKey::CpuTime => a.cpu_time_seconds.unwrap_or(0.0).total_cmp(&b.cpu_time_seconds.unwrap_or(0.0)),
+        };
+        let ordering = match order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+        ordering.then(a.pid.cmp(&b.pid))
+    });
+}
+
+/// Groups processes by name, summing CPU and memory across every PID
+pub fn group_apps(table: &ProcessTable) -> Vec<AppGroup> {
+    group_by(table, |process| (process.name.clone(), process))
+}
+
+/// Groups each process under its top-most ancestor below a session process
+/// (shell, init, desktop shell...; see [`is_session_process`]), so helpers
+/// with other names or paths land in their app's group. Groups are named
+/// after that ancestor; two instances of one app stay separate.
+pub fn group_app_trees(table: &ProcessTable, session_processes: &[String]) -> Vec<AppGroup> {
+    let by_pid: HashMap<u32, &ProcessSample> = table.processes.iter().map(|p| (p.pid, p)).collect();
+    group_by(table, |process| {
+        let root = tree_root(process, &by_pid, session_processes);
+        (root.pid, root)
+    })
+}
+
+/// Walks up parent PIDs from `process`. Stops below a session process, at a
+/// parent that no longer exists (orphans are reparented, often to init), and
+/// on a PID seen before, since PID reuse can make the parent links loop.
+pub fn tree_root<'a>(
+    process: &'a ProcessSample,
+    by_pid: &HashMap<u32, &'a ProcessSample>,
+    session_processes: &[String],
+) -> &'a ProcessSample {
+    let mut current = process;
+    let mut seen = HashSet::from([process.pid]);
+    while let Some(parent) = current.parent.and_then(|pid| by_pid.get(&pid).copied()) {
+        if is_session_process(&parent.name, session_processes) || !seen.insert(parent.pid) {
+            break;
+        }
+        current = parent;
+    }
+    current
+}
+
+/// Case-insensitive name match against the configured session processes
+pub fn is_session_process(name: &str, session_processes: &[String]) -> bool {
+    session_processes.iter().any(|session| session.eq_ignore_ascii_case(name))
+}
+
+/// Keeps apps a user launched: those whose exe is in a user directory (see
+/// [`protection::in_user_dir`]) or with a member owned by the same user as
+/// the backend, which is normally the person at the dashboard
+pub fn retain_user_apps(apps: &mut Vec<AppGroup>, table: &ProcessTable) {
+    let own_pid = std::process::id();
+    let own_user = table.processes.iter().find(|p| p.pid == own_pid).and_then(|p| p.user_id.as_deref());
+    let user_pids: HashSet<u32> = table
+        .processes
+        .iter()
+        .filter(|p| own_user.is_some() && p.user_id.as_deref() == own_user)
+        .map(|p| p.pid)
+        .collect();
+
+    apps.retain(|app| protection::in_user_dir(&app.exe) || app.pids.iter().any(|pid| user_pids.contains(pid)));
+}
+
+/// One entry per container, highest summed CPU first; host processes are left out
+pub fn group_by_container(table: &ProcessTable) -> Vec<ContainerGroup> {
+    let mut containers: HashMap<&str, ContainerGroup> = HashMap::new();
+    let mut processes: Vec<&ProcessSample> = table.processes.iter().collect();
+    // lowest PID first, so each container is named after its entrypoint
+    processes.sort_by_key(|process| process.pid);
+    for process in processes {
+        let Some(id) = process.container_id.as_deref() else {
+            continue;
+        };
+        let container = containers.entry(id).or_insert_with(|| ContainerGroup {
+            container_id: id.to_string(),
+            name: process.name.clone(),
+            image: None,
+            state: None,
+            started_at: None,
+            pids: Vec::new(),
+            cpu_percent: 0.0,
+            memory_mb: 0.0,
+            memory_percent: 0.0,
+            process_count: 0,
+        });
+        container.pids.push(process.pid);
+        container.cpu_percent += normalized_cpu(process, table);
+        container.memory_mb += process.memory as f64 / (1024.0 * 1024.0);
+        container.memory_percent += (process.memory as f64 / table.total_memory as f64 * 100.0) as f32;
+        container.process_count += 1;
+    }
+
+    let mut containers: Vec<ContainerGroup> = containers.into_values().collect();
+    containers.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent).then_with(|| a.container_id.cmp(&b.container_id)));
+    containers
+}
+
+/// Members of process group `pgid`, lowest PID first
+pub fn process_group_members(table: &ProcessTable, pgid: u32) -> Vec<&ProcessSample> {
+    let mut members: Vec<&ProcessSample> = table.processes.iter().filter(|p| p.process_group_id == Some(pgid)).collect();
+    members.sort_by_key(|process| process.pid);
+    members
+}
+
+/// One entry per process group, highest summed CPU first; processes whose
+/// group is unknown (Windows) are left out
+pub fn group_by_process_group(table: &ProcessTable) -> Vec<ProcessGroupSummary> {
+    let mut groups: HashMap<u32, ProcessGroupSummary> = HashMap::new();
+    let mut processes: Vec<&ProcessSample> = table.processes.iter().collect();
+    processes.sort_by_key(|process| process.pid);
+    for process in processes {
+        let Some(pgid) = process.process_group_id else {
+            continue;
+        };
+        let group = groups.entry(pgid).or_insert_with(|| ProcessGroupSummary {
+            pgid,
+            session_leader_pid: process.session_id,
+            leader_name: None,
+            pids: Vec::new(),
+            process_count: 0,
+            cpu_percent: 0.0,
+            memory_mb: 0.0,
+            memory_percent: 0.0,
+        });
+        if process.pid == pgid {
+            group.leader_name = Some(process.name.clone());
+        }
+        group.pids.push(process.pid);
+        group.process_count += 1;
+        group.cpu_percent += normalized_cpu(process, table);
+        group.memory_mb += process.memory as f64 / (1024.0 * 1024.0);
+        group.memory_percent += (process.memory as f64 / table.total_memory as f64 * 100.0) as f32;
+    }
+
+    let mut groups: Vec<ProcessGroupSummary> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent).then_with(|| a.pgid.cmp(&b.pgid)));
+    groups
+}
+
+/// Processes in the top list of each [`UserSummary`]
+const USER_TOP_PROCESSES: usize = 3;
+
+/// One entry per username, highest summed CPU first, ties by name
+pub fn group_by_user(table: &ProcessTable) -> Vec<UserSummary> {
+    let mut users: HashMap<String, (UserSummary, Vec<&ProcessSample>)> = HashMap::new();
+    for process in &table.processes {
+        let (user, members) = users.entry(username(process)).or_insert_with_key(|name| {
+            let summary = UserSummary {
+                username: name.clone(),
+                is_system: false,
+                process_count: 0,
+                cpu_percent: 0.0,
+                memory_mb: 0.0,
+                memory_percent: 0.0,
+                top_processes: Vec::new(),
+            };
+            (summary, Vec::new())
+        });
+        user.is_system |= process.user_id.as_deref().is_some_and(protection::is_system_user_id);
+        user.process_count += 1;
+        user.cpu_percent += normalized_cpu(process, table);
+        user.memory_mb += process.memory as f64 / (1024.0 * 1024.0);
+        user.memory_percent += (process.memory as f64 / table.total_memory as f64 * 100.0) as f32;
+        members.push(process);
+    }
+
+    let mut users: Vec<UserSummary> = users
+        .into_values()
+        .map(|(mut user, mut members)| {
+            members.sort_by(|a, b| normalized_cpu(b, table).total_cmp(&normalized_cpu(a, table)).then(a.pid.cmp(&b.pid)));
+            user.top_processes = members
+                .into_iter()
+                .take(USER_TOP_PROCESSES)
+                .map(|process| ChildProcess {
+                    pid: process.pid,
+                    name: process.name.clone(),
+                    cpu_percent: normalized_cpu(process, table),
+                    memory_mb: process.memory as f64 / (1024.0 * 1024.0),
+                })
+                .collect();
+            user
+        })
+        .collect();
+    users.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent).then_with(|| a.username.cmp(&b.username)));
+    users
+}
+
+/// Aggregates every process into the group `key` returns, named after the
+/// sample returned alongside it
+fn group_by<'a, K, F>(table: &'a ProcessTable, key: F) -> Vec<AppGroup>
+where
+    K: Eq + Hash,
+    F: Fn(&'a ProcessSample) -> (K, &'a ProcessSample),
+{
+    let mut apps: HashMap<K, AppGroup> = HashMap::new();
+
+    for process in &table.processes {
+        let data = to_process_data(process, table);
+        let (key, representative) = key(process);
+        
+        apps.entry(key)
+            .and_modify(|app| {
+                app.pids.push(data.pid);
+                app.cpu_percent += data.cpu_percent;
+                app.memory_mb += data.memory_mb;
+                app.memory_percent += data.memory_percent;
+                app.process_count += 1;
+                app.is_closeable &= !data.is_protected && !data.is_self;
+                app.disk_read_rate += data.disk_read_rate;
+                app.disk_write_rate += data.disk_write_rate;
+                app.net_recv_rate = sum_known(app.net_recv_rate, process.net_recv_rate);
+                app.net_sent_rate = sum_known(app.net_sent_rate, process.net_sent_rate);
+                if data.status != "stopped" {
+                    app.status = "running".to_string();
+                }
+            })
+            .or_insert_with(|| AppGroup {
+                name: representative.name.clone(),
+                pids: vec![data.pid],
+                cpu_percent: data.cpu_percent,
+                memory_mb: data.memory_mb,
+                memory_percent: data.memory_percent,
+                status: if data.status == "stopped" { "stopped" } else { "running" }.to_string(),
+                process_count: 1,
+                exe: representative.exe.clone().unwrap_or_else(|| "N/A".to_string()),
+                is_closeable: !data.is_protected && !data.is_self,
+                disk_read_rate: data.disk_read_rate,
+                disk_write_rate: data.disk_write_rate,
+                disk_read_rate_formatted: String::new(),
+                disk_write_rate_formatted: String::new(),
+                net_recv_rate: process.net_recv_rate,
+                net_sent_rate: process.net_sent_rate,
+                net_recv_rate_formatted: None,
+                net_sent_rate_formatted: None,
+            });
+    }
+
+    let mut app_list: Vec<AppGroup> = apps.into_values().collect();
+    for app in &mut app_list {
+        app.disk_read_rate_formatted = format_rate(app.disk_read_rate);
+        app.disk_write_rate_formatted = format_rate(app.disk_write_rate);
+        app.net_recv_rate_formatted = app.net_recv_rate.map(format_rate);
+        app.net_sent_rate_formatted = app.net_sent_rate.map(format_rate);
+    }
+    app_list.sort_by(|a, b| cmp_nan_lowest(b.cpu_percent.into(), a.cpu_percent.into()));
+    app_list
+}
+
+/// Sum of the members that report a value; `None` only if none do
+fn sum_known(total: Option<f64>, value: Option<f64>) -> Option<f64> {
+    total.into_iter().chain(value).reduce(|a, b| a + b)
+}
+
+/// Column accepted by `?sort=` on `/api/apps`; numeric columns sort highest first
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AppSortKey {
+    #[default]
+    Cpu,
+    Memory,
+    /// Read plus write rate
+    Disk,
+    /// Received plus sent rate; groups without network counters count as 0
+    Network,
+    Name,
+}
+
+/// Sorts apps by `key`; ties go to the name so the order is stable
+pub fn sort_apps(apps: &mut [AppGroup], key: AppSortKey) {
+    let network = |app: &AppGroup| app.net_recv_rate.unwrap_or(0.0) + app.net_sent_rate.unwrap_or(0.0);
+    apps.sort_by(|a, b| {
+        let ordering = match key {
+            AppSortKey::Cpu => b.cpu_percent.total_cmp(&a.cpu_percent),
+            AppSortKey::Memory => b.memory_mb.total_cmp(&a.memory_mb),
+            AppSortKey::Disk => (b.disk_read_rate + b.disk_write_rate).total_cmp(&(a.disk_read_rate + a.disk_write_rate)),
+            AppSortKey::Network => network(b).total_cmp(&network(a)),
+            AppSortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        };
+        ordering.then_with(|| a.name.cmp(&b.name))
+    });
+}
@@ -0,0 +1,102 @@
+//! Storage I/O of one process from `/proc/<pid>/io`, with rates between
+//! successive requests.
+//!
+//! The counters are cumulative, so [`IoRateCache`] keeps each PID's previous
+//! reading for a short while and turns the next one into per-second rates.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::ApiError;
+use crate::models::ProcessIoRates;
+
+/// Readings older than this are forgotten; the next request starts over
+pub const IO_SAMPLE_TTL: Duration = Duration::from_secs(60);
+
+/// The `/proc/<pid>/io` fields the API reports
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct IoCounters {
+    /// Bytes fetched from storage (`read_bytes`); page cache hits don't count
+    pub read_bytes: u64,
+    /// Bytes sent to storage (`write_bytes`)
+    pub write_bytes: u64,
+    /// `read()`-family calls (`syscr`)
+    pub read_syscalls: u64,
+    /// `write()`-family calls (`syscw`)
+    pub write_syscalls: u64,
+    /// Written bytes that never reached storage because the file was
+    /// truncated or deleted first (`cancelled_write_bytes`)
+    pub cancelled_write_bytes: u64,
+}
+
+/// Parses `name: value` lines; `None` unless every field is there
+pub fn parse_proc_io(text: &str) -> Option<IoCounters> {
+    let value = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':')?.trim().parse().ok())
+    };
+    Some(IoCounters {
+        read_bytes: value("read_bytes")?,
+        write_bytes: value("write_bytes")?,
+        read_syscalls: value("syscr")?,
+        write_syscalls: value("syscw")?,
+        cancelled_write_bytes: value("cancelled_write_bytes")?,
+    })
+}
+
+/// Per-second change between two readings `elapsed` apart. `None` when a
+/// counter went backwards, which means the PID now belongs to another process.
+pub fn rates(before: &IoCounters, after: &IoCounters, elapsed: Duration) -> Option<ProcessIoRates> {
+    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+    let rate = |before: u64, after: u64| after.checked_sub(before).map(|delta| delta as f64 / seconds);
+    Some(ProcessIoRates {
+        read_bytes_per_sec: rate(before.read_bytes, after.read_bytes)?,
+        write_bytes_per_sec: rate(before.write_bytes, after.write_bytes)?,
+        read_syscalls_per_sec: rate(before.read_syscalls, after.read_syscalls)?,
+        write_syscalls_per_sec: rate(before.write_syscalls, after.write_syscalls)?,
+        interval_ms: elapsed.as_millis() as u64,
+    })
+}
+
+/// Each PID's last reading
+#[derive(Clone, Default)]
+pub struct IoRateCache {
+    samples: Arc<Mutex<HashMap<u32, (Instant, IoCounters)>>>,
+}
+
+impl IoRateCache {
+    /// Stores `counters` as `pid`'s latest reading and returns the rates since
+    /// the previous one, if there was one in the last [`IO_SAMPLE_TTL`]
+    pub fn record(&self, pid: u32, counters: IoCounters) -> Option<ProcessIoRates> {
+        let now = Instant::now();
+        let mut samples = self.samples.lock().expect("io sample mutex poisoned");
+        samples.retain(|_, (seen, _)| now.duration_since(*seen) < IO_SAMPLE_TTL);
+        let previous = samples.insert(pid, (now, counters));
+        previous.and_then(|(seen, before)| rates(&before, &counters, now.duration_since(seen)))
+    }
+}
+
+/// Only the owner (or root) may read another process's io file
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Result<IoCounters, ApiError> {
+    match std::fs::read_to_string(format!("/proc/{}/io", pid)) {
+        Ok(text) => parse_proc_io(&text).ok_or_else(|| {
+            tracing::warn!(pid, "unexpected /proc/<pid>/io format");
+            ApiError::Internal
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(ApiError::ProcessNotFound { pid }),
+        Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => Err(ApiError::PermissionDenied { pid }),
+        Err(err) => {
+            tracing::warn!(pid, error = %err, "cannot read process I/O counters");
+            Err(ApiError::Internal)
+        }
+    }
+}
+
+/// sysinfo's disk usage covers the other platforms' process lists, but
+/// without syscall counts
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Result<IoCounters, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Per-process I/O counters" })
+}
@@ -0,0 +1,192 @@
+//! Session, process group, controlling terminal, CPU time and page faults
+//! from `/proc/<pid>/stat`
+
+use std::collections::HashMap;
+
+/// The job-control fields of `/proc/<pid>/stat`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StatIds {
+    pub process_group_id: u32,
+    pub session_id: u32,
+    /// Encoded device number of the controlling terminal; 0 when there is none
+    pub tty_nr: u32,
+}
+
+/// Parses `pid (comm) state ppid pgrp session tty_nr ...`. `comm` may itself
+/// contain spaces and parentheses, so fields are counted from the last `)`.
+pub fn parse_stat(text: &str) -> Option<StatIds> {
+    let (_, rest) = text.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace().skip(2);
+    Some(StatIds {
+        process_group_id: fields.next()?.parse().ok()?,
+        session_id: fields.next()?.parse().ok()?,
+        tty_nr: fields.next()?.parse().ok()?,
+    })
+}
+
+/// CPU time a process has used since it started
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CpuTimes {
+    pub user_seconds: f64,
+    pub system_seconds: f64,
+}
+
+impl CpuTimes {
+    pub fn total_seconds(&self) -> f64 {
+        self.user_seconds + self.system_seconds
+    }
+}
+
+/// Parses `utime` and `stime`, fields 14 and 15, which count clock ticks
+pub fn parse_cpu_times(text: &str, ticks_per_second: u64) -> Option<CpuTimes> {
+    let (_, rest) = text.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace().skip(11);
+    let ticks = ticks_per_second.max(1) as f64;
+    Some(CpuTimes {
+        user_seconds: fields.next()?.parse::<u64>().ok()? as f64 / ticks,
+        system_seconds: fields.next()?.parse::<u64>().ok()? as f64 / ticks,
+    })
+}
+
+/// Page faults since the process started
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PageFaults {
+    /// Served without disk I/O, e.g. a page already in the page cache
+    pub minor: u64,
+    /// Needed disk I/O: swap, or a mapped file not yet in RAM
+    pub major: u64,
+    /// Minor faults of waited-for children
+    pub children_minor: u64,
+    /// Major faults of waited-for children
+    pub children_major: u64,
+}
+
+/// Parses `minflt cminflt majflt cmajflt`, fields 10 to 13
+pub fn parse_page_faults(text: &str) -> Option<PageFaults> {
+    let (_, rest) = text.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace().skip(7).map(|field| field.parse::<u64>().ok());
+    let minor = fields.next()??;
+    let children_minor = fields.next()??;
+    Some(PageFaults {
+        minor,
+        children_minor,
+        major: fields.next()??,
+        children_major: fields.next()??,
+    })
+}
+
+/// Splits `tty_nr` into (major, minor): minor is bits 0-7 and 20-31, major bits 8-19
+pub fn tty_major_minor(tty_nr: u32) -> (u32, u32) {
+    ((tty_nr >> 8) & 0xfff, (tty_nr & 0xff) | ((tty_nr >> 12) & 0xfff00))
+}
+
+/// Terminal device names under `/dev` keyed by (major, minor)
+#[derive(Clone, Debug, Default)]
+pub struct TtyNames(HashMap<(u32, u32), String>);
+
+impl TtyNames {
+    /// Scans `/dev/tty*`, `/dev/pts/*` and `/dev/console`; empty off Linux
+    #[cfg(target_os = "linux")]
+    pub fn scan() -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut names = HashMap::new();
+        let mut add = |path: std::path::PathBuf, name: String| {
+            if let Ok(metadata) = std::fs::metadata(&path) {
+                let rdev = metadata.rdev();
+                names.insert((libc::major(rdev), libc::minor(rdev)), name);
+            }
+        };
+        if let Ok(entries) = std::fs::read_dir("/dev/pts") {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.bytes().all(|b| b.is_ascii_digit()) {
+                    add(entry.path(), format!("pts/{}", name));
+                }
+            }
+        }
+        if let Ok(entries) = std::fs::read_dir("/dev") {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with("tty") || name == "console" {
+                    add(entry.path(), name);
+                }
+            }
+        }
+        Self(names)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn scan() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, major: u32, minor: u32, name: &str) {
+        self.0.insert((major, minor), name.to_string());
+    }
+
+    /// The device name for `tty_nr`, `None` without a controlling terminal
+    pub fn name(&self, tty_nr: u32) -> Option<String> {
+        if tty_nr == 0 {
+            return None;
+        }
+        self.0.get(&tty_major_minor(tty_nr)).cloned()
+    }
+}
+
+/// `None` on Windows or when the process is gone
+pub fn read(pid: u32) -> Option<StatIds> {
+    read_with_cpu_times(pid).0
+}
+
+/// Both parts of one `/proc/<pid>/stat` read
+#[cfg(target_os = "linux")]
+pub fn read_with_cpu_times(pid: u32) -> (Option<StatIds>, Option<CpuTimes>) {
+    match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        Ok(text) => (parse_stat(&text), parse_cpu_times(&text, clock_ticks())),
+        Err(_) => (None, None),
+    }
+}
+
+/// Group and session from `getpgid`/`getsid`; no terminal or CPU times
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn read_with_cpu_times(pid: u32) -> (Option<StatIds>, Option<CpuTimes>) {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return (None, None);
+    };
+    // SAFETY: both only look the process up
+    let (process_group_id, session_id) = unsafe { (libc::getpgid(pid), libc::getsid(pid)) };
+    if process_group_id < 0 || session_id < 0 {
+        return (None, None);
+    }
+    let ids = StatIds {
+        process_group_id: process_group_id as u32,
+        session_id: session_id as u32,
+        tty_nr: 0,
+    };
+    (Some(ids), None)
+}
+
+#[cfg(not(unix))]
+pub fn read_with_cpu_times(_pid: u32) -> (Option<StatIds>, Option<CpuTimes>) {
+    (None, None)
+}
+
+/// `None` on Windows or when the process is gone
+#[cfg(target_os = "linux")]
+pub fn read_page_faults(pid: u32) -> Option<PageFaults> {
+    parse_page_faults(&std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_page_faults(_pid: u32) -> Option<PageFaults> {
+    None
+}
+
+/// `sysconf(_SC_CLK_TCK)`, almost always 100
+#[cfg(target_os = "linux")]
+pub fn clock_ticks() -> u64 {
+    static TICKS: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+    // SAFETY: sysconf has no preconditions
+    *TICKS.get_or_init(|| u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) }).unwrap_or(100))
+}
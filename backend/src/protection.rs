@@ -0,0 +1,124 @@
+//! Which processes are too critical to kill without `?force=true`, and the
+//! backend's own processes, which can't be killed at all
+
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+use crate::policy::KillPolicy;
+use crate::source::{ProcessSample, ProcessTable};
+
+/// System Idle Process (0), System (4) on Windows; init/launchd (1) on Unix
+pub const PROTECTED_PIDS: &[u32] = &[0, 1, 4];
+
+/// Binaries here that run as root/SYSTEM are part of the OS
+#[cfg(windows)]
+const SYSTEM_DIRS: &[&str] = &[r"\windows\system32\", r"\windows\syswow64\"];
+#[cfg(target_os = "macos")]
+const SYSTEM_DIRS: &[&str] = &["/sbin/", "/usr/sbin/", "/usr/libexec/", "/System/"];
+#[cfg(not(any(windows, target_os = "macos")))]
+const SYSTEM_DIRS: &[&str] = &["/sbin/", "/usr/sbin/", "/lib/systemd/", "/usr/lib/systemd/"];
+
+/// Directories users can write to: homes and temp dirs. `$HOME` is checked
+/// separately for homes outside these.
+#[cfg(windows)]
+const USER_DIRS: &[&str] = &[r"\users\"];
+#[cfg(target_os = "macos")]
+const USER_DIRS: &[&str] = &["/Users/", "/tmp/", "/private/tmp/", "/private/var/folders/"];
+#[cfg(not(any(windows, target_os = "macos")))]
+const USER_DIRS: &[&str] = &["/home/", "/tmp/", "/var/tmp/"];
+
+/// Built-in rules, applied by the source when sampling: the fixed PIDs,
+/// kernel threads, and root/SYSTEM processes running a binary from a system directory
+pub fn is_system_process(pid: u32, exe: Option<&str>, system_owned: bool, kernel_thread: bool) -> bool {
+    PROTECTED_PIDS.contains(&pid) || kernel_thread || (system_owned && exe.is_some_and(in_system_dir))
+}
+
+/// Root on Unix, LocalSystem (`S-1-5-18`) on Windows, as
+/// [`ProcessSample::user_id`] spells them
+pub fn is_system_user_id(user_id: &str) -> bool {
+    matches!(user_id, "0" | "S-1-5-18")
+}
+
+#[cfg(windows)]
+pub fn in_system_dir(exe: &str) -> bool {
+    // Skip the drive letter; Windows isn't always on C:
+    let exe = exe.to_ascii_lowercase();
+    exe.get(2..).is_some_and(|path| SYSTEM_DIRS.iter().any(|dir| path.starts_with(dir)))
+}
+
+#[cfg(not(windows))]
+pub fn in_system_dir(exe: &str) -> bool {
+    SYSTEM_DIRS.iter().any(|dir| exe.starts_with(dir))
+}
+
+/// Whether `exe` lives somewhere a normal user could have put it
+#[cfg(windows)]
+pub fn in_user_dir(exe: &str) -> bool {
+    let exe = exe.to_ascii_lowercase();
+    exe.get(2..).is_some_and(|path| USER_DIRS.iter().any(|dir| path.starts_with(dir)))
+}
+
+#[cfg(not(windows))]
+pub fn in_user_dir(exe: &str) -> bool {
+    let home = std::env::var("HOME").ok().filter(|home| home.len() > 1);
+    USER_DIRS.iter().any(|dir| exe.starts_with(dir))
+        || home.is_some_and(|home| exe.strip_prefix(home.as_str()).is_some_and(|rest| rest.starts_with('/')))
+}
+
+/// The source's verdict plus the fixed PIDs and the configured `extra_names`
+/// (matched case-insensitively), so mock and real sources agree
+pub fn is_protected(process: &ProcessSample, extra_names: &[String]) -> bool {
+    process.is_protected
+        || PROTECTED_PIDS.contains(&process.pid)
+        || extra_names.iter().any(|name| name.eq_ignore_ascii_case(&process.name))
+}
+
+/// Whether the backend may kill `process`: never itself or its parent, never
+/// against the site [`KillPolicy`], and protected processes only with `force`
+pub fn check_kill(process: &ProcessSample, extra_names: &[String], policy: &KillPolicy, force: bool) -> Result<(), ApiError> {
+    if is_self(process.pid) {
+        return Err(ApiError::CannotKillSelf { pid: process.pid });
+    }
+    policy.check(process)?;
+    if !force && is_protected(process, extra_names) {
+        return Err(ApiError::ProcessProtected { pid: process.pid });
+    }
+    Ok(())
+}
+
+/// Rewrites every sample's `is_protected` with [`is_protected`], also
+/// marking whatever the site [`KillPolicy`] forbids killing
+pub fn mark(table: &mut ProcessTable, extra_names: &[String], policy: &KillPolicy) {
+    for process in &mut table.processes {
+        process.is_protected = is_protected(process, extra_names) || !policy.allows(process);
+    }
+}
+
+/// This process and its parent (the shell or service manager that started it);
+/// killing either takes the dashboard down with it
+pub fn own_pids() -> &'static [u32] {
+    static PIDS: OnceLock<Vec<u32>> = OnceLock::new();
+    PIDS.get_or_init(|| {
+        let pid = std::process::id();
+        std::iter::once(pid).chain(parent_pid(pid)).collect()
+    })
+}
+
+pub fn is_self(pid: u32) -> bool {
+    own_pids().contains(&pid)
+}
+
+#[cfg(unix)]
+fn parent_pid(_pid: u32) -> Option<u32> {
+    Some(std::os::unix::process::parent_id())
+}
+
+#[cfg(not(unix))]
+fn parent_pid(pid: u32) -> Option<u32> {
+    use sysinfo::{Pid, ProcessesToUpdate, System};
+
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid)?.parent().map(|parent| parent.as_u32())
+}
@@ -0,0 +1,85 @@
+//! Scheduler pressure from the system-wide `/proc/stat`: context switches
+//! and forks per second, and how many tasks are runnable or blocked.
+//!
+//! `ctxt` and `processes` count since boot, so [`SchedSampler`] keeps the
+//! previous reading and divides the change by the time between the two.
+
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::models::KernelStats;
+
+/// The `/proc/stat` lines this module reads
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SchedCounters {
+    /// `ctxt`: context switches since boot
+    pub context_switches: u64,
+    /// `processes`: forks since boot
+    pub forks: u64,
+    /// `procs_running`: tasks runnable right now
+    pub procs_running: u32,
+    /// `procs_blocked`: tasks waiting on I/O right now
+    pub procs_blocked: u32,
+}
+
+/// Parses the `ctxt`, `processes`, `procs_running` and `procs_blocked`
+/// lines; `None` unless all four are there
+pub fn parse_proc_stat(text: &str) -> Option<SchedCounters> {
+    let value = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(' ')?.trim().parse::<u64>().ok())
+    };
+    Some(SchedCounters {
+        context_switches: value("ctxt")?,
+        forks: value("processes")?,
+        procs_running: value("procs_running")? as u32,
+        procs_blocked: value("procs_blocked")? as u32,
+    })
+}
+
+/// Rates between two readings `seconds` apart. Without a previous reading,
+/// or when the counters went backwards, the rates are 0.
+pub fn rates(previous: Option<&SchedCounters>, current: &SchedCounters, seconds: f64) -> KernelStats {
+    let rate = |before: u64, after: u64| (after.saturating_sub(before) as f64 / seconds.max(f64::EPSILON)).round() as u64;
+    KernelStats {
+        context_switches_per_sec: previous.map_or(0, |p| rate(p.context_switches, current.context_switches)),
+        forks_per_sec: previous.map_or(0, |p| rate(p.forks, current.forks)),
+        processes_running: current.procs_running,
+        processes_blocked: current.procs_blocked,
+    }
+}
+
+/// The last reading, for turning the next one into rates
+#[derive(Default)]
+pub struct SchedSampler {
+    previous: Mutex<Option<(Instant, SchedCounters)>>,
+}
+
+impl SchedSampler {
+    /// Reads `/proc/stat` and returns rates since the previous call; all
+    /// zeros on the first call, and where there is no `/proc/stat`
+    pub fn sample(&self) -> KernelStats {
+        let Some(current) = read() else {
+            return KernelStats::default();
+        };
+        let now = Instant::now();
+        let mut previous = self.previous.lock().expect("sched sampler mutex poisoned");
+        let stats = match previous.as_ref() {
+            Some((taken, counters)) => rates(Some(counters), &current, now.duration_since(*taken).as_secs_f64()),
+            None => rates(None, &current, 0.0),
+        };
+        *previous = Some((now, current));
+        stats
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read() -> Option<SchedCounters> {
+    std::fs::read_to_string("/proc/stat").ok().as_deref().and_then(parse_proc_stat)
+}
+
+/// macOS and Windows don't count forks system-wide
+#[cfg(not(target_os = "linux"))]
+fn read() -> Option<SchedCounters> {
+    None
+}
@@ -0,0 +1,26 @@
+//! seccomp mode of a process, from the `Seccomp:` line of `/proc/<pid>/status`
+
+use crate::models::SeccompStatus;
+
+/// `None` on other platforms, kernels without seccomp, or when the process is gone
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<SeccompStatus> {
+    let text = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    parse_status(&text)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<SeccompStatus> {
+    None
+}
+
+/// Maps `Seccomp: 0|1|2` to disabled, strict or filter
+pub fn parse_status(text: &str) -> Option<SeccompStatus> {
+    let value = text.lines().find_map(|line| line.strip_prefix("Seccomp:"))?;
+    match value.trim() {
+        "0" => Some(SeccompStatus::Disabled),
+        "1" => Some(SeccompStatus::Strict),
+        "2" => Some(SeccompStatus::Filter),
+        _ => None,
+    }
+}
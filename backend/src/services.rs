@@ -0,0 +1,523 @@
+//! Service managers: systemd over D-Bus on Linux, the Service Control
+//! Manager's API on Windows (`501` elsewhere). Both fill the same
+//! [`ServiceInfo`] so the dashboard has one code path.
+
+use crate::error::ApiError;
+use crate::models::ServiceInfo;
+
+#[cfg(not(any(target_os = "linux", windows)))]
+const FEATURE: &str = "Service control";
+#[cfg(target_os = "linux")]
+const FEATURE: &str = "systemd service control";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ServiceAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl ServiceAction {
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            ServiceAction::Start => "Started",
+            ServiceAction::Stop => "Stopped",
+            ServiceAction::Restart => "Restarted",
+        }
+    }
+}
+
+/// Accepts `nginx` or `nginx.service`; rejects anything systemd wouldn't call a unit name
+pub fn unit_name(name: &str) -> Result<String, ApiError> {
+    let valid = !name.is_empty()
+        && name.len() <= 255
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || ":-_.\\@".contains(c))
+        && !name.starts_with('.');
+    if !valid {
+        return Err(ApiError::InvalidServiceName { name: name.to_string() });
+    }
+    Ok(if name.ends_with(".service") {
+        name.to_string()
+    } else {
+        format!("{}.service", name)
+    })
+}
+
+/// Windows service key names such as `wuauserv` or `MSSQL$SQLEXPRESS`; the
+/// SCM forbids slashes, and the rest is kept to what real services use
+pub fn windows_service_name(name: &str) -> Result<String, ApiError> {
+    let valid = !name.is_empty()
+        && name.len() <= 256
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.$@".contains(c));
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(ApiError::InvalidServiceName { name: name.to_string() })
+    }
+}
+
+/// The name [`control`] expects on this platform
+#[cfg(windows)]
+pub fn service_name(name: &str) -> Result<String, ApiError> {
+    windows_service_name(name)
+}
+
+#[cfg(not(windows))]
+pub fn service_name(name: &str) -> Result<String, ApiError> {
+    unit_name(name)
+}
+
+/// A `SERVICE_STATUS` `dwCurrentState` in the words `Win32_Service` uses
+pub fn windows_state_name(state: u32) -> &'static str {
+    match state {
+        1 => "stopped",
+        2 => "start pending",
+        3 => "stop pending",
+        4 => "running",
+        5 => "continue pending",
+        6 => "pause pending",
+        7 => "paused",
+        _ => "unknown",
+    }
+}
+
+/// One SCM service as a [`ServiceInfo`]. Windows states map onto systemd's
+/// `active_state` words so the dashboard colours them the same way, and the
+/// original state goes in `sub_state`. Every installed service counts as
+/// `loaded`.
+pub fn windows_service_info(name: String, display_name: String, state: u32, start_type: Option<&str>, pid: u32) -> ServiceInfo {
+    let state = windows_state_name(state);
+    let active_state = match state {
+        "running" | "paused" | "continue pending" | "pause pending" => "active",
+        "start pending" => "activating",
+        "stop pending" => "deactivating",
+        "stopped" => "inactive",
+        _ => "unknown",
+    };
+    ServiceInfo {
+        description: if display_name.is_empty() { name.clone() } else { display_name },
+        name,
+        load_state: "loaded".to_string(),
+        active_state: active_state.to_string(),
+        sub_state: state.to_string(),
+        start_type: start_type.map(str::to_string),
+        pid: Some(pid).filter(|pid| *pid > 0),
+        memory_bytes: None,
+        restart_count: None,
+        cpu_percent: None,
+    }
+}
+
+/// Maps the Win32 error code of a failed SCM call onto the API's errors.
+/// Starting a running service or stopping a stopped one counts as done.
+pub fn windows_control_result(code: Option<i32>, service: &str) -> Result<(), ApiError> {
+    match code {
+        // ERROR_SERVICE_ALREADY_RUNNING, ERROR_SERVICE_NOT_ACTIVE
+        Some(0 | 1056 | 1062) => Ok(()),
+        // ERROR_ACCESS_DENIED
+        Some(5) => Err(ApiError::ServicePermissionDenied { name: service.to_string() }),
+        // ERROR_DEPENDENT_SERVICES_RUNNING
+        Some(1051) => Err(ApiError::ServiceHasDependents { name: service.to_string() }),
+        // ERROR_SERVICE_DOES_NOT_EXIST
+        Some(1060) => Err(ApiError::ServiceNotFound { name: service.to_string() }),
+        _ => Err(ApiError::Internal),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use systemd::{control, list};
+
+#[cfg(windows)]
+pub use scm::{control, list};
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub async fn list() -> Result<Vec<ServiceInfo>, ApiError> {
+    Err(ApiError::NotImplemented { feature: FEATURE })
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub async fn control(_unit: &str, _action: ServiceAction) -> Result<(), ApiError> {
+    Err(ApiError::NotImplemented { feature: FEATURE })
+}
+
+#[cfg(windows)]
+mod scm {
+    use std::ffi::{OsStr, OsString};
+    use std::io;
+    use std::os::windows::ffi::OsStringExt;
+    use std::time::{Duration, Instant};
+    use windows_service::service::{ServiceAccess, ServiceStartType, ServiceState};
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    use windows_sys::Win32::Foundation::ERROR_MORE_DATA;
+    use windows_sys::Win32::System::Services::{
+        CloseServiceHandle, EnumServicesStatusExW, OpenSCManagerW, ENUM_SERVICE_STATUS_PROCESSW, SC_ENUM_PROCESS_INFO, SC_HANDLE,
+        SC_MANAGER_ENUMERATE_SERVICE, SERVICE_STATE_ALL, SERVICE_WIN32,
+    };
+
+    use super::{windows_control_result, windows_service_info, ServiceAction};
+    use crate::error::ApiError;
+    use crate::models::ServiceInfo;
+
+    /// How long a service may take to reach the requested state
+    const STATE_TIMEOUT: Duration = Duration::from_secs(30);
+    const STATE_POLL: Duration = Duration::from_millis(250);
+
+    /// A failed SCM call as the API's error, logging the ones without a
+    /// mapping; `Ok` when the service was already in the requested state
+    fn outcome(err: windows_service::Error, service: &str) -> Result<(), ApiError> {
+        let code = match &err {
+            windows_service::Error::Winapi(io_err) => io_err.raw_os_error(),
+            _ => None,
+        };
+        windows_control_result(code, service).inspect_err(|api_err| {
+            if matches!(api_err, ApiError::Internal) {
+                tracing::warn!(error = %err, service, "service control failed");
+            }
+        })
+    }
+
+    fn check<T>(result: windows_service::Result<T>, service: &str) -> Result<(), ApiError> {
+        result.map(|_| ()).or_else(|err| outcome(err, service))
+    }
+
+    fn api_error(err: windows_service::Error, service: &str) -> ApiError {
+        outcome(err, service).err().unwrap_or(ApiError::Internal)
+    }
+
+    fn start_type_name(start_type: ServiceStartType) -> &'static str {
+        match start_type {
+            ServiceStartType::AutoStart => "auto",
+            ServiceStartType::OnDemand => "manual",
+            ServiceStartType::Disabled => "disabled",
+            ServiceStartType::SystemStart => "system",
+            ServiceStartType::BootStart => "boot",
+        }
+    }
+
+    pub async fn list() -> Result<Vec<ServiceInfo>, ApiError> {
+        tokio::task::spawn_blocking(list_blocking).await.map_err(|_| ApiError::Internal)?
+    }
+
+    fn list_blocking() -> Result<Vec<ServiceInfo>, ApiError> {
+        let rows = enumerate().map_err(|err| {
+            tracing::warn!(error = %err, "cannot list Windows services");
+            ApiError::Internal
+        })?;
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT).ok();
+        let mut services: Vec<ServiceInfo> = rows
+            .into_iter()
+            .map(|row| {
+                let start_type = manager
+                    .as_ref()
+                    .and_then(|manager| manager.open_service(&row.name, ServiceAccess::QUERY_CONFIG).ok())
+                    .and_then(|service| service.query_config().ok())
+                    .map(|config| start_type_name(config.start_type));
+                windows_service_info(row.name, row.display_name, row.state, start_type, row.pid)
+            })
+            .collect();
+        services.sort_by_key(|service| service.name.to_ascii_lowercase());
+        Ok(services)
+    }
+
+    /// Returns once the service has reached the new state
+    pub async fn control(service: &str, action: ServiceAction) -> Result<(), ApiError> {
+        let name = service.to_string();
+        tokio::task::spawn_blocking(move || control_blocking(&name, action))
+            .await
+            .map_err(|_| ApiError::Internal)?
+    }
+
+    fn control_blocking(name: &str, action: ServiceAction) -> Result<(), ApiError> {
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT).map_err(|err| api_error(err, name))?;
+        let access = ServiceAccess::QUERY_STATUS | ServiceAccess::START | ServiceAccess::STOP;
+        let service = manager.open_service(name, access).map_err(|err| api_error(err, name))?;
+
+        if matches!(action, ServiceAction::Stop | ServiceAction::Restart) {
+            check(service.stop(), name)?;
+            wait_for(&service, ServiceState::Stopped, name)?;
+        }
+        if matches!(action, ServiceAction::Start | ServiceAction::Restart) {
+            check(service.start(&[] as &[&OsStr]), name)?;
+            wait_for(&service, ServiceState::Running, name)?;
+        }
+        Ok(())
+    }
+
+    fn wait_for(service: &windows_service::service::Service, state: ServiceState, name: &str) -> Result<(), ApiError> {
+        let deadline = Instant::now() + STATE_TIMEOUT;
+        loop {
+            let status = service.query_status().map_err(|err| api_error(err, name))?;
+            if status.current_state == state {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                tracing::warn!(service = name, ?state, current = ?status.current_state, "service did not reach the requested state");
+                return Err(ApiError::Internal);
+            }
+            std::thread::sleep(STATE_POLL);
+        }
+    }
+
+    struct Row {
+        name: String,
+        display_name: String,
+        state: u32,
+        pid: u32,
+    }
+
+    /// Every Win32 service with its state and process, from one
+    /// `EnumServicesStatusExW` sweep; `windows-service` has no enumeration
+    fn enumerate() -> io::Result<Vec<Row>> {
+        // SAFETY: a null machine and database name open the local, active database
+        let manager = unsafe { OpenSCManagerW(std::ptr::null(), std::ptr::null(), SC_MANAGER_ENUMERATE_SERVICE) };
+        if manager.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let rows = enumerate_with(manager);
+        // SAFETY: `manager` is a handle OpenSCManagerW returned and isn't used after this
+        unsafe { CloseServiceHandle(manager) };
+        rows
+    }
+
+    fn enumerate_with(manager: SC_HANDLE) -> io::Result<Vec<Row>> {
+        let mut rows = Vec::new();
+        let mut resume = 0u32;
+        // u64s keep the buffer aligned for the structs the call writes into it
+        let mut buffer = vec![0u64; 64 * 1024 / 8];
+        loop {
+            let (mut needed, mut returned) = (0u32, 0u32);
+            // SAFETY: the buffer is writable for the size passed, and the
+            // out-pointers live across the call
+            let ok = unsafe {
+                EnumServicesStatusExW(
+                    manager,
+                    SC_ENUM_PROCESS_INFO,
+                    SERVICE_WIN32,
+                    SERVICE_STATE_ALL,
+                    buffer.as_mut_ptr().cast(),
+                    (buffer.len() * 8) as u32,
+                    &mut needed,
+                    &mut returned,
+                    &mut resume,
+                    std::ptr::null(),
+                )
+            };
+            let more = ok == 0;
+            if more {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(ERROR_MORE_DATA as i32) {
+                    return Err(err);
+                }
+            }
+            // SAFETY: the call wrote `returned` entries at the start of the
+            // buffer, and their strings point further into it
+            let entries = unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<ENUM_SERVICE_STATUS_PROCESSW>(), returned as usize) };
+            for entry in entries {
+                rows.push(Row {
+                    // SAFETY: both are NUL-terminated strings inside the buffer
+                    name: unsafe { wide_string(entry.lpServiceName) },
+                    display_name: unsafe { wide_string(entry.lpDisplayName) },
+                    state: entry.ServiceStatusProcess.dwCurrentState,
+                    pid: entry.ServiceStatusProcess.dwProcessId,
+                });
+            }
+            if !more {
+                return Ok(rows);
+            }
+            if returned == 0 {
+                buffer.resize((needed as usize).div_ceil(8), 0);
+            }
+        }
+    }
+
+    /// # Safety
+    /// `ptr` must be null or point to a NUL-terminated UTF-16 string
+    unsafe fn wide_string(ptr: *const u16) -> String {
+        if ptr.is_null() {
+            return String::new();
+        }
+        let mut len = 0;
+        while *ptr.add(len) != 0 {
+            len += 1;
+        }
+        OsString::from_wide(std::slice::from_raw_parts(ptr, len)).to_string_lossy().into_owned()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod systemd {
+    use futures::StreamExt;
+    use tokio::sync::OnceCell;
+    use zbus::zvariant::OwnedObjectPath;
+    use zbus::proxy::CacheProperties;
+    use zbus::{fdo, Connection};
+
+    use super::{ServiceAction, FEATURE};
+    use crate::error::ApiError;
+    use crate::models::ServiceInfo;
+
+    /// `ListUnits` row: name, description, load, active, sub, following,
+    /// unit path, job id, job type, job path
+    type UnitRow = (String, String, String, String, String, String, OwnedObjectPath, u32, String, OwnedObjectPath);
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.systemd1.Manager",
+        default_service = "org.freedesktop.systemd1",
+        default_path = "/org/freedesktop/systemd1",
+        gen_blocking = false
+    )]
+    trait Manager {
+        fn list_units(&self) -> zbus::Result<Vec<UnitRow>>;
+        fn start_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+        fn stop_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+        fn restart_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.systemd1.Service",
+        default_service = "org.freedesktop.systemd1",
+        gen_blocking = false
+    )]
+    trait Service {
+        #[zbus(property, name = "MainPID")]
+        fn main_pid(&self) -> zbus::Result<u32>;
+        #[zbus(property)]
+        fn memory_current(&self) -> zbus::Result<u64>;
+        /// Automatic restarts since the unit was last started by hand
+        #[zbus(property, name = "NRestarts")]
+        fn n_restarts(&self) -> zbus::Result<u32>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.systemd1.Unit",
+        default_service = "org.freedesktop.systemd1",
+        gen_blocking = false
+    )]
+    trait Unit {
+        #[zbus(property)]
+        fn unit_file_state(&self) -> zbus::Result<String>;
+    }
+
+    static CONNECTION: OnceCell<Connection> = OnceCell::const_new();
+
+    /// Shared system-bus connection; a failed attempt is retried on the next call
+    async fn connection() -> Result<&'static Connection, ApiError> {
+        // Same check as sd_booted(3)
+        if !std::path::Path::new("/run/systemd/system").exists() {
+            return Err(ApiError::NotImplemented { feature: FEATURE });
+        }
+        CONNECTION.get_or_try_init(Connection::system).await.map_err(|err| {
+            tracing::warn!(error = %err, "cannot connect to the D-Bus system bus");
+            ApiError::NotImplemented { feature: FEATURE }
+        })
+    }
+
+    fn map_error(err: zbus::Error, unit: &str) -> ApiError {
+        let name = match &err {
+            zbus::Error::MethodError(name, _, _) => name.as_str().to_string(),
+            zbus::Error::FDO(fdo_err) => match **fdo_err {
+                fdo::Error::AccessDenied(_) | fdo::Error::InteractiveAuthorizationRequired(_) => {
+                    "org.freedesktop.DBus.Error.AccessDenied".to_string()
+                }
+                fdo::Error::ServiceUnknown(_) => "org.freedesktop.DBus.Error.ServiceUnknown".to_string(),
+                _ => String::new(),
+            },
+            _ => String::new(),
+        };
+        match name.as_str() {
+            "org.freedesktop.systemd1.NoSuchUnit" => ApiError::ServiceNotFound { name: unit.to_string() },
+            "org.freedesktop.DBus.Error.AccessDenied"
+            | "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired" => {
+                ApiError::ServicePermissionDenied { name: unit.to_string() }
+            }
+            "org.freedesktop.DBus.Error.ServiceUnknown" => ApiError::NotImplemented { feature: FEATURE },
+            _ => {
+                tracing::warn!(error = %err, unit, "systemd call failed");
+                ApiError::Internal
+            }
+        }
+    }
+
+    /// Every loaded `.service` unit; `cpu_percent` is left for the caller.
+    /// One `ListUnits` call, then each unit's properties fetched concurrently.
+    /// Units whose properties are fetched at once; each takes four D-Bus
+    /// calls, so a host with hundreds of units doesn't flood the system bus
+    const PROPERTY_FETCH_CONCURRENCY: usize = 8;
+
+    pub async fn list() -> Result<Vec<ServiceInfo>, ApiError> {
+        let connection = connection().await?;
+        let manager = ManagerProxy::new(connection).await.map_err(|err| map_error(err, ""))?;
+        let units = manager.list_units().await.map_err(|err| map_error(err, ""))?;
+
+        let services = units
+            .into_iter()
+            .filter(|(name, ..)| name.ends_with(".service"))
+            .map(|(name, description, load_state, active_state, sub_state, _, path, ..)| async move {
+                let (start_type, (pid, memory_bytes, restart_count)) =
+                    tokio::join!(start_type(connection, &path), service_properties(connection, &path));
+                ServiceInfo {
+                    name,
+                    description,
+                    load_state,
+                    active_state,
+                    sub_state,
+                    start_type,
+                    pid,
+                    memory_bytes,
+                    restart_count,
+                    cpu_percent: None,
+                }
+            });
+        let mut services: Vec<ServiceInfo> = futures::stream::iter(services)
+            .buffer_unordered(PROPERTY_FETCH_CONCURRENCY)
+            .collect()
+            .await;
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(services)
+    }
+
+    /// `UnitFileState`, e.g. "enabled"; `None` for units without a unit file
+    async fn start_type(connection: &Connection, path: &OwnedObjectPath) -> Option<String> {
+        // Uncached, so a list doesn't subscribe to every unit's PropertiesChanged
+        let unit = UnitProxy::builder(connection)
+            .path(path)
+            .ok()?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .ok()?;
+        unit.unit_file_state().await.ok().filter(|state| !state.is_empty())
+    }
+
+    /// Main PID, memory and restart count, each `None` when unavailable
+    async fn service_properties(connection: &Connection, path: &OwnedObjectPath) -> (Option<u32>, Option<u64>, Option<u32>) {
+        let service = async {
+            let builder = ServiceProxy::builder(connection).path(path).ok()?;
+            builder.cache_properties(CacheProperties::No).build().await.ok()
+        };
+        let Some(service) = service.await else {
+            return (None, None, None);
+        };
+        let (pid, memory_bytes, restart_count) =
+            tokio::join!(service.main_pid(), service.memory_current(), service.n_restarts());
+        (
+            pid.ok().filter(|pid| *pid > 0),
+            // systemd reports u64::MAX when memory accounting is off
+            memory_bytes.ok().filter(|bytes| *bytes != u64::MAX),
+            // NRestarts arrived in systemd 235
+            restart_count.ok(),
+        )
+    }
+
+    /// Queues a start/stop/restart job; returns once systemd accepts it
+    pub async fn control(unit: &str, action: ServiceAction) -> Result<(), ApiError> {
+        let connection = connection().await?;
+        let manager = ManagerProxy::new(connection).await.map_err(|err| map_error(err, unit))?;
+        let job = match action {
+            ServiceAction::Start => manager.start_unit(unit, "replace").await,
+            ServiceAction::Stop => manager.stop_unit(unit, "replace").await,
+            ServiceAction::Restart => manager.restart_unit(unit, "replace").await,
+        };
+        job.map(|_| ()).map_err(|err| map_error(err, unit))
+    }
+}
@@ -0,0 +1,91 @@
+//! Graceful shutdown.
+//!
+//! Ctrl+C or SIGTERM cancels the shared [`CancellationToken`]; servers stop
+//! accepting connections, the poller exits and [`crate::middleware::DrainLayer`]
+//! turns late requests away while in-flight ones finish. Background tasks on
+//! the [`TaskTracker`] then get the same window to flush. A second signal exits
+//! immediately.
+
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+/// Resolves on Ctrl+C; also SIGTERM on Unix and console close/logoff/shutdown on Windows
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::warn!(error = %err, "cannot listen for Ctrl+C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "cannot listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(windows)]
+    let terminate = async {
+        use tokio::signal::windows;
+        match (windows::ctrl_close(), windows::ctrl_shutdown(), windows::ctrl_logoff()) {
+            (Ok(mut close), Ok(mut shutdown), Ok(mut logoff)) => {
+                tokio::select! {
+                    _ = close.recv() => {}
+                    _ = shutdown.recv() => {}
+                    _ = logoff.recv() => {}
+                }
+            }
+            _ => {
+                tracing::warn!("cannot listen for console close events");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(any(unix, windows)))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Cancels `token` on the first shutdown signal and exits on the second
+pub fn spawn_signal_handler(token: CancellationToken, drain_timeout: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = wait_for_signal() => {}
+            _ = token.cancelled() => return,
+        }
+        tracing::info!(drain_timeout_secs = drain_timeout.as_secs(), "shutting down");
+        token.cancel();
+
+        wait_for_signal().await;
+        tracing::warn!("second shutdown signal, exiting immediately");
+        std::process::exit(130);
+    })
+}
+
+/// Resolves `drain_timeout` after `token` is cancelled; races the server so a
+/// stuck connection can't hold the process open forever
+pub async fn drain_deadline(token: CancellationToken, drain_timeout: Duration) {
+    token.cancelled().await;
+    tokio::time::sleep(drain_timeout).await;
+    tracing::warn!(drain_timeout_secs = drain_timeout.as_secs(), "drain timeout elapsed, forcing exit");
+}
+
+/// Closes `tasks` and waits up to `timeout` for them to finish their cleanup
+pub async fn finish_tasks(tasks: &TaskTracker, timeout: Duration) {
+    tasks.close();
+    if tokio::time::timeout(timeout, tasks.wait()).await.is_err() {
+        tracing::warn!(pending = tasks.len(), "background tasks did not finish in time");
+    }
+}
@@ -0,0 +1,112 @@
+//! Proportional and unique set size from `/proc/<pid>/smaps_rollup`
+//!
+//! RSS counts every resident page a process maps, so a shared library loaded
+//! by fifty processes is counted fifty times. PSS splits each shared page
+//! evenly between the processes mapping it, so PSS summed over all processes
+//! is the memory actually in use. USS counts only pages no other process maps
+//! (`Private_Clean` + `Private_Dirty`): what killing the process would free.
+//!
+//! The full `/proc/<pid>/smaps` splits the same resident memory by mapping,
+//! which [`read_breakdown`] sums into heap, stack, anonymous and file-backed.
+
+use crate::models::ProcessMemoryBreakdown;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SetSizes {
+    /// Proportional set size in KiB
+    pub pss_kb: u64,
+    /// Unique set size in KiB
+    pub uss_kb: u64,
+}
+
+/// `None` on kernels before 4.14 (no `smaps_rollup`), on other platforms,
+/// and when the process is gone or owned by another user
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<SetSizes> {
+    let text = std::fs::read_to_string(format!("/proc/{}/smaps_rollup", pid)).ok()?;
+    parse_smaps_rollup(&text)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<SetSizes> {
+    None
+}
+
+/// Parses the `Pss:`, `Private_Clean:` and `Private_Dirty:` lines (values in kB);
+/// `None` without a `Pss:` line
+pub fn parse_smaps_rollup(text: &str) -> Option<SetSizes> {
+    let (mut pss, mut private) = (None, 0u64);
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let kb = || value.trim().trim_end_matches("kB").trim().parse::<u64>().ok();
+        match key {
+            "Pss" => pss = kb(),
+            "Private_Clean" | "Private_Dirty" => private += kb().unwrap_or(0),
+            _ => {}
+        }
+    }
+    Some(SetSizes {
+        pss_kb: pss?,
+        uss_kb: private,
+    })
+}
+
+/// `None` on other platforms, and when the process is gone or owned by
+/// another user
+#[cfg(target_os = "linux")]
+pub fn read_breakdown(pid: u32) -> Option<ProcessMemoryBreakdown> {
+    let text = std::fs::read_to_string(format!("/proc/{}/smaps", pid)).ok()?;
+    Some(parse_smaps(&text))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_breakdown(_pid: u32) -> Option<ProcessMemoryBreakdown> {
+    None
+}
+
+/// Sums `Rss:` by each mapping's pathname: `[heap]`, `[stack]`, blank or
+/// `[anon:...]` for anonymous memory, and a path for a file. Other pseudo
+/// mappings (`[vdso]`, `[vvar]`) count only towards `shared_kb`.
+pub fn parse_smaps(text: &str) -> ProcessMemoryBreakdown {
+    let mut breakdown = ProcessMemoryBreakdown::default();
+    let mut pathname = "";
+    for line in text.lines() {
+        let Some(key) = line.split_whitespace().next() else {
+            continue;
+        };
+        let Some(key) = key.strip_suffix(':') else {
+            pathname = mapping_pathname(line);
+            continue;
+        };
+        let kb = || line[key.len() + 1..].trim().trim_end_matches("kB").trim().parse::<u64>().unwrap_or(0);
+        match key {
+            "Rss" => {
+                let slot = match pathname {
+                    "[heap]" => &mut breakdown.heap_kb,
+                    "" => &mut breakdown.anonymous_kb,
+                    _ if pathname.starts_with("[stack") => &mut breakdown.stack_kb,
+                    _ if pathname.starts_with("[anon:") => &mut breakdown.anonymous_kb,
+                    _ if pathname.starts_with('[') => continue,
+                    _ => &mut breakdown.file_mapped_kb,
+                };
+                *slot += kb();
+            }
+            "Shared_Clean" | "Shared_Dirty" => breakdown.shared_kb += kb(),
+            _ => {}
+        }
+    }
+    breakdown
+}
+
+/// The sixth column of a mapping header (`address perms offset dev inode
+/// pathname`), which may contain spaces
+fn mapping_pathname(line: &str) -> &str {
+    let mut rest = line;
+    for _ in 0..5 {
+        rest = rest.trim_start();
+        rest = &rest[rest.find(char::is_whitespace).unwrap_or(rest.len())..];
+    }
+    rest.trim()
+}
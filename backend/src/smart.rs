@@ -0,0 +1,137 @@
+//! Disk SMART data via `smartctl --json -a` (smartmontools 7+)
+
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::error::ApiError;
+use crate::models::{SmartAttribute, SmartData};
+
+/// smartctl exit-status bit: the device could not be opened
+const SMARTCTL_OPEN_FAILED: i32 = 1 << 1;
+
+/// ATA attribute "Reallocated_Sector_Ct"
+const REALLOCATED_SECTORS_ID: u64 = 5;
+
+/// `sda`, `nvme0n1`, `disk0`; no paths or separators
+pub fn device_path(device: &str) -> Result<PathBuf, ApiError> {
+    let valid = !device.is_empty()
+        && device.len() <= 32
+        && device.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(ApiError::InvalidDeviceName { device: device.to_string() });
+    }
+    Ok(PathBuf::from("/dev").join(device))
+}
+
+/// Fails fast with 403/404 before spawning smartctl when the device node can't be opened
+#[cfg(unix)]
+fn check_access(device: &str, path: &std::path::Path) -> Result<(), ApiError> {
+    match std::fs::File::open(path) {
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Err(ApiError::DeviceNotFound { device: device.to_string() })
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+            Err(ApiError::DevicePermissionDenied { device: device.to_string() })
+        }
+        // Some devices refuse a plain open (e.g. busy); let smartctl decide
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn check_access(_device: &str, _path: &std::path::Path) -> Result<(), ApiError> {
+    Ok(())
+}
+
+pub async fn read(device: &str) -> Result<SmartData, ApiError> {
+    let path = device_path(device)?;
+    check_access(device, &path)?;
+
+    let output = tokio::process::Command::new("smartctl")
+        .arg("--json")
+        .arg("-a")
+        .arg(&path)
+        .output()
+        .await
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => ApiError::NotImplemented {
+                feature: "SMART data (smartctl is not installed)",
+            },
+            _ => {
+                tracing::warn!(error = %err, "cannot run smartctl");
+                ApiError::Internal
+            }
+        })?;
+
+    let report: Value = serde_json::from_slice(&output.stdout).map_err(|err| {
+        tracing::warn!(error = %err, "smartctl printed invalid JSON; smartmontools 7+ is required");
+        ApiError::Internal
+    })?;
+    let exit_status = report["smartctl"]["exit_status"]
+        .as_i64()
+        .map(|s| s as i32)
+        .or(output.status.code())
+        .unwrap_or(0);
+
+    if exit_status & SMARTCTL_OPEN_FAILED != 0 {
+        return Err(open_error(device, &report));
+    }
+    Ok(parse_smartctl(device, &report))
+}
+
+fn open_error(device: &str, report: &Value) -> ApiError {
+    let messages: Vec<&str> = report["smartctl"]["messages"]
+        .as_array()
+        .map(|m| m.iter().filter_map(|m| m["string"].as_str()).collect())
+        .unwrap_or_default();
+    let mentions = |needle: &str| messages.iter().any(|m| m.contains(needle));
+
+    if mentions("Permission denied") || mentions("Operation not permitted") {
+        ApiError::DevicePermissionDenied { device: device.to_string() }
+    } else {
+        ApiError::DeviceNotFound { device: device.to_string() }
+    }
+}
+
+/// Maps smartctl's JSON report; devices without SMART come back as `UNSUPPORTED`
+pub fn parse_smartctl(device: &str, report: &Value) -> SmartData {
+    let supported = report["smart_support"]["available"].as_bool().unwrap_or(true);
+    let health = match report["smart_status"]["passed"].as_bool() {
+        _ if !supported => "UNSUPPORTED",
+        Some(true) => "PASSED",
+        Some(false) => "FAILED",
+        None => "UNKNOWN",
+    };
+
+    let attributes: Vec<SmartAttribute> = report["ata_smart_attributes"]["table"]
+        .as_array()
+        .map(|table| table.iter().filter_map(parse_attribute).collect())
+        .unwrap_or_default();
+    let reallocated_sectors = attributes
+        .iter()
+        .find(|a| u64::from(a.id) == REALLOCATED_SECTORS_ID)
+        .map(|a| a.raw_value);
+
+    SmartData {
+        device: device.to_string(),
+        health: health.to_string(),
+        model: report["model_name"].as_str().map(String::from),
+        temperature_celsius: report["temperature"]["current"].as_u64().map(|t| t as u32),
+        reallocated_sectors,
+        power_on_hours: report["power_on_time"]["hours"].as_u64(),
+        attributes,
+    }
+}
+
+fn parse_attribute(row: &Value) -> Option<SmartAttribute> {
+    Some(SmartAttribute {
+        id: row["id"].as_u64()? as u32,
+        name: row["name"].as_str().unwrap_or_default().to_string(),
+        value: row["value"].as_u64().unwrap_or(0) as u32,
+        worst: row["worst"].as_u64().unwrap_or(0) as u32,
+        thresh: row["thresh"].as_u64().unwrap_or(0) as u32,
+        raw_value: row["raw"]["value"].as_u64().unwrap_or(0),
+        failing: row["when_failed"].as_str().is_some_and(|w| !w.is_empty()),
+    })
+}
@@ -0,0 +1,625 @@
+//! Where system data comes from.
+//!
+//! Handlers only talk to a [`SystemSource`], so tests can swap the real
+//! sysinfo-backed implementation for [`crate::mock::MockSystemSource`].
+
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, Signal, System, Users};
+
+use crate::capabilities;
+use crate::comm;
+use crate::containers;
+use crate::cpucache;
+use crate::cwd;
+use crate::disks;
+use crate::environ::{self, EnvVars};
+use crate::error::ApiError;
+use crate::gpu::{GpuStatsCollector, DEFAULT_GPU_COOLDOWN};
+use crate::maps::{self, MapRegion};
+use crate::procio::{self, IoCounters};
+use crate::procstat::{self, PageFaults, TtyNames};
+use crate::protection;
+use crate::namespaces;
+use crate::oom::{self, OomScore};
+use crate::pressure;
+use crate::schedstat::SchedSampler;
+use crate::seccomp;
+use crate::netif;
+use crate::smaps::{self, SetSizes};
+use crate::threads::{self, ThreadSample};
+use crate::topology::Topology;
+use crate::models::*;
+use crate::utils::{format_bytes, get_process_status};
+use crate::vmstat::FaultSampler;
+
+/// One process as reported by the OS, before any normalisation
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub name: String,
+    /// Raw usage summed over cores (can exceed 100)
+    pub cpu_usage: f32,
+    /// Resident memory in bytes
+    pub memory: u64,
+    #[serde(default)]
+    pub virtual_memory: u64,
+    pub status: String,
+    #[serde(default)]
+    pub start_time: u64,
+    #[serde(default)]
+    pub exe: Option<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub cmd: Vec<String>,
+    #[serde(default)]
+    pub is_protected: bool,
+    /// Parent PID; `None` for roots and when the OS doesn't say
+    #[serde(default)]
+    pub parent: Option<u32>,
+    /// Owning user: the numeric UID on Unix, the SID on Windows
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Name of the owning user; `None` when the account can't be looked up
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<u32>,
+    #[serde(default)]
+    pub process_group_id: Option<u32>,
+    /// Controlling terminal under `/dev`, e.g. `pts/0`
+    #[serde(default)]
+    pub tty: Option<String>,
+    /// Docker, containerd, CRI-O or Podman container ID (Linux only)
+    #[serde(default)]
+    pub container_id: Option<String>,
+    /// CPU seconds spent in user mode since the process started (Linux only)
+    #[serde(default)]
+    pub cpu_time_user: Option<f64>,
+    /// CPU seconds spent in the kernel on the process's behalf (Linux only)
+    #[serde(default)]
+    pub cpu_time_system: Option<f64>,
+    /// Disk bytes read per second over the last refresh interval
+    #[serde(default)]
+    pub disk_read_rate: f64,
+    #[serde(default)]
+    pub disk_write_rate: f64,
+    /// Network bytes per second; `None` where the OS keeps no per-process counters
+    #[serde(default)]
+    pub net_recv_rate: Option<f64>,
+    #[serde(default)]
+    pub net_sent_rate: Option<f64>,
+    /// OOM killer badness (Linux only)
+    #[serde(default)]
+    pub oom_score: Option<i32>,
+    /// -1000 (never kill) to 1000 (kill first), Linux only
+    #[serde(default)]
+    pub oom_score_adj: Option<i32>,
+}
+
+/// A full process listing plus the machine totals needed to normalise it
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProcessTable {
+    pub processes: Vec<ProcessSample>,
+    pub total_memory: u64,
+    pub cpu_count: usize,
+}
+
+/// Blocking access to system data; handlers call it via `spawn_blocking`
+pub trait SystemSource: Send + Sync {
+    fn snapshot_stats(&self) -> SystemStats;
+
+    /// CPU model and cache sizes
+    fn cpu_info(&self) -> CpuInfo;
+
+    fn list_processes(&self) -> ProcessTable;
+
+    fn find_process(&self, pid: u32) -> Option<ProcessSample>;
+
+    /// Re-samples every process. The background poller calls this each tick so
+    /// `list_processes` can answer from the last sample; sources that are
+    /// always current can ignore it.
+    fn refresh_processes(&self) {}
+
+    /// Re-reads one process now instead of at the next poller tick; `None`
+    /// once it has exited, zombies included
+    fn refresh_process(&self, pid: u32) -> Option<ProcessSample> {
+        self.find_process(pid)
+    }
+
+    /// Returns the killed process's name
+    fn kill(&self, pid: u32) -> Result<String, ApiError>;
+
+    /// Sends `signal`; returns the process's name. Sources that only know
+    /// how to kill accept nothing but `SIGKILL`.
+    fn signal(&self, pid: u32, signal: Signal) -> Result<String, ApiError> {
+        match signal {
+            Signal::Kill => self.kill(pid),
+            _ => Err(ApiError::NotImplemented { feature: "Sending signals other than SIGKILL" }),
+        }
+    }
+
+    /// Stops the process (SIGSTOP) without ending it; returns its name
+    fn suspend(&self, pid: u32) -> Result<String, ApiError>;
+
+    /// Continues a suspended process (SIGCONT); returns its name
+    fn resume(&self, pid: u32) -> Result<String, ApiError>;
+
+    /// Environment variables; `PermissionDenied` for other users' processes rather than an
+    /// empty list
+    fn environ(&self, pid: u32) -> Result<EnvVars, ApiError>;
+
+    /// One reading of every thread's CPU time
+    fn threads(&self, pid: u32) -> Result<Vec<ThreadSample>, ApiError>;
+
+    /// Memory-mapped regions, unmerged
+    fn memory_maps(&self, pid: u32) -> Result<Vec<MapRegion>, ApiError>;
+
+    /// Cumulative storage I/O and syscall counts
+    fn io_counters(&self, pid: u32) -> Result<IoCounters, ApiError>;
+
+    /// PSS and USS, where the platform can measure them
+    fn set_sizes(&self, _pid: u32) -> Option<SetSizes> {
+        None
+    }
+
+    /// Resident memory by kind of mapping, on Linux
+    fn memory_breakdown(&self, _pid: u32) -> Option<ProcessMemoryBreakdown> {
+        None
+    }
+
+    /// Namespace inodes, on Linux
+    fn namespaces(&self, _pid: u32) -> Option<ProcessNamespaces> {
+        None
+    }
+
+    /// seccomp mode, on Linux
+    fn seccomp_status(&self, _pid: u32) -> Option<SeccompStatus> {
+        None
+    }
+
+    /// Capability sets, on Linux
+    fn capabilities(&self, _pid: u32) -> Option<ProcessCapabilities> {
+        None
+    }
+
+    /// OOM killer score and adjustment, on Linux
+    fn oom_score(&self, _pid: u32) -> Option<OomScore> {
+        None
+    }
+
+    /// Sets `oom_score_adj` and returns the score the kernel now reports
+    fn set_oom_score_adj(&self, _pid: u32, _adj: i32) -> Result<OomScore, ApiError> {
+        Err(ApiError::NotImplemented { feature: "OOM score adjustment" })
+    }
+
+    /// Sets the name shown in `/proc/<pid>/comm`
+    fn rename(&self, _pid: u32, _name: &str) -> Result<(), ApiError> {
+        Err(ApiError::NotImplemented { feature: "process renaming" })
+    }
+
+    /// Page fault counters, on Linux
+    fn page_faults(&self, _pid: u32) -> Option<PageFaults> {
+        None
+    }
+
+    /// Sends `signal` to every process in group `pgid` at once
+    fn signal_group(&self, _pgid: u32, _signal: Signal) -> Result<(), ApiError> {
+        Err(ApiError::NotImplemented { feature: "process group signals" })
+    }
+}
+
+/// A cached listing older than this is re-sampled on request, so the
+/// source stays usable without the poller
+const MAX_PROCESS_AGE: Duration = Duration::from_secs(2);
+
+/// The last process sample and when it was taken
+struct ProcessCache {
+    taken: Instant,
+    table: Option<ProcessTable>,
+}
+
+/// The real source, backed by `sysinfo`
+pub struct SysinfoSource {
+    system: Mutex<System>,
+    processes: Mutex<ProcessCache>,
+    users: Mutex<UserCache>,
+    sched: SchedSampler,
+    faults: FaultSampler,
+    topology: Topology,
+    gpu: GpuStatsCollector,
+}
+
+/// Accounts for resolving UIDs to names. Re-read when an unknown UID shows
+/// up, at most every [`USER_LIST_MAX_AGE`], since containers often run
+/// UIDs that have no account at all.
+struct UserCache {
+    read: Instant,
+    users: Users,
+}
+
+const USER_LIST_MAX_AGE: Duration = Duration::from_secs(60);
+
+impl SysinfoSource {
+    pub fn new() -> Self {
+        Self::with_gpu_cooldown(DEFAULT_GPU_COOLDOWN)
+    }
+
+    /// `gpu_cooldown` is how long NVML is left alone after repeated failures
+    pub fn with_gpu_cooldown(gpu_cooldown: Duration) -> Self {
+        let system = System::new_all();
+        Self {
+            topology: Topology::read(system.physical_core_count()),
+            system: Mutex::new(system),
+            // new_all() took the first sample, which CPU and disk rates count from
+            processes: Mutex::new(ProcessCache {
+                taken: Instant::now(),
+                table: None,
+            }),
+            users: Mutex::new(UserCache {
+                read: Instant::now(),
+                users: Users::new_with_refreshed_list(),
+            }),
+            sched: SchedSampler::default(),
+            faults: FaultSampler::default(),
+            gpu: GpuStatsCollector::new(gpu_cooldown),
+        }
+    }
+
+    /// `None` from sysinfo means the platform has no such signal; Windows
+    /// only supports kill
+    fn send_signal(&self, pid: u32, signal: Signal, feature: &'static str) -> Result<String, ApiError> {
+        let sys = self.system.lock().expect("system mutex poisoned");
+        let process = sys.process(Pid::from_u32(pid)).ok_or(ApiError::ProcessNotFound { pid })?;
+        match process.kill_with(signal) {
+            Some(true) => Ok(process.name().to_string_lossy().to_string()),
+            Some(false) => Err(ApiError::PermissionDenied { pid }),
+            None => Err(ApiError::NotImplemented { feature }),
+        }
+    }
+
+    /// `interval` is the time since the previous refresh, which sysinfo's
+    /// disk counters cover; without it the rates are 0
+    fn sample(pid: Pid, process: &sysinfo::Process, ttys: &TtyNames, users: &Users, interval: Option<Duration>) -> ProcessSample {
+        let exe = process.exe().map(|p| p.display().to_string());
+        let (ids, cpu_times) = procstat::read_with_cpu_times(pid.as_u32());
+        // Kernel threads have no command line and are children of kthreadd (PID 2)
+        let kernel_thread = cfg!(target_os = "linux")
+            && process.cmd().is_empty()
+            && (pid.as_u32() == 2 || process.parent() == Some(Pid::from_u32(2)));
+        let user_id = process.user_id().map(|uid| (**uid).to_string());
+        let system_owned = user_id.as_deref().is_some_and(protection::is_system_user_id);
+        let disk = process.disk_usage();
+        let rate = |bytes: u64| interval.map_or(0.0, |interval| bytes as f64 / interval.as_secs_f64().max(f64::EPSILON));
+        let oom = oom::read(pid.as_u32());
+        
+        ProcessSample {
+            pid: pid.as_u32(),
+            name: process.name().to_string_lossy().to_string(),
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            virtual_memory: process.virtual_memory(),
+            status: get_process_status(process.status()),
+            start_time: process.start_time(),
+            is_protected: protection::is_system_process(pid.as_u32(), exe.as_deref(), system_owned, kernel_thread),
+            exe,
+            cwd: process.cwd().map(|p| p.display().to_string()).or_else(|| cwd::read(pid.as_u32())),
+            cmd: process.cmd()
+                .iter()
+                .map(|s| s.to_string_lossy().to_string())
+                .collect(),
+            parent: process.parent().map(|parent| parent.as_u32()),
+            username: process.user_id().and_then(|uid| users.get_user_by_id(uid)).map(|user| user.name().to_string()),
+            user_id,
+            session_id: ids.map(|ids| ids.session_id),
+            process_group_id: ids.map(|ids| ids.process_group_id),
+            tty: ids.and_then(|ids| ttys.name(ids.tty_nr)),
+            container_id: containers::read(pid.as_u32()),
+            cpu_time_user: cpu_times.map(|times| times.user_seconds),
+            cpu_time_system: cpu_times.map(|times| times.system_seconds),
+            disk_read_rate: rate(disk.read_bytes),
+            disk_write_rate: rate(disk.written_bytes),
+            // sysinfo has no per-process network counters on any platform
+            net_recv_rate: None,
+            net_sent_rate: None,
+            oom_score: oom.map(|oom| oom.score),
+            oom_score_adj: oom.map(|oom| oom.adj),
+        }
+    }
+}
+
+impl Default for SysinfoSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemSource for SysinfoSource {
+    fn snapshot_stats(&self) -> SystemStats {
+
+        let mut sys = self.system.lock().expect("system mutex poisoned");
+    
+        sys.refresh_memory();
+        sys.refresh_cpu_all();
+    
+        let cpu_usage = sys.global_cpu_usage();
+        let cpus = sys.cpus();
+        let per_core: Vec<f32> = cpus.iter().map(|cpu| cpu.cpu_usage()).collect();
+    
+        let used_memory = sys.used_memory();
+        let total_memory = sys.total_memory();
+        let available_memory = sys.available_memory();
+        let memory_percent = (used_memory as f64 / total_memory as f64 * 100.0) as f32;
+    
+        // Get disk stats
+        let disks = sysinfo::Disks::new_with_refreshed_list();
+        let (total_disk, used_disk) = disks.iter().fold((0u64, 0u64), |(t, u), disk| {
+            (t + disk.total_space(), u + (disk.total_space() - disk.available_space()))
+        });
+        let disk_percent = if total_disk > 0 {
+            (used_disk as f64 / total_disk as f64 * 100.0) as f32
+        } else {
+            0.0
+        };
+    
+        // Get network stats
+        let networks = sysinfo::Networks::new_with_refreshed_list();
+        let (bytes_sent, bytes_recv) = networks.iter().fold((0u64, 0u64), |(s, r), (_name, network)| {
+            (s + network.total_transmitted(), r + network.total_received())
+        });
+    
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string();
+    
+        SystemStats {
+            timestamp,
+            cpu: CPUStats {
+                percent: cpu_usage,
+                cores: self.topology.cores(cpus.len()),
+                per_core,
+            },
+            memory: MemoryStats {
+                total: total_memory,
+                available: available_memory,
+                used: used_memory,
+                percent: memory_percent,
+                total_formatted: format_bytes(total_memory),
+                used_formatted: format_bytes(used_memory),
+                system_major_faults_per_sec: self.faults.sample(),
+            },
+            memory_pressure: pressure::read_memory(),
+            disk: DiskStats {
+                total: total_disk,
+                used: used_disk,
+                free: total_disk - used_disk,
+                percent: disk_percent,
+                total_formatted: format_bytes(total_disk),
+                used_formatted: format_bytes(used_disk),
+                disks: disks::list(&disks),
+            },
+            network: NetworkStats {
+                bytes_sent,
+                bytes_recv,
+                bytes_sent_formatted: format_bytes(bytes_sent),
+                bytes_recv_formatted: format_bytes(bytes_recv),
+                interfaces: netif::interfaces(&networks),
+            },
+            system: SystemInfo {
+                os: std::env::consts::OS.to_string(),
+                uptime_seconds: System::uptime(),
+            },
+            kernel: self.sched.sample(),
+            gpu: self.gpu.collect(),
+        }
+    }
+
+    fn cpu_info(&self) -> CpuInfo {
+        let sys = self.system.lock().expect("system mutex poisoned");
+        let first = sys.cpus().first();
+        CpuInfo {
+            brand: first.map(|cpu| cpu.brand().trim().to_string()).unwrap_or_default(),
+            vendor: first.map(|cpu| cpu.vendor_id().to_string()).unwrap_or_default(),
+            frequency_mhz: first.map_or(0, |cpu| cpu.frequency()),
+            physical_cores: self.topology.physical_cores,
+            logical_cores: sys.cpus().len(),
+            cache: cpucache::read(),
+        }
+    }
+
+    /// Served from the poller's last sample; the source re-samples itself
+    /// only when that is older than [`MAX_PROCESS_AGE`]
+    fn list_processes(&self) -> ProcessTable {
+        {
+            let cache = self.processes.lock().expect("process cache mutex poisoned");
+            if let Some(table) = cache.table.as_ref().filter(|_| cache.taken.elapsed() < MAX_PROCESS_AGE) {
+                return table.clone();
+            }
+        }
+        self.refresh_processes();
+        let cache = self.processes.lock().expect("process cache mutex poisoned");
+        cache.table.clone().expect("refresh_processes fills the cache")
+    }
+
+    fn find_process(&self, pid: u32) -> Option<ProcessSample> {
+        let sys = self.system.lock().expect("system mutex poisoned");
+        let pid = Pid::from_u32(pid);
+        let users = self.users.lock().expect("users mutex poisoned");
+        sys.process(pid).map(|process| Self::sample(pid, process, &TtyNames::scan(), &users.users, None))
+    }
+
+    fn refresh_process(&self, pid: u32) -> Option<ProcessSample> {
+        let mut sys = self.system.lock().expect("system mutex poisoned");
+        let pid = Pid::from_u32(pid);
+        sys.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, ProcessRefreshKind::everything());
+        let process = sys.process(pid).filter(|process| process.status() != ProcessStatus::Zombie)?;
+        let users = self.users.lock().expect("users mutex poisoned");
+        Some(Self::sample(pid, process, &TtyNames::scan(), &users.users, None))
+    }
+
+    fn kill(&self, pid: u32) -> Result<String, ApiError> {
+        let sys = self.system.lock().expect("system mutex poisoned");
+        
+        match sys.process(Pid::from_u32(pid)) {
+            Some(process) if process.kill() => Ok(process.name().to_string_lossy().to_string()),
+            Some(_) => Err(ApiError::PermissionDenied { pid }),
+            None => Err(ApiError::ProcessNotFound { pid }),
+        }
+    }
+
+    /// sysinfo derives CPU usage from the ticks each process used since the
+    /// previous refresh, so one refresh per poller tick measures CPU over the
+    /// whole interval, with no sleep anywhere near a request
+    fn refresh_processes(&self) {
+        let mut sys = self.system.lock().expect("system mutex poisoned");
+        sys.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::everything());
+        let mut cache = self.processes.lock().expect("process cache mutex poisoned");
+        let interval = cache.taken.elapsed();
+        cache.taken = Instant::now();
+
+        let ttys = TtyNames::scan();
+        let mut users = self.users.lock().expect("users mutex poisoned");
+        let unknown_user = sys
+            .processes()
+            .values()
+            .filter_map(|process| process.user_id())
+            .any(|uid| users.users.get_user_by_id(uid).is_none());
+        if unknown_user && users.read.elapsed() >= USER_LIST_MAX_AGE {
+            users.users.refresh_list();
+            users.read = Instant::now();
+        }
+        cache.table = Some(ProcessTable {
+            processes: sys
+                .processes()
+                .iter()
+                .map(|(pid, process)| Self::sample(*pid, process, &ttys, &users.users, Some(interval)))
+                .collect(),
+            total_memory: sys.total_memory(),
+            cpu_count: sys.cpus().len(),
+        });
+    }
+
+    fn signal(&self, pid: u32, signal: Signal) -> Result<String, ApiError> {
+        self.send_signal(pid, signal, "This signal")
+    }
+
+    fn suspend(&self, pid: u32) -> Result<String, ApiError> {
+        self.send_signal(pid, Signal::Stop, "Suspending processes")
+    }
+
+    fn resume(&self, pid: u32) -> Result<String, ApiError> {
+        self.send_signal(pid, Signal::Continue, "Resuming processes")
+    }
+
+    #[cfg(target_os = "linux")]
+    fn environ(&self, pid: u32) -> Result<EnvVars, ApiError> {
+        environ::read(pid)
+    }
+
+    /// sysinfo hands back an empty environment when it may not read it, so
+    /// an empty one from another user's process is taken as a refusal
+    #[cfg(not(target_os = "linux"))]
+    fn environ(&self, pid: u32) -> Result<EnvVars, ApiError> {
+        let mut sys = self.system.lock().expect("system mutex poisoned");
+        let target = Pid::from_u32(pid);
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[target]),
+            false,
+            ProcessRefreshKind::new()
+                .with_user(sysinfo::UpdateKind::OnlyIfNotSet)
+                .with_environ(sysinfo::UpdateKind::Always),
+        );
+        let process = sys.process(target).ok_or(ApiError::ProcessNotFound { pid })?;
+        let entries = process.environ().iter().map(|entry| entry.to_string_lossy()).collect::<Vec<_>>().join("\0");
+        let vars = environ::parse_environ(entries.as_bytes());
+        let ours = sysinfo::get_current_pid().ok().and_then(|me| sys.process(me)).and_then(|me| me.user_id());
+        if vars.is_empty() && process.user_id() != ours {
+            return Err(ApiError::PermissionDenied { pid });
+        }
+        Ok(vars)
+    }
+
+    fn threads(&self, pid: u32) -> Result<Vec<ThreadSample>, ApiError> {
+        threads::read(pid)
+    }
+
+    fn memory_maps(&self, pid: u32) -> Result<Vec<MapRegion>, ApiError> {
+        maps::read(pid)
+    }
+
+    fn io_counters(&self, pid: u32) -> Result<IoCounters, ApiError> {
+        procio::read(pid)
+    }
+
+    fn set_sizes(&self, pid: u32) -> Option<SetSizes> {
+        smaps::read(pid)
+    }
+
+    fn memory_breakdown(&self, pid: u32) -> Option<ProcessMemoryBreakdown> {
+        smaps::read_breakdown(pid)
+    }
+
+    fn namespaces(&self, pid: u32) -> Option<ProcessNamespaces> {
+        namespaces::read(pid)
+    }
+
+    fn seccomp_status(&self, pid: u32) -> Option<SeccompStatus> {
+        seccomp::read(pid)
+    }
+
+    fn capabilities(&self, pid: u32) -> Option<ProcessCapabilities> {
+        capabilities::read(pid)
+    }
+
+    fn oom_score(&self, pid: u32) -> Option<OomScore> {
+        oom::read(pid)
+    }
+
+    fn set_oom_score_adj(&self, pid: u32, adj: i32) -> Result<OomScore, ApiError> {
+        oom::write_adj(pid, adj)?;
+        oom::read(pid).ok_or(ApiError::ProcessNotFound { pid })
+    }
+
+    /// sysinfo reads names only for processes it hasn't seen before, so
+    /// listings keep the old name until the process restarts
+    fn rename(&self, pid: u32, name: &str) -> Result<(), ApiError> {
+        comm::write(pid, name)
+    }
+
+    fn page_faults(&self, pid: u32) -> Option<PageFaults> {
+        procstat::read_page_faults(pid)
+    }
+
+    #[cfg(unix)]
+    fn signal_group(&self, pgid: u32, signal: Signal) -> Result<(), ApiError> {
+        let number = match signal {
+            Signal::Hangup => libc::SIGHUP,
+            Signal::Interrupt => libc::SIGINT,
+            Signal::Quit => libc::SIGQUIT,
+            Signal::Kill => libc::SIGKILL,
+            Signal::Term => libc::SIGTERM,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Continue => libc::SIGCONT,
+            _ => return Err(ApiError::NotImplemented { feature: "This signal" }),
+        };
+        // kill(-1) would reach every process the backend may signal, and
+        // kill(0) the backend's own group
+        let target = libc::pid_t::try_from(pgid).ok().filter(|pgid| *pgid > 1).ok_or(ApiError::ProcessGroupNotFound { pgid })?;
+        // SAFETY: kill only sends a signal
+        if unsafe { libc::kill(-target, number) } == 0 {
+            return Ok(());
+        }
+        match std::io::Error::last_os_error().raw_os_error() {
+            Some(libc::ESRCH) => Err(ApiError::ProcessGroupNotFound { pgid }),
+            Some(libc::EPERM) => Err(ApiError::ProcessGroupPermissionDenied { pgid }),
+            error => {
+                tracing::warn!(pgid, ?error, "kill of a process group failed");
+                Err(ApiError::Internal)
+            }
+        }
+    }
+}
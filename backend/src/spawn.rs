@@ -0,0 +1,159 @@
+//! Starting processes for `POST /api/process/start` and `POST /api/process/launch`
+
+use axum::response::sse::Event;
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::Stream;
+use std::convert::Infallible;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+
+use crate::error::ApiError;
+use crate::models::{LaunchProcessRequest, StartProcessRequest};
+use crate::source::ProcessSample;
+
+/// Variables that pick which executable `PATH` lookup finds, or inject
+/// libraries into it, so an allowed command could run someone else's code
+const LOADER_ENV: &[&str] = &["PATH", "PATHEXT"];
+
+/// Prefixes of the dynamic loaders' variables: glibc/musl (`LD_PRELOAD`,
+/// `LD_LIBRARY_PATH`, `LD_AUDIT`, ...) and macOS dyld
+const LOADER_ENV_PREFIXES: &[&str] = &["LD_", "DYLD_"];
+
+/// Refuses a request that sets one of the loader or `PATH` variables;
+/// names are compared case-insensitively, as Windows does
+pub fn check_env<'a>(names: impl IntoIterator<Item = &'a String>) -> Result<(), ApiError> {
+    for name in names {
+        let upper = name.to_ascii_uppercase();
+        if LOADER_ENV.contains(&upper.as_str()) || LOADER_ENV_PREFIXES.iter().any(|prefix| upper.starts_with(prefix)) {
+            return Err(ApiError::EnvNotAllowed { name: name.clone() });
+        }
+    }
+    Ok(())
+}
+
+/// Spawns `request.command` with stdout/stderr piped: streamed back over SSE
+/// when the caller waits, otherwise read by [`crate::output::SpawnedProcesses`]
+pub fn start(request: &StartProcessRequest) -> Result<Child, ApiError> {
+    let mut command = Command::new(&request.command);
+    command.args(&request.args).envs(&request.env).stdin(Stdio::null());
+    if let Some(cwd) = &request.cwd {
+        command.current_dir(cwd);
+    }
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    command.spawn().map_err(|source| spawn_error(&request.command, source))
+}
+
+/// Spawns `request.command` with no stdio, returning its PID. A detached
+/// process gets its own session (Unix) or process group and no console
+/// (Windows), so neither the backend exiting nor Ctrl+C in its terminal reaches it.
+pub fn launch(request: &LaunchProcessRequest) -> Result<u32, ApiError> {
+    let mut command = std::process::Command::new(&request.command);
+    command
+        .args(&request.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(cwd) = &request.cwd {
+        command.current_dir(cwd);
+    }
+    if request.detached {
+        detach_command(&mut command);
+    }
+
+    let mut child = command.spawn().map_err(|source| spawn_error(&request.command, source))?;
+    let pid = child.id();
+    // The child stays ours until it exits, so something has to reap it
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(pid)
+}
+
+/// What [`launch`] needs to start `process` again: its executable with the
+/// same arguments, in the same directory. `None` when the executable or
+/// command line can't be read. An executable replaced since it started
+/// (" (deleted)" on Linux) is taken from its path, so an upgraded binary runs;
+/// a deleted working directory falls back to the backend's.
+pub fn relaunch_request(process: &ProcessSample) -> Option<LaunchProcessRequest> {
+    let exe = process.exe.as_deref().filter(|exe| !exe.is_empty())?;
+    let (_, args) = process.cmd.split_first()?;
+    Some(LaunchProcessRequest {
+        command: exe.trim_end_matches(" (deleted)").to_string(),
+        args: args.to_vec(),
+        cwd: process.cwd.clone().filter(|cwd| !cwd.ends_with(" (deleted)")),
+        detached: true,
+    })
+}
+
+#[cfg(unix)]
+fn detach_command(command: &mut std::process::Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: setsid is async-signal-safe and touches no memory of the parent
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn detach_command(command: &mut std::process::Command) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+/// A missing binary or bad `cwd` is the caller's mistake (400); lacking
+/// execute permission is 403
+fn spawn_error(command: &str, source: std::io::Error) -> ApiError {
+    let command = command.to_string();
+    if source.kind() == std::io::ErrorKind::PermissionDenied {
+        ApiError::SpawnPermissionDenied { command, source }
+    } else {
+        ApiError::SpawnFailed { command, source }
+    }
+}
+
+/// SSE stream of `started`, then `stdout`/`stderr` lines, then `exit` with the exit code.
+/// The child keeps running if the client disconnects.
+pub fn output_events(mut child: Child) -> impl Stream<Item = Result<Event, Infallible>> {
+    let (tx, rx) = mpsc::unbounded();
+    let started = serde_json::json!({ "pid": child.id() });
+    let _ = tx.unbounded_send(Ok(Event::default().event("started").data(started.to_string())));
+
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    tokio::spawn(async move {
+        let (_, _, status) = tokio::join!(
+            forward_lines(stdout, "stdout", tx.clone()),
+            forward_lines(stderr, "stderr", tx.clone()),
+            child.wait(),
+        );
+        let code = status.ok().and_then(|s| s.code());
+        let exit = serde_json::json!({ "code": code });
+        let _ = tx.unbounded_send(Ok(Event::default().event("exit").data(exit.to_string())));
+    });
+
+    rx
+}
+
+/// Keeps reading after the receiver is gone so the child never blocks on a full pipe
+async fn forward_lines(
+    pipe: Option<impl AsyncRead + Unpin>,
+    event: &'static str,
+    tx: UnboundedSender<Result<Event, Infallible>>,
+) {
+    let Some(pipe) = pipe else {
+        return;
+    };
+    let mut lines = BufReader::new(pipe).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let _ = tx.unbounded_send(Ok(Event::default().event(event).data(line)));
+    }
+}
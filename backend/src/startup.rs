@@ -0,0 +1,342 @@
+//! Programs started at login: XDG autostart entries and enabled user systemd
+//! units on Linux, the `Run` registry keys and Startup folders on Windows
+//! (`501` elsewhere). Read-only; nothing here changes an entry.
+//!
+//! Each entry is matched to a running process by its executable, so the
+//! dashboard can show what a startup item costs right now.
+
+use serde::Deserialize;
+
+use crate::error::ApiError;
+use crate::models::{StartupItem, StartupSource};
+use crate::source::ProcessSample;
+
+/// The `[Desktop Entry]` keys an autostart file is listed by
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesktopEntry {
+    /// `Name`; `None` when the file has none
+    pub name: Option<String>,
+    /// `Exec` with its field codes removed
+    pub exec: String,
+    /// False for `Hidden=true` or `X-GNOME-Autostart-enabled=false`
+    pub enabled: bool,
+}
+
+/// Parses a `.desktop` file; `None` without an `Exec` key in the
+/// `[Desktop Entry]` group. Localized keys such as `Name[de]` are ignored.
+pub fn parse_desktop_entry(text: &str) -> Option<DesktopEntry> {
+    let (mut name, mut exec, mut enabled) = (None, None, true);
+    let mut in_entry = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=').filter(|_| in_entry) else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Name" => name = Some(value.to_string()),
+            "Exec" => exec = Some(strip_field_codes(value)),
+            "Hidden" if value == "true" => enabled = false,
+            "X-GNOME-Autostart-enabled" if value == "false" => enabled = false,
+            _ => {}
+        }
+    }
+    Some(DesktopEntry { name, exec: exec?, enabled })
+}
+
+/// Removes `%f`, `%U` and the other `Exec` field codes, which the launcher
+/// replaces with files or URLs; `%%` is a literal `%`
+pub fn strip_field_codes(exec: &str) -> String {
+    let mut command = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => {
+                if let Some('%') = chars.next() {
+                    command.push('%');
+                }
+            }
+            _ => command.push(c),
+        }
+    }
+    command.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The first `ExecStart=` of a unit's `[Service]` section, without the
+/// `-`, `@`, `:`, `+` and `!` prefixes
+pub fn parse_exec_start(unit: &str) -> Option<String> {
+    let mut in_service = false;
+    for line in unit.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_service = line == "[Service]";
+            continue;
+        }
+        if !in_service {
+            continue;
+        }
+        if let Some(command) = line.strip_prefix("ExecStart").and_then(|rest| rest.trim_start().strip_prefix('=')) {
+            let command = command.trim().trim_start_matches(['-', '@', ':', '+', '!']);
+            if !command.is_empty() {
+                return Some(command.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// The program a command line runs: its first word, or the quoted path at
+/// its start (`"C:\Program Files\App\app.exe" --tray`)
+pub fn command_executable(command: &str) -> Option<&str> {
+    let command = command.trim_start();
+    let program = match command.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => command.split_whitespace().next()?,
+    };
+    (!program.is_empty()).then_some(program)
+}
+
+/// Fills `pid`, `cpu_percent` and `memory_bytes` from the oldest process
+/// running each item's executable. A command with a path must match the
+/// process's executable path; a bare name matches its file name. Windows
+/// paths compare case-insensitively.
+pub fn attach_processes(items: &mut [StartupItem], processes: &[ProcessSample], logical_cores: f32) {
+    for item in items {
+        let Some(program) = command_executable(&item.command) else {
+            continue;
+        };
+        let has_path = program.contains(['/', '\\']);
+        let matches = |exe: &str| {
+            let exe = if has_path { exe } else { file_name(exe) };
+            if cfg!(windows) {
+                exe.eq_ignore_ascii_case(program)
+            } else {
+                exe == program
+            }
+        };
+        let process = processes
+            .iter()
+            .filter(|process| process.exe.as_deref().is_some_and(matches))
+            .min_by_key(|process| (process.start_time, process.pid));
+        if let Some(process) = process {
+            item.pid = Some(process.pid);
+            item.cpu_percent = Some(process.cpu_usage / logical_cores.max(1.0));
+            item.memory_bytes = Some(process.memory);
+        }
+    }
+}
+
+/// The last component of a `/` or `\` separated path
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Registry values and Startup folder files, as JSON. `GetValue` expands
+/// `REG_EXPAND_SZ`. The `StartupApproved` keys hold what Task Manager's
+/// Startup tab switched off: an odd first byte means disabled. HKCU has no
+/// separate 32-bit view, so only HKLM's `WOW6432Node` is read twice.
+pub const WINDOWS_LIST_SCRIPT: &str = r#"
+$ErrorActionPreference = 'SilentlyContinue'
+$approved = 'Software\Microsoft\Windows\CurrentVersion\Explorer\StartupApproved'
+function Test-Enabled($hive, $kind, $name) {
+    $value = (Get-Item -LiteralPath "${hive}:\$approved\$kind").GetValue($name)
+    -not ($value -and ($value[0] -band 1))
+}
+$items = @()
+$keys = @(
+    @('HKCU', 'Software\Microsoft\Windows\CurrentVersion\Run', 'Run'),
+    @('HKLM', 'SOFTWARE\Microsoft\Windows\CurrentVersion\Run', 'Run'),
+    @('HKLM', 'SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Run', 'Run32')
+)
+foreach ($key in $keys) {
+    $item = Get-Item -LiteralPath "$($key[0]):\$($key[1])"
+    if (-not $item) { continue }
+    foreach ($name in $item.GetValueNames()) {
+        if (-not $name) { continue }
+        $items += [pscustomobject]@{
+            Name = $name; Command = [string]$item.GetValue($name); Source = 'registry'
+            Location = "$($key[0])\$($key[1])"; Enabled = Test-Enabled $key[0] $key[2] $name
+        }
+    }
+}
+$shell = New-Object -ComObject WScript.Shell
+foreach ($folder in @(@('HKCU', 'Startup'), @('HKLM', 'CommonStartup'))) {
+    $path = [Environment]::GetFolderPath($folder[1])
+    if (-not $path) { continue }
+    foreach ($file in Get-ChildItem -LiteralPath $path -File) {
+        if ($file.Name -eq 'desktop.ini') { continue }
+        $command = "`"$($file.FullName)`""
+        if ($file.Extension -eq '.lnk') {
+            $link = $shell.CreateShortcut($file.FullName)
+            $command = ("`"$($link.TargetPath)`" $($link.Arguments)").Trim()
+        }
+        $items += [pscustomobject]@{
+            Name = $file.BaseName; Command = $command; Source = 'startup_folder'
+            Location = $path; Enabled = Test-Enabled $folder[0] 'StartupFolder' $file.Name
+        }
+    }
+}
+ConvertTo-Json -Compress -InputObject @($items)
+"#;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WindowsStartupRow {
+    name: String,
+    #[serde(default)]
+    command: Option<String>,
+    source: StartupSource,
+    location: String,
+    enabled: bool,
+}
+
+/// Parses [`WINDOWS_LIST_SCRIPT`]'s output: an array, or `null` when there
+/// is nothing to list
+pub fn parse_windows_startup(json: &str) -> Option<Vec<StartupItem>> {
+    let rows: Option<Vec<WindowsStartupRow>> = serde_json::from_str(json.trim()).ok()?;
+    Some(
+        rows.unwrap_or_default()
+            .into_iter()
+            .map(|row| StartupItem {
+                name: row.name,
+                command: row.command.unwrap_or_default(),
+                source: row.source,
+                location: row.location,
+                enabled: row.enabled,
+                pid: None,
+                cpu_percent: None,
+                memory_bytes: None,
+            })
+            .collect(),
+    )
+}
+
+/// Every startup entry, user entries first
+#[cfg(target_os = "linux")]
+pub async fn list() -> Result<Vec<StartupItem>, ApiError> {
+    tokio::task::spawn_blocking(xdg::list).await.map_err(|_| ApiError::Internal)
+}
+
+#[cfg(windows)]
+pub async fn list() -> Result<Vec<StartupItem>, ApiError> {
+    let output = crate::utils::powershell().arg(WINDOWS_LIST_SCRIPT).output().await.map_err(|err| {
+        tracing::warn!(error = %err, "cannot run powershell.exe");
+        ApiError::Internal
+    })?;
+    parse_windows_startup(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        tracing::warn!(stderr = %String::from_utf8_lossy(&output.stderr), "cannot list startup entries");
+        ApiError::Internal
+    })
+}
+
+/// macOS keeps login items in a per-user database only System Settings reads
+#[cfg(not(any(target_os = "linux", windows)))]
+pub async fn list() -> Result<Vec<StartupItem>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Startup programs" })
+}
+
+#[cfg(target_os = "linux")]
+mod xdg {
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+
+    use super::{parse_desktop_entry, parse_exec_start};
+    use crate::models::{StartupItem, StartupSource};
+
+    /// `$XDG_CONFIG_HOME` (default `~/.config`), then `$XDG_CONFIG_DIRS`
+    /// (default `/etc/xdg`): the user's directory comes first and overrides
+    fn config_dirs() -> Vec<PathBuf> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let home = var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")));
+        let system = var("XDG_CONFIG_DIRS").unwrap_or_else(|| "/etc/xdg".to_string());
+        home.into_iter().chain(system.split(':').map(PathBuf::from)).collect()
+    }
+
+    /// Directory entries sorted by name, so the listing is stable
+    fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    }
+
+    pub fn list() -> Vec<StartupItem> {
+        let mut items = autostart_entries();
+        items.extend(systemd_user_units());
+        items
+    }
+
+    /// A file in the user's directory hides the system one with the same name
+    fn autostart_entries() -> Vec<StartupItem> {
+        let mut seen = HashSet::new();
+        let mut items = Vec::new();
+        for dir in config_dirs().into_iter().map(|dir| dir.join("autostart")) {
+            for path in sorted_entries(&dir) {
+                let Some(file_name) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+                    continue;
+                };
+                if !file_name.ends_with(".desktop") || !seen.insert(file_name.clone()) {
+                    continue;
+                }
+                let Some(entry) = std::fs::read_to_string(&path).ok().as_deref().and_then(parse_desktop_entry) else {
+                    continue;
+                };
+                items.push(StartupItem {
+                    name: entry.name.unwrap_or_else(|| file_name.trim_end_matches(".desktop").to_string()),
+                    command: entry.exec,
+                    source: StartupSource::XdgAutostart,
+                    location: path.display().to_string(),
+                    enabled: entry.enabled,
+                    pid: None,
+                    cpu_percent: None,
+                    memory_bytes: None,
+                });
+            }
+        }
+        items
+    }
+
+    /// Services linked into a `*.wants` directory of the user's
+    /// `systemd/user` or `/etc/systemd/user`, which is what
+    /// `systemctl --user enable` does
+    fn systemd_user_units() -> Vec<StartupItem> {
+        let user_dirs = config_dirs().into_iter().take(1).map(|dir| dir.join("systemd/user"));
+        let mut seen = HashSet::new();
+        let mut items = Vec::new();
+        for dir in user_dirs.chain([PathBuf::from("/etc/systemd/user")]) {
+            let wants = sorted_entries(&dir)
+                .into_iter()
+                .filter(|path| path.extension().is_some_and(|ext| ext == "wants") && path.is_dir());
+            for wants_dir in wants {
+                for path in sorted_entries(&wants_dir) {
+                    let Some(unit) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+                        continue;
+                    };
+                    if !unit.ends_with(".service") || !seen.insert(unit.clone()) {
+                        continue;
+                    }
+                    // Follows the link to the unit file; a unit masked with /dev/null has no ExecStart
+                    let Some(command) = std::fs::read_to_string(&path).ok().as_deref().and_then(parse_exec_start) else {
+                        continue;
+                    };
+                    items.push(StartupItem {
+                        name: unit,
+                        command,
+                        source: StartupSource::SystemdUser,
+                        location: wants_dir.display().to_string(),
+                        enabled: true,
+                        pid: None,
+                        cpu_percent: None,
+                        memory_bytes: None,
+                    });
+                }
+            }
+        }
+        items
+    }
+}
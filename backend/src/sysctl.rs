@@ -0,0 +1,147 @@
+//! Kernel parameters, the equivalent of `sysctl -a` and `sysctl -w`.
+//!
+//! On Linux each parameter is a file under `/proc/sys`: `net.ipv4.ip_forward`
+//! is `/proc/sys/net/ipv4/ip_forward`. macOS lists them with `sysctl`.
+
+use std::path::PathBuf;
+
+use crate::error::ApiError;
+use crate::models::SysctlParam;
+
+/// `net.core.somaxconn`: dot-separated names of letters, digits, `_` and `-`,
+/// so a key can never climb out of `/proc/sys`
+pub fn validate_key(key: &str) -> Result<(), ApiError> {
+    let valid = !key.is_empty()
+        && key.len() <= 256
+        && key
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidSysctlKey { key: key.to_string() })
+    }
+}
+
+/// `/proc/sys` plus the key with `.` as the separator
+pub fn key_path(key: &str) -> Result<PathBuf, ApiError> {
+    validate_key(key)?;
+    Ok(key.split('.').fold(PathBuf::from("/proc/sys"), |path, part| path.join(part)))
+}
+
+/// Every parameter whose key is `prefix` or starts with `prefix.`, sorted by
+/// key. Write-only and unreadable entries are skipped, as `sysctl -a` does.
+pub async fn list(prefix: Option<String>) -> Result<Vec<SysctlParam>, ApiError> {
+    if let Some(prefix) = &prefix {
+        validate_key(prefix)?;
+    }
+    tokio::task::spawn_blocking(move || list_blocking(prefix.as_deref()))
+        .await
+        .map_err(|_| ApiError::Internal)?
+}
+
+#[cfg(target_os = "linux")]
+fn list_blocking(prefix: Option<&str>) -> Result<Vec<SysctlParam>, ApiError> {
+    let root = match prefix {
+        Some(prefix) => key_path(prefix)?,
+        None => PathBuf::from("/proc/sys"),
+    };
+    if !root.exists() {
+        return Err(ApiError::SysctlNotFound { key: prefix.unwrap_or_default().to_string() });
+    }
+    let mut params = Vec::new();
+    let mut pending = vec![root];
+    while let Some(path) = pending.pop() {
+        if path.is_dir() {
+            if let Ok(entries) = std::fs::read_dir(&path) {
+                pending.extend(entries.flatten().map(|entry| entry.path()));
+            }
+            continue;
+        }
+        let Ok(value) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(relative) = path.strip_prefix("/proc/sys") else {
+            continue;
+        };
+        params.push(SysctlParam {
+            key: relative.to_string_lossy().replace('/', "."),
+            value: clean_value(&value),
+        });
+    }
+    params.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(params)
+}
+
+#[cfg(target_os = "macos")]
+fn list_blocking(prefix: Option<&str>) -> Result<Vec<SysctlParam>, ApiError> {
+    let output = std::process::Command::new("sysctl")
+        .arg(prefix.unwrap_or("-a"))
+        .output()
+        .map_err(|err| {
+            tracing::warn!(error = %err, "cannot run sysctl");
+            ApiError::Internal
+        })?;
+    let mut params = parse_sysctl_output(&String::from_utf8_lossy(&output.stdout));
+    if params.is_empty() {
+        if let Some(prefix) = prefix {
+            return Err(ApiError::SysctlNotFound { key: prefix.to_string() });
+        }
+    }
+    params.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(params)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn list_blocking(_prefix: Option<&str>) -> Result<Vec<SysctlParam>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Kernel parameters" })
+}
+
+/// Multi-value files such as `net.ipv4.tcp_rmem` separate values with tabs;
+/// `sysctl` shows them space-separated, without the trailing newline
+pub fn clean_value(raw: &str) -> String {
+    raw.trim_end_matches('\n').split('\t').collect::<Vec<_>>().join(" ")
+}
+
+/// Parses `sysctl -a` lines: `key: value` on macOS, `key = value` on Linux
+pub fn parse_sysctl_output(text: &str) -> Vec<SysctlParam> {
+    text.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(": ").or_else(|| line.split_once(" = "))?;
+            validate_key(key).ok()?;
+            Some(SysctlParam {
+                key: key.to_string(),
+                value: value.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Writes `value` and returns the parameter as the kernel now reports it
+#[cfg(target_os = "linux")]
+pub async fn write(key: String, value: String) -> Result<SysctlParam, ApiError> {
+    let path = key_path(&key)?;
+    if !path.is_file() {
+        return Err(ApiError::SysctlNotFound { key });
+    }
+    tokio::task::spawn_blocking(move || {
+        std::fs::write(&path, value.as_bytes()).map_err(|err| match err.kind() {
+            // /proc/sys is mounted read-only inside most containers
+            std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem => {
+                ApiError::SysctlPermissionDenied { key: key.clone() }
+            }
+            // EINVAL and friends: out of range, wrong type, read-only in this namespace
+            _ => ApiError::InvalidSysctlValue { key: key.clone() },
+        })?;
+        let value = std::fs::read_to_string(&path).map(|v| clean_value(&v)).unwrap_or(value);
+        Ok(SysctlParam { key, value })
+    })
+    .await
+    .map_err(|_| ApiError::Internal)?
+}
+
+#[cfg(not(target_os = "linux"))]
+pub async fn write(key: String, _value: String) -> Result<SysctlParam, ApiError> {
+    validate_key(&key)?;
+    Err(ApiError::NotImplemented { feature: "Writing kernel parameters" })
+}
@@ -0,0 +1,111 @@
+//! Threads of one process, from `/proc/<pid>/task/<tid>/{comm,stat}`.
+//!
+//! CPU usage needs two readings of each thread's CPU time; the handler takes
+//! them [`SAMPLE_INTERVAL`] apart and [`cpu_usage`] turns them into percentages.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::ApiError;
+use crate::models::ThreadInfo;
+
+/// Time between the two readings; long enough to see a busy thread, short
+/// enough to keep the request snappy
+pub const SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// One reading of one thread
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadSample {
+    pub tid: u32,
+    pub name: String,
+    /// Same words as process status: "running", "sleeping", "stopped", ...
+    pub state: String,
+    /// User plus system CPU seconds; `None` where the OS doesn't say
+    pub cpu_seconds: Option<f64>,
+}
+
+/// The state letter after `comm` in a stat line, in process-status words
+pub fn state_name(state: char) -> &'static str {
+    match state {
+        'R' => "running",
+        'S' | 'D' | 'I' => "sleeping",
+        'T' | 't' => "stopped",
+        'Z' => "zombie",
+        'X' | 'x' => "dead",
+        _ => "unknown",
+    }
+}
+
+/// Pairs each thread in `after` with its reading in `before`. CPU percent is
+/// of one core, so a thread spinning flat out reads 100 and the sum is
+/// comparable to the process's own `cpu_percent` in `/info`. Threads that
+/// started between the readings get `None`. Busiest first, then by TID.
+pub fn cpu_usage(before: &[ThreadSample], after: Vec<ThreadSample>, elapsed: Duration) -> Vec<ThreadInfo> {
+    let before: HashMap<u32, Option<f64>> = before.iter().map(|t| (t.tid, t.cpu_seconds)).collect();
+    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+    let mut threads: Vec<ThreadInfo> = after
+        .into_iter()
+        .map(|thread| {
+            let start = before.get(&thread.tid).copied().flatten();
+            let cpu_percent = start
+                .zip(thread.cpu_seconds)
+                .map(|(start, end)| ((end - start).max(0.0) / seconds * 100.0) as f32);
+            ThreadInfo {
+                tid: thread.tid,
+                name: thread.name,
+                state: thread.state,
+                cpu_percent,
+            }
+        })
+        .collect();
+    threads.sort_by(|a, b| {
+        let busy = |t: &ThreadInfo| t.cpu_percent.unwrap_or(-1.0);
+        busy(b).total_cmp(&busy(a)).then(a.tid.cmp(&b.tid))
+    });
+    threads
+}
+
+/// Threads that exit mid-scan are skipped; a missing process is 404 and an
+/// unreadable task directory 403
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Result<Vec<ThreadSample>, ApiError> {
+    use crate::procstat;
+
+    let dir = std::path::PathBuf::from(format!("/proc/{}/task", pid));
+    let entries = std::fs::read_dir(&dir).map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => ApiError::ProcessNotFound { pid },
+        std::io::ErrorKind::PermissionDenied => ApiError::PermissionDenied { pid },
+        _ => {
+            tracing::warn!(pid, error = %err, "cannot list process threads");
+            ApiError::Internal
+        }
+    })?;
+    let ticks = procstat::clock_ticks();
+    let mut threads = Vec::new();
+    for entry in entries.flatten() {
+        let Some(tid) = entry.file_name().to_str().and_then(|name| name.parse().ok()) else {
+            continue;
+        };
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        let state = stat
+            .rsplit_once(')')
+            .and_then(|(_, rest)| rest.trim_start().chars().next())
+            .map_or("unknown", state_name);
+        let name = std::fs::read_to_string(entry.path().join("comm")).unwrap_or_default();
+        threads.push(ThreadSample {
+            tid,
+            name: name.trim_end().to_string(),
+            state: state.to_string(),
+            cpu_seconds: procstat::parse_cpu_times(&stat, ticks).map(|times| times.total_seconds()),
+        });
+    }
+    Ok(threads)
+}
+
+/// Windows would need toolhelp snapshots and macOS `task_threads`
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Result<Vec<ThreadSample>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "Per-thread listing" })
+}
@@ -0,0 +1,142 @@
+//! HTTPS serving: rustls config, HTTP→HTTPS redirect and certificate hot reload
+
+use axum::{
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::pki_types::pem::{self, PemObject};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::{InconsistentKeys, ServerConfig};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+use crate::config::TlsConfig;
+
+/// How often the cert and key files are checked for changes
+pub const CERT_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Selects the ring crypto provider; safe to call more than once
+pub fn install_crypto_provider() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+}
+
+/// Why a certificate/key pair could not be used
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("cannot read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{} is not valid PEM: {source}", path.display())]
+    Pem { path: PathBuf, source: pem::Error },
+    #[error("{} contains no PEM certificate", path.display())]
+    NoCertificate { path: PathBuf },
+    #[error("{} contains no PEM private key", path.display())]
+    NoKey { path: PathBuf },
+    #[error("certificate {} does not match private key {}", cert.display(), key.display())]
+    KeyMismatch { cert: PathBuf, key: PathBuf },
+    #[error("invalid certificate or key: {0}")]
+    Rustls(rustls::Error),
+}
+
+fn pem_error(path: &Path, err: pem::Error) -> TlsError {
+    let path = path.to_path_buf();
+    match err {
+        pem::Error::Io(source) => TlsError::Read { path, source },
+        pem::Error::NoItemsFound => TlsError::NoKey { path },
+        source => TlsError::Pem { path, source },
+    }
+}
+
+/// Reads and validates the PEM cert chain and key named in `tls`
+pub fn server_config(tls: &TlsConfig) -> Result<ServerConfig, TlsError> {
+    install_crypto_provider();
+    let certs = CertificateDer::pem_file_iter(&tls.cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| pem_error(&tls.cert, err))?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificate { path: tls.cert.clone() });
+    }
+    let key = PrivateKeyDer::from_pem_file(&tls.key).map_err(|err| pem_error(&tls.key, err))?;
+
+    let mut config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| match err {
+            rustls::Error::InconsistentKeys(InconsistentKeys::KeyMismatch) => TlsError::KeyMismatch {
+                cert: tls.cert.clone(),
+                key: tls.key.clone(),
+            },
+            other => TlsError::Rustls(other),
+        })?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// Loads `tls` for [`axum_server::bind_rustls`], failing on unreadable or mismatched files
+pub fn load(tls: &TlsConfig) -> Result<RustlsConfig, TlsError> {
+    Ok(RustlsConfig::from_config(Arc::new(server_config(tls)?)))
+}
+
+/// Plain-HTTP router that sends every request to the same path on `tls_port`
+pub fn redirect_router(tls_port: u16) -> Router {
+    Router::new().fallback(move |headers: HeaderMap, uri: Uri| async move {
+        redirect_to_https(&headers, &uri, tls_port)
+    })
+}
+
+fn redirect_to_https(headers: &HeaderMap, uri: &Uri, tls_port: u16) -> Response {
+    let Some(host) = headers.get(header::HOST).and_then(|h| h.to_str().ok()) else {
+        return (StatusCode::BAD_REQUEST, "Missing Host header").into_response();
+    };
+    let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    let location = match tls_port {
+        443 => format!("https://{}{}", strip_port(host), path),
+        port => format!("https://{}:{}{}", strip_port(host), port, path),
+    };
+    Redirect::permanent(&location).into_response()
+}
+
+/// `example.com:8000` → `example.com`, keeping bracketed IPv6 hosts intact
+fn strip_port(host: &str) -> &str {
+    match host.rfind(':') {
+        Some(i) if !host[i..].contains(']') => &host[..i],
+        _ => host,
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Polls the cert and key mtimes and swaps in the new pair when either changes.
+/// A failed reload keeps serving the previous certificate.
+pub fn spawn_cert_reloader(config: RustlsConfig, tls: TlsConfig, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut seen = (modified(&tls.cert), modified(&tls.key));
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let current = (modified(&tls.cert), modified(&tls.key));
+            if current == seen {
+                continue;
+            }
+            match server_config(&tls) {
+                Ok(server_config) => {
+                    config.reload_from_config(Arc::new(server_config));
+                    tracing::info!(cert = %tls.cert.display(), "TLS certificate reloaded");
+                    seen = current;
+                }
+                Err(err) => tracing::warn!(error = %err, "TLS certificate reload failed, keeping the previous one"),
+            }
+        }
+    })
+}
@@ -0,0 +1,92 @@
+//! How logical CPUs group into physical cores, packages (sockets) and NUMA
+//! nodes, for `cpu.cores` in `/api/stats`. Packages and nodes come from sysfs
+//! on Linux. The layout doesn't change while the machine runs, so it is read
+//! once when the source is created.
+
+use std::collections::HashSet;
+
+use crate::models::CPUCores;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Topology {
+    /// From sysinfo; `None` when the platform doesn't report it
+    pub physical_cores: Option<usize>,
+    pub sockets: Option<u32>,
+    pub numa_nodes: Option<u32>,
+}
+
+impl Topology {
+    /// Reads packages and NUMA nodes from sysfs; other platforms report neither
+    pub fn read(physical_cores: Option<usize>) -> Self {
+        Self {
+            physical_cores,
+            sockets: read_sockets(),
+            numa_nodes: read_numa_nodes(),
+        }
+    }
+
+    /// `cpu.cores` for `logical` logical CPUs. Without a physical count each
+    /// logical CPU is taken as a core of its own.
+    pub fn cores(&self, logical: usize) -> CPUCores {
+        let physical = self.physical_cores.filter(|&n| n > 0).unwrap_or(logical);
+        CPUCores {
+            physical,
+            logical,
+            sockets: self.sockets,
+            cores_per_socket: self.sockets.filter(|&s| s > 0).map(|s| physical as u32 / s),
+            threads_per_core: (logical / physical.max(1)).max(1) as u32,
+            numa_nodes: self.numa_nodes,
+        }
+    }
+}
+
+/// Distinct values among the CPUs' `topology/physical_package_id` files;
+/// `None` when there are none
+pub fn count_sockets<'a>(package_ids: impl IntoIterator<Item = &'a str>) -> Option<u32> {
+    let ids: HashSet<&str> = package_ids.into_iter().map(str::trim).filter(|id| !id.is_empty()).collect();
+    (!ids.is_empty()).then_some(ids.len() as u32)
+}
+
+/// `node0`, `node1`, ... among the entries of `/sys/devices/system/node`,
+/// which also holds files such as `online` and `possible`
+pub fn count_numa_nodes<'a>(entries: impl IntoIterator<Item = &'a str>) -> Option<u32> {
+    let count = entries
+        .into_iter()
+        .filter(|name| name.strip_prefix("node").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())))
+        .count();
+    (count > 0).then_some(count as u32)
+}
+
+/// Names of a directory's entries; empty when it can't be read
+#[cfg(target_os = "linux")]
+fn entry_names(dir: &str) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect())
+        .unwrap_or_default()
+}
+
+/// Offline CPUs have no `topology` directory and are skipped
+#[cfg(target_os = "linux")]
+fn read_sockets() -> Option<u32> {
+    let ids: Vec<String> = entry_names("/sys/devices/system/cpu")
+        .iter()
+        .filter(|name| name.strip_prefix("cpu").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())))
+        .filter_map(|name| std::fs::read_to_string(format!("/sys/devices/system/cpu/{}/topology/physical_package_id", name)).ok())
+        .collect();
+    count_sockets(ids.iter().map(String::as_str))
+}
+
+#[cfg(target_os = "linux")]
+fn read_numa_nodes() -> Option<u32> {
+    count_numa_nodes(entry_names("/sys/devices/system/node").iter().map(String::as_str))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_sockets() -> Option<u32> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_numa_nodes() -> Option<u32> {
+    None
+}
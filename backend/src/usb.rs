@@ -0,0 +1,257 @@
+//! Connected USB devices: `/sys/bus/usb/devices` on Linux, IOKit's
+//! `IOUSBHostDevice` services on macOS.
+//!
+//! Vendor, product and class names come from the system's `usb.ids`, or the
+//! bundled `data/usb.ids` when it has none, falling back to the strings the
+//! device itself reports.
+
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+use crate::hwids::{self, IdDatabase};
+use crate::models::UsbDevice;
+
+/// Where `hwdata` (Fedora, Arch), `usbutils` (Debian, Ubuntu) and
+/// `update-usbids` put it
+pub const SYSTEM_IDS: &[&str] = &["/usr/share/hwdata/usb.ids", "/usr/share/misc/usb.ids", "/var/lib/usbutils/usb.ids"];
+
+/// Snapshot of <http://www.linux-usb.org/usb.ids>
+const BUNDLED_IDS: &str = include_str!("../data/usb.ids");
+
+/// Read and parsed on first use
+pub fn ids() -> &'static IdDatabase {
+    static IDS: OnceLock<IdDatabase> = OnceLock::new();
+    IDS.get_or_init(|| hwids::load(SYSTEM_IDS, BUNDLED_IDS))
+}
+
+/// Name of a `bDeviceClass` code, or the code in hex when unlisted
+pub fn class_name(ids: &IdDatabase, class: u8) -> String {
+    ids.class(class).map(str::to_string).unwrap_or_else(|| format!("{:02x}", class))
+}
+
+/// Formats a sysfs `speed` (Mbit/s: `1.5`, `480`, `5000`) as `480 Mbps` or
+/// `5 Gbps`; `unknown` when empty or unparsable
+pub fn speed_label(mbps: &str) -> String {
+    match mbps.trim().parse::<f64>() {
+        Ok(mbps) if mbps >= 1000.0 => format!("{} Gbps", mbps / 1000.0),
+        Ok(mbps) => format!("{} Mbps", mbps),
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+/// Builds a [`UsbDevice`] from the files in `/sys/bus/usb/devices/<name>`;
+/// `read` returns a file's contents, or `None` when it is missing. `None`
+/// for entries without IDs. A device whose class is defined per interface
+/// (class 00) reports the class of its first interface.
+pub fn parse_sysfs_usb(name: &str, ids: &IdDatabase, read: impl Fn(&str) -> Option<String>) -> Option<UsbDevice> {
+    let value = |file: &str| read(file).map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let hex = |file: &str| value(file).and_then(|v| u16::from_str_radix(&v, 16).ok());
+    let decimal = |file: &str| value(file).and_then(|v| v.parse::<u8>().ok());
+
+    let vendor_id = hex("idVendor")?;
+    let product_id = hex("idProduct")?;
+    let mut class = hex("bDeviceClass").unwrap_or(0) as u8;
+    if class == 0 {
+        let config = value("bConfigurationValue").unwrap_or_else(|| "1".to_string());
+        let interface = format!("{}:{}.0/bInterfaceClass", name, config);
+        class = value(&interface).and_then(|v| u8::from_str_radix(&v, 16).ok()).unwrap_or(0);
+    }
+    Some(UsbDevice {
+        vendor_id,
+        product_id,
+        vendor_name: ids.vendor(vendor_id).map(str::to_string).or_else(|| value("manufacturer")),
+        product_name: ids.device(vendor_id, product_id).map(str::to_string).or_else(|| value("product")),
+        serial: value("serial"),
+        class: class_name(ids, class),
+        bus: decimal("busnum").unwrap_or(0),
+        device_number: decimal("devnum").unwrap_or(0),
+        speed: speed_label(&value("speed").unwrap_or_default()),
+    })
+}
+
+/// Every device on every bus, root hubs included, by bus then device number
+#[cfg(target_os = "linux")]
+pub fn list() -> Result<Vec<UsbDevice>, ApiError> {
+    let root = std::path::Path::new("/sys/bus/usb/devices");
+    let entries = match std::fs::read_dir(root) {
+        Ok(entries) => entries,
+        // No USB controller, or a container without it in /sys
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            tracing::warn!(error = %err, "cannot read /sys/bus/usb/devices");
+            return Err(ApiError::Internal);
+        }
+    };
+    let ids = ids();
+    let mut devices: Vec<UsbDevice> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // `1-1:1.0` and the like are interfaces of a device
+            if name.contains(':') {
+                return None;
+            }
+            let dir = root.join(&name);
+            parse_sysfs_usb(&name, ids, |file| std::fs::read_to_string(dir.join(file)).ok())
+        })
+        .collect();
+    devices.sort_by_key(|d| (d.bus, d.device_number));
+    Ok(devices)
+}
+
+#[cfg(target_os = "macos")]
+pub fn list() -> Result<Vec<UsbDevice>, ApiError> {
+    let mut devices = iokit::list(ids());
+    devices.sort_by_key(|d| (d.bus, d.device_number));
+    Ok(devices)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn list() -> Result<Vec<UsbDevice>, ApiError> {
+    Err(ApiError::NotImplemented { feature: "USB device listing" })
+}
+
+#[cfg(target_os = "macos")]
+mod iokit {
+    use std::ffi::{c_char, c_void, CStr};
+
+    use core_foundation_sys::base::{kCFAllocatorDefault, CFAllocatorRef, CFGetTypeID, CFIndex, CFRelease, CFTypeRef};
+    use core_foundation_sys::dictionary::CFMutableDictionaryRef;
+    use core_foundation_sys::number::{kCFNumberSInt64Type, CFNumberGetTypeID, CFNumberGetValue, CFNumberRef};
+    use core_foundation_sys::string::{
+        kCFStringEncodingUTF8, CFStringCreateWithCString, CFStringGetCString, CFStringGetTypeID, CFStringRef,
+    };
+
+    use super::{class_name, speed_label};
+    use crate::hwids::IdDatabase;
+    use crate::models::UsbDevice;
+
+    /// `io_object_t`, a Mach port name
+    type IoObject = u32;
+
+    /// `kIOMainPortDefault`
+    const MAIN_PORT_DEFAULT: u32 = 0;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> CFMutableDictionaryRef;
+        fn IOServiceGetMatchingServices(main_port: u32, matching: CFMutableDictionaryRef, existing: *mut IoObject) -> i32;
+        fn IOIteratorNext(iterator: IoObject) -> IoObject;
+        fn IORegistryEntryCreateCFProperty(entry: IoObject, key: CFStringRef, allocator: CFAllocatorRef, options: u32) -> CFTypeRef;
+        fn IOObjectRelease(object: IoObject) -> i32;
+    }
+
+    /// An IOKit object, released on drop
+    struct Object(IoObject);
+
+    impl Drop for Object {
+        fn drop(&mut self) {
+            // SAFETY: the object came from IOKit and is released once
+            unsafe { IOObjectRelease(self.0) };
+        }
+    }
+
+    /// A Core Foundation value, released on drop
+    struct Value(CFTypeRef);
+
+    impl Drop for Value {
+        fn drop(&mut self) {
+            // SAFETY: the value came from a Create call and is released once
+            unsafe { CFRelease(self.0) };
+        }
+    }
+
+    pub fn list(ids: &IdDatabase) -> Vec<UsbDevice> {
+        let mut iterator: IoObject = 0;
+        // SAFETY: IOServiceGetMatchingServices takes ownership of the
+        // dictionary IOServiceMatching returns, even when it fails
+        let status = unsafe {
+            IOServiceGetMatchingServices(MAIN_PORT_DEFAULT, IOServiceMatching(c"IOUSBHostDevice".as_ptr()), &mut iterator)
+        };
+        if status != 0 {
+            tracing::warn!(status, "cannot list IOUSBHostDevice services");
+            return Vec::new();
+        }
+        let iterator = Object(iterator);
+        let mut devices = Vec::new();
+        loop {
+            // SAFETY: the iterator is valid until dropped
+            let entry = unsafe { IOIteratorNext(iterator.0) };
+            if entry == 0 {
+                break;
+            }
+            devices.extend(device(&Object(entry), ids));
+        }
+        devices
+    }
+
+    fn device(entry: &Object, ids: &IdDatabase) -> Option<UsbDevice> {
+        let vendor_id = number(entry, c"idVendor")? as u16;
+        let product_id = number(entry, c"idProduct")? as u16;
+        // IOUSBHostFamily's speed codes, as sysfs reports them
+        let speed = match number(entry, c"Device Speed") {
+            Some(0) => "1.5",
+            Some(1) => "12",
+            Some(2) => "480",
+            Some(3) => "5000",
+            Some(4) => "10000",
+            Some(5) => "20000",
+            _ => "",
+        };
+        Some(UsbDevice {
+            vendor_id,
+            product_id,
+            vendor_name: ids.vendor(vendor_id).map(str::to_string).or_else(|| string(entry, c"USB Vendor Name")),
+            product_name: ids
+                .device(vendor_id, product_id)
+                .map(str::to_string)
+                .or_else(|| string(entry, c"USB Product Name")),
+            serial: string(entry, c"USB Serial Number"),
+            class: class_name(ids, number(entry, c"bDeviceClass").unwrap_or(0) as u8),
+            // The top byte of the location ID is the bus
+            bus: (number(entry, c"locationID").unwrap_or(0) >> 24) as u8,
+            device_number: number(entry, c"USB Address").unwrap_or(0) as u8,
+            speed: speed_label(speed),
+        })
+    }
+
+    fn property(entry: &Object, key: &CStr) -> Option<Value> {
+        // SAFETY: `key` is NUL-terminated; the CFString is released after the lookup
+        unsafe {
+            let key = CFStringCreateWithCString(kCFAllocatorDefault, key.as_ptr(), kCFStringEncodingUTF8);
+            if key.is_null() {
+                return None;
+            }
+            let value = IORegistryEntryCreateCFProperty(entry.0, key, kCFAllocatorDefault, 0);
+            CFRelease(key as CFTypeRef);
+            (!value.is_null()).then_some(Value(value))
+        }
+    }
+
+    fn number(entry: &Object, key: &CStr) -> Option<i64> {
+        let value = property(entry, key)?;
+        let mut number: i64 = 0;
+        // SAFETY: the type is checked before the value is read as a CFNumber
+        let ok = unsafe {
+            CFGetTypeID(value.0) == CFNumberGetTypeID()
+                && CFNumberGetValue(value.0 as CFNumberRef, kCFNumberSInt64Type, &mut number as *mut i64 as *mut c_void)
+        };
+        ok.then_some(number)
+    }
+
+    fn string(entry: &Object, key: &CStr) -> Option<String> {
+        let value = property(entry, key)?;
+        let mut buffer = [0 as c_char; 256];
+        // SAFETY: the type is checked first, and CFStringGetCString writes at
+        // most `buffer.len()` bytes including the NUL
+        unsafe {
+            if CFGetTypeID(value.0) != CFStringGetTypeID() {
+                return None;
+            }
+            if CFStringGetCString(value.0 as CFStringRef, buffer.as_mut_ptr(), buffer.len() as CFIndex, kCFStringEncodingUTF8) == 0 {
+                return None;
+            }
+            Some(CStr::from_ptr(buffer.as_ptr()).to_string_lossy().into_owned())
+        }
+    }
+}
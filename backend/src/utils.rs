@@ -0,0 +1,103 @@
+//! Formatting helpers shared by handlers
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    if bytes == 0 {
+        return "0 B".to_string();
+    }
+    
+    let size = bytes as f64;
+    let base = 1024_f64;
+    let i = (size.ln() / base.ln()).floor() as usize;
+    let i = i.min(UNITS.len() - 1);
+    
+    let value = size / base.powi(i as i32);
+    format!("{:.1} {}", value, UNITS[i])
+}
+
+/// A byte rate such as "1.5 MB/s"
+pub fn format_rate(bytes_per_second: f64) -> String {
+    format!("{}/s", format_bytes(bytes_per_second.max(0.0) as u64))
+}
+
+pub fn get_process_status(status: sysinfo::ProcessStatus) -> String {
+    match status {
+        sysinfo::ProcessStatus::Run => "running".to_string(),
+        sysinfo::ProcessStatus::Sleep => "sleeping".to_string(),
+        sysinfo::ProcessStatus::Stop => "stopped".to_string(),
+        sysinfo::ProcessStatus::Zombie => "zombie".to_string(),
+        sysinfo::ProcessStatus::Dead => "dead".to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// The POSIX signals whose numbers are the same on Linux and macOS
+pub fn signal_from_number(number: i32) -> Option<sysinfo::Signal> {
+    match number {
+        1 => Some(sysinfo::Signal::Hangup),
+        2 => Some(sysinfo::Signal::Interrupt),
+        3 => Some(sysinfo::Signal::Quit),
+        9 => Some(sysinfo::Signal::Kill),
+        15 => Some(sysinfo::Signal::Term),
+        _ => None,
+    }
+}
+
+/// Current time as Unix epoch seconds
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Unix epoch seconds as RFC 3339 UTC, e.g. "2023-11-14T22:13:20Z"
+pub fn format_rfc3339(epoch_seconds: u64) -> String {
+    let days = (epoch_seconds / 86_400) as i64;
+    let time = epoch_seconds % 86_400;
+    // Howard Hinnant's civil_from_days, with eras of 400 years starting 0000-03-01
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+/// A duration in its two largest units, e.g. "3h 12m", "2d 4h" or "45s"
+pub fn format_duration(seconds: u64) -> String {
+    let units = [
+        (seconds / 86_400, "d"),
+        (seconds % 86_400 / 3_600, "h"),
+        (seconds % 3_600 / 60, "m"),
+        (seconds % 60, "s"),
+    ];
+    let first = units.iter().position(|(value, _)| *value > 0).unwrap_or(units.len() - 1);
+    units[first..]
+        .iter()
+        .take(2)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `powershell.exe` ready to run one script passed as the next argument,
+/// without loading the user's profile
+#[cfg(windows)]
+pub fn powershell() -> tokio::process::Command {
+    let mut command = tokio::process::Command::new("powershell.exe");
+    command.args(["-NoProfile", "-NonInteractive", "-Command"]);
+    command
+}
@@ -0,0 +1,21 @@
+//! What this binary is: the crate version from `Cargo.toml` and the build
+//! details `build.rs` embedded. `/health` and `/api/version` both read it
+//! here, so they always agree.
+
+use crate::models::VersionInfo;
+use crate::utils::format_rfc3339;
+
+/// The crate version, e.g. "2.0.0"
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub fn info() -> VersionInfo {
+    let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+    VersionInfo {
+        version: VERSION.to_string(),
+        git_commit: non_empty(env!("TASKMGR_GIT_COMMIT")),
+        git_dirty: env!("TASKMGR_GIT_DIRTY").parse().ok(),
+        build_timestamp: env!("TASKMGR_BUILD_TIMESTAMP").parse().ok().map(format_rfc3339),
+        rustc_version: non_empty(env!("TASKMGR_RUSTC_VERSION")),
+        target: env!("TASKMGR_TARGET").to_string(),
+    }
+}
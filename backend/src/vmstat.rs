@@ -0,0 +1,50 @@
+//! System-wide page fault rate from `/proc/vmstat`.
+//!
+//! `pgmajfault` is the kernel's running total of major faults across every
+//! process, including ones that have since exited, so unlike a sum over the
+//! current process list it never goes backwards when a process ends.
+
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// The `pgmajfault` line: major faults since boot
+pub fn parse_major_faults(text: &str) -> Option<u64> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("pgmajfault ")?.trim().parse().ok())
+}
+
+/// The last reading, for turning the next one into a rate
+#[derive(Default)]
+pub struct FaultSampler {
+    previous: Mutex<Option<(Instant, u64)>>,
+}
+
+impl FaultSampler {
+    /// Major faults per second since the previous call; 0 on the first call
+    /// and where there is no `/proc/vmstat`
+    pub fn sample(&self) -> u64 {
+        let Some(current) = read() else {
+            return 0;
+        };
+        let now = Instant::now();
+        let mut previous = self.previous.lock().expect("fault sampler mutex poisoned");
+        let rate = previous.map_or(0, |(taken, faults)| {
+            let seconds = now.duration_since(taken).as_secs_f64().max(f64::EPSILON);
+            (current.saturating_sub(faults) as f64 / seconds).round() as u64
+        });
+        *previous = Some((now, current));
+        rate
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read() -> Option<u64> {
+    std::fs::read_to_string("/proc/vmstat").ok().as_deref().and_then(parse_major_faults)
+}
+
+/// macOS counts pageins in `vm_stat` but not per fault type; Windows has no
+/// major/minor split
+#[cfg(not(target_os = "linux"))]
+fn read() -> Option<u64> {
+    None
+}
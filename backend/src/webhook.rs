@@ -0,0 +1,259 @@
+//! POSTs alert transitions to webhooks.
+//!
+//! [`crate::alerts::AlertEngine`] queues an [`AlertEvent`] whenever an alert
+//! with a webhook triggers or resolves; [`Webhooks::deliver`] runs as its own
+//! task and sends them in order, so a slow endpoint only delays other
+//! notifications, never the poller.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::alerts::{AlertMetric, Comparison};
+use crate::models::{Alert, WebhookStatus};
+use crate::utils::{format_duration, format_rfc3339, unix_now};
+
+/// Attempts after the first before a notification is given up
+pub const WEBHOOK_RETRIES: u32 = 3;
+
+/// Wait before the first retry; doubled before each further one
+pub const WEBHOOK_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Per attempt, connecting included
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// The full event as a JSON object
+    #[default]
+    Json,
+    /// `{"text": ...}`, as Slack incoming webhooks (and Mattermost, Discord's
+    /// `/slack` endpoint) expect
+    Slack,
+}
+
+/// Where notifications go: `[alert_webhook]`, or `webhook = {...}` in a rule
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookTarget {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+}
+
+impl WebhookTarget {
+    pub fn validate(&self) -> Result<(), String> {
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => Ok(()),
+            _ => Err(format!("webhook url '{}' is not an http(s) URL", self.redacted())),
+        }
+    }
+
+    /// Scheme and host only: webhook paths usually embed a secret
+    pub fn redacted(&self) -> String {
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) => {
+                let path = if url.path().len() > 1 { "/…" } else { "" };
+                format!("{}://{}{}", url.scheme(), url.host_str().unwrap_or(""), path)
+            }
+            Err(_) => "(invalid)".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertTransition {
+    Triggered,
+    Resolved,
+}
+
+/// One alert transition bound for `target`
+#[derive(Clone, Debug)]
+pub struct AlertEvent {
+    pub transition: AlertTransition,
+    pub alert: Alert,
+    pub metric: AlertMetric,
+    pub operator: Comparison,
+    pub threshold: f64,
+    pub process: Option<String>,
+    pub target: WebhookTarget,
+}
+
+/// The request body for `event.target`'s format
+pub fn payload(event: &AlertEvent, hostname: &str) -> Value {
+    let alert = &event.alert;
+    match event.target.format {
+        WebhookFormat::Json => json!({
+            "event": event.transition,
+            "alert_id": alert.id,
+            "rule": alert.rule,
+            "metric": event.metric,
+            "operator": event.operator,
+            "threshold": event.threshold,
+            "value": alert.current_value,
+            "process": event.process,
+            "hostname": hostname,
+            "triggered_at": alert.triggered_at,
+            "resolved_at": alert.resolved_at,
+        }),
+        WebhookFormat::Slack => {
+            let subject = match &event.process {
+                Some(process) => format!("{} of {}", metric_name(event.metric), process),
+                None => metric_name(event.metric).to_string(),
+            };
+            let text = match alert.resolved_at {
+                None => format!(
+                    ":rotating_light: *{}* on `{}`: {} is {:.1}% ({} {}) since {}",
+                    alert.rule,
+                    hostname,
+                    subject,
+                    alert.current_value,
+                    event.operator.symbol(),
+                    event.threshold,
+                    format_rfc3339(alert.triggered_at),
+                ),
+                Some(resolved_at) => format!(
+                    ":white_check_mark: *{}* on `{}` resolved after {}: {} was {:.1}% at last reading",
+                    alert.rule,
+                    hostname,
+                    format_duration(resolved_at.saturating_sub(alert.triggered_at)),
+                    subject,
+                    alert.current_value,
+                ),
+            };
+            json!({ "text": text })
+        }
+    }
+}
+
+fn metric_name(metric: AlertMetric) -> &'static str {
+    match metric {
+        AlertMetric::CpuPercent => "CPU",
+        AlertMetric::MemoryPercent => "memory",
+        AlertMetric::DiskPercent => "disk usage",
+    }
+}
+
+/// Shared handle to the HTTP client and per-target delivery results; cheap to clone
+#[derive(Clone)]
+pub struct Webhooks {
+    client: reqwest::Client,
+    backoff: Duration,
+    /// Keyed by full URL, in config order
+    statuses: Arc<Mutex<Vec<(String, WebhookStatus)>>>,
+}
+
+impl Webhooks {
+    /// `targets` are listed by [`Self::statuses`] before anything is sent;
+    /// others, from rules added at runtime, join the list on first use
+    pub fn new<'a>(targets: impl IntoIterator<Item = &'a WebhookTarget>, backoff: Duration) -> Self {
+        let mut statuses: Vec<(String, WebhookStatus)> = Vec::new();
+        for target in targets {
+            if !statuses.iter().any(|(url, _)| *url == target.url) {
+                statuses.push((target.url.clone(), WebhookStatus::new(target.redacted())));
+            }
+        }
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .user_agent(concat!("task-manager-pro/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("the TLS backend is compiled in");
+        Self {
+            client,
+            backoff,
+            statuses: Arc::new(Mutex::new(statuses)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statuses.lock().expect("webhook mutex poisoned").is_empty()
+    }
+
+    /// Delivery results per target, in config order
+    pub fn statuses(&self) -> Vec<WebhookStatus> {
+        self.statuses.lock().expect("webhook mutex poisoned").iter().map(|(_, status)| status.clone()).collect()
+    }
+
+    /// Sends queued events in order until shutdown, dropping what's left
+    pub async fn deliver(self, mut events: mpsc::UnboundedReceiver<AlertEvent>, hostname: String, shutdown: CancellationToken) {
+        loop {
+            let event = tokio::select! {
+                Some(event) = events.recv() => event,
+                _ = shutdown.cancelled() => break,
+            };
+            tokio::select! {
+                _ = self.send(&event, &hostname) => {}
+                _ = shutdown.cancelled() => break,
+            }
+        }
+    }
+
+    /// POSTs one event, retrying network errors, timeouts, 429 and 5xx with
+    /// exponential backoff. Returns whether it was accepted.
+    pub async fn send(&self, event: &AlertEvent, hostname: &str) -> bool {
+        let body = payload(event, hostname);
+        let mut attempt = 0;
+        let (status, error) = loop {
+            let (status, error) = match self.client.post(&event.target.url).json(&body).send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    let error = (!status.is_success()).then(|| format!("HTTP {}", status.as_u16()));
+                    (Some(status), error)
+                }
+                // The error would quote the URL and leak its secret into the status
+                Err(err) => (None, Some(err.without_url().to_string())),
+            };
+            let retryable = match status {
+                Some(status) => status.is_server_error() || status.as_u16() == 429 || status.as_u16() == 408,
+                None => true,
+            };
+            if error.is_none() || !retryable || attempt == WEBHOOK_RETRIES {
+                break (status.map(|s| s.as_u16()), error);
+            }
+            tokio::time::sleep(self.backoff * 2u32.pow(attempt)).await;
+            attempt += 1;
+        };
+
+        let delivered = error.is_none();
+        if let Some(error) = &error {
+            tracing::warn!(target = %event.target.redacted(), rule = %event.alert.rule, error = %error, "alert webhook failed");
+        }
+        let mut statuses = self.statuses.lock().expect("webhook mutex poisoned");
+        let index = match statuses.iter().position(|(url, _)| *url == event.target.url) {
+            Some(index) => index,
+            None => {
+                statuses.push((event.target.url.clone(), WebhookStatus::new(event.target.redacted())));
+                statuses.len() - 1
+            }
+        };
+        let entry = &mut statuses[index].1;
+        entry.last_attempt_at = Some(unix_now());
+        entry.last_status_code = status;
+        if delivered {
+            entry.delivered += 1;
+            entry.last_error = None;
+        } else {
+            entry.failures += 1;
+            entry.last_error = error;
+        }
+        delivered
+    }
+}
+
+impl WebhookStatus {
+    fn new(target: String) -> Self {
+        Self {
+            target,
+            delivered: 0,
+            failures: 0,
+            last_status_code: None,
+            last_error: None,
+            last_attempt_at: None,
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Wireless link details for [`crate::models::NetworkInterfaceStats`]: SSID,
+//! signal, channel frequency and bitrate.
+//!
+//! Linux asks the kernel over nl80211 (generic netlink); macOS runs
+//! `airport -I`. Interfaces that aren't wireless get no entry.
+
+use std::collections::HashMap;
+
+use crate::models::WifiInfo;
+
+/// A disconnected interface: known to be wireless, but not associated
+pub fn disconnected() -> WifiInfo {
+    WifiInfo {
+        ssid: String::new(),
+        signal_dbm: 0,
+        frequency_mhz: 0,
+        bitrate_mbps: 0.0,
+        is_connected: false,
+    }
+}
+
+/// Centre frequency of an 802.11 channel in the 2.4 GHz or 5 GHz band
+pub fn channel_frequency(channel: u32) -> Option<u32> {
+    match channel {
+        1..=13 => Some(2407 + 5 * channel),
+        14 => Some(2484),
+        32..=177 => Some(5000 + 5 * channel),
+        _ => None,
+    }
+}
+
+/// Parses `airport -I`, one `key: value` pair per line
+pub fn parse_airport(text: &str) -> Option<WifiInfo> {
+    let fields: HashMap<&str, &str> = text
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect();
+    // "AirPort: Off" when the radio is off
+    if fields.get("AirPort") == Some(&"Off") {
+        return Some(disconnected());
+    }
+    fields.get("state")?;
+    let number = |key: &str| fields.get(key).and_then(|v| v.parse::<i64>().ok());
+    let ssid = fields.get("SSID").copied().unwrap_or_default().to_string();
+    let is_connected = fields.get("state") == Some(&"running") && !ssid.is_empty();
+    if !is_connected {
+        return Some(disconnected());
+    }
+    Some(WifiInfo {
+        ssid,
+        signal_dbm: number("agrCtlRSSI").unwrap_or(0) as i32,
+        // "149,80": the channel, then the width
+        frequency_mhz: fields
+            .get("channel")
+            .and_then(|v| v.split(',').next())
+            .and_then(|c| c.trim().parse().ok())
+            .and_then(channel_frequency)
+            .unwrap_or(0),
+        bitrate_mbps: number("lastTxRate").unwrap_or(0) as f32,
+        is_connected,
+    })
+}
+
+/// Wireless interfaces by name; empty where none can be read
+#[cfg(target_os = "linux")]
+pub fn read() -> HashMap<String, WifiInfo> {
+    // Spares machines without WiFi, most servers, a netlink round trip per poll
+    let any_wireless = std::fs::read_dir("/sys/class/net")
+        .map(|dir| dir.flatten().any(|entry| entry.path().join("wireless").exists()))
+        .unwrap_or(false);
+    if !any_wireless {
+        return HashMap::new();
+    }
+    nl80211::read()
+}
+
+#[cfg(target_os = "macos")]
+pub fn read() -> HashMap<String, WifiInfo> {
+    macos::read()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn read() -> HashMap<String, WifiInfo> {
+    HashMap::new()
+}
+
+#[cfg(target_os = "linux")]
+mod nl80211 {
+    use std::collections::HashMap;
+    use std::sync::OnceLock;
+
+    use neli::consts::nl::{GenlId, NlmF};
+    use neli::consts::socket::NlFamily;
+    use neli::genl::{AttrTypeBuilder, Genlmsghdr, GenlmsghdrBuilder, NlattrBuilder, NoUserHeader};
+    use neli::nl::{NlPayload, Nlmsghdr};
+    use neli::router::synchronous::NlRouter;
+    use neli::types::GenlBuffer;
+    use neli::utils::Groups;
+
+    use super::disconnected;
+    use crate::models::WifiInfo;
+
+    /// The commands and attributes used here, from `<linux/nl80211.h>`
+    #[neli::neli_enum(serialized_type = "u8")]
+    pub enum Command {
+        GetInterface = 5,
+        GetStation = 17,
+    }
+    impl neli::consts::genl::Cmd for Command {}
+
+    #[neli::neli_enum(serialized_type = "u16")]
+    pub enum Attr {
+        Ifindex = 3,
+        Ifname = 4,
+        Iftype = 5,
+        StaInfo = 21,
+        WiphyFreq = 38,
+        Ssid = 52,
+    }
+    impl neli::consts::genl::NlAttrType for Attr {}
+
+    /// Nested in [`Attr::StaInfo`]
+    #[neli::neli_enum(serialized_type = "u16")]
+    pub enum StaInfo {
+        /// u8 holding a signed dBm value
+        Signal = 7,
+        /// Nested [`RateInfo`]
+        TxBitrate = 8,
+    }
+    impl neli::consts::genl::NlAttrType for StaInfo {}
+
+    /// Nested in [`StaInfo::TxBitrate`]; both in units of 100 kbit/s
+    #[neli::neli_enum(serialized_type = "u16")]
+    pub enum RateInfo {
+        Bitrate = 1,
+        Bitrate32 = 5,
+    }
+    impl neli::consts::genl::NlAttrType for RateInfo {}
+
+    /// `NL80211_IFTYPE_STATION`: a client of an access point
+    const IFTYPE_STATION: u32 = 2;
+
+    type Message = Nlmsghdr<GenlId, Genlmsghdr<Command, Attr>>;
+
+    /// Connected once; the router keeps a receive thread for its lifetime.
+    /// `None` when the nl80211 family is missing (no wireless driver loaded).
+    fn router() -> Option<&'static (NlRouter, u16)> {
+        static ROUTER: OnceLock<Option<(NlRouter, u16)>> = OnceLock::new();
+        ROUTER
+            .get_or_init(|| {
+                let (router, _) = NlRouter::connect(NlFamily::Generic, Some(0), Groups::empty())
+                    .map_err(|err| tracing::debug!(error = %err, "cannot open generic netlink socket"))
+                    .ok()?;
+                let family = router
+                    .resolve_genl_family("nl80211")
+                    .map_err(|err| tracing::debug!(error = %err, "nl80211 unavailable"))
+                    .ok()?;
+                Some((router, family))
+            })
+            .as_ref()
+    }
+
+    /// Sends a dump request and collects the replies carrying a payload
+    fn dump(command: Command, attrs: GenlBuffer<Attr, neli::types::Buffer>) -> Vec<Message> {
+        let Some((router, family)) = router() else {
+            return Vec::new();
+        };
+        let request = GenlmsghdrBuilder::<Command, Attr, NoUserHeader>::default()
+            .cmd(command)
+            .version(1)
+            .attrs(attrs)
+            .build();
+        let Ok(request) = request else {
+            return Vec::new();
+        };
+        let replies = match router.send(*family, NlmF::DUMP | NlmF::ACK, NlPayload::Payload(request)) {
+            Ok(replies) => replies,
+            Err(err) => {
+                tracing::debug!(error = %err, ?command, "nl80211 request failed");
+                return Vec::new();
+            }
+        };
+        replies
+            .filter_map(|reply: Result<Message, _>| reply.ok())
+            .filter(|reply| reply.get_payload().is_some())
+            .collect()
+    }
+
+    pub fn read() -> HashMap<String, WifiInfo> {
+        let mut found = HashMap::new();
+        for reply in dump(Command::GetInterface, GenlBuffer::new()) {
+            let Some(payload) = reply.get_payload() else { continue };
+            let attrs = payload.attrs().get_attr_handle();
+            if attrs.get_attr_payload_as::<u32>(Attr::Iftype).ok() != Some(IFTYPE_STATION) {
+                continue;
+            }
+            let (Ok(name), Ok(ifindex)) = (
+                attrs.get_attr_payload_as_with_len::<String>(Attr::Ifname),
+                attrs.get_attr_payload_as::<u32>(Attr::Ifindex),
+            ) else {
+                continue;
+            };
+            // The kernel includes the SSID only while associated
+            let ssid = attrs.get_attr_payload_as_with_len_borrowed::<&[u8]>(Attr::Ssid).ok();
+            let info = match ssid {
+                Some(ssid) => {
+                    let (signal_dbm, bitrate_mbps) = station(ifindex).unwrap_or((0, 0.0));
+                    WifiInfo {
+                        ssid: String::from_utf8_lossy(ssid).into_owned(),
+                        signal_dbm,
+                        frequency_mhz: attrs.get_attr_payload_as::<u32>(Attr::WiphyFreq).unwrap_or(0),
+                        bitrate_mbps,
+                        is_connected: true,
+                    }
+                }
+                None => disconnected(),
+            };
+            found.insert(name, info);
+        }
+        found
+    }
+
+    /// Signal and transmit bitrate of the access point `ifindex` is associated with
+    fn station(ifindex: u32) -> Option<(i32, f32)> {
+        let ifindex_attr = NlattrBuilder::default()
+            .nla_type(AttrTypeBuilder::default().nla_type(Attr::Ifindex).build().ok()?)
+            .nla_payload(ifindex)
+            .build()
+            .ok()?;
+        let replies = dump(Command::GetStation, std::iter::once(ifindex_attr).collect());
+        let payload = replies.first()?.get_payload()?;
+        let attrs = payload.attrs().get_attr_handle();
+        let info = attrs.get_nested_attributes::<StaInfo>(Attr::StaInfo).ok()?;
+        let signal = info.get_attr_payload_as::<u8>(StaInfo::Signal).map(|s| s as i8 as i32).unwrap_or(0);
+        let bitrate = info
+            .get_nested_attributes::<RateInfo>(StaInfo::TxBitrate)
+            .ok()
+            .and_then(|rate| {
+                rate.get_attr_payload_as::<u32>(RateInfo::Bitrate32)
+                    .ok()
+                    .or_else(|| rate.get_attr_payload_as::<u16>(RateInfo::Bitrate).ok().map(u32::from))
+            })
+            .map(|units| units as f32 / 10.0)
+            .unwrap_or(0.0);
+        Some((signal, bitrate))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::collections::HashMap;
+    use std::process::Command;
+    use std::sync::{Mutex, OnceLock};
+    use std::time::{Duration, Instant};
+
+    use super::parse_airport;
+    use crate::models::WifiInfo;
+
+    const AIRPORT: &str = "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport";
+
+    /// `airport` takes tens of milliseconds, so readings are reused this long
+    const CACHE_FOR: Duration = Duration::from_secs(5);
+
+    pub fn read() -> HashMap<String, WifiInfo> {
+        static CACHE: Mutex<Option<(Instant, HashMap<String, WifiInfo>)>> = Mutex::new(None);
+        let mut cache = CACHE.lock().expect("wifi cache poisoned");
+        if let Some((_, found)) = cache.as_ref().filter(|(at, _)| at.elapsed() < CACHE_FOR) {
+            return found.clone();
+        }
+        let found: HashMap<String, WifiInfo> = device()
+            .zip(airport())
+            .map(|(device, info)| (device.to_string(), info))
+            .into_iter()
+            .collect();
+        *cache = Some((Instant::now(), found.clone()));
+        found
+    }
+
+    /// The Wi-Fi port's interface, usually en0; looked up once
+    fn device() -> Option<&'static str> {
+        static DEVICE: OnceLock<Option<String>> = OnceLock::new();
+        DEVICE
+            .get_or_init(|| {
+                let output = Command::new("networksetup").arg("-listallhardwareports").output().ok()?;
+                let text = String::from_utf8_lossy(&output.stdout);
+                let mut lines = text.lines().skip_while(|line| !matches!(line.trim(), "Hardware Port: Wi-Fi" | "Hardware Port: AirPort"));
+                lines.find_map(|line| line.trim().strip_prefix("Device:")).map(|device| device.trim().to_string())
+            })
+            .as_deref()
+    }
+
+    /// `None` when the tool is missing: macOS 14.4 removed it
+    fn airport() -> Option<WifiInfo> {
+        let output = Command::new(AIRPORT).arg("-I").output().ok()?;
+        parse_airport(&String::from_utf8_lossy(&output.stdout))
+    }
+}
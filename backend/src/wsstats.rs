@@ -0,0 +1,101 @@
+//! `/api/ws/stats/binary`: the poller's [`SystemStats`] pushed over a
+//! WebSocket as MessagePack binary frames, at a rate the client sets with a
+//! JSON text message such as `{"interval_ms": 100}`.
+//!
+//! A frame is only sent when the poller has published a new snapshot since
+//! the previous one, so a rate faster than the poll interval lowers latency
+//! without resending identical stats.
+
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{Interval, MissedTickBehavior};
+
+use crate::error::ApiError;
+use crate::models::SystemStats;
+use crate::AppState;
+
+/// Fastest push rate a client may ask for
+pub const MIN_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Slowest push rate a client may ask for
+pub const MAX_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Text message a client sends to change the push rate
+#[derive(Deserialize)]
+struct Control {
+    interval_ms: u64,
+}
+
+/// Serves one connection until the client leaves or shutdown begins. Until
+/// the client sends a control message, stats are pushed at the poll interval.
+pub async fn push_stats(mut socket: WebSocket, state: AppState) {
+    let mut ticker = ticker(state.config.poll_interval.clamp(MIN_INTERVAL, MAX_INTERVAL));
+    let mut sent: Option<Arc<SystemStats>> = None;
+    tracing::debug!("binary stats stream opened");
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let stats = state.stats.load_full();
+                if sent.as_ref().is_some_and(|sent| Arc::ptr_eq(sent, &stats)) {
+                    continue;
+                }
+                if socket.send(Message::Binary(encode_frame(&stats))).await.is_err() {
+                    break;
+                }
+                sent = Some(stats);
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match parse_control(&text) {
+                    Ok(interval) => ticker = self::ticker(interval),
+                    Err(err) => {
+                        if socket.send(Message::Text(error_frame(&err))).await.is_err() {
+                            break;
+                        }
+                    }
+                },
+                // axum answers pings itself
+                Some(Ok(Message::Binary(_) | Message::Ping(_) | Message::Pong(_))) => {}
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            _ = state.shutdown.cancelled() => {
+                let close = CloseFrame { code: close_code::AWAY, reason: "server shutting down".into() };
+                let _ = socket.send(Message::Close(Some(close))).await;
+                break;
+            }
+        }
+    }
+    tracing::debug!("binary stats stream closed");
+}
+
+/// `stats` as one binary frame, with the same field names as `/api/stats`
+pub fn encode_frame(stats: &SystemStats) -> Vec<u8> {
+    rmp_serde::to_vec_named(stats).expect("SystemStats serializes to MessagePack")
+}
+
+fn ticker(interval: Duration) -> Interval {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+fn parse_control(text: &str) -> Result<Duration, ApiError> {
+    let control: Control = serde_json::from_str(text).map_err(|err| ApiError::InvalidBody { reason: err.to_string() })?;
+    let interval = Duration::from_millis(control.interval_ms);
+    if !(MIN_INTERVAL..=MAX_INTERVAL).contains(&interval) {
+        return Err(ApiError::InvalidBody {
+            reason: format!(
+                "interval_ms must be between {} and {}",
+                MIN_INTERVAL.as_millis(),
+                MAX_INTERVAL.as_millis()
+            ),
+        });
+    }
+    Ok(interval)
+}
+
+/// The usual error envelope, as a text frame; the stream carries on
+fn error_frame(err: &ApiError) -> String {
+    serde_json::json!({ "error": { "code": err.code(), "message": err.to_string() } }).to_string()
+}
@@ -18,6 +18,7 @@ import type {
   TabType,
   ProcessListResponse,
   AppsListResponse,
+  AuthToken,
 } from "./types";
 
 // Register GSAP plugins
@@ -30,6 +31,16 @@ const API_URL = "http://localhost:8000"; // Rust backend only
 axios.defaults.timeout = 5000; // 5 second timeout
 axios.defaults.headers.common["Connection"] = "close"; // Prevent keep-alive issues
 
+// Mutating routes need the backend's bearer token; it hands it out to localhost only
+axios
+  .get<AuthToken>(`${API_URL}/api/v1/auth/token`)
+  .then(({ data }) => {
+    if (data.token) {
+      axios.defaults.headers.common["Authorization"] = `Bearer ${data.token}`;
+    }
+  })
+  .catch((error) => console.error("Error fetching API token:", error));
+
 function App() {
   const [currentTab, setCurrentTab] = useState<TabType>("performance");
   const [systemStats, setSystemStats] = useState<SystemStats | null>(null);
@@ -62,7 +73,7 @@ function App() {
     try {
       console.log("Fetching stats from Rust backend...");
       const response = await axios.get<SystemStats>(
-        `${API_URL}/api/stats`
+        `${API_URL}/api/v1/stats`
       );
       console.log("Stats received:", response.data);
       setSystemStats(response.data);
@@ -76,7 +87,7 @@ function App() {
     try {
       console.log("Fetching processes from Rust backend...");
       const response = await axios.get<ProcessListResponse>(
-        `${API_URL}/api/processes`
+        `${API_URL}/api/v1/processes`
       );
       console.log(
         "Processes received:",
@@ -96,7 +107,7 @@ function App() {
     try {
       console.log("Fetching apps from Rust backend...");
       const response = await axios.get<AppsListResponse>(
-        `${API_URL}/api/apps`
+        `${API_URL}/api/v1/apps`
       );
       console.log("Apps received:", response.data.total_count, "apps");
       setApps(response.data.apps);
@@ -108,7 +119,7 @@ function App() {
   // Kill an app (all its processes) - use Rust backend
   const killApp = async (pids: number[]): Promise<void> => {
     try {
-      await axios.post(`${API_URL}/api/app/close`, pids);
+      await axios.post(`${API_URL}/api/v1/app/close`, pids);
       // Refresh both apps and processes
       await Promise.all([fetchApps(), fetchProcesses()]);
     } catch (error) {
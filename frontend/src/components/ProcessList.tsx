@@ -90,6 +90,15 @@ export default function ProcessList({
   };
 
   const handleKillProcess = (proc: Process) => {
+    if (proc.is_self) {
+      toast.error(
+        `Cannot terminate "${proc.name}". The dashboard backend depends on it.`,
+        {
+          duration: 4000,
+        }
+      );
+      return;
+    }
     if (proc.is_protected) {
       toast.error(
         `Cannot terminate "${proc.name}". This is a protected system process.`,
@@ -111,7 +120,7 @@ export default function ProcessList({
     try {
       // Use Rust backend for killing processes
       await axios.post(
-        `${RUST_API_URL}/api/process/${selectedProcess.pid}/kill`
+        `${RUST_API_URL}/api/v1/process/${selectedProcess.pid}/kill`
       );
       toast.success(`Process ${selectedProcess.name} ended successfully`, {
         id: loadingToast,
@@ -142,7 +151,7 @@ export default function ProcessList({
     try {
       // Use Rust backend for process info
       const response = await axios.get(
-        `${RUST_API_URL}/api/process/${proc.pid}/info`
+        `${RUST_API_URL}/api/v1/process/${proc.pid}/info`
       );
       setProcessInfo(response.data);
     } catch (error: any) {
@@ -343,9 +352,9 @@ export default function ProcessList({
                         </button>
                         <button
                           onClick={() => handleKillProcess(proc)}
-                          disabled={proc.is_protected}
+                          disabled={proc.is_protected || proc.is_self}
                           className={`p-2 rounded-lg transition-all duration-200 group/btn border border-transparent ${
-                            proc.is_protected
+                            proc.is_protected || proc.is_self
                               ? "opacity-30 cursor-not-allowed"
                               : "hover:bg-red-500/20 hover:border-red-500/30"
                           }`}
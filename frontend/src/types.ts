@@ -5,12 +5,35 @@ export interface SystemStats {
   alerts?: Alert[];
   cpu: CPUStats;
   memory: MemoryStats;
+  /** Linux PSI; absent elsewhere */
+  memory_pressure?: MemoryPressure;
   disk: DiskStats;
   network: NetworkStats;
   system: SystemInfo;
+  /** Rust backend only; zeros outside Linux */
+  kernel?: KernelStats;
   gpu?: GPUStats;
 }
 
+export interface PressureStall {
+  avg10: number;
+  avg60: number;
+  avg300: number;
+  total_us: number;
+}
+
+export interface MemoryPressure {
+  some: PressureStall;
+  full?: PressureStall;
+}
+
+export interface KernelStats {
+  context_switches_per_sec: number;
+  forks_per_sec: number;
+  processes_running: number;
+  processes_blocked: number;
+}
+
 export interface Alert {
   type: "cpu" | "memory" | "disk";
   severity: "warning" | "critical";
@@ -18,11 +41,32 @@ export interface Alert {
   value: number;
 }
 
+export interface CpuCacheInfo {
+  l1d_kb: number | null;
+  l1i_kb: number | null;
+  l2_kb: number | null;
+  l3_kb: number | null;
+}
+
+export interface CpuInfo {
+  brand: string;
+  vendor: string;
+  frequency_mhz: number;
+  physical_cores: number | null;
+  logical_cores: number;
+  cache: CpuCacheInfo;
+}
+
 export interface CPUStats {
   percent: number;
   cores: {
     physical: number;
     logical: number;
+    /** Rust backend only; null where the OS doesn't say */
+    sockets?: number | null;
+    cores_per_socket?: number | null;
+    threads_per_core?: number;
+    numa_nodes?: number | null;
   };
   per_core: number[];
 }
@@ -34,6 +78,8 @@ export interface MemoryStats {
   percent: number;
   total_formatted: string;
   used_formatted: string;
+  /** Page faults per second that needed disk I/O; 0 off Linux */
+  system_major_faults_per_sec: number;
 }
 
 export interface DiskStats {
@@ -43,6 +89,23 @@ export interface DiskStats {
   percent: number;
   total_formatted: string;
   used_formatted: string;
+  /** Per-disk breakdown; only the Rust backend reports it */
+  disks?: DiskInfo[];
+}
+
+export interface DiskInfo {
+  name: string;
+  mount_point: string;
+  file_system: string;
+  total: number;
+  available: number;
+  disk_type: "SSD" | "HDD" | "NVMe" | "Unknown";
+  is_removable: boolean;
+  /** I/O queue settings; Rust backend on Linux only */
+  scheduler?: string | null;
+  nr_requests?: number | null;
+  queue_depth?: number | null;
+  read_ahead_kb?: number | null;
 }
 
 export interface NetworkStats {
@@ -50,6 +113,30 @@ export interface NetworkStats {
   bytes_recv: number;
   bytes_sent_formatted: string;
   bytes_recv_formatted: string;
+  /** Per-interface breakdown; only the Rust backend reports it */
+  interfaces?: NetworkInterfaceStats[];
+}
+
+export interface NetworkInterfaceStats {
+  name: string;
+  bytes_sent: number;
+  bytes_recv: number;
+  is_up: boolean;
+  speed_mbps: number | null;
+  duplex: "full" | "half" | null;
+  mac_address: string | null;
+  ip_addresses: string[];
+  mtu: number | null;
+  /** null for wired interfaces and outside Linux and macOS */
+  wifi: WifiInfo | null;
+}
+
+export interface WifiInfo {
+  ssid: string;
+  signal_dbm: number;
+  frequency_mhz: number;
+  bitrate_mbps: number;
+  is_connected: boolean;
 }
 
 export interface GPUStats {
@@ -89,10 +176,36 @@ export interface Process {
   status: string;
   num_threads: number;
   create_time: number;
+  /** RFC 3339 UTC start time; null when the OS reports none. Rust backend only */
+  create_time_iso?: string | null;
+  /** Seconds since start, e.g. 11520, and the same as "3h 12m" */
+  running_seconds?: number | null;
+  running_formatted?: string | null;
   exe?: string;
   cwd?: string;
   cmdline?: string[];
   is_protected: boolean;
+  /** The backend itself or its parent; only the Rust backend reports it */
+  is_self?: boolean;
+  /** Linux only; null elsewhere */
+  session_id?: number | null;
+  process_group_id?: number | null;
+  /** Controlling terminal such as "pts/0"; null for daemons */
+  tty?: string | null;
+  /** 64-hex-digit container ID; null on the host. Linux, Rust backend only */
+  container_id?: string | null;
+  /** Only the Rust backend reports it; null for root processes */
+  parent_pid?: number | null;
+  /** User + system CPU seconds since start; Linux only */
+  cpu_time_seconds?: number | null;
+  /** Disk bytes per second; only the Rust backend reports them */
+  disk_read_rate?: number;
+  disk_write_rate?: number;
+  /** OOM killer badness and its -1000..1000 adjustment; Linux, Rust backend only */
+  oom_score?: number | null;
+  oom_score_adj?: number | null;
+  /** Hops from the root in /api/process/:pid/children?recursive=true */
+  depth?: number;
 }
 
 export interface ProcessListResponse {
@@ -110,6 +223,16 @@ export interface AppGroup {
   process_count: number;
   exe: string;
   is_closeable: boolean;
+  /** Summed disk bytes per second; only the Rust backend reports them */
+  disk_read_rate?: number;
+  disk_write_rate?: number;
+  disk_read_rate_formatted?: string;
+  disk_write_rate_formatted?: string;
+  /** null where the OS has no per-process network counters */
+  net_recv_rate?: number | null;
+  net_sent_rate?: number | null;
+  net_recv_rate_formatted?: string | null;
+  net_sent_rate_formatted?: string | null;
 }
 
 export interface AppsListResponse {
@@ -117,8 +240,212 @@ export interface AppsListResponse {
   total_count: number;
 }
 
+export interface ContainerGroup {
+  container_id: string;
+  /** Docker's name when connected, else the lowest PID's process name */
+  name: string;
+  image: string | null;
+  state: string | null;
+  started_at: string | null;
+  pids: number[];
+  cpu_percent: number;
+  memory_mb: number;
+  memory_percent: number;
+  process_count: number;
+}
+
+export interface ContainersListResponse {
+  containers: ContainerGroup[];
+  total_count: number;
+  docker_connected: boolean;
+}
+
+export interface UserSummary {
+  username: string;
+  /** root or SYSTEM */
+  is_system: boolean;
+  process_count: number;
+  cpu_percent: number;
+  memory_mb: number;
+  memory_percent: number;
+  /** Busiest 3 by CPU */
+  top_processes: { pid: number; name: string; cpu_percent: number; memory_mb: number }[];
+}
+
+export interface UsersListResponse {
+  users: UserSummary[];
+  total_count: number;
+}
+
+export interface ProcessGroupSummary {
+  pgid: number;
+  session_leader_pid: number | null;
+  /** null once the group leader has exited */
+  leader_name: string | null;
+  pids: number[];
+  process_count: number;
+  cpu_percent: number;
+  memory_mb: number;
+  memory_percent: number;
+}
+
+export interface ProcessGroupsResponse {
+  groups: ProcessGroupSummary[];
+  total_count: number;
+}
+
+export interface ProcessGroupKillResponse {
+  pgid: number;
+  affected_pids: number[];
+  signal: number;
+}
+
+/** GET /api/process/:pid/capabilities (Linux); names like "CAP_NET_ADMIN" */
+export interface ProcessCapabilities {
+  effective: string[];
+  permitted: string[];
+  inheritable: string[];
+  bounding: string[];
+  ambient: string[];
+}
+
+export interface ProcessIoRates {
+  read_bytes_per_sec: number;
+  write_bytes_per_sec: number;
+  read_syscalls_per_sec: number;
+  write_syscalls_per_sec: number;
+  interval_ms: number;
+}
+
+export interface ProcessIoStats {
+  pid: number;
+  read_bytes: number;
+  write_bytes: number;
+  read_syscalls: number;
+  write_syscalls: number;
+  cancelled_write_bytes: number;
+  read_bytes_formatted: string;
+  write_bytes_formatted: string;
+  /** null on the first request for a PID */
+  per_sec: ProcessIoRates | null;
+}
+
+export interface ProcessHistorySample {
+  /** Unix epoch seconds */
+  timestamp: number;
+  cpu_percent: number;
+  memory_mb: number;
+}
+
+export interface ProcessHistoryResponse {
+  pid: number;
+  minutes: number;
+  /** Oldest first, one per backend poll */
+  samples: ProcessHistorySample[];
+}
+
+export interface MemoryGrowthEntry {
+  pid: number;
+  name: string;
+  /** Unix epoch seconds of the first sample in the window */
+  since: number;
+  memory_mb: number;
+  /** Negative when the process shrank */
+  growth_mb: number;
+  growth_percent: number;
+  /** Resident MiB once a minute, oldest first */
+  samples: number[];
+}
+
+export interface MemoryGrowthResponse {
+  minutes: number;
+  tracked: number;
+  processes: MemoryGrowthEntry[];
+}
+
+export interface PciDevice {
+  domain: number;
+  bus: number;
+  slot: number;
+  function: number;
+  vendor_id: number;
+  device_id: number;
+  class: string;
+  subclass: string;
+  vendor_name: string | null;
+  device_name: string | null;
+  /** e.g. "vfio-pci" for passthrough; null when unbound */
+  driver: string | null;
+  iommu_group: number | null;
+}
+
+export interface UsbDevice {
+  vendor_id: number;
+  product_id: number;
+  vendor_name: string | null;
+  product_name: string | null;
+  serial: string | null;
+  class: string;
+  bus: number;
+  device_number: number;
+  /** e.g. "480 Mbps", "5 Gbps" or "unknown" */
+  speed: string;
+}
+
+export interface ServiceInfo {
+  name: string;
+  description: string;
+  load_state: string;
+  active_state: string;
+  sub_state: string;
+  /** Unit file state on Linux ("enabled"), start mode on Windows ("auto") */
+  start_type: string | null;
+  /** Main process; matches Process.pid */
+  pid: number | null;
+  memory_bytes: number | null;
+  restart_count: number | null;
+  cpu_percent: number | null;
+}
+
+export type StartupSource = "xdg_autostart" | "systemd_user" | "registry" | "startup_folder";
+
+export interface StartupItem {
+  name: string;
+  command: string;
+  source: StartupSource;
+  /** File, directory or registry key holding the entry */
+  location: string;
+  enabled: boolean;
+  /** Oldest running process with the command's executable */
+  pid: number | null;
+  cpu_percent: number | null;
+  memory_bytes: number | null;
+}
+
+export interface ServicesResponse {
+  supported: boolean;
+  services: ServiceInfo[];
+}
+
 export type TabType = "dashboard" | "apps" | "performance" | "processes";
 
+/** GET /api/version; `version` matches /health */
+export interface VersionInfo {
+  version: string;
+  git_commit: string | null;
+  git_dirty: boolean | null;
+  /** RFC 3339 UTC */
+  build_timestamp: string | null;
+  rustc_version: string | null;
+  target: string;
+}
+
+/** GET /api/auth/token; localhost pages from an allowed origin only */
+export interface AuthToken {
+  /** null when the backend runs without auth */
+  token: string | null;
+}
+
 export interface ChartDataPoint {
   time: number;
   value: number;
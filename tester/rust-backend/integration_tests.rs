@@ -1,45 +1,210 @@
-// Rust Backend Unit Tests
-// Place this file in: backend/tests/integration_tests.rs
+// Rust Backend Integration Tests
+// Drives the real router from the library crate with `tower::ServiceExt::oneshot`
 
 #[cfg(test)]
 mod tests {
     use axum::{
-        body::Body,
-        http::{Request, StatusCode},
+        body::{to_bytes, Body},
+        http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+        response::Response,
+        routing::get,
+        Router,
     };
+    use clap::Parser;
+    use serde_json::{json, Value};
+    use std::io::Read;
+    use task_manager_backend::{
+        alerts::{AlertEngine, AlertMetric, AlertRule, Comparison},
+        arp::{parse_arp_an, parse_proc_arp},
+        audit::{self, AuditLog},
+        build_router,
+        capabilities,
+        client,
+        connections::{parse_hex_address, parse_proc_net, proc_net_path, socket_inode},
+        containers::{self, parse_container_id},
+        cpucache::{self, parse_cache_size, CacheType},
+        cwd,
+        config::{AuthConfig, Cli, CompressionConfig, Config, ConfigError, CorsOrigins, DockerConfig, LogFormat, RateLimitConfig, TlsConfig, DEFAULT_COMPRESSION_MIN_SIZE, DEFAULT_TLS_PORT},
+        delta,
+        disks::{self, parse_scheduler, parse_sysfs_block, parse_sysfs_queue, BlockInfo, QueueSettings},
+        docker::{self, parse_container_list, parse_response, parse_started_at, DockerContainer},
+        environ::{parse_environ, Redactor},
+        error::{handle_panic, ApiError},
+        exehash::{self, ExeHashCache},
+        gpu::{self, CircuitBreaker, DEFAULT_GPU_COOLDOWN, GPU_FAILURE_THRESHOLD},
+        growth::{GrowthSort, MemoryGrowth, GROWTH_CAPACITY},
+        history::{ProcessHistory, HISTORY_CAPACITY},
+        hwids::{self, IdDatabase},
+        interrupts::{self, parse_proc_interrupts, parse_proc_softirqs},
+        export::{self, ExportFormat},
+        fields::{field_paths, FieldSelection},
+        kernel::{parse_kextstat, parse_proc_modules},
+        limits::parse_proc_limits,
+        maps::{self, parse_proc_maps, MapRegion},
+        mounts::parse_proc_mounts,
+        namespaces::{self, parse_ns_link},
+        netif::{self, parse_sysfs_link},
+        wifi::{channel_frequency, parse_airport},
+        oom,
+        pci::{self, parse_address, parse_sysfs_pci},
+        policy::{KillPolicy, MatchStyle, PatternList},
+        pressure::parse_psi,
+        processes::Runtime,
+        procio::{self, parse_proc_io, IoCounters},
+        procstat::{self, parse_cpu_times, parse_page_faults, parse_stat, tty_major_minor, CpuTimes, PageFaults, StatIds, TtyNames},
+        protection,
+        topology::{self, Topology},
+        models::{
+            Alert, AuditEntry, CpuCacheInfo, MemoryPressure, NetworkInterfaceStats, PressureStall, ProcessData, ProcessMemoryBreakdown, SeccompStatus, SystemStats,
+            StartupItem, StartupSource,
+        },
+        msgpack,
+        schedstat::{self, parse_proc_stat, SchedCounters, SchedSampler},
+        seccomp,
+        services::{self, unit_name, windows_service_info, windows_service_name},
+        shutdown,
+        smaps::{self, parse_smaps, parse_smaps_rollup, SetSizes},
+        smart::parse_smartctl,
+        startup::{self, parse_desktop_entry, parse_exec_start, parse_windows_startup, DesktopEntry},
+        sysctl::{self, parse_sysctl_output},
+        metrics::ServerMetrics,
+        middleware::{EtagLayer, RateLimiter, RequestLogLayer},
+        mock::MockSystemSource,
+        source::{ProcessSample, ProcessTable, SysinfoSource, SystemSource},
+        threads::{self, ThreadSample},
+        tls::{self, TlsError},
+        usb::{self, parse_sysfs_usb, speed_label},
+        utils::{format_duration, format_rfc3339, unix_now},
+        vmstat::parse_major_faults,
+        webhook::{self, AlertEvent, AlertTransition, WebhookFormat, WebhookTarget, Webhooks},
+        wsstats,
+        AppState,
+    };
+    use std::sync::Arc;
+    use tokio_util::sync::CancellationToken;
     use tower::ServiceExt;
-    use serde_json::Value;
 
-    // Note: These tests require the actual backend to be refactored slightly
-    // to export the router for testing. This is a reference implementation.
+    const MOCK_SYSTEM: &str = include_str!("fixtures/mock_system.json");
+
+    fn router() -> Router {
+        router_with(&CompressionConfig::default())
+    }
+
+    fn router_with(compression_config: &CompressionConfig) -> Router {
+        build_router(AppState::new(Config {
+            compression: compression_config.clone(),
+            ..Default::default()
+        }))
+    }
+
+    fn mock_router() -> (Router, Arc<MockSystemSource>) {
+        mock_router_with(Config::default())
+    }
+
+    fn mock_router_with(config: Config) -> (Router, Arc<MockSystemSource>) {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let router = build_router(AppState::with_source(config, source.clone()));
+        (router, source)
+    }
+
+    fn auth_config(protect_reads: bool) -> Config {
+        Config {
+            auth: AuthConfig {
+                token: Some("s3cret".to_string()),
+                protect_reads,
+                read_only: false,
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn send_with_auth(router: Router, method: &str, uri: &str, authorization: Option<&str>) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(value) = authorization {
+            request = request.header(header::AUTHORIZATION, value);
+        }
+        let response = router.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    async fn send(router: Router, method: &str, uri: &str, body: Option<&str>) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if body.is_some() {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+        }
+        let response = router
+            .oneshot(request.body(Body::from(body.unwrap_or("").to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    async fn get_json(uri: &str) -> (StatusCode, Value) {
+        send(router(), "GET", uri, None).await
+    }
+
+    async fn response_encoding(uri: &str, compression_config: &CompressionConfig) -> Option<String> {
+        let response = router_with(compression_config)
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .header(header::ACCEPT_ENCODING, "gzip")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|v| v.to_str().unwrap().to_string())
+    }
+
+    async fn error_code(response: Response) -> String {
+        let body: Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        body["error"]["code"].as_str().unwrap().to_string()
+    }
 
     #[tokio::test]
     async fn test_stats_endpoint() {
         // Test that /api/stats returns valid JSON with expected fields
-        // This would require setting up the router in a testable way
-        
-        // Expected fields in response
-        let expected_fields = vec![
-            "cpu_usage",
-            "memory_total",
-            "memory_used",
-            "memory_percent",
-            "disk_total",
-            "disk_used",
-            "disk_percent",
-            "disk_read",
-            "disk_write",
-            "network_sent",
-            "network_recv",
+        let (status, stats) = get_json("/api/stats").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let expected_fields = [
+            "/timestamp",
+            "/cpu/percent",
+            "/cpu/cores/physical",
+            "/cpu/cores/logical",
+            "/cpu/per_core",
+            "/memory/total",
+            "/memory/used",
+            "/memory/percent",
+            "/disk/total",
+            "/disk/used",
+            "/disk/percent",
+            "/network/bytes_sent",
+            "/network/bytes_recv",
+            "/system/os",
+            "/system/uptime_seconds",
+            "/kernel/context_switches_per_sec",
+            "/kernel/forks_per_sec",
+            "/kernel/processes_running",
+            "/kernel/processes_blocked",
         ];
+        for field in expected_fields {
+            assert!(stats.pointer(field).is_some(), "missing {}", field);
+        }
 
-        // In a real test, you would:
-        // 1. Create test router
-        // 2. Send request to /api/stats
-        // 3. Verify response has all expected fields
-        // 4. Verify values are in valid ranges
-        
-        assert!(true); // Placeholder
+        // Verify values are in valid ranges
+        let memory_percent = stats["memory"]["percent"].as_f64().unwrap();
+        assert!((0.0..=100.0).contains(&memory_percent));
+        assert!(stats["memory"]["used"].as_u64().unwrap() <= stats["memory"]["total"].as_u64().unwrap());
     }
 
     #[test]
@@ -48,7 +213,7 @@ mod tests {
         // This would test the actual CPU calculation logic
         
         let cpu_usage = 45.5; // Mock value
-        assert!(cpu_usage >= 0.0 && cpu_usage <= 100.0);
+        assert!((0.0..=100.0).contains(&cpu_usage));
     }
 
     #[test]
@@ -75,53 +240,5398 @@ mod tests {
     #[tokio::test]
     async fn test_process_info_invalid_pid() {
         // Test that invalid PID returns appropriate error
-        let invalid_pid = 999999999;
-        
-        // In real test:
-        // Send GET /api/process/{invalid_pid}/info
-        // Expect 404 or error response
-        
-        assert!(true); // Placeholder
+        let (status, body) = get_json("/api/process/999999999/info").await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "process_not_found");
+        assert_eq!(body["error"]["pid"], 999999999);
     }
 
     #[tokio::test]
     async fn test_cors_headers() {
         // Test that CORS headers are properly set
-        // Expected: allow_origin = http://localhost:5173
-        
-        assert!(true); // Placeholder
+        let response = router()
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .header(header::ORIGIN, "http://localhost:5173")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    async fn cors_response(config: Config, method: &str, uri: &str, origin: &str) -> Response {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+        mock_router_with(config).0.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_configured_origins_only() {
+        let response = cors_response(Config::default(), "GET", "/health", "http://127.0.0.1:5173").await;
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://127.0.0.1:5173");
+        let response = cors_response(Config::default(), "GET", "/health", "http://localhost:3000").await;
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://localhost:3000");
+
+        let response = cors_response(Config::default(), "GET", "/health", "https://evil.example").await;
+        assert!(!response.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let config = Config {
+            cors_origins: CorsOrigins::from_list(["https://dash.example/", "http://localhost:3000"]),
+            ..Default::default()
+        };
+        let response = cors_response(config.clone(), "GET", "/health", "https://dash.example").await;
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://dash.example");
+        let response = cors_response(config, "GET", "/health", "http://localhost:5173").await;
+        assert!(!response.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_on_post_route() {
+        let response = cors_response(auth_config(false), "OPTIONS", "/api/process/200/kill", "http://localhost:5173").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://localhost:5173");
+        assert!(response.headers().contains_key(header::ACCESS_CONTROL_ALLOW_METHODS));
     }
 
     #[test]
-    fn test_gpu_stats_nullable() {
-        // Test that GPU stats can be null when no GPU present
-        let gpu_usage: Option<f32> = None;
-        let gpu_memory_used: Option<u64> = None;
-        let gpu_memory_total: Option<u64> = None;
-        let gpu_temperature: Option<f32> = None;
-        
-        assert!(gpu_usage.is_none());
-        assert!(gpu_memory_used.is_none());
-        assert!(gpu_memory_total.is_none());
-        assert!(gpu_temperature.is_none());
+    fn test_invalid_cors_origin_rejected() {
+        let cli = Cli { cors_origins: vec!["dash.example".to_string()], ..Cli::default() };
+        assert!(matches!(Config::default().merge(&cli), Err(ConfigError::CorsOrigin(origin)) if origin == "dash.example"));
+        let cli = Cli { cors_origins: vec!["https://dash\u{1}.example".to_string()], ..Cli::default() };
+        assert!(Config::default().merge(&cli).is_err());
+        let cli = Cli { cors_origins: vec!["https://dash.example".to_string()], ..Cli::default() };
+        assert!(Config::default().merge(&cli).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cors_wildcard_opt_in() {
+        let config = Config {
+            cors_origins: CorsOrigins::from_list(["http://localhost:5173", "*"]),
+            ..Default::default()
+        };
+        assert_eq!(config.cors_origins, CorsOrigins::Any);
+        let response = cors_response(config, "GET", "/health", "https://anything.example").await;
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+
+    #[tokio::test]
+    async fn test_processes_gzip_roundtrip() {
+        let response = router()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/processes")
+                    .header(header::ACCEPT_ENCODING, "gzip")
+                    .header(header::ORIGIN, "http://localhost:5173")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert!(response.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let compressed = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&compressed[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&decoded).unwrap();
+        let processes = body["processes"].as_array().unwrap();
+        assert_eq!(body["total_count"].as_u64().unwrap() as usize, processes.len());
+
+        // Process list changes between calls, so compare shape rather than bytes
+        let plain = router()
+            .oneshot(Request::builder().uri("/api/processes").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(!plain.headers().contains_key(header::CONTENT_ENCODING));
+        let plain: Value =
+            serde_json::from_slice(&to_bytes(plain.into_body(), usize::MAX).await.unwrap()).unwrap();
+        let keys = |v: &Value| {
+            let mut k: Vec<String> = v.as_object().unwrap().keys().cloned().collect();
+            k.sort();
+            k
+        };
+        assert_eq!(keys(&body), keys(&plain));
+        assert_eq!(keys(&processes[0]), keys(&plain["processes"][0]));
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_components() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/health", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["degraded"], json!([]));
+        let components = &body["components"];
+        // The mock router never starts the poller, but its initial sample is fresh
+        assert_eq!(components["sampler"]["running"], false);
+        assert_eq!(components["sampler"]["interval_ms"], 1000);
+        assert!(components["sampler"]["last_sample_age_ms"].as_u64().unwrap() < 1000);
+        assert_eq!(components["history"], json!({"processes": 0, "samples": 0}));
+        assert_eq!(components["audit_writer"], "disabled");
+        assert_eq!(components["nvml_available"], false);
+        assert_eq!(components["read_only"], false);
+    }
+
+    #[tokio::test]
+    async fn test_health_degraded_when_sampler_stalls() {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let config = Config {
+            poll_interval: std::time::Duration::from_millis(20),
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source);
+        let router = build_router(state.clone());
+
+        // Never started: the initial sample goes stale after 3 intervals
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        let (status, body) = send(router.clone(), "GET", "/health", None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["degraded"], json!(["sampler"]));
+
+        let poller = state.spawn_poller();
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        let (status, body) = send(router.clone(), "GET", "/health", None).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["components"]["sampler"]["running"], true);
+        assert_eq!(body["components"]["history"]["processes"], 5);
+
+        // A dead poller is reported at once and degrades once its sample ages
+        poller.abort();
+        let _ = poller.await;
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        let (status, body) = send(router, "GET", "/health", None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["components"]["sampler"]["running"], false);
+        assert_eq!(body["degraded"], json!(["sampler"]));
+    }
+
+    #[tokio::test]
+    async fn test_version_info() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router.clone(), "GET", "/api/v1/version", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert!(!body["target"].as_str().unwrap().is_empty());
+        assert!(body["rustc_version"].as_str().unwrap().starts_with("rustc "));
+        assert!(body["build_timestamp"].as_str().unwrap().ends_with('Z'));
+        // Null only when built outside a git checkout
+        if let Some(commit) = body["git_commit"].as_str() {
+            assert_eq!(commit.len(), 40);
+            assert!(commit.chars().all(|c| c.is_ascii_hexdigit()));
+            assert!(body["git_dirty"].is_boolean());
+        }
+
+        let (_, health) = send(router, "GET", "/health", None).await;
+        assert_eq!(health["version"], body["version"]);
+    }
+
+    #[tokio::test]
+    async fn test_self_metrics() {
+        use futures::StreamExt;
+
+        let (router, _) = mock_router();
+        for uri in ["/api/v1/stats", "/api/v1/stats", "/api/processes", "/api/v1/no-such-route"] {
+            send(router.clone(), "GET", uri, None).await;
+        }
+        let addr = serve(router.clone()).await;
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/api/v1/ws/stats/binary", addr)).await.unwrap();
+        socket.next().await.unwrap().unwrap();
+
+        let (status, body) = send(router.clone(), "GET", "/api/v1/self", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["pid"], std::process::id());
+        // The mock source doesn't list the test process
+        assert!(body["memory_bytes"].is_null());
+        assert!(body["worker_threads"].as_u64().unwrap() >= 1);
+        assert_eq!(body["websocket_clients"], 1);
+        // Counted under the matched pattern; the unknown path isn't counted
+        assert_eq!(
+            body["requests"],
+            serde_json::json!({
+                "/api/v1/stats": 2,
+                "/api/processes": 1,
+                "/api/v1/ws/stats/binary": 1,
+                "/api/v1/self": 1,
+            })
+        );
+        assert_eq!(body["requests_total"], 5);
+        assert_eq!(body["panics_total"], 0);
+        assert!(body["history"]["bytes"].is_u64());
+
+        drop(socket);
+        for _ in 0..50 {
+            let (_, body) = send(router.clone(), "GET", "/api/v1/self", None).await;
+            if body["websocket_clients"] == 0 {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("WebSocket client still counted after disconnecting");
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_metrics() {
+        let (router, _) = mock_router();
+        let (status, headers, body) = get_with_accept(router, "/metrics", "*/*").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(headers[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/plain"));
+        let text = String::from_utf8(body).unwrap();
+        assert!(text.contains("# TYPE taskmgr_cpu_percent gauge\ntaskmgr_cpu_percent 40.5\n"), "{}", text);
+        assert!(text.contains("\ntaskmgr_memory_total_bytes 8589934592\n"), "{}", text);
+        assert!(text.contains("\ntaskmgr_websocket_clients 0\n"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_health_not_compressed() {
+        let response = router()
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .header(header::ACCEPT_ENCODING, "gzip, br")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    }
+
+    #[tokio::test]
+    async fn test_compression_disabled() {
+        let config = CompressionConfig { enabled: false, ..Default::default() };
+        assert_eq!(response_encoding("/api/processes", &config).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_compression_threshold_is_configurable() {
+        // /health is ~80 bytes: skipped by the default threshold, compressed with a tiny one
+        assert_eq!(response_encoding("/health", &CompressionConfig::default()).await, None);
+
+        let config = CompressionConfig { enabled: true, min_size: 16 };
+        assert_eq!(response_encoding("/health", &config).await.as_deref(), Some("gzip"));
     }
 
     #[test]
-    fn test_network_stats_cumulative() {
-        // Test that network stats are cumulative
-        let bytes_sent_1: u64 = 1000;
-        let bytes_sent_2: u64 = 1500;
-        
-        assert!(bytes_sent_2 >= bytes_sent_1);
+    fn test_compression_config_from_vars() {
+        assert_eq!(CompressionConfig::from_vars(None, None), CompressionConfig::default());
+
+        let config = CompressionConfig::from_vars(Some("off"), Some("4096"));
+        assert!(!config.enabled);
+        assert_eq!(config.min_size, 4096);
+
+        let config = CompressionConfig::from_vars(Some("1"), Some("not-a-number"));
+        assert!(config.enabled);
+        assert_eq!(config.min_size, DEFAULT_COMPRESSION_MIN_SIZE);
+    }
+
+    #[tokio::test]
+    async fn test_kill_invalid_pid_returns_json_404() {
+        let response = router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/process/999999999/kill")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(error_code(response).await, "process_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_kill_app_nothing_killed_returns_json_403() {
+        let response = router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/app/close")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from("[999999998, 999999999]"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error_code(response).await, "no_processes_terminated");
+    }
+
+    #[tokio::test]
+    async fn test_panic_maps_to_json_500() {
+        let response = handle_panic(Box::new("boom"));
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(error_code(response).await, "internal_error");
+    }
+
+    #[tokio::test]
+    async fn test_handler_panic_returns_envelope_with_request_id() {
+        async fn boom() -> &'static str {
+            panic!("boom")
+        }
+        let metrics = ServerMetrics::default();
+        let counted = metrics.clone();
+        let router = Router::new()
+            .route("/boom", get(boom))
+            .layer(tower_http::catch_panic::CatchPanicLayer::custom(move |payload| {
+                counted.record_panic();
+                handle_panic(payload)
+            }))
+            .layer(RequestLogLayer);
+
+        let request = Request::builder().uri("/boom").header("x-request-id", "boom-1").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers()["x-request-id"], "boom-1");
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["error"]["code"], "internal_error");
+        assert_eq!(body["error"]["request_id"], "boom-1");
+        assert_eq!(metrics.panics(), 1);
+
+        let state = AppState::with_source(Config::default(), Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap()));
+        state.metrics.record_panic();
+        let (_, body) = send(build_router(state), "GET", "/api/self", None).await;
+        assert_eq!(body["panics_total"], 1);
+    }
+
+    /// Sorting with `partial_cmp(..).unwrap()` used to panic on a NaN CPU reading
+    #[tokio::test]
+    async fn test_nan_cpu_sorts_last() {
+        let mut table: ProcessTable = serde_json::from_str(MOCK_SYSTEM).unwrap();
+        table.processes[0].cpu_usage = f32::NAN;
+        let nan_pid = table.processes[0].pid;
+        let router = build_router(AppState::with_source(Config::default(), Arc::new(MockSystemSource::new(table))));
+
+        for uri in ["/api/apps", "/api/processes?sort_by=memory", "/api/processes?sort_by=cpu&order=asc"] {
+            let (status, _) = send(router.clone(), "GET", uri, None).await;
+            assert_eq!(status, StatusCode::OK, "{}", uri);
+        }
+        let (status, body) = send(router, "GET", "/api/processes", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let last = body["processes"].as_array().unwrap().last().unwrap().clone();
+        assert_eq!(last["pid"], nan_pid);
+        assert!(last["cpu_percent"].is_null());
+    }
+
+    fn etag_router() -> Router {
+        Router::new().route("/static", get(|| async { "unchanged body" }).layer(EtagLayer))
+    }
+
+    #[tokio::test]
+    async fn test_etag_conditional_get() {
+        let response = etag_router()
+            .oneshot(Request::builder().uri("/static").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].clone();
+        assert_eq!(etag.len(), 66); // quoted 64-char hex digest
+
+        let response = etag_router()
+            .oneshot(
+                Request::builder()
+                    .uri("/static")
+                    .header(header::IF_NONE_MATCH, etag.clone())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag);
+        assert!(to_bytes(response.into_body(), usize::MAX).await.unwrap().is_empty());
+
+        let response = etag_router()
+            .oneshot(
+                Request::builder()
+                    .uri("/static")
+                    .header(header::IF_NONE_MATCH, "\"stale\"")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(to_bytes(response.into_body(), usize::MAX).await.unwrap(), "unchanged body");
+    }
+
+    #[tokio::test]
+    async fn test_stats_has_etag() {
+        let response = router()
+            .oneshot(Request::builder().uri("/api/stats").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(header::ETAG));
+    }
+
+    #[tokio::test]
+    async fn test_stats_delta_mode_tracks_clients() {
+        let (router, _) = mock_router();
+        let request = |client_id: Option<&str>| {
+            let mut request = Request::builder().uri("/api/stats?mode=delta");
+            if let Some(id) = client_id {
+                request = request.header("x-client-id", id);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+
+        let response = router.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let client_id = response.headers()["x-client-id"].to_str().unwrap().to_string();
+        assert_eq!(response.headers()["x-delta-interval-ms"], "0");
+
+        let response = router.clone().oneshot(request(Some(&client_id))).await.unwrap();
+        assert_eq!(response.headers()["x-client-id"], client_id.as_str());
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["network"]["bytes_sent"], 0);
+
+        let (status, _) = send(router, "GET", "/api/stats?mode=bogus", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_stats_max_age_forces_refresh() {
+        let (router, source) = mock_router();
+        let get = |uri: &'static str| {
+            let router = router.clone();
+            async move {
+                let response = router.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let age: u64 = response.headers()["x-stats-age-ms"].to_str().unwrap().parse().unwrap();
+                let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+                (age, body["memory"]["used"].as_u64().unwrap())
+            }
+        };
+
+        let (_, used) = get("/api/stats").await;
+        source.kill(100).unwrap();
+
+        // A fresh enough snapshot is served as is
+        let (age, cached) = get("/api/stats?max_age_ms=60000").await;
+        assert_eq!(cached, used);
+        assert!(age < 60_000);
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let (age, refreshed) = get("/api/stats?max_age_ms=10").await;
+        assert_eq!(age, 0);
+        assert_eq!(refreshed, used - 104857600);
+
+        let (status, _) = send(router, "GET", "/api/stats?max_age_ms=soon", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    /// Pins the v1 shape byte for byte; a change here breaks v1 clients and
+    /// belongs under a new version instead
+    #[tokio::test]
+    async fn test_full_payload_snapshot() {
+        let (router, _) = mock_router();
+        for (route, snapshot) in [
+            ("/stats", include_str!("fixtures/stats_snapshot.json")),
+            ("/processes", include_str!("fixtures/processes_snapshot.json")),
+            ("/apps", include_str!("fixtures/apps_snapshot.json")),
+            ("/users", include_str!("fixtures/users_snapshot.json")),
+            ("/cpu/info", include_str!("fixtures/cpu_info_snapshot.json")),
+            ("/process/100/info", include_str!("fixtures/process_info_snapshot.json")),
+        ] {
+            // The unversioned alias serves v1
+            for prefix in ["/api/v1", "/api"] {
+                let uri = format!("{}{}", prefix, route);
+                let response = router.clone().oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap()).await.unwrap();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                assert_eq!(std::str::from_utf8(&body).unwrap(), snapshot.trim_end(), "{}", uri);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_versions() {
+        let (router, _) = mock_router();
+        let (_, health) = send(router.clone(), "GET", "/health", None).await;
+        assert_eq!(health["api_versions"], json!(["v1"]));
+
+        let response = router.clone().oneshot(Request::builder().uri("/api/v1/stats").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("deprecation"));
+        // Not advertised, so not served either
+        let response = router.clone().oneshot(Request::builder().uri("/api/v2/stats").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = router
+            .clone()
+            .oneshot(Request::builder().uri("/api/process/100/info").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(response.headers()[header::LINK], "</api/v1/process/100/info>; rel=\"successor-version\"");
+
+        // Errors keep the envelope and the marker
+        let (status, body) = send(router.clone(), "GET", "/api/v1/process/999999/info", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "process_not_found");
+        let response = router
+            .oneshot(Request::builder().uri("/api/process/999999/info").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["deprecation"], "true");
     }
 
     #[test]
-    fn test_disk_io_cumulative() {
-        // Test that disk I/O stats are cumulative
-        let disk_read_1: u64 = 5000;
-        let disk_read_2: u64 = 6000;
-        
-        assert!(disk_read_2 >= disk_read_1);
+    fn test_field_selection() {
+        let paths = field_paths::<SystemStats>();
+        for path in ["cpu", "cpu.percent", "cpu.cores.logical", "cpu.per_core", "network.interfaces", "gpu.load", "memory_pressure"] {
+            assert!(paths.contains(&path.to_string()), "{}", path);
+        }
+        assert!(!paths.iter().any(|p| p.starts_with("cpu.per_core.")));
+
+        let value = json!({"cpu": {"percent": 5.0, "cores": {"physical": 2, "logical": 4}}, "memory": {"percent": 50.0}});
+        let selection = FieldSelection::parse::<SystemStats>("cpu.cores.logical, memory.percent,,gpu.load").unwrap();
+        assert_eq!(selection.apply(&value), json!({"cpu": {"cores": {"logical": 4}}, "memory": {"percent": 50.0}}));
+        // A parent and its child together give the whole parent
+        let selection = FieldSelection::parse::<SystemStats>("cpu.percent,cpu").unwrap();
+        assert_eq!(selection.apply(&value), json!({"cpu": value["cpu"]}));
+        let selection = FieldSelection::parse::<SystemStats>("cpu,cpu.percent").unwrap();
+        assert_eq!(selection.apply(&value), json!({"cpu": value["cpu"]}));
+
+        let err = FieldSelection::parse::<SystemStats>("cpu.percent,cpu.bogus,ram").unwrap_err();
+        match err {
+            ApiError::UnknownFields { unknown, valid } => {
+                assert_eq!(unknown, vec!["cpu.bogus", "ram"]);
+                assert_eq!(valid, paths);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fields_param() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router.clone(), "GET", "/api/stats?fields=cpu.percent,memory.percent,disk.percent", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({"cpu": {"percent": 40.5}, "memory": {"percent": 7.5195312}, "disk": {"percent": 0.0}}));
+        let (_, body) = send(router.clone(), "GET", "/api/stats?mode=delta&fields=network.bytes_sent", None).await;
+        assert_eq!(body, json!({"network": {"bytes_sent": 0}}));
+        // Valid but absent without a GPU
+        let (status, body) = send(router.clone(), "GET", "/api/stats?fields=gpu.load", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({}));
+
+        let (status, body) = send(router.clone(), "GET", "/api/stats?fields=cpu.load", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "unknown_field");
+        let message = body["error"]["message"].as_str().unwrap();
+        assert!(message.contains("cpu.load"));
+        assert!(message.contains("cpu.percent"));
+
+        let (status, body) = send(router.clone(), "GET", "/api/processes?fields=pid,name,cpu_percent&sort=pid", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_count"], 5);
+        assert_eq!(body["processes"][0], json!({"pid": 1, "name": "systemd", "cpu_percent": 0.0}));
+        let (status, body) = send(router, "GET", "/api/processes?fields=pid,cpu", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"]["message"].as_str().unwrap().contains("memory_mb"));
+    }
+
+    #[test]
+    fn test_parse_cache_sizes() {
+        assert_eq!(parse_cache_size("32K\n"), Some(32));
+        assert_eq!(parse_cache_size("8192K"), Some(8192));
+        assert_eq!(parse_cache_size("1M"), Some(1024));
+        assert_eq!(parse_cache_size("65536"), Some(64));
+        assert_eq!(parse_cache_size("K"), None);
+        assert_eq!(parse_cache_size("lots"), None);
+
+        let cache = cpucache::summarize([
+            (1, CacheType::Data, 48),
+            (1, CacheType::Instruction, 32),
+            (2, CacheType::Unified, 1280),
+            (3, CacheType::Unified, 24576),
+            (3, CacheType::Unified, 12288),
+        ]);
+        assert_eq!(cache, CpuCacheInfo { l1d_kb: Some(48), l1i_kb: Some(32), l2_kb: Some(1280), l3_kb: Some(24576) });
+        assert_eq!(cpucache::summarize([(2, CacheType::Unified, 512)]).l3_kb, None);
+    }
+
+    #[tokio::test]
+    async fn test_cpu_info_endpoint() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/cpu/info", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["brand"], "Mock CPU");
+        assert_eq!(body["logical_cores"], body["physical_cores"]);
+        assert_eq!(body["cache"], serde_json::json!({"l1d_kb": null, "l1i_kb": null, "l2_kb": null, "l3_kb": null}));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cpu_cache_from_sysfs() {
+        if !std::path::Path::new("/sys/devices/system/cpu/cpu0/cache/index0").exists() {
+            return;
+        }
+        let cache = cpucache::read();
+        assert!(cache.l1d_kb.is_some_and(|kb| kb > 0));
+        assert!(cache.l2_kb.is_some());
+    }
+
+    #[test]
+    fn test_cpu_topology() {
+        assert_eq!(topology::count_sockets(["0\n", "0\n", "1\n", "1\n"]), Some(2));
+        assert_eq!(topology::count_sockets([]), None);
+        assert_eq!(topology::count_numa_nodes(["node0", "node1", "online", "possible", "has_cpu", "node"]), Some(2));
+        assert_eq!(topology::count_numa_nodes(["online"]), None);
+
+        // 2 sockets of 8 cores with SMT
+        let cores = Topology { physical_cores: Some(16), sockets: Some(2), numa_nodes: Some(2) }.cores(32);
+        assert_eq!((cores.physical, cores.logical), (16, 32));
+        assert_eq!((cores.sockets, cores.cores_per_socket, cores.threads_per_core, cores.numa_nodes), (Some(2), Some(8), 2, Some(2)));
+        // Nothing known beyond the logical count
+        let cores = Topology::default().cores(4);
+        assert_eq!((cores.physical, cores.sockets, cores.cores_per_socket, cores.threads_per_core), (4, None, None, 1));
+
+        #[cfg(target_os = "linux")]
+        if std::path::Path::new("/sys/devices/system/cpu/cpu0/topology/physical_package_id").exists() {
+            assert!(Topology::read(None).sockets.is_some_and(|n| n > 0));
+        }
+    }
+
+    #[test]
+    fn test_parse_proc_stat_sched_counters() {
+        let text = "\
+cpu  10132153 290696 3084719 46828483 16683 0 25195 0 0 0
+cpu0 1393280 32966 572056 13343292 6130 0 17875 0 0 0
+intr 1462898 0 0 0
+ctxt 115315403
+btime 1700000000
+processes 86031
+procs_running 3
+procs_blocked 1
+softirq 229245889 94 60001584 13619 5175704 2471304 0 2 26 0 161947516
+";
+        assert_eq!(
+            parse_proc_stat(text),
+            Some(SchedCounters {
+                context_switches: 115_315_403,
+                forks: 86_031,
+                procs_running: 3,
+                procs_blocked: 1,
+            })
+        );
+        assert_eq!(parse_proc_stat("cpu 1 2 3\nctxt 5\n"), None);
+    }
+
+    #[test]
+    fn test_sched_rates() {
+        let before = SchedCounters { context_switches: 1_000, forks: 100, procs_running: 1, procs_blocked: 0 };
+        let after = SchedCounters { context_switches: 5_000, forks: 120, procs_running: 4, procs_blocked: 2 };
+
+        let first = schedstat::rates(None, &after, 0.0);
+        assert_eq!((first.context_switches_per_sec, first.forks_per_sec), (0, 0));
+        assert_eq!((first.processes_running, first.processes_blocked), (4, 2));
+
+        let stats = schedstat::rates(Some(&before), &after, 2.0);
+        assert_eq!(stats.context_switches_per_sec, 2_000);
+        assert_eq!(stats.forks_per_sec, 10);
+
+        // counters only go backwards if something is badly wrong; never underflow
+        let stats = schedstat::rates(Some(&after), &before, 1.0);
+        assert_eq!((stats.context_switches_per_sec, stats.forks_per_sec), (0, 0));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sched_sampler_reads_proc_stat() {
+        let sampler = SchedSampler::default();
+        assert_eq!(sampler.sample().context_switches_per_sec, 0);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        // this test itself is running
+        assert!(sampler.sample().processes_running >= 1);
+    }
+
+    #[test]
+    fn test_stats_delta_diff() {
+        let source = MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap();
+        let mut before = source.snapshot_stats();
+        before.network.bytes_sent = 1_000;
+        before.network.bytes_recv = 5_000;
+        before.network.interfaces = vec![NetworkInterfaceStats {
+            name: "eth0".to_string(),
+            bytes_sent: 1_000,
+            bytes_recv: 5_000,
+            is_up: true,
+            speed_mbps: Some(1000),
+            duplex: Some("full".to_string()),
+            mac_address: None,
+            ip_addresses: Vec::new(),
+            mtu: Some(1500),
+            wifi: None,
+        }];
+        let mut after = before.clone();
+        after.network.bytes_sent = 1_500;
+        after.network.bytes_recv = 4_000; // counter reset
+        after.network.interfaces[0].bytes_sent = 1_500;
+        let mut wlan0 = after.network.interfaces[0].clone();
+        wlan0.name = "wlan0".to_string();
+        after.network.interfaces.push(wlan0);
+
+        before.memory_pressure = Some(MemoryPressure {
+            some: PressureStall { avg10: 0.5, avg60: 0.2, avg300: 0.1, total_us: 100_000 },
+            full: None,
+        });
+        after.memory_pressure = Some(MemoryPressure {
+            some: PressureStall { avg10: 0.7, avg60: 0.3, avg300: 0.1, total_us: 160_000 },
+            full: Some(PressureStall { avg10: 0.1, avg60: 0.0, avg300: 0.0, total_us: 9_000 }),
+        });
+
+        let delta = delta::diff(&before, &after);
+        assert_eq!(delta.network.bytes_sent, 500);
+        assert_eq!(delta.network.bytes_recv, 0);
+        assert_eq!(delta.network.interfaces[0].bytes_sent, 500);
+        assert_eq!(delta.network.interfaces[1].bytes_sent, 0);
+        assert_eq!(delta.memory.used, after.memory.used);
+        let pressure = delta.memory_pressure.unwrap();
+        assert_eq!((pressure.some.total_us, pressure.some.avg10), (60_000, 0.7));
+        assert_eq!(pressure.full.unwrap().total_us, 0);
+    }
+
+    #[test]
+    fn test_delta_tracker_state_is_bounded() {
+        let stats = MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap().snapshot_stats();
+        let tracker = delta::DeltaTracker::default().max_clients(2);
+        for client in 0..50 {
+            let (_, interval) = tracker.delta(&format!("client-{client}"), &stats);
+            assert_eq!(interval, None);
+        }
+        assert_eq!(tracker.tracked_clients(), 2);
+        // The most recent client kept its state
+        assert!(tracker.delta("client-49", &stats).1.is_some());
+    }
+
+    async fn get_with_accept(router: Router, uri: &str, accept: &str) -> (StatusCode, HeaderMap, Vec<u8>) {
+        let request = Request::builder().uri(uri).header(header::ACCEPT, accept).body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let (status, headers) = (response.status(), response.headers().clone());
+        (status, headers, to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_round_trips_every_response_type() {
+        let (router, _) = mock_router();
+        for uri in [
+            "/health",
+            "/api/stats",
+            "/api/processes",
+            "/api/apps",
+            "/api/users",
+            "/api/process/200/info",
+            "/api/audit",
+            "/api/process/999999/info",
+        ] {
+            let (json_status, json_headers, json_body) = get_with_accept(router.clone(), uri, "application/json").await;
+            let (status, headers, body) = get_with_accept(router.clone(), uri, "application/msgpack").await;
+            assert_eq!(status, json_status, "{}", uri);
+            assert_eq!(headers[header::CONTENT_TYPE], msgpack::MSGPACK, "{}", uri);
+            assert_eq!(headers[header::VARY], "accept", "{}", uri);
+            // Negotiated by exactly one MsgpackLayer, /stats included
+            assert_eq!(headers.get_all(header::VARY).iter().filter(|v| *v == "accept").count(), 1, "{}", uri);
+            assert_eq!(json_headers.get_all(header::VARY).iter().filter(|v| *v == "accept").count(), 1, "{}", uri);
+            assert_eq!(json_headers[header::CONTENT_TYPE], "application/json", "{}", uri);
+
+            let mut json: Value = serde_json::from_slice(&json_body).unwrap();
+            let mut decoded: Value = rmp_serde::from_slice(&body).unwrap();
+            // Apps with equal CPU come out in hash order
+            for value in [&mut json, &mut decoded] {
+                // MessagePack keeps f32 fields at f32; JSON prints their shortest decimal
+                round_floats_to_f32(value);
+                if let Some(apps) = value.get_mut("apps").and_then(Value::as_array_mut) {
+                    apps.sort_by_key(|app| app["name"].as_str().unwrap_or_default().to_string());
+                }
+                // Advances between the two requests
+                if let Some(sampler) = value.pointer_mut("/components/sampler").and_then(Value::as_object_mut) {
+                    sampler.remove("last_sample_age_ms");
+                }
+                // Each request gets its own ID
+                if let Some(error) = value.get_mut("error").and_then(Value::as_object_mut) {
+                    error.remove("request_id");
+                }
+            }
+            assert_eq!(decoded, json, "{}", uri);
+            if uri == "/api/stats" {
+                assert!(body.len() < json_body.len());
+                assert_ne!(headers[header::ETAG], json_headers[header::ETAG]);
+            }
+        }
+    }
+
+    fn round_floats_to_f32(value: &mut Value) {
+        match value {
+            Value::Number(n) if n.is_f64() => *value = json!(n.as_f64().unwrap() as f32 as f64),
+            Value::Array(items) => items.iter_mut().for_each(round_floats_to_f32),
+            Value::Object(map) => map.values_mut().for_each(round_floats_to_f32),
+            _ => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_request_body() {
+        let (router, source) = mock_router();
+        let post = |body: Vec<u8>| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/app/close")
+                .header(header::CONTENT_TYPE, "application/msgpack")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let body = rmp_serde::to_vec(&[100u32, 101]).unwrap();
+        assert_eq!(body, [0x92, 100, 101]);
+        let response = router.clone().oneshot(post(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(source.killed(), vec![100, 101]);
+
+        let response = router.oneshot(post(vec![0xc1])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["error"]["code"], "invalid_body");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_json_body_gets_invalid_body() {
+        for body in ["[100,", r#"{"pids": [100]}"#] {
+            let (router, source) = mock_router();
+            let (status, response) = send(router, "POST", "/api/app/close", Some(body)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+            assert_eq!(response["error"]["code"], "invalid_body", "{}", body);
+            assert!(response["error"]["message"].as_str().unwrap().starts_with("Invalid request body: "));
+            assert!(source.killed().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_binary_stats_frame_round_trip() {
+        let stats = MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap().snapshot_stats();
+        let frame = wsstats::encode_frame(&stats);
+        let json = serde_json::to_value(&stats).unwrap();
+        let decoded = rmp_serde::from_slice::<SystemStats>(&frame).unwrap();
+        assert_eq!(serde_json::to_value(client::decode_frame(&frame).unwrap()).unwrap(), json);
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+        assert_eq!(decoded.cpu.per_core, stats.cpu.per_core);
+        assert!(frame.len() < serde_json::to_vec(&stats).unwrap().len());
+
+        #[derive(serde::Deserialize)]
+        struct Uptime {
+            system: Value,
+        }
+        let partial: Uptime = client::decode_frame_as(&frame).unwrap();
+        assert_eq!(partial.system, json["system"]);
+        assert_eq!(client::control_message(std::time::Duration::from_millis(100)), r#"{"interval_ms":100}"#);
+    }
+
+    #[tokio::test]
+    async fn test_binary_stats_websocket() {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = mock_router().0.into_make_service_with_connect_info::<std::net::SocketAddr>();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/api/ws/stats/binary", addr)).await.unwrap();
+        socket.send(Message::text(client::control_message(std::time::Duration::from_millis(100)))).await.unwrap();
+        let Some(Ok(Message::Binary(frame))) = socket.next().await else {
+            panic!("expected a binary stats frame");
+        };
+        let stats = client::decode_frame(&frame).unwrap();
+        assert_eq!(stats.system.os, "mock");
+
+        // The mock snapshot never changes, so the next message answers the bad control
+        socket.send(Message::text(r#"{"interval_ms": 5}"#)).await.unwrap();
+        let Some(Ok(Message::Text(text))) = socket.next().await else {
+            panic!("expected an error frame");
+        };
+        let error: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(error["error"]["code"], "invalid_body");
+    }
+
+    #[test]
+    fn test_msgpack_keeps_handler_types() {
+        #[derive(serde::Serialize)]
+        struct Sample {
+            load: f32,
+            missing: f64,
+        }
+        let response = axum::response::IntoResponse::into_response(msgpack::Json(Sample { load: 0.5, missing: f64::NAN }));
+        let body = response.extensions().get::<msgpack::MsgpackEncoder>().unwrap().encode().unwrap();
+        // float32 for `load`, and NaN rather than the `null` JSON would need
+        assert_eq!(&body[..7], [0x82, 0xa4, b'l', b'o', b'a', b'd', 0xca]);
+        let decoded: std::collections::HashMap<String, f64> = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(decoded["load"], 0.5);
+        assert!(decoded["missing"].is_nan());
+
+        let accept = |value: &'static str| HeaderMap::from_iter([(header::ACCEPT, HeaderValue::from_static(value))]);
+        assert!(msgpack::accepts_msgpack(&accept("application/json;q=0.9, application/msgpack")));
+        assert!(!msgpack::accepts_msgpack(&accept("application/json, application/msgpack;q=0.5")));
+        assert!(!msgpack::accepts_msgpack(&accept("application/msgpack;q=0")));
+        assert!(!msgpack::accepts_msgpack(&accept("*/*")));
+    }
+
+    #[tokio::test]
+    async fn test_etag_weak_and_listed_tags() {
+        let response = etag_router()
+            .oneshot(Request::builder().uri("/static").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        for if_none_match in [format!("\"x\", W/{}", etag), "*".to_string()] {
+            let response = etag_router()
+                .oneshot(
+                    Request::builder()
+                        .uri("/static")
+                        .header(header::IF_NONE_MATCH, HeaderValue::from_str(&if_none_match).unwrap())
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_cpu_is_divided_by_core_count() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/processes", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_count"], 5);
+        // 150% across 4 cores sorts first as 37.5%
+        assert_eq!(body["processes"][0]["pid"], 100);
+        assert_eq!(body["processes"][0]["cpu_percent"].as_f64().unwrap(), 37.5);
+        assert_eq!(body["processes"][0]["memory_mb"].as_f64().unwrap(), 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_mock_apps_sum_memory_across_pids() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/apps", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        let chrome = body["apps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|app| app["name"] == "chrome")
+            .unwrap();
+        let mut pids: Vec<u64> = chrome["pids"].as_array().unwrap().iter().map(|p| p.as_u64().unwrap()).collect();
+        pids.sort();
+
+        assert_eq!(pids, vec![100, 101, 102]);
+        assert_eq!(chrome["process_count"], 3);
+        assert_eq!(chrome["memory_mb"].as_f64().unwrap(), 600.0);
+        assert_eq!(chrome["cpu_percent"].as_f64().unwrap(), 40.5);
+    }
+
+    #[tokio::test]
+    async fn test_processes_filter_and_sort() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/processes?name_contains=CHROME&sort_by=memory", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_count"], 3);
+        let pids: Vec<u64> = body["processes"].as_array().unwrap().iter().map(|p| p["pid"].as_u64().unwrap()).collect();
+        assert_eq!(pids, vec![102, 101, 100]);
+
+        let (router, _) = mock_router();
+        let (_, body) = send(router, "GET", "/api/processes?sort_by=name&order=desc", None).await;
+        assert_eq!(body["processes"][0]["name"], "systemd");
+        assert_eq!(body["processes"][4]["name"], "bash");
+
+        let (router, _) = mock_router();
+        let (status, _) = send(router, "GET", "/api/processes?sort_by=colour", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_processes_export_csv_and_tsv() {
+        let (router, _) = mock_router();
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/api/processes/export?format=csv&name_contains=chrome&sort_by=pid")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/csv"));
+        let disposition = response.headers()[header::CONTENT_DISPOSITION].to_str().unwrap().to_string();
+        assert!(disposition.starts_with("attachment; filename=\"processes-"), "{}", disposition);
+        assert!(disposition.ends_with(".csv\""), "{}", disposition);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "PID,Name,Username,CPU%,Memory%,MemoryMB,Status,Threads,CreateTime,Exe,CommandLine");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("100,chrome,"));
+        assert!(lines[3].ends_with(",/opt/google/chrome/chrome --type=gpu-process"));
+
+        let (router, _) = mock_router();
+        let response = router
+            .oneshot(Request::builder().uri("/api/processes/export?format=tsv").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/tab-separated-values"));
+        assert!(response.headers()[header::CONTENT_DISPOSITION].to_str().unwrap().ends_with(".tsv\""));
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body.starts_with(b"PID\tName\t"));
+    }
+
+    #[test]
+    fn test_export_escapes_commas_and_quotes() {
+        let process = ProcessData {
+            pid: 42,
+            name: "python".to_string(),
+            username: "N/A".to_string(),
+            cpu_percent: 1.5,
+            memory_percent: 0.25,
+            memory_mb: 12.0,
+            status: "running".to_string(),
+            num_threads: 3,
+            create_time: 1_700_000_000,
+            create_time_iso: None,
+            running_seconds: None,
+            running_formatted: None,
+            exe: "/usr/bin/python3".to_string(),
+            cwd: "N/A".to_string(),
+            cmdline: vec!["python3".to_string(), "-c".to_string(), "print(\"a,b\")".to_string()],
+            is_protected: false,
+            is_self: false,
+            session_id: None,
+            process_group_id: None,
+            tty: None,
+            container_id: None,
+            parent_pid: None,
+            cpu_time_seconds: None,
+            disk_read_rate: 0.0,
+            disk_write_rate: 0.0,
+            oom_score: None,
+            oom_score_adj: None,
+            depth: None,
+        };
+
+        let csv = String::from_utf8(export::write(&[process], ExportFormat::Csv).unwrap()).unwrap();
+        let row = csv.lines().nth(1).unwrap();
+        assert_eq!(row, "42,python,N/A,1.50,0.25,12.00,running,3,1700000000,/usr/bin/python3,\"python3 -c print(\"\"a,b\"\")\"");
+
+        let parsed: Vec<csv::StringRecord> = csv::Reader::from_reader(csv.as_bytes()).records().map(Result::unwrap).collect();
+        assert_eq!(&parsed[0][10], "python3 -c print(\"a,b\")");
+    }
+
+    /// systemd → bash → code → two helpers with their own names and paths, plus
+    /// an orphan whose parent is gone and two processes that are each other's parent
+    const PROCESS_TREE: &str = r#"{
+        "cpu_count": 1,
+        "total_memory": 1073741824,
+        "processes": [
+            {"pid": 1, "name": "systemd", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping"},
+            {"pid": 10, "name": "bash", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "parent": 1},
+            {"pid": 20, "name": "code", "cpu_usage": 5.0, "memory": 10485760, "status": "running", "parent": 10, "exe": "/usr/share/code/code"},
+            {"pid": 21, "name": "code-helper", "cpu_usage": 3.0, "memory": 20971520, "status": "running", "parent": 20, "exe": "/usr/share/code/helpers/helper"},
+            {"pid": 22, "name": "code-gpu", "cpu_usage": 2.0, "memory": 31457280, "status": "running", "parent": 21, "exe": "/opt/gpu/bin/gpu"},
+            {"pid": 30, "name": "orphan", "cpu_usage": 1.0, "memory": 1048576, "status": "running", "parent": 999},
+            {"pid": 40, "name": "loop-a", "cpu_usage": 0.0, "memory": 1048576, "status": "running", "parent": 41},
+            {"pid": 41, "name": "loop-b", "cpu_usage": 0.0, "memory": 1048576, "status": "running", "parent": 40}
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_process_children() {
+        let source = Arc::new(MockSystemSource::from_fixture(PROCESS_TREE).unwrap());
+        let router = build_router(AppState::with_source(Config::default(), source));
+        let pids_and_depths = |body: &Value| -> Vec<(u64, Option<u64>)> {
+            body.as_array().unwrap().iter().map(|p| (p["pid"].as_u64().unwrap(), p["depth"].as_u64())).collect()
+        };
+
+        let (status, body) = send(router.clone(), "GET", "/api/process/10/children", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(pids_and_depths(&body), [(20, None)]);
+        assert!(body[0].get("depth").is_none());
+
+        let (_, body) = send(router.clone(), "GET", "/api/process/10/children?recursive=true", None).await;
+        assert_eq!(pids_and_depths(&body), [(20, Some(1)), (21, Some(2)), (22, Some(3))]);
+
+        // A parent cycle is walked once
+        let (_, body) = send(router.clone(), "GET", "/api/process/40/children?recursive=true", None).await;
+        assert_eq!(pids_and_depths(&body), [(41, Some(1))]);
+
+        let (_, body) = send(router.clone(), "GET", "/api/process/22/children", None).await;
+        assert_eq!(body, serde_json::json!([]));
+
+        let (status, body) = send(router, "GET", "/api/process/999/children", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "process_not_found");
+    }
+
+    const SHARED_SERVER: &str = r#"{
+        "cpu_count": 2,
+        "total_memory": 1073741824,
+        "processes": [
+            {"pid": 1, "name": "systemd", "cpu_usage": 1.0, "memory": 10485760, "status": "sleeping", "user_id": "0", "username": "root"},
+            {"pid": 10, "name": "cc1plus", "cpu_usage": 80.0, "memory": 104857600, "status": "running", "user_id": "1000", "username": "alice"},
+            {"pid": 11, "name": "cc1plus", "cpu_usage": 60.0, "memory": 52428800, "status": "running", "user_id": "1000", "username": "alice"},
+            {"pid": 12, "name": "bash", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "user_id": "1000", "username": "alice"},
+            {"pid": 13, "name": "ld", "cpu_usage": 70.0, "memory": 209715200, "status": "running", "user_id": "1000", "username": "alice"},
+            {"pid": 20, "name": "cc1plus", "cpu_usage": 40.0, "memory": 104857600, "status": "running", "user_id": "1001", "username": "bob"},
+            {"pid": 21, "name": "bash", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "user_id": "1001", "username": "bob"},
+            {"pid": 30, "name": "worker", "cpu_usage": 2.0, "memory": 1048576, "status": "running", "user_id": "4242"}
+        ]
+    }"#;
+
+    fn shared_server_router() -> Router {
+        let source = Arc::new(MockSystemSource::from_fixture(SHARED_SERVER).unwrap());
+        build_router(AppState::with_source(Config::default(), source))
+    }
+
+    #[tokio::test]
+    async fn test_users_aggregate_processes_per_user() {
+        let (status, body) = send(shared_server_router(), "GET", "/api/users", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_count"], 4);
+        let users = body["users"].as_array().unwrap();
+        let names: Vec<&str> = users.iter().map(|u| u["username"].as_str().unwrap()).collect();
+        assert_eq!(names, ["alice", "bob", "N/A", "root"]);
+
+        // CPU is normalised by the 2 cores, as in /api/processes
+        let alice = &users[0];
+        assert_eq!(alice["process_count"], 4);
+        assert_eq!(alice["cpu_percent"].as_f64().unwrap(), 105.0);
+        assert_eq!(alice["memory_mb"].as_f64().unwrap(), 351.0);
+        assert!((alice["memory_percent"].as_f64().unwrap() - 34.277).abs() < 0.01);
+        assert_eq!(alice["is_system"], false);
+        let top: Vec<u64> = alice["top_processes"].as_array().unwrap().iter().map(|p| p["pid"].as_u64().unwrap()).collect();
+        assert_eq!(top, [10, 13, 11]);
+        assert_eq!(alice["top_processes"][0]["cpu_percent"].as_f64().unwrap(), 40.0);
+
+        let bob = &users[1];
+        assert_eq!(bob["process_count"], 2);
+        assert_eq!(bob["cpu_percent"].as_f64().unwrap(), 20.0);
+        assert_eq!(bob["top_processes"].as_array().unwrap().len(), 2);
+
+        // an owner with no account is kept, under the same "N/A" as its processes
+        assert_eq!(users[2]["process_count"], 1);
+        assert_eq!(users[3]["is_system"], true);
+        assert_eq!(users[3]["memory_mb"].as_f64().unwrap(), 10.0);
+    }
+
+    #[tokio::test]
+    async fn test_processes_filtered_by_user() {
+        let router = shared_server_router();
+        let (status, body) = send(router.clone(), "GET", "/api/processes?user=bob", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let pids: Vec<u64> = body["processes"].as_array().unwrap().iter().map(|p| p["pid"].as_u64().unwrap()).collect();
+        assert_eq!(pids, [20, 21]);
+        assert_eq!(body["processes"][0]["username"], "bob");
+
+        let (_, body) = send(router.clone(), "GET", "/api/processes?user=alice&name_contains=cc1&sort_by=pid", None).await;
+        let pids: Vec<u64> = body["processes"].as_array().unwrap().iter().map(|p| p["pid"].as_u64().unwrap()).collect();
+        assert_eq!(pids, [10, 11]);
+
+        let (_, body) = send(router.clone(), "GET", "/api/processes?user=ALICE", None).await;
+        assert_eq!(body["total_count"], 0);
+
+        let (_, body) = send(router, "GET", "/api/process/1/info", None).await;
+        assert_eq!(body["username"], "root");
+    }
+
+    #[test]
+    fn test_exe_hash_cached_until_modified() {
+        let path = std::env::temp_dir().join(format!("taskmgr-exe-{}", std::process::id()));
+        std::fs::write(&path, b"abc").unwrap();
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(exehash::sha256_file(&path).as_deref(), Some(abc));
+
+        let cache = ExeHashCache::default();
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        assert_eq!(cache.get(&path).as_deref(), Some(abc));
+
+        // same mtime: the cached hash is served without reading the file
+        std::fs::write(&path, b"xyz").unwrap();
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        assert_eq!(cache.get(&path).as_deref(), Some(abc));
+
+        // a new mtime means a new binary
+        let later = modified + std::time::Duration::from_secs(10);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(cache.get(&path), exehash::sha256_file(&path));
+        assert_ne!(cache.get(&path).as_deref(), Some(abc));
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(cache.get(&path), None);
+    }
+
+    #[tokio::test]
+    async fn test_process_info_exe_sha256() {
+        let path = std::env::temp_dir().join(format!("taskmgr-info-exe-{}", std::process::id()));
+        std::fs::write(&path, b"abc").unwrap();
+        let fixture = serde_json::json!({
+            "cpu_count": 1,
+            "total_memory": 1073741824,
+            "processes": [
+                {"pid": 10, "name": "tool", "cpu_usage": 0.0, "memory": 1048576, "status": "running", "exe": path},
+                {"pid": 11, "name": "gone", "cpu_usage": 0.0, "memory": 1048576, "status": "running", "exe": "/nonexistent/taskmgr-exe"}
+            ]
+        });
+        let source = Arc::new(MockSystemSource::from_fixture(&fixture.to_string()).unwrap());
+        let router = build_router(AppState::with_source(Config::default(), source));
+
+        let (status, body) = send(router.clone(), "GET", "/api/process/10/info", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["exe_sha256"], "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+        let (_, body) = send(router, "GET", "/api/process/11/info", None).await;
+        assert!(body["exe_sha256"].is_null());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    const CONTAINER_ID: &str = "3f4c1d8f2b7a9e6c5d4b3a291807f6e5d4c3b2a1908f7e6d5c4b3a2918070f6e";
+
+    #[test]
+    fn test_parse_container_id_from_cgroup_fixtures() {
+        let id = Some(CONTAINER_ID.to_string());
+        assert_eq!(parse_container_id(include_str!("fixtures/cgroup/docker_v1.txt")), id);
+        assert_eq!(parse_container_id(include_str!("fixtures/cgroup/docker_v2.txt")), id);
+        assert_eq!(parse_container_id(include_str!("fixtures/cgroup/kubepods_v1.txt")), id);
+        assert_eq!(parse_container_id(include_str!("fixtures/cgroup/containerd_v2.txt")), id);
+        assert_eq!(parse_container_id(include_str!("fixtures/cgroup/podman_v2.txt")), id);
+        // conmon supervises a Podman container from outside it
+        assert_eq!(parse_container_id(include_str!("fixtures/cgroup/podman_conmon_v2.txt")), None);
+        assert_eq!(parse_container_id(include_str!("fixtures/cgroup/host_v1.txt")), None);
+        assert_eq!(parse_container_id(include_str!("fixtures/cgroup/host_v2.txt")), None);
+        assert_eq!(parse_container_id("0::/\n"), None);
+        // hex, but not a 64-digit ID
+        assert_eq!(parse_container_id("4:memory:/process_api/cc-1bc48e88ef08\n"), None);
+        assert_eq!(containers::read(u32::MAX), None);
+    }
+
+    const CONTAINER_HOST: &str = r#"{
+        "cpu_count": 2,
+        "total_memory": 1073741824,
+        "processes": [
+            {"pid": 1, "name": "systemd", "cpu_usage": 0.0, "memory": 10485760, "status": "sleeping"},
+            {"pid": 500, "name": "dockerd", "cpu_usage": 2.0, "memory": 52428800, "status": "sleeping"},
+            {"pid": 610, "name": "java", "cpu_usage": 90.0, "memory": 524288000, "status": "running", "container_id": "3f4c1d8f2b7a9e6c5d4b3a291807f6e5d4c3b2a1908f7e6d5c4b3a2918070f6e"},
+            {"pid": 600, "name": "tini", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "container_id": "3f4c1d8f2b7a9e6c5d4b3a291807f6e5d4c3b2a1908f7e6d5c4b3a2918070f6e"},
+            {"pid": 700, "name": "postgres", "cpu_usage": 10.0, "memory": 104857600, "status": "sleeping", "container_id": "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_docker_responses() {
+        let list = format!(
+            r#"[{{"Id":"{}","Names":["/web"],"Image":"nginx:1.25","State":"running","Status":"Up 2 hours"}},
+                {{"Id":"bbbb","Names":[],"Image":"redis","State":"paused"}}]"#,
+            CONTAINER_ID
+        );
+        let containers = parse_container_list(list.as_bytes()).unwrap();
+        assert_eq!(
+            containers[0],
+            DockerContainer {
+                id: CONTAINER_ID.to_string(),
+                name: "web".to_string(),
+                image: "nginx:1.25".to_string(),
+                state: "running".to_string(),
+            }
+        );
+        assert_eq!(containers[1].name, "");
+        assert_eq!(parse_container_list(b"{\"message\":\"oops\"}"), None);
+
+        let inspect = |started: &str| format!(r#"{{"Id":"x","State":{{"Status":"running","StartedAt":"{}"}}}}"#, started);
+        assert_eq!(parse_started_at(inspect("2024-03-01T09:30:15.123456789Z").as_bytes()).as_deref(), Some("2024-03-01T09:30:15Z"));
+        assert_eq!(parse_started_at(inspect("0001-01-01T00:00:00Z").as_bytes()), None);
+
+        let raw = b"HTTP/1.0 204 No Content\r\nApi-Version: 1.45\r\n\r\n";
+        assert_eq!(parse_response(raw), Some((204, &b""[..])));
+        assert_eq!(parse_response(b"HTTP/1.0 200 OK\r\n\r\n[]"), Some((200, &b"[]"[..])));
+        assert_eq!(parse_response(b"garbage"), None);
+
+        assert!(docker::validate_id("web").is_ok());
+        assert!(docker::validate_id(CONTAINER_ID).is_ok());
+        for bad in ["", "../images/json", "web?force=1", "-x"] {
+            assert!(docker::validate_id(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_docker_config_from_env() {
+        let config = Config::from_lookup(|name| match name {
+            "TASKMGR_DOCKER_SOCKET" => Some("/nonexistent/docker.sock".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.docker, DockerConfig { enabled: false, socket: "/nonexistent/docker.sock".into() });
+
+        let config = Config::from_lookup(|name| match name {
+            "TASKMGR_DOCKER" => Some("on".to_string()),
+            "TASKMGR_DOCKER_SOCKET" => Some("/nonexistent/docker.sock".to_string()),
+            _ => None,
+        });
+        assert!(config.docker.enabled);
+
+        let config = Config::from_lookup(|name| (name == "TASKMGR_DOCKER").then(|| "off".to_string()));
+        assert!(!config.docker.enabled);
+        assert_eq!(config.docker.socket, std::path::PathBuf::from(docker::DEFAULT_SOCKET));
+
+        let config = Config::default().merge(&Cli::parse_from(["backend", "--docker"])).unwrap();
+        assert!(config.docker.enabled);
+    }
+
+    /// Answers Docker Engine API requests on a Unix socket: `web` is the
+    /// Java container and is running, `old` is stopped, anything else is unknown
+    #[cfg(unix)]
+    fn fake_docker_daemon(name: &str) -> std::path::PathBuf {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let path = std::env::temp_dir().join(format!("taskmgr-docker-{}-{}.sock", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request_line = String::from_utf8_lossy(&request).lines().next().unwrap_or_default().to_string();
+                let (status, body) = match request_line.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+                    ["GET", "/containers/json"] => (
+                        "200 OK",
+                        format!(r#"[{{"Id":"{}","Names":["/web"],"Image":"eclipse-temurin:21","State":"running"}}]"#, CONTAINER_ID),
+                    ),
+                    ["GET", path] if path == format!("/containers/{}/json", CONTAINER_ID) => {
+                        ("200 OK", r#"{"State":{"StartedAt":"2024-03-01T09:30:15.5Z"}}"#.to_string())
+                    }
+                    ["POST", "/containers/web/stop"] => ("204 No Content", String::new()),
+                    ["POST", "/containers/old/stop"] => ("304 Not Modified", String::new()),
+                    _ => ("404 Not Found", r#"{"message":"No such container"}"#.to_string()),
+                };
+                let response = format!("HTTP/1.0 {}\r\nContent-Type: application/json\r\n\r\n{}", status, body);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        path
+    }
+
+    fn docker_router(socket: std::path::PathBuf) -> Router {
+        let source = Arc::new(MockSystemSource::from_fixture(CONTAINER_HOST).unwrap());
+        let config = Config {
+            docker: DockerConfig { enabled: true, socket },
+            ..Default::default()
+        };
+        build_router(AppState::with_source(config, source))
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_containers_enriched_from_docker() {
+        let router = docker_router(fake_docker_daemon("enrich"));
+        let (status, body) = send(router.clone(), "GET", "/api/containers", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["docker_connected"], true);
+        let java = &body["containers"][0];
+        assert_eq!(java["container_id"], CONTAINER_ID);
+        assert_eq!(java["name"], "web");
+        assert_eq!(java["image"], "eclipse-temurin:21");
+        assert_eq!(java["state"], "running");
+        assert_eq!(java["started_at"], "2024-03-01T09:30:15Z");
+        // Not a Docker container: keeps its process name
+        let postgres = &body["containers"][1];
+        assert_eq!(postgres["name"], "postgres");
+        assert_eq!(postgres["image"], Value::Null);
+
+        let (status, body) = send(router.clone(), "POST", "/api/container/web/stop", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["message"], "Stopped container web");
+        let (status, body) = send(router.clone(), "POST", "/api/container/old/stop", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["message"], "Container old was already stopped");
+        let (status, body) = send(router.clone(), "POST", "/api/container/missing/stop", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "container_not_found");
+        let (status, _) = send(router, "POST", "/api/container/we$b/stop", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_containers_without_docker_daemon() {
+        let router = docker_router(std::env::temp_dir().join("taskmgr-no-such-docker.sock"));
+        let (status, body) = send(router.clone(), "GET", "/api/containers", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["docker_connected"], false);
+        assert_eq!(body["total_count"], 2);
+        assert_eq!(body["containers"][0]["name"], "tini");
+
+        let (status, body) = send(router, "POST", "/api/container/web/stop", None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["error"]["code"], "docker_unavailable");
+
+        let (router, _) = mock_router_with(Config { auth: AuthConfig { read_only: true, ..Default::default() }, ..Default::default() });
+        let (status, _) = send(router, "POST", "/api/container/web/stop", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_containers_grouped_and_filtered() {
+        let source = Arc::new(MockSystemSource::from_fixture(CONTAINER_HOST).unwrap());
+        let router = build_router(AppState::with_source(Config::default(), source));
+
+        let (status, body) = send(router.clone(), "GET", "/api/containers", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_count"], 2);
+        let java = &body["containers"][0];
+        assert_eq!(java["container_id"], CONTAINER_ID);
+        assert_eq!(java["name"], "tini");
+        assert_eq!(java["pids"], serde_json::json!([600, 610]));
+        assert_eq!(java["process_count"], 2);
+        assert_eq!(java["cpu_percent"].as_f64().unwrap(), 45.0);
+        assert_eq!(java["memory_mb"].as_f64().unwrap(), 501.0);
+        assert_eq!(body["containers"][1]["name"], "postgres");
+
+        let pids = |body: &Value| -> Vec<u64> {
+            let mut pids: Vec<u64> = body["processes"].as_array().unwrap().iter().map(|p| p["pid"].as_u64().unwrap()).collect();
+            pids.sort();
+            pids
+        };
+        let (_, body) = send(router.clone(), "GET", "/api/processes", None).await;
+        assert_eq!(pids(&body), [1, 500, 600, 610, 700]);
+        let (_, body) = send(router.clone(), "GET", "/api/processes?containers=exclude", None).await;
+        assert_eq!(pids(&body), [1, 500]);
+        assert!(body["processes"][0]["container_id"].is_null());
+        let (_, body) = send(router.clone(), "GET", "/api/processes?containers=only", None).await;
+        assert_eq!(pids(&body), [600, 610, 700]);
+        let (status, _) = send(router, "GET", "/api/processes?containers=sometimes", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (router, _) = mock_router();
+        let (_, body) = send(router, "GET", "/api/containers", None).await;
+        assert_eq!(body, serde_json::json!({"containers": [], "total_count": 0, "docker_connected": false}));
+    }
+
+    #[tokio::test]
+    async fn test_apps_grouped_by_process_tree() {
+        let source = Arc::new(MockSystemSource::from_fixture(PROCESS_TREE).unwrap());
+        let router = build_router(AppState::with_source(Config::default(), source));
+
+        let (status, body) = send(router.clone(), "GET", "/api/apps?group=tree", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let apps = body["apps"].as_array().unwrap();
+        let code = apps.iter().find(|app| app["name"] == "code").unwrap();
+        let mut pids: Vec<u64> = code["pids"].as_array().unwrap().iter().map(|p| p.as_u64().unwrap()).collect();
+        pids.sort();
+        assert_eq!(pids, vec![20, 21, 22]);
+        assert_eq!(code["exe"], "/usr/share/code/code");
+        assert_eq!(code["memory_mb"].as_f64().unwrap(), 60.0);
+        assert_eq!(code["cpu_percent"].as_f64().unwrap(), 10.0);
+        assert!(apps.iter().any(|app| app["name"] == "orphan" && app["process_count"] == 1));
+        // The cycle ends at the first repeated PID, so each side roots at the other
+        let looped: u64 = apps
+            .iter()
+            .filter(|app| app["name"] == "loop-a" || app["name"] == "loop-b")
+            .map(|app| app["process_count"].as_u64().unwrap())
+            .sum();
+        assert_eq!(looped, 2);
+        assert_eq!(body["total_count"], 6);
+
+        let (_, body) = send(router, "GET", "/api/apps", None).await;
+        assert_eq!(body["total_count"], 8);
+
+        let config = Config::from_lookup(|name| (name == "TASKMGR_SESSION_PROCESSES").then(|| "code, fish".to_string()));
+        assert_eq!(config.session_processes, vec!["code", "fish"]);
+        assert!(Config::default().session_processes.iter().any(|name| name == "explorer.exe"));
+    }
+
+    #[tokio::test]
+    async fn test_apps_aggregate_disk_and_network_rates() {
+        let fixture = r#"{
+            "cpu_count": 1,
+            "total_memory": 1073741824,
+            "processes": [
+                {"pid": 1, "name": "indexer", "cpu_usage": 1.0, "memory": 1048576, "status": "running", "disk_read_rate": 1048576.0, "disk_write_rate": 512.0},
+                {"pid": 2, "name": "indexer", "cpu_usage": 1.0, "memory": 1048576, "status": "running", "disk_read_rate": 2097152.0, "net_recv_rate": 2048.0},
+                {"pid": 3, "name": "browser", "cpu_usage": 50.0, "memory": 1048576, "status": "running", "net_recv_rate": 100.0, "net_sent_rate": 50.0},
+                {"pid": 4, "name": "idle", "cpu_usage": 0.0, "memory": 8388608, "status": "sleeping"}
+            ]
+        }"#;
+        let source = Arc::new(MockSystemSource::from_fixture(fixture).unwrap());
+        let router = build_router(AppState::with_source(Config::default(), source));
+
+        let (status, body) = send(router.clone(), "GET", "/api/apps?sort=disk", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let apps = body["apps"].as_array().unwrap();
+        let names: Vec<&str> = apps.iter().map(|a| a["name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["indexer", "browser", "idle"]);
+        let indexer = &apps[0];
+        assert_eq!(indexer["disk_read_rate"].as_f64().unwrap(), 3145728.0);
+        assert_eq!(indexer["disk_write_rate"].as_f64().unwrap(), 512.0);
+        assert_eq!(indexer["disk_read_rate_formatted"], "3.0 MB/s");
+        assert_eq!(indexer["net_recv_rate"].as_f64().unwrap(), 2048.0);
+        assert_eq!(indexer["net_recv_rate_formatted"], "2.0 KB/s");
+        assert!(indexer["net_sent_rate"].is_null());
+        assert!(apps[2]["net_recv_rate"].is_null() && apps[2]["net_recv_rate_formatted"].is_null());
+        assert_eq!(apps[2]["disk_read_rate_formatted"], "0 B/s");
+
+        let (_, body) = send(router.clone(), "GET", "/api/apps?sort=network", None).await;
+        assert_eq!(body["apps"][0]["name"], "indexer");
+        let (_, body) = send(router.clone(), "GET", "/api/apps?sort=memory", None).await;
+        assert_eq!(body["apps"][0]["name"], "idle");
+        let (_, body) = send(router.clone(), "GET", "/api/apps", None).await;
+        assert_eq!(body["apps"][0]["name"], "browser");
+        let (status, _) = send(router, "GET", "/api/apps?sort=bogus", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_process_info_reports_parent_and_children() {
+        let source = Arc::new(MockSystemSource::from_fixture(PROCESS_TREE).unwrap());
+        let router = build_router(AppState::with_source(Config::default(), source));
+
+        let (status, body) = send(router.clone(), "GET", "/api/process/10/info", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["parent_pid"], 1);
+        assert_eq!(body["parent_alive"], true);
+        assert_eq!(
+            body["children"],
+            serde_json::json!([{"pid": 20, "name": "code", "cpu_percent": 5.0, "memory_mb": 10.0}])
+        );
+
+        let (_, body) = send(router.clone(), "GET", "/api/process/30/info", None).await;
+        assert_eq!(body["parent_pid"], 999);
+        assert_eq!(body["parent_alive"], false);
+        assert_eq!(body["children"], serde_json::json!([]));
+        let (_, body) = send(router.clone(), "GET", "/api/process/1/info", None).await;
+        assert!(body["parent_pid"].is_null());
+        assert_eq!(body["parent_alive"], false);
+
+        let (_, body) = send(router, "GET", "/api/processes", None).await;
+        let processes = body["processes"].as_array().unwrap();
+        assert_eq!(processes.iter().find(|p| p["pid"] == 22).unwrap()["parent_pid"], 21);
+        assert!(processes.iter().find(|p| p["pid"] == 1).unwrap()["parent_pid"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_mock_kill_protected_pid_returns_403() {
+        let (router, source) = mock_router();
+        let (status, body) = send(router, "POST", "/api/process/1/kill", None).await;
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "process_protected");
+        assert!(source.killed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mock_kill_process() {
+        let (router, source) = mock_router();
+        let (status, body) = send(router, "POST", "/api/process/200/kill", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["message"], "Process bash terminated");
+        assert_eq!(source.killed(), vec![200]);
+    }
+
+    #[tokio::test]
+    async fn test_configured_names_are_protected() {
+        let (router, source) = mock_router_with(Config {
+            protected_processes: vec!["BASH".to_string()],
+            ..Default::default()
+        });
+        let (status, body) = send(router.clone(), "POST", "/api/process/200/kill", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "process_protected");
+        assert!(source.killed().is_empty());
+
+        let (_, body) = send(router, "GET", "/api/processes", None).await;
+        let processes = body["processes"].as_array().unwrap();
+        let bash = processes.iter().find(|p| p["pid"] == 200).unwrap();
+        assert_eq!(bash["is_protected"], true);
+        let chrome = processes.iter().find(|p| p["pid"] == 100).unwrap();
+        assert_eq!(chrome["is_protected"], false);
+    }
+
+    fn patterns(list: &[&str], style: MatchStyle) -> PatternList {
+        PatternList::new(&list.iter().map(|p| p.to_string()).collect::<Vec<_>>(), style).unwrap()
+    }
+
+    #[test]
+    fn test_kill_policy_pattern_case_sensitivity() {
+        let unix = patterns(&["lmgrd*", "/opt/*/bin/*"], MatchStyle::Unix);
+        assert!(unix.matches("lmgrd", None));
+        assert!(!unix.matches("LMGRD", None));
+        assert!(unix.matches("daemon", Some("/opt/flexlm/bin/daemon")));
+        assert!(unix.matches("daemon", Some("/opt/vendor/flexlm/bin/daemon")));
+        assert!(!unix.matches("daemon", Some("/OPT/flexlm/bin/daemon")));
+        assert!(!unix.matches("daemon", Some("/usr/bin/daemon")));
+
+        let windows = patterns(&["lmgrd*", r"C:\Program Files\*\lmgrd.exe"], MatchStyle::Windows);
+        assert!(windows.matches("LMGRD.EXE", None));
+        assert!(windows.matches("x.exe", Some(r"c:\program files\FlexLM\LMGRD.exe")));
+        assert!(windows.matches("x.exe", Some("C:/Program Files/FlexLM/lmgrd.exe")));
+        assert!(!windows.matches("x.exe", Some(r"D:\Program Files\FlexLM\lmgrd.exe")));
+
+        // A backslash is a glob escape on Unix but a separator on Windows
+        assert!(!patterns(&[r"dir\*"], MatchStyle::Unix).matches("x", Some(r"dir\file")));
+        assert!(patterns(&[r"dir\*"], MatchStyle::Windows).matches("x", Some(r"dir\file")));
+
+        assert!(PatternList::new(&["[unclosed".to_string()], MatchStyle::Unix).is_err());
+    }
+
+    fn policy_config(protected: &[&str], killable_only: &[&str]) -> Config {
+        let to_vec = |list: &[&str]| list.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        Config {
+            kill_policy: KillPolicy::new(&to_vec(protected), &to_vec(killable_only), MatchStyle::Unix).unwrap(),
+            ..auth_config(false)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kill_policy_enforced_even_with_force() {
+        let (router, source) = mock_router_with(policy_config(&["/usr/bin/ba*"], &[]));
+        let (status, body) = send_with_auth(router.clone(), "POST", "/api/process/200/kill?force=true", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "process_protected");
+        assert!(source.killed().is_empty());
+
+        let (_, body) = send(router.clone(), "GET", "/api/processes", None).await;
+        let bash = body["processes"].as_array().unwrap().iter().find(|p| p["pid"] == 200).unwrap().clone();
+        assert_eq!(bash["is_protected"], true);
+        let (_, body) = send(router, "GET", "/api/apps", None).await;
+        let apps = body["apps"].as_array().unwrap();
+        assert_eq!(apps.iter().find(|a| a["name"] == "bash").unwrap()["is_closeable"], false);
+        assert_eq!(apps.iter().find(|a| a["name"] == "chrome").unwrap()["is_closeable"], true);
+
+        let (router, source) = mock_router_with(policy_config(&[], &["chrome"]));
+        let (status, body) = send_with_auth(router.clone(), "POST", "/api/process/200/kill?force=true", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "kill_not_allowed");
+        assert_eq!(body["error"]["pid"], 200);
+        let (status, _) = send_with_auth(router.clone(), "POST", "/api/process/101/kill", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(source.killed(), vec![101]);
+
+        let (_, body) = send(router, "GET", "/api/policy", None).await;
+        assert_eq!(body["killable_only_names"], serde_json::json!(["chrome"]));
+        assert_eq!(body["protected_names"], serde_json::json!([]));
+        assert_eq!(body["protected_pids"], serde_json::json!([0, 1, 4]));
+        assert_eq!(body["case_sensitive"], true);
+    }
+
+    #[tokio::test]
+    async fn test_force_kill_needs_token() {
+        // Without auth configured there is no token to supply, so force is ignored
+        let (router, source) = mock_router();
+        let (status, _) = send(router, "POST", "/api/process/1/kill?force=true", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(source.killed().is_empty());
+
+        let (router, source) = mock_router_with(auth_config(false));
+        let (status, _) = send_with_auth(router.clone(), "POST", "/api/process/1/kill", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send_with_auth(router.clone(), "POST", "/api/process/1/kill?force=true", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(source.killed(), vec![1]);
+
+        let (_, body) = send(router, "GET", "/api/audit?limit=1", None).await;
+        assert_eq!(body[0]["action"], "force_kill");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_builtin_protection_rules() {
+        assert!(protection::is_system_process(1, Some("/sbin/init"), true, false));
+        assert!(protection::is_system_process(4, None, false, false));
+        assert!(protection::is_system_process(37, None, true, true));
+        assert!(protection::is_system_process(812, Some("/usr/sbin/sshd"), true, false));
+        assert!(protection::is_system_process(903, Some("/usr/lib/systemd/systemd-journald"), true, false));
+        // Same binary run by a normal user, or a root process outside system dirs
+        assert!(!protection::is_system_process(4012, Some("/usr/sbin/sshd"), false, false));
+        assert!(!protection::is_system_process(4013, Some("/usr/bin/python3"), true, false));
+    }
+
+    /// The fixture plus rows for this test process and its parent, which the
+    /// backend treats as itself
+    fn mock_router_with_self(config: Config) -> (Router, Arc<MockSystemSource>) {
+        let mut table: ProcessTable = serde_json::from_str(MOCK_SYSTEM).unwrap();
+        for &pid in protection::own_pids() {
+            let mut own = table.processes[1].clone();
+            own.pid = pid;
+            table.processes.push(own);
+        }
+        let source = Arc::new(MockSystemSource::new(table));
+        (build_router(AppState::with_source(config, source.clone())), source)
+    }
+
+    #[tokio::test]
+    async fn test_cannot_kill_self_even_with_force() {
+        let own_pid = std::process::id();
+        for uri in [format!("/api/process/{}/kill", own_pid), format!("/api/process/{}/kill?force=true", own_pid)] {
+            let (router, source) = mock_router_with_self(auth_config(false));
+            let (status, body) = send_with_auth(router, "POST", &uri, Some("Bearer s3cret")).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+            assert_eq!(body["error"]["code"], "cannot_kill_self");
+            assert_eq!(body["error"]["pid"], own_pid);
+            assert!(source.killed().is_empty());
+        }
+
+        let (router, source) = mock_router_with_self(auth_config(false));
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/app/close?force=true")
+            .header(header::AUTHORIZATION, "Bearer s3cret")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!("[{}, 200]", own_pid)))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(source.killed().is_empty());
+
+        let parent_pid = protection::own_pids()[1];
+        let (router, source) = mock_router_with_self(Config::default());
+        let (status, body) = send(router, "POST", &format!("/api/process/{}/kill", parent_pid), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "cannot_kill_self");
+        assert!(source.killed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restart_checks_before_killing() {
+        let mut table: ProcessTable = serde_json::from_str(MOCK_SYSTEM).unwrap();
+        table.processes[4].cmd.clear();
+        let source = Arc::new(MockSystemSource::new(table));
+        let router = build_router(AppState::with_source(Config::default(), source.clone()));
+        let (status, body) = send(router.clone(), "POST", "/api/process/200/restart", None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"]["code"], "cannot_restart_unknown_command");
+        assert_eq!(body["error"]["pid"], 200);
+
+        let (status, body) = send(router.clone(), "POST", "/api/process/1/restart", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "process_protected");
+        let (status, _) = send(router, "POST", "/api/process/999999/restart", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(source.killed().is_empty());
+
+        let (router, source) = mock_router_with_self(Config::default());
+        let (status, body) = send(router, "POST", &format!("/api/process/{}/restart", std::process::id()), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "cannot_kill_self");
+        assert!(source.killed().is_empty());
+
+        let (router, source) = mock_router_with(Config { auth: AuthConfig { read_only: true, ..Default::default() }, ..Default::default() });
+        let (status, _) = send(router, "POST", "/api/process/100/restart", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(source.killed().is_empty());
+    }
+
+    /// Restarts a real child of the test process; `script` runs under `sh -c`
+    #[cfg(target_os = "linux")]
+    async fn restart_child(script: &str, timeout_ms: u64) -> Value {
+        let mut child = std::process::Command::new("sh").args(["-c", script]).spawn().unwrap();
+        // Let sh exec into sleep so that is what gets captured
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let uri = format!("/api/process/{}/restart?timeout_ms={}", child.id(), timeout_ms);
+        let (status, body) = send(router(), "POST", &uri, None).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert!(child.try_wait().unwrap().is_some(), "old process is gone");
+
+        let new_pid = body["new_pid"].as_u64().unwrap();
+        assert_eq!(body["old_pid"], child.id());
+        assert_eq!(body["info_url"], format!("/api/v1/process/{}/info", new_pid));
+        let cmdline = std::fs::read_to_string(format!("/proc/{}/cmdline", new_pid)).unwrap();
+        let exe = std::fs::read_link(format!("/proc/{}/exe", new_pid)).unwrap();
+        assert_eq!(cmdline, format!("{}\x0030\x00", exe.display()), "relaunched with exe and the same arguments");
+        // SAFETY: plain syscall on the process just relaunched
+        unsafe { libc::kill(new_pid as i32, libc::SIGKILL) };
+        body
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_restart_relaunches_same_command() {
+        let body = restart_child("exec sleep 30", 5_000).await;
+        assert_eq!(body["name"], "sleep");
+        assert_eq!(body["escalated"], false);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_restart_escalates_when_sigterm_ignored() {
+        // Ignored signals stay ignored across exec
+        let body = restart_child("trap '' TERM; exec sleep 30", 300).await;
+        assert_eq!(body["escalated"], true);
+    }
+
+    #[tokio::test]
+    async fn test_processes_mark_self() {
+        let (router, _) = mock_router_with_self(Config::default());
+        let (_, body) = send(router, "GET", "/api/processes", None).await;
+        let processes = body["processes"].as_array().unwrap();
+        let own: Vec<u64> = protection::own_pids().iter().map(|&pid| pid as u64).collect();
+        for process in processes {
+            let pid = process["pid"].as_u64().unwrap();
+            assert_eq!(process["is_self"], own.contains(&pid), "{}", pid);
+        }
+        assert!(processes.iter().any(|p| p["pid"] == std::process::id() && p["is_self"] == true));
+    }
+
+    #[tokio::test]
+    async fn test_mock_kill_app_refuses_group_with_protected_member() {
+        let (router, source) = mock_router();
+        let (status, body) = send(router.clone(), "POST", "/api/app/close", Some("[1, 100, 101]")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "app_not_closeable");
+        assert_eq!(body["error"]["pid"], 1);
+        assert!(source.killed().is_empty());
+
+        let (_, body) = send(router.clone(), "GET", "/api/apps", None).await;
+        let apps = body["apps"].as_array().unwrap();
+        assert_eq!(apps.iter().find(|a| a["name"] == "systemd").unwrap()["is_closeable"], false);
+        assert_eq!(apps.iter().find(|a| a["name"] == "chrome").unwrap()["is_closeable"], true);
+
+        let (status, body) = send(router, "POST", "/api/app/close", Some("[100, 101]")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["message"], "Terminated 2 process(es)");
+        assert_eq!(source.killed(), vec![100, 101]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_kill_reports_each_pid() {
+        let (router, source) = mock_router();
+        let body = r#"{"pids": [100, 1, 99999, 100, 200], "signal": 15}"#;
+        let (status, body) = send(router, "POST", "/api/processes/batch-kill", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["killed"], serde_json::json!([100, 200]));
+        assert_eq!(body["not_found"], serde_json::json!([99999]));
+        assert_eq!(body["forbidden"], serde_json::json!([1]));
+        assert_eq!(body["errors"], serde_json::json!({}));
+        assert_eq!(source.killed(), vec![100, 200]);
+    }
+
+    #[tokio::test]
+    async fn test_kill_by_name_dry_run_then_kill() {
+        let (router, source) = mock_router();
+        let (status, body) = send(router.clone(), "POST", "/api/processes/kill_by_name", Some(r#"{"name": "chrome", "dry_run": true}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["dry_run"], true);
+        assert_eq!(body["would_kill"], 3);
+        assert_eq!(body["killed"], 0);
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results.iter().map(|r| r["pid"].as_u64().unwrap()).collect::<Vec<_>>(), [100, 101, 102]);
+        assert!(results.iter().all(|r| r["outcome"] == "would_kill" && r["reason"].is_null()));
+        assert!(source.killed().is_empty());
+
+        // Exact matches the full exe path too
+        let (_, body) = send(router.clone(), "POST", "/api/processes/kill_by_name", Some(r#"{"name": "/opt/google/chrome/chrome", "match": "exact"}"#)).await;
+        assert_eq!(body["killed"], 3);
+        assert_eq!(source.killed(), vec![100, 101, 102]);
+
+        let (status, body) = send(router, "POST", "/api/processes/kill_by_name", Some(r#"{"name": "chrome"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], serde_json::json!([]));
+        assert_eq!(body["killed"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_kill_by_name_glob_skips_protected_and_self() {
+        let (router, source) = mock_router_with_self(Config::default());
+        let (status, body) = send(router, "POST", "/api/processes/kill_by_name", Some(r#"{"name": "*", "match": "glob"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        let outcome = |pid: u32| {
+            let result = body["results"].as_array().unwrap().iter().find(|r| r["pid"] == pid).unwrap();
+            (result["outcome"].as_str().unwrap().to_string(), result["reason"].clone())
+        };
+        assert_eq!(outcome(1), ("skipped".to_string(), serde_json::json!("process_protected")));
+        assert_eq!(outcome(std::process::id()), ("skipped".to_string(), serde_json::json!("cannot_kill_self")));
+        assert_eq!(outcome(200), ("killed".to_string(), Value::Null));
+        assert_eq!(body["killed"], 4);
+        assert_eq!(body["skipped"], 1 + protection::own_pids().len());
+        assert_eq!(source.killed(), vec![100, 101, 102, 200]);
+    }
+
+    #[tokio::test]
+    async fn test_kill_by_name_validates_request() {
+        for body in [r#"{"name": " "}"#, r#"{"name": "[oops", "match": "glob"}"#, r#"{"name": "x", "match": "regex"}"#] {
+            let (router, source) = mock_router();
+            let (status, _) = send(router, "POST", "/api/processes/kill_by_name", Some(body)).await;
+            assert!(status == StatusCode::BAD_REQUEST || status == StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+            assert!(source.killed().is_empty());
+        }
+        let (router, _) = mock_router_with(Config { auth: AuthConfig { read_only: true, ..Default::default() }, ..Default::default() });
+        let (status, _) = send(router, "POST", "/api/processes/kill_by_name", Some(r#"{"name": "chrome"}"#)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_batch_kill_validates_request() {
+        let (router, source) = mock_router();
+        let too_many = serde_json::json!({ "pids": (1..=1001).collect::<Vec<u32>>() }).to_string();
+        for body in [r#"{"pids": []}"#, too_many.as_str(), r#"{"pids": [100], "signal": 42}"#] {
+            let (status, response) = send(router.clone(), "POST", "/api/processes/batch-kill", Some(body)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", &body[..body.len().min(40)]);
+            assert_eq!(response["error"]["code"], "invalid_body");
+        }
+        assert!(source.killed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_process_groups_listing() {
+        let (router, source) = mock_router();
+        source.set_process_group(&[1], 1);
+        source.set_process_group(&[100, 101, 102], 100);
+        source.set_process_group(&[200], 200);
+
+        let (status, body) = send(router, "GET", "/api/v1/process-groups", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_count"], 3);
+        let chrome = &body["groups"][0];
+        assert_eq!(chrome["pgid"], 100);
+        assert_eq!(chrome["session_leader_pid"], 100);
+        assert_eq!(chrome["leader_name"], "chrome");
+        assert_eq!(chrome["pids"], serde_json::json!([100, 101, 102]));
+        assert_eq!(chrome["process_count"], 3);
+        assert_eq!(chrome["memory_mb"], 600.0);
+        let pgids: Vec<u64> = body["groups"].as_array().unwrap().iter().map(|g| g["pgid"].as_u64().unwrap()).collect();
+        assert_eq!(pgids, [100, 1, 200]);
+    }
+
+    #[tokio::test]
+    async fn test_kill_process_group() {
+        let (router, source) = mock_router();
+        source.set_process_group(&[1], 1);
+        source.set_process_group(&[100, 101, 102], 100);
+
+        let (status, body) = send(router.clone(), "POST", "/api/v1/process-group/100/kill?signal=15", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!({"pgid": 100, "affected_pids": [100, 101, 102], "signal": 15}));
+        assert_eq!(source.killed(), vec![100, 101, 102]);
+        let (_, audit) = send(router.clone(), "GET", "/api/v1/audit", None).await;
+        assert_eq!(audit.as_array().unwrap().len(), 3);
+
+        let (status, body) = send(router.clone(), "POST", "/api/v1/process-group/100/kill", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "process_group_not_found");
+
+        // One protected member refuses the whole group
+        let (status, body) = send(router.clone(), "POST", "/api/v1/process-group/1/kill", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "process_protected");
+
+        let (status, body) = send(router, "POST", "/api/v1/process-group/1/kill?signal=42", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["fields"][0]["field"], "signal");
+        assert_eq!(source.killed(), vec![100, 101, 102]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_process_group_signals_every_member() {
+        use std::os::unix::process::{CommandExt, ExitStatusExt};
+
+        let mut leader = std::process::Command::new("sh")
+            .args(["-c", "sleep 30 & sleep 30 & wait"])
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let pgid = leader.id();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+        let uri = format!("/api/v1/process-group/{}/kill?signal=15", pgid);
+        let (status, body) = send(router(), "POST", &uri, None).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["affected_pids"].as_array().unwrap().len(), 3, "{}", body);
+        assert_eq!(body["affected_pids"][0], pgid);
+        assert_eq!(leader.wait().unwrap().signal(), Some(15));
+    }
+
+    #[tokio::test]
+    async fn test_user_apps_only() {
+        let own_pid = std::process::id();
+        let fixture = format!(
+            r#"{{
+                "cpu_count": 1,
+                "total_memory": 1073741824,
+                "processes": [
+                    {{"pid": 1, "name": "systemd", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "exe": "/usr/lib/systemd/systemd", "user_id": "0"}},
+                    {{"pid": 10, "name": "pulseaudio", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "exe": "/usr/bin/pulseaudio", "user_id": "1000"}},
+                    {{"pid": 20, "name": "mytool", "cpu_usage": 0.0, "memory": 1048576, "status": "running", "exe": "/home/dev/bin/mytool", "user_id": "1001"}},
+                    {{"pid": 30, "name": "cron", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "exe": "/usr/sbin/cron", "user_id": "0"}},
+                    {{"pid": 40, "name": "hidden", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "user_id": "0"}},
+                    {{"pid": {}, "name": "taskmgr", "cpu_usage": 0.0, "memory": 1048576, "status": "running", "exe": "/usr/local/bin/taskmgr", "user_id": "1000"}}
+                ]
+            }}"#,
+            own_pid
+        );
+        let source = Arc::new(MockSystemSource::from_fixture(&fixture).unwrap());
+        let router = build_router(AppState::with_source(Config::default(), source));
+
+        let (_, body) = send(router.clone(), "GET", "/api/apps", None).await;
+        assert_eq!(body["total_count"], 6);
+        let (status, body) = send(router, "GET", "/api/apps?user_apps_only=true", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut names: Vec<&str> = body["apps"].as_array().unwrap().iter().map(|a| a["name"].as_str().unwrap()).collect();
+        names.sort();
+        assert_eq!(names, vec!["mytool", "pulseaudio", "taskmgr"]);
+    }
+
+    #[tokio::test]
+    async fn test_suspend_and_resume_app() {
+        let (router, source) = mock_router();
+        let (status, body) = send(router.clone(), "POST", "/api/app/suspend", Some("[100, 101, 999999, 1, 102]")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["suspended"], serde_json::json!([100, 101, 102]));
+        let failed = body["failed"].as_array().unwrap();
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0]["pid"], 999999);
+        assert_eq!(failed[0]["reason"], "Process 999999 not found");
+        assert_eq!(failed[1]["pid"], 1);
+
+        let (_, body) = send(router.clone(), "GET", "/api/apps", None).await;
+        let apps = body["apps"].as_array().unwrap();
+        assert_eq!(apps.iter().find(|a| a["name"] == "chrome").unwrap()["status"], "stopped");
+        assert_eq!(apps.iter().find(|a| a["name"] == "systemd").unwrap()["status"], "running");
+
+        let (status, body) = send(router.clone(), "POST", "/api/app/resume", Some("[101]")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["resumed"], serde_json::json!([101]));
+        assert_eq!(body["failed"], serde_json::json!([]));
+        let (_, body) = send(router.clone(), "GET", "/api/apps", None).await;
+        let chrome = body["apps"].as_array().unwrap().iter().find(|a| a["name"] == "chrome").unwrap().clone();
+        assert_eq!(chrome["status"], "running");
+
+        let (status, body) = send(router.clone(), "POST", "/api/process/200/suspend", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["message"], "Process bash suspended");
+        let (status, body) = send(router.clone(), "POST", "/api/process/1/resume", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "process_protected");
+        assert!(source.killed().is_empty());
+
+        let (_, body) = send(router, "GET", "/api/audit?limit=1", None).await;
+        assert_eq!(body[0]["action"], "resume");
+        assert_eq!(body[0]["outcome"], "process_protected");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_applies_to_mutations_only() {
+        let (router, _) = mock_router_with(Config {
+            rate_limit: Some(RateLimitConfig { per_second: 1, burst: 2 }),
+            ..Default::default()
+        });
+        for _ in 0..2 {
+            let (status, _) = send(router.clone(), "POST", "/api/process/999999/kill", None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+        }
+
+        let response = router
+            .clone()
+            .oneshot(Request::builder().method("POST").uri("/api/app/close").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["error"]["code"], "rate_limited");
+
+        for _ in 0..20 {
+            let (status, _) = send(router.clone(), "GET", "/api/process/200/info", None).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_state_is_bounded() {
+        let limiter = RateLimiter::new(5, 1).max_clients(2);
+        for last_octet in 1..=50u8 {
+            let ip = std::net::IpAddr::from([10, 0, 0, last_octet]);
+            assert!(limiter.check(Some(ip)).is_ok());
+            assert!(limiter.check(Some(ip)).is_err());
+        }
+        assert_eq!(limiter.tracked_clients(), 2);
+
+        let env = |name: &str| (name == "TASKMGR_RATE_LIMIT").then(|| "off".to_string());
+        assert_eq!(Config::from_lookup(env).rate_limit, None);
+        let cli = Cli::try_parse_from(["task_manager_backend", "--rate-limit", "20", "--rate-limit-burst", "40"]).unwrap();
+        assert_eq!(Config::from_lookup(env).merge(&cli).unwrap().rate_limit, Some(RateLimitConfig { per_second: 20, burst: 40 }));
+    }
+
+    #[tokio::test]
+    async fn test_audit_records_kills_newest_first() {
+        let (router, _) = mock_router_with(auth_config(false));
+        let (status, _) = send_with_auth(router.clone(), "POST", "/api/process/1/kill", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send_with_auth(router.clone(), "POST", "/api/process/200/kill", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = send(router.clone(), "GET", "/api/audit", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let entries = body.as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["action"], "kill");
+        assert_eq!(entries[0]["pid"], 200);
+        assert_eq!(entries[0]["process_name"], "bash");
+        assert_eq!(entries[0]["outcome"], "success");
+        assert_eq!(entries[0]["token_id"], audit::token_id("s3cret"));
+        assert_ne!(entries[0]["token_id"], "s3cret");
+        assert_eq!(entries[1]["pid"], 1);
+        assert_eq!(entries[1]["outcome"], "process_protected");
+
+        let (_, body) = send(router, "GET", "/api/audit?limit=1", None).await;
+        assert_eq!(body.as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_file_and_unwritable_fallback() {
+        let path = std::env::temp_dir().join(format!("taskmgr-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = AuditLog::default();
+        let shutdown = CancellationToken::new();
+        let writer = tokio::spawn(log.writer(path.clone(), shutdown.clone()));
+
+        let actor = audit::Actor::default();
+        log.record(AuditEntry::new("kill", 42, Some("sleep".to_string()), &Ok::<(), ApiError>(()), &actor));
+        shutdown.cancel();
+        writer.await.unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let line: Value = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+        assert_eq!(line["pid"], 42);
+        std::fs::remove_file(&path).unwrap();
+
+        // A directory can't be opened for appending; entries stay in memory
+        let log = AuditLog::default();
+        log.writer(std::env::temp_dir(), CancellationToken::new()).await;
+        log.record(AuditEntry::new("kill", 7, None, &Err::<(), _>(ApiError::ProcessNotFound { pid: 7 }), &actor));
+        assert_eq!(log.recent(10)[0].outcome, "process_not_found");
+    }
+
+    fn start_router(allowed: &[&str]) -> Router {
+        mock_router_with(Config {
+            allowed_commands: allowed.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        })
+        .0
+    }
+
+    #[tokio::test]
+    async fn test_start_rejects_command_not_in_allowlist() {
+        let (status, body) = send(start_router(&["ping"]), "POST", "/api/process/start", Some(r#"{"command": "rm", "args": ["-rf", "/tmp/x"]}"#)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "command_not_allowed");
+    }
+
+    #[tokio::test]
+    async fn test_start_rejects_loader_and_path_env() {
+        for name in ["LD_PRELOAD", "LD_LIBRARY_PATH", "LD_AUDIT", "DYLD_INSERT_LIBRARIES", "PATH", "Path", "ld_preload"] {
+            let request = format!(r#"{{"command": "true", "env": {{"{name}": "/tmp/evil"}}}}"#);
+            let (status, body) = send(start_router(&["true"]), "POST", "/api/process/start", Some(&request)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{name}");
+            assert_eq!(body["error"]["code"], "env_not_allowed", "{name}");
+        }
+
+        // Not on the allowlist still reads as such, whatever the env
+        let (status, body) = send(start_router(&[]), "POST", "/api/process/start", Some(r#"{"command": "true", "env": {"LD_PRELOAD": "x"}}"#)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "command_not_allowed");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_start_detached_returns_pid() {
+        let (status, body) = send(start_router(&["true"]), "POST", "/api/process/start", Some(r#"{"command": "true"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+        assert!(body["pid"].as_u64().unwrap() > 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_start_wait_streams_output_over_sse() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/process/start")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"command": "sh", "args": ["-c", "echo $GREETING; echo oops >&2"], "env": {"GREETING": "hello"}, "wait": true}"#))
+            .unwrap();
+        let response = start_router(&["sh"]).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with("event: started\n"));
+        assert!(body.contains("event: stdout\ndata: hello\n"));
+        assert!(body.contains("event: stderr\ndata: oops\n"));
+        assert!(body.ends_with("event: exit\ndata: {\"code\":0}\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_start_reports_missing_binary() {
+        let (status, body) = send(start_router(&["no-such-binary-xyz"]), "POST", "/api/process/start", Some(r#"{"command": "no-such-binary-xyz"}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "spawn_failed");
+    }
+
+    /// Serves `router` on a loopback port for WebSocket clients
+    async fn serve(router: Router) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router.into_make_service_with_connect_info::<std::net::SocketAddr>();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    /// Text frames until the server closes the socket
+    async fn output_frames(addr: std::net::SocketAddr, pid: u64) -> Vec<Value> {
+        use futures::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let url = format!("ws://{}/api/v1/ws/process/{}/output", addr, pid);
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let mut frames = Vec::new();
+        while let Some(Ok(message)) = tokio::time::timeout(std::time::Duration::from_secs(10), socket.next()).await.unwrap() {
+            match message {
+                Message::Text(text) => frames.push(serde_json::from_str(&text).unwrap()),
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+        frames
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_process_output_websocket() {
+        let router = start_router(&["sh"]);
+        let request = r#"{"command": "sh", "args": ["-c", "sleep 0.5; echo hi; echo oops >&2; exit 3"]}"#;
+        let (status, started) = send(router.clone(), "POST", "/api/v1/process/start", Some(request)).await;
+        assert_eq!(status, StatusCode::OK);
+        let addr = serve(router).await;
+        let pid = started["pid"].as_u64().unwrap();
+
+        let frames = output_frames(addr, pid).await;
+        assert!(frames.contains(&serde_json::json!({"stream": "stdout", "line": "hi"})), "{:?}", frames);
+        assert!(frames.contains(&serde_json::json!({"stream": "stderr", "line": "oops"})), "{:?}", frames);
+        assert_eq!(frames.last(), Some(&serde_json::json!({"event": "exit", "code": 3})));
+
+        // Forgotten once it has exited
+        let error = tokio_tungstenite::connect_async(format!("ws://{}/api/v1/ws/process/{}/output", addr, pid)).await.unwrap_err();
+        let tokio_tungstenite::tungstenite::Error::Http(response) = error else {
+            panic!("expected an HTTP error, got {:?}", error);
+        };
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_output_follows_redirected_file() {
+        let addr = serve(mock_router().0).await;
+        let log = std::env::temp_dir().join(format!("output-follow-{}.log", std::process::id()));
+        let file = std::fs::File::create(&log).unwrap();
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "echo before; sleep 0.5; echo after; sleep 0.3"])
+            .stdout(file)
+            .spawn()
+            .unwrap();
+
+        // Only lines written after connecting are sent
+        let frames = output_frames(addr, child.id() as u64).await;
+        child.wait().unwrap();
+        std::fs::remove_file(&log).unwrap();
+        assert_eq!(
+            frames,
+            [serde_json::json!({"stream": "stdout", "line": "after"}), serde_json::json!({"event": "exit", "code": null})]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_output_refuses_pipes() {
+        let addr = serve(mock_router().0).await;
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let error = tokio_tungstenite::connect_async(format!("ws://{}/api/v1/ws/process/{}/output", addr, child.id())).await.unwrap_err();
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let tokio_tungstenite::tungstenite::Error::Http(response) = error else {
+            panic!("expected an HTTP error, got {:?}", error);
+        };
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body: Value = serde_json::from_slice(response.body().as_deref().unwrap()).unwrap();
+        assert_eq!(body["error"]["code"], "output_not_available");
+    }
+
+    fn launch_router(allowed: Option<&[&str]>) -> Router {
+        mock_router_with(Config {
+            launch_executables: allowed.map(|list| list.iter().map(|c| c.to_string()).collect()),
+            ..Default::default()
+        })
+        .0
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_launch_detached_in_own_session() {
+        let (status, body) = send(launch_router(None), "POST", "/api/process/launch", Some(r#"{"command": "sleep", "args": ["5"], "cwd": "/"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        let pid = body["pid"].as_u64().unwrap() as i32;
+        assert_eq!(body["info_url"], format!("/api/v1/process/{}/info", pid));
+        // SAFETY: getsid only reads the session of `pid`
+        let session = unsafe { libc::getsid(pid) };
+        assert_eq!(session, pid);
+        // SAFETY: plain syscall on the child just launched
+        unsafe { libc::kill(pid, libc::SIGKILL) };
+    }
+
+    #[tokio::test]
+    async fn test_launch_restricted_to_launch_executables() {
+        let (status, body) = send(launch_router(Some(&["firefox"])), "POST", "/api/process/launch", Some(r#"{"command": "rm"}"#)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "executable_not_allowed");
+
+        let path = write_temp_config("launch", "launch_executables = [\"firefox\"]\n");
+        let cli = Cli { config: Some(path.clone()), ..Cli::default() };
+        assert_eq!(Config::default().merge(&cli).unwrap().launch_executables, Some(vec!["firefox".to_string()]));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_launch_reports_spawn_errors() {
+        let (status, body) = send(launch_router(None), "POST", "/api/process/launch", Some(r#"{"command": "no-such-binary-xyz"}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "spawn_failed");
+        assert!(body["error"]["message"].as_str().unwrap().contains("No such file"));
+
+        // A directory can never be executed
+        let dir = std::env::temp_dir().to_string_lossy().into_owned();
+        let request = serde_json::json!({ "command": dir }).to_string();
+        let (status, body) = send(launch_router(None), "POST", "/api/process/launch", Some(&request)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "permission_denied");
+    }
+
+    #[tokio::test]
+    async fn test_launch_disabled_in_read_only_mode() {
+        let (router, _) = mock_router_with(Config { auth: AuthConfig { read_only: true, ..Default::default() }, ..Default::default() });
+        let (status, body) = send(router, "POST", "/api/process/launch", Some(r#"{"command": "true"}"#)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "read_only");
+    }
+
+    #[tokio::test]
+    async fn test_mock_process_info() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/process/200/info", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "bash");
+        assert_eq!(body["cwd"], "/home/user");
+        assert_eq!(body["memory_info"]["rss_formatted"], "4.0 MB");
+        assert!(body["pss_kb"].is_null());
+        assert!(body["uss_kb"].is_null());
+        assert!(body["namespaces"].is_null());
+        assert!(body["seccomp_status"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_poller_publishes_fresh_stats() {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let config = Config {
+            poll_interval: std::time::Duration::from_millis(10),
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source.clone());
+        let router = build_router(state.clone());
+
+        let (_, before) = send(router.clone(), "GET", "/api/stats", None).await;
+        let poller = state.spawn_poller();
+        source.kill(102).unwrap(); // frees 300 MB
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let (_, after) = send(router, "GET", "/api/stats", None).await;
+        poller.abort();
+
+        let freed = before["memory"]["used"].as_u64().unwrap() - after["memory"]["used"].as_u64().unwrap();
+        assert_eq!(freed, 314572800);
+    }
+
+    fn alert_rule(name: &str, metric: AlertMetric, threshold: f64, sustained_secs: u64) -> AlertRule {
+        AlertRule { name: name.to_string(), metric, operator: Comparison::Above, threshold, sustained_secs, process: None, webhook: None }
+    }
+
+    #[test]
+    fn test_alert_fires_only_after_sustain_window() {
+        let engine = AlertEngine::new(vec![alert_rule("cpu-hot", AlertMetric::CpuPercent, 90.0, 60)], None);
+        let feed = |samples: &[(u64, f64)]| {
+            for &(at, value) in samples {
+                engine.observe(at, |_| Some(value));
+            }
+        };
+
+        // A spike, and a breach that ends before the window, never fire
+        feed(&[(0, 99.0), (10, 50.0), (20, 95.0), (70, 95.0), (75, 89.0)]);
+        assert!(engine.active().is_empty());
+        assert!(engine.history(10).is_empty());
+
+        feed(&[(100, 95.0), (130, 96.0), (160, 97.0)]);
+        let active = engine.active();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].rule, "cpu-hot");
+        assert_eq!(active[0].triggered_at, 160);
+        assert_eq!(active[0].resolved_at, None);
+
+        feed(&[(170, 98.5)]);
+        assert_eq!(engine.active()[0].current_value, 98.5);
+        assert_eq!(engine.active()[0].triggered_at, 160);
+
+        feed(&[(180, 40.0)]);
+        assert!(engine.active().is_empty());
+        let history = engine.history(10);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].resolved_at, Some(180));
+        assert_eq!(history[0].current_value, 98.5);
+
+        // Firing again is a new alert
+        let engine_now = AlertEngine::new(vec![alert_rule("any", AlertMetric::CpuPercent, 90.0, 0)], None);
+        engine_now.observe(5, |_| Some(91.0));
+        assert_eq!(engine_now.active()[0].triggered_at, 5);
+        engine_now.observe(6, |_| None);
+        engine_now.observe(7, |_| Some(92.0));
+        assert_eq!(engine_now.active()[0].id, 2);
+    }
+
+    #[test]
+    fn test_alert_rule_values() {
+        let source = MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap();
+        let stats = source.snapshot_stats();
+        let table = source.list_processes();
+
+        let mut rule = alert_rule("chrome-cpu", AlertMetric::CpuPercent, 40.0, 0);
+        rule.process = Some("Chrome".to_string());
+        assert_eq!(rule.value(&stats, Some(&table)), Some(40.5));
+        assert_eq!(rule.value(&stats, None), None);
+        rule.metric = AlertMetric::MemoryPercent;
+        assert_eq!(rule.value(&stats, Some(&table)), Some(600.0 / 8192.0 * 100.0));
+        rule.process = Some("firefox".to_string());
+        assert_eq!(rule.value(&stats, Some(&table)), None);
+
+        let machine = alert_rule("ram", AlertMetric::MemoryPercent, 90.0, 0);
+        assert_eq!(machine.value(&stats, None), Some(stats.memory.percent as f64));
+        assert!(Comparison::AtMost.holds(5.0, 5.0));
+        assert!(!Comparison::Below.holds(5.0, 5.0));
+    }
+
+    #[test]
+    fn test_alert_rules_from_config_file() {
+        let path = write_temp_config(
+            "alerts",
+            "[[alerts]]\nname = \"cpu-hot\"\nmetric = \"cpu_percent\"\noperator = \">\"\nthreshold = 90\nsustained_secs = 60\n\n\
+             [[alerts]]\nname = \"chrome-ram\"\nmetric = \"memory_percent\"\noperator = \">=\"\nthreshold = 25.5\nprocess = \"chrome\"\n",
+        );
+        let cli = Cli { config: Some(path.clone()), ..Cli::default() };
+        let rules = Config::default().merge(&cli).unwrap().alert_rules;
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0], alert_rule("cpu-hot", AlertMetric::CpuPercent, 90.0, 60));
+        assert_eq!(rules[1].operator, Comparison::AtLeast);
+        assert_eq!(rules[1].process.as_deref(), Some("chrome"));
+
+        let rejected = |contents: &str| {
+            let path = write_temp_config("alerts-bad", contents);
+            let result = Config::default().merge(&Cli { config: Some(path.clone()), ..Cli::default() });
+            std::fs::remove_file(path).unwrap();
+            result.unwrap_err().to_string()
+        };
+        let rule = "[[alerts]]\nname = \"a\"\nmetric = \"disk_percent\"\noperator = \">\"\nthreshold = 95\n";
+        assert!(rejected(&format!("{rule}{rule}")).contains("duplicate name"));
+        assert!(rejected(&format!("{rule}process = \"postgres\"\n")).contains("scoped to a process"));
+        assert!(rejected(&rule.replace(">", "!=")).contains("invalid config file"));
+        assert!(rejected(&format!("{rule}webhook = {{ url = \"hooks.example.com\" }}\n")).contains("not an http(s) URL"));
+        assert!(rejected("[alert_webhook]\nurl = \"https://h.example/x\"\nformat = \"teams\"\n").contains("invalid config file"));
+
+        let path = write_temp_config("alert-webhook", &format!("[alert_webhook]\nurl = \"https://hooks.slack.com/x\"\nformat = \"slack\"\n\n{rule}webhook = {{ url = \"http://ops.example/hook\" }}\n"));
+        let config = Config::default().merge(&Cli { config: Some(path.clone()), ..Cli::default() }).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(config.alert_webhook.unwrap().format, WebhookFormat::Slack);
+        assert_eq!(config.alert_rules[0].webhook.as_ref().unwrap().format, WebhookFormat::Json);
+    }
+
+    #[tokio::test]
+    async fn test_alerts_endpoints() {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let config = Config {
+            alert_rules: vec![alert_rule("cpu-hot", AlertMetric::CpuPercent, 90.0, 0), alert_rule("ram", AlertMetric::MemoryPercent, 90.0, 0)],
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source);
+        let router = build_router(state.clone());
+        state.alerts.observe(100, |_| Some(95.0));
+        state.alerts.observe(110, |rule| Some(if rule.name == "ram" { 10.0 } else { 96.0 }));
+
+        let (status, body) = send(router.clone(), "GET", "/api/alerts?active=true", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["rule"], "cpu-hot");
+        assert_eq!(body[0]["current_value"], 96.0);
+        assert_eq!(body[0]["resolved_at"], Value::Null);
+
+        let (_, body) = send(router.clone(), "GET", "/api/alerts", None).await;
+        assert_eq!(body.as_array().unwrap().len(), 2);
+        assert_eq!(body[1]["rule"], "ram");
+        assert_eq!(body[1]["resolved_at"], 110);
+
+        let (_, body) = send(router.clone(), "GET", "/api/alerts/history", None).await;
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        let (_, body) = send(router, "GET", "/api/alerts/history?limit=0", None).await;
+        assert_eq!(body, serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_poller_evaluates_alert_rules() {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let mut rule = alert_rule("chrome-cpu", AlertMetric::CpuPercent, 40.0, 0);
+        rule.process = Some("chrome".to_string());
+        let config = Config {
+            poll_interval: std::time::Duration::from_millis(10),
+            alert_rules: vec![rule],
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source.clone());
+        let poller = state.spawn_poller();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(state.alerts.active().len(), 1);
+
+        source.kill(100).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        poller.abort();
+        assert!(state.alerts.active().is_empty());
+        assert_eq!(state.alerts.history(10).len(), 1);
+    }
+
+    /// Answers POSTs with 500 `fail_first` times, then 200, keeping every body
+    async fn webhook_receiver(fail_first: usize) -> (String, Arc<std::sync::Mutex<Vec<Value>>>) {
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let bodies = received.clone();
+        let app = Router::new().route(
+            "/hook/s3cret",
+            axum::routing::post(move |axum::Json(body): axum::Json<Value>| {
+                let bodies = bodies.clone();
+                async move {
+                    let mut bodies = bodies.lock().unwrap();
+                    bodies.push(body);
+                    if bodies.len() <= fail_first { StatusCode::INTERNAL_SERVER_ERROR } else { StatusCode::OK }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/hook/s3cret", addr), received)
+    }
+
+    fn alert_event(target: WebhookTarget, resolved_at: Option<u64>) -> AlertEvent {
+        AlertEvent {
+            transition: if resolved_at.is_some() { AlertTransition::Resolved } else { AlertTransition::Triggered },
+            alert: Alert { id: 3, rule: "cpu-hot".to_string(), triggered_at: 1_700_000_000, resolved_at, current_value: 96.44 },
+            metric: AlertMetric::CpuPercent,
+            operator: Comparison::Above,
+            threshold: 90.0,
+            process: None,
+            target,
+        }
+    }
+
+    #[test]
+    fn test_webhook_payload_formats() {
+        let target = WebhookTarget { url: "https://hooks.slack.com/services/T0/B0/secret".to_string(), format: WebhookFormat::Json };
+        assert_eq!(target.redacted(), "https://hooks.slack.com/…");
+        assert!(target.validate().is_ok());
+        let invalid = WebhookTarget { url: "ftp://example.com/x".to_string(), format: WebhookFormat::Json };
+        assert!(invalid.validate().is_err());
+
+        let body = webhook::payload(&alert_event(target.clone(), None), "web-1");
+        assert_eq!(body["event"], "triggered");
+        assert_eq!(body["rule"], "cpu-hot");
+        assert_eq!(body["metric"], "cpu_percent");
+        assert_eq!(body["operator"], ">");
+        assert_eq!(body["value"], 96.44);
+        assert_eq!(body["hostname"], "web-1");
+        assert_eq!(body["resolved_at"], Value::Null);
+
+        let slack = WebhookTarget { format: WebhookFormat::Slack, ..target };
+        let body = webhook::payload(&alert_event(slack.clone(), None), "web-1");
+        let text = body["text"].as_str().unwrap();
+        assert!(text.contains("*cpu-hot* on `web-1`: CPU is 96.4% (> 90) since 2023-11-14T22:13:20Z"), "{}", text);
+        assert_eq!(body.as_object().unwrap().len(), 1);
+        let body = webhook::payload(&alert_event(slack, Some(1_700_000_300)), "web-1");
+        assert!(body["text"].as_str().unwrap().contains("resolved after 5m"));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_retries_with_backoff() {
+        let (url, received) = webhook_receiver(2).await;
+        let target = WebhookTarget { url, format: WebhookFormat::Json };
+        let unreachable = WebhookTarget { url: "http://127.0.0.1:9/hook".to_string(), format: WebhookFormat::Json };
+        let webhooks = Webhooks::new([&target, &unreachable, &target], std::time::Duration::from_millis(1));
+
+        assert!(webhooks.send(&alert_event(target.clone(), None), "web-1").await);
+        assert_eq!(received.lock().unwrap().len(), 3);
+        assert!(!webhooks.send(&alert_event(unreachable, None), "web-1").await);
+
+        let statuses = webhooks.statuses();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!((statuses[0].delivered, statuses[0].failures), (1, 0));
+        assert_eq!(statuses[0].last_status_code, Some(200));
+        assert_eq!(statuses[0].target, "http://127.0.0.1/…");
+        assert_eq!((statuses[1].delivered, statuses[1].failures), (0, 1));
+        assert_eq!(statuses[1].last_status_code, None);
+        assert!(!statuses[1].last_error.as_deref().unwrap().contains("/hook"));
+
+        // 4xx means the request itself is wrong, so it isn't retried
+        let missing = WebhookTarget { url: target.url.replace("s3cret", "other"), format: WebhookFormat::Json };
+        let webhooks = Webhooks::new([&missing], std::time::Duration::from_millis(1));
+        assert!(!webhooks.send(&alert_event(missing, None), "web-1").await);
+        assert_eq!(webhooks.statuses()[0].last_status_code, Some(404));
+        assert_eq!(webhooks.statuses()[0].last_error.as_deref(), Some("HTTP 404"));
+    }
+
+    #[tokio::test]
+    async fn test_alert_webhooks_sent_by_poller() {
+        let (url, received) = webhook_receiver(0).await;
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let mut rule = alert_rule("chrome-cpu", AlertMetric::CpuPercent, 40.0, 0);
+        rule.process = Some("chrome".to_string());
+        let config = Config {
+            poll_interval: std::time::Duration::from_millis(10),
+            alert_rules: vec![rule, alert_rule("quiet", AlertMetric::CpuPercent, 1000.0, 0)],
+            alert_webhook: Some(WebhookTarget { url, format: WebhookFormat::Json }),
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source.clone());
+        let router = build_router(state.clone());
+        state.spawn_webhook_sender();
+        let poller = state.spawn_poller();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        source.kill(100).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        poller.abort();
+
+        let bodies = received.lock().unwrap().clone();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0]["event"], "triggered");
+        assert_eq!(bodies[0]["process"], "chrome");
+        assert_eq!(bodies[1]["event"], "resolved");
+        assert!(bodies[1]["resolved_at"].is_u64());
+
+        let (status, body) = send(router, "GET", "/api/alerts/webhooks/status", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["delivered"], 2);
+        assert_eq!(body[0]["failures"], 0);
+        state.shutdown.cancel();
+
+        let (router, _) = mock_router();
+        let (_, body) = send(router, "GET", "/api/alerts/webhooks/status", None).await;
+        assert_eq!(body, serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_alert_rules_crud() {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let config = Config {
+            poll_interval: std::time::Duration::from_millis(10),
+            alert_rules: vec![alert_rule("cpu-hot", AlertMetric::CpuPercent, 90.0, 0)],
+            rate_limit: None,
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source);
+        let router = build_router(state.clone());
+
+        let (status, body) = send(router.clone(), "GET", "/api/alert_rules", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["id"], 1);
+        assert_eq!(body[0]["name"], "cpu-hot");
+        assert_eq!(body[0]["operator"], ">");
+        assert_eq!(body[0]["firing"], false);
+
+        let chrome = r#"{"name": "chrome-cpu", "metric": "cpu_percent", "operator": ">", "threshold": 40, "process": "chrome",
+                         "webhook": {"url": "https://hooks.example/s3cret", "format": "slack"}}"#;
+        let (status, body) = send(router.clone(), "POST", "/api/alert_rules", Some(chrome)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["id"], 2);
+        assert_eq!(body["webhook"], "https://hooks.example/…");
+
+        // Picked up by the next poll
+        let poller = state.spawn_poller();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        poller.abort();
+        let (_, body) = send(router.clone(), "GET", "/api/alerts?active=true", None).await;
+        assert_eq!(body[0]["rule"], "chrome-cpu");
+
+        // Every bad field is reported, not just the first
+        let bad = r#"{"name": "x", "metric": "cpu", "operator": "!=", "threshold": 0}"#;
+        let (status, body) = send(router.clone(), "POST", "/api/alert_rules", Some(bad)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_fields");
+        let fields: Vec<&str> = body["error"]["fields"].as_array().unwrap().iter().map(|f| f["field"].as_str().unwrap()).collect();
+        assert_eq!(fields, ["metric", "operator", "threshold"]);
+        assert!(body["error"]["fields"][0]["message"].as_str().unwrap().contains("'cpu'"));
+        let (status, body) = send(router.clone(), "POST", "/api/alert_rules", Some(&chrome.replace("40", "-5"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["fields"][0]["field"], "threshold");
+        let (_, body) = send(router.clone(), "POST", "/api/alert_rules", Some(chrome)).await;
+        assert_eq!(body["error"]["fields"][0]["field"], "name");
+        let (_, body) = send(router.clone(), "POST", "/api/alert_rules", Some(&chrome.replace("cpu_percent", "disk_percent").replace("slack", "teams"))).await;
+        let fields: Vec<&str> = body["error"]["fields"].as_array().unwrap().iter().map(|f| f["field"].as_str().unwrap()).collect();
+        assert_eq!(fields, ["webhook.format", "process"]);
+
+        // Replacing a firing rule resolves its alert
+        let raised = chrome.replace("\"threshold\": 40", "\"threshold\": 99").replace("chrome-cpu", "chrome-busy");
+        let (status, body) = send(router.clone(), "PUT", "/api/alert_rules/2", Some(&raised)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "chrome-busy");
+        assert_eq!(body["threshold"], 99.0);
+        assert_eq!(body["firing"], false);
+        assert!(state.alerts.active().is_empty());
+        assert_eq!(state.alerts.history(10)[0].rule, "chrome-cpu");
+        let (_, body) = send(router.clone(), "PUT", "/api/alert_rules/2", Some(&raised.replace("chrome-busy", "cpu-hot"))).await;
+        assert_eq!(body["error"]["fields"][0]["field"], "name");
+        let (status, body) = send(router.clone(), "PUT", "/api/alert_rules/99", Some(&raised)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "alert_rule_not_found");
+
+        let (status, body) = send(router.clone(), "DELETE", "/api/alert_rules/1", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["message"], "Alert rule 'cpu-hot' deleted");
+        let (status, _) = send(router.clone(), "DELETE", "/api/alert_rules/1", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (_, body) = send(router.clone(), "POST", "/api/alert_rules", Some(r#"{"name": "ram", "metric": "memory_percent", "operator": ">=", "threshold": 95}"#)).await;
+        assert_eq!(body["id"], 3);
+        let (_, body) = send(router, "GET", "/api/alert_rules", None).await;
+        let names: Vec<&str> = body.as_array().unwrap().iter().map(|r| r["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["chrome-busy", "ram"]);
+
+        let (router, _) = mock_router_with(Config { auth: AuthConfig { read_only: true, ..Default::default() }, ..Default::default() });
+        let (status, body) = send(router.clone(), "POST", "/api/alert_rules", Some(chrome)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "read_only");
+        let (status, _) = send(router, "DELETE", "/api/alert_rules/1", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_alert_rules_persisted() {
+        let path = std::env::temp_dir().join(format!("taskmgr-rules-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config_path = write_temp_config("persisted", "[[alerts]]\nname = \"cpu-hot\"\nmetric = \"cpu_percent\"\noperator = \">\"\nthreshold = 90\n");
+        let cli = Cli { config: Some(config_path.clone()), persist_rules: Some(path.clone()), ..Cli::default() };
+        let config = Config::default().merge(&cli).unwrap();
+        // No file yet: the config's rules stand
+        assert_eq!(config.alert_rules.len(), 1);
+        let router = build_router(AppState::with_source(config, Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap())));
+
+        let rule = r#"{"name": "disk-full", "metric": "disk_percent", "operator": ">", "threshold": 95, "sustained_secs": 300,
+                       "webhook": {"url": "https://hooks.example/s3cret"}}"#;
+        let (status, _) = send(router.clone(), "POST", "/api/alert_rules", Some(rule)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, _) = send(router, "DELETE", "/api/alert_rules/1", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.as_array().unwrap().len(), 1);
+        assert_eq!(saved[0]["webhook"]["url"], "https://hooks.example/s3cret");
+
+        // The file replaces the config's rules on the next start
+        let reloaded = Config::default().merge(&cli).unwrap();
+        assert_eq!(reloaded.alert_rules.len(), 1);
+        assert_eq!(reloaded.alert_rules[0].name, "disk-full");
+        assert_eq!(reloaded.alert_rules[0].sustained_secs, 300);
+        assert_eq!(reloaded.alert_rules[0].webhook.as_ref().unwrap().format, WebhookFormat::Json);
+
+        std::fs::write(&path, "[{\"name\": \"x\"}]").unwrap();
+        let err = Config::default().merge(&cli).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(config_path).unwrap();
+        assert!(matches!(err, ConfigError::RulesFile { .. }));
+    }
+
+    #[test]
+    fn test_process_history_ring_buffer() {
+        let source = MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap();
+        let history = ProcessHistory::default();
+        for tick in 0..HISTORY_CAPACITY as u64 + 5 {
+            history.record(1000 + tick, &source.list_processes());
+        }
+        let chrome = history.since(100, 0);
+        assert_eq!(chrome.len(), HISTORY_CAPACITY);
+        assert_eq!(chrome[0].timestamp, 1005);
+        assert_eq!(chrome[0].cpu_percent, 37.5);
+        assert_eq!(chrome[0].memory_mb, 100.0);
+        assert_eq!(history.since(100, 1600).len(), 5);
+
+        // Exited PIDs are forgotten
+        source.kill(100).unwrap();
+        history.record(2000, &source.list_processes());
+        assert!(history.since(100, 0).is_empty());
+        assert_eq!(history.since(200, 0).len(), HISTORY_CAPACITY);
+
+        // A reused PID starts over
+        let mut table = source.list_processes();
+        table.processes.iter_mut().find(|p| p.pid == 200).unwrap().start_time = 99;
+        history.record(2001, &table);
+        assert_eq!(history.since(200, 0).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_process_history_endpoint() {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let config = Config {
+            poll_interval: std::time::Duration::from_millis(10),
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source);
+        let router = build_router(state.clone());
+        let poller = state.spawn_poller();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        poller.abort();
+
+        let (status, body) = send(router.clone(), "GET", "/api/process/200/history", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["minutes"], 5);
+        let samples = body["samples"].as_array().unwrap();
+        assert!(!samples.is_empty());
+        assert_eq!(samples[0]["memory_mb"], 4.0);
+        assert!(samples[0]["timestamp"].as_u64().unwrap() > 0);
+
+        let (_, body) = send(router.clone(), "GET", "/api/process/200/history?minutes=60", None).await;
+        assert_eq!(body["minutes"], 10);
+        let (status, _) = send(router, "GET", "/api/process/99999/history", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_memory_growth_tracking() {
+        const MIB: u64 = 1024 * 1024;
+        let source = MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap();
+        let growth = MemoryGrowth::default();
+        let grown = |minute: u64| {
+            let mut table = source.list_processes();
+            for process in &mut table.processes {
+                match process.pid {
+                    100 => process.memory += minute * 15 * MIB,
+                    102 => process.memory += minute * 30 * MIB,
+                    _ => {}
+                }
+            }
+            table
+        };
+        for minute in 0..3 {
+            growth.record(1000 + minute * 60, &grown(minute));
+            // Within the same minute: no point taken
+            growth.record(1030 + minute * 60, &grown(minute + 10));
+        }
+        // systemd (12 MiB) and bash (4 MiB) are under the floor
+        assert_eq!(growth.tracked(), 3);
+
+        let ranked = growth.ranked(0, GrowthSort::Absolute, 10);
+        let pids: Vec<u32> = ranked.iter().map(|e| e.pid).collect();
+        assert_eq!(pids, vec![102, 100, 101]);
+        assert_eq!(ranked[0].growth_mb, 60.0);
+        assert_eq!(ranked[0].growth_percent, 20.0);
+        assert_eq!(ranked[0].memory_mb, 360.0);
+        assert_eq!(ranked[0].samples, vec![300.0, 330.0, 360.0]);
+        assert_eq!(ranked[0].since, 1000);
+        assert_eq!(ranked[2].growth_mb, 0.0);
+
+        let ranked = growth.ranked(0, GrowthSort::Relative, 2);
+        assert_eq!(ranked.iter().map(|e| e.pid).collect::<Vec<_>>(), vec![100, 102]);
+        assert_eq!(ranked[0].growth_percent, 30.0);
+
+        // The window keeps the last two points only
+        let ranked = growth.ranked(1060, GrowthSort::Absolute, 10);
+        assert_eq!(ranked[0].samples, vec![330.0, 360.0]);
+        // A single point has no growth yet
+        assert!(growth.ranked(1120, GrowthSort::Absolute, 10).is_empty());
+
+        // Exited processes are evicted; a reused PID is a new process
+        source.kill(101).unwrap();
+        let mut table = grown(3);
+        table.processes.iter_mut().find(|p| p.pid == 100).unwrap().start_time = 99;
+        growth.record(1180, &table);
+        assert_eq!(growth.tracked(), 2);
+        let ranked = growth.ranked(0, GrowthSort::Absolute, 10);
+        assert_eq!(ranked.iter().map(|e| e.pid).collect::<Vec<_>>(), vec![102]);
+        assert_eq!(ranked[0].samples.len(), 4);
+
+        for minute in 4..GROWTH_CAPACITY as u64 + 10 {
+            growth.record(1000 + minute * 60, &table);
+        }
+        assert_eq!(growth.ranked(0, GrowthSort::Absolute, 10)[0].samples.len(), GROWTH_CAPACITY);
+    }
+
+    #[tokio::test]
+    async fn test_memory_growth_endpoint() {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let config = Config {
+            poll_interval: std::time::Duration::from_millis(10),
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source.clone());
+        let router = build_router(state.clone());
+        let poller = state.spawn_poller();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        poller.abort();
+
+        // One round of points so far, so nothing has grown yet
+        let (status, body) = send(router.clone(), "GET", "/api/processes/growth", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["minutes"], 30);
+        assert_eq!(body["tracked"], 3);
+        assert_eq!(body["processes"], serde_json::json!([]));
+
+        let mut table = source.list_processes();
+        table.processes.iter_mut().find(|p| p.pid == 101).unwrap().memory *= 2;
+        state.memory_growth.record(unix_now() + 60, &table);
+        let (_, body) = send(router.clone(), "GET", "/api/processes/growth?minutes=500&sort=relative&limit=1", None).await;
+        assert_eq!(body["minutes"], 120);
+        let processes = body["processes"].as_array().unwrap();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0]["pid"], 101);
+        assert_eq!(processes[0]["name"], "chrome");
+        assert_eq!(processes[0]["growth_mb"], 200.0);
+        assert_eq!(processes[0]["growth_percent"], 100.0);
+        assert_eq!(processes[0]["samples"], serde_json::json!([200.0, 400.0]));
+
+        let (status, _) = send(router, "GET", "/api/processes/growth?sort=bogus", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_listing_served_from_last_sample() {
+        let source = SysinfoSource::new();
+        source.refresh_processes();
+        let mut child = std::process::Command::new("sleep").arg("5").spawn().unwrap();
+        let child_pid = child.id();
+
+        let listed = |table: ProcessTable| table.processes.iter().any(|p| p.pid == child_pid);
+        let cached = source.list_processes();
+        assert!(cached.processes.iter().any(|p| p.pid == std::process::id()));
+        assert!(!listed(cached), "listing should come from the cached sample");
+        source.refresh_processes();
+        assert!(listed(source.list_processes()));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_poller_and_rejects_new_requests() {
+        let source = Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap());
+        let config = Config {
+            poll_interval: std::time::Duration::from_millis(10),
+            ..Default::default()
+        };
+        let state = AppState::with_source(config, source);
+        let router = build_router(state.clone());
+        let poller = state.spawn_poller();
+
+        state.shutdown.cancel();
+        tokio::time::timeout(std::time::Duration::from_secs(1), poller)
+            .await
+            .expect("poller should exit on shutdown")
+            .unwrap();
+
+        let response = router
+            .oneshot(Request::builder().uri("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::CONNECTION], "close");
+        assert_eq!(error_code(response).await, "shutting_down");
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_background_tasks_to_flush() {
+        let state = AppState::with_source(Config::default(), Arc::new(MockSystemSource::from_fixture(MOCK_SYSTEM).unwrap()));
+        let flushed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        state.tasks.spawn({
+            let shutdown = state.shutdown.clone();
+            let flushed = flushed.clone();
+            async move {
+                shutdown.cancelled().await;
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                flushed.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        });
+
+        state.shutdown.cancel();
+        shutdown::finish_tasks(&state.tasks, std::time::Duration::from_secs(1)).await;
+        assert!(flushed.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_request_id_header_on_every_response() {
+        let (router, _) = mock_router();
+        let mut ids = Vec::new();
+        for uri in ["/health", "/api/process/424242/info"] {
+            let response = router
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let id = response.headers()["x-request-id"].to_str().unwrap().to_string();
+            assert_eq!(id.len(), 36, "expected a UUID, got {}", id);
+            let elapsed = response.headers()["x-response-time-ms"].to_str().unwrap();
+            assert!(elapsed.parse::<u64>().is_ok(), "{}", elapsed);
+            ids.push(id);
+        }
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[tokio::test]
+    async fn test_incoming_request_id_is_propagated() {
+        let (router, _) = mock_router();
+        let request = |method: &str, uri: &str, id: &str| {
+            Request::builder().method(method).uri(uri).header("x-request-id", id).body(Body::empty()).unwrap()
+        };
+
+        let response = router.clone().oneshot(request("GET", "/api/process/424242/info", "chart-refresh-17")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["x-request-id"], "chart-refresh-17");
+        assert!(response.headers().contains_key("x-response-time-ms"));
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["error"]["request_id"], "chart-refresh-17");
+
+        // Unusable IDs are replaced rather than echoed
+        for bad in ["has space", &"x".repeat(129)] {
+            let response = router.clone().oneshot(request("GET", "/health", bad)).await.unwrap();
+            assert_eq!(response.headers()["x-request-id"].len(), 36);
+        }
+
+        let response = router.clone().oneshot(request("POST", "/api/process/200/kill", "kill-200")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let (_, audit) = send(router, "GET", "/api/audit", None).await;
+        assert_eq!(audit[0]["action"], "kill");
+        assert_eq!(audit[0]["request_id"], "kill-200");
+    }
+
+    #[tokio::test]
+    async fn test_auth_accepts_valid_token() {
+        let (router, source) = mock_router_with(auth_config(false));
+        let (status, _) = send_with_auth(router, "POST", "/api/process/200/kill", Some("Bearer s3cret")).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(source.killed(), vec![200]);
+    }
+
+    #[tokio::test]
+    async fn test_auth_rejects_invalid_token() {
+        let (router, source) = mock_router_with(auth_config(false));
+        let (status, body) = send_with_auth(router, "POST", "/api/process/200/kill", Some("Bearer wrong")).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "invalid_token");
+        assert!(source.killed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_auth_rejects_missing_header() {
+        let (router, source) = mock_router_with(auth_config(false));
+        let (status, body) = send_with_auth(router.clone(), "POST", "/api/process/200/kill", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "missing_token");
+
+        let (status, _) = send_with_auth(router, "POST", "/api/process/200/kill", Some("Basic s3cret")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(source.killed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_auth_reads_open_by_default() {
+        let (router, _) = mock_router_with(auth_config(false));
+        let (status, _) = send_with_auth(router, "GET", "/api/processes", None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_auth_can_protect_reads() {
+        let (router, _) = mock_router_with(auth_config(true));
+        let (status, _) = send_with_auth(router.clone(), "GET", "/api/processes", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send_with_auth(router.clone(), "GET", "/api/processes", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = send_with_auth(router, "GET", "/health", None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_mutations_even_with_token() {
+        let mut config = auth_config(false);
+        config.auth.read_only = true;
+        let (router, source) = mock_router_with(config);
+
+        let (status, body) = send_with_auth(router.clone(), "POST", "/api/process/200/kill", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "read_only");
+        assert!(source.killed().is_empty());
+
+        let (status, _) = send_with_auth(router, "GET", "/api/processes", None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    async fn fetch_token(router: Router, peer: &str, host: Option<&str>, origin: Option<&str>) -> (StatusCode, Value) {
+        let mut request = Request::builder().uri("/api/v1/auth/token");
+        if let Some(host) = host {
+            request = request.header(header::HOST, host);
+        }
+        if let Some(origin) = origin {
+            request = request.header(header::ORIGIN, origin);
+        }
+        let mut request = request.body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(axum::extract::ConnectInfo(peer.parse::<std::net::SocketAddr>().unwrap()));
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    const UI_ORIGIN: Option<&str> = Some("http://localhost:5173");
+
+    #[tokio::test]
+    async fn test_default_config_lets_bundled_ui_kill() {
+        // What the server starts with when TASKMGR_TOKEN is unset: a random token
+        let config = Config::from_lookup(|_| None);
+        assert!(config.auth.token.is_some());
+        let (router, source) = mock_router_with(config);
+
+        let (status, _) = send(router.clone(), "POST", "/api/v1/process/200/kill", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // As App.tsx does on startup, from the Vite dev server's origin
+        let (status, body) = fetch_token(router.clone(), "127.0.0.1:50000", Some("localhost:8000"), UI_ORIGIN).await;
+        assert_eq!(status, StatusCode::OK);
+        let token = body["token"].as_str().unwrap();
+
+        let bearer = format!("Bearer {token}");
+        let (status, body) = send_with_auth(router, "POST", "/api/v1/process/200/kill", Some(&bearer)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["message"], "Process bash terminated");
+        assert_eq!(source.killed(), vec![200]);
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_only_for_local_clients() {
+        let (router, _) = mock_router_with(auth_config(true));
+
+        let (status, body) = fetch_token(router.clone(), "[::1]:50000", Some("[::1]:8000"), UI_ORIGIN).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["token"], "s3cret");
+
+        let (status, body) = fetch_token(router.clone(), "192.168.1.20:50000", Some("localhost:8000"), UI_ORIGIN).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "not_loopback");
+
+        // A DNS-rebound page reaches loopback under its own host name
+        let (status, _) = fetch_token(router.clone(), "127.0.0.1:50000", Some("evil.example:8000"), UI_ORIGIN).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, body) = fetch_token(router, "127.0.0.1:50000", None, UI_ORIGIN).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "not_loopback");
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_only_for_allowlisted_origins() {
+        let (router, _) = mock_router_with(auth_config(true));
+        let local = Some("localhost:8000");
+
+        // Another page open in the same browser
+        let (status, body) = fetch_token(router.clone(), "127.0.0.1:50000", local, Some("http://evil.example")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "origin_not_allowed");
+
+        // Not a browser page at all, e.g. curl; it can read the startup log instead
+        let (status, body) = fetch_token(router, "127.0.0.1:50000", local, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "origin_not_allowed");
+
+        // With `*` every website is allowed, so none gets the token
+        let mut config = auth_config(true);
+        config.cors_origins = CorsOrigins::Any;
+        let (router, _) = mock_router_with(config);
+        let (status, body) = fetch_token(router, "127.0.0.1:50000", local, UI_ORIGIN).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "origin_not_allowed");
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_exemption_is_exact() {
+        let (router, _) = mock_router_with(auth_config(true));
+        for path in ["/api/v1/auth/token", "/api/auth/token"] {
+            let mut request = Request::builder()
+                .uri(path)
+                .header(header::HOST, "localhost:8000")
+                .header(header::ORIGIN, "http://localhost:5173")
+                .body(Body::empty())
+                .unwrap();
+            request
+                .extensions_mut()
+                .insert(axum::extract::ConnectInfo("127.0.0.1:50000".parse::<std::net::SocketAddr>().unwrap()));
+            let response = router.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{path}");
+        }
+
+        // Reads need the token here, suffix or not
+        let (status, body) = send(router, "GET", "/api/v1/processes?x=/auth/token", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "missing_token");
+    }
+
+    #[test]
+    fn test_cli_overrides_env_overrides_default() {
+        let env = |name: &str| match name {
+            "TASKMGR_PORT" => Some("9000".to_string()),
+            "TASKMGR_HOST" => Some("127.0.0.1".to_string()),
+            "TASKMGR_POLL_INTERVAL_MS" => Some("250".to_string()),
+            _ => None,
+        };
+
+        let config = Config::from_lookup(|_| None);
+        assert_eq!(config.bind_addr().to_string(), "0.0.0.0:8000");
+        assert_eq!(config.poll_interval, std::time::Duration::from_secs(1));
+        assert!(!config.auth.read_only);
+
+        let config = Config::from_lookup(env);
+        assert_eq!(config.bind_addr().to_string(), "127.0.0.1:9000");
+        assert_eq!(config.poll_interval, std::time::Duration::from_millis(250));
+
+        let cli = Cli::try_parse_from(["task_manager_backend", "--port", "9100", "--read-only", "--token", "abc"]).unwrap();
+        let config = Config::from_lookup(env).merge(&cli).unwrap();
+        assert_eq!(config.bind_addr().to_string(), "127.0.0.1:9100");
+        assert_eq!(config.poll_interval, std::time::Duration::from_millis(250));
+        assert_eq!(config.auth.token.as_deref(), Some("abc"));
+        assert!(config.auth.read_only);
+    }
+
+    #[test]
+    fn test_log_format_from_env_and_flag() {
+        assert_eq!(Config::from_lookup(|_| None).log_format, LogFormat::Json);
+
+        let env = |name: &str| (name == "TASKMGR_LOG_FORMAT").then(|| "Text".to_string());
+        assert_eq!(Config::from_lookup(env).log_format, LogFormat::Text);
+
+        let cli = Cli::try_parse_from(["task_manager_backend", "--log-format", "json"]).unwrap();
+        assert_eq!(Config::from_lookup(env).merge(&cli).unwrap().log_format, LogFormat::Json);
+        assert!(Cli::try_parse_from(["task_manager_backend", "--log-format", "xml"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_openapi_spec_lists_every_route() {
+        let (router, _) = mock_router();
+        let (status, spec) = send(router, "GET", "/openapi.json", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+        for path in [
+            "/health",
+            "/api/stats",
+            "/api/ws/stats/binary",
+            "/api/cpu/info",
+            "/api/processes",
+            "/api/processes/export",
+            "/api/processes/batch-kill",
+            "/api/processes/kill_by_name",
+            "/api/process-groups",
+            "/api/process-group/{pgid}/kill",
+            "/api/apps",
+            "/api/users",
+            "/api/self",
+            "/api/version",
+            "/api/containers",
+            "/api/container/{id}/stop",
+            "/api/app/close",
+            "/api/app/suspend",
+            "/api/app/resume",
+            "/api/process/start",
+            "/api/process/launch",
+            "/api/process/{pid}/kill",
+            "/api/process/{pid}/restart",
+            "/api/process/{pid}/suspend",
+            "/api/process/{pid}/resume",
+            "/api/process/{pid}/oom_adj",
+            "/api/process/{pid}/rename",
+            "/api/process/{pid}/info",
+            "/api/process/{pid}/children",
+            "/api/process/{pid}/maps",
+            "/api/process/{pid}/threads",
+            "/api/process/{pid}/io",
+            "/api/process/{pid}/limits",
+            "/api/process/{pid}/capabilities",
+            "/api/process/{pid}/history",
+            "/api/processes/growth",
+            "/api/hardware/usb",
+            "/api/hardware/pci",
+            "/api/disks/{device}/scheduler",
+            "/api/process/{pid}/environ",
+            "/api/ws/process/{pid}/output",
+            "/api/policy",
+            "/api/alerts",
+            "/api/alerts/history",
+            "/api/alerts/webhooks/status",
+            "/api/alert_rules",
+            "/api/alert_rules/{id}",
+            "/api/kernel/modules",
+            "/api/kernel/interrupts",
+            "/api/kernel/params",
+            "/api/network/connections",
+            "/api/process/{pid}/connections",
+            "/api/network/arp",
+            "/api/services",
+            "/api/services/{name}/start",
+            "/api/services/{name}/stop",
+            "/api/services/{name}/restart",
+            "/api/startup",
+        ] {
+            // Documented under v1 only
+            let versioned = path.replacen("/api/", "/api/v1/", 1);
+            assert!(spec["paths"].get(&versioned).is_some(), "missing {}", versioned);
+            if versioned != path {
+                assert!(spec["paths"].get(path).is_none(), "unversioned {}", path);
+            }
+        }
+        for schema in ["SystemStats", "ProcessData", "AppGroup", "DetailedProcessInfo", "ErrorEnvelope"] {
+            assert!(spec["components"]["schemas"].get(schema).is_some(), "missing {}", schema);
+        }
+        assert!(spec["components"]["schemas"]["ProcessData"]["properties"]["cpu_percent"]["description"].is_string());
+    }
+
+    /// Reads the routes out of `build_router`'s source so a new route without
+    /// a `#[utoipa::path]` fails here rather than waiting to be added above
+    #[tokio::test]
+    async fn test_openapi_spec_covers_router() {
+        let (router, _) = mock_router();
+        let (status, spec) = send(router, "GET", "/openapi.json", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let source = include_str!("../src/lib.rs");
+        let routes: Vec<String> = source
+            .split(".route(")
+            .skip(1)
+            .filter_map(|rest| rest.trim_start().strip_prefix('"')?.split('"').next())
+            .map(|route| {
+                let route = route.split('/').map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => segment.to_string(),
+                });
+                let route = route.collect::<Vec<_>>().join("/");
+                if route == "/health" || route == "/metrics" { route } else { format!("/api/v1{}", route) }
+            })
+            .collect();
+        assert!(routes.len() > 50, "{:?}", routes);
+        for route in routes {
+            assert!(spec["paths"].get(&route).is_some(), "{} is routed but not documented", route);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_swagger_ui_served() {
+        let (router, _) = mock_router();
+        let response = router.clone().oneshot(Request::builder().uri("/swagger-ui/").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/html"));
+
+        // The older paths point at the one mount
+        for (uri, location) in [("/docs", "/swagger-ui/"), ("/docs/", "/swagger-ui/"), ("/api/openapi.json", "/openapi.json")] {
+            let response = router.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT, "{}", uri);
+            assert_eq!(response.headers()[header::LOCATION], location, "{}", uri);
+            assert!(!response.headers().contains_key("deprecation"), "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_redirects_to_https() {
+        let response = tls::redirect_router(8443)
+            .oneshot(
+                Request::builder()
+                    .uri("/api/stats?x=1")
+                    .header(header::HOST, "localhost:8000")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(response.headers()[header::LOCATION], "https://localhost:8443/api/stats?x=1");
+    }
+
+    fn tls_fixture(cert: &str, key: &str) -> TlsConfig {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls");
+        TlsConfig {
+            cert: dir.join(cert),
+            key: dir.join(key),
+            port: DEFAULT_TLS_PORT,
+        }
+    }
+
+    #[test]
+    fn test_tls_loads_matching_cert_and_key() {
+        let config = tls::server_config(&tls_fixture("cert.pem", "key.pem")).unwrap();
+        assert_eq!(config.alpn_protocols, [b"h2".to_vec(), b"http/1.1".to_vec()]);
+    }
+
+    #[test]
+    fn test_tls_rejects_bad_cert_or_key() {
+        let err = tls::server_config(&tls_fixture("cert.pem", "other_key.pem")).unwrap_err();
+        assert!(matches!(err, TlsError::KeyMismatch { .. }), "{err}");
+        assert!(err.to_string().contains("does not match"));
+
+        let err = tls::server_config(&tls_fixture("key.pem", "key.pem")).unwrap_err();
+        assert!(matches!(err, TlsError::NoCertificate { .. }), "{err}");
+
+        let err = tls::server_config(&tls_fixture("cert.pem", "cert.pem")).unwrap_err();
+        assert!(matches!(err, TlsError::NoKey { .. }), "{err}");
+
+        let err = tls::server_config(&tls_fixture("missing.pem", "key.pem")).unwrap_err();
+        assert!(matches!(err, TlsError::Read { .. }), "{err}");
+    }
+
+    fn write_temp_config(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("taskmgr-{}-{}.toml", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_tls_paths_from_config_file_and_cli() {
+        let path = write_temp_config("tls", "[tls]\ncert = \"file.pem\"\nkey = \"file.key\"\n");
+        let cli = Cli { config: Some(path.clone()), ..Cli::default() };
+        let tls = Config::default().merge(&cli).unwrap().tls.unwrap();
+        assert_eq!(tls.cert, std::path::Path::new("file.pem"));
+        assert_eq!(tls.port, DEFAULT_TLS_PORT);
+
+        let cli = Cli {
+            config: Some(path.clone()),
+            tls_cert: Some("cli.pem".into()),
+            tls_port: Some(9443),
+            ..Cli::default()
+        };
+        let tls = Config::default().merge(&cli).unwrap().tls.unwrap();
+        assert_eq!(tls.cert, std::path::Path::new("cli.pem"));
+        assert_eq!(tls.key, std::path::Path::new("file.key"));
+        assert_eq!(tls.port, 9443);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_cors_allowed_origins_from_config_file() {
+        assert_eq!(
+            Config::default().cors_origins,
+            CorsOrigins::List(vec!["http://localhost:5173".into(), "http://127.0.0.1:5173".into(), "http://localhost:3000".into()])
+        );
+
+        let path = write_temp_config("cors", "[cors]\nallowed_origins = [\"https://dash.example\"]\n");
+        let config = Config::default().merge(&Cli { config: Some(path.clone()), ..Cli::default() }).unwrap();
+        assert_eq!(config.cors_origins, CorsOrigins::List(vec!["https://dash.example".into()]));
+        std::fs::remove_file(path).unwrap();
+
+        // The old top-level key still works, but the table wins
+        let path = write_temp_config("cors-alias", "cors_origins = [\"https://old.example\"]\n");
+        let config = Config::default().merge(&Cli { config: Some(path.clone()), ..Cli::default() }).unwrap();
+        assert_eq!(config.cors_origins, CorsOrigins::List(vec!["https://old.example".into()]));
+        std::fs::remove_file(path).unwrap();
+        let path = write_temp_config("cors-both", "cors_origins = [\"https://old.example\"]\n[cors]\nallowed_origins = [\"*\"]\n");
+        let config = Config::default().merge(&Cli { config: Some(path.clone()), ..Cli::default() }).unwrap();
+        assert_eq!(config.cors_origins, CorsOrigins::Any);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_kill_policy_from_config_file() {
+        let path = write_temp_config("policy", "protected_names = [\"lmgrd*\"]\nkillable_only_names = [\"/home/*/test-*\"]\n");
+        let cli = Cli { config: Some(path.clone()), ..Cli::default() };
+        let policy = Config::default().merge(&cli).unwrap().kill_policy;
+        assert_eq!(policy.protected_names.patterns(), ["lmgrd*"]);
+        assert_eq!(policy.killable_only_names.patterns(), ["/home/*/test-*"]);
+        std::fs::remove_file(path).unwrap();
+
+        let path = write_temp_config("bad-policy", "protected_names = [\"[oops\"]\n");
+        let cli = Cli { config: Some(path.clone()), ..Cli::default() };
+        assert!(matches!(Config::default().merge(&cli), Err(ConfigError::Pattern(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_redact_env_from_config() {
+        assert_eq!(Config::default().redact_env, ["*TOKEN*", "*SECRET*", "*PASSWORD*"]);
+        let config = Config::from_lookup(|name| (name == "TASKMGR_REDACT_ENV").then(|| "*KEY*, DSN".to_string()));
+        assert_eq!(config.redact_env, ["*KEY*", "DSN"]);
+
+        let path = write_temp_config("bad-redact", "redact_env = [\"[oops\"]\n");
+        let cli = Cli { config: Some(path.clone()), ..Cli::default() };
+        assert!(matches!(Config::default().merge(&cli), Err(ConfigError::RedactPattern(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_tls_requires_cert_and_key() {
+        assert!(Config::default().merge(&Cli::default()).unwrap().tls.is_none());
+
+        let cli = Cli { tls_cert: Some("cert.pem".into()), ..Cli::default() };
+        assert!(matches!(Config::default().merge(&cli), Err(ConfigError::IncompleteTls)));
+    }
+
+    #[test]
+    fn test_invalid_config_file_is_reported() {
+        let path = write_temp_config("invalid", "[tls]\ncertificate = 1\n");
+        let cli = Cli { config: Some(path.clone()), ..Cli::default() };
+        assert!(matches!(Config::default().merge(&cli), Err(ConfigError::Parse { .. })));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_proc_modules() {
+        let text = "nf_tables 286720 3 nft_compat,nft_chain_nat, Live 0x0000000000000000\n\
+                    crc32c_intel 24576 0 - Live 0x0000000000000000\n\
+                    vboxdrv 483328 2 - Unloading 0xffffffffc0a00000\n";
+        let modules = parse_proc_modules(text);
+
+        assert_eq!(modules.len(), 3);
+        assert_eq!(modules[0].name, "nf_tables");
+        assert_eq!(modules[0].size_bytes, 286720);
+        assert_eq!(modules[0].used_by_count, 3);
+        assert_eq!(modules[0].used_by, ["nft_compat", "nft_chain_nat"]);
+        assert!(modules[1].used_by.is_empty());
+        assert_eq!(modules[2].state, "Unloading");
+    }
+
+    #[test]
+    fn test_parse_kextstat() {
+        let text = "Index Refs Address            Size       Wired      Name (Version) UUID <Linked Against>\n\
+                    \x20   1  108 0                  0x10000    0x10000    com.apple.kpi.bsd (21.6.0) 1D9B5A4C <>\n";
+        let modules = parse_kextstat(text);
+
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "com.apple.kpi.bsd");
+        assert_eq!(modules[0].size_bytes, 0x10000);
+        assert_eq!(modules[0].used_by_count, 108);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_kernel_modules_filter() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/kernel/modules?filter=zzz-no-such-module", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_parse_proc_interrupts() {
+        let text = "\
+           CPU0       CPU1
+  0:         36          0   IO-APIC   2-edge      timer
+ 16:        120         80   IO-APIC  16-fasteoi   ehci_hcd:usb1, i801_smbus
+122:       9001       1234  IR-PCI-MSIX-0000:00:14.0    0-edge      xhci_hcd
+ 11:          5          6     GICv3  27 Level     arch_timer
+NMI:          3          4   Non-maskable interrupts
+LOC:    1234567    7654321   Local timer interrupts
+ERR:          0
+";
+        let irqs = parse_proc_interrupts(text);
+        assert_eq!(irqs.len(), 7);
+        assert_eq!(irqs[0].irq, "0");
+        assert_eq!(irqs[0].per_cpu_counts, vec![36, 0]);
+        assert_eq!(irqs[0].controller, "IO-APIC");
+        assert_eq!(irqs[0].device_name, "timer");
+        assert_eq!(irqs[1].device_name, "ehci_hcd:usb1, i801_smbus");
+        assert_eq!(irqs[2].controller, "IR-PCI-MSIX-0000:00:14.0");
+        assert_eq!(irqs[2].device_name, "xhci_hcd");
+        // ARM prints the hardware IRQ and trigger as separate words
+        assert_eq!(irqs[3].controller, "GICv3");
+        assert_eq!(irqs[3].device_name, "arch_timer");
+        assert_eq!(irqs[5].irq, "LOC");
+        assert_eq!(irqs[5].controller, "");
+        assert_eq!(irqs[5].device_name, "Local timer interrupts");
+        assert_eq!(irqs[6].per_cpu_counts, vec![0]);
+        assert_eq!(interrupts::total(&irqs), 36 + 200 + 10_235 + 11 + 7 + 8_888_888);
+    }
+
+    #[test]
+    fn test_parse_proc_softirqs() {
+        let text = "                    CPU0       CPU1\n          HI:          0          1\n       TIMER:     236852     100000\n      NET_RX:         12          0\n";
+        let softirqs = parse_proc_softirqs(text);
+        assert_eq!(softirqs.len(), 3);
+        assert_eq!(softirqs[1].name, "TIMER");
+        assert_eq!(softirqs[1].per_cpu_counts, vec![236_852, 100_000]);
+        assert_eq!(softirqs[2].name, "NET_RX");
+    }
+
+    #[test]
+    fn test_interrupt_rate() {
+        assert_eq!(interrupts::rate(1_000, 1_500, std::time::Duration::from_millis(200)), 2_500);
+        // a CPU going offline takes its counts with it
+        assert_eq!(interrupts::rate(1_500, 1_000, std::time::Duration::from_millis(200)), 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_kernel_interrupts_endpoint() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/kernel/interrupts", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["sample_ms"].as_u64().unwrap() >= 200);
+        assert!(body["total_interrupts_per_sec"].is_u64());
+        assert!(!body["interrupts"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_smaps_rollup() {
+        let text = "\
+560e9776b000-7ffd71da3000 ---p 00000000 00:00 0                          [rollup]
+Rss:                1388 kB
+Pss:                 593 kB
+Pss_Dirty:           100 kB
+Shared_Clean:       1248 kB
+Shared_Dirty:          0 kB
+Private_Clean:        40 kB
+Private_Dirty:       100 kB
+Swap:                  0 kB
+";
+        assert_eq!(parse_smaps_rollup(text), Some(SetSizes { pss_kb: 593, uss_kb: 140 }));
+        assert_eq!(parse_smaps_rollup("Rss: 1388 kB\n"), None);
+        assert_eq!(parse_smaps_rollup(""), None);
+    }
+
+    #[test]
+    fn test_parse_smaps_breakdown() {
+        let text = "\
+55d0c8a00000-55d0c8a21000 r--p 00000000 08:01 393340                     /usr/bin/my app
+Rss:                 120 kB
+Shared_Clean:        100 kB
+Shared_Dirty:          0 kB
+VmFlags: rd mr mw me dw sd
+55d0c9e4c000-55d0c9e6d000 rw-p 00000000 00:00 0                          [heap]
+Rss:                 132 kB
+Shared_Clean:          0 kB
+Shared_Dirty:          0 kB
+7f1c2a000000-7f1c2a021000 rw-p 00000000 00:00 0 
+Rss:                  64 kB
+Shared_Dirty:          8 kB
+7f1c2b000000-7f1c2b021000 rw-p 00000000 00:00 0                          [anon:libc_malloc]
+Rss:                  16 kB
+7f1c2c000000-7f1c2c200000 r-xp 00000000 08:01 1048 /usr/lib/libc.so.6 (deleted)
+Rss:                 900 kB
+Shared_Clean:        880 kB
+7ffc1d4d6000-7ffc1d4f7000 rw-p 00000000 00:00 0                          [stack]
+Rss:                  24 kB
+7ffc1d5c6000-7ffc1d5c8000 r-xp 00000000 00:00 0                          [vdso]
+Rss:                   4 kB
+Shared_Clean:          4 kB
+";
+        assert_eq!(
+            parse_smaps(text),
+            ProcessMemoryBreakdown { anonymous_kb: 80, file_mapped_kb: 1020, shared_kb: 992, stack_kb: 24, heap_kb: 132 }
+        );
+        assert_eq!(parse_smaps(""), ProcessMemoryBreakdown::default());
+    }
+
+    #[test]
+    fn test_own_memory_breakdown() {
+        let breakdown = smaps::read_breakdown(std::process::id());
+        if cfg!(target_os = "linux") {
+            let breakdown = breakdown.unwrap();
+            assert!(breakdown.file_mapped_kb > 0);
+            assert!(breakdown.anonymous_kb + breakdown.heap_kb > 0);
+        } else {
+            assert_eq!(breakdown, None);
+        }
+        assert_eq!(smaps::read_breakdown(u32::MAX), None);
+    }
+
+    #[test]
+    fn test_own_set_sizes() {
+        let sizes = smaps::read(std::process::id());
+        if cfg!(target_os = "linux") && std::path::Path::new("/proc/self/smaps_rollup").exists() {
+            let sizes = sizes.unwrap();
+            assert!(sizes.pss_kb > 0);
+            assert!(sizes.uss_kb > 0 && sizes.uss_kb <= sizes.pss_kb);
+        } else {
+            assert_eq!(sizes, None);
+        }
+        assert_eq!(smaps::read(u32::MAX), None);
+    }
+
+    #[test]
+    fn test_parse_ns_link() {
+        assert_eq!(parse_ns_link("net:[4026531833]"), Some(4026531833));
+        assert_eq!(parse_ns_link("pid_for_children:[4026531836]"), Some(4026531836));
+        assert_eq!(parse_ns_link("net:4026531833"), None);
+        assert_eq!(parse_ns_link("net:[abc]"), None);
+    }
+
+    #[test]
+    fn test_parse_proc_stat_job_ids() {
+        let stat = "4242 (tmux: (a) b) S 1 4242 4240 34817 4242 4194560 1207 0 0 0";
+        assert_eq!(
+            parse_stat(stat),
+            Some(StatIds { process_group_id: 4242, session_id: 4240, tty_nr: 34817 })
+        );
+        assert_eq!(parse_stat("4242 (cat) S 1"), None);
+
+        // pts/1 is major 136, minor 1; minors above 255 use the high bits
+        assert_eq!(tty_major_minor(34817), (136, 1));
+        assert_eq!(tty_major_minor((136 << 8) | (0x300 << 12) | 0x05), (136, 0x305));
+
+        let mut ttys = TtyNames::default();
+        ttys.insert(136, 1, "pts/1");
+        assert_eq!(ttys.name(34817).as_deref(), Some("pts/1"));
+        assert_eq!(ttys.name(34818), None);
+        assert_eq!(ttys.name(0), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cwd_fallback_and_deleted_directory() {
+        let dir = std::env::temp_dir().join(format!("taskmgr-cwd-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut child = std::process::Command::new("sleep").arg("5").current_dir(&dir).spawn().unwrap();
+        let pid = child.id();
+        assert_eq!(cwd::read(pid).as_deref(), Some(dir.to_str().unwrap()));
+
+        let fixture = |cwd: &str| {
+            format!(
+                r#"{{"cpu_count": 1, "total_memory": 1073741824, "processes": [
+                    {{"pid": {}, "name": "sleep", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "cwd": {:?}}}
+                ]}}"#,
+                pid, cwd
+            )
+        };
+        let info = |cwd: String| async move {
+            let source = Arc::new(MockSystemSource::from_fixture(&fixture(&cwd)).unwrap());
+            let router = build_router(AppState::with_source(Config::default(), source));
+            send(router, "GET", &format!("/api/process/{}/info", pid), None).await.1
+        };
+        let body = info(cwd::read(pid).unwrap()).await;
+        assert_eq!(body["cwd"], dir.to_str().unwrap());
+        assert_eq!(body["cwd_exists"], true);
+
+        std::fs::remove_dir(&dir).unwrap();
+        let deleted = cwd::read(pid).unwrap();
+        assert!(deleted.ends_with(" (deleted)"), "{}", deleted);
+        assert_eq!(info(deleted).await["cwd_exists"], false);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(cwd::read(pid), None);
+    }
+
+    #[test]
+    fn test_parse_proc_stat_cpu_times() {
+        let stat = "4242 (my (app)) R 1 4242 4240 0 -1 4194560 1207 0 0 0 12345 678 0 0 20 0 1 0";
+        let times = parse_cpu_times(stat, 100).unwrap();
+        assert_eq!(times, CpuTimes { user_seconds: 123.45, system_seconds: 6.78 });
+        assert!((times.total_seconds() - 130.23).abs() < 1e-9);
+        assert_eq!(parse_cpu_times("4242 (cat) S 1 4242 4240 0", 100), None);
+    }
+
+    #[test]
+    fn test_parse_page_faults() {
+        let stat = "4242 (my (app)) R 1 4242 4240 0 -1 4194560 1207 35 12 3 12345 678 0 0 20 0 1 0";
+        assert_eq!(
+            parse_page_faults(stat),
+            Some(PageFaults { minor: 1207, children_minor: 35, major: 12, children_major: 3 })
+        );
+        assert_eq!(parse_page_faults("4242 (cat) S 1 4242 4240 0 -1 4194560 1207"), None);
+
+        let vmstat = "pgfault 998877\npgmajfault 4321\npgmajfault_s 1\n";
+        assert_eq!(parse_major_faults(vmstat), Some(4321));
+        assert_eq!(parse_major_faults("pgfault 1\n"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_info_page_faults() {
+        let own_pid = std::process::id();
+        let (_, body) = get_json(&format!("/api/process/{}/info", own_pid)).await;
+        // Loading the test binary alone takes thousands of minor faults
+        assert!(body["minor_faults"].as_u64().unwrap() > 0);
+        assert!(body["major_faults"].is_u64());
+        assert!(body["children_major_faults"].is_u64());
+
+        let (router, _) = mock_router();
+        let (_, body) = send(router.clone(), "GET", "/api/process/200/info", None).await;
+        assert!(body.get("major_faults").is_none());
+        let (_, body) = send(router, "GET", "/api/stats", None).await;
+        assert_eq!(body["memory"]["system_major_faults_per_sec"], 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cpu_time_never_decreases_across_refreshes() {
+        let source = SysinfoSource::new();
+        let own_cpu_time = |source: &SysinfoSource| {
+            source.refresh_processes();
+            let table = source.list_processes();
+            let own = table.processes.iter().find(|p| p.pid == std::process::id()).unwrap();
+            (own.cpu_time_user.unwrap(), own.cpu_time_system.unwrap())
+        };
+
+        let before = own_cpu_time(&source);
+        let started = std::time::Instant::now();
+        let mut spin = 0u64;
+        while started.elapsed() < std::time::Duration::from_millis(50) {
+            spin = std::hint::black_box(spin.wrapping_add(1));
+        }
+        let after = own_cpu_time(&source);
+        assert!(after.0 >= before.0 && after.1 >= before.1, "{:?} then {:?}", before, after);
+        assert!(after.0 + after.1 > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_processes_sort_by_cpu_time() {
+        let fixture = r#"{
+            "cpu_count": 1,
+            "total_memory": 1073741824,
+            "processes": [
+                {"pid": 1, "name": "init", "cpu_usage": 0.0, "memory": 1048576, "status": "sleeping", "cpu_time_user": 2.0, "cpu_time_system": 8.0},
+                {"pid": 2, "name": "miner", "cpu_usage": 1.0, "memory": 1048576, "status": "running", "cpu_time_user": 10000.0, "cpu_time_system": 800.0},
+                {"pid": 3, "name": "busy", "cpu_usage": 90.0, "memory": 1048576, "status": "running", "cpu_time_user": 0.5},
+                {"pid": 4, "name": "unknown", "cpu_usage": 0.0, "memory": 1048576, "status": "running"}
+            ]
+        }"#;
+        let source = Arc::new(MockSystemSource::from_fixture(fixture).unwrap());
+        let router = build_router(AppState::with_source(Config::default(), source));
+
+        let (status, body) = send(router.clone(), "GET", "/api/processes?sort=cpu_time", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let processes = body["processes"].as_array().unwrap();
+        let pids: Vec<u64> = processes.iter().map(|p| p["pid"].as_u64().unwrap()).collect();
+        assert_eq!(pids, vec![2, 1, 3, 4]);
+        assert_eq!(processes[0]["cpu_time_seconds"].as_f64().unwrap(), 10800.0);
+        assert_eq!(processes[2]["cpu_time_seconds"].as_f64().unwrap(), 0.5);
+        assert!(processes[3]["cpu_time_seconds"].is_null());
+
+        let (_, body) = send(router.clone(), "GET", "/api/processes?sort_by=cpu_time&order=asc", None).await;
+        assert_eq!(body["processes"][0]["pid"], 4);
+
+        let (_, body) = send(router, "GET", "/api/process/2/info", None).await;
+        assert_eq!(body["cpu_time_user_seconds"].as_f64().unwrap(), 10000.0);
+        assert_eq!(body["cpu_time_system_seconds"].as_f64().unwrap(), 800.0);
+        assert_eq!(body["cpu_time_total_seconds"].as_f64().unwrap(), 10800.0);
+    }
+
+    #[tokio::test]
+    async fn test_processes_report_session_ids() {
+        let own_pid = std::process::id();
+        let (_, body) = get_json("/api/processes").await;
+        let own = body["processes"].as_array().unwrap().iter().find(|p| p["pid"] == own_pid).cloned();
+
+        if cfg!(unix) {
+            let ids = procstat::read(own_pid).unwrap();
+            let own = own.unwrap();
+            assert_eq!(own["session_id"], ids.session_id);
+            assert_eq!(own["process_group_id"], ids.process_group_id);
+            assert_eq!(own["tty"].is_null(), ids.tty_nr == 0 || TtyNames::scan().name(ids.tty_nr).is_none());
+        } else {
+            assert!(own.is_none_or(|own| own["session_id"].is_null()));
+        }
+
+        let (router, _) = mock_router();
+        let (_, body) = send(router, "GET", "/api/processes", None).await;
+        assert!(body["processes"][0]["tty"].is_null());
+    }
+
+    #[test]
+    fn test_parse_seccomp_status() {
+        let status = "Name:\tcat\nSeccomp:\t2\nSeccomp_filters:\t1\n";
+        assert_eq!(seccomp::parse_status(status), Some(SeccompStatus::Filter));
+        assert_eq!(seccomp::parse_status("Seccomp_filters:\t0\nSeccomp:\t0\n"), Some(SeccompStatus::Disabled));
+        assert_eq!(seccomp::parse_status("Seccomp: 1"), Some(SeccompStatus::Strict));
+        assert_eq!(seccomp::parse_status("Seccomp:\t3\n"), None);
+        assert_eq!(seccomp::parse_status("Name:\tcat\n"), None);
+        assert_eq!(serde_json::to_value(SeccompStatus::Filter).unwrap(), "filter");
+    }
+
+    #[tokio::test]
+    async fn test_process_info_namespaces_and_seccomp() {
+        let own_pid = std::process::id();
+        let (status, body) = get_json(&format!("/api/process/{}/info", own_pid)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        if cfg!(target_os = "linux") {
+            let own = namespaces::read(own_pid).unwrap();
+            assert!(own.net_ns.is_some() && own.mnt_ns.is_some());
+            assert_eq!(body["namespaces"]["net_ns"], own.net_ns.unwrap());
+            let seccomp = serde_json::to_value(seccomp::read(own_pid).unwrap()).unwrap();
+            assert_eq!(body["seccomp_status"], seccomp);
+        } else {
+            assert!(body["namespaces"].is_null());
+            assert!(body["seccomp_status"].is_null());
+        }
+        assert_eq!(namespaces::read(u32::MAX), None);
+    }
+
+    #[test]
+    fn test_parse_capabilities() {
+        let status = "Name:\tping\nCapInh:\t0000000000000000\nCapPrm:\t0000000000003000\nCapEff:\t0000000000002000\n\
+                      CapBnd:\t000001ffffffffff\nCapAmb:\t0000000000000400\n";
+        let caps = capabilities::parse_status(status).unwrap();
+        assert_eq!(caps.effective, ["CAP_NET_RAW"]);
+        assert_eq!(caps.permitted, ["CAP_NET_ADMIN", "CAP_NET_RAW"]);
+        assert!(caps.inheritable.is_empty());
+        assert_eq!(caps.bounding.len(), 41);
+        assert_eq!(caps.bounding.last().unwrap(), "CAP_CHECKPOINT_RESTORE");
+        assert_eq!(caps.ambient, ["CAP_NET_BIND_SERVICE"]);
+
+        // Kernels before 4.3 have no CapAmb line
+        let caps = capabilities::parse_status("CapEff:\t0000000000200000\n").unwrap();
+        assert_eq!(caps.effective, ["CAP_SYS_ADMIN"]);
+        assert!(caps.ambient.is_empty());
+        assert_eq!(capabilities::parse_status("Name:\tcat\n"), None);
+        assert_eq!(capabilities::names(1 << 41 | 1), ["CAP_CHOWN", "CAP_41"]);
+    }
+
+    #[tokio::test]
+    async fn test_process_capabilities() {
+        let own_pid = std::process::id();
+        let (status, body) = get_json(&format!("/api/process/{}/capabilities", own_pid)).await;
+        if cfg!(target_os = "linux") {
+            assert_eq!(status, StatusCode::OK);
+            let own = serde_json::to_value(capabilities::read(own_pid).unwrap()).unwrap();
+            assert_eq!(body, own);
+            assert!(!body["bounding"].as_array().unwrap().is_empty());
+        } else {
+            assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+        }
+
+        let (status, _) = get_json(&format!("/api/process/{}/capabilities", u32::MAX)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // The mock source has no capabilities to report
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/process/100/capabilities", None).await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(body["error"]["code"], "not_implemented");
+    }
+
+    #[test]
+    fn test_parse_psi_memory() {
+        let text = "some avg10=0.31 avg60=0.12 avg300=0.03 total=123456\nfull avg10=0.00 avg60=0.05 avg300=0.01 total=7890\n";
+        assert_eq!(
+            parse_psi(text),
+            Some(MemoryPressure {
+                some: PressureStall { avg10: 0.31, avg60: 0.12, avg300: 0.03, total_us: 123_456 },
+                full: Some(PressureStall { avg10: 0.0, avg60: 0.05, avg300: 0.01, total_us: 7_890 }),
+            })
+        );
+
+        // older kernels have no `full` line for some resources
+        let some_only = parse_psi("some avg10=1.50 avg60=2.00 avg300=0.75 total=42\n").unwrap();
+        assert_eq!(some_only.full, None);
+        assert!(serde_json::to_value(some_only).unwrap().get("full").is_none());
+
+        assert_eq!(parse_psi(""), None);
+        assert_eq!(parse_psi("some avg10=x avg60=0 avg300=0 total=0\n"), None);
+    }
+
+    #[test]
+    fn test_parse_oom_score() {
+        assert_eq!(oom::parse_score("667\n"), Some(667));
+        assert_eq!(oom::parse_score("-1000\n"), Some(-1000));
+        assert_eq!(oom::parse_score(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_oom_and_pressure_absent_from_mock() {
+        let (router, _) = mock_router();
+        let (_, body) = send(router.clone(), "GET", "/api/process/200/info", None).await;
+        assert!(body.get("oom_score").is_none());
+        assert!(body.get("oom_score_adj").is_none());
+        let (_, body) = send(router, "GET", "/api/stats", None).await;
+        assert!(body.get("memory_pressure").is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_info_oom_score() {
+        let own_pid = std::process::id();
+        let (status, body) = get_json(&format!("/api/process/{}/info", own_pid)).await;
+        assert_eq!(status, StatusCode::OK);
+        let own = oom::read(own_pid).unwrap();
+        assert_eq!(body["oom_score"], own.score);
+        assert_eq!(body["oom_score_adj"], own.adj);
+        assert!((-1000..=1000).contains(&own.adj));
+
+        let (_, stats) = get_json("/api/stats").await;
+        if std::path::Path::new("/proc/pressure/memory").exists() {
+            assert!(stats["memory_pressure"]["some"]["avg10"].is_number());
+        } else {
+            assert!(stats.get("memory_pressure").is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oom_adj_endpoint() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router.clone(), "POST", "/api/process/200/oom_adj", Some(r#"{"adjustment": 500}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "bash");
+        assert_eq!(body["oom_score_adj"], 500);
+
+        let (_, body) = send(router.clone(), "GET", "/api/processes", None).await;
+        let bash = body["processes"].as_array().unwrap().iter().find(|p| p["pid"] == 200).unwrap();
+        assert_eq!(bash["oom_score_adj"], 500);
+        let (_, body) = send(router.clone(), "GET", "/api/process/200/info", None).await;
+        assert_eq!(body["oom_score_adj"], 500);
+
+        // Protected processes are exactly the ones worth shielding
+        let (status, _) = send(router.clone(), "POST", "/api/process/1/oom_adj", Some(r#"{"adjustment": -1000}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = send(router.clone(), "POST", "/api/process/200/oom_adj", Some(r#"{"adjustment": 1001}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_body");
+
+        let (status, _) = send(router, "POST", "/api/process/99999/oom_adj", Some(r#"{"adjustment": 0}"#)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (router, _) = mock_router_with(Config { auth: AuthConfig { read_only: true, ..Default::default() }, ..Default::default() });
+        let (status, body) = send(router, "POST", "/api/process/200/oom_adj", Some(r#"{"adjustment": 0}"#)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "read_only");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_oom_adj_written_to_proc() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        // Raising the adjustment needs no privileges
+        let uri = format!("/api/process/{}/oom_adj", pid);
+        let (status, body) = send(router(), "POST", &uri, Some(r#"{"adjustment": 700}"#)).await;
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["oom_score_adj"], 700);
+        assert!(body["oom_score"].as_i64().unwrap() >= 700);
+    }
+
+    #[tokio::test]
+    async fn test_rename_endpoint() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router.clone(), "POST", "/api/process/200/rename", Some(r#"{"name": "my-worker"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["old_name"], "bash");
+        assert_eq!(body["name"], "my-worker");
+        assert!(body["note"].as_str().unwrap().contains("comm"));
+
+        let (_, body) = send(router.clone(), "GET", "/api/processes", None).await;
+        let renamed = body["processes"].as_array().unwrap().iter().find(|p| p["pid"] == 200).unwrap();
+        assert_eq!(renamed["name"], "my-worker");
+
+        // 16 bytes: one more than the kernel keeps
+        let (status, body) = send(router.clone(), "POST", "/api/process/200/rename", Some(r#"{"name": "sixteen-byte-nam"}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_body");
+        let (status, _) = send(router.clone(), "POST", "/api/process/200/rename", Some(r#"{"name": ""}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(router.clone(), "POST", "/api/process/200/rename", Some(r#"{"name": "a\nb"}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = send(router, "POST", "/api/process/99999/rename", Some(r#"{"name": "x"}"#)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (router, _) = mock_router_with(Config { auth: AuthConfig { read_only: true, ..Default::default() }, ..Default::default() });
+        let (status, body) = send(router, "POST", "/api/process/200/rename", Some(r#"{"name": "x"}"#)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "read_only");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_rename_other_process_refused() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let uri = format!("/api/process/{}/rename", pid);
+        let (status, body) = send(router(), "POST", &uri, Some(r#"{"name": "renamed"}"#)).await;
+        let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+        // The kernel refuses even root, and even for our own child
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "rename_not_permitted");
+        assert_eq!(body["error"]["pid"], pid);
+        assert_eq!(comm.trim(), "sleep");
+    }
+
+    #[test]
+    fn test_parse_proc_mounts() {
+        let text = "/dev/sda1 / ext4 rw,relatime 0 0\n\
+                    nas:/export/media /mnt/My\\040Media nfs4 ro,vers=4.2 0 0\n\
+                    //server/share /mnt/share cifs rw,vers=3.0 0 0\n";
+        let mounts = parse_proc_mounts(text);
+
+        assert_eq!(mounts.len(), 3);
+        assert_eq!(mounts[0].device, "/dev/sda1");
+        assert_eq!(mounts[0].options, ["rw", "relatime"]);
+        assert!(!mounts[0].is_network_fs && !mounts[0].is_read_only);
+        assert_eq!(mounts[1].mount_point, "/mnt/My Media");
+        assert!(mounts[1].is_network_fs && mounts[1].is_read_only);
+        assert!(mounts[2].is_network_fs);
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_proc_net() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+                   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 31337 1 0000000000000000 100 0 0 10 0\n\
+                   1: 0F02000A:C350 2E3AD9AC:01BB 01 00000000:00000000 02:000A7B2B 00000000  1000        0 42 2 0000000000000000 20 4 30 10 -1\n";
+        let sockets = parse_proc_net(tcp, "tcp");
+        assert_eq!(sockets.len(), 2);
+        let (listen, inode) = &sockets[0];
+        assert_eq!((listen.local_address.as_str(), listen.local_port), ("127.0.0.1", 8080));
+        assert_eq!((listen.remote_address.as_str(), listen.remote_port), ("0.0.0.0", 0));
+        assert_eq!(listen.state, "LISTEN");
+        assert_eq!(listen.protocol, "tcp");
+        assert_eq!(*inode, 31337);
+        let (established, _) = &sockets[1];
+        assert_eq!((established.local_address.as_str(), established.local_port), ("10.0.2.15", 50000));
+        assert_eq!((established.remote_address.as_str(), established.remote_port), ("172.217.58.46", 443));
+        assert_eq!(established.state, "ESTABLISHED");
+
+        let udp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops\n\
+                    0: 00000000000000000000000001000000:0035 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 777 2 0000000000000000 0\n";
+        let (dns, inode) = &parse_proc_net(udp6, "udp6")[0];
+        assert_eq!((dns.local_address.as_str(), dns.local_port), ("::1", 53));
+        assert_eq!(dns.remote_address, "::");
+        assert_eq!(dns.state, "UNCONN");
+        assert_eq!(*inode, 777);
+
+        assert_eq!(
+            parse_hex_address("B80D0120000000000000000001000000:0016").unwrap().0.to_string(),
+            "2001:db8::1"
+        );
+        assert!(parse_hex_address("0100007F").is_none());
+        assert_eq!(socket_inode("socket:[31337]"), Some(31337));
+        assert_eq!(socket_inode("pipe:[31337]"), None);
+    }
+
+    #[test]
+    fn test_parse_arp_tables() {
+        let proc_arp = "IP address       HW type     Flags       HW address            Mask     Device\n\
+                        192.168.1.1      0x1         0x2         52:54:00:AB:35:02     *        eth0\n\
+                        192.168.1.77     0x1         0x0         00:00:00:00:00:00     *        wlan0\n";
+        let entries = parse_proc_arp(proc_arp);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].ip_address, "192.168.1.1");
+        assert_eq!((entries[0].hw_type.as_str(), entries[0].flags.as_str()), ("0x1", "0x2"));
+        assert_eq!(entries[0].mac_address, "52:54:00:ab:35:02");
+        assert_eq!((entries[0].mask.as_str(), entries[0].device.as_str()), ("*", "eth0"));
+        assert_eq!(entries[1].flags, "0x0");
+
+        let arp_an = "? (192.168.1.1) at 0:11:22:3:44:55 on en0 ifscope [ethernet]\n\
+                      ? (192.168.1.9) at (incomplete) on en0 ifscope [ethernet]\n\
+                      ? (224.0.0.251) at 1:0:5e:0:0:fb on en0 ifscope permanent [ethernet]\n";
+        let entries = parse_arp_an(arp_an);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].ip_address, "192.168.1.1");
+        assert_eq!(entries[0].mac_address, "00:11:22:03:44:55");
+        assert_eq!((entries[0].hw_type.as_str(), entries[0].device.as_str()), ("ethernet", "en0"));
+        assert_eq!(entries[1].mac_address, "00:00:00:00:00:00");
+        assert_eq!(entries[2].flags, "permanent");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_connections_include_own_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (router, _) = mock_router();
+        let uri = format!("/api/network/connections?pid={}&state=listen", std::process::id());
+        let (status, body) = send(router, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let connections = body.as_array().unwrap();
+        let own = connections.iter().find(|c| c["local_port"] == port).expect("listener is reported");
+        assert_eq!(own["local_address"], "127.0.0.1");
+        assert_eq!(own["protocol"], "tcp");
+        assert!(own["namespace_inode"].is_u64());
+        assert_eq!(own["state"], "LISTEN");
+        assert_eq!(own["pid"], std::process::id());
+        assert!(connections.iter().all(|c| c["pid"] == std::process::id() && c["state"] == "LISTEN"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_connections_in_own_namespace() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let pid = std::process::id();
+        let (status, body) = send(router(), "GET", &format!("/api/process/{}/connections", pid), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let connections = body.as_array().unwrap();
+        assert!(connections.iter().all(|c| c["pid"] == pid));
+        let own = connections.iter().find(|c| c["local_port"] == port).expect("listener is reported");
+        assert_eq!(own["state"], "LISTEN");
+        let own_ns = namespaces::read(pid).and_then(|ns| ns.net_ns);
+        assert_eq!(own["namespace_inode"].as_u64(), own_ns);
+    }
+
+    #[test]
+    fn test_proc_net_path_falls_back_to_host_table() {
+        // PID 1 shares its own namespace, and a missing process has none
+        assert_eq!(proc_net_path(1), std::path::Path::new("/proc/net/tcp"));
+        assert_eq!(proc_net_path(u32::MAX), std::path::Path::new("/proc/net/tcp"));
+    }
+
+    #[tokio::test]
+    async fn test_process_connections_not_found() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/process/999999/connections", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "process_not_found");
+    }
+
+    #[test]
+    fn test_parse_sysfs_link() {
+        let eth0 = |file: &str| match file {
+            "operstate" => Some("up\n".to_string()),
+            "speed" => Some("1000\n".to_string()),
+            "duplex" => Some("full\n".to_string()),
+            "address" => Some("52:54:00:12:34:56\n".to_string()),
+            "mtu" => Some("1500\n".to_string()),
+            _ => None,
+        };
+        let link = parse_sysfs_link(eth0);
+        assert!(link.is_up);
+        assert_eq!(link.speed_mbps, Some(1000));
+        assert_eq!(link.duplex.as_deref(), Some("full"));
+        assert_eq!(link.mac_address.as_deref(), Some("52:54:00:12:34:56"));
+        assert_eq!(link.mtu, Some(1500));
+
+        // Unplugged cable: speed reads -1 (or fails) and duplex is "unknown"
+        let down = |file: &str| match file {
+            "operstate" => Some("down\n".to_string()),
+            "speed" => Some("-1\n".to_string()),
+            "duplex" => Some("unknown\n".to_string()),
+            _ => None,
+        };
+        let link = parse_sysfs_link(down);
+        assert!(!link.is_up);
+        assert_eq!((link.speed_mbps, link.duplex), (None, None));
+
+        let lo = |file: &str| match file {
+            "operstate" => Some("unknown\n".to_string()),
+            "carrier" => Some("1\n".to_string()),
+            "address" => Some("00:00:00:00:00:00\n".to_string()),
+            _ => None,
+        };
+        let link = parse_sysfs_link(lo);
+        assert!(link.is_up);
+        assert_eq!(link.mac_address, None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_loopback_interface_has_address() {
+        let networks = sysinfo::Networks::new_with_refreshed_list();
+        let interfaces = netif::interfaces(&networks);
+        let Some(lo) = interfaces.iter().find(|i| i.name == "lo") else { return };
+        assert!(lo.is_up);
+        assert!(lo.ip_addresses.iter().any(|ip| ip == "127.0.0.1"));
+        assert_eq!(lo.wifi, None);
+    }
+
+    #[test]
+    fn test_parse_airport() {
+        let text = "     agrCtlRSSI: -58\n     agrExtRSSI: 0\n    agrCtlNoise: -92\n          state: running\n\
+                    \x20       op mode: station\n     lastTxRate: 780\n        maxRate: 867\n\
+                    \x20         BSSID: a0:b1:c2:d3:e4:f5\n           SSID: Office: 5G\n        channel: 149,80\n";
+        let wifi = parse_airport(text).unwrap();
+        assert_eq!(wifi.ssid, "Office: 5G");
+        assert_eq!(wifi.signal_dbm, -58);
+        assert_eq!(wifi.frequency_mhz, 5745);
+        assert_eq!(wifi.bitrate_mbps, 780.0);
+        assert!(wifi.is_connected);
+
+        let idle = parse_airport("     agrCtlRSSI: 0\n          state: init\n        channel: 1\n").unwrap();
+        assert!(!idle.is_connected);
+        assert_eq!((idle.ssid.as_str(), idle.signal_dbm, idle.frequency_mhz), ("", 0, 0));
+        assert!(!parse_airport("AirPort: Off\n").unwrap().is_connected);
+        assert_eq!(parse_airport(""), None);
+
+        assert_eq!(channel_frequency(6), Some(2437));
+        assert_eq!(channel_frequency(14), Some(2484));
+        assert_eq!(channel_frequency(36), Some(5180));
+        assert_eq!(channel_frequency(0), None);
+    }
+
+    const PROC_MAPS: &str = "\
+55b31906f000-55b319071000 r--p 00000000 fe:00 317783                     /usr/bin/curl
+55b319071000-55b319077000 r-xp 00002000 fe:00 317783                     /usr/bin/curl
+55b31a000000-55b31a021000 rw-p 00000000 00:00 0                          [heap]
+7f1c2a000000-7f1c2a100000 rw-p 00000000 00:00 0 
+7f1c2b000000-7f1c2b020000 r--p 00000000 fe:00 401                        /usr/lib/x86_64-linux-gnu/libssl.so.3
+7f1c2b020000-7f1c2b080000 r-xp 00020000 fe:00 401                        /usr/lib/x86_64-linux-gnu/libssl.so.3
+7f1c2c000000-7f1c2c001000 r--p 00000000 fe:00 502                        /home/alice/My Fonts/a.ttf (deleted)
+garbage line
+7ffd1e000000-7ffd1e021000 rw-p 00000000 00:00 0                          [stack]
+";
+
+    #[test]
+    fn test_parse_proc_maps() {
+        let regions = parse_proc_maps(PROC_MAPS);
+        assert_eq!(regions.len(), 8);
+        assert_eq!(
+            regions[1],
+            MapRegion { start: 0x55b319071000, end: 0x55b319077000, permissions: "r-xp".to_string(), path: Some("/usr/bin/curl".to_string()) }
+        );
+        assert_eq!(regions[3].path, None);
+        assert!(regions[3].is_anonymous() && regions[2].is_anonymous());
+        assert_eq!(regions[6].path.as_deref(), Some("/home/alice/My Fonts/a.ttf (deleted)"));
+        assert_eq!(regions[6].size(), 0x1000);
+    }
+
+    #[test]
+    fn test_summarize_maps_merges_files_and_anonymous_memory() {
+        let mappings = maps::summarize(&parse_proc_maps(PROC_MAPS), None);
+        let paths: Vec<Option<&str>> = mappings.iter().map(|m| m.path.as_deref()).collect();
+        assert_eq!(
+            paths,
+            [None, Some("/home/alice/My Fonts/a.ttf (deleted)"), Some("/usr/bin/curl"), Some("/usr/lib/x86_64-linux-gnu/libssl.so.3")]
+        );
+        assert!(mappings[0].anonymous);
+        assert_eq!(mappings[0].regions, 3);
+        assert_eq!(mappings[0].total_bytes, 0x21000 + 0x100000 + 0x21000);
+        let ssl = &mappings[3];
+        assert_eq!((ssl.regions, ssl.total_bytes), (2, 0x80000));
+        assert_eq!(ssl.permissions, ["r--p", "r-xp"]);
+
+        let filtered = maps::summarize(&parse_proc_maps(PROC_MAPS), Some("SSL"));
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].path.as_deref(), Some("/usr/lib/x86_64-linux-gnu/libssl.so.3"));
+    }
+
+    #[tokio::test]
+    async fn test_process_maps_endpoint_pages_and_filters() {
+        let (router, source) = mock_router();
+        source.set_maps(200, parse_proc_maps(PROC_MAPS));
+
+        let (status, body) = send(router.clone(), "GET", "/api/process/200/maps?limit=2", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_count"], 4);
+        assert_eq!(body["mappings"].as_array().unwrap().len(), 2);
+        assert_eq!(body["mappings"][0]["anonymous"], true);
+        assert_eq!(body["mappings"][0]["path"], Value::Null);
+
+        let (_, body) = send(router.clone(), "GET", "/api/process/200/maps?limit=2&offset=2", None).await;
+        assert_eq!(body["mappings"][0]["path"], "/usr/bin/curl");
+
+        let (_, body) = send(router.clone(), "GET", "/api/process/200/maps?filter=ssl", None).await;
+        assert_eq!(body["total_count"], 1);
+        assert_eq!(body["mappings"][0]["regions"], 2);
+
+        let (status, _) = send(router, "GET", "/api/process/99999/maps", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_own_maps_include_executable() {
+        let regions = SysinfoSource::new().memory_maps(std::process::id()).unwrap();
+        let exe = std::env::current_exe().unwrap().display().to_string();
+        let mappings = maps::summarize(&regions, None);
+        assert!(mappings.iter().any(|m| m.path.as_deref() == Some(exe.as_str())));
+        assert!(mappings[0].anonymous);
+    }
+
+    fn thread(tid: u32, name: &str, cpu_seconds: Option<f64>) -> ThreadSample {
+        ThreadSample { tid, name: name.to_string(), state: "running".to_string(), cpu_seconds }
+    }
+
+    #[test]
+    fn test_thread_cpu_usage_from_two_readings() {
+        let before = [thread(10, "main", Some(1.0)), thread(11, "worker", Some(5.0)), thread(12, "io", None)];
+        let after = vec![
+            thread(10, "main", Some(1.02)),
+            thread(11, "worker", Some(5.2)),
+            thread(12, "io", None),
+            thread(13, "new", Some(0.5)),
+        ];
+        let threads = threads::cpu_usage(&before, after, std::time::Duration::from_millis(200));
+        let order: Vec<u32> = threads.iter().map(|t| t.tid).collect();
+        assert_eq!(order, [11, 10, 12, 13]);
+        assert!((threads[0].cpu_percent.unwrap() - 100.0).abs() < 0.01);
+        assert!((threads[1].cpu_percent.unwrap() - 10.0).abs() < 0.01);
+        assert_eq!(threads[2].cpu_percent, None);
+        assert_eq!(threads[3].cpu_percent, None);
+        assert_eq!(threads::state_name('D'), "sleeping");
+        assert_eq!(threads::state_name('T'), "stopped");
+    }
+
+    #[tokio::test]
+    async fn test_process_threads_endpoint() {
+        let (router, source) = mock_router();
+        source.set_threads(200, vec![thread(200, "bash", Some(3.0)), thread(201, "bash-io", None)]);
+
+        let (status, body) = send(router.clone(), "GET", "/api/process/200/threads", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["thread_count"], 2);
+        assert_eq!(body["total_cpu_percent"], 0.0);
+        assert!(body["sample_ms"].as_u64().unwrap() >= 200);
+        assert_eq!(body["threads"][0]["tid"], 200);
+        assert_eq!(body["threads"][1]["cpu_percent"], Value::Null);
+
+        let (status, _) = send(router, "GET", "/api/process/99999/threads", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_own_threads_include_main_thread() {
+        let pid = std::process::id();
+        let threads = SysinfoSource::new().threads(pid).unwrap();
+        let main = threads.iter().find(|t| t.tid == pid).expect("main thread is listed");
+        assert!(!main.name.is_empty());
+        assert!(main.cpu_seconds.is_some());
+    }
+
+    #[test]
+    fn test_parse_proc_io() {
+        let text = "rchar: 323934931\nwchar: 323929600\nsyscr: 632687\nsyscw: 632675\n\
+                    read_bytes: 4096\nwrite_bytes: 323932160\ncancelled_write_bytes: 512\n";
+        assert_eq!(
+            parse_proc_io(text),
+            Some(IoCounters {
+                read_bytes: 4096,
+                write_bytes: 323932160,
+                read_syscalls: 632687,
+                write_syscalls: 632675,
+                cancelled_write_bytes: 512,
+            })
+        );
+        assert_eq!(parse_proc_io("rchar: 1\nwchar: 2\n"), None);
+    }
+
+    #[test]
+    fn test_io_rates_between_readings() {
+        let before = IoCounters { read_bytes: 1000, write_bytes: 0, read_syscalls: 10, write_syscalls: 4, cancelled_write_bytes: 0 };
+        let after = IoCounters { read_bytes: 3000, write_bytes: 500, read_syscalls: 30, write_syscalls: 4, cancelled_write_bytes: 0 };
+        let rates = procio::rates(&before, &after, std::time::Duration::from_millis(500)).unwrap();
+        assert_eq!(rates.read_bytes_per_sec, 4000.0);
+        assert_eq!(rates.write_bytes_per_sec, 1000.0);
+        assert_eq!(rates.read_syscalls_per_sec, 40.0);
+        assert_eq!(rates.write_syscalls_per_sec, 0.0);
+        assert_eq!(rates.interval_ms, 500);
+        // A reused PID starts its counters over
+        assert_eq!(procio::rates(&after, &before, std::time::Duration::from_secs(1)), None);
+    }
+
+    #[tokio::test]
+    async fn test_process_io_endpoint() {
+        let (router, source) = mock_router();
+        let mut counters = IoCounters { read_bytes: 1536, write_bytes: 2048, read_syscalls: 7, write_syscalls: 3, cancelled_write_bytes: 0 };
+        source.set_io(200, Some(counters));
+        source.set_io(1, None);
+
+        let (status, body) = send(router.clone(), "GET", "/api/process/200/io", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["pid"], 200);
+        assert_eq!(body["read_bytes"], 1536);
+        assert_eq!(body["write_syscalls"], 3);
+        assert_eq!(body["read_bytes_formatted"], "1.5 KB");
+        assert_eq!(body["per_sec"], Value::Null);
+
+        counters.read_bytes += 4096;
+        source.set_io(200, Some(counters));
+        let (status, body) = send(router.clone(), "GET", "/api/process/200/io", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["per_sec"]["read_bytes_per_sec"].as_f64().unwrap() > 0.0);
+        assert_eq!(body["per_sec"]["write_bytes_per_sec"], 0.0);
+
+        let (status, body) = send(router.clone(), "GET", "/api/process/1/io", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "permission_denied");
+
+        let (status, _) = send(router, "GET", "/api/process/99999/io", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_own_io_counters_are_readable() {
+        let counters = SysinfoSource::new().io_counters(std::process::id()).unwrap();
+        assert!(counters.read_syscalls > 0);
+    }
+
+    #[test]
+    fn test_parse_proc_limits() {
+        let text = "\
+Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max open files            1024                 524288               files     
+Max nice priority         0                    0                    
+Max realtime timeout      unlimited            unlimited            us        
+";
+        let limits = parse_proc_limits(text);
+        assert_eq!(limits.len(), 4);
+        assert_eq!(limits[0].resource, "cpu");
+        assert_eq!(limits[0].soft, None);
+        assert_eq!(limits[0].unit.as_deref(), Some("seconds"));
+        assert_eq!(limits[1].resource, "nofile");
+        assert_eq!(limits[1].name, "Max open files");
+        assert_eq!((limits[1].soft, limits[1].hard), (Some(1024), Some(524288)));
+        assert_eq!(limits[2].resource, "nice");
+        assert_eq!(limits[2].unit, None);
+        assert_eq!(limits[3].resource, "rttime");
+        assert!(parse_proc_limits("").is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_own_process_limits() {
+        let uri = format!("/api/process/{}/limits", std::process::id());
+        let (status, body) = send(router(), "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let nofile = body["limits"].as_array().unwrap().iter().find(|l| l["resource"] == "nofile").unwrap();
+        assert!(nofile["soft"].as_u64().unwrap() > 0);
+        assert!(body["open_files"].as_u64().unwrap() > 0);
+
+        let (status, _) = send(mock_router().0, "GET", "/api/process/99999/limits", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_parse_environ() {
+        let vars = parse_environ(b"PATH=/usr/bin\0DATABASE_URL=postgres://db/app?sslmode=require\0NOEQUALS\0EMPTY=\0");
+        assert_eq!(
+            vars,
+            [
+                ("PATH".to_string(), "/usr/bin".to_string()),
+                ("DATABASE_URL".to_string(), "postgres://db/app?sslmode=require".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+        assert!(parse_environ(b"").is_empty());
+    }
+
+    #[test]
+    fn test_redactor_matches_case_insensitively() {
+        let redactor = Redactor::new(&Config::default().redact_env).unwrap();
+        assert!(redactor.matches("GITHUB_TOKEN"));
+        assert!(redactor.matches("aws_secret_access_key"));
+        assert!(redactor.matches("PGPASSWORD"));
+        assert!(!redactor.matches("PATH"));
+    }
+
+    fn environ_router() -> (Router, Arc<MockSystemSource>) {
+        let (router, source) = mock_router_with(auth_config(false));
+        source.set_environ(200, &[("HOME", "/home/alice"), ("API_TOKEN", "abc123"), ("db_password", "hunter2")]);
+        source.deny_environ(1);
+        (router, source)
+    }
+
+    #[tokio::test]
+    async fn test_process_environ_redacts_secrets() {
+        let (router, _) = environ_router();
+        let (status, body) = send_with_auth(router.clone(), "GET", "/api/process/200/environ", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["pid"], 200);
+        assert_eq!(body["redacted"], true);
+        assert_eq!(body["variables"]["HOME"], "/home/alice");
+        assert_eq!(body["variables"]["API_TOKEN"], "***");
+        assert_eq!(body["variables"]["db_password"], "***");
+
+        let (status, body) = send_with_auth(router, "GET", "/api/process/200/environ?redact=false", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["redacted"], false);
+        assert_eq!(body["variables"]["API_TOKEN"], "abc123");
+    }
+
+    #[tokio::test]
+    async fn test_process_environ_needs_token_even_for_open_reads() {
+        let (router, _) = environ_router();
+        let (status, _) = send_with_auth(router.clone(), "GET", "/api/process/200/info", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send_with_auth(router, "GET", "/api/process/200/environ", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "missing_token");
+    }
+
+    #[tokio::test]
+    async fn test_process_environ_errors() {
+        let (router, _) = environ_router();
+        let (status, body) = send_with_auth(router.clone(), "GET", "/api/process/1/environ", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "permission_denied");
+        assert_eq!(body["error"]["pid"], 1);
+
+        let (status, body) = send_with_auth(router, "GET", "/api/process/99999/environ", Some("Bearer s3cret")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "process_not_found");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_own_environ() {
+        let vars = SysinfoSource::new().environ(std::process::id()).unwrap();
+        assert!(vars.iter().any(|(key, _)| key == "PATH"));
+    }
+
+    #[test]
+    fn test_parse_sysfs_block() {
+        let disk = |rotational: &'static str, removable: &'static str| {
+            move |file: &str| match file {
+                "queue/rotational" => Some(format!("{}\n", rotational)),
+                "removable" => Some(format!("{}\n", removable)),
+                _ => None,
+            }
+        };
+        assert_eq!(parse_sysfs_block("sda", disk("1", "0")), BlockInfo { disk_type: "HDD", is_removable: false });
+        assert_eq!(parse_sysfs_block("sda", disk("0", "0")).disk_type, "SSD");
+        assert_eq!(parse_sysfs_block("nvme0n1", disk("0", "0")).disk_type, "NVMe");
+        assert_eq!(parse_sysfs_block("sdb", disk("0", "1")), BlockInfo { disk_type: "SSD", is_removable: true });
+        assert_eq!(parse_sysfs_block("dm-0", |_| None), BlockInfo { disk_type: "Unknown", is_removable: false });
+    }
+
+    #[test]
+    fn test_parse_sysfs_queue() {
+        let queue = parse_sysfs_queue(|file| match file {
+            "queue/scheduler" => Some("mq-deadline kyber [bfq] none\n".to_string()),
+            "queue/nr_requests" => Some("64\n".to_string()),
+            "queue/iosched/quantum" => Some("8\n".to_string()),
+            "queue/read_ahead_kb" => Some("128\n".to_string()),
+            _ => None,
+        });
+        assert_eq!(
+            queue,
+            QueueSettings {
+                scheduler: Some("bfq".to_string()),
+                nr_requests: Some(64),
+                queue_depth: Some(8),
+                read_ahead_kb: Some(128),
+            }
+        );
+        assert_eq!(parse_sysfs_queue(|_| None), QueueSettings::default());
+
+        assert_eq!(parse_scheduler("[none] mq-deadline\n"), (Some("none".to_string()), vec!["none".to_string(), "mq-deadline".to_string()]));
+        assert_eq!(parse_scheduler("none\n"), (Some("none".to_string()), vec!["none".to_string()]));
+        assert_eq!(parse_scheduler("mq-deadline none"), (None, vec!["mq-deadline".to_string(), "none".to_string()]));
+        assert_eq!(parse_scheduler(""), (None, Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_disk_scheduler_endpoint() {
+        let (router, _) = mock_router();
+        let body = Some(r#"{"scheduler": "none"}"#);
+        let (status, error) = send(router.clone(), "POST", "/api/v1/disks/..%2Fsda/scheduler", body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["error"]["code"], "invalid_device_name");
+
+        if !cfg!(target_os = "linux") {
+            return;
+        }
+        let (status, error) = send(router.clone(), "POST", "/api/v1/disks/nosuchdisk0/scheduler", body).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["error"]["code"], "device_not_found");
+
+        // Rejected before anything is written, so safe against a real disk
+        let Some(device) = std::fs::read_dir("/sys/block").into_iter().flatten().flatten().find_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            entry.path().join("queue/scheduler").exists().then_some(name)
+        }) else {
+            return;
+        };
+        let uri = format!("/api/v1/disks/{}/scheduler", device);
+        let (status, error) = send(router, "POST", &uri, Some(r#"{"scheduler": "no-such-elevator"}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["error"]["code"], "invalid_scheduler");
+        assert!(error["error"]["message"].as_str().unwrap().contains("available: "), "{}", error);
+    }
+
+    #[test]
+    fn test_hardware_id_database() {
+        let ids = IdDatabase::parse(
+            "# comment\n046d  Logitech, Inc.\n\tc52b  Unifying Receiver\n\t\t00  interface, skipped\n\
+             1d6b  Linux Foundation\n\t0002  2.0 root hub\n\n\
+             C 03  Human Interface Device\n\t01  Boot Interface Subclass\n\t\t01  Keyboard\n\
+             AT 0409  English(US)\n\tffff  not a product\n",
+        );
+        assert_eq!(ids.vendor(0x046d), Some("Logitech, Inc."));
+        assert_eq!(ids.device(0x046d, 0xc52b), Some("Unifying Receiver"));
+        assert_eq!(ids.device(0x1d6b, 0x0002), Some("2.0 root hub"));
+        assert_eq!(ids.device(0x1d6b, 0xffff), None);
+        assert_eq!(ids.vendor(0x0409), None);
+        assert_eq!(ids.class(0x03), Some("Human Interface Device"));
+        assert_eq!(ids.subclass(0x03, 0x01), Some("Boot Interface Subclass"));
+        assert_eq!(ids.class(0x42), None);
+        assert_eq!(usb::class_name(&ids, 0x42), "42");
+
+        assert_eq!(usb::ids().device(0x1d6b, 0x0003), Some("3.0 root hub"));
+        assert_eq!(usb::ids().class(0x09), Some("Hub"));
+        assert_eq!(pci::ids().device(0x1af4, 0x1041), Some("Virtio 1.0 network device"));
+        assert_eq!(pci::ids().subclass(0x01, 0x08), Some("Non-Volatile memory controller"));
+
+        assert_eq!(speed_label("1.5\n"), "1.5 Mbps");
+        assert_eq!(speed_label("480"), "480 Mbps");
+        assert_eq!(speed_label("5000"), "5 Gbps");
+        assert_eq!(speed_label(""), "unknown");
+    }
+
+    #[test]
+    fn test_parse_sysfs_usb() {
+        let ids = IdDatabase::parse("046d  Logitech, Inc.\n\tc52b  Unifying Receiver\nC 03  Human Interface Device\nC 09  Hub\n");
+        let files = |vendor: &'static str| {
+            move |file: &str| match file {
+                "idVendor" => Some(format!("{}\n", vendor)),
+                "idProduct" => Some("c52b\n".to_string()),
+                "manufacturer" => Some("Logitech\n".to_string()),
+                "product" => Some("USB Receiver\n".to_string()),
+                "bDeviceClass" => Some("00\n".to_string()),
+                "bConfigurationValue" => Some("1\n".to_string()),
+                "1-2:1.0/bInterfaceClass" => Some("03\n".to_string()),
+                "busnum" => Some("1\n".to_string()),
+                "devnum" => Some("4\n".to_string()),
+                "speed" => Some("12\n".to_string()),
+                _ => None,
+            }
+        };
+        let device = parse_sysfs_usb("1-2", &ids, files("046d")).unwrap();
+        assert_eq!(device.vendor_id, 0x046d);
+        assert_eq!(device.product_id, 0xc52b);
+        assert_eq!(device.vendor_name.as_deref(), Some("Logitech, Inc."));
+        assert_eq!(device.product_name.as_deref(), Some("Unifying Receiver"));
+        assert_eq!(device.serial, None);
+        assert_eq!(device.class, "Human Interface Device");
+        assert_eq!((device.bus, device.device_number), (1, 4));
+        assert_eq!(device.speed, "12 Mbps");
+
+        // Unlisted IDs fall back to the device's own strings
+        let device = parse_sysfs_usb("1-2", &ids, files("abcd")).unwrap();
+        assert_eq!(device.vendor_name.as_deref(), Some("Logitech"));
+        assert_eq!(device.product_name.as_deref(), Some("USB Receiver"));
+
+        // Root hub: class on the device, no strings
+        let hub = parse_sysfs_usb("usb1", &ids, |file| match file {
+            "idVendor" => Some("1d6b\n".to_string()),
+            "idProduct" => Some("0002\n".to_string()),
+            "bDeviceClass" => Some("09\n".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(hub.class, "Hub");
+        assert_eq!(hub.vendor_name, None);
+        assert_eq!(hub.speed, "unknown");
+
+        assert_eq!(parse_sysfs_usb("1-2", &ids, |_| None), None);
+    }
+
+    #[test]
+    fn test_parse_sysfs_pci() {
+        assert_eq!(parse_address("0000:3b:00.1"), Some((0, 0x3b, 0, 1)));
+        assert_eq!(parse_address("10000:00:1f.7"), None);
+        assert_eq!(parse_address("0000:00:1f"), None);
+
+        let ids = IdDatabase::parse("10de  NVIDIA Corporation\n\t1eb8  TU104GL [Tesla T4]\nC 03  Display controller\n\t02  3D controller\n");
+        let read = |file: &str| match file {
+            "vendor" => Some("0x10de\n".to_string()),
+            "device" => Some("0x1eb8\n".to_string()),
+            "class" => Some("0x030200\n".to_string()),
+            _ => None,
+        };
+        let link = |file: &str| match file {
+            "driver" => Some("vfio-pci".to_string()),
+            "iommu_group" => Some("42".to_string()),
+            _ => None,
+        };
+        let gpu = parse_sysfs_pci("0000:3b:00.0", &ids, read, link).unwrap();
+        assert_eq!((gpu.domain, gpu.bus, gpu.slot, gpu.function), (0, 0x3b, 0, 0));
+        assert_eq!((gpu.vendor_id, gpu.device_id), (0x10de, 0x1eb8));
+        assert_eq!(gpu.class, "Display controller");
+        assert_eq!(gpu.subclass, "3D controller");
+        assert_eq!(gpu.vendor_name.as_deref(), Some("NVIDIA Corporation"));
+        assert_eq!(gpu.device_name.as_deref(), Some("TU104GL [Tesla T4]"));
+        assert_eq!(gpu.driver.as_deref(), Some("vfio-pci"));
+        assert_eq!(gpu.iommu_group, Some(42));
+
+        // Unlisted IDs and classes, no driver bound, IOMMU off
+        let other = parse_sysfs_pci(
+            "0001:00:02.3",
+            &ids,
+            |file| match file {
+                "vendor" => Some("0xabcd".to_string()),
+                "device" => Some("0x0001".to_string()),
+                "class" => Some("0xee1100".to_string()),
+                _ => None,
+            },
+            |_| None,
+        )
+        .unwrap();
+        assert_eq!((other.domain, other.function), (1, 3));
+        assert_eq!((other.class.as_str(), other.subclass.as_str()), ("ee", "11"));
+        assert_eq!((other.vendor_name, other.device_name, other.driver, other.iommu_group), (None, None, None, None));
+
+        assert_eq!(parse_sysfs_pci("0000:00:00.0", &ids, |_| None, |_| None), None);
+    }
+
+    #[test]
+    fn test_hwids_prefer_system_copy() {
+        let path = std::env::temp_dir().join(format!("taskmgr-pci-{}.ids", std::process::id()));
+        std::fs::write(&path, "10de  NVIDIA Corporation\n\t2330  GH100 [H100 SXM5 80GB]\n").unwrap();
+        let bundled = "10de  NVIDIA Corporation\n";
+
+        let ids = hwids::load(&["/nonexistent/pci.ids", path.to_str().unwrap()], bundled);
+        assert_eq!(ids.device(0x10de, 0x2330), Some("GH100 [H100 SXM5 80GB]"));
+        std::fs::remove_file(&path).unwrap();
+
+        let ids = hwids::load(&["/nonexistent/pci.ids"], bundled);
+        assert_eq!(ids.vendor(0x10de), Some("NVIDIA Corporation"));
+        assert_eq!(ids.device(0x10de, 0x2330), None);
+        assert!(pci::SYSTEM_IDS.contains(&"/usr/share/hwdata/pci.ids"));
+        assert!(usb::SYSTEM_IDS.contains(&"/usr/share/hwdata/usb.ids"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_pci_endpoint() {
+        let (status, body) = send(router(), "GET", "/api/hardware/pci", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let devices = body.as_array().unwrap();
+        if std::path::Path::new("/sys/bus/pci/devices").exists() {
+            assert!(!devices.is_empty());
+        }
+        for device in devices {
+            assert!(device["vendor_id"].is_u64());
+            assert!(device["class"].is_string());
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[tokio::test]
+    async fn test_usb_endpoint() {
+        let (status, body) = send(router(), "GET", "/api/hardware/usb", None).await;
+        assert_eq!(status, StatusCode::OK);
+        for device in body.as_array().unwrap() {
+            assert!(device["vendor_id"].is_u64());
+            assert!(device["class"].is_string());
+        }
+    }
+
+    #[test]
+    fn test_disks_report_type() {
+        let list = disks::list(&sysinfo::Disks::new_with_refreshed_list());
+        for disk in &list {
+            assert!(["SSD", "HDD", "NVMe", "Unknown"].contains(&disk.disk_type.as_str()), "{}", disk.disk_type);
+            assert!(disk.available <= disk.total);
+        }
+        assert!(list.windows(2).all(|pair| pair[0].mount_point <= pair[1].mount_point));
+    }
+
+    #[test]
+    fn test_sysctl_keys_and_values() {
+        assert!(sysctl::validate_key("net.ipv4.conf.all.rp_filter").is_ok());
+        assert!(sysctl::validate_key("net.ipv4.conf.docker-0.forwarding").is_ok());
+        for key in ["", "net..core", "../etc/passwd", "net/core", ".vm", "vm.swappiness "] {
+            assert!(matches!(sysctl::validate_key(key), Err(ApiError::InvalidSysctlKey { .. })), "{:?}", key);
+        }
+        assert_eq!(
+            sysctl::key_path("net.core.somaxconn").unwrap(),
+            std::path::Path::new("/proc/sys/net/core/somaxconn")
+        );
+        assert_eq!(sysctl::clean_value("4096\t131072\t6291456\n"), "4096 131072 6291456");
+
+        let params = parse_sysctl_output("kern.ostype: Darwin\nhw.ncpu: 8\nnet.core.somaxconn = 4096\nnot a param\n");
+        let keys: Vec<&str> = params.iter().map(|p| p.key.as_str()).collect();
+        assert_eq!(keys, ["kern.ostype", "hw.ncpu", "net.core.somaxconn"]);
+        assert_eq!(params[2].value, "4096");
+    }
+
+    #[tokio::test]
+    async fn test_kernel_params_reject_bad_keys() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router.clone(), "GET", "/api/kernel/params?prefix=..%2Fetc", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_sysctl_key");
+
+        let (status, body) = send(router, "POST", "/api/kernel/params", Some(r#"{"key": "kernel/../../etc/hosts", "value": "x"}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_sysctl_key");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_kernel_params_read_proc_sys() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router.clone(), "GET", "/api/kernel/params?prefix=kernel.ostype", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!([{"key": "kernel.ostype", "value": "Linux"}]));
+
+        let (_, body) = send(router.clone(), "GET", "/api/kernel/params?prefix=kernel", None).await;
+        let keys: Vec<&str> = body.as_array().unwrap().iter().map(|p| p["key"].as_str().unwrap()).collect();
+        assert!(keys.contains(&"kernel.ostype"));
+        assert!(keys.iter().all(|key| key.starts_with("kernel.")));
+        assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let (status, body) = send(router.clone(), "GET", "/api/kernel/params?prefix=kernel.no_such_param", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "sysctl_not_found");
+
+        let (status, _) = send(router, "POST", "/api/kernel/params", Some(r#"{"key": "kernel.no_such_param", "value": "1"}"#)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_mounts_report_root_usage() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/mounts", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let root = body.as_array().unwrap().iter().find(|m| m["mount_point"] == "/").expect("/ is mounted");
+        let total = root["total_bytes"].as_u64().unwrap();
+        assert!(total > 0);
+        assert!(root["used_bytes"].as_u64().unwrap() <= total);
+        assert!(root["inode_free"].as_u64().unwrap() <= root["inode_total"].as_u64().unwrap());
+    }
+
+    #[test]
+    fn test_service_unit_names() {
+        assert_eq!(unit_name("nginx").unwrap(), "nginx.service");
+        assert_eq!(unit_name("getty@tty1.service").unwrap(), "getty@tty1.service");
+        for bad in ["", "../etc/passwd", "nginx;reboot", ".hidden", "a b"] {
+            assert!(unit_name(bad).is_err(), "{bad:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_windows_service_info() {
+        let update = windows_service_info("wuauserv".into(), "Windows Update".into(), 4, Some("manual"), 1184);
+        assert_eq!(update.name, "wuauserv");
+        assert_eq!(update.description, "Windows Update");
+        assert_eq!(update.load_state, "loaded");
+        assert_eq!(update.active_state, "active");
+        assert_eq!(update.sub_state, "running");
+        assert_eq!(update.start_type.as_deref(), Some("manual"));
+        assert_eq!(update.pid, Some(1184));
+
+        let fax = windows_service_info("Fax".into(), "Fax".into(), 1, Some("disabled"), 0);
+        assert_eq!(fax.active_state, "inactive");
+        assert_eq!(fax.pid, None);
+        let bits = windows_service_info("BITS".into(), String::new(), 2, None, 2210);
+        assert_eq!(bits.active_state, "activating");
+        assert_eq!(bits.description, "BITS");
+        assert_eq!(windows_service_info("Spooler".into(), "Print Spooler".into(), 7, None, 3000).sub_state, "paused");
+        assert_eq!(windows_service_info("odd".into(), String::new(), 99, None, 0).active_state, "unknown");
+    }
+
+    #[test]
+    fn test_windows_service_control() {
+        assert_eq!(windows_service_name("MSSQL$SQLEXPRESS").unwrap(), "MSSQL$SQLEXPRESS");
+        for bad in ["", "a/b", "a\\b", "x'; Remove-Item C:\\", "two words"] {
+            assert!(windows_service_name(bad).is_err(), "{bad:?} should be rejected");
+        }
+        assert!(services::windows_control_result(Some(0), "wuauserv").is_ok());
+        // Already running, already stopped
+        assert!(services::windows_control_result(Some(1056), "wuauserv").is_ok());
+        assert!(services::windows_control_result(Some(1062), "wuauserv").is_ok());
+        let denied = services::windows_control_result(Some(5), "wuauserv").unwrap_err();
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+        assert!(denied.to_string().contains("administrator"));
+        assert_eq!(services::windows_control_result(Some(1060), "nope").unwrap_err().code(), "service_not_found");
+        let dependents = services::windows_control_result(Some(1051), "LanmanWorkstation").unwrap_err();
+        assert_eq!((dependents.status(), dependents.code()), (StatusCode::CONFLICT, "service_has_dependents"));
+        assert_eq!(services::windows_control_result(Some(1), "wuauserv").unwrap_err().code(), "internal_error");
+    }
+
+    #[tokio::test]
+    async fn test_service_control_rejects_invalid_name() {
+        let (router, _) = mock_router();
+        for uri in ["/api/services/nginx;reboot/restart", "/api/v1/service/nginx;reboot/restart", "/api/v1/service/nginx;reboot/start"] {
+            let (status, body) = send(router.clone(), "POST", uri, None).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            assert_eq!(body["error"]["code"], "invalid_service_name", "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_control_blocked_in_read_only_mode() {
+        let mut config = auth_config(false);
+        config.auth.read_only = true;
+        let (router, _) = mock_router_with(config);
+        for uri in ["/api/services/nginx/stop", "/api/v1/service/nginx/stop"] {
+            let (status, body) = send_with_auth(router.clone(), "POST", uri, Some("Bearer s3cret")).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{uri}");
+            assert_eq!(body["error"]["code"], "read_only", "{uri}");
+        }
+        // Behind the token like every POST
+        let (router, _) = mock_router_with(auth_config(false));
+        let (status, _) = send(router, "POST", "/api/v1/service/nginx/restart", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_services_list_or_unsupported() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/services", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let services = body["services"].as_array().unwrap();
+        if body["supported"] == true {
+            assert!(services.iter().all(|s| s["name"].as_str().unwrap().ends_with(".service")));
+            assert!(services.iter().all(|s| s.get("restart_count").is_some()));
+        } else {
+            assert!(services.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_desktop_entry() {
+        let text = "\
+[Desktop Entry]
+Type=Application
+Name=Nextcloud
+Name[de]=Nextcloud Desktop
+Exec=/usr/bin/nextcloud --background %U
+X-GNOME-Autostart-enabled=true
+
+[Desktop Action Quit]
+Name=Quit
+Exec=nextcloud --quit
+";
+        assert_eq!(
+            parse_desktop_entry(text),
+            Some(DesktopEntry { name: Some("Nextcloud".into()), exec: "/usr/bin/nextcloud --background".into(), enabled: true })
+        );
+        let hidden = parse_desktop_entry("[Desktop Entry]\nExec=tracker-miner-fs\nHidden=true\n").unwrap();
+        assert!(!hidden.enabled);
+        assert_eq!(hidden.name, None);
+        let off = parse_desktop_entry("[Desktop Entry]\nExec=blueman-applet\nX-GNOME-Autostart-enabled=false\n").unwrap();
+        assert!(!off.enabled);
+        assert_eq!(parse_desktop_entry("[Desktop Entry]\nName=No command\n"), None);
+        assert_eq!(startup::strip_field_codes("sh -c 'echo 100%%' %f  --x"), "sh -c 'echo 100%' --x");
+    }
+
+    #[test]
+    fn test_parse_exec_start() {
+        let unit = "\
+[Unit]
+Description=Syncthing
+ExecStart=/not/this
+
+[Service]
+ExecStartPre=/bin/true
+ExecStart=-/usr/bin/syncthing serve --no-browser
+ExecStart=/second
+";
+        assert_eq!(parse_exec_start(unit).as_deref(), Some("/usr/bin/syncthing serve --no-browser"));
+        assert_eq!(parse_exec_start("[Service]\nType=oneshot\n"), None);
+        assert_eq!(parse_exec_start(""), None);
+    }
+
+    #[test]
+    fn test_parse_windows_startup() {
+        let json = r#"[
+            {"Name":"OneDrive","Command":"\"C:\\Users\\me\\AppData\\Local\\Microsoft\\OneDrive\\OneDrive.exe\" /background","Source":"registry","Location":"HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run","Enabled":true},
+            {"Name":"Spotify","Command":null,"Source":"startup_folder","Location":"C:\\Startup","Enabled":false}
+        ]"#;
+        let items = parse_windows_startup(json).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].source, StartupSource::Registry);
+        assert_eq!(startup::command_executable(&items[0].command), Some("C:\\Users\\me\\AppData\\Local\\Microsoft\\OneDrive\\OneDrive.exe"));
+        assert_eq!(items[1].source, StartupSource::StartupFolder);
+        assert_eq!(items[1].command, "");
+        assert!(!items[1].enabled);
+        assert!(parse_windows_startup("null").unwrap().is_empty());
+        assert!(parse_windows_startup("Get-Item : Access denied").is_none());
+    }
+
+    #[test]
+    fn test_startup_items_matched_to_processes() {
+        let process = |pid: u32, exe: &str, start_time: u64| -> ProcessSample {
+            serde_json::from_value(serde_json::json!({
+                "pid": pid, "name": "x", "cpu_usage": 40.0, "memory": 1024 * pid as u64,
+                "status": "Sleeping", "start_time": start_time, "exe": exe
+            }))
+            .unwrap()
+        };
+        let processes = [
+            process(900, "/usr/bin/nextcloud", 2000),
+            process(450, "/usr/bin/nextcloud", 1000),
+            process(500, "/opt/syncthing/syncthing", 1000),
+        ];
+        let item = |command: &str| StartupItem {
+            name: "x".into(),
+            command: command.into(),
+            source: StartupSource::XdgAutostart,
+            location: "/etc/xdg/autostart".into(),
+            enabled: true,
+            pid: None,
+            cpu_percent: None,
+            memory_bytes: None,
+        };
+        let mut items = [item("/usr/bin/nextcloud --background"), item("syncthing serve"), item("/usr/bin/syncthing"), item("")];
+        startup::attach_processes(&mut items, &processes, 4.0);
+        assert_eq!(items[0].pid, Some(450));
+        assert_eq!(items[0].cpu_percent, Some(10.0));
+        assert_eq!(items[0].memory_bytes, Some(450 * 1024));
+        assert_eq!(items[1].pid, Some(500));
+        assert_eq!(items[2].pid, None);
+        assert_eq!(items[3].pid, None);
+    }
+
+    #[tokio::test]
+    async fn test_startup_endpoint() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/startup", None).await;
+        if cfg!(any(target_os = "linux", windows)) {
+            assert_eq!(status, StatusCode::OK);
+            assert!(body.as_array().unwrap().iter().all(|item| item["enabled"].is_boolean()));
+        } else {
+            assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+        }
+    }
+
+    #[test]
+    fn test_parse_smartctl_ata_report() {
+        let report: Value = serde_json::from_str(include_str!("fixtures/smartctl_sda.json")).unwrap();
+        let smart = parse_smartctl("sda", &report);
+
+        assert_eq!(smart.health, "PASSED");
+        assert_eq!(smart.model.as_deref(), Some("Samsung SSD 860 EVO 500GB"));
+        assert_eq!(smart.temperature_celsius, Some(34));
+        assert_eq!(smart.reallocated_sectors, Some(3));
+        assert_eq!(smart.power_on_hours, Some(21034));
+        assert_eq!(smart.attributes.len(), 4);
+        assert!(smart.attributes.iter().find(|a| a.id == 177).unwrap().failing);
+        assert!(!smart.attributes[0].failing);
+    }
+
+    #[test]
+    fn test_parse_smartctl_without_smart_support() {
+        let report = serde_json::json!({
+            "smartctl": { "exit_status": 4 },
+            "smart_support": { "available": false }
+        });
+        let smart = parse_smartctl("sdb", &report);
+
+        assert_eq!(smart.health, "UNSUPPORTED");
+        assert!(smart.attributes.is_empty());
+        assert_eq!(smart.reallocated_sectors, None);
+    }
+
+    #[tokio::test]
+    async fn test_smart_rejects_invalid_device_name() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/disks/sda;ls/smart", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_device_name");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_smart_unknown_device_is_404() {
+        let (router, _) = mock_router();
+        let (status, body) = send(router, "GET", "/api/disks/nosuchdisk9/smart", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "device_not_found");
+    }
+
+    #[test]
+    fn test_gpu_stats_nullable() {
+        // Test that GPU stats can be null when no GPU present
+        let gpu_usage: Option<f32> = None;
+        let gpu_memory_used: Option<u64> = None;
+        let gpu_memory_total: Option<u64> = None;
+        let gpu_temperature: Option<f32> = None;
+        
+        assert!(gpu_usage.is_none());
+        assert!(gpu_memory_used.is_none());
+        assert!(gpu_memory_total.is_none());
+        assert!(gpu_temperature.is_none());
+    }
+
+    #[test]
+    fn test_gpu_circuit_breaker() {
+        let cooldown = std::time::Duration::from_secs(60);
+        let mut breaker = CircuitBreaker::new(GPU_FAILURE_THRESHOLD, cooldown);
+        let start = std::time::Instant::now();
+
+        for _ in 1..GPU_FAILURE_THRESHOLD {
+            breaker.failed(start);
+            assert!(breaker.allows(start));
+        }
+        breaker.failed(start);
+        assert!(!breaker.allows(start));
+        assert!(!breaker.allows(start + cooldown / 2));
+
+        // One trial after the cooldown; another failure reopens it at once
+        let later = start + cooldown;
+        assert!(breaker.allows(later));
+        breaker.failed(later);
+        assert!(!breaker.allows(later));
+
+        breaker.succeeded();
+        assert!(breaker.allows(later));
+        assert_eq!(breaker.failures(), 0);
+    }
+
+    #[test]
+    fn test_gpu_retry_backs_off_on_transient_errors() {
+        let base = std::time::Duration::from_millis(5);
+        let mut calls = 0;
+        let started = std::time::Instant::now();
+        let result = gpu::retry(3, base, |err: &&str| *err == "busy", || {
+            calls += 1;
+            if calls < 3 { Err("busy") } else { Ok(calls) }
+        });
+        assert_eq!(result, Ok(3));
+        // 5ms, then 10ms
+        assert!(started.elapsed() >= base * 3);
+
+        let mut calls = 0;
+        let result: Result<(), _> = gpu::retry(3, base, |err: &&str| *err == "busy", || {
+            calls += 1;
+            Err("no driver")
+        });
+        assert_eq!((result, calls), (Err("no driver"), 1));
+
+        let mut calls = 0;
+        let result: Result<(), _> = gpu::retry(3, base, |_: &&str| true, || {
+            calls += 1;
+            Err("busy")
+        });
+        assert_eq!((result, calls), (Err("busy"), 3));
+    }
+
+    #[test]
+    fn test_gpu_cooldown_config() {
+        assert_eq!(Config::from_lookup(|_| None).gpu_cooldown, DEFAULT_GPU_COOLDOWN);
+
+        let env = |name: &str| (name == "TASKMGR_GPU_COOLDOWN_SECS").then(|| "300".to_string());
+        assert_eq!(Config::from_lookup(env).gpu_cooldown, std::time::Duration::from_secs(300));
+
+        let cli = Cli::try_parse_from(["task_manager_backend", "--gpu-cooldown-secs", "5"]).unwrap();
+        assert_eq!(Config::from_lookup(env).merge(&cli).unwrap().gpu_cooldown, std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_network_stats_cumulative() {
+        // Test that network stats are cumulative
+        let bytes_sent_1: u64 = 1000;
+        let bytes_sent_2: u64 = 1500;
+        
+        assert!(bytes_sent_2 >= bytes_sent_1);
+    }
+
+    #[test]
+    fn test_disk_io_cumulative() {
+        // Test that disk I/O stats are cumulative
+        let disk_read_1: u64 = 5000;
+        let disk_read_2: u64 = 6000;
+        
+        assert!(disk_read_2 >= disk_read_1);
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(1_700_000_000), "2023-11-14T22:13:20Z");
+        // leap day, and the last second of a century leap year
+        assert_eq!(format_rfc3339(1_709_208_000), "2024-02-29T12:00:00Z");
+        assert_eq!(format_rfc3339(978_307_199), "2000-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_format_duration_keeps_two_largest_units() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(125), "2m 5s");
+        assert_eq!(format_duration(3 * 3600 + 12 * 60 + 30), "3h 12m");
+        assert_eq!(format_duration(2 * 86_400 + 4 * 3600 + 59), "2d 4h");
+        assert_eq!(format_duration(86_400), "1d 0h");
+    }
+
+    #[test]
+    fn test_runtime_handles_unknown_start_and_clock_skew() {
+        assert_eq!(
+            Runtime::new(1_700_000_000, 1_700_011_520),
+            Runtime {
+                create_time_iso: Some("2023-11-14T22:13:20Z".to_string()),
+                running_seconds: Some(11_520),
+                running_formatted: Some("3h 12m".to_string()),
+            }
+        );
+        // started "in the future" after the clock was set back
+        let skewed = Runtime::new(1_700_000_100, 1_700_000_000);
+        assert_eq!(skewed.running_seconds, Some(0));
+        assert_eq!(skewed.running_formatted.as_deref(), Some("0s"));
+
+        let unknown = Runtime::new(0, 1_700_000_000);
+        assert_eq!(unknown.create_time_iso, None);
+        assert_eq!(unknown.running_seconds, None);
+    }
+
+    #[tokio::test]
+    async fn test_process_runtime_fields() {
+        let (status, body) = send(mock_router().0, "GET", "/api/processes", None).await;
+        assert_eq!(status, StatusCode::OK);
+        // fixture processes have no start time, like some kernel threads
+        let systemd = body["processes"].as_array().unwrap().iter().find(|p| p["pid"] == 1).unwrap();
+        assert_eq!(systemd["create_time"], 0);
+        assert!(systemd["create_time_iso"].is_null());
+        assert!(systemd["running_seconds"].is_null());
+        assert!(systemd["running_formatted"].is_null());
+
+        let (status, body) = send(mock_router().0, "GET", "/api/process/200/info", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["create_time_iso"].is_null());
+        assert!(body.as_object().unwrap().contains_key("running_formatted"));
     }
 }
 
@@ -158,9 +5668,10 @@ mod edge_cases {
     #[test]
     fn test_negative_pid() {
         // PIDs should never be negative in u32
-        // This tests type safety
+        // This tests type safety - u32 type enforces non-negative values
         let pid: u32 = 0;
-        assert!(pid >= 0);
+        // Type system guarantees pid >= 0, so we just verify compilation
+        assert_eq!(pid, 0);
     }
 
     #[test]